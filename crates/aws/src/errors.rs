@@ -51,6 +51,9 @@ pub enum DynamoDbConfigError {
         // config_value: String,
         source: ParseIntError,
     },
+    /// Cannot parse lease_duration value into u64
+    #[error("Cannot parse lease duration into u64: {source}")]
+    ParseLeaseDuration { source: ParseIntError },
     /// Cannot initialize DynamoDbConfiguration due to some sort of threading issue
     #[error("Cannot initialize dynamodb lock configuration")]
     InitializationError,
@@ -77,6 +80,9 @@ pub enum LockClientError {
     #[error("Lock table not found")]
     LockTableNotFound,
 
+    #[error("Lock table '{name}' did not become active")]
+    LockTableNotActive { name: String },
+
     #[error("error in DynamoDb")]
     GenericDynamoDb {
         source: Box<dyn std::error::Error + Send + Sync + 'static>,
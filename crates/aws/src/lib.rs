@@ -4,6 +4,9 @@ pub mod errors;
 pub mod logstore;
 #[cfg(feature = "native-tls")]
 mod native;
+#[cfg(any(feature = "sns", feature = "eventbridge"))]
+pub mod observer;
+pub mod pins;
 pub mod storage;
 use aws_config::SdkConfig;
 use aws_sdk_dynamodb::{
@@ -13,7 +16,7 @@ use aws_sdk_dynamodb::{
     },
     types::{
         AttributeDefinition, AttributeValue, BillingMode, KeySchemaElement, KeyType,
-        ScalarAttributeType,
+        ScalarAttributeType, TableStatus, TimeToLiveSpecification, TimeToLiveStatus,
     },
     Client,
 };
@@ -26,7 +29,7 @@ use std::{
     sync::Arc,
     time::{Duration, SystemTime},
 };
-use tracing::debug;
+use tracing::{debug, warn};
 
 use deltalake_core::logstore::{logstores, LogStore, LogStoreFactory};
 use deltalake_core::storage::{factories, url_prefix_handler, ObjectStoreRef, StorageOptions};
@@ -94,6 +97,7 @@ pub fn register_handlers(_additional_prefixes: Option<Url>) {
 /// - temp_path: String - name of temporary file containing commit info
 /// - complete: bool - operation completed, i.e. atomic rename from `tempPath` to `fileName` succeeded
 /// - expire_time: `Option<SystemTime>` - epoch seconds at which this external commit entry is safe to be deleted
+/// - lease_id: `Option<String>` - token of the lease held by the writer of an incomplete entry, replaced on every renewal
 #[derive(Debug, PartialEq)]
 pub struct CommitEntry {
     /// Commit version, stored as file name (e.g., 00000N.json) in dynamodb (relative to `_delta_log/`
@@ -104,6 +108,10 @@ pub struct CommitEntry {
     pub complete: bool,
     /// If complete = true, epoch seconds at which this external commit entry is safe to be deleted
     pub expire_time: Option<SystemTime>,
+    /// If complete = false, token of the lease held by the writer of this entry. The writer
+    /// replaces it on every renewal, so a token that doesn't change for a whole lease duration
+    /// belongs to an expired lease.
+    pub lease_id: Option<String>,
 }
 
 impl CommitEntry {
@@ -115,8 +123,33 @@ impl CommitEntry {
             temp_path,
             complete: false,
             expire_time: None,
+            lease_id: None,
         }
     }
+
+    /// Hold a lease on the new entry, see [`DynamoDbLockClient::start_lease_heartbeat`].
+    pub fn with_lease(mut self) -> Self {
+        self.lease_id = Some(uuid::Uuid::new_v4().to_string());
+        self
+    }
+
+    /// Whether the writer of this incomplete entry holds a lease on it. Other clients leave
+    /// the entry to its writer until they have seen the same lease for a whole lease duration.
+    pub fn is_leased(&self) -> bool {
+        !self.complete && self.lease_id.is_some()
+    }
+}
+
+/// Renews the lease of an incomplete commit entry in the background until it is dropped.
+#[derive(Debug)]
+pub struct LeaseHeartbeat {
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl Drop for LeaseHeartbeat {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
 }
 
 /// Lock client backed by DynamoDb.
@@ -140,6 +173,7 @@ impl DynamoDbLockClient {
         lock_table_name: Option<String>,
         billing_mode: Option<String>,
         max_elapsed_request_time: Option<String>,
+        lease_duration: Option<String>,
     ) -> Result<Self, DynamoDbConfigError> {
         let dynamodb_client = aws_sdk_dynamodb::Client::new(sdk_config);
 
@@ -165,10 +199,19 @@ impl DynamoDbLockClient {
             )
             .map_err(|err| DynamoDbConfigError::ParseMaxElapsedRequestTime { source: err })?;
 
+        let lease_duration = lease_duration
+            .or_else(|| std::env::var(constants::LEASE_DURATION_KEY_NAME).ok())
+            .map_or_else(
+                || Ok(constants::DEFAULT_LEASE_DURATION),
+                |secs| u64::from_str(&secs).map(Duration::from_secs),
+            )
+            .map_err(|err| DynamoDbConfigError::ParseLeaseDuration { source: err })?;
+
         let config = DynamoDbConfig {
             billing_mode,
             lock_table_name,
             max_elapsed_request_time,
+            lease_duration,
             sdk_config: sdk_config.clone(),
         };
         Ok(Self {
@@ -232,6 +275,89 @@ impl DynamoDbLockClient {
         }
     }
 
+    /// Wait until the lock table is `active` and can be used, e.g. after creating it with
+    /// [`Self::try_create_lock_table`].
+    ///
+    /// Fails if the table doesn't become active within the maximum elapsed request time.
+    pub async fn wait_for_lock_table_active(&self) -> Result<(), LockClientError> {
+        let deadline = tokio::time::Instant::now() + self.config.max_elapsed_request_time;
+        loop {
+            let status = self
+                .dynamodb_client
+                .describe_table()
+                .table_name(&self.config.lock_table_name)
+                .send()
+                .await
+                .map_err(|err| LockClientError::GenericDynamoDb {
+                    source: Box::new(err.into_service_error()),
+                })?
+                .table
+                .and_then(|table| table.table_status);
+            if status == Some(TableStatus::Active) {
+                return Ok(());
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(LockClientError::LockTableNotActive {
+                    name: self.config.lock_table_name.clone(),
+                });
+            }
+            debug!("waiting for lock table to become active, status: {status:?}");
+            tokio::time::sleep(constants::LOCK_TABLE_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Let DynamoDb delete completed commit entries once their `expireTime` has passed.
+    ///
+    /// Does nothing if time to live is already enabled for the lock table.
+    pub async fn enable_commit_entry_expiration(&self) -> Result<(), LockClientError> {
+        let status = self
+            .dynamodb_client
+            .describe_time_to_live()
+            .table_name(&self.config.lock_table_name)
+            .send()
+            .await
+            .map_err(|err| LockClientError::GenericDynamoDb {
+                source: Box::new(err.into_service_error()),
+            })?
+            .time_to_live_description
+            .and_then(|description| description.time_to_live_status);
+        if matches!(
+            status,
+            Some(TimeToLiveStatus::Enabled | TimeToLiveStatus::Enabling)
+        ) {
+            return Ok(());
+        }
+        self.dynamodb_client
+            .update_time_to_live()
+            .table_name(&self.config.lock_table_name)
+            .time_to_live_specification(
+                TimeToLiveSpecification::builder()
+                    .attribute_name(constants::ATTR_EXPIRE_TIME)
+                    .enabled(true)
+                    .build()
+                    .unwrap(),
+            )
+            .send()
+            .await
+            .map_err(|err| LockClientError::GenericDynamoDb {
+                source: Box::new(err.into_service_error()),
+            })?;
+        Ok(())
+    }
+
+    /// Create the lock table if it doesn't exist and wait until it can be used.
+    ///
+    /// Newly created tables are set up to expire completed commit entries, see
+    /// [`Self::enable_commit_entry_expiration`].
+    pub async fn ensure_lock_table(&self) -> Result<CreateLockTableResult, LockClientError> {
+        let result = self.try_create_lock_table().await?;
+        self.wait_for_lock_table_active().await?;
+        if result == CreateLockTableResult::TableCreated {
+            self.enable_commit_entry_expiration().await?;
+        }
+        Ok(result)
+    }
+
     /// Get the name of the lock table for transactional commits used by the DynamoDb lock client.
     pub fn get_lock_table_name(&self) -> String {
         self.config.lock_table_name.clone()
@@ -415,6 +541,131 @@ impl DynamoDbLockClient {
         .await
     }
 
+    /// Replace the lease `lease_id` of the incomplete entry for `version` with `new_lease_id`.
+    ///
+    /// Returns [`UpdateLogEntryResult::AlreadyCompleted`] when the entry has been completed or
+    /// its lease was broken by another client in the meantime.
+    pub async fn renew_lease(
+        &self,
+        version: i64,
+        table_path: &str,
+        lease_id: &str,
+        new_lease_id: &str,
+    ) -> Result<UpdateLogEntryResult, LockClientError> {
+        self.update_leased_entry(
+            version,
+            table_path,
+            lease_id,
+            format!("SET {} = :n", constants::ATTR_LEASE_ID),
+            Some((":n", new_lease_id)),
+        )
+        .await
+    }
+
+    /// Release the lease `lease_id` of the incomplete entry for `version`, so that the caller
+    /// can repair the entry in place of its writer.
+    ///
+    /// DynamoDB only removes the lease if it is still `lease_id`, i.e. if the writer hasn't
+    /// renewed it since the caller observed it. Otherwise this returns
+    /// [`UpdateLogEntryResult::AlreadyCompleted`] and the writer keeps its lease.
+    pub async fn break_lease(
+        &self,
+        version: i64,
+        table_path: &str,
+        lease_id: &str,
+    ) -> Result<UpdateLogEntryResult, LockClientError> {
+        self.update_leased_entry(
+            version,
+            table_path,
+            lease_id,
+            format!("REMOVE {}", constants::ATTR_LEASE_ID),
+            None,
+        )
+        .await
+    }
+
+    async fn update_leased_entry(
+        &self,
+        version: i64,
+        table_path: &str,
+        lease_id: &str,
+        update_expression: String,
+        value: Option<(&str, &str)>,
+    ) -> Result<UpdateLogEntryResult, LockClientError> {
+        let mut values = maplit::hashmap! {
+            ":l".to_owned() => string_attr(lease_id),
+            ":f".into() => string_attr("false"),
+        };
+        if let Some((name, value)) = value {
+            values.insert(name.to_owned(), string_attr(value));
+        }
+        self.retry(|| async {
+            match self
+                .dynamodb_client
+                .update_item()
+                .table_name(self.get_lock_table_name())
+                .set_key(Some(self.get_primary_key(version, table_path)))
+                .update_expression(update_expression.clone())
+                .set_expression_attribute_values(Some(values.clone()))
+                .condition_expression(constants::CONDITION_UPDATE_LEASED.as_str())
+                .send()
+                .await
+            {
+                Ok(_) => Ok(UpdateLogEntryResult::UpdatePerformed),
+                Err(err) => match err.as_service_error() {
+                    Some(UpdateItemError::ProvisionedThroughputExceededException(_)) => Err(
+                        backoff::Error::transient(LockClientError::ProvisionedThroughputExceeded),
+                    ),
+                    Some(UpdateItemError::ConditionalCheckFailedException(_)) => {
+                        Ok(UpdateLogEntryResult::AlreadyCompleted)
+                    }
+                    _ => Err(backoff::Error::permanent(err.into())),
+                },
+            }
+        })
+        .await
+    }
+
+    /// Renew the lease `lease_id` of the incomplete entry for `version` every third of the
+    /// lease duration, until the returned heartbeat is dropped, the entry is completed or the
+    /// lease is broken by another client.
+    ///
+    /// Writers hold the lease while they move their commit to `N.json`, so a slow commit isn't
+    /// taken over by other clients while its writer is still working on it.
+    pub fn start_lease_heartbeat(
+        self: &Arc<Self>,
+        table_path: &str,
+        version: i64,
+        lease_id: &str,
+    ) -> LeaseHeartbeat {
+        let client = Arc::clone(self);
+        let table_path = table_path.to_owned();
+        let mut lease_id = lease_id.to_owned();
+        let period = (self.config.lease_duration / 3).max(constants::MIN_LEASE_RENEWAL_INTERVAL);
+        let task = tokio::spawn(async move {
+            let mut renewals =
+                tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            loop {
+                renewals.tick().await;
+                let new_lease_id = uuid::Uuid::new_v4().to_string();
+                match client
+                    .renew_lease(version, &table_path, &lease_id, &new_lease_id)
+                    .await
+                {
+                    Ok(UpdateLogEntryResult::UpdatePerformed) => {
+                        debug!("renewed lease of commit entry {version} for {table_path}");
+                        lease_id = new_lease_id;
+                    }
+                    Ok(UpdateLogEntryResult::AlreadyCompleted) => break,
+                    Err(err) => {
+                        warn!("failed to renew lease of commit entry {version} for {table_path}: {err}")
+                    }
+                }
+            }
+        });
+        LeaseHeartbeat { task }
+    }
+
     async fn retry<I, E, Fn, Fut>(&self, operation: Fn) -> Result<I, E>
     where
         Fn: FnMut() -> Fut,
@@ -460,11 +711,13 @@ impl TryFrom<&HashMap<String, AttributeValue>> for CommitEntry {
                 })
                 .transpose()?
                 .map(epoch_to_system_time);
+        let lease_id = extract_optional_string_field(item, constants::ATTR_LEASE_ID)?.cloned();
         Ok(Self {
             version,
             temp_path,
             complete: extract_required_string_field(item, constants::ATTR_COMPLETE)? == "true",
             expire_time,
+            lease_id,
         })
     }
 }
@@ -495,6 +748,9 @@ fn create_value_map(
             num_attr(system_time_to_epoch(t)),
         )
     });
+    commit_entry.lease_id.as_ref().map(|lease_id| {
+        value_map.insert(constants::ATTR_LEASE_ID.to_owned(), string_attr(lease_id))
+    });
     value_map
 }
 
@@ -503,6 +759,7 @@ pub struct DynamoDbConfig {
     pub billing_mode: BillingMode,
     pub lock_table_name: String,
     pub max_elapsed_request_time: Duration,
+    pub lease_duration: Duration,
     pub sdk_config: SdkConfig,
 }
 
@@ -512,6 +769,7 @@ impl PartialEq for DynamoDbConfig {
         self.billing_mode == other.billing_mode
             && self.lock_table_name == other.lock_table_name
             && self.max_elapsed_request_time == other.max_elapsed_request_time
+            && self.lease_duration == other.lease_duration
             && self.sdk_config.endpoint_url() == other.sdk_config.endpoint_url()
             && self.sdk_config.region() == other.sdk_config.region()
     }
@@ -536,12 +794,15 @@ pub mod constants {
     pub const LOCK_TABLE_KEY_NAME: &str = "DELTA_DYNAMO_TABLE_NAME";
     pub const BILLING_MODE_KEY_NAME: &str = "DELTA_DYNAMO_BILLING_MODE";
     pub const MAX_ELAPSED_REQUEST_TIME_KEY_NAME: &str = "DELTA_DYNAMO_MAX_ELAPSED_REQUEST_TIME";
+    pub const AUTO_CREATE_TABLE_KEY_NAME: &str = "DELTA_DYNAMO_AUTO_CREATE_TABLE";
+    pub const LEASE_DURATION_KEY_NAME: &str = "DELTA_DYNAMO_LEASE_DURATION";
 
     pub const ATTR_TABLE_PATH: &str = "tablePath";
     pub const ATTR_FILE_NAME: &str = "fileName";
     pub const ATTR_TEMP_PATH: &str = "tempPath";
     pub const ATTR_COMPLETE: &str = "complete";
     pub const ATTR_EXPIRE_TIME: &str = "expireTime";
+    pub const ATTR_LEASE_ID: &str = "leaseId";
 
     pub const STRING_TYPE: &str = "S";
 
@@ -552,11 +813,19 @@ pub mod constants {
         pub static ref CONDITION_EXPR_CREATE: String = format!(
             "attribute_not_exists({ATTR_TABLE_PATH}) and attribute_not_exists({ATTR_FILE_NAME})"
         );
+        pub static ref CONDITION_UPDATE_LEASED: String =
+            format!("{ATTR_COMPLETE} = :f and {ATTR_LEASE_ID} = :l");
     }
 
     pub const CONDITION_UPDATE_INCOMPLETE: &str = "complete = :f";
 
     pub const DEFAULT_COMMIT_ENTRY_EXPIRATION_DELAY: Duration = Duration::from_secs(86_400);
+
+    pub const LOCK_TABLE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+    pub const DEFAULT_LEASE_DURATION: Duration = Duration::from_secs(20);
+    pub const MIN_LEASE_RENEWAL_INTERVAL: Duration = Duration::from_secs(1);
+    pub const LEASE_POLL_INTERVAL: Duration = Duration::from_millis(500);
 }
 
 /// Extract a field from an item's attribute value map, producing a descriptive error
@@ -580,6 +849,24 @@ fn extract_required_string_field<'a>(
         .map(|s| s.as_str())
 }
 
+/// Extract an optional String field from an item's attribute value map.
+/// This call fails if the field exists, but is not of type string.
+fn extract_optional_string_field<'a>(
+    fields: &'a HashMap<String, AttributeValue>,
+    field_name: &str,
+) -> Result<Option<&'a String>, LockClientError> {
+    fields
+        .get(field_name)
+        .map(|attr| {
+            attr.as_s().map_err(|_| LockClientError::InconsistentData {
+                description: format!(
+                    "field with name '{field_name}' exists, but is not of type string"
+                ),
+            })
+        })
+        .transpose()
+}
+
 /// Extract an optional String field from an item's attribute value map.
 /// This call fails if the field exists, but is not of type string.
 fn extract_optional_number_field<'a>(
@@ -645,16 +932,37 @@ mod tests {
             temp_path: Path::from("_delta_log/tmp/0_abc.json"),
             complete: true,
             expire_time: Some(system_time),
+            lease_id: None,
         })?;
         commit_entry_roundtrip(&CommitEntry {
             version: 139,
             temp_path: Path::from("_delta_log/tmp/0_abc.json"),
             complete: false,
             expire_time: None,
+            lease_id: None,
         })?;
+        commit_entry_roundtrip(
+            &CommitEntry::new(140, Path::from("_delta_log/tmp/1_abc.json")).with_lease(),
+        )?;
         Ok(())
     }
 
+    #[test]
+    fn commit_entry_lease_test() {
+        let entry = CommitEntry::new(0, Path::from("_delta_log/tmp/0_abc.json"));
+        assert!(!entry.is_leased());
+        let mut entry = entry.with_lease();
+        assert!(entry.is_leased());
+        assert_ne!(
+            entry.lease_id,
+            CommitEntry::new(0, Path::from("_delta_log/tmp/0_abc.json"))
+                .with_lease()
+                .lease_id
+        );
+        entry.complete = true;
+        assert!(!entry.is_leased());
+    }
+
     /// In cases where there is no dynamodb specified locking provider, this should get a default
     /// logstore
     #[test]
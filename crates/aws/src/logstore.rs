@@ -7,6 +7,10 @@ use crate::errors::LockClientError;
 use crate::storage::S3StorageOptions;
 use crate::{constants, CommitEntry, DynamoDbLockClient, UpdateLogEntryResult};
 
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
 use bytes::Bytes;
 use deltalake_core::{ObjectStoreError, Path};
 use tracing::{debug, error, warn};
@@ -15,7 +19,7 @@ use url::Url;
 use deltalake_core::logstore::*;
 use deltalake_core::{
     operations::transaction::TransactionError,
-    storage::{ObjectStoreRef, StorageOptions},
+    storage::{str_is_truthy, ObjectStoreRef, StorageOptions},
     DeltaResult, DeltaTableError,
 };
 
@@ -25,9 +29,12 @@ const MAX_REPAIR_RETRIES: i64 = 3;
 /// [`LogStore`] implementation backed by DynamoDb
 pub struct S3DynamoDbLogStore {
     pub(crate) storage: ObjectStoreRef,
-    lock_client: DynamoDbLockClient,
+    lock_client: Arc<DynamoDbLockClient>,
     config: LogStoreConfig,
     table_path: String,
+    auto_create_lock_table: bool,
+    /// Leases of incomplete entries seen by this client, with the instant they were first seen
+    observed_leases: Mutex<HashMap<i64, (String, Instant)>>,
 }
 
 impl std::fmt::Debug for S3DynamoDbLogStore {
@@ -58,6 +65,10 @@ impl S3DynamoDbLogStore {
                 .extra_opts
                 .get(constants::MAX_ELAPSED_REQUEST_TIME_KEY_NAME)
                 .cloned(),
+            s3_options
+                .extra_opts
+                .get(constants::LEASE_DURATION_KEY_NAME)
+                .cloned(),
         )
         .map_err(|err| DeltaTableError::ObjectStore {
             source: ObjectStoreError::Generic {
@@ -66,17 +77,44 @@ impl S3DynamoDbLogStore {
             },
         })?;
         let table_path = to_uri(&location, &Path::from(""));
+        let auto_create_lock_table = s3_options
+            .extra_opts
+            .get(constants::AUTO_CREATE_TABLE_KEY_NAME)
+            .map(|val| str_is_truthy(val))
+            .unwrap_or(false);
         Ok(Self {
             storage: object_store,
-            lock_client,
+            lock_client: Arc::new(lock_client),
             config: LogStoreConfig {
                 location,
                 options: options.into(),
             },
             table_path,
+            auto_create_lock_table,
+            observed_leases: Mutex::new(HashMap::new()),
         })
     }
 
+    /// Write the commit entry to DynamoDb, creating the lock table first if it is missing and
+    /// [`constants::AUTO_CREATE_TABLE_KEY_NAME`] is set.
+    async fn put_commit_entry(&self, entry: &CommitEntry) -> Result<(), LockClientError> {
+        match self
+            .lock_client
+            .put_commit_entry(&self.table_path, entry)
+            .await
+        {
+            Err(LockClientError::LockTableNotFound) if self.auto_create_lock_table => {
+                let table_name = self.lock_client.get_lock_table_name();
+                warn!("Lock table '{table_name}' not found, creating it");
+                self.lock_client.ensure_lock_table().await?;
+                self.lock_client
+                    .put_commit_entry(&self.table_path, entry)
+                    .await
+            }
+            result => result,
+        }
+    }
+
     /// Attempt to repair an incomplete log entry by moving the temporary commit file
     /// to `N.json` and update the associated log entry to mark it as completed.
     pub async fn repair_entry(
@@ -109,6 +147,94 @@ impl S3DynamoDbLogStore {
         unreachable!("for loop yields Ok or Err in body when retry = MAX_REPAIR_RETRIES")
     }
 
+    /// Repair an incomplete log entry written by another client once its writer has completed
+    /// it or its lease has expired, waiting at most `max_wait` for either.
+    ///
+    /// A lease has expired once its token hasn't changed for a whole lease duration, as measured
+    /// by this client's monotonic clock, so clock skew between clients doesn't matter. The entry
+    /// is then only taken over if DynamoDB still holds that very lease. Returns `None` when the
+    /// writer still holds its lease after `max_wait`.
+    async fn repair_entry_after_lease(
+        &self,
+        mut entry: CommitEntry,
+        max_wait: Duration,
+    ) -> Result<Option<RepairLogEntryResult>, TransactionError> {
+        let lease_duration = self.lock_client.get_dynamodb_config().lease_duration;
+        let deadline = Instant::now() + max_wait;
+        loop {
+            let Some(lease_id) = entry.lease_id.clone().filter(|_| entry.is_leased()) else {
+                self.forget_lease(entry.version);
+                return self.repair_entry(&entry).await.map(Some);
+            };
+            let leased_for = self.observe_lease(entry.version, &lease_id).elapsed();
+            if leased_for >= lease_duration {
+                let result = self
+                    .lock_client
+                    .break_lease(entry.version, &self.table_path, &lease_id)
+                    .await
+                    .map_err(|err| TransactionError::LogStoreError {
+                        msg: format!(
+                            "unable to break lease of entry for '{}': failure to write to DynamoDb",
+                            entry.version
+                        ),
+                        source: Box::new(err),
+                    })?;
+                // otherwise the writer renewed its lease or completed the entry in the meantime
+                if result == UpdateLogEntryResult::UpdatePerformed {
+                    debug!("lease of {entry:?} expired, repairing it in place of its writer");
+                    self.forget_lease(entry.version);
+                    return self.repair_entry(&entry).await.map(Some);
+                }
+            } else {
+                let now = Instant::now();
+                if now >= deadline {
+                    debug!("the writer of {entry:?} still holds its lease");
+                    return Ok(None);
+                }
+                tokio::time::sleep(
+                    (deadline - now)
+                        .min(lease_duration - leased_for)
+                        .min(constants::LEASE_POLL_INTERVAL),
+                )
+                .await;
+            }
+            entry = match self
+                .lock_client
+                .get_commit_entry(&self.table_path, entry.version)
+                .await
+                .map_err(|err| TransactionError::LogStoreError {
+                    msg: format!(
+                        "unable to read entry for '{}': failure to read from DynamoDb",
+                        entry.version
+                    ),
+                    source: Box::new(err),
+                })? {
+                Some(entry) => entry,
+                None => {
+                    self.forget_lease(entry.version);
+                    return Ok(Some(RepairLogEntryResult::AlreadyCompleted));
+                }
+            };
+        }
+    }
+
+    /// Instant at which this client first saw `lease_id` on the entry for `version`.
+    fn observe_lease(&self, version: i64, lease_id: &str) -> Instant {
+        let mut leases = self.observed_leases.lock().unwrap();
+        match leases.get(&version) {
+            Some((observed, seen_at)) if observed == lease_id => *seen_at,
+            _ => {
+                let seen_at = Instant::now();
+                leases.insert(version, (lease_id.to_owned(), seen_at));
+                seen_at
+            }
+        }
+    }
+
+    fn forget_lease(&self, version: i64) {
+        self.observed_leases.lock().unwrap().remove(&version);
+    }
+
     /// Update an incomplete log entry to completed.
     async fn try_complete_entry(
         &self,
@@ -173,8 +299,9 @@ impl LogStore for S3DynamoDbLogStore {
             .map_err(|err| DeltaTableError::GenericError {
                 source: Box::new(err),
             })?;
+        // leave entries that are still leased to their writers
         if let Some(entry) = entry {
-            self.repair_entry(&entry).await?;
+            self.repair_entry_after_lease(entry, Duration::ZERO).await?;
         }
         Ok(())
     }
@@ -184,8 +311,10 @@ impl LogStore for S3DynamoDbLogStore {
             .lock_client
             .get_commit_entry(&self.table_path, version)
             .await;
+        // the caller asks for this very version, so give its writer up to one lease duration
         if let Ok(Some(entry)) = entry {
-            self.repair_entry(&entry).await?;
+            let max_wait = self.lock_client.get_dynamodb_config().lease_duration;
+            self.repair_entry_after_lease(entry, max_wait).await?;
         }
         read_commit_entry(&self.storage, version).await
     }
@@ -200,11 +329,10 @@ impl LogStore for S3DynamoDbLogStore {
         version: i64,
         tmp_commit: &Path,
     ) -> Result<(), TransactionError> {
-        let entry = CommitEntry::new(version, tmp_commit.clone());
+        let entry = CommitEntry::new(version, tmp_commit.clone()).with_lease();
         debug!("Writing commit entry for {self:?}: {entry:?}");
-        // create log entry in dynamo db: complete = false, no expireTime
-        self.lock_client
-            .put_commit_entry(&self.table_path, &entry)
+        // create log entry in dynamo db: complete = false, no expireTime, leased by this writer
+        self.put_commit_entry(&entry)
             .await
             .map_err(|err| match err {
                 LockClientError::VersionAlreadyExists { version, .. } => {
@@ -234,7 +362,12 @@ impl LogStore for S3DynamoDbLogStore {
         // retry logic and more robust error handling under the assumption that any other client
         // could attempt to concurrently repair that very same entry. In fact, the original writer
         // of the commit is just one delta client competing to perform the repair operation, as any
-        // other client could see the incomplete commit to immediately trigger a repair.
+        // other client could see the incomplete commit to trigger a repair once the lease expires.
+        // The lease is renewed until the commit is finalized, however long that takes.
+        let _heartbeat = entry.lease_id.as_deref().map(|lease_id| {
+            self.lock_client
+                .start_lease_heartbeat(&self.table_path, version, lease_id)
+        });
         self.repair_entry(&entry).await?;
         Ok(())
     }
@@ -250,8 +383,12 @@ impl LogStore for S3DynamoDbLogStore {
             })?;
         // when there is a latest entry in DynamoDb, we can avoid the file listing in S3.
         if let Some(entry) = entry {
-            self.repair_entry(&entry).await?;
-            Ok(entry.version)
+            let version = entry.version;
+            match self.repair_entry_after_lease(entry, Duration::ZERO).await? {
+                Some(_) => Ok(version),
+                // the latest commit is still being written and isn't visible yet
+                None => Ok(version - 1),
+            }
         } else {
             get_latest_version(self, current_version).await
         }
@@ -0,0 +1,114 @@
+//! Commit observers publishing commit events to AWS messaging services.
+
+use aws_config::SdkConfig;
+use deltalake_core::operations::transaction::{CommitEvent, CommitObserver};
+use deltalake_core::{DeltaResult, DeltaTableError};
+
+/// `detail-type` of the EventBridge events and subject of the SNS messages
+pub const COMMIT_EVENT_DETAIL_TYPE: &str = "Delta Table Commit";
+
+fn serialize_event(event: &CommitEvent) -> DeltaResult<String> {
+    serde_json::to_string(event).map_err(|err| DeltaTableError::GenericError {
+        source: Box::new(err),
+    })
+}
+
+/// Publishes each [`CommitEvent`] as JSON message to an SNS topic
+#[cfg(feature = "sns")]
+#[derive(Debug, Clone)]
+pub struct SnsCommitObserver {
+    client: aws_sdk_sns::Client,
+    topic_arn: String,
+}
+
+#[cfg(feature = "sns")]
+impl SnsCommitObserver {
+    /// Create a new observer publishing to the topic with the given arn
+    pub fn new(sdk_config: &SdkConfig, topic_arn: impl Into<String>) -> Self {
+        Self {
+            client: aws_sdk_sns::Client::new(sdk_config),
+            topic_arn: topic_arn.into(),
+        }
+    }
+}
+
+#[cfg(feature = "sns")]
+#[async_trait::async_trait]
+impl CommitObserver for SnsCommitObserver {
+    async fn on_commit(&self, event: &CommitEvent) -> DeltaResult<()> {
+        self.client
+            .publish()
+            .topic_arn(&self.topic_arn)
+            .subject(COMMIT_EVENT_DETAIL_TYPE)
+            .message(serialize_event(event)?)
+            .send()
+            .await
+            .map_err(|err| DeltaTableError::GenericError {
+                source: Box::new(err),
+            })?;
+        Ok(())
+    }
+}
+
+/// Puts each [`CommitEvent`] as event on an EventBridge event bus
+#[cfg(feature = "eventbridge")]
+#[derive(Debug, Clone)]
+pub struct EventBridgeCommitObserver {
+    client: aws_sdk_eventbridge::Client,
+    event_bus_name: String,
+    source: String,
+}
+
+#[cfg(feature = "eventbridge")]
+impl EventBridgeCommitObserver {
+    /// Create a new observer putting events on the given event bus, with `delta-rs` as source
+    pub fn new(sdk_config: &SdkConfig, event_bus_name: impl Into<String>) -> Self {
+        Self {
+            client: aws_sdk_eventbridge::Client::new(sdk_config),
+            event_bus_name: event_bus_name.into(),
+            source: "delta-rs".to_string(),
+        }
+    }
+
+    /// Set the `source` of the emitted events
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = source.into();
+        self
+    }
+}
+
+#[cfg(feature = "eventbridge")]
+#[async_trait::async_trait]
+impl CommitObserver for EventBridgeCommitObserver {
+    async fn on_commit(&self, event: &CommitEvent) -> DeltaResult<()> {
+        let entry = aws_sdk_eventbridge::types::PutEventsRequestEntry::builder()
+            .event_bus_name(&self.event_bus_name)
+            .source(&self.source)
+            .detail_type(COMMIT_EVENT_DETAIL_TYPE)
+            .resources(&event.table_uri)
+            .detail(serialize_event(event)?)
+            .build();
+        let output = self
+            .client
+            .put_events()
+            .entries(entry)
+            .send()
+            .await
+            .map_err(|err| DeltaTableError::GenericError {
+                source: Box::new(err),
+            })?;
+        if output.failed_entry_count() > 0 {
+            let message = output
+                .entries()
+                .iter()
+                .filter_map(|entry| entry.error_message())
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(DeltaTableError::Generic(format!(
+                "Failed to put commit event for version {}: {message}",
+                event.version
+            )));
+        }
+        Ok(())
+    }
+}
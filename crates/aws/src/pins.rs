@@ -0,0 +1,191 @@
+//! Snapshot pin registry shared between processes through DynamoDb.
+
+use std::collections::HashMap;
+
+use aws_config::SdkConfig;
+use aws_sdk_dynamodb::types::{
+    AttributeDefinition, AttributeValue, BillingMode, KeySchemaElement, KeyType,
+    ScalarAttributeType,
+};
+use aws_sdk_dynamodb::Client;
+use deltalake_core::table::pins::{PinRegistry, SnapshotPin};
+use deltalake_core::{DeltaResult, DeltaTableError};
+
+use crate::{num_attr, string_attr};
+
+const ATTR_TABLE_PATH: &str = "tablePath";
+const ATTR_PIN_ID: &str = "pinId";
+const ATTR_VERSION: &str = "version";
+const ATTR_EXPIRES_AT: &str = "expiresAt";
+/// Expiry in seconds since the epoch, to be used as the table's TTL attribute
+const ATTR_EXPIRE_TIME: &str = "expireTime";
+
+fn dynamodb_error<E: std::error::Error + Send + Sync + 'static>(err: E) -> DeltaTableError {
+    DeltaTableError::GenericError {
+        source: Box::new(err),
+    }
+}
+
+fn inconsistent_data(field: &str) -> DeltaTableError {
+    DeltaTableError::Generic(format!("invalid or missing field '{field}' in pin table"))
+}
+
+/// [`PinRegistry`] storing pins in a DynamoDb table, with `tablePath` as partition key and
+/// `pinId` as sort key. Enable TTL on the `expireTime` attribute to let DynamoDb delete
+/// expired pins.
+#[derive(Debug, Clone)]
+pub struct DynamoDbPinRegistry {
+    client: Client,
+    table_name: String,
+}
+
+impl DynamoDbPinRegistry {
+    /// Create a registry storing pins in the DynamoDb table `table_name`
+    pub fn new(sdk_config: &SdkConfig, table_name: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(sdk_config),
+            table_name: table_name.into(),
+        }
+    }
+
+    /// Create the DynamoDb table of the registry
+    pub async fn try_create_pin_table(&self) -> DeltaResult<()> {
+        self.client
+            .create_table()
+            .table_name(&self.table_name)
+            .set_attribute_definitions(Some(vec![
+                AttributeDefinition::builder()
+                    .attribute_name(ATTR_TABLE_PATH)
+                    .attribute_type(ScalarAttributeType::S)
+                    .build()
+                    .map_err(dynamodb_error)?,
+                AttributeDefinition::builder()
+                    .attribute_name(ATTR_PIN_ID)
+                    .attribute_type(ScalarAttributeType::S)
+                    .build()
+                    .map_err(dynamodb_error)?,
+            ]))
+            .set_key_schema(Some(vec![
+                KeySchemaElement::builder()
+                    .attribute_name(ATTR_TABLE_PATH)
+                    .key_type(KeyType::Hash)
+                    .build()
+                    .map_err(dynamodb_error)?,
+                KeySchemaElement::builder()
+                    .attribute_name(ATTR_PIN_ID)
+                    .key_type(KeyType::Range)
+                    .build()
+                    .map_err(dynamodb_error)?,
+            ]))
+            .billing_mode(BillingMode::PayPerRequest)
+            .send()
+            .await
+            .map_err(dynamodb_error)?;
+        Ok(())
+    }
+}
+
+fn pin_from_item(item: &HashMap<String, AttributeValue>) -> DeltaResult<SnapshotPin> {
+    let string = |field: &str| {
+        item.get(field)
+            .and_then(|attr| attr.as_s().ok())
+            .cloned()
+            .ok_or_else(|| inconsistent_data(field))
+    };
+    let number = |field: &str| {
+        item.get(field)
+            .and_then(|attr| attr.as_n().ok())
+            .and_then(|n| n.parse::<i64>().ok())
+            .ok_or_else(|| inconsistent_data(field))
+    };
+    Ok(SnapshotPin {
+        id: string(ATTR_PIN_ID)?,
+        table_uri: string(ATTR_TABLE_PATH)?,
+        version: number(ATTR_VERSION)?,
+        expires_at: number(ATTR_EXPIRES_AT)?,
+    })
+}
+
+#[async_trait::async_trait]
+impl PinRegistry for DynamoDbPinRegistry {
+    async fn register(&self, pin: &SnapshotPin) -> DeltaResult<()> {
+        self.client
+            .put_item()
+            .table_name(&self.table_name)
+            .set_item(Some(maplit::hashmap! {
+                ATTR_TABLE_PATH.to_owned() => string_attr(&pin.table_uri),
+                ATTR_PIN_ID.to_owned() => string_attr(&pin.id),
+                ATTR_VERSION.to_owned() => num_attr(pin.version),
+                ATTR_EXPIRES_AT.to_owned() => num_attr(pin.expires_at),
+                ATTR_EXPIRE_TIME.to_owned() => num_attr(pin.expires_at / 1000),
+            }))
+            .send()
+            .await
+            .map_err(dynamodb_error)?;
+        Ok(())
+    }
+
+    async fn release(&self, pin: &SnapshotPin) -> DeltaResult<()> {
+        self.client
+            .delete_item()
+            .table_name(&self.table_name)
+            .set_key(Some(maplit::hashmap! {
+                ATTR_TABLE_PATH.to_owned() => string_attr(&pin.table_uri),
+                ATTR_PIN_ID.to_owned() => string_attr(&pin.id),
+            }))
+            .send()
+            .await
+            .map_err(dynamodb_error)?;
+        Ok(())
+    }
+
+    async fn pins(&self, table_uri: &str) -> DeltaResult<Vec<SnapshotPin>> {
+        let mut pins = Vec::new();
+        let mut start_key = None;
+        loop {
+            let result = self
+                .client
+                .query()
+                .table_name(&self.table_name)
+                .consistent_read(true)
+                .key_condition_expression(format!("{ATTR_TABLE_PATH} = :tn"))
+                .set_expression_attribute_values(Some(
+                    maplit::hashmap!(":tn".into() => string_attr(table_uri)),
+                ))
+                .set_exclusive_start_key(start_key)
+                .send()
+                .await
+                .map_err(dynamodb_error)?;
+            for item in result.items() {
+                pins.push(pin_from_item(item)?);
+            }
+            start_key = result.last_evaluated_key;
+            if start_key.is_none() {
+                return Ok(pins);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pin_from_item() {
+        let item = maplit::hashmap! {
+            ATTR_TABLE_PATH.to_owned() => string_attr("s3://bucket/table"),
+            ATTR_PIN_ID.to_owned() => string_attr("pin"),
+            ATTR_VERSION.to_owned() => num_attr(3),
+            ATTR_EXPIRES_AT.to_owned() => num_attr(1000),
+        };
+        let pin = pin_from_item(&item).unwrap();
+        assert_eq!(pin.table_uri, "s3://bucket/table");
+        assert_eq!(pin.version, 3);
+        assert_eq!(pin.expires_at, 1000);
+
+        let mut item = item;
+        item.remove(ATTR_VERSION);
+        assert!(pin_from_item(&item).is_err());
+    }
+}
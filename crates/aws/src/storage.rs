@@ -4,7 +4,7 @@ use aws_config::{Region, SdkConfig};
 use bytes::Bytes;
 use deltalake_core::storage::object_store::{
     aws::AmazonS3ConfigKey, parse_url_opts, GetOptions, GetResult, ListResult, MultipartId,
-    ObjectMeta, ObjectStore, PutOptions, PutResult, Result as ObjectStoreResult,
+    ObjectMeta, ObjectStore, PutMode, PutOptions, PutResult, Result as ObjectStoreResult,
 };
 use deltalake_core::storage::{str_is_truthy, ObjectStoreFactory, ObjectStoreRef, StorageOptions};
 use deltalake_core::{DeltaResult, ObjectStoreError, Path};
@@ -17,6 +17,7 @@ use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::io::AsyncWrite;
+use tracing::warn;
 use url::Url;
 
 use crate::errors::DynamoDbConfigError;
@@ -66,7 +67,17 @@ impl ObjectStoreFactory for S3ObjectStoreFactory {
         url: &Url,
         options: &StorageOptions,
     ) -> DeltaResult<(ObjectStoreRef, Path)> {
-        let options = self.with_env_s3(options);
+        let mut options = self.with_env_s3(options);
+        let conditional_put = str_option(&options.0, s3_constants::AWS_S3_LOCKING_PROVIDER)
+            .as_deref()
+            == Some(s3_constants::CONDITIONAL_PUT_LOCKING_PROVIDER);
+        if conditional_put {
+            // S3 creates objects with `If-None-Match: *` only if configured to use conditional puts
+            options
+                .0
+                .entry(AmazonS3ConfigKey::ConditionalPut.as_ref().to_string())
+                .or_insert_with(|| "etag".to_string());
+        }
         let (store, prefix) = parse_url_opts(
             url,
             options.0.iter().filter_map(|(key, value)| {
@@ -88,7 +99,8 @@ impl ObjectStoreFactory for S3ObjectStoreFactory {
         let store = S3StorageBackend::try_new(
             store.into(),
             Some("dynamodb") == options.locking_provider.as_deref() || options.allow_unsafe_rename,
-        )?;
+        )?
+        .with_conditional_put(conditional_put);
 
         Ok((Arc::new(store), prefix))
     }
@@ -259,6 +271,8 @@ pub struct S3StorageBackend {
     inner: ObjectStoreRef,
     /// Whether allowed to performance rename_if_not_exist as rename
     allow_unsafe_rename: bool,
+    /// Whether to create the destination of rename_if_not_exist with a conditional put
+    conditional_put: bool,
 }
 
 impl std::fmt::Display for S3StorageBackend {
@@ -275,8 +289,28 @@ impl S3StorageBackend {
         Ok(Self {
             inner: storage,
             allow_unsafe_rename,
+            conditional_put: false,
         })
     }
+
+    /// Create the destination of [`ObjectStore::rename_if_not_exists`] and
+    /// [`ObjectStore::copy_if_not_exists`] with a conditional put, sending `If-None-Match: *`.
+    ///
+    /// This makes commits atomic without a locking provider. The inner store has to be
+    /// configured with [`AmazonS3ConfigKey::ConditionalPut`].
+    pub fn with_conditional_put(mut self, conditional_put: bool) -> Self {
+        self.conditional_put = conditional_put;
+        self
+    }
+
+    /// Copy `from` to `to` with a conditional put, failing if `to` already exists
+    async fn put_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        let bytes = self.inner.get(from).await?.bytes().await?;
+        self.inner
+            .put_opts(to, bytes, PutMode::Create.into())
+            .await?;
+        Ok(())
+    }
 }
 
 impl std::fmt::Debug for S3StorageBackend {
@@ -340,12 +374,25 @@ impl ObjectStore for S3StorageBackend {
         self.inner.copy(from, to).await
     }
 
-    async fn copy_if_not_exists(&self, _from: &Path, _to: &Path) -> ObjectStoreResult<()> {
-        todo!()
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        if self.conditional_put {
+            self.put_if_not_exists(from, to).await
+        } else {
+            Err(ObjectStoreError::NotSupported {
+                source: "copy_if_not_exists requires the conditional_put locking provider".into(),
+            })
+        }
     }
 
     async fn rename_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
-        if self.allow_unsafe_rename {
+        if self.conditional_put {
+            self.put_if_not_exists(from, to).await?;
+            // the destination exists, failing to clean up only leaves a temporary file behind
+            if let Err(err) = self.inner.delete(from).await {
+                warn!("failed to delete {from} after renaming it to {to}: {err}");
+            }
+            Ok(())
+        } else if self.allow_unsafe_rename {
             self.inner.rename(from, to).await
         } else {
             Err(ObjectStoreError::Generic {
@@ -391,9 +438,12 @@ pub mod s3_constants {
     /// [virtual host addressing](http://docs.aws.amazon.com/AmazonS3/latest/dev/VirtualHosting.html).
     pub const AWS_S3_ADDRESSING_STYLE: &str = "AWS_S3_ADDRESSING_STYLE";
     /// Locking provider to use for safe atomic rename.
-    /// `dynamodb` is currently the only supported locking provider.
+    /// Either `dynamodb`, or `conditional_put` to rely on S3 conditional writes.
     /// If not set, safe atomic rename is not available.
     pub const AWS_S3_LOCKING_PROVIDER: &str = "AWS_S3_LOCKING_PROVIDER";
+    /// Value of [`AWS_S3_LOCKING_PROVIDER`] creating commits with `If-None-Match: *` conditional
+    /// puts, which doesn't require a DynamoDb table.
+    pub const CONDITIONAL_PUT_LOCKING_PROVIDER: &str = "conditional_put";
     /// The role to assume for S3 writes.
     pub const AWS_S3_ASSUME_ROLE_ARN: &str = "AWS_S3_ASSUME_ROLE_ARN";
     /// The role session name to use when a role is assumed. If not provided a random session name is generated.
@@ -744,4 +794,41 @@ mod tests {
             }
         });
     }
+
+    #[tokio::test]
+    async fn test_conditional_put_rename() {
+        use deltalake_core::storage::object_store::memory::InMemory;
+
+        let store = S3StorageBackend::try_new(Arc::new(InMemory::new()), false)
+            .unwrap()
+            .with_conditional_put(true);
+        let commit = Path::from("_delta_log/00000000000000000000.json");
+        let (first, second) = (
+            Path::from("_delta_log/_commit_a.json.tmp"),
+            Path::from("_delta_log/_commit_b.json.tmp"),
+        );
+        store.put(&first, Bytes::from("a")).await.unwrap();
+        store.put(&second, Bytes::from("b")).await.unwrap();
+
+        store.rename_if_not_exists(&first, &commit).await.unwrap();
+        assert!(store.head(&first).await.is_err());
+        let err = store
+            .rename_if_not_exists(&second, &commit)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ObjectStoreError::AlreadyExists { .. }));
+        let data = store.get(&commit).await.unwrap().bytes().await.unwrap();
+        assert_eq!(data, Bytes::from("a"));
+    }
+
+    #[tokio::test]
+    async fn test_copy_if_not_exists_unsupported() {
+        use deltalake_core::storage::object_store::memory::InMemory;
+
+        let store = S3StorageBackend::try_new(Arc::new(InMemory::new()), false).unwrap();
+        let (from, to) = (Path::from("a"), Path::from("b"));
+        store.put(&from, Bytes::from("a")).await.unwrap();
+        let err = store.copy_if_not_exists(&from, &to).await.unwrap_err();
+        assert!(matches!(err, ObjectStoreError::NotSupported { .. }));
+    }
 }
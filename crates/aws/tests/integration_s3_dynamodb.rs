@@ -3,13 +3,14 @@
 #![cfg(feature = "integration_test")]
 
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use aws_config::SdkConfig;
 use aws_sdk_dynamodb::types::BillingMode;
 use deltalake_aws::logstore::{RepairLogEntryResult, S3DynamoDbLogStore};
 use deltalake_aws::storage::S3StorageOptions;
-use deltalake_aws::{CommitEntry, DynamoDbConfig, DynamoDbLockClient};
+use deltalake_aws::{CommitEntry, DynamoDbConfig, DynamoDbLockClient, UpdateLogEntryResult};
 use deltalake_core::kernel::{Action, Add, DataType, PrimitiveType, StructField, StructType};
 use deltalake_core::logstore::LogStore;
 use deltalake_core::operations::transaction::{CommitBuilder, PreparedCommit};
@@ -44,6 +45,7 @@ fn make_client() -> TestResult<DynamoDbLockClient> {
         None,
         None,
         None,
+        None,
     )?)
 }
 
@@ -62,6 +64,7 @@ fn client_configs_via_env_variables() -> TestResult<()> {
         deltalake_aws::constants::BILLING_MODE_KEY_NAME,
         "PAY_PER_REQUEST".to_owned(),
     );
+    std::env::set_var(deltalake_aws::constants::LEASE_DURATION_KEY_NAME, "30");
     let client = make_client()?;
     let config = client.get_dynamodb_config();
     let options: S3StorageOptions = S3StorageOptions::try_default().unwrap();
@@ -70,6 +73,7 @@ fn client_configs_via_env_variables() -> TestResult<()> {
             billing_mode: BillingMode::PayPerRequest,
             lock_table_name: "some_table".to_owned(),
             max_elapsed_request_time: Duration::from_secs(64),
+            lease_duration: Duration::from_secs(30),
             sdk_config: options.sdk_config,
         },
         *config,
@@ -77,6 +81,7 @@ fn client_configs_via_env_variables() -> TestResult<()> {
     std::env::remove_var(deltalake_aws::constants::LOCK_TABLE_KEY_NAME);
     std::env::remove_var(deltalake_aws::constants::MAX_ELAPSED_REQUEST_TIME_KEY_NAME);
     std::env::remove_var(deltalake_aws::constants::BILLING_MODE_KEY_NAME);
+    std::env::remove_var(deltalake_aws::constants::LEASE_DURATION_KEY_NAME);
     Ok(())
 }
 
@@ -96,6 +101,45 @@ async fn get_missing_item() -> TestResult<()> {
     Ok(())
 }
 
+#[tokio::test]
+#[serial]
+async fn test_ensure_lock_table() -> TestResult<()> {
+    let _context = IntegrationContext::new(Box::new(S3Integration::default()))?;
+    let options: S3StorageOptions = S3StorageOptions::try_default().unwrap();
+    let client = DynamoDbLockClient::try_new(
+        &options.sdk_config,
+        Some(format!("delta_log_{}", uuid::Uuid::new_v4())),
+        None,
+        None,
+        None,
+    )?;
+    let result = client
+        .put_commit_entry(
+            "s3://my_delta_table",
+            &CommitEntry::new(0, Path::from("tmp")),
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(deltalake_aws::errors::LockClientError::LockTableNotFound)
+    ));
+    assert_eq!(
+        client.ensure_lock_table().await?,
+        deltalake_aws::CreateLockTableResult::TableCreated
+    );
+    assert_eq!(
+        client.ensure_lock_table().await?,
+        deltalake_aws::CreateLockTableResult::TableAlreadyExists
+    );
+    client
+        .put_commit_entry(
+            "s3://my_delta_table",
+            &CommitEntry::new(0, Path::from("tmp")),
+        )
+        .await?;
+    Ok(())
+}
+
 #[tokio::test]
 #[serial]
 async fn test_append() -> TestResult<()> {
@@ -122,7 +166,7 @@ async fn test_repair_commit_entry() -> TestResult<()> {
     )?;
 
     // create an incomplete log entry, commit file not yet moved from its temporary location
-    let entry = create_incomplete_commit_entry(&table, 1, "unfinished_commit").await?;
+    let entry = create_incomplete_commit_entry(&table, 1, "unfinished_commit", false).await?;
     let read_entry = client
         .get_latest_entry(&table.table_uri())
         .await?
@@ -133,7 +177,7 @@ async fn test_repair_commit_entry() -> TestResult<()> {
         log_store.repair_entry(&read_entry).await?
     );
     // create another incomplete log entry, this time move the temporary file already
-    let entry = create_incomplete_commit_entry(&table, 2, "unfinished_commit").await?;
+    let entry = create_incomplete_commit_entry(&table, 2, "unfinished_commit", false).await?;
     log_store
         .object_store()
         .rename_if_not_exists(&entry.temp_path, &commit_uri_from_version(entry.version))
@@ -161,7 +205,7 @@ async fn test_repair_commit_entry() -> TestResult<()> {
 async fn test_repair_on_update() -> TestResult<()> {
     let context = IntegrationContext::new(Box::new(S3Integration::default()))?;
     let mut table = prepare_table(&context, "repair_on_update").await?;
-    let _entry = create_incomplete_commit_entry(&table, 1, "unfinished_commit").await?;
+    let _entry = create_incomplete_commit_entry(&table, 1, "unfinished_commit", false).await?;
     table.update().await?;
     // table update should find and update to newest, incomplete commit entry
     assert_eq!(table.version(), 1);
@@ -174,7 +218,7 @@ async fn test_repair_on_update() -> TestResult<()> {
 async fn test_repair_on_load() -> TestResult<()> {
     let context = IntegrationContext::new(Box::new(S3Integration::default()))?;
     let mut table = prepare_table(&context, "repair_on_update").await?;
-    let _entry = create_incomplete_commit_entry(&table, 1, "unfinished_commit").await?;
+    let _entry = create_incomplete_commit_entry(&table, 1, "unfinished_commit", false).await?;
     table.load_version(1).await?;
     // table should fix the broken entry while loading a specific version
     assert_eq!(table.version(), 1);
@@ -182,6 +226,53 @@ async fn test_repair_on_load() -> TestResult<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_lease_heartbeat() -> TestResult<()> {
+    let context = IntegrationContext::new(Box::new(S3Integration::default()))?;
+    let mut table = prepare_table(&context, "lease_heartbeat").await?;
+    let options: S3StorageOptions = S3StorageOptions::try_default().unwrap();
+    let client = Arc::new(DynamoDbLockClient::try_new(
+        &options.sdk_config,
+        None,
+        None,
+        None,
+        Some("3".to_owned()),
+    )?);
+    let lease = client.get_dynamodb_config().lease_duration;
+    let entry = create_incomplete_commit_entry(&table, 1, "leased_commit", true).await?;
+    let lease_id = entry.lease_id.clone().expect("no lease!");
+
+    // the heartbeat keeps renewing the lease, other clients leave the entry to its writer
+    let heartbeat = client.start_lease_heartbeat(&table.table_uri(), 1, &lease_id);
+    tokio::time::sleep(lease * 2).await;
+    let read_entry = client
+        .get_commit_entry(&table.table_uri(), 1)
+        .await?
+        .expect("no entry!");
+    assert!(read_entry.is_leased());
+    assert_ne!(read_entry.lease_id, entry.lease_id);
+    table.update().await?;
+    assert_eq!(table.version(), 0);
+
+    // a lease that was renewed in the meantime can't be broken
+    assert_eq!(
+        UpdateLogEntryResult::AlreadyCompleted,
+        client.break_lease(1, &table.table_uri(), &lease_id).await?
+    );
+
+    // once the heartbeat stops, other clients take over the entry after a whole lease duration
+    drop(heartbeat);
+    tokio::time::sleep(lease / 2).await;
+    table.update().await?;
+    assert_eq!(table.version(), 0);
+    tokio::time::sleep(lease).await;
+    table.update().await?;
+    assert_eq!(table.version(), 1);
+    validate_lock_table_state(&table, 1).await?;
+    Ok(())
+}
+
 const WORKERS: i64 = 3;
 const COMMITS: i64 = 15;
 
@@ -260,6 +351,7 @@ async fn create_incomplete_commit_entry(
     table: &DeltaTable,
     version: i64,
     tag: &str,
+    leased: bool,
 ) -> TestResult<CommitEntry> {
     let actions = vec![add_action(tag)];
     let operation = DeltaOperation::Write {
@@ -273,7 +365,10 @@ async fn create_incomplete_commit_entry(
         .into_prepared_commit_future()
         .await?;
 
-    let commit_entry = CommitEntry::new(version, prepared.path().to_owned());
+    let mut commit_entry = CommitEntry::new(version, prepared.path().to_owned());
+    if leased {
+        commit_entry = commit_entry.with_lease();
+    }
     make_client()?
         .put_commit_entry(&table.table_uri(), &commit_entry)
         .await?;
@@ -34,8 +34,7 @@ enum AzureCredential {
     BearerToken,
     /// Authorizing with secret
     ClientSecret,
-    /// Using a shared access signature
-    #[allow(dead_code)]
+    /// Using a managed identity
     ManagedIdentity,
     /// Using a shared access signature
     SasKey,
@@ -60,7 +59,12 @@ impl AzureCredential {
                 AzureConfigKey::FederatedTokenFile,
             ]),
             Self::SasKey => Vec::from_iter([AzureConfigKey::SasKey]),
-            Self::ManagedIdentity => Vec::new(),
+            // the identity is optional, configuring any of them selects a managed identity
+            Self::ManagedIdentity => Vec::from_iter([
+                AzureConfigKey::ObjectId,
+                AzureConfigKey::MsiResourceId,
+                AzureConfigKey::MsiEndpoint,
+            ]),
         }
     }
 }
@@ -128,7 +132,9 @@ impl AzureConfigHelper {
     pub fn build(mut self) -> Result<HashMap<AzureConfigKey, String>> {
         let mut has_credential = false;
 
-        if self.config.contains_key(&AzureConfigKey::UseAzureCli) {
+        if self.config.contains_key(&AzureConfigKey::UseAzureCli)
+            || self.has_any_config(&AzureCredential::ManagedIdentity)
+        {
             has_credential = true;
         }
 
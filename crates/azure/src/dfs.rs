@@ -0,0 +1,492 @@
+//! Client for the Data Lake Storage Gen2 (DFS) endpoint of storage accounts.
+//!
+//! Accounts with a hierarchical namespace (HNS) keep directories as objects of their own and
+//! rename paths atomically through the DFS endpoint. A rename conditioned on a missing
+//! destination gives commits `put_if_absent` semantics, where the blob endpoint has to copy
+//! the temporary commit file and delete it again.
+//!
+//! Requests use the HTTP client options of the object store, e.g. its timeouts and proxy, and
+//! are retried according to the storage retry options of the table.
+use std::collections::HashMap;
+
+use deltalake_core::storage::retry::StorageRetryConfig;
+use deltalake_core::storage::str_is_truthy;
+use deltalake_core::Path;
+use object_store::azure::{
+    AzureAuthorizer, AzureConfigKey, AzureCredential, AzureCredentialProvider,
+    MicrosoftAzureBuilder,
+};
+use object_store::{ClientConfigKey, ClientOptions};
+use reqwest::header::{HeaderValue, CONTENT_LENGTH, IF_NONE_MATCH};
+use reqwest::{Client, Method, Request, Response, StatusCode};
+use tracing::debug;
+use url::Url;
+
+use crate::error::{Error, Result};
+
+const HNS_ENABLED_HEADER: &str = "x-ms-namespace-enabled";
+const RENAME_SOURCE_HEADER: &str = "x-ms-rename-source";
+const ERROR_CODE_HEADER: &str = "x-ms-error-code";
+
+/// Error codes of a rename whose destination already exists
+const DESTINATION_EXISTS_CODES: [&str; 2] = ["PathAlreadyExists", "ConditionNotMet"];
+
+const BLOB_DOMAIN: &str = ".blob.core.windows.net";
+const DFS_DOMAIN: &str = ".dfs.core.windows.net";
+
+/// Location of a table within a storage account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AdlsLocation {
+    pub account: String,
+    pub container: String,
+    /// Path of the table root within the container
+    pub prefix: Path,
+}
+
+impl AdlsLocation {
+    /// Parse the location of a table from its url, falling back to the account and container
+    /// given in `config` where the url does not name them, the same way the object store does.
+    ///
+    /// Returns `None` for locations which are not served by the public DFS endpoint, like the
+    /// storage emulator, custom endpoints and Fabric.
+    pub fn try_new(url: &Url, config: &HashMap<AzureConfigKey, String>) -> Result<Option<Self>> {
+        let is_set =
+            |key: AzureConfigKey| config.get(&key).map_or(false, |value| str_is_truthy(value));
+        let is_custom = is_set(AzureConfigKey::UseEmulator)
+            || is_set(AzureConfigKey::UseFabricEndpoint)
+            || config.contains_key(&AzureConfigKey::Endpoint);
+        if is_custom {
+            return Ok(None);
+        }
+
+        let host = url
+            .host_str()
+            .ok_or_else(|| Error::Parse(format!("missing host in url: {url}")))?;
+        let (account, container) = match url.scheme() {
+            "az" | "adl" | "azure" => (None, Some(host)),
+            "abfs" | "abfss" if url.username().is_empty() => (None, Some(host)),
+            "abfs" | "abfss" => match account_from_host(host) {
+                Some(account) => (Some(account), Some(url.username())),
+                None => return Ok(None),
+            },
+            "https" => match account_from_host(host) {
+                Some(account) => (Some(account), None),
+                None => return Ok(None),
+            },
+            scheme => return Err(Error::Parse(format!("unsupported url scheme: {scheme}"))),
+        };
+
+        let from_config = |key: AzureConfigKey| config.get(&key).map(String::as_str);
+        let account = account
+            .or_else(|| from_config(AzureConfigKey::AccountName))
+            .ok_or_else(|| Error::Parse(format!("missing account name for url: {url}")))?
+            .to_string();
+        let container = container
+            .or_else(|| from_config(AzureConfigKey::ContainerName))
+            .ok_or_else(|| Error::Parse(format!("missing container name for url: {url}")))?
+            .to_string();
+        let prefix = Path::parse(url.path()).map_err(|err| Error::Parse(err.to_string()))?;
+
+        Ok(Some(Self {
+            account,
+            container,
+            prefix,
+        }))
+    }
+}
+
+fn account_from_host(host: &str) -> Option<&str> {
+    host.strip_suffix(DFS_DOMAIN)
+        .or_else(|| host.strip_suffix(BLOB_DOMAIN))
+}
+
+/// Everything needed to create the [`DfsClient`] of a table once it is used
+pub(crate) struct DfsConfig {
+    pub location: AdlsLocation,
+    /// Configuration of the object store, holding the credentials and client options
+    pub azure_config: HashMap<AzureConfigKey, String>,
+    pub retry: StorageRetryConfig,
+}
+
+impl std::fmt::Debug for DfsConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DfsConfig")
+            .field("location", &self.location)
+            .finish()
+    }
+}
+
+/// Client for the requests to a storage account which are not covered by the object store
+pub(crate) struct DfsClient {
+    location: AdlsLocation,
+    credentials: AzureCredentialProvider,
+    client: Client,
+    retry: StorageRetryConfig,
+}
+
+impl std::fmt::Debug for DfsClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DfsClient")
+            .field("location", &self.location)
+            .finish()
+    }
+}
+
+impl DfsClient {
+    /// Create a client for the table at `url`, authorized with the credentials and using the
+    /// client options of the object store
+    pub fn try_new(config: &DfsConfig, url: &Url) -> Result<Self> {
+        let azure = config
+            .azure_config
+            .iter()
+            .fold(
+                MicrosoftAzureBuilder::new().with_url(url.as_str()),
+                |builder, (key, value)| builder.with_config(*key, value),
+            )
+            .build()?;
+        Ok(Self {
+            location: config.location.clone(),
+            credentials: azure.credentials().clone(),
+            client: http_client(&config.azure_config)?,
+            retry: config.retry.clone(),
+        })
+    }
+
+    /// Url of the container on the DFS endpoint
+    fn filesystem_url(&self) -> Url {
+        let mut url = Url::parse(&format!("https://{}{DFS_DOMAIN}", self.location.account))
+            .expect("account names are valid host names");
+        url.path_segments_mut()
+            .expect("https urls have a path")
+            .push(&self.location.container);
+        url
+    }
+
+    /// Url of a path, relative to the table root, on the DFS endpoint
+    fn path_url(&self, path: &Path) -> Url {
+        let mut url = self.filesystem_url();
+        url.path_segments_mut()
+            .expect("https urls have a path")
+            .extend(self.location.prefix.parts())
+            .extend(path.parts());
+        url
+    }
+
+    /// Send the authorized request built by `build`, retrying attempts which failed with a
+    /// transient error. Requests which are not `idempotent` are only retried if they were
+    /// rejected before the account processed them.
+    async fn send(
+        &self,
+        build: impl Fn(&AzureCredential) -> Result<Request>,
+        idempotent: bool,
+    ) -> Result<Response> {
+        let mut retries = 0;
+        loop {
+            let credential = self.credentials.get_credential().await?;
+            let mut request = build(&credential)?;
+            if let Some(timeout) = self.retry.timeout {
+                *request.timeout_mut() = Some(timeout);
+            }
+            AzureAuthorizer::new(&credential, &self.location.account).authorize(&mut request);
+
+            let result = self.client.execute(request).await;
+            let transient = match &result {
+                Ok(response) => {
+                    matches!(
+                        response.status(),
+                        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+                    ) || (idempotent && response.status().is_server_error())
+                }
+                Err(err) => err.is_connect() || (idempotent && err.is_timeout()),
+            };
+            if !transient || retries >= self.retry.max_retries {
+                return Ok(result?);
+            }
+            retries += 1;
+            let delay = self.retry.backoff.delay(retries);
+            debug!("retrying request to {:?} in {delay:?}", self.location);
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Check whether the account has a hierarchical namespace.
+    ///
+    /// The properties of the container are read, so that credentials scoped to the container,
+    /// e.g. a container SAS, suffice.
+    pub async fn is_hns_enabled(&self) -> Result<bool> {
+        let mut url = self.filesystem_url();
+        url.query_pairs_mut().append_pair("resource", "filesystem");
+        let response = self
+            .send(|_| Ok(Request::new(Method::HEAD, url.clone())), true)
+            .await?
+            .error_for_status()?;
+        Ok(response
+            .headers()
+            .get(HNS_ENABLED_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map_or(false, |value| value.eq_ignore_ascii_case("true")))
+    }
+
+    /// Atomically rename `from` to `to`, both relative to the table root. Returns `false`
+    /// without renaming if `to` already exists.
+    pub async fn rename_if_not_exists(&self, from: &Path, to: &Path) -> Result<bool> {
+        let build = |credential: &AzureCredential| {
+            // the source is authorized separately, shared access signatures have to be appended
+            let mut source = self.path_url(from).path().to_string();
+            if let AzureCredential::SASToken(pairs) = credential {
+                let query = url::form_urlencoded::Serializer::new(String::new())
+                    .extend_pairs(pairs)
+                    .finish();
+                source = format!("{source}?{query}");
+            }
+
+            let mut request = Request::new(Method::PUT, self.path_url(to));
+            let headers = request.headers_mut();
+            headers.insert(
+                RENAME_SOURCE_HEADER,
+                HeaderValue::from_str(&source).map_err(|err| Error::Parse(err.to_string()))?,
+            );
+            headers.insert(IF_NONE_MATCH, HeaderValue::from_static("*"));
+            headers.insert(CONTENT_LENGTH, HeaderValue::from_static("0"));
+            Ok(request)
+        };
+
+        let response = self.send(build, false).await?;
+        let status = response.status();
+        let code = response
+            .headers()
+            .get(ERROR_CODE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string);
+        match rename_outcome(status, code.as_deref()) {
+            Some(renamed) => Ok(renamed),
+            None => Err(Error::Response {
+                status,
+                code: code.unwrap_or_default(),
+                message: response.text().await.unwrap_or_default(),
+            }),
+        }
+    }
+}
+
+/// Build an HTTP client with the client options of the object store configured by `config`,
+/// including its defaults, so that DFS requests are subject to the same timeouts, proxy and
+/// TLS settings as the requests of the object store
+fn http_client(config: &HashMap<AzureConfigKey, String>) -> Result<Client> {
+    let options = config
+        .iter()
+        .fold(ClientOptions::new(), |options, (key, value)| match key {
+            AzureConfigKey::Client(key) => options.with_config(*key, value),
+            _ => options,
+        });
+    let value = |key: ClientConfigKey| options.get_config_value(&key);
+    let is_set = |key: ClientConfigKey| value(key).map_or(false, |value| str_is_truthy(&value));
+    let duration = |key: ClientConfigKey| {
+        value(key)
+            .map(|value| {
+                humantime::parse_duration(&value)
+                    .map_err(|err| Error::Parse(format!("invalid {}: {err}", key.as_ref())))
+            })
+            .transpose()
+    };
+
+    let mut builder = Client::builder();
+    if let Some(user_agent) = value(ClientConfigKey::UserAgent) {
+        builder = builder.user_agent(user_agent);
+    }
+    if let Some(proxy_url) = value(ClientConfigKey::ProxyUrl) {
+        let mut proxy = reqwest::Proxy::all(proxy_url)?;
+        if let Some(certificate) = value(ClientConfigKey::ProxyCaCertificate) {
+            builder = builder
+                .add_root_certificate(reqwest::tls::Certificate::from_pem(certificate.as_bytes())?);
+        }
+        if let Some(excludes) = value(ClientConfigKey::ProxyExcludes) {
+            proxy = proxy.no_proxy(reqwest::NoProxy::from_string(&excludes));
+        }
+        builder = builder.proxy(proxy);
+    }
+    if let Some(timeout) = duration(ClientConfigKey::Timeout)? {
+        builder = builder.timeout(timeout);
+    }
+    if let Some(timeout) = duration(ClientConfigKey::ConnectTimeout)? {
+        builder = builder.connect_timeout(timeout);
+    }
+    if let Some(timeout) = duration(ClientConfigKey::PoolIdleTimeout)? {
+        builder = builder.pool_idle_timeout(timeout);
+    }
+    if let Some(max) = value(ClientConfigKey::PoolMaxIdlePerHost) {
+        builder = builder.pool_max_idle_per_host(
+            max.parse()
+                .map_err(|_| Error::Parse(format!("invalid pool_max_idle_per_host: {max}")))?,
+        );
+    }
+    if is_set(ClientConfigKey::Http1Only) {
+        builder = builder.http1_only();
+    }
+    if is_set(ClientConfigKey::Http2Only) {
+        builder = builder.http2_prior_knowledge();
+    }
+    if is_set(ClientConfigKey::AllowInvalidCertificates) {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    Ok(builder
+        .https_only(!is_set(ClientConfigKey::AllowHttp))
+        .build()?)
+}
+
+/// Whether a rename succeeded, or `None` if it failed for another reason than an existing
+/// destination
+fn rename_outcome(status: StatusCode, code: Option<&str>) -> Option<bool> {
+    match status {
+        status if status.is_success() => Some(true),
+        StatusCode::PRECONDITION_FAILED => Some(false),
+        StatusCode::CONFLICT
+            if code.map_or(false, |code| DESTINATION_EXISTS_CODES.contains(&code)) =>
+        {
+            Some(false)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(url: &str, config: &[(AzureConfigKey, &str)]) -> Result<Option<AdlsLocation>> {
+        let config = config
+            .iter()
+            .map(|(key, value)| (*key, value.to_string()))
+            .collect();
+        AdlsLocation::try_new(&Url::parse(url).unwrap(), &config)
+    }
+
+    #[test]
+    fn test_parse_location() {
+        let expected = AdlsLocation {
+            account: "account".to_string(),
+            container: "container".to_string(),
+            prefix: Path::from("path/to/table"),
+        };
+        let account = [(AzureConfigKey::AccountName, "account")];
+        for (url, config) in [
+            (
+                "abfss://container@account.dfs.core.windows.net/path/to/table",
+                &[][..],
+            ),
+            (
+                "abfs://container@account.blob.core.windows.net/path/to/table",
+                &[][..],
+            ),
+            ("abfss://container/path/to/table", &account[..]),
+            ("az://container/path/to/table", &account[..]),
+            ("adl://container/path/to/table", &account[..]),
+            (
+                "https://account.dfs.core.windows.net/path/to/table",
+                &[(AzureConfigKey::ContainerName, "container")][..],
+            ),
+        ] {
+            assert_eq!(
+                location(url, config).unwrap(),
+                Some(expected.clone()),
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_location_unsupported() {
+        assert!(location("az://container/table", &[])
+            .unwrap_err()
+            .to_string()
+            .contains("missing account name"));
+        assert!(location(
+            "az://container/table",
+            &[
+                (AzureConfigKey::AccountName, "devstoreaccount1"),
+                (AzureConfigKey::UseEmulator, "true")
+            ]
+        )
+        .unwrap()
+        .is_none());
+        assert!(location(
+            "abfss://workspace@onelake.dfs.fabric.microsoft.com/table",
+            &[]
+        )
+        .unwrap()
+        .is_none());
+    }
+
+    #[test]
+    fn test_rename_outcome() {
+        assert_eq!(rename_outcome(StatusCode::CREATED, None), Some(true));
+        assert_eq!(
+            rename_outcome(StatusCode::CONFLICT, Some("PathAlreadyExists")),
+            Some(false)
+        );
+        assert_eq!(
+            rename_outcome(StatusCode::PRECONDITION_FAILED, Some("ConditionNotMet")),
+            Some(false)
+        );
+        assert_eq!(
+            rename_outcome(StatusCode::CONFLICT, Some("SourcePathIsBeingDeleted")),
+            None
+        );
+        assert_eq!(
+            rename_outcome(StatusCode::NOT_FOUND, Some("SourcePathNotFound")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_http_client() {
+        let config = HashMap::from([
+            (
+                AzureConfigKey::Client(ClientConfigKey::Timeout),
+                "10s".to_string(),
+            ),
+            (
+                AzureConfigKey::Client(ClientConfigKey::ProxyUrl),
+                "http://localhost:8080".to_string(),
+            ),
+        ]);
+        assert!(http_client(&config).is_ok());
+
+        let config = HashMap::from([(
+            AzureConfigKey::Client(ClientConfigKey::Timeout),
+            "soon".to_string(),
+        )]);
+        assert!(http_client(&config)
+            .unwrap_err()
+            .to_string()
+            .contains("invalid timeout"));
+    }
+
+    #[test]
+    fn test_dfs_urls() {
+        let config = DfsConfig {
+            location: AdlsLocation {
+                account: "account".to_string(),
+                container: "container".to_string(),
+                prefix: Path::from("path/to/table"),
+            },
+            azure_config: HashMap::from([
+                (AzureConfigKey::AccountName, "account".to_string()),
+                (AzureConfigKey::AccessKey, "a2V5".to_string()),
+            ]),
+            retry: Default::default(),
+        };
+        let url = Url::parse("abfss://container@account.dfs.core.windows.net/path/to/table");
+        let client = DfsClient::try_new(&config, &url.unwrap()).unwrap();
+        assert_eq!(
+            client.filesystem_url().as_str(),
+            "https://account.dfs.core.windows.net/container"
+        );
+        assert_eq!(
+            client
+                .path_url(&Path::from("_delta_log/00000000000000000000.json"))
+                .as_str(),
+            "https://account.dfs.core.windows.net/container/path/to/table/_delta_log/00000000000000000000.json"
+        );
+    }
+}
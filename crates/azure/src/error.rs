@@ -4,12 +4,21 @@ pub(crate) type Result<T, E = Error> = std::result::Result<T, E>;
 
 #[derive(thiserror::Error, Debug)]
 pub(crate) enum Error {
-    #[allow(dead_code)]
     #[error("failed to parse config: {0}")]
     Parse(String),
 
     #[error(transparent)]
     ObjectStore(#[from] object_store::Error),
+
+    #[error("request to storage account failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("storage account responded with {status} {code}: {message}")]
+    Response {
+        status: reqwest::StatusCode,
+        code: String,
+        message: String,
+    },
 }
 
 impl From<Error> for DeltaTableError {
@@ -17,6 +26,9 @@ impl From<Error> for DeltaTableError {
         match e {
             Error::Parse(msg) => DeltaTableError::Generic(msg),
             Error::ObjectStore(e) => DeltaTableError::ObjectStore { source: e },
+            e => DeltaTableError::GenericError {
+                source: Box::new(e),
+            },
         }
     }
 }
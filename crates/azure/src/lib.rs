@@ -2,17 +2,32 @@ use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
 
-use deltalake_core::logstore::{default_logstore, logstores, LogStore, LogStoreFactory};
+use deltalake_core::logstore::{logstores, LogStore, LogStoreConfig, LogStoreFactory};
+use deltalake_core::storage::retry::StorageRetryConfig;
 use deltalake_core::storage::{
-    factories, url_prefix_handler, ObjectStoreFactory, ObjectStoreRef, StorageOptions,
+    factories, str_is_truthy, url_prefix_handler, ObjectStoreFactory, ObjectStoreRef,
+    StorageOptions,
 };
 use deltalake_core::{DeltaResult, Path};
 use object_store::azure::AzureConfigKey;
 use object_store::parse_url_opts;
 use url::Url;
 
+use crate::dfs::{AdlsLocation, DfsConfig};
+use crate::logstore::AdlsLogStore;
+
 mod config;
+mod dfs;
 pub mod error;
+pub mod logstore;
+
+pub mod constants {
+    /// Whether the storage account has a hierarchical namespace, in which case commits are
+    /// atomically renamed through the DFS endpoint. Detected from the properties of the
+    /// container when the first commit is written if unset. Set it to skip the detection,
+    /// e.g. for credentials which may not read the container properties.
+    pub const HNS_ENABLED_KEY_NAME: &str = "AZURE_HNS_ENABLED";
+}
 
 trait AzureOptions {
     fn as_azure_options(&self) -> HashMap<AzureConfigKey, String>;
@@ -54,7 +69,29 @@ impl LogStoreFactory for AzureFactory {
         location: &Url,
         options: &StorageOptions,
     ) -> DeltaResult<Arc<dyn LogStore>> {
-        Ok(default_logstore(store, location, options))
+        let hns_enabled = options
+            .0
+            .get(constants::HNS_ENABLED_KEY_NAME)
+            .map(|value| str_is_truthy(value));
+        let config = config::AzureConfigHelper::try_new(options.as_azure_options())?.build()?;
+        // the DFS client is only created once a commit is written to an ADLS location
+        let dfs = match AdlsLocation::try_new(location, &config)? {
+            Some(adls_location) if hns_enabled != Some(false) => Some(DfsConfig {
+                location: adls_location,
+                azure_config: config,
+                retry: StorageRetryConfig::from_options(options)?.unwrap_or_default(),
+            }),
+            _ => None,
+        };
+        Ok(Arc::new(AdlsLogStore::new(
+            store,
+            dfs,
+            hns_enabled,
+            LogStoreConfig {
+                location: location.clone(),
+                options: options.clone(),
+            },
+        )))
     }
 }
 
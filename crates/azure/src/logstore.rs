@@ -0,0 +1,148 @@
+//! Log store implementation for ADLS Gen2 storage accounts.
+//! Accounts with a hierarchical namespace commit by atomically renaming the temporary commit
+//! file through the DFS endpoint. Other accounts fall back to the blob endpoint, where the
+//! object store emulates `rename_if_not_exists` by copying and deleting the commit file.
+
+use std::sync::OnceLock;
+
+use bytes::Bytes;
+use deltalake_core::logstore::*;
+use deltalake_core::{
+    operations::transaction::TransactionError,
+    storage::{commit_uri_from_version, ObjectStoreRef},
+    DeltaResult, Path,
+};
+use tracing::{debug, warn};
+
+use crate::dfs::{DfsClient, DfsConfig};
+
+/// [`LogStore`] implementation for ADLS Gen2 storage accounts
+pub struct AdlsLogStore {
+    storage: ObjectStoreRef,
+    dfs_config: Option<DfsConfig>,
+    /// The DFS client, created with the first commit
+    dfs: OnceLock<DfsClient>,
+    /// Whether the account has a hierarchical namespace, once configured or detected
+    hns_enabled: OnceLock<bool>,
+    config: LogStoreConfig,
+}
+
+impl std::fmt::Debug for AdlsLogStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "AdlsLogStore({})", self.config.location)
+    }
+}
+
+impl AdlsLogStore {
+    /// Create a new [`AdlsLogStore`]. Without a `dfs_config` commits always go through the
+    /// blob endpoint; `hns_enabled` skips detecting the hierarchical namespace.
+    pub(crate) fn new(
+        storage: ObjectStoreRef,
+        dfs_config: Option<DfsConfig>,
+        hns_enabled: Option<bool>,
+        config: LogStoreConfig,
+    ) -> Self {
+        let lock = OnceLock::new();
+        if let Some(enabled) = hns_enabled {
+            let _ = lock.set(enabled);
+        }
+        Self {
+            storage,
+            dfs_config,
+            dfs: OnceLock::new(),
+            hns_enabled: lock,
+            config,
+        }
+    }
+
+    /// The DFS client, created on first use
+    fn dfs(&self) -> Option<&DfsClient> {
+        let dfs_config = self.dfs_config.as_ref()?;
+        if let Some(dfs) = self.dfs.get() {
+            return Some(dfs);
+        }
+        match DfsClient::try_new(dfs_config, &self.config.location) {
+            Ok(dfs) => {
+                let _ = self.dfs.set(dfs);
+                self.dfs.get()
+            }
+            Err(err) => {
+                warn!("failed to create DFS client for {self:?}: {err}");
+                None
+            }
+        }
+    }
+
+    /// The DFS client, if the account has a hierarchical namespace. Failed detections are not
+    /// cached, so they are retried with the next commit.
+    async fn hns_client(&self) -> Option<&DfsClient> {
+        let dfs = self.dfs()?;
+        if let Some(enabled) = self.hns_enabled.get() {
+            return enabled.then_some(dfs);
+        }
+        match dfs.is_hns_enabled().await {
+            Ok(enabled) => {
+                debug!("hierarchical namespace of {self:?} enabled: {enabled}");
+                let _ = self.hns_enabled.set(enabled);
+                enabled.then_some(dfs)
+            }
+            Err(err) => {
+                warn!("failed to detect hierarchical namespace of {self:?}: {err}");
+                None
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl LogStore for AdlsLogStore {
+    fn name(&self) -> String {
+        "AdlsLogStore".into()
+    }
+
+    async fn read_commit_entry(&self, version: i64) -> DeltaResult<Option<Bytes>> {
+        read_commit_entry(self.log_object_store().as_ref(), version).await
+    }
+
+    /// Tries to commit a prepared commit file. Returns [`TransactionError::VersionAlreadyExists`]
+    /// if the given `version` already exists. The caller should handle the retry logic itself.
+    async fn write_commit_entry(
+        &self,
+        version: i64,
+        tmp_commit: &Path,
+    ) -> Result<(), TransactionError> {
+        let Some(dfs) = self.hns_client().await else {
+            return write_commit_entry(self.log_object_store().as_ref(), version, tmp_commit).await;
+        };
+        let renamed = dfs
+            .rename_if_not_exists(tmp_commit, &commit_uri_from_version(version))
+            .await
+            .map_err(|err| TransactionError::LogStoreError {
+                msg: "failed to rename commit through the DFS endpoint".to_owned(),
+                source: Box::new(err),
+            })?;
+        if renamed {
+            Ok(())
+        } else {
+            Err(TransactionError::VersionAlreadyExists(version))
+        }
+    }
+
+    async fn get_latest_version(&self, current_version: i64) -> DeltaResult<i64> {
+        get_latest_version(self, current_version).await
+    }
+
+    fn object_store(&self) -> ObjectStoreRef {
+        self.storage.clone()
+    }
+
+    /// The log is kept next to the data files, the DFS endpoint renames commits within the
+    /// same container
+    fn log_object_store(&self) -> ObjectStoreRef {
+        self.storage.clone()
+    }
+
+    fn config(&self) -> &LogStoreConfig {
+        &self.config
+    }
+}
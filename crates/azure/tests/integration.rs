@@ -1,8 +1,10 @@
 #![cfg(feature = "integration_test")]
 
 use bytes::Bytes;
+use deltalake_core::operations::transaction::TransactionError;
 use deltalake_core::DeltaTableBuilder;
 use deltalake_test::read::read_table_paths;
+use deltalake_test::utils::TestTables;
 use deltalake_test::{test_concurrent_writes, test_read_tables, IntegrationContext, TestResult};
 use object_store::path::Path;
 use serial_test::serial;
@@ -38,6 +40,46 @@ async fn test_concurrency_azure() -> TestResult {
     Ok(())
 }
 
+/// Against the emulator commits fall back to the blob endpoint, running with
+/// `AZURE_STORAGE_USE_EMULATOR=0` against an account with a hierarchical namespace
+/// exercises the renames through the DFS endpoint.
+#[tokio::test]
+#[serial]
+async fn test_adls_log_store() -> TestResult {
+    let context = IntegrationContext::new(Box::new(MsftIntegration::default()))?;
+    context.load_table(TestTables::Simple).await?;
+
+    let log_store = context.table_builder(TestTables::Simple).build_storage()?;
+    assert_eq!(log_store.name(), "AdlsLogStore");
+
+    let version = log_store.get_latest_version(0).await?;
+    assert!(log_store.read_commit_entry(version).await?.is_some());
+
+    let tmp_commit = Path::from("_delta_log/_commit_integration_test.json.tmp");
+    log_store
+        .log_object_store()
+        .put(&tmp_commit, Bytes::from_static(b"{}"))
+        .await?;
+
+    let result = log_store.write_commit_entry(version, &tmp_commit).await;
+    assert!(
+        matches!(result, Err(TransactionError::VersionAlreadyExists(v)) if v == version),
+        "expected the existing commit to be kept, got {result:?}"
+    );
+
+    log_store
+        .write_commit_entry(version + 1, &tmp_commit)
+        .await?;
+    assert!(log_store.read_commit_entry(version + 1).await?.is_some());
+    assert!(log_store
+        .log_object_store()
+        .head(&tmp_commit)
+        .await
+        .is_err());
+
+    Ok(())
+}
+
 // NOTE: This test is ignored based on [this
 // comment](https://github.com/delta-io/delta-rs/pull/1564#issuecomment-1721048753) and we should
 // figure out a way to re-enable this test at least in the GitHub Actions CI environment
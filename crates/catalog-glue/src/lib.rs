@@ -1,7 +1,14 @@
 //! Glue Data Catalog.
 //!
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use aws_config::{BehaviorVersion, SdkConfig};
-use deltalake_core::data_catalog::{DataCatalog, DataCatalogError};
+use aws_sdk_glue::types::{SerDeInfo, StorageDescriptor, Table, TableInput};
+use deltalake_core::data_catalog::{register_catalog, DataCatalog, DataCatalogError};
+use deltalake_core::{DeltaTable, DeltaTableError};
+
+pub mod schema;
 
 #[derive(thiserror::Error, Debug)]
 pub enum GlueError {
@@ -18,6 +25,20 @@ pub enum GlueError {
         #[from]
         source: aws_sdk_glue::Error,
     },
+
+    /// Error building a request for the AWS SDK
+    #[error("Failed to build an AWS SDK request: {source}")]
+    BuildError {
+        #[from]
+        source: aws_sdk_glue::error::BuildError,
+    },
+
+    /// Error reading the Delta table to register
+    #[error("Failed to read the Delta table: {source}")]
+    DeltaTable {
+        #[from]
+        source: DeltaTableError,
+    },
 }
 
 impl From<GlueError> for DataCatalogError {
@@ -49,6 +70,14 @@ impl GlueDataCatalog {
     }
 }
 
+/// Register a [GlueDataCatalog] with environmental configuration for `glue://` catalog URIs,
+/// e.g. `glue://my_db.my_table`
+pub async fn register_handlers() -> Result<(), GlueError> {
+    let catalog = GlueDataCatalog::from_env().await?;
+    register_catalog("glue", Arc::new(catalog));
+    Ok(())
+}
+
 impl std::fmt::Debug for GlueDataCatalog {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(fmt, "GlueDataCatalog")
@@ -58,6 +87,114 @@ impl std::fmt::Debug for GlueDataCatalog {
 // Placeholder suffix created by Spark in the Glue Data Catalog Location
 const PLACEHOLDER_SUFFIX: &str = "-__PLACEHOLDER__";
 
+// Storage format Spark and Athena expect for Delta tables registered in the Glue Data Catalog
+const PARQUET_INPUT_FORMAT: &str = "org.apache.hadoop.hive.ql.io.parquet.MapredParquetInputFormat";
+const PARQUET_OUTPUT_FORMAT: &str =
+    "org.apache.hadoop.hive.ql.io.parquet.MapredParquetOutputFormat";
+const PARQUET_SERDE: &str = "org.apache.hadoop.hive.ql.io.parquet.serde.ParquetHiveSerDe";
+
+impl GlueDataCatalog {
+    /// Register `table` as `database_name.table_name` in the Glue Data Catalog.
+    ///
+    /// Columns are translated into Athena/Hive types and the table parameters Spark uses to
+    /// detect Delta tables are set, so the table can be queried from both Athena and EMR.
+    /// An existing table with the same name is updated in place.
+    pub async fn register_table(
+        &self,
+        catalog_id: Option<String>,
+        database_name: &str,
+        table_name: &str,
+        table: &DeltaTable,
+    ) -> Result<(), GlueError> {
+        let table_input = table_input(table_name, table)?;
+
+        let result = self
+            .client
+            .create_table()
+            .set_catalog_id(catalog_id.clone())
+            .database_name(database_name)
+            .table_input(table_input.clone())
+            .send()
+            .await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err)
+                if err
+                    .as_service_error()
+                    .map_or(false, |err| err.is_already_exists_exception()) =>
+            {
+                self.client
+                    .update_table()
+                    .set_catalog_id(catalog_id)
+                    .database_name(database_name)
+                    .table_input(table_input)
+                    .send()
+                    .await
+                    .map_err(|e| GlueError::AWSError { source: e.into() })?;
+                Ok(())
+            }
+            Err(err) => Err(GlueError::AWSError { source: err.into() }),
+        }
+    }
+}
+
+/// Whether a table registered in the Glue Data Catalog is a Delta table, as registered by
+/// delta-rs, Spark or a Glue crawler
+fn is_delta_table(table: &Table) -> bool {
+    table.parameters().map_or(false, |parameters| {
+        parameters
+            .get("table_type")
+            .map_or(false, |value| value.eq_ignore_ascii_case("delta"))
+            || parameters
+                .get("spark.sql.sources.provider")
+                .map_or(false, |value| value.eq_ignore_ascii_case("delta"))
+    })
+}
+
+fn table_input(table_name: &str, table: &DeltaTable) -> Result<TableInput, GlueError> {
+    let metadata = table.metadata()?;
+    let schema = metadata.schema().map_err(DeltaTableError::from)?;
+    let (columns, partition_keys) = schema::glue_columns(&schema, &metadata.partition_columns)?;
+    let location = table.table_uri().replace("s3a://", "s3://");
+
+    let serde_info = SerDeInfo::builder()
+        .serialization_library(PARQUET_SERDE)
+        .parameters("serialization.format", "1")
+        .parameters("path", &location)
+        .build();
+    let storage_descriptor = StorageDescriptor::builder()
+        .set_columns(Some(columns))
+        .location(&location)
+        .input_format(PARQUET_INPUT_FORMAT)
+        .output_format(PARQUET_OUTPUT_FORMAT)
+        .serde_info(serde_info)
+        .build();
+
+    // table properties are pushed along, the parameters identifying the table as Delta win
+    let mut parameters = metadata
+        .configuration
+        .iter()
+        .filter_map(|(key, value)| Some((key.clone(), value.clone()?)))
+        .collect::<HashMap<_, _>>();
+    parameters.extend([
+        ("EXTERNAL".to_string(), "TRUE".to_string()),
+        ("table_type".to_string(), "DELTA".to_string()),
+        (
+            "spark.sql.sources.provider".to_string(),
+            "delta".to_string(),
+        ),
+    ]);
+
+    Ok(TableInput::builder()
+        .name(table_name)
+        .set_description(metadata.description.clone())
+        .table_type("EXTERNAL_TABLE")
+        .storage_descriptor(storage_descriptor)
+        .set_partition_keys(Some(partition_keys))
+        .set_parameters(Some(parameters))
+        .build()?)
+}
+
 #[async_trait::async_trait]
 impl DataCatalog for GlueDataCatalog {
     /// Get the table storage location from the Glue Data Catalog
@@ -111,4 +248,172 @@ impl DataCatalog for GlueDataCatalog {
             Err(err) => Err(err.into()),
         }
     }
+
+    /// List the databases of the Glue Data Catalog of `catalog_id`, the account of the client by
+    /// default
+    async fn list_databases(
+        &self,
+        catalog_id: Option<String>,
+    ) -> Result<Vec<String>, DataCatalogError> {
+        let mut names = Vec::new();
+        let mut next_token = None;
+        loop {
+            let response = self
+                .client
+                .get_databases()
+                .set_catalog_id(catalog_id.clone())
+                .set_next_token(next_token)
+                .send()
+                .await
+                .map_err(|e| GlueError::AWSError { source: e.into() })?;
+            names.extend(
+                response
+                    .database_list()
+                    .iter()
+                    .map(|database| database.name().to_string()),
+            );
+            next_token = response.next_token().map(String::from);
+            if next_token.is_none() {
+                return Ok(names);
+            }
+        }
+    }
+
+    /// List the Delta tables of a database of the Glue Data Catalog
+    async fn list_tables(
+        &self,
+        catalog_id: Option<String>,
+        database_name: &str,
+    ) -> Result<Vec<String>, DataCatalogError> {
+        let mut names = Vec::new();
+        let mut next_token = None;
+        loop {
+            let response = self
+                .client
+                .get_tables()
+                .set_catalog_id(catalog_id.clone())
+                .database_name(database_name)
+                .set_next_token(next_token)
+                .send()
+                .await
+                .map_err(|e| GlueError::AWSError { source: e.into() })?;
+            names.extend(
+                response
+                    .table_list()
+                    .iter()
+                    .filter(|table| is_delta_table(table))
+                    .map(|table| table.name().to_string()),
+            );
+            next_token = response.next_token().map(String::from);
+            if next_token.is_none() {
+                return Ok(names);
+            }
+        }
+    }
+
+    /// Update the existing entry of the table in the Glue Data Catalog with an
+    /// `UpdateTableRequest`. Use [`GlueDataCatalog::register_table`] to create missing entries
+    async fn sync_table_metadata(
+        &self,
+        catalog_id: Option<String>,
+        database_name: &str,
+        table_name: &str,
+        table: &DeltaTable,
+    ) -> Result<(), DataCatalogError> {
+        let table_input = table_input(table_name, table)?;
+        self.client
+            .update_table()
+            .set_catalog_id(catalog_id)
+            .database_name(database_name)
+            .table_input(table_input)
+            .send()
+            .await
+            .map_err(|e| GlueError::AWSError { source: e.into() })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(parameters: &[(&str, &str)]) -> Table {
+        Table::builder()
+            .name("events")
+            .set_parameters(Some(
+                parameters
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect(),
+            ))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_is_delta_table() {
+        assert!(is_delta_table(&table(&[("table_type", "DELTA")])));
+        assert!(is_delta_table(&table(&[(
+            "spark.sql.sources.provider",
+            "delta"
+        )])));
+        assert!(!is_delta_table(&table(&[("table_type", "ICEBERG")])));
+        assert!(!is_delta_table(&table(&[])));
+    }
+
+    #[tokio::test]
+    async fn test_table_input() {
+        use deltalake_core::kernel::{DataType, PrimitiveType};
+        use deltalake_core::DeltaOps;
+
+        let table = DeltaOps::new_in_memory()
+            .create()
+            .with_column("id", DataType::Primitive(PrimitiveType::Long), true, None)
+            .with_column("date", DataType::Primitive(PrimitiveType::Date), true, None)
+            .with_partition_columns(["date"])
+            .with_configuration([
+                ("delta.appendOnly", Some("true")),
+                ("table_type", Some("ICEBERG")),
+            ])
+            .await
+            .unwrap();
+        let input = table_input("events", &table).unwrap();
+
+        let descriptor = input.storage_descriptor().unwrap();
+        let columns = descriptor
+            .columns()
+            .iter()
+            .map(|column| (column.name(), column.r#type()))
+            .collect::<Vec<_>>();
+        assert_eq!(columns, vec![("id", Some("bigint"))]);
+        assert_eq!(descriptor.input_format(), Some(PARQUET_INPUT_FORMAT));
+        assert_eq!(descriptor.output_format(), Some(PARQUET_OUTPUT_FORMAT));
+        assert_eq!(
+            descriptor.serde_info().unwrap().serialization_library(),
+            Some(PARQUET_SERDE)
+        );
+
+        let partition_keys = input
+            .partition_keys()
+            .iter()
+            .map(|column| (column.name(), column.r#type()))
+            .collect::<Vec<_>>();
+        assert_eq!(partition_keys, vec![("date", Some("date"))]);
+        let parameters = input.parameters().unwrap();
+        assert_eq!(
+            parameters.get("delta.appendOnly").map(String::as_str),
+            Some("true")
+        );
+        assert_eq!(
+            parameters.get("table_type").map(String::as_str),
+            Some("DELTA")
+        );
+        assert!(is_delta_table(
+            &Table::builder()
+                .name("events")
+                .set_parameters(input.parameters().cloned())
+                .build()
+                .unwrap()
+        ));
+    }
 }
@@ -0,0 +1,139 @@
+//! Translation of Delta schemas into Glue column definitions.
+//!
+//! Glue stores column types as Hive type strings, which are also understood by Athena and
+//! Spark on EMR. Nested types are spelled out recursively, e.g. `array<struct<a:int>>`.
+
+use aws_sdk_glue::types::Column;
+use deltalake_core::kernel::{DataType, MetadataValue, PrimitiveType, StructField, StructType};
+
+use crate::GlueError;
+
+/// Column metadata key Spark uses for column comments
+const COMMENT_METADATA_KEY: &str = "comment";
+
+/// Translate a Delta data type into the corresponding Athena/Hive type string
+pub fn glue_type(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Primitive(primitive) => match primitive {
+            PrimitiveType::String => "string".to_string(),
+            PrimitiveType::Long => "bigint".to_string(),
+            PrimitiveType::Integer => "int".to_string(),
+            PrimitiveType::Short => "smallint".to_string(),
+            PrimitiveType::Byte => "tinyint".to_string(),
+            PrimitiveType::Float => "float".to_string(),
+            PrimitiveType::Double => "double".to_string(),
+            PrimitiveType::Boolean => "boolean".to_string(),
+            PrimitiveType::Binary => "binary".to_string(),
+            PrimitiveType::Date => "date".to_string(),
+            // Hive has no notion of time zones, both are stored as microseconds
+            PrimitiveType::Timestamp | PrimitiveType::TimestampNtz => "timestamp".to_string(),
+            PrimitiveType::Decimal(precision, scale) => format!("decimal({precision},{scale})"),
+        },
+        DataType::Array(array) => format!("array<{}>", glue_type(array.element_type())),
+        DataType::Map(map) => format!(
+            "map<{},{}>",
+            glue_type(map.key_type()),
+            glue_type(map.value_type())
+        ),
+        DataType::Struct(fields) => format!(
+            "struct<{}>",
+            fields
+                .fields()
+                .iter()
+                .map(|field| format!("{}:{}", field.name(), glue_type(field.data_type())))
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+    }
+}
+
+fn glue_column(field: &StructField) -> Result<Column, GlueError> {
+    let comment = match field.metadata().get(COMMENT_METADATA_KEY) {
+        Some(MetadataValue::String(comment)) => Some(comment.clone()),
+        _ => None,
+    };
+    Ok(Column::builder()
+        .name(field.name())
+        .r#type(glue_type(field.data_type()))
+        .set_comment(comment)
+        .build()?)
+}
+
+/// Split the schema into the regular columns and partition keys of a Glue table.
+///
+/// Hive expects partition columns to be listed after all other columns, in partition order.
+pub fn glue_columns(
+    schema: &StructType,
+    partition_columns: &[String],
+) -> Result<(Vec<Column>, Vec<Column>), GlueError> {
+    let columns = schema
+        .fields()
+        .iter()
+        .filter(|field| !partition_columns.contains(field.name()))
+        .map(glue_column)
+        .collect::<Result<Vec<_>, _>>()?;
+    let partition_keys = partition_columns
+        .iter()
+        .map(|name| {
+            let field = schema
+                .field_with_name(name)
+                .map_err(|_| GlueError::MissingMetadata {
+                    metadata: format!("Partition column {name}"),
+                })?;
+            glue_column(field)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((columns, partition_keys))
+}
+
+#[cfg(test)]
+mod tests {
+    use deltalake_core::kernel::{ArrayType, MapType};
+
+    use super::*;
+
+    #[test]
+    fn test_glue_type() {
+        let nested = StructType::new(vec![
+            StructField::new("a", DataType::Primitive(PrimitiveType::Integer), true),
+            StructField::new(
+                "b",
+                DataType::Array(Box::new(ArrayType::new(
+                    DataType::Primitive(PrimitiveType::String),
+                    true,
+                ))),
+                true,
+            ),
+        ]);
+        assert_eq!(
+            glue_type(&DataType::Struct(Box::new(nested))),
+            "struct<a:int,b:array<string>>"
+        );
+        assert_eq!(
+            glue_type(&DataType::Map(Box::new(MapType::new(
+                DataType::Primitive(PrimitiveType::String),
+                DataType::Primitive(PrimitiveType::Decimal(10, 2)),
+                true,
+            )))),
+            "map<string,decimal(10,2)>"
+        );
+        assert_eq!(
+            glue_type(&DataType::Primitive(PrimitiveType::TimestampNtz)),
+            "timestamp"
+        );
+    }
+
+    #[test]
+    fn test_glue_columns() {
+        let schema = StructType::new(vec![
+            StructField::new("date", DataType::Primitive(PrimitiveType::Date), true),
+            StructField::new("id", DataType::Primitive(PrimitiveType::Long), false),
+        ]);
+        let (columns, partition_keys) = glue_columns(&schema, &["date".to_string()]).unwrap();
+        assert_eq!(columns.len(), 1);
+        assert_eq!(columns[0].name(), "id");
+        assert_eq!(columns[0].r#type(), Some("bigint"));
+        assert_eq!(partition_keys.len(), 1);
+        assert_eq!(partition_keys[0].name(), "date");
+    }
+}
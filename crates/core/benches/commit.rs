@@ -0,0 +1,86 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use deltalake_core::bench_utils::{
+    bench_add, bench_record_batch, create_log_table, BenchTableConfig,
+};
+use deltalake_core::kernel::Action;
+use deltalake_core::operations::transaction::{CommitBuilder, TableReference};
+use deltalake_core::protocol::{DeltaOperation, SaveMode};
+use deltalake_core::writer::{DeltaWriter, RecordBatchWriter};
+use tokio::runtime::Runtime;
+
+fn commit(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("commit");
+    group.sample_size(20);
+
+    for files_per_commit in [10, 1_000] {
+        let config = BenchTableConfig {
+            num_commits: 1,
+            files_per_commit,
+            ..Default::default()
+        };
+        let adds = (0..files_per_commit)
+            .map(|file| Action::Add(bench_add(&config, 1, file)))
+            .collect::<Vec<_>>();
+        group.throughput(Throughput::Elements(files_per_commit as u64));
+        group.bench_with_input(
+            BenchmarkId::new("commit_adds", files_per_commit),
+            &adds,
+            |b, adds| {
+                b.iter_batched(
+                    || runtime.block_on(create_log_table(&config)).unwrap(),
+                    |table| {
+                        runtime.block_on(async {
+                            CommitBuilder::default()
+                                .with_actions(adds.clone())
+                                .build(
+                                    Some(table.snapshot().unwrap() as &dyn TableReference),
+                                    table.log_store(),
+                                    DeltaOperation::Write {
+                                        mode: SaveMode::Append,
+                                        partition_by: None,
+                                        predicate: None,
+                                    },
+                                )
+                                .unwrap()
+                                .await
+                                .unwrap()
+                        })
+                    },
+                    BatchSize::PerIteration,
+                )
+            },
+        );
+    }
+
+    for rows_per_file in [1_000, 100_000] {
+        let config = BenchTableConfig {
+            num_commits: 0,
+            rows_per_file,
+            ..Default::default()
+        };
+        let batch = bench_record_batch(&config, 0);
+        group.throughput(Throughput::Elements(rows_per_file as u64));
+        group.bench_with_input(
+            BenchmarkId::new("write_batch", rows_per_file),
+            &batch,
+            |b, batch| {
+                b.iter_batched(
+                    || runtime.block_on(create_log_table(&config)).unwrap(),
+                    |mut table| {
+                        runtime.block_on(async {
+                            let mut writer = RecordBatchWriter::for_table(&table).unwrap();
+                            writer.write(batch.clone()).await.unwrap();
+                            writer.flush_and_commit(&mut table).await.unwrap()
+                        })
+                    },
+                    BatchSize::PerIteration,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, commit);
+criterion_main!(benches);
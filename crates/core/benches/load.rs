@@ -0,0 +1,37 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use deltalake_core::bench_utils::{create_log_table, BenchTableConfig};
+use deltalake_core::DeltaTable;
+use tokio::runtime::Runtime;
+
+fn load(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("load");
+    group.sample_size(10);
+    for (num_commits, files_per_commit) in [(10, 1_000), (1_000, 10)] {
+        let config = BenchTableConfig {
+            num_commits,
+            files_per_commit,
+            num_partitions: 16,
+            ..Default::default()
+        };
+        let table = runtime.block_on(create_log_table(&config)).unwrap();
+        let id = format!("{num_commits}x{files_per_commit}");
+
+        group.bench_with_input(BenchmarkId::new("log_replay", &id), &table, |b, table| {
+            b.iter(|| {
+                runtime.block_on(async {
+                    let mut loaded = DeltaTable::new(table.log_store(), Default::default());
+                    loaded.load().await.unwrap();
+                    loaded
+                })
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("parse_stats", &id), &table, |b, table| {
+            b.iter(|| table.snapshot().unwrap().add_actions_table(true).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, load);
+criterion_main!(benches);
@@ -0,0 +1,36 @@
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use deltalake_core::logstore::get_actions;
+
+fn commit_with_adds(num_adds: usize) -> Bytes {
+    let lines = (0..num_adds)
+        .map(|i| {
+            format!(
+                r#"{{"add":{{"path":"part={p}/part-{i:05}-c6b9f8a1-7a57-4a8e-9d3b-1d6f1c2b3a4e-c000.snappy.parquet","partitionValues":{{"part":"{p}"}},"size":{size},"modificationTime":1700000000000,"dataChange":true,"stats":"{{\"numRecords\":{size},\"minValues\":{{\"id\":{i},\"name\":\"a\"}},\"maxValues\":{{\"id\":{max},\"name\":\"z\"}},\"nullCount\":{{\"id\":0,\"name\":0}}}}"}}}}"#,
+                p = i % 16,
+                size = 1024 + i,
+                max = i + 1000,
+            )
+        })
+        .collect::<Vec<_>>();
+    Bytes::from(lines.join("\n"))
+}
+
+fn parse_log(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_log");
+    for num_adds in [1_000, 10_000] {
+        let commit = commit_with_adds(num_adds);
+        group.throughput(Throughput::Bytes(commit.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("get_actions", num_adds),
+            &commit,
+            |b, commit| {
+                b.iter(|| futures::executor::block_on(get_actions(0, commit.clone())).unwrap())
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse_log);
+criterion_main!(benches);
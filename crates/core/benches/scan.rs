@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use deltalake_core::bench_utils::{create_data_table, BenchTableConfig};
+use deltalake_core::datafusion::prelude::SessionContext;
+use tokio::runtime::Runtime;
+
+const QUERIES: [(&str, &str); 3] = [
+    ("full_scan", "SELECT sum(c0) FROM bench"),
+    ("file_pruning", "SELECT sum(c0) FROM bench WHERE id < 1000"),
+    (
+        "partition_pruning",
+        "SELECT sum(c0) FROM bench WHERE part = 'p1'",
+    ),
+];
+
+fn scan(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let config = BenchTableConfig {
+        num_commits: 10,
+        files_per_commit: 10,
+        num_partitions: 4,
+        rows_per_file: 10_000,
+        ..Default::default()
+    };
+    let table = runtime.block_on(create_data_table(&config)).unwrap();
+    let ctx = SessionContext::new();
+    ctx.register_table("bench", Arc::new(table)).unwrap();
+
+    let mut group = c.benchmark_group("scan");
+    group.sample_size(10);
+    for (name, sql) in QUERIES {
+        group.bench_with_input(BenchmarkId::new(name, config.num_files()), sql, |b, sql| {
+            b.iter(|| {
+                runtime.block_on(async { ctx.sql(sql).await.unwrap().collect().await.unwrap() })
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, scan);
+criterion_main!(benches);
@@ -0,0 +1,279 @@
+//! Generators of synthetic tables for benchmarks
+//!
+//! Performance regressions in log replay, statistics parsing and writes only become visible on
+//! tables of a realistic shape. The generators in this module create tables with a configurable
+//! number of commits, files, partitions and statistics columns, either as log only tables whose
+//! add actions reference files that do not exist, which is enough to benchmark loading a table,
+//! or as tables with actual data files.
+//!
+//! The benchmarks in `crates/core/benches` are built on these generators.
+//!
+//! # Example
+//! ```rust ignore
+//! let config = BenchTableConfig {
+//!     num_commits: 100,
+//!     files_per_commit: 100,
+//!     ..Default::default()
+//! };
+//! let table = create_log_table(&config).await?;
+//! assert_eq!(table.get_files_count().unwrap(), 10_000);
+//! ````
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Int64Array, StringArray};
+use arrow::datatypes::Schema as ArrowSchema;
+use arrow::record_batch::RecordBatch;
+use serde_json::{Map, Value};
+
+use crate::errors::DeltaResult;
+use crate::kernel::{Action, Add, DataType, PrimitiveType, StructField, StructType};
+use crate::operations::transaction::{CommitBuilder, TableReference};
+use crate::protocol::{DeltaOperation, SaveMode};
+use crate::writer::{DeltaWriter, RecordBatchWriter};
+use crate::{DeltaOps, DeltaTable};
+
+/// Name of the partition column of partitioned benchmark tables
+pub const PARTITION_COLUMN: &str = "part";
+
+/// Shape of a generated benchmark table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchTableConfig {
+    /// Location of the table, an in-memory table is created if `None`
+    pub location: Option<String>,
+    /// Number of commits adding files, after the commit creating the table
+    pub num_commits: usize,
+    /// Number of files added by every commit
+    pub files_per_commit: usize,
+    /// Number of distinct partitions, the table is not partitioned if 0
+    pub num_partitions: usize,
+    /// Number of `long` columns besides `id`, all of which have statistics
+    pub num_stats_columns: usize,
+    /// Number of rows of every file
+    pub rows_per_file: usize,
+}
+
+impl Default for BenchTableConfig {
+    fn default() -> Self {
+        Self {
+            location: None,
+            num_commits: 10,
+            files_per_commit: 10,
+            num_partitions: 0,
+            num_stats_columns: 4,
+            rows_per_file: 100,
+        }
+    }
+}
+
+impl BenchTableConfig {
+    /// Total number of files of the generated table
+    pub fn num_files(&self) -> usize {
+        self.num_commits * self.files_per_commit
+    }
+
+    fn partition(&self, file_index: usize) -> Option<String> {
+        (self.num_partitions > 0).then(|| format!("p{}", file_index % self.num_partitions))
+    }
+
+    fn first_id(&self, file_index: usize) -> i64 {
+        (file_index * self.rows_per_file) as i64
+    }
+}
+
+/// Schema of benchmark tables: `id`, the partition column if partitioned, and the statistics
+/// columns `c0`, `c1`, ...
+pub fn bench_schema(config: &BenchTableConfig) -> StructType {
+    let mut fields = vec![StructField::new(
+        "id",
+        DataType::Primitive(PrimitiveType::Long),
+        false,
+    )];
+    if config.num_partitions > 0 {
+        fields.push(StructField::new(
+            PARTITION_COLUMN,
+            DataType::Primitive(PrimitiveType::String),
+            true,
+        ));
+    }
+    fields.extend((0..config.num_stats_columns).map(|column| {
+        StructField::new(
+            format!("c{column}"),
+            DataType::Primitive(PrimitiveType::Long),
+            true,
+        )
+    }));
+    StructType::new(fields)
+}
+
+/// Add action of the `file`th file of the `commit`th commit of a log only table
+pub fn bench_add(config: &BenchTableConfig, commit: usize, file: usize) -> Add {
+    let file_index = commit * config.files_per_commit + file;
+    let first = config.first_id(file_index);
+    let last = first + config.rows_per_file.max(1) as i64 - 1;
+
+    let mut min_values = Map::new();
+    let mut max_values = Map::new();
+    let mut null_count = Map::new();
+    min_values.insert("id".to_string(), first.into());
+    max_values.insert("id".to_string(), last.into());
+    null_count.insert("id".to_string(), 0.into());
+    for column in 0..config.num_stats_columns {
+        let name = format!("c{column}");
+        min_values.insert(name.clone(), (first + column as i64).into());
+        max_values.insert(name.clone(), (last + column as i64).into());
+        null_count.insert(name, 0.into());
+    }
+    let stats = Value::from_iter([
+        ("numRecords".to_string(), Value::from(config.rows_per_file)),
+        ("minValues".to_string(), Value::Object(min_values)),
+        ("maxValues".to_string(), Value::Object(max_values)),
+        ("nullCount".to_string(), Value::Object(null_count)),
+    ]);
+
+    let partition = config.partition(file_index);
+    let file_name = format!("part-{commit:05}-{file:05}-bench.snappy.parquet");
+    Add {
+        path: match &partition {
+            Some(partition) => format!("{PARTITION_COLUMN}={partition}/{file_name}"),
+            None => file_name,
+        },
+        partition_values: partition
+            .map(|partition| HashMap::from([(PARTITION_COLUMN.to_string(), Some(partition))]))
+            .unwrap_or_default(),
+        size: (config.rows_per_file * (1 + config.num_stats_columns) * 8) as i64,
+        modification_time: 1_700_000_000_000 + file_index as i64,
+        data_change: true,
+        stats: Some(stats.to_string()),
+        tags: None,
+        deletion_vector: None,
+        base_row_id: None,
+        default_row_commit_version: None,
+        clustering_provider: None,
+        stats_parsed: None,
+    }
+}
+
+/// Rows of the `file_index`th file of a benchmark table
+pub fn bench_record_batch(config: &BenchTableConfig, file_index: usize) -> RecordBatch {
+    let schema = ArrowSchema::try_from(&bench_schema(config)).expect("valid benchmark schema");
+    let first = config.first_id(file_index);
+    let ids = (0..config.rows_per_file as i64).map(|row| first + row);
+
+    let mut columns: Vec<ArrayRef> = vec![Arc::new(ids.clone().collect::<Int64Array>())];
+    if let Some(partition) = config.partition(file_index) {
+        columns.push(Arc::new(StringArray::from(vec![
+            partition;
+            config.rows_per_file
+        ])));
+    }
+    for column in 0..config.num_stats_columns {
+        columns.push(Arc::new(
+            ids.clone()
+                .map(|id| id + column as i64)
+                .collect::<Int64Array>(),
+        ));
+    }
+    RecordBatch::try_new(Arc::new(schema), columns).expect("valid benchmark batch")
+}
+
+async fn create_empty_table(config: &BenchTableConfig) -> DeltaResult<DeltaTable> {
+    let ops = match &config.location {
+        Some(location) => DeltaOps::try_from_uri(location).await?,
+        None => DeltaOps::new_in_memory(),
+    };
+    let mut builder = ops
+        .create()
+        .with_columns(bench_schema(config).fields().clone());
+    if config.num_partitions > 0 {
+        builder = builder.with_partition_columns([PARTITION_COLUMN]);
+    }
+    builder.await
+}
+
+async fn commit_adds(table: &mut DeltaTable, adds: Vec<Add>) -> DeltaResult<()> {
+    let partition_by = table.metadata()?.partition_columns.clone();
+    let operation = DeltaOperation::Write {
+        mode: SaveMode::Append,
+        partition_by: (!partition_by.is_empty()).then_some(partition_by),
+        predicate: None,
+    };
+    CommitBuilder::default()
+        .with_actions(adds.into_iter().map(Action::Add).collect())
+        .build(
+            Some(table.snapshot()? as &dyn TableReference),
+            table.log_store(),
+            operation,
+        )?
+        .await?;
+    table.update().await
+}
+
+/// Create a table whose add actions reference files that do not exist. Such tables can be
+/// loaded, but not scanned
+pub async fn create_log_table(config: &BenchTableConfig) -> DeltaResult<DeltaTable> {
+    let mut table = create_empty_table(config).await?;
+    for commit in 0..config.num_commits {
+        let adds = (0..config.files_per_commit)
+            .map(|file| bench_add(config, commit, file))
+            .collect();
+        commit_adds(&mut table, adds).await?;
+    }
+    Ok(table)
+}
+
+/// Create a table with actual data files
+pub async fn create_data_table(config: &BenchTableConfig) -> DeltaResult<DeltaTable> {
+    let mut table = create_empty_table(config).await?;
+    let mut writer = RecordBatchWriter::for_table(&table)?;
+    for commit in 0..config.num_commits {
+        let mut adds = Vec::with_capacity(config.files_per_commit);
+        for file in 0..config.files_per_commit {
+            let file_index = commit * config.files_per_commit + file;
+            writer.write(bench_record_batch(config, file_index)).await?;
+            adds.extend(writer.flush().await?);
+        }
+        commit_adds(&mut table, adds).await?;
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_create_log_table() {
+        let config = BenchTableConfig {
+            num_commits: 3,
+            files_per_commit: 4,
+            num_partitions: 2,
+            ..Default::default()
+        };
+        let table = create_log_table(&config).await.unwrap();
+        assert_eq!(table.version(), 3);
+        assert_eq!(table.get_files_count().unwrap(), config.num_files());
+        let stats = table.snapshot().unwrap().add_actions_table(true).unwrap();
+        assert_eq!(stats.num_rows(), 12);
+        assert!(stats.column_by_name("min.c3").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_create_data_table() {
+        let config = BenchTableConfig {
+            num_commits: 2,
+            files_per_commit: 2,
+            num_partitions: 2,
+            num_stats_columns: 1,
+            rows_per_file: 10,
+            ..Default::default()
+        };
+        let table = create_data_table(&config).await.unwrap();
+        assert_eq!(table.version(), 2);
+        assert_eq!(table.get_files_count().unwrap(), 4);
+        let batch = bench_record_batch(&config, 3);
+        assert_eq!(batch.num_rows(), 10);
+        assert_eq!(batch.num_columns(), 3);
+    }
+}
@@ -0,0 +1,257 @@
+//! Caching of table locations resolved through a [`DataCatalog`].
+//!
+//! Resolving a table location requires a round trip to the catalog service. Services resolving
+//! many tables in short succession can wrap their catalog in a [`CachingDataCatalog`] to reuse
+//! resolved locations for a configurable time to live.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::{DataCatalog, DataCatalogError};
+use crate::DeltaTable;
+
+const DEFAULT_TTL: Duration = Duration::from_secs(300);
+
+type CacheKey = (Option<String>, String, String);
+
+/// A [`DataCatalog`] caching the resolved storage locations of the wrapped catalog.
+///
+/// Only successful resolutions are cached, errors are always returned from the wrapped catalog.
+#[derive(Debug)]
+pub struct CachingDataCatalog<C: DataCatalog> {
+    inner: C,
+    ttl: Duration,
+    entries: Mutex<HashMap<CacheKey, (String, Instant)>>,
+}
+
+impl<C: DataCatalog> CachingDataCatalog<C> {
+    /// Wrap `inner`, caching locations for five minutes
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            ttl: DEFAULT_TTL,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Set the duration resolved locations are cached for
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// The wrapped catalog
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Remove the cached location of a single table
+    pub fn invalidate(&self, catalog_id: Option<&str>, database_name: &str, table_name: &str) {
+        let key = (
+            catalog_id.map(String::from),
+            database_name.to_string(),
+            table_name.to_string(),
+        );
+        self.entries.lock().unwrap().remove(&key);
+    }
+
+    /// Remove the cached locations of all tables in a database
+    pub fn invalidate_database(&self, catalog_id: Option<&str>, database_name: &str) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|(catalog, database, _), _| {
+                catalog.as_deref() != catalog_id || database != database_name
+            });
+    }
+
+    /// Remove all cached locations
+    pub fn invalidate_all(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    fn cached(&self, key: &CacheKey) -> Option<String> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some((location, resolved_at)) if resolved_at.elapsed() < self.ttl => {
+                Some(location.clone())
+            }
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<C: DataCatalog> DataCatalog for CachingDataCatalog<C> {
+    async fn get_table_storage_location(
+        &self,
+        catalog_id: Option<String>,
+        database_name: &str,
+        table_name: &str,
+    ) -> Result<String, DataCatalogError> {
+        let key = (
+            catalog_id.clone(),
+            database_name.to_string(),
+            table_name.to_string(),
+        );
+        if let Some(location) = self.cached(&key) {
+            return Ok(location);
+        }
+        let location = self
+            .inner
+            .get_table_storage_location(catalog_id, database_name, table_name)
+            .await?;
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(key, (location.clone(), Instant::now()));
+        Ok(location)
+    }
+
+    async fn list_catalogs(&self) -> Result<Vec<String>, DataCatalogError> {
+        self.inner.list_catalogs().await
+    }
+
+    async fn list_databases(
+        &self,
+        catalog_id: Option<String>,
+    ) -> Result<Vec<String>, DataCatalogError> {
+        self.inner.list_databases(catalog_id).await
+    }
+
+    async fn list_tables(
+        &self,
+        catalog_id: Option<String>,
+        database_name: &str,
+    ) -> Result<Vec<String>, DataCatalogError> {
+        self.inner.list_tables(catalog_id, database_name).await
+    }
+
+    /// Update the entry in the wrapped catalog and drop the cached location of the table
+    async fn sync_table_metadata(
+        &self,
+        catalog_id: Option<String>,
+        database_name: &str,
+        table_name: &str,
+        table: &DeltaTable,
+    ) -> Result<(), DataCatalogError> {
+        self.invalidate(catalog_id.as_deref(), database_name, table_name);
+        self.inner
+            .sync_table_metadata(catalog_id, database_name, table_name, table)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct CountingCatalog {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl DataCatalog for CountingCatalog {
+        async fn get_table_storage_location(
+            &self,
+            _catalog_id: Option<String>,
+            database_name: &str,
+            table_name: &str,
+        ) -> Result<String, DataCatalogError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if table_name == "missing" {
+                return Err(DataCatalogError::InvalidDataCatalog {
+                    data_catalog: "counting".to_string(),
+                });
+            }
+            Ok(format!("s3://bucket/{database_name}/{table_name}"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_caching_data_catalog() {
+        let catalog = CachingDataCatalog::new(CountingCatalog::default());
+        let calls = || catalog.inner().calls.load(Ordering::SeqCst);
+
+        for _ in 0..3 {
+            let location = catalog
+                .get_table_storage_location(None, "db", "events")
+                .await
+                .unwrap();
+            assert_eq!(location, "s3://bucket/db/events");
+        }
+        assert_eq!(calls(), 1);
+
+        catalog
+            .get_table_storage_location(None, "db", "users")
+            .await
+            .unwrap();
+        assert_eq!(calls(), 2);
+
+        catalog.invalidate(None, "db", "events");
+        catalog
+            .get_table_storage_location(None, "db", "events")
+            .await
+            .unwrap();
+        assert_eq!(calls(), 3);
+
+        catalog.invalidate_database(None, "db");
+        catalog
+            .get_table_storage_location(None, "db", "users")
+            .await
+            .unwrap();
+        assert_eq!(calls(), 4);
+
+        // errors are never cached
+        for _ in 0..2 {
+            assert!(catalog
+                .get_table_storage_location(None, "db", "missing")
+                .await
+                .is_err());
+        }
+        assert_eq!(calls(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_sync_invalidates_cached_location() {
+        let catalog = CachingDataCatalog::new(CountingCatalog::default());
+        let table = crate::open_table("../test/tests/data/simple_table")
+            .await
+            .unwrap();
+        catalog
+            .get_table_storage_location(None, "db", "events")
+            .await
+            .unwrap();
+
+        // the counting catalog does not support syncing, the entry is dropped nonetheless
+        assert!(table
+            .sync_to_catalog(&catalog, None, "db", "events")
+            .await
+            .is_err());
+        catalog
+            .get_table_storage_location(None, "db", "events")
+            .await
+            .unwrap();
+        assert_eq!(catalog.inner().calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_caching_data_catalog_ttl() {
+        let catalog = CachingDataCatalog::new(CountingCatalog::default()).with_ttl(Duration::ZERO);
+        for _ in 0..2 {
+            catalog
+                .get_table_storage_location(Some("main".to_string()), "db", "events")
+                .await
+                .unwrap();
+        }
+        assert_eq!(catalog.inner().calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,424 @@
+//! Expose Delta tables as external tables in Snowflake and BigQuery.
+//!
+//! Snowflake reads the Delta log natively, but caches the list of data files of an external
+//! table until it is refreshed. BigQuery BigLake tables read the symlink format manifests
+//! generated by [`GenerateBuilder`](crate::operations::generate::GenerateBuilder). In both
+//! cases the external table has to be refreshed after every commit, which can be automated by
+//! registering an [`ExternalTableObserver`] with the [`CommitProperties`](crate::operations::transaction::CommitProperties)
+//! of each write.
+//!
+//! The helpers only generate the DDL statements. Statements are executed through a
+//! [`SqlExecutor`], which wraps the client of the respective warehouse.
+
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+use object_store::path::Path;
+
+use crate::errors::DeltaResult;
+use crate::kernel::{DataType, Metadata, PrimitiveType, StructField};
+use crate::logstore::LogStoreRef;
+use crate::operations::generate::{write_manifests, MANIFEST_DIR};
+use crate::operations::transaction::{CommitEvent, CommitObserver};
+use crate::table::state::DeltaTableState;
+use crate::DeltaTable;
+
+/// Executes SQL statements against an external warehouse
+#[async_trait::async_trait]
+pub trait SqlExecutor: Debug + Send + Sync {
+    /// Execute a single statement
+    async fn execute(&self, statement: &str) -> DeltaResult<()>;
+}
+
+/// The warehouse and name of an external table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExternalTableTarget {
+    /// A Snowflake external table with `TABLE_FORMAT = DELTA`
+    Snowflake {
+        /// Fully qualified name of the external table
+        table_name: String,
+        /// Stage location of the table root, e.g. `@my_stage/events/`
+        stage: String,
+    },
+    /// A manifest based BigQuery BigLake table
+    BigQuery {
+        /// Fully qualified name of the external table, e.g. `project.dataset.events`
+        table_name: String,
+        /// Cloud resource connection used to access the table, e.g. `project.us.biglake`
+        connection: String,
+    },
+}
+
+impl ExternalTableTarget {
+    /// A Snowflake external table reading the table root from `stage`
+    pub fn snowflake(table_name: impl Into<String>, stage: impl Into<String>) -> Self {
+        Self::Snowflake {
+            table_name: table_name.into(),
+            stage: stage.into(),
+        }
+    }
+
+    /// A BigQuery BigLake table accessing the table through `connection`
+    pub fn bigquery(table_name: impl Into<String>, connection: impl Into<String>) -> Self {
+        Self::BigQuery {
+            table_name: table_name.into(),
+            connection: connection.into(),
+        }
+    }
+
+    /// Whether the external table reads symlink format manifests
+    pub fn uses_manifest(&self) -> bool {
+        matches!(self, Self::BigQuery { .. })
+    }
+
+    /// The statement creating (or replacing) the external table for a table at `table_uri`
+    pub fn create_statement(&self, table_uri: &str, metadata: &Metadata) -> DeltaResult<String> {
+        let schema = metadata.schema()?;
+        let (partition_fields, fields): (Vec<&StructField>, Vec<&StructField>) = schema
+            .fields()
+            .iter()
+            .partition(|field| metadata.partition_columns.contains(field.name()));
+
+        let statement = match self {
+            Self::Snowflake { table_name, stage } => {
+                let columns = fields
+                    .iter()
+                    .map(|field| {
+                        let data_type = snowflake_type(field.data_type());
+                        format!(
+                            "    {} {data_type} AS (VALUE:{}::{data_type})",
+                            quote(field.name(), '"'),
+                            quote(field.name(), '"')
+                        )
+                    })
+                    .chain(partition_fields.iter().map(|field| {
+                        let data_type = snowflake_type(field.data_type());
+                        format!(
+                            "    {} {data_type} AS (PARSE_JSON(METADATA$EXTERNAL_TABLE_PARTITION):{}::{data_type})",
+                            quote(field.name(), '"'),
+                            field.name().to_uppercase()
+                        )
+                    }))
+                    .collect::<Vec<_>>()
+                    .join(",\n");
+                let partition_by = if partition_fields.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        "PARTITION BY ({})\n",
+                        partition_fields
+                            .iter()
+                            .map(|field| quote(field.name(), '"'))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                };
+                format!(
+                    "CREATE OR REPLACE EXTERNAL TABLE {table_name} (\n{columns}\n)\n{partition_by}\
+                     LOCATION = {stage}\n\
+                     FILE_FORMAT = (TYPE = PARQUET)\n\
+                     TABLE_FORMAT = DELTA\n\
+                     AUTO_REFRESH = FALSE\n\
+                     REFRESH_ON_CREATE = FALSE"
+                )
+            }
+            Self::BigQuery {
+                table_name,
+                connection,
+            } => {
+                let column_list = |fields: &[&StructField]| {
+                    fields
+                        .iter()
+                        .map(|field| {
+                            format!(
+                                "    {} {}",
+                                quote(field.name(), '`'),
+                                bigquery_type(field.data_type())
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join(",\n")
+                };
+                let manifest_root = format!("{}/{MANIFEST_DIR}", table_uri.trim_end_matches('/'));
+                let mut options = vec![
+                    "format = 'PARQUET'".to_string(),
+                    "enable_list_inference = true".to_string(),
+                    "file_set_spec_type = 'NEW_LINE_DELIMITED_MANIFEST'".to_string(),
+                ];
+                let partition_columns = if partition_fields.is_empty() {
+                    options.push(format!("uris = ['{manifest_root}/manifest']"));
+                    String::new()
+                } else {
+                    options.push(format!("uris = ['{manifest_root}/*/manifest']"));
+                    options.push(format!("hive_partition_uri_prefix = '{manifest_root}'"));
+                    format!(
+                        "WITH PARTITION COLUMNS (\n{}\n)\n",
+                        column_list(&partition_fields)
+                    )
+                };
+                format!(
+                    "CREATE OR REPLACE EXTERNAL TABLE {} (\n{}\n)\n{partition_columns}\
+                     WITH CONNECTION {}\n\
+                     OPTIONS (\n    {}\n)",
+                    quote(table_name, '`'),
+                    column_list(&fields),
+                    quote(connection, '`'),
+                    options.join(",\n    ")
+                )
+            }
+        };
+        Ok(statement)
+    }
+
+    /// Statements refreshing the external table after a commit
+    pub fn refresh_statements(&self) -> Vec<String> {
+        match self {
+            Self::Snowflake { table_name, .. } => {
+                vec![format!("ALTER EXTERNAL TABLE {table_name} REFRESH")]
+            }
+            // BigQuery reads the manifests on every query
+            Self::BigQuery { .. } => Vec::new(),
+        }
+    }
+}
+
+fn quote(identifier: &str, quote: char) -> String {
+    let escaped = identifier.replace(quote, &format!("{quote}{quote}"));
+    format!("{quote}{escaped}{quote}")
+}
+
+fn snowflake_type(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Primitive(primitive) => match primitive {
+            PrimitiveType::String => "VARCHAR".to_string(),
+            PrimitiveType::Long => "BIGINT".to_string(),
+            PrimitiveType::Integer => "INT".to_string(),
+            PrimitiveType::Short => "SMALLINT".to_string(),
+            PrimitiveType::Byte => "TINYINT".to_string(),
+            PrimitiveType::Float => "FLOAT".to_string(),
+            PrimitiveType::Double => "DOUBLE".to_string(),
+            PrimitiveType::Boolean => "BOOLEAN".to_string(),
+            PrimitiveType::Binary => "BINARY".to_string(),
+            PrimitiveType::Date => "DATE".to_string(),
+            PrimitiveType::Timestamp => "TIMESTAMP_LTZ".to_string(),
+            PrimitiveType::TimestampNtz => "TIMESTAMP_NTZ".to_string(),
+            PrimitiveType::Decimal(precision, scale) => format!("NUMBER({precision},{scale})"),
+        },
+        // semi-structured values are not typed any further in snowflake
+        DataType::Array(_) => "ARRAY".to_string(),
+        DataType::Struct(_) | DataType::Map(_) => "OBJECT".to_string(),
+    }
+}
+
+fn bigquery_type(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Primitive(primitive) => match primitive {
+            PrimitiveType::String => "STRING".to_string(),
+            PrimitiveType::Long
+            | PrimitiveType::Integer
+            | PrimitiveType::Short
+            | PrimitiveType::Byte => "INT64".to_string(),
+            PrimitiveType::Float | PrimitiveType::Double => "FLOAT64".to_string(),
+            PrimitiveType::Boolean => "BOOL".to_string(),
+            PrimitiveType::Binary => "BYTES".to_string(),
+            PrimitiveType::Date => "DATE".to_string(),
+            PrimitiveType::Timestamp => "TIMESTAMP".to_string(),
+            PrimitiveType::TimestampNtz => "DATETIME".to_string(),
+            PrimitiveType::Decimal(precision, scale) if *precision <= 38 && *scale <= 9 => {
+                format!("NUMERIC({precision}, {scale})")
+            }
+            PrimitiveType::Decimal(precision, scale) => {
+                format!("BIGNUMERIC({precision}, {scale})")
+            }
+        },
+        DataType::Array(array) => format!("ARRAY<{}>", bigquery_type(array.element_type())),
+        DataType::Struct(fields) => format!(
+            "STRUCT<{}>",
+            fields
+                .fields()
+                .iter()
+                .map(|field| format!(
+                    "{} {}",
+                    quote(field.name(), '`'),
+                    bigquery_type(field.data_type())
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        // parquet maps are read as repeated key value pairs
+        DataType::Map(map) => format!(
+            "ARRAY<STRUCT<key {}, value {}>>",
+            bigquery_type(map.key_type()),
+            bigquery_type(map.value_type())
+        ),
+    }
+}
+
+/// Register `table` as external table in the warehouse described by `target`.
+///
+/// Manifests are generated if the target reads them. The create statement is executed if an
+/// `executor` is given, and returned in any case.
+pub async fn register_external_table(
+    table: &DeltaTable,
+    target: &ExternalTableTarget,
+    executor: Option<&dyn SqlExecutor>,
+) -> DeltaResult<String> {
+    let snapshot = table.snapshot()?;
+    if target.uses_manifest() {
+        write_manifests(&table.log_store(), snapshot).await?;
+    }
+    let statement = target.create_statement(&table.table_uri(), snapshot.metadata())?;
+    if let Some(executor) = executor {
+        executor.execute(&statement).await?;
+    }
+    Ok(statement)
+}
+
+/// Keeps an external table in sync with the Delta table after every commit.
+///
+/// After each commit the manifests are regenerated if the target reads them, and the refresh
+/// statements are executed. If the schema or partitioning of the table changed, the external
+/// table is recreated instead.
+#[derive(Debug)]
+pub struct ExternalTableObserver {
+    log_store: LogStoreRef,
+    target: ExternalTableTarget,
+    executor: Option<Arc<dyn SqlExecutor>>,
+    /// Schema and partition columns last seen, to detect schema changes
+    last_layout: Mutex<Option<(String, Vec<String>)>>,
+}
+
+impl ExternalTableObserver {
+    /// Create a new observer for the table stored in `log_store`
+    pub fn new(log_store: LogStoreRef, target: ExternalTableTarget) -> Self {
+        Self {
+            log_store,
+            target,
+            executor: None,
+            last_layout: Mutex::new(None),
+        }
+    }
+
+    /// Execute refresh statements with `executor`, otherwise only manifests are updated
+    pub fn with_executor(mut self, executor: Arc<dyn SqlExecutor>) -> Self {
+        self.executor = Some(executor);
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl CommitObserver for ExternalTableObserver {
+    async fn on_commit(&self, event: &CommitEvent) -> DeltaResult<()> {
+        let snapshot = DeltaTableState::try_new(
+            &Path::default(),
+            self.log_store.log_object_store(),
+            Default::default(),
+            Some(event.version),
+        )
+        .await?;
+        if self.target.uses_manifest() {
+            write_manifests(&self.log_store, &snapshot).await?;
+        }
+
+        let metadata = snapshot.metadata();
+        let layout = (
+            metadata.schema_string.clone(),
+            metadata.partition_columns.clone(),
+        );
+        let layout_changed = {
+            let mut last_layout = self.last_layout.lock().unwrap();
+            let changed = last_layout.as_ref().is_some_and(|last| last != &layout);
+            *last_layout = Some(layout);
+            changed
+        };
+
+        if let Some(executor) = &self.executor {
+            if layout_changed {
+                let statement = self
+                    .target
+                    .create_statement(&self.log_store.root_uri(), metadata)?;
+                executor.execute(&statement).await?;
+            } else {
+                for statement in self.target.refresh_statements() {
+                    executor.execute(&statement).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::{ArrayType, StructType};
+
+    fn metadata() -> Metadata {
+        let schema = StructType::new(vec![
+            StructField::new("id", DataType::Primitive(PrimitiveType::Long), false),
+            StructField::new(
+                "tags",
+                DataType::Array(Box::new(ArrayType::new(
+                    DataType::Primitive(PrimitiveType::String),
+                    true,
+                ))),
+                true,
+            ),
+            StructField::new("date", DataType::Primitive(PrimitiveType::Date), true),
+        ]);
+        Metadata::builder(schema)
+            .with_partition_columns(["date"])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_snowflake_statements() {
+        let target = ExternalTableTarget::snowflake("db.public.events", "@lake/events/");
+        let statement = target
+            .create_statement("s3://bucket/events", &metadata())
+            .unwrap();
+        assert_eq!(
+            statement,
+            "CREATE OR REPLACE EXTERNAL TABLE db.public.events (\n    \
+             \"id\" BIGINT AS (VALUE:\"id\"::BIGINT),\n    \
+             \"tags\" ARRAY AS (VALUE:\"tags\"::ARRAY),\n    \
+             \"date\" DATE AS (PARSE_JSON(METADATA$EXTERNAL_TABLE_PARTITION):DATE::DATE)\n)\n\
+             PARTITION BY (\"date\")\n\
+             LOCATION = @lake/events/\n\
+             FILE_FORMAT = (TYPE = PARQUET)\n\
+             TABLE_FORMAT = DELTA\n\
+             AUTO_REFRESH = FALSE\n\
+             REFRESH_ON_CREATE = FALSE"
+        );
+        assert_eq!(
+            target.refresh_statements(),
+            vec!["ALTER EXTERNAL TABLE db.public.events REFRESH".to_string()]
+        );
+        assert!(!target.uses_manifest());
+    }
+
+    #[test]
+    fn test_bigquery_statements() {
+        let target = ExternalTableTarget::bigquery("project.dataset.events", "project.us.lake");
+        let statement = target
+            .create_statement("gs://bucket/events/", &metadata())
+            .unwrap();
+        assert_eq!(
+            statement,
+            "CREATE OR REPLACE EXTERNAL TABLE `project.dataset.events` (\n    \
+             `id` INT64,\n    \
+             `tags` ARRAY<STRING>\n)\n\
+             WITH PARTITION COLUMNS (\n    `date` DATE\n)\n\
+             WITH CONNECTION `project.us.lake`\n\
+             OPTIONS (\n    \
+             format = 'PARQUET',\n    \
+             enable_list_inference = true,\n    \
+             file_set_spec_type = 'NEW_LINE_DELIMITED_MANIFEST',\n    \
+             uris = ['gs://bucket/events/_symlink_format_manifest/*/manifest'],\n    \
+             hive_partition_uri_prefix = 'gs://bucket/events/_symlink_format_manifest'\n)"
+        );
+        assert!(target.refresh_statements().is_empty());
+        assert!(target.uses_manifest());
+    }
+}
@@ -0,0 +1,1037 @@
+//! Hive Metastore catalog.
+//!
+//! On premise deployments commonly keep their tables in a Hive Metastore rather than a managed
+//! catalog. [`HmsCatalog`] talks to the metastore directly over its Thrift interface, using the
+//! binary protocol on a buffered socket, which is the default configuration of the metastore.
+//! Besides resolving the location of registered tables, it registers Delta tables so they can be
+//! queried from Spark, Hive and Trino.
+//!
+//! This module is gated behind the "hms" feature.
+//!
+//! # Example
+//! ```rust ignore
+//! let catalog = HmsCatalog::new("metastore.internal:9083");
+//! catalog.register_table("analytics", "events", &table).await?;
+//! let location = catalog
+//!     .get_table_storage_location(None, "analytics", "events")
+//!     .await?;
+//! ````
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::time::Duration;
+
+use tokio::io::{AsyncWriteExt, BufStream};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+
+use self::thrift::{encode_message, read_message, MessageType, Value};
+use super::{DataCatalog, DataCatalogError};
+use crate::kernel::{DataType, MetadataValue, PrimitiveType, StructField};
+use crate::DeltaTable;
+
+mod thrift;
+
+/// Name of the default catalog of the metastore
+pub const DEFAULT_CATALOG: &str = "hive";
+
+/// Default timeout of a single call to the metastore
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Number of tables fetched at once when listing tables
+const TABLE_BATCH_SIZE: usize = 100;
+
+// Placeholder suffix created by Spark in the location of data source tables
+const PLACEHOLDER_SUFFIX: &str = "-__PLACEHOLDER__";
+
+// Storage format Spark and Hive expect for Delta tables registered in the metastore
+const PARQUET_INPUT_FORMAT: &str = "org.apache.hadoop.hive.ql.io.parquet.MapredParquetInputFormat";
+const PARQUET_OUTPUT_FORMAT: &str =
+    "org.apache.hadoop.hive.ql.io.parquet.MapredParquetOutputFormat";
+const PARQUET_SERDE: &str = "org.apache.hadoop.hive.ql.io.parquet.serde.ParquetHiveSerDe";
+
+/// Column metadata key Spark uses for column comments
+const COMMENT_METADATA_KEY: &str = "comment";
+
+/// Errors of the Hive Metastore catalog
+#[derive(thiserror::Error, Debug)]
+pub enum HmsError {
+    /// The connection to the metastore failed
+    #[error("Failed to communicate with the Hive Metastore: {source}")]
+    Io {
+        /// The underlying io error
+        #[from]
+        source: std::io::Error,
+    },
+
+    /// The call did not complete within the timeout
+    #[error("Hive Metastore call '{method}' timed out after {timeout:?}")]
+    Timeout {
+        /// Name of the call
+        method: &'static str,
+        /// Timeout of the call
+        timeout: Duration,
+    },
+
+    /// The metastore sent a response which could not be decoded
+    #[error("Invalid response from the Hive Metastore: {0}")]
+    Protocol(String),
+
+    /// The metastore raised an exception
+    #[error("Hive Metastore call '{method}' failed with {exception}: {message}")]
+    Metastore {
+        /// Name of the call
+        method: &'static str,
+        /// Name of the exception, such as `NoSuchObjectException`
+        exception: String,
+        /// Message of the exception
+        message: String,
+    },
+}
+
+impl HmsError {
+    /// Whether the metastore reported that the database or table does not exist
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, HmsError::Metastore { exception, .. }
+            if exception == "NoSuchObjectException" || exception == "UnknownDBException")
+    }
+}
+
+impl From<HmsError> for DataCatalogError {
+    fn from(err: HmsError) -> Self {
+        DataCatalogError::Generic {
+            catalog: "HMS",
+            source: Box::new(err),
+        }
+    }
+}
+
+/// A column of a table registered in the metastore
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HmsColumn {
+    /// Name of the column
+    pub name: String,
+    /// Hive type of the column, such as `array<bigint>`
+    pub data_type: String,
+    /// Comment of the column
+    pub comment: Option<String>,
+}
+
+/// A table registered in the metastore
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HmsTable {
+    /// Name of the database containing the table
+    pub database_name: String,
+    /// Name of the table
+    pub table_name: String,
+    /// Owner of the table
+    pub owner: Option<String>,
+    /// Type of the table, such as `EXTERNAL_TABLE`
+    pub table_type: Option<String>,
+    /// Storage location of the table
+    pub location: Option<String>,
+    /// Input format class of the table
+    pub input_format: Option<String>,
+    /// Output format class of the table
+    pub output_format: Option<String>,
+    /// Serialization library class of the table
+    pub serialization_library: Option<String>,
+    /// Parameters of the serialization library
+    pub serde_parameters: HashMap<String, String>,
+    /// Regular columns of the table
+    pub columns: Vec<HmsColumn>,
+    /// Partition columns of the table
+    pub partition_keys: Vec<HmsColumn>,
+    /// Table parameters
+    pub parameters: HashMap<String, String>,
+}
+
+impl HmsTable {
+    /// Whether the table is a Delta table, as registered by delta-rs or Spark
+    pub fn is_delta_table(&self) -> bool {
+        let is_delta = |key: &str| {
+            self.parameters
+                .get(key)
+                .map_or(false, |value| value.eq_ignore_ascii_case("delta"))
+        };
+        is_delta("table_type") || is_delta("spark.sql.sources.provider")
+    }
+
+    /// Storage location of the table. Spark registers data source tables with a placeholder
+    /// location and keeps the actual location in the `path` serde parameter
+    pub fn storage_location(&self) -> Option<String> {
+        if let Some(path) = self.serde_parameters.get("path") {
+            return Some(path.clone());
+        }
+        self.location.as_ref().map(|location| {
+            location
+                .strip_suffix(PLACEHOLDER_SUFFIX)
+                .unwrap_or(location)
+                .to_string()
+        })
+    }
+
+    /// Describe `table` for registration as `database_name.table_name`.
+    ///
+    /// Columns are translated into Hive types and the table parameters Spark uses to detect
+    /// Delta tables are set, along with the table properties.
+    pub fn from_delta_table(
+        database_name: &str,
+        table_name: &str,
+        table: &DeltaTable,
+    ) -> crate::DeltaResult<Self> {
+        let metadata = table.metadata()?;
+        let schema = metadata.schema()?;
+        let columns = schema
+            .fields()
+            .iter()
+            .filter(|field| !metadata.partition_columns.contains(field.name()))
+            .map(hms_column)
+            .collect();
+        let partition_keys = metadata
+            .partition_columns
+            .iter()
+            .map(|name| schema.field_with_name(name).map(hms_column))
+            .collect::<Result<Vec<_>, _>>()?;
+        let location = table.table_uri().replace("s3a://", "s3://");
+
+        Ok(Self {
+            database_name: database_name.to_string(),
+            table_name: table_name.to_string(),
+            owner: None,
+            table_type: Some("EXTERNAL_TABLE".to_string()),
+            location: Some(location.clone()),
+            input_format: Some(PARQUET_INPUT_FORMAT.to_string()),
+            output_format: Some(PARQUET_OUTPUT_FORMAT.to_string()),
+            serialization_library: Some(PARQUET_SERDE.to_string()),
+            serde_parameters: HashMap::from([
+                ("serialization.format".to_string(), "1".to_string()),
+                ("path".to_string(), location),
+            ]),
+            columns,
+            partition_keys,
+            parameters: metadata
+                .configuration
+                .iter()
+                .filter_map(|(key, value)| Some((key.clone(), value.clone()?)))
+                // the parameters identifying the table as Delta win over table properties
+                .chain([
+                    ("EXTERNAL".to_string(), "TRUE".to_string()),
+                    ("table_type".to_string(), "DELTA".to_string()),
+                    (
+                        "spark.sql.sources.provider".to_string(),
+                        "delta".to_string(),
+                    ),
+                ])
+                .collect(),
+        })
+    }
+
+    fn to_thrift(&self) -> Value {
+        let string = |value: &Option<String>| value.as_ref().map(Value::string);
+        let columns = |columns: &[HmsColumn]| {
+            Value::struct_list(columns.iter().map(|column| {
+                Value::structure([
+                    (1, Some(Value::string(&column.name))),
+                    (2, Some(Value::string(&column.data_type))),
+                    (3, string(&column.comment)),
+                ])
+            }))
+        };
+        let serde_info = Value::structure([
+            (1, Some(Value::string(&self.table_name))),
+            (2, string(&self.serialization_library)),
+            (3, Some(Value::string_map(&self.serde_parameters))),
+        ]);
+        let storage_descriptor = Value::structure([
+            (1, Some(columns(&self.columns))),
+            (2, string(&self.location)),
+            (3, string(&self.input_format)),
+            (4, string(&self.output_format)),
+            (5, Some(Value::Bool(false))),
+            (6, Some(Value::I32(-1))),
+            (7, Some(serde_info)),
+            (8, Some(Value::string_list(Vec::<String>::new()))),
+            (9, Some(Value::struct_list([]))),
+            (10, Some(Value::string_map(&HashMap::new()))),
+        ]);
+        Value::structure([
+            (1, Some(Value::string(&self.table_name))),
+            (2, Some(Value::string(&self.database_name))),
+            (3, string(&self.owner)),
+            (4, Some(Value::I32(chrono::Utc::now().timestamp() as i32))),
+            (5, Some(Value::I32(0))),
+            (6, Some(Value::I32(0))),
+            (7, Some(storage_descriptor)),
+            (8, Some(columns(&self.partition_keys))),
+            (9, Some(Value::string_map(&self.parameters))),
+            (12, string(&self.table_type)),
+        ])
+    }
+
+    fn from_thrift(value: &Value) -> Result<Self, HmsError> {
+        let fields = value
+            .as_struct()
+            .ok_or_else(|| HmsError::Protocol("expected a table".to_string()))?;
+        let string = |fields: &std::collections::BTreeMap<i16, Value>, id| {
+            fields.get(&id).and_then(Value::as_str).map(String::from)
+        };
+        let storage_descriptor = fields.get(&7).and_then(Value::as_struct);
+        let serde_info = storage_descriptor
+            .and_then(|sd| sd.get(&7))
+            .and_then(Value::as_struct);
+
+        Ok(Self {
+            database_name: string(fields, 2).unwrap_or_default(),
+            table_name: string(fields, 1).unwrap_or_default(),
+            owner: string(fields, 3),
+            table_type: string(fields, 12),
+            location: storage_descriptor.and_then(|sd| string(sd, 2)),
+            input_format: storage_descriptor.and_then(|sd| string(sd, 3)),
+            output_format: storage_descriptor.and_then(|sd| string(sd, 4)),
+            serialization_library: serde_info.and_then(|info| string(info, 2)),
+            serde_parameters: serde_info
+                .and_then(|info| info.get(&3))
+                .map(string_map)
+                .unwrap_or_default(),
+            columns: storage_descriptor
+                .and_then(|sd| sd.get(&1))
+                .map(column_list)
+                .unwrap_or_default(),
+            partition_keys: fields.get(&8).map(column_list).unwrap_or_default(),
+            parameters: fields.get(&9).map(string_map).unwrap_or_default(),
+        })
+    }
+}
+
+fn string_map(value: &Value) -> HashMap<String, String> {
+    value
+        .as_map()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|(key, value)| Some((key.as_str()?.to_string(), value.as_str()?.to_string())))
+        .collect()
+}
+
+fn column_list(value: &Value) -> Vec<HmsColumn> {
+    value
+        .as_list()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(Value::as_struct)
+        .map(|fields| HmsColumn {
+            name: fields
+                .get(&1)
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            data_type: fields
+                .get(&2)
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            comment: fields.get(&3).and_then(Value::as_str).map(String::from),
+        })
+        .collect()
+}
+
+fn string_list(value: &Value) -> Vec<String> {
+    value
+        .as_list()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|value| value.as_str().map(String::from))
+        .collect()
+}
+
+/// Translate a Delta data type into the corresponding Hive type string
+pub fn hive_type(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Primitive(primitive) => match primitive {
+            PrimitiveType::String => "string".to_string(),
+            PrimitiveType::Long => "bigint".to_string(),
+            PrimitiveType::Integer => "int".to_string(),
+            PrimitiveType::Short => "smallint".to_string(),
+            PrimitiveType::Byte => "tinyint".to_string(),
+            PrimitiveType::Float => "float".to_string(),
+            PrimitiveType::Double => "double".to_string(),
+            PrimitiveType::Boolean => "boolean".to_string(),
+            PrimitiveType::Binary => "binary".to_string(),
+            PrimitiveType::Date => "date".to_string(),
+            // Hive has no notion of time zones, both are stored as microseconds
+            PrimitiveType::Timestamp | PrimitiveType::TimestampNtz => "timestamp".to_string(),
+            PrimitiveType::Decimal(precision, scale) => format!("decimal({precision},{scale})"),
+        },
+        DataType::Array(array) => format!("array<{}>", hive_type(array.element_type())),
+        DataType::Map(map) => format!(
+            "map<{},{}>",
+            hive_type(map.key_type()),
+            hive_type(map.value_type())
+        ),
+        DataType::Struct(fields) => format!(
+            "struct<{}>",
+            fields
+                .fields()
+                .iter()
+                .map(|field| format!("{}:{}", field.name(), hive_type(field.data_type())))
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+    }
+}
+
+fn hms_column(field: &StructField) -> HmsColumn {
+    let comment = match field.metadata().get(COMMENT_METADATA_KEY) {
+        Some(MetadataValue::String(comment)) => Some(comment.clone()),
+        _ => None,
+    };
+    HmsColumn {
+        name: field.name().clone(),
+        data_type: hive_type(field.data_type()),
+        comment,
+    }
+}
+
+/// Hive Metastore reached over Thrift
+pub struct HmsCatalog {
+    address: String,
+    timeout: Duration,
+    seq_id: AtomicI32,
+    connection: Mutex<Option<BufStream<TcpStream>>>,
+}
+
+impl HmsCatalog {
+    /// Create a new [`HmsCatalog`] for the metastore listening at `address`, e.g.
+    /// `metastore.internal:9083`. The connection is established by the first call
+    pub fn new(address: impl Into<String>) -> Self {
+        Self {
+            address: address.into(),
+            timeout: DEFAULT_TIMEOUT,
+            seq_id: AtomicI32::new(0),
+            connection: Mutex::new(None),
+        }
+    }
+
+    /// Set the timeout of a single call to the metastore, 60 seconds by default
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Call `method` with `args`. Returns the result of the call, `None` for void methods.
+    /// `exceptions` are the names of the exceptions declared by the method, in order of their
+    /// field ids
+    async fn call(
+        &self,
+        method: &'static str,
+        args: Value,
+        exceptions: &[&str],
+    ) -> Result<Option<Value>, HmsError> {
+        let mut connection = self.connection.lock().await;
+        let seq_id = self.seq_id.fetch_add(1, Ordering::Relaxed);
+        let result = tokio::time::timeout(
+            self.timeout,
+            Self::exchange(&self.address, &mut connection, method, seq_id, &args),
+        )
+        .await
+        .unwrap_or_else(|_| {
+            Err(HmsError::Timeout {
+                method,
+                timeout: self.timeout,
+            })
+        });
+        // the state of the connection is unknown after a failed exchange
+        let reply = match result {
+            Ok(reply) => reply,
+            Err(err) => {
+                *connection = None;
+                return Err(err);
+            }
+        };
+        if reply.name != method || reply.seq_id != seq_id {
+            *connection = None;
+            return Err(HmsError::Protocol(format!(
+                "expected reply to '{method}' #{seq_id}, got '{}' #{}",
+                reply.name, reply.seq_id
+            )));
+        }
+
+        let fields = reply
+            .body
+            .as_struct()
+            .ok_or_else(|| HmsError::Protocol("expected a struct".to_string()))?;
+        let message = |value: &Value| {
+            value
+                .as_struct()
+                .and_then(|fields| fields.get(&1))
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        };
+        if reply.message_type == MessageType::Exception {
+            return Err(HmsError::Metastore {
+                method,
+                exception: "TApplicationException".to_string(),
+                message: message(&reply.body),
+            });
+        }
+        if let Some((id, exception)) = fields.iter().find(|(id, _)| **id > 0) {
+            let name = exceptions
+                .get(*id as usize - 1)
+                .map(|name| name.to_string())
+                .unwrap_or_else(|| format!("exception {id}"));
+            return Err(HmsError::Metastore {
+                method,
+                exception: name,
+                message: message(exception),
+            });
+        }
+        Ok(fields.get(&0).cloned())
+    }
+
+    async fn exchange(
+        address: &str,
+        connection: &mut Option<BufStream<TcpStream>>,
+        method: &'static str,
+        seq_id: i32,
+        args: &Value,
+    ) -> Result<thrift::Message, HmsError> {
+        let stream = match connection {
+            Some(stream) => stream,
+            None => connection.insert(BufStream::new(TcpStream::connect(address).await?)),
+        };
+        let message = encode_message(MessageType::Call, method, seq_id, args);
+        stream.write_all(&message).await?;
+        stream.flush().await?;
+        read_message(stream).await
+    }
+
+    async fn call_with_result(
+        &self,
+        method: &'static str,
+        args: Value,
+        exceptions: &[&str],
+    ) -> Result<Value, HmsError> {
+        self.call(method, args, exceptions)
+            .await?
+            .ok_or_else(|| HmsError::Protocol(format!("missing result of '{method}'")))
+    }
+
+    /// Get the table `database_name.table_name`
+    pub async fn get_table(
+        &self,
+        database_name: &str,
+        table_name: &str,
+    ) -> Result<HmsTable, HmsError> {
+        let args = Value::structure([
+            (1, Some(Value::string(database_name))),
+            (2, Some(Value::string(table_name))),
+        ]);
+        let table = self
+            .call_with_result(
+                "get_table",
+                args,
+                &["MetaException", "NoSuchObjectException"],
+            )
+            .await?;
+        HmsTable::from_thrift(&table)
+    }
+
+    /// Create `table`
+    pub async fn create_table(&self, table: &HmsTable) -> Result<(), HmsError> {
+        let args = Value::structure([(1, Some(table.to_thrift()))]);
+        self.call(
+            "create_table",
+            args,
+            &[
+                "AlreadyExistsException",
+                "InvalidObjectException",
+                "MetaException",
+                "NoSuchObjectException",
+            ],
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Replace the table `database_name.table_name` by `table`
+    pub async fn alter_table(
+        &self,
+        database_name: &str,
+        table_name: &str,
+        table: &HmsTable,
+    ) -> Result<(), HmsError> {
+        let args = Value::structure([
+            (1, Some(Value::string(database_name))),
+            (2, Some(Value::string(table_name))),
+            (3, Some(table.to_thrift())),
+        ]);
+        self.call(
+            "alter_table",
+            args,
+            &["InvalidOperationException", "MetaException"],
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Get the names of all databases
+    pub async fn get_all_databases(&self) -> Result<Vec<String>, HmsError> {
+        let names = self
+            .call_with_result(
+                "get_all_databases",
+                Value::structure([]),
+                &["MetaException"],
+            )
+            .await?;
+        Ok(string_list(&names))
+    }
+
+    /// Get the names of all tables of a database
+    pub async fn get_all_tables(&self, database_name: &str) -> Result<Vec<String>, HmsError> {
+        let args = Value::structure([(1, Some(Value::string(database_name)))]);
+        let names = self
+            .call_with_result("get_all_tables", args, &["MetaException"])
+            .await?;
+        Ok(string_list(&names))
+    }
+
+    /// Get the tables of a database with the given names
+    pub async fn get_table_objects_by_name(
+        &self,
+        database_name: &str,
+        table_names: &[String],
+    ) -> Result<Vec<HmsTable>, HmsError> {
+        let args = Value::structure([
+            (1, Some(Value::string(database_name))),
+            (2, Some(Value::string_list(table_names.iter().cloned()))),
+        ]);
+        let tables = self
+            .call_with_result(
+                "get_table_objects_by_name",
+                args,
+                &[
+                    "MetaException",
+                    "InvalidOperationException",
+                    "UnknownDBException",
+                ],
+            )
+            .await?;
+        tables
+            .as_list()
+            .unwrap_or_default()
+            .iter()
+            .map(HmsTable::from_thrift)
+            .collect()
+    }
+
+    /// Register `table` as `database_name.table_name`. An existing table with the same name is
+    /// updated in place
+    pub async fn register_table(
+        &self,
+        database_name: &str,
+        table_name: &str,
+        table: &DeltaTable,
+    ) -> crate::DeltaResult<()> {
+        let hms_table = HmsTable::from_delta_table(database_name, table_name, table)?;
+        let to_delta_error = |err: HmsError| crate::DeltaTableError::GenericError {
+            source: Box::new(err),
+        };
+        match self.create_table(&hms_table).await {
+            Err(HmsError::Metastore { exception, .. }) if exception == "AlreadyExistsException" => {
+                self.alter_table(database_name, table_name, &hms_table)
+                    .await
+                    .map_err(to_delta_error)
+            }
+            result => result.map_err(to_delta_error),
+        }
+    }
+}
+
+impl std::fmt::Debug for HmsCatalog {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "HmsCatalog({})", self.address)
+    }
+}
+
+/// Reject catalog ids other than [`DEFAULT_CATALOG`]
+fn check_catalog_id(catalog_id: Option<String>) -> Result<(), DataCatalogError> {
+    match catalog_id.filter(|id| id != DEFAULT_CATALOG) {
+        Some(catalog_id) => Err(DataCatalogError::InvalidDataCatalog {
+            data_catalog: catalog_id,
+        }),
+        None => Ok(()),
+    }
+}
+
+#[async_trait::async_trait]
+impl DataCatalog for HmsCatalog {
+    /// Get the table storage location from the metastore
+    async fn get_table_storage_location(
+        &self,
+        catalog_id: Option<String>,
+        database_name: &str,
+        table_name: &str,
+    ) -> Result<String, DataCatalogError> {
+        check_catalog_id(catalog_id)?;
+        let table = self.get_table(database_name, table_name).await?;
+        Ok(table.storage_location().ok_or(HmsError::Protocol(format!(
+            "table {database_name}.{table_name} has no location"
+        )))?)
+    }
+
+    /// List the databases of the metastore
+    async fn list_databases(
+        &self,
+        catalog_id: Option<String>,
+    ) -> Result<Vec<String>, DataCatalogError> {
+        check_catalog_id(catalog_id)?;
+        Ok(self.get_all_databases().await?)
+    }
+
+    /// List the Delta tables of a database of the metastore
+    async fn list_tables(
+        &self,
+        catalog_id: Option<String>,
+        database_name: &str,
+    ) -> Result<Vec<String>, DataCatalogError> {
+        check_catalog_id(catalog_id)?;
+        let names = self.get_all_tables(database_name).await?;
+        let mut delta_tables = Vec::new();
+        for chunk in names.chunks(TABLE_BATCH_SIZE) {
+            let tables = self.get_table_objects_by_name(database_name, chunk).await?;
+            delta_tables.extend(
+                tables
+                    .into_iter()
+                    .filter(HmsTable::is_delta_table)
+                    .map(|table| table.table_name),
+            );
+        }
+        Ok(delta_tables)
+    }
+
+    /// Replace the entry of the table in the metastore with the current state of `table`
+    async fn sync_table_metadata(
+        &self,
+        catalog_id: Option<String>,
+        database_name: &str,
+        table_name: &str,
+        table: &DeltaTable,
+    ) -> Result<(), DataCatalogError> {
+        check_catalog_id(catalog_id)?;
+        let hms_table =
+            HmsTable::from_delta_table(database_name, table_name, table).map_err(|err| {
+                DataCatalogError::Generic {
+                    catalog: "HMS",
+                    source: Box::new(err),
+                }
+            })?;
+        Ok(self
+            .alter_table(database_name, table_name, &hms_table)
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::kernel::{ArrayType, StructType};
+
+    /// Answer a single call with `result`, or the exception with the given field id
+    async fn serve_one(
+        listener: &TcpListener,
+        expected_method: &str,
+        reply: impl FnOnce(&Value) -> Value,
+    ) {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut socket = BufStream::new(socket);
+        let call = read_message(&mut socket).await.unwrap();
+        assert_eq!(call.message_type, MessageType::Call);
+        assert_eq!(call.name, expected_method);
+        let body = reply(&call.body);
+        let response = encode_message(MessageType::Reply, &call.name, call.seq_id, &body);
+        socket.write_all(&response).await.unwrap();
+        socket.flush().await.unwrap();
+    }
+
+    /// Answer the calls of a single connection in order, returning the arguments of the calls
+    async fn serve_calls(
+        listener: &TcpListener,
+        replies: Vec<(&str, MessageType, Value)>,
+    ) -> Vec<Value> {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut socket = BufStream::new(socket);
+        let mut args = Vec::new();
+        for (method, message_type, body) in replies {
+            let call = read_message(&mut socket).await.unwrap();
+            assert_eq!(call.message_type, MessageType::Call);
+            assert_eq!(call.name, method);
+            let response = encode_message(message_type, &call.name, call.seq_id, &body);
+            socket.write_all(&response).await.unwrap();
+            socket.flush().await.unwrap();
+            args.push(call.body);
+        }
+        args
+    }
+
+    fn exception(message: &str) -> Value {
+        Value::structure([(1, Some(Value::string(message)))])
+    }
+
+    fn spark_table() -> HmsTable {
+        HmsTable {
+            database_name: "analytics".to_string(),
+            table_name: "events".to_string(),
+            location: Some(format!("s3://bucket/events{PLACEHOLDER_SUFFIX}")),
+            serde_parameters: HashMap::from([(
+                "path".to_string(),
+                "s3://bucket/events".to_string(),
+            )]),
+            parameters: HashMap::from([(
+                "spark.sql.sources.provider".to_string(),
+                "DELTA".to_string(),
+            )]),
+            columns: vec![HmsColumn {
+                name: "id".to_string(),
+                data_type: "bigint".to_string(),
+                comment: None,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_table_thrift_roundtrip() {
+        let table = HmsTable {
+            owner: Some("etl".to_string()),
+            table_type: Some("EXTERNAL_TABLE".to_string()),
+            input_format: Some(PARQUET_INPUT_FORMAT.to_string()),
+            serialization_library: Some(PARQUET_SERDE.to_string()),
+            partition_keys: vec![HmsColumn {
+                name: "date".to_string(),
+                data_type: "date".to_string(),
+                comment: Some("day of the event".to_string()),
+            }],
+            ..spark_table()
+        };
+        assert_eq!(HmsTable::from_thrift(&table.to_thrift()).unwrap(), table);
+        assert!(table.is_delta_table());
+        assert_eq!(
+            table.storage_location().as_deref(),
+            Some("s3://bucket/events")
+        );
+    }
+
+    #[test]
+    fn test_hive_type() {
+        let data_type = DataType::Array(Box::new(ArrayType::new(
+            DataType::Struct(Box::new(StructType::new(vec![StructField::new(
+                "a",
+                DataType::Primitive(PrimitiveType::Decimal(10, 2)),
+                true,
+            )]))),
+            true,
+        )));
+        assert_eq!(hive_type(&data_type), "array<struct<a:decimal(10,2)>>");
+    }
+
+    #[tokio::test]
+    async fn test_get_table() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let catalog = HmsCatalog::new(listener.local_addr().unwrap().to_string());
+
+        let server = async {
+            serve_one(&listener, "get_table", |args| {
+                let args = args.as_struct().unwrap();
+                assert_eq!(args.get(&1).and_then(Value::as_str), Some("analytics"));
+                assert_eq!(args.get(&2).and_then(Value::as_str), Some("events"));
+                Value::structure([(0, Some(spark_table().to_thrift()))])
+            })
+            .await;
+            serve_one(&listener, "get_table", |_| {
+                let exception =
+                    Value::structure([(1, Some(Value::string("missing.events not found")))]);
+                Value::structure([(2, Some(exception))])
+            })
+            .await;
+        };
+        let client = async {
+            let location = catalog
+                .get_table_storage_location(None, "analytics", "events")
+                .await
+                .unwrap();
+            assert_eq!(location, "s3://bucket/events");
+
+            // the server closed the first connection, the next call reconnects
+            let _ = catalog.get_table("missing", "events").await;
+            let err = catalog.get_table("missing", "events").await.unwrap_err();
+            assert!(err.is_not_found(), "{err}");
+        };
+        tokio::join!(server, client);
+
+        assert!(catalog
+            .get_table_storage_location(Some("spark".to_string()), "analytics", "events")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_and_alter_table() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let catalog = HmsCatalog::new(listener.local_addr().unwrap().to_string());
+        let table = crate::operations::DeltaOps::new_in_memory()
+            .create()
+            .with_columns(
+                crate::writer::test_utils::get_delta_schema()
+                    .fields()
+                    .clone(),
+            )
+            .with_partition_columns(["modified"])
+            .with_configuration_property(crate::DeltaConfigKey::AppendOnly, Some("true"))
+            .await
+            .unwrap();
+        let hms_table = HmsTable::from_delta_table("analytics", "events", &table).unwrap();
+        assert_eq!(hms_table.partition_keys.len(), 1);
+        assert_eq!(
+            hms_table
+                .parameters
+                .get("delta.appendOnly")
+                .map(String::as_str),
+            Some("true")
+        );
+
+        let void = Value::structure([]);
+        let server = serve_calls(
+            &listener,
+            vec![
+                ("create_table", MessageType::Reply, void.clone()),
+                // register_table updates existing tables
+                (
+                    "create_table",
+                    MessageType::Reply,
+                    Value::structure([(1, Some(exception("events exists")))]),
+                ),
+                ("alter_table", MessageType::Reply, void.clone()),
+                (
+                    "alter_table",
+                    MessageType::Reply,
+                    Value::structure([(2, Some(exception("metastore down")))]),
+                ),
+            ],
+        );
+        let client = async {
+            catalog.create_table(&hms_table).await.unwrap();
+            catalog
+                .register_table("analytics", "events", &table)
+                .await
+                .unwrap();
+            let err = catalog
+                .sync_table_metadata(None, "analytics", "events", &table)
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("MetaException"), "{err}");
+        };
+        let (args, _) = tokio::join!(server, client);
+
+        let decoded = |value: &Value| HmsTable::from_thrift(value).unwrap();
+        for create_args in &args[..2] {
+            let create_args = create_args.as_struct().unwrap();
+            assert_eq!(create_args.len(), 1);
+            assert_eq!(decoded(&create_args[&1]), hms_table);
+        }
+        for alter_args in &args[2..] {
+            let alter_args = alter_args.as_struct().unwrap();
+            assert_eq!(alter_args[&1].as_str(), Some("analytics"));
+            assert_eq!(alter_args[&2].as_str(), Some("events"));
+            assert_eq!(decoded(&alter_args[&3]), hms_table);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_databases_and_tables() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let catalog = HmsCatalog::new(listener.local_addr().unwrap().to_string());
+        let hive_table = HmsTable {
+            table_name: "logs".to_string(),
+            parameters: HashMap::new(),
+            ..spark_table()
+        };
+
+        let server = serve_calls(
+            &listener,
+            vec![
+                (
+                    "get_all_databases",
+                    MessageType::Reply,
+                    Value::structure([(0, Some(Value::string_list(["analytics", "default"])))]),
+                ),
+                (
+                    "get_all_tables",
+                    MessageType::Reply,
+                    Value::structure([(0, Some(Value::string_list(["events", "logs"])))]),
+                ),
+                (
+                    "get_table_objects_by_name",
+                    MessageType::Reply,
+                    Value::structure([(
+                        0,
+                        Some(Value::struct_list([
+                            spark_table().to_thrift(),
+                            hive_table.to_thrift(),
+                        ])),
+                    )]),
+                ),
+                (
+                    "get_all_tables",
+                    MessageType::Reply,
+                    Value::structure([(1, Some(exception("unknown database")))]),
+                ),
+                (
+                    "get_all_databases",
+                    MessageType::Exception,
+                    Value::structure([
+                        (1, Some(Value::string("Invalid method name"))),
+                        (2, Some(Value::I32(1))),
+                    ]),
+                ),
+            ],
+        );
+        let client = async {
+            let databases = catalog.list_databases(None).await.unwrap();
+            assert_eq!(databases, vec!["analytics", "default"]);
+            let tables = catalog.list_tables(None, "analytics").await.unwrap();
+            assert_eq!(tables, vec!["events"]);
+
+            let err = catalog.get_all_tables("missing").await.unwrap_err();
+            assert!(
+                matches!(&err, HmsError::Metastore { exception, .. } if exception == "MetaException"),
+                "{err}"
+            );
+            let err = catalog.get_all_databases().await.unwrap_err();
+            assert!(
+                matches!(
+                    &err,
+                    HmsError::Metastore { exception, message, .. }
+                        if exception == "TApplicationException" && message == "Invalid method name"
+                ),
+                "{err}"
+            );
+        };
+        let (args, _) = tokio::join!(server, client);
+
+        assert_eq!(args[0], Value::structure([]));
+        assert_eq!(
+            args[1],
+            Value::structure([(1, Some(Value::string("analytics")))])
+        );
+        assert_eq!(
+            args[2],
+            Value::structure([
+                (1, Some(Value::string("analytics"))),
+                (2, Some(Value::string_list(["events", "logs"]))),
+            ])
+        );
+    }
+}
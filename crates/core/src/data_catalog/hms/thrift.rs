@@ -0,0 +1,523 @@
+//! Minimal implementation of the Thrift binary protocol spoken by the Hive Metastore.
+//!
+//! Messages are encoded from and decoded into a generic [`Value`] tree, the Hive Metastore
+//! structures are translated in the parent module. Fields of decoded structs are kept by id,
+//! so structures of newer metastore versions decode fine and unknown fields are ignored.
+
+use std::collections::BTreeMap;
+
+use futures::future::BoxFuture;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use super::HmsError;
+
+const VERSION_1: u32 = 0x8001_0000;
+const VERSION_MASK: u32 = 0xffff_0000;
+
+/// Upper bound of strings and containers, to fail fast on corrupted or non-thrift responses
+const MAX_LENGTH: usize = 64 * 1024 * 1024;
+
+/// Type of a thrift message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MessageType {
+    Call = 1,
+    Reply = 2,
+    Exception = 3,
+}
+
+mod field_type {
+    pub const STOP: u8 = 0;
+    pub const BOOL: u8 = 2;
+    pub const BYTE: u8 = 3;
+    pub const DOUBLE: u8 = 4;
+    pub const I16: u8 = 6;
+    pub const I32: u8 = 8;
+    pub const I64: u8 = 10;
+    pub const STRING: u8 = 11;
+    pub const STRUCT: u8 = 12;
+    pub const MAP: u8 = 13;
+    pub const SET: u8 = 14;
+    pub const LIST: u8 = 15;
+}
+
+/// A thrift value
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Bool(bool),
+    Byte(i8),
+    Double(f64),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    /// Strings and binaries
+    String(Vec<u8>),
+    Struct(BTreeMap<i16, Value>),
+    /// Key and value types, needed to encode empty maps
+    Map(u8, u8, Vec<(Value, Value)>),
+    /// Element type, needed to encode empty lists
+    List(u8, Vec<Value>),
+}
+
+impl Value {
+    pub(crate) fn string(value: impl Into<String>) -> Self {
+        Value::String(value.into().into_bytes())
+    }
+
+    pub(crate) fn string_list(values: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Value::List(
+            field_type::STRING,
+            values.into_iter().map(Value::string).collect(),
+        )
+    }
+
+    pub(crate) fn string_map<'a>(
+        values: impl IntoIterator<Item = (&'a String, &'a String)>,
+    ) -> Self {
+        Value::Map(
+            field_type::STRING,
+            field_type::STRING,
+            values
+                .into_iter()
+                .map(|(key, value)| (Value::string(key), Value::string(value)))
+                .collect(),
+        )
+    }
+
+    pub(crate) fn struct_list(values: impl IntoIterator<Item = Value>) -> Self {
+        Value::List(field_type::STRUCT, values.into_iter().collect())
+    }
+
+    /// Build a struct from its set fields
+    pub(crate) fn structure(fields: impl IntoIterator<Item = (i16, Option<Value>)>) -> Self {
+        Value::Struct(
+            fields
+                .into_iter()
+                .filter_map(|(id, value)| value.map(|value| (id, value)))
+                .collect(),
+        )
+    }
+
+    fn type_id(&self) -> u8 {
+        match self {
+            Value::Bool(_) => field_type::BOOL,
+            Value::Byte(_) => field_type::BYTE,
+            Value::Double(_) => field_type::DOUBLE,
+            Value::I16(_) => field_type::I16,
+            Value::I32(_) => field_type::I32,
+            Value::I64(_) => field_type::I64,
+            Value::String(_) => field_type::STRING,
+            Value::Struct(_) => field_type::STRUCT,
+            Value::Map(..) => field_type::MAP,
+            Value::List(..) => field_type::LIST,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(bytes) => std::str::from_utf8(bytes).ok(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_struct(&self) -> Option<&BTreeMap<i16, Value>> {
+        match self {
+            Value::Struct(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_list(&self) -> Option<&[Value]> {
+        match self {
+            Value::List(_, values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_map(&self) -> Option<&[(Value, Value)]> {
+        match self {
+            Value::Map(_, _, entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        match self {
+            Value::Bool(value) => buf.push(u8::from(*value)),
+            Value::Byte(value) => buf.extend_from_slice(&value.to_be_bytes()),
+            Value::Double(value) => buf.extend_from_slice(&value.to_be_bytes()),
+            Value::I16(value) => buf.extend_from_slice(&value.to_be_bytes()),
+            Value::I32(value) => buf.extend_from_slice(&value.to_be_bytes()),
+            Value::I64(value) => buf.extend_from_slice(&value.to_be_bytes()),
+            Value::String(bytes) => {
+                buf.extend_from_slice(&(bytes.len() as i32).to_be_bytes());
+                buf.extend_from_slice(bytes);
+            }
+            Value::Struct(fields) => {
+                for (id, value) in fields {
+                    buf.push(value.type_id());
+                    buf.extend_from_slice(&id.to_be_bytes());
+                    value.encode(buf);
+                }
+                buf.push(field_type::STOP);
+            }
+            Value::Map(key_type, value_type, entries) => {
+                buf.push(*key_type);
+                buf.push(*value_type);
+                buf.extend_from_slice(&(entries.len() as i32).to_be_bytes());
+                for (key, value) in entries {
+                    key.encode(buf);
+                    value.encode(buf);
+                }
+            }
+            Value::List(element_type, values) => {
+                buf.push(*element_type);
+                buf.extend_from_slice(&(values.len() as i32).to_be_bytes());
+                for value in values {
+                    value.encode(buf);
+                }
+            }
+        }
+    }
+}
+
+/// Encode a message with the arguments of a call
+pub(crate) fn encode_message(
+    message_type: MessageType,
+    name: &str,
+    seq_id: i32,
+    body: &Value,
+) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&(VERSION_1 | message_type as u32).to_be_bytes());
+    Value::string(name).encode(&mut buf);
+    buf.extend_from_slice(&seq_id.to_be_bytes());
+    body.encode(&mut buf);
+    buf
+}
+
+/// A decoded message
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Message {
+    pub(crate) message_type: MessageType,
+    pub(crate) name: String,
+    pub(crate) seq_id: i32,
+    pub(crate) body: Value,
+}
+
+struct Decoder<'a, R> {
+    reader: &'a mut R,
+}
+
+impl<'a, R: AsyncRead + Unpin + Send> Decoder<'a, R> {
+    async fn read_length(&mut self) -> Result<usize, HmsError> {
+        let length = self.reader.read_i32().await?;
+        usize::try_from(length)
+            .ok()
+            .filter(|length| *length <= MAX_LENGTH)
+            .ok_or_else(|| HmsError::Protocol(format!("invalid length {length}")))
+    }
+
+    async fn read_string(&mut self) -> Result<Vec<u8>, HmsError> {
+        let length = self.read_length().await?;
+        let mut bytes = vec![0; length];
+        self.reader.read_exact(&mut bytes).await?;
+        Ok(bytes)
+    }
+
+    fn read_value(&mut self, type_id: u8) -> BoxFuture<'_, Result<Value, HmsError>> {
+        Box::pin(async move { self.read_value_inner(type_id).await })
+    }
+
+    async fn read_value_inner(&mut self, type_id: u8) -> Result<Value, HmsError> {
+        Ok(match type_id {
+            field_type::BOOL => Value::Bool(self.reader.read_u8().await? != 0),
+            field_type::BYTE => Value::Byte(self.reader.read_i8().await?),
+            field_type::DOUBLE => Value::Double(self.reader.read_f64().await?),
+            field_type::I16 => Value::I16(self.reader.read_i16().await?),
+            field_type::I32 => Value::I32(self.reader.read_i32().await?),
+            field_type::I64 => Value::I64(self.reader.read_i64().await?),
+            field_type::STRING => Value::String(self.read_string().await?),
+            field_type::STRUCT => {
+                let mut fields = BTreeMap::new();
+                loop {
+                    let field_type = self.reader.read_u8().await?;
+                    if field_type == field_type::STOP {
+                        break;
+                    }
+                    let id = self.reader.read_i16().await?;
+                    let value = self.read_value(field_type).await?;
+                    fields.insert(id, value);
+                }
+                Value::Struct(fields)
+            }
+            field_type::MAP => {
+                let key_type = self.reader.read_u8().await?;
+                let value_type = self.reader.read_u8().await?;
+                let length = self.read_length().await?;
+                let mut entries = Vec::with_capacity(length.min(1024));
+                for _ in 0..length {
+                    let key = self.read_value(key_type).await?;
+                    let value = self.read_value(value_type).await?;
+                    entries.push((key, value));
+                }
+                Value::Map(key_type, value_type, entries)
+            }
+            field_type::SET | field_type::LIST => {
+                let element_type = self.reader.read_u8().await?;
+                let length = self.read_length().await?;
+                let mut values = Vec::with_capacity(length.min(1024));
+                for _ in 0..length {
+                    values.push(self.read_value(element_type).await?);
+                }
+                Value::List(element_type, values)
+            }
+            other => return Err(HmsError::Protocol(format!("unknown field type {other}"))),
+        })
+    }
+}
+
+/// Read a single message in the strict binary protocol
+pub(crate) async fn read_message<R: AsyncRead + Unpin + Send>(
+    reader: &mut R,
+) -> Result<Message, HmsError> {
+    let mut decoder = Decoder { reader };
+    let header = decoder.reader.read_u32().await?;
+    if header & VERSION_MASK != VERSION_1 {
+        return Err(HmsError::Protocol(format!(
+            "unsupported message header {header:#x}"
+        )));
+    }
+    let message_type = match header & 0xff {
+        1 => MessageType::Call,
+        2 => MessageType::Reply,
+        3 => MessageType::Exception,
+        other => return Err(HmsError::Protocol(format!("unknown message type {other}"))),
+    };
+    let name = String::from_utf8_lossy(&decoder.read_string().await?).into_owned();
+    let seq_id = decoder.reader.read_i32().await?;
+    let body = decoder.read_value(field_type::STRUCT).await?;
+    Ok(Message {
+        message_type,
+        name,
+        seq_id,
+        body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn roundtrip(message_type: MessageType, name: &str, seq_id: i32, body: &Value) {
+        let bytes = encode_message(message_type, name, seq_id, body);
+        let message = read_message(&mut bytes.as_slice()).await.unwrap();
+        assert_eq!(
+            message,
+            Message {
+                message_type,
+                name: name.to_string(),
+                seq_id,
+                body: body.clone(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_message_roundtrip() {
+        let body = Value::structure([
+            (1, Some(Value::string("db"))),
+            (2, None),
+            (3, Some(Value::I32(-7))),
+            (4, Some(Value::string_list(["a", "b"]))),
+            (
+                5,
+                Some(Value::string_map(&std::collections::HashMap::from([(
+                    "k".to_string(),
+                    "v".to_string(),
+                )]))),
+            ),
+            (
+                6,
+                Some(Value::struct_list([Value::structure([(
+                    1,
+                    Some(Value::Bool(true)),
+                )])])),
+            ),
+            (7, Some(Value::I64(i64::MAX))),
+        ]);
+        let bytes = encode_message(MessageType::Call, "get_table", 42, &body);
+        let message = read_message(&mut bytes.as_slice()).await.unwrap();
+        assert_eq!(message.message_type, MessageType::Call);
+        assert_eq!(message.name, "get_table");
+        assert_eq!(message.seq_id, 42);
+        assert_eq!(message.body, body);
+        assert_eq!(message.body.as_struct().unwrap().len(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_value_roundtrip() {
+        let values = [
+            Value::Bool(true),
+            Value::Bool(false),
+            Value::Byte(i8::MIN),
+            Value::Double(-1.5),
+            Value::Double(f64::MAX),
+            Value::I16(i16::MIN),
+            Value::I32(i32::MAX),
+            Value::I64(i64::MIN),
+            Value::string(""),
+            Value::string("ünïcode"),
+            Value::String(vec![0, 0xff, 0x80]),
+            Value::structure([]),
+            Value::structure([(-1, Some(Value::I32(1))), (i16::MAX, Some(Value::I32(2)))]),
+            Value::Map(field_type::I32, field_type::STRUCT, vec![]),
+            Value::Map(
+                field_type::I64,
+                field_type::LIST,
+                vec![(Value::I64(1), Value::string_list(["a"]))],
+            ),
+            Value::List(field_type::BOOL, vec![]),
+            Value::List(field_type::DOUBLE, vec![Value::Double(0.0)]),
+            Value::List(
+                field_type::LIST,
+                vec![Value::List(field_type::I16, vec![Value::I16(3)])],
+            ),
+            Value::struct_list([Value::structure([(
+                1,
+                Some(Value::structure([(1, Some(Value::string("nested")))])),
+            )])]),
+        ];
+        for (id, value) in values.iter().enumerate() {
+            let body = Value::structure([(id as i16, Some(value.clone()))]);
+            roundtrip(MessageType::Reply, "get_value", id as i32, &body).await;
+        }
+        let body = Value::structure(
+            values
+                .iter()
+                .enumerate()
+                .map(|(id, value)| (id as i16 + 1, Some(value.clone()))),
+        );
+        for message_type in [
+            MessageType::Call,
+            MessageType::Reply,
+            MessageType::Exception,
+        ] {
+            roundtrip(message_type, "get_values", i32::MIN, &body).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_encode_binary_protocol() {
+        // get_table("db", "t") as encoded by the reference thrift implementation
+        let body = Value::structure([
+            (1, Some(Value::string("db"))),
+            (2, Some(Value::string("t"))),
+        ]);
+        let expected: Vec<u8> = [
+            &[0x80, 0x01, 0x00, 0x01][..],
+            &[0, 0, 0, 9],
+            b"get_table",
+            &[0, 0, 0, 7],
+            &[11, 0, 1, 0, 0, 0, 2, b'd', b'b'],
+            &[11, 0, 2, 0, 0, 0, 1, b't'],
+            &[0],
+        ]
+        .concat();
+        assert_eq!(
+            encode_message(MessageType::Call, "get_table", 7, &body),
+            expected
+        );
+
+        let body = Value::structure([
+            (0, Some(Value::string_list(["a"]))),
+            (
+                1,
+                Some(Value::Map(field_type::STRING, field_type::I32, vec![])),
+            ),
+            (2, Some(Value::Bool(true))),
+            (3, Some(Value::I64(-2))),
+        ]);
+        let expected: Vec<u8> = [
+            &[0x80, 0x01, 0x00, 0x02][..],
+            &[0, 0, 0, 1],
+            b"f",
+            &[0xff, 0xff, 0xff, 0xff],
+            &[15, 0, 0, 11, 0, 0, 0, 1, 0, 0, 0, 1, b'a'],
+            &[13, 0, 1, 11, 8, 0, 0, 0, 0],
+            &[2, 0, 2, 1],
+            &[10, 0, 3, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe],
+            &[0],
+        ]
+        .concat();
+        assert_eq!(encode_message(MessageType::Reply, "f", -1, &body), expected);
+    }
+
+    #[tokio::test]
+    async fn test_decode_set_as_list() {
+        let mut bytes = encode_message(MessageType::Reply, "f", 1, &Value::structure([]));
+        bytes.pop();
+        bytes.extend_from_slice(&[14, 0, 0, 8, 0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 2, 0]);
+        let message = read_message(&mut bytes.as_slice()).await.unwrap();
+        assert_eq!(
+            message.body,
+            Value::structure([(
+                0,
+                Some(Value::List(
+                    field_type::I32,
+                    vec![Value::I32(1), Value::I32(2)]
+                ))
+            )])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_consecutive_messages() {
+        let first = Value::structure([(0, Some(Value::string_list(["db"])))]);
+        let second = Value::structure([(1, Some(Value::structure([])))]);
+        let mut bytes = encode_message(MessageType::Reply, "get_all_databases", 1, &first);
+        bytes.extend(encode_message(MessageType::Reply, "get_table", 2, &second));
+
+        let mut reader = bytes.as_slice();
+        let message = read_message(&mut reader).await.unwrap();
+        assert_eq!((message.seq_id, message.body), (1, first));
+        let message = read_message(&mut reader).await.unwrap();
+        assert_eq!((message.seq_id, message.body), (2, second));
+        assert!(reader.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reject_invalid_messages() {
+        let mut bytes = encode_message(MessageType::Reply, "get_table", 1, &Value::structure([]));
+        bytes[0] = 0;
+        assert!(read_message(&mut bytes.as_slice()).await.is_err());
+
+        // truncated message
+        let bytes = encode_message(
+            MessageType::Reply,
+            "get_table",
+            1,
+            &Value::structure([(1, Some(Value::string("db")))]),
+        );
+        assert!(read_message(&mut &bytes[..bytes.len() - 2]).await.is_err());
+
+        // unknown message type
+        let mut bytes = encode_message(MessageType::Reply, "f", 1, &Value::structure([]));
+        bytes[3] = 5;
+        assert!(read_message(&mut bytes.as_slice()).await.is_err());
+
+        // unknown field type
+        let mut bytes = encode_message(MessageType::Reply, "f", 1, &Value::structure([]));
+        bytes.pop();
+        bytes.extend_from_slice(&[9, 0, 1, 0]);
+        assert!(read_message(&mut bytes.as_slice()).await.is_err());
+
+        // negative and oversized lengths
+        for length in [-1, MAX_LENGTH as i32 + 1] {
+            let mut bytes = encode_message(MessageType::Reply, "f", 1, &Value::structure([]));
+            bytes.pop();
+            bytes.extend_from_slice(&[11, 0, 1]);
+            bytes.extend_from_slice(&length.to_be_bytes());
+            assert!(read_message(&mut bytes.as_slice()).await.is_err());
+        }
+    }
+}
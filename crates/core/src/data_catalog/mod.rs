@@ -1,12 +1,24 @@
 //! Catalog abstraction for Delta Table
 
 use std::fmt::Debug;
+use std::sync::{Arc, OnceLock};
 
+use dashmap::DashMap;
+
+use crate::DeltaTable;
+
+pub use cache::CachingDataCatalog;
+pub use router::CatalogRouter;
 #[cfg(feature = "unity-experimental")]
 pub use unity::*;
 
+pub mod cache;
 #[cfg(feature = "unity-experimental")]
 pub mod client;
+pub mod external_table;
+#[cfg(feature = "hms")]
+pub mod hms;
+pub mod router;
 #[cfg(feature = "datafusion")]
 pub mod storage;
 #[cfg(feature = "unity-experimental")]
@@ -65,6 +77,13 @@ pub enum DataCatalogError {
         data_catalog: String,
     },
 
+    /// The catalog does not support the requested operation
+    #[error("Operation '{operation}' is not supported by this data catalog")]
+    UnsupportedOperation {
+        /// Name of the operation
+        operation: &'static str,
+    },
+
     /// Unknown configuration key
     #[error("Unknown configuration key '{catalog}' in '{key}' catalog.")]
     UnknownConfigKey {
@@ -86,4 +105,158 @@ pub trait DataCatalog: Send + Sync + Debug {
         database_name: &str,
         table_name: &str,
     ) -> Result<String, DataCatalogError>;
+
+    /// List the names of the catalogs available through this data catalog
+    async fn list_catalogs(&self) -> Result<Vec<String>, DataCatalogError> {
+        Err(DataCatalogError::UnsupportedOperation {
+            operation: "list_catalogs",
+        })
+    }
+
+    /// List the names of the databases, also called schemas, of a catalog
+    async fn list_databases(
+        &self,
+        _catalog_id: Option<String>,
+    ) -> Result<Vec<String>, DataCatalogError> {
+        Err(DataCatalogError::UnsupportedOperation {
+            operation: "list_databases",
+        })
+    }
+
+    /// List the names of the Delta tables of a database. Tables of other formats are skipped
+    async fn list_tables(
+        &self,
+        _catalog_id: Option<String>,
+        _database_name: &str,
+    ) -> Result<Vec<String>, DataCatalogError> {
+        Err(DataCatalogError::UnsupportedOperation {
+            operation: "list_tables",
+        })
+    }
+
+    /// Update the catalog entry of the table `database_name.table_name` from `table`, pushing
+    /// its current location, schema, partition columns and properties. Catalog entries go
+    /// stale when the schema of a table evolves or the table is moved
+    async fn sync_table_metadata(
+        &self,
+        _catalog_id: Option<String>,
+        _database_name: &str,
+        _table_name: &str,
+        _table: &DeltaTable,
+    ) -> Result<(), DataCatalogError> {
+        Err(DataCatalogError::UnsupportedOperation {
+            operation: "sync_table_metadata",
+        })
+    }
+}
+
+/// Registry of [`DataCatalog`] instances, keyed by the scheme of the catalog URIs they resolve
+pub type CatalogRegistry = Arc<DashMap<String, Arc<dyn DataCatalog>>>;
+
+/// The process global registry of data catalogs, used to resolve catalog URIs such as
+/// `glue://my_db.my_table`. No catalog is registered by default
+pub fn catalogs() -> CatalogRegistry {
+    static REGISTRY: OnceLock<CatalogRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(CatalogRegistry::default).clone()
+}
+
+/// Register `catalog` to resolve catalog URIs with `scheme`, replacing any catalog registered
+/// for the scheme before
+pub fn register_catalog(scheme: impl Into<String>, catalog: Arc<dyn DataCatalog>) {
+    catalogs().insert(scheme.into().to_ascii_lowercase(), catalog);
+}
+
+/// A table addressed through a data catalog, as `<scheme>://[<catalog>.]<database>.<table>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogUri {
+    /// Scheme the data catalog is registered with
+    pub scheme: String,
+    /// Catalog within the data catalog, if any
+    pub catalog_id: Option<String>,
+    /// Name of the database, also called schema
+    pub database_name: String,
+    /// Name of the table
+    pub table_name: String,
+}
+
+impl CatalogUri {
+    /// Parse a catalog URI such as `glue://my_db.my_table` or `unity://catalog.schema.table`
+    pub fn parse(uri: &str) -> Result<Self, DataCatalogError> {
+        let invalid = || DataCatalogError::Generic {
+            catalog: "registry",
+            source: format!("invalid catalog uri '{uri}'").into(),
+        };
+        let (scheme, name) = uri.split_once("://").ok_or_else(invalid)?;
+        let parts = name.trim_end_matches('/').split('.').collect::<Vec<_>>();
+        if scheme.is_empty() || parts.iter().any(|part| part.is_empty()) {
+            return Err(invalid());
+        }
+        let (catalog_id, database_name, table_name) = match parts.as_slice() {
+            [catalog, database, table] => (Some(catalog.to_string()), *database, *table),
+            [database, table] => (None, *database, *table),
+            _ => return Err(invalid()),
+        };
+        Ok(Self {
+            scheme: scheme.to_ascii_lowercase(),
+            catalog_id,
+            database_name: database_name.to_string(),
+            table_name: table_name.to_string(),
+        })
+    }
+
+    /// The catalog registered for the scheme of the URI
+    pub fn catalog(&self) -> Result<Arc<dyn DataCatalog>, DataCatalogError> {
+        catalogs()
+            .get(&self.scheme)
+            .map(|catalog| catalog.value().clone())
+            .ok_or_else(|| DataCatalogError::InvalidDataCatalog {
+                data_catalog: self.scheme.clone(),
+            })
+    }
+
+    /// Resolve the storage location of the table through the registered catalog
+    pub async fn resolve(&self) -> Result<String, DataCatalogError> {
+        self.catalog()?
+            .get_table_storage_location(
+                self.catalog_id.clone(),
+                &self.database_name,
+                &self.table_name,
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_catalog_uri() {
+        assert_eq!(
+            CatalogUri::parse("glue://my_db.my_table").unwrap(),
+            CatalogUri {
+                scheme: "glue".to_string(),
+                catalog_id: None,
+                database_name: "my_db".to_string(),
+                table_name: "my_table".to_string(),
+            }
+        );
+        let uri = CatalogUri::parse("UNITY://main.sales.orders").unwrap();
+        assert_eq!(uri.scheme, "unity");
+        assert_eq!(uri.catalog_id.as_deref(), Some("main"));
+        assert_eq!(uri.table_name, "orders");
+
+        for invalid in [
+            "my_db.my_table",
+            "glue://my_table",
+            "glue://a.b.c.d",
+            "glue://db.",
+        ] {
+            assert!(CatalogUri::parse(invalid).is_err(), "{invalid}");
+        }
+        assert!(CatalogUri::parse("unregistered://db.table")
+            .unwrap()
+            .catalog()
+            .is_err());
+    }
 }
@@ -0,0 +1,229 @@
+//! Dispatch table resolution to one of several data catalogs.
+//!
+//! Applications spanning several metastores register each [`DataCatalog`] under a name with a
+//! [`CatalogRouter`]. Tables are then addressed as `<catalog>.<database>.<table>`, or through the
+//! [`DataCatalog`] implementation of the router where the catalog id starts with the name of the
+//! catalog to use, e.g. `glue_prod` or `glue_prod.123456789012`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::{DataCatalog, DataCatalogError};
+use crate::DeltaTable;
+
+/// Routes table resolution to registered catalogs based on the catalog name
+#[derive(Debug, Default, Clone)]
+pub struct CatalogRouter {
+    catalogs: HashMap<String, Arc<dyn DataCatalog>>,
+    default: Option<Arc<dyn DataCatalog>>,
+}
+
+impl CatalogRouter {
+    /// Create a router without any registered catalogs
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `catalog` under `name`, replacing any catalog previously registered with it
+    pub fn with_catalog(mut self, name: impl Into<String>, catalog: Arc<dyn DataCatalog>) -> Self {
+        self.catalogs.insert(name.into(), catalog);
+        self
+    }
+
+    /// Set the catalog used for tables that don't specify a catalog
+    pub fn with_default(mut self, catalog: Arc<dyn DataCatalog>) -> Self {
+        self.default = Some(catalog);
+        self
+    }
+
+    /// Names of all registered catalogs
+    pub fn catalog_names(&self) -> impl Iterator<Item = &str> {
+        self.catalogs.keys().map(|name| name.as_str())
+    }
+
+    /// The catalog registered under `name`
+    pub fn catalog(&self, name: &str) -> Option<&Arc<dyn DataCatalog>> {
+        self.catalogs.get(name)
+    }
+
+    /// Split a catalog id into the catalog to route to and the id passed on to that catalog
+    fn route(
+        &self,
+        catalog_id: Option<String>,
+    ) -> Result<(&Arc<dyn DataCatalog>, Option<String>), DataCatalogError> {
+        let Some(catalog_id) = catalog_id else {
+            return self
+                .default
+                .as_ref()
+                .map(|catalog| (catalog, None))
+                .ok_or_else(|| DataCatalogError::InvalidDataCatalog {
+                    data_catalog: "<default>".to_string(),
+                });
+        };
+        let (name, inner_id) = match catalog_id.split_once('.') {
+            Some((name, inner_id)) => (name, Some(inner_id.to_string())),
+            None => (catalog_id.as_str(), None),
+        };
+        self.catalogs
+            .get(name)
+            .map(|catalog| (catalog, inner_id))
+            .ok_or_else(|| DataCatalogError::InvalidDataCatalog {
+                data_catalog: name.to_string(),
+            })
+    }
+
+    /// Resolve the storage location of a table addressed as `catalog.database.table`.
+    ///
+    /// Names with only two parts are resolved through the default catalog.
+    pub async fn resolve(&self, qualified_name: &str) -> Result<String, DataCatalogError> {
+        let parts = qualified_name.split('.').collect::<Vec<_>>();
+        let (catalog_id, database_name, table_name) = match parts.as_slice() {
+            [catalog, database, table] => (Some(catalog.to_string()), *database, *table),
+            [database, table] => (None, *database, *table),
+            _ => {
+                return Err(DataCatalogError::Generic {
+                    catalog: "router",
+                    source: format!("invalid table name '{qualified_name}'").into(),
+                })
+            }
+        };
+        self.get_table_storage_location(catalog_id, database_name, table_name)
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl DataCatalog for CatalogRouter {
+    async fn get_table_storage_location(
+        &self,
+        catalog_id: Option<String>,
+        database_name: &str,
+        table_name: &str,
+    ) -> Result<String, DataCatalogError> {
+        let (catalog, catalog_id) = self.route(catalog_id)?;
+        catalog
+            .get_table_storage_location(catalog_id, database_name, table_name)
+            .await
+    }
+
+    /// The names of the registered catalogs, in alphabetical order
+    async fn list_catalogs(&self) -> Result<Vec<String>, DataCatalogError> {
+        let mut names = self.catalog_names().map(String::from).collect::<Vec<_>>();
+        names.sort();
+        Ok(names)
+    }
+
+    async fn list_databases(
+        &self,
+        catalog_id: Option<String>,
+    ) -> Result<Vec<String>, DataCatalogError> {
+        let (catalog, catalog_id) = self.route(catalog_id)?;
+        catalog.list_databases(catalog_id).await
+    }
+
+    async fn list_tables(
+        &self,
+        catalog_id: Option<String>,
+        database_name: &str,
+    ) -> Result<Vec<String>, DataCatalogError> {
+        let (catalog, catalog_id) = self.route(catalog_id)?;
+        catalog.list_tables(catalog_id, database_name).await
+    }
+
+    async fn sync_table_metadata(
+        &self,
+        catalog_id: Option<String>,
+        database_name: &str,
+        table_name: &str,
+        table: &DeltaTable,
+    ) -> Result<(), DataCatalogError> {
+        let (catalog, catalog_id) = self.route(catalog_id)?;
+        catalog
+            .sync_table_metadata(catalog_id, database_name, table_name, table)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct StaticCatalog(&'static str);
+
+    #[async_trait::async_trait]
+    impl DataCatalog for StaticCatalog {
+        async fn get_table_storage_location(
+            &self,
+            catalog_id: Option<String>,
+            database_name: &str,
+            table_name: &str,
+        ) -> Result<String, DataCatalogError> {
+            let account = catalog_id.unwrap_or_default();
+            Ok(format!(
+                "{}://{account}/{database_name}/{table_name}",
+                self.0
+            ))
+        }
+
+        async fn list_tables(
+            &self,
+            catalog_id: Option<String>,
+            database_name: &str,
+        ) -> Result<Vec<String>, DataCatalogError> {
+            let account = catalog_id.unwrap_or_default();
+            Ok(vec![format!("{}_{account}_{database_name}", self.0)])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_catalog_router() {
+        let router = CatalogRouter::new()
+            .with_catalog("glue_prod", Arc::new(StaticCatalog("s3")))
+            .with_catalog("hms", Arc::new(StaticCatalog("hdfs")));
+
+        assert_eq!(
+            router.resolve("glue_prod.db.events").await.unwrap(),
+            "s3:///db/events"
+        );
+        assert_eq!(
+            router.resolve("hms.db.events").await.unwrap(),
+            "hdfs:///db/events"
+        );
+        assert_eq!(
+            router
+                .get_table_storage_location(Some("glue_prod.1234".to_string()), "db", "events")
+                .await
+                .unwrap(),
+            "s3://1234/db/events"
+        );
+        assert!(matches!(
+            router.resolve("unity.db.events").await,
+            Err(DataCatalogError::InvalidDataCatalog { .. })
+        ));
+        assert!(router.resolve("db.events").await.is_err());
+        assert!(router.resolve("events").await.is_err());
+
+        assert_eq!(
+            router.list_catalogs().await.unwrap(),
+            vec!["glue_prod", "hms"]
+        );
+        assert_eq!(
+            router
+                .list_tables(Some("glue_prod.1234".to_string()), "db")
+                .await
+                .unwrap(),
+            vec!["s3_1234_db"]
+        );
+        assert!(matches!(
+            router.list_databases(Some("hms".to_string())).await,
+            Err(DataCatalogError::UnsupportedOperation { .. })
+        ));
+
+        let router = router.with_default(Arc::new(StaticCatalog("gs")));
+        assert_eq!(
+            router.resolve("db.events").await.unwrap(),
+            "gs:///db/events"
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! Legacy Databricks workspace `hive_metastore` catalog.
+//!
+//! Tables created before a workspace was migrated to Unity Catalog remain registered in the
+//! workspace local hive metastore. Databricks exposes these tables through the tables API of
+//! the workspace under the reserved `hive_metastore` catalog, using the same token based
+//! authentication as Unity Catalog.
+
+use super::models::{DataSourceFormat, GetTableResponse};
+use super::{UnityCatalog, UnityCatalogBuilder, UnityCatalogError};
+use crate::data_catalog::{DataCatalog, DataCatalogError, DataCatalogResult};
+
+/// Name of the catalog containing the tables of the workspace local hive metastore
+pub const HIVE_METASTORE_CATALOG: &str = "hive_metastore";
+
+/// Databricks workspace local hive metastore
+pub struct DatabricksHiveMetastore {
+    client: UnityCatalog,
+}
+
+impl DatabricksHiveMetastore {
+    /// Create a new [`DatabricksHiveMetastore`] from the workspace url and personal access token
+    pub fn try_new(
+        workspace_url: impl Into<String>,
+        access_token: impl Into<String>,
+    ) -> DataCatalogResult<Self> {
+        UnityCatalogBuilder::new()
+            .with_workspace_url(workspace_url)
+            .with_access_token(access_token)
+            .build_hive_metastore()
+    }
+
+    /// Create a new [`DatabricksHiveMetastore`] using the `DATABRICKS_HOST` and
+    /// `DATABRICKS_TOKEN` environment variables
+    pub fn from_env() -> DataCatalogResult<Self> {
+        UnityCatalogBuilder::from_env().build_hive_metastore()
+    }
+
+    pub(super) fn new(client: UnityCatalog) -> Self {
+        Self { client }
+    }
+}
+
+/// Reject catalog ids other than [`HIVE_METASTORE_CATALOG`]
+fn check_catalog_id(catalog_id: Option<String>) -> DataCatalogResult<()> {
+    match catalog_id.filter(|id| id != HIVE_METASTORE_CATALOG) {
+        Some(catalog_id) => Err(DataCatalogError::InvalidDataCatalog {
+            data_catalog: catalog_id,
+        }),
+        None => Ok(()),
+    }
+}
+
+#[async_trait::async_trait]
+impl DataCatalog for DatabricksHiveMetastore {
+    /// Get the table storage location from the workspace hive metastore
+    async fn get_table_storage_location(
+        &self,
+        catalog_id: Option<String>,
+        database_name: &str,
+        table_name: &str,
+    ) -> Result<String, DataCatalogError> {
+        check_catalog_id(catalog_id)?;
+        let table = match self
+            .client
+            .get_table(HIVE_METASTORE_CATALOG, database_name, table_name)
+            .await?
+        {
+            GetTableResponse::Success(table) => table,
+            GetTableResponse::Error(err) => {
+                return Err(UnityCatalogError::InvalidTable {
+                    error_code: err.error_code,
+                    message: err.message,
+                }
+                .into())
+            }
+        };
+        if !matches!(table.data_source_format, DataSourceFormat::Delta) {
+            return Err(UnityCatalogError::InvalidTable {
+                error_code: "NOT_A_DELTA_TABLE".to_string(),
+                message: format!("{} is not a delta table", table.full_name),
+            }
+            .into());
+        }
+        // managed tables are stored in the DBFS root, which is only accessible from a workspace
+        if table.storage_location.starts_with("dbfs:") {
+            return Err(UnityCatalogError::InvalidTable {
+                error_code: "UNSUPPORTED_LOCATION".to_string(),
+                message: format!(
+                    "{} is stored in DBFS at {}, which is not accessible outside of Databricks",
+                    table.full_name, table.storage_location
+                ),
+            }
+            .into());
+        }
+        Ok(table.storage_location)
+    }
+
+    /// The workspace hive metastore is a single catalog
+    async fn list_catalogs(&self) -> Result<Vec<String>, DataCatalogError> {
+        Ok(vec![HIVE_METASTORE_CATALOG.to_string()])
+    }
+
+    /// List the databases of the workspace hive metastore
+    async fn list_databases(
+        &self,
+        catalog_id: Option<String>,
+    ) -> Result<Vec<String>, DataCatalogError> {
+        check_catalog_id(catalog_id)?;
+        self.client.schema_names(HIVE_METASTORE_CATALOG).await
+    }
+
+    /// List the Delta tables of a database of the workspace hive metastore
+    async fn list_tables(
+        &self,
+        catalog_id: Option<String>,
+        database_name: &str,
+    ) -> Result<Vec<String>, DataCatalogError> {
+        check_catalog_id(catalog_id)?;
+        self.client
+            .delta_table_names(HIVE_METASTORE_CATALOG, database_name)
+            .await
+    }
+}
+
+impl std::fmt::Debug for DatabricksHiveMetastore {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "DatabricksHiveMetastore")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Body, Response};
+
+    use super::*;
+    use crate::data_catalog::client::mock_server::MockServer;
+    use crate::data_catalog::client::ClientOptions;
+
+    fn table_response(format: &str, location: &str) -> String {
+        format!(
+            r#"{{
+                "name": "events",
+                "data_source_format": "{format}",
+                "full_name": "hive_metastore.db.events",
+                "schema_name": "db",
+                "storage_location": "{location}",
+                "metastore_id": "1234"
+            }}"#
+        )
+    }
+
+    #[tokio::test]
+    async fn test_hive_metastore_location() {
+        let server = MockServer::new();
+        let catalog = UnityCatalogBuilder::new()
+            .with_workspace_url(server.url())
+            .with_access_token("token")
+            .with_client_options(ClientOptions::default().with_allow_http(true))
+            .build_hive_metastore()
+            .unwrap();
+
+        server.push_fn(|req| {
+            assert_eq!(
+                req.uri().path(),
+                "/api/2.1/unity-catalog/tables/hive_metastore.db.events"
+            );
+            assert_eq!(req.headers()["authorization"], "Bearer token");
+            Response::new(Body::from(table_response(
+                "DELTA",
+                "s3://bucket/warehouse/events",
+            )))
+        });
+        let location = catalog
+            .get_table_storage_location(None, "db", "events")
+            .await
+            .unwrap();
+        assert_eq!(location, "s3://bucket/warehouse/events");
+
+        server.push_fn(|_| {
+            Response::new(Body::from(table_response(
+                "PARQUET",
+                "s3://bucket/warehouse/events",
+            )))
+        });
+        assert!(catalog
+            .get_table_storage_location(None, "db", "events")
+            .await
+            .is_err());
+
+        server.push_fn(|_| {
+            Response::new(Body::from(table_response(
+                "DELTA",
+                "dbfs:/user/hive/warehouse/events",
+            )))
+        });
+        assert!(catalog
+            .get_table_storage_location(Some(HIVE_METASTORE_CATALOG.to_string()), "db", "events")
+            .await
+            .is_err());
+
+        assert!(matches!(
+            catalog
+                .get_table_storage_location(Some("main".to_string()), "db", "events")
+                .await,
+            Err(DataCatalogError::InvalidDataCatalog { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_hive_metastore_list_tables() {
+        let server = MockServer::new();
+        let catalog = UnityCatalogBuilder::new()
+            .with_workspace_url(server.url())
+            .with_access_token("token")
+            .with_client_options(ClientOptions::default().with_allow_http(true))
+            .build_hive_metastore()
+            .unwrap();
+
+        server.push_fn(|req| {
+            assert_eq!(req.uri().path(), "/api/2.1/unity-catalog/tables");
+            let query = req.uri().query().unwrap();
+            assert!(query.contains("catalog_name=hive_metastore"));
+            assert!(query.contains("schema_name=db"));
+            assert!(!query.contains("page_token"));
+            Response::new(Body::from(format!(
+                r#"{{"tables": [{}], "next_page_token": "page2"}}"#,
+                table_response("DELTA", "s3://bucket/warehouse/events")
+            )))
+        });
+        server.push_fn(|req| {
+            assert!(req.uri().query().unwrap().contains("page_token=page2"));
+            Response::new(Body::from(format!(
+                r#"{{"tables": [{}]}}"#,
+                table_response("PARQUET", "s3://bucket/warehouse/raw")
+            )))
+        });
+        let tables = catalog.list_tables(None, "db").await.unwrap();
+        assert_eq!(tables, vec!["events"]);
+
+        assert_eq!(
+            catalog.list_catalogs().await.unwrap(),
+            vec![HIVE_METASTORE_CATALOG]
+        );
+        assert!(matches!(
+            catalog.list_databases(Some("main".to_string())).await,
+            Err(DataCatalogError::InvalidDataCatalog { .. })
+        ));
+    }
+}
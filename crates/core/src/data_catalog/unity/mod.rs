@@ -5,10 +5,12 @@ use std::str::FromStr;
 
 use reqwest::header::{HeaderValue, AUTHORIZATION};
 
-use self::credential::{AzureCliCredential, ClientSecretOAuthProvider, CredentialProvider};
+use self::credential::{
+    AzureCliCredential, ClientSecretOAuthProvider, CredentialProvider, DatabricksOAuthM2MProvider,
+};
 use self::models::{
-    GetSchemaResponse, GetTableResponse, ListCatalogsResponse, ListSchemasResponse,
-    ListTableSummariesResponse,
+    DataSourceFormat, ErrorResponse, GetSchemaResponse, GetTableResponse, ListCatalogsResponse,
+    ListSchemasResponse, ListTableSummariesResponse, ListTablesResponse,
 };
 use super::client::retry::RetryExt;
 use super::{client::retry::RetryConfig, DataCatalog, DataCatalogError, DataCatalogResult};
@@ -17,8 +19,11 @@ use crate::storage::str_is_truthy;
 pub mod credential;
 #[cfg(feature = "datafusion")]
 pub mod datafusion;
+pub mod hive_metastore;
 pub mod models;
 
+pub use hive_metastore::{DatabricksHiveMetastore, HIVE_METASTORE_CATALOG};
+
 /// Possible errors from the unity-catalog/tables API call
 #[derive(thiserror::Error, Debug)]
 enum UnityCatalogError {
@@ -59,6 +64,24 @@ enum UnityCatalogError {
 
     #[error("Missing or corrupted federated token file for WorkloadIdentity.")]
     FederatedTokenFile,
+
+    /// Request returned an error response
+    #[error("Request failed: {error_code}: {message}")]
+    ErrorResponse {
+        /// Error code
+        error_code: String,
+        /// Error description
+        message: String,
+    },
+}
+
+impl From<ErrorResponse> for UnityCatalogError {
+    fn from(err: ErrorResponse) -> Self {
+        UnityCatalogError::ErrorResponse {
+            error_code: err.error_code,
+            message: err.message,
+        }
+    }
 }
 
 impl From<UnityCatalogError> for DataCatalogError {
@@ -361,7 +384,10 @@ impl UnityCatalogBuilder {
         self
     }
 
-    /// Sets the client secret for use in client secret flow
+    /// Sets the client secret for use in client secret flow.
+    ///
+    /// With an authority id the service principal authenticates against Azure AD, otherwise
+    /// through Databricks OAuth machine-to-machine authentication against the workspace.
     pub fn with_client_secret(mut self, client_secret: impl Into<String>) -> Self {
         self.client_secret = Some(client_secret.into());
         self
@@ -416,6 +442,20 @@ impl UnityCatalogBuilder {
                 )),
             ));
         }
+        if let (Some(client_id), Some(client_secret), Some(workspace_url)) = (
+            self.client_id.as_ref(),
+            self.client_secret.as_ref(),
+            self.workspace_url.as_ref(),
+        ) {
+            return Some(CredentialProvider::TokenCredential(
+                Default::default(),
+                Box::new(DatabricksOAuthM2MProvider::new(
+                    client_id,
+                    client_secret,
+                    workspace_url,
+                )),
+            ));
+        }
         if self.use_azure_cli {
             return Some(CredentialProvider::TokenCredential(
                 Default::default(),
@@ -449,6 +489,11 @@ impl UnityCatalogBuilder {
             retry_config: self.retry_config,
         })
     }
+
+    /// Build a [`DatabricksHiveMetastore`] resolving tables in the legacy workspace metastore
+    pub fn build_hive_metastore(self) -> DataCatalogResult<DatabricksHiveMetastore> {
+        Ok(DatabricksHiveMetastore::new(self.build()?))
+    }
 }
 
 /// Databricks Unity Catalog
@@ -591,6 +636,81 @@ impl UnityCatalog {
         Ok(resp.json().await?)
     }
 
+    /// Gets an array of all tables for the current metastore under the parent catalog and schema.
+    ///
+    /// The caller must be a metastore admin or an owner of (or have the SELECT privilege on) the
+    /// table. There is no guarantee of a specific ordering of the elements in the array.
+    pub async fn list_tables(
+        &self,
+        catalog_name: impl AsRef<str>,
+        schema_name: impl AsRef<str>,
+        page_token: Option<&str>,
+    ) -> DataCatalogResult<ListTablesResponse> {
+        let token = self.get_credential().await?;
+        // https://docs.databricks.com/api-explorer/workspace/tables/list
+        let mut query = vec![
+            ("catalog_name", catalog_name.as_ref()),
+            ("schema_name", schema_name.as_ref()),
+        ];
+        if let Some(page_token) = page_token {
+            query.push(("page_token", page_token));
+        }
+        let resp = self
+            .client
+            .get(format!("{}/tables", self.catalog_url()))
+            .query(&query)
+            .header(AUTHORIZATION, token)
+            .send_retry(&self.retry_config)
+            .await?;
+
+        Ok(resp.json().await?)
+    }
+
+    /// Names of the schemas of a catalog
+    pub(crate) async fn schema_names(&self, catalog_name: &str) -> DataCatalogResult<Vec<String>> {
+        match self.list_schemas(catalog_name).await? {
+            ListSchemasResponse::Success { schemas } => {
+                Ok(schemas.into_iter().map(|schema| schema.name).collect())
+            }
+            ListSchemasResponse::Error(err) => Err(UnityCatalogError::from(err).into()),
+        }
+    }
+
+    /// Names of the Delta tables of a schema, following all pages of the listing
+    pub(crate) async fn delta_table_names(
+        &self,
+        catalog_name: &str,
+        schema_name: &str,
+    ) -> DataCatalogResult<Vec<String>> {
+        let mut names = Vec::new();
+        let mut page_token = None;
+        loop {
+            match self
+                .list_tables(catalog_name, schema_name, page_token.as_deref())
+                .await?
+            {
+                ListTablesResponse::Success {
+                    tables,
+                    next_page_token,
+                } => {
+                    names.extend(
+                        tables
+                            .into_iter()
+                            .filter(|table| {
+                                matches!(table.data_source_format, DataSourceFormat::Delta)
+                            })
+                            .map(|table| table.name),
+                    );
+                    match next_page_token.filter(|token| !token.is_empty()) {
+                        Some(token) => page_token = Some(token),
+                        None => return Ok(names),
+                    }
+                }
+                ListTablesResponse::Error(err) => return Err(UnityCatalogError::from(err).into()),
+            }
+        }
+    }
+
     /// Gets a table from the metastore for a specific catalog and schema.
     ///
     /// The caller must be a metastore admin, be the owner of the table and have the
@@ -648,6 +768,35 @@ impl DataCatalog for UnityCatalog {
             .into()),
         }
     }
+
+    /// List the catalogs of the metastore
+    async fn list_catalogs(&self) -> Result<Vec<String>, DataCatalogError> {
+        match UnityCatalog::list_catalogs(self).await? {
+            ListCatalogsResponse::Success { catalogs } => {
+                Ok(catalogs.into_iter().map(|catalog| catalog.name).collect())
+            }
+            ListCatalogsResponse::Error(err) => Err(UnityCatalogError::from(err).into()),
+        }
+    }
+
+    /// List the schemas of a catalog, `main` by default
+    async fn list_databases(
+        &self,
+        catalog_id: Option<String>,
+    ) -> Result<Vec<String>, DataCatalogError> {
+        self.schema_names(catalog_id.as_deref().unwrap_or("main"))
+            .await
+    }
+
+    /// List the Delta tables of a schema
+    async fn list_tables(
+        &self,
+        catalog_id: Option<String>,
+        database_name: &str,
+    ) -> Result<Vec<String>, DataCatalogError> {
+        self.delta_table_names(catalog_id.as_deref().unwrap_or("main"), database_name)
+            .await
+    }
 }
 
 impl std::fmt::Debug for UnityCatalog {
@@ -75,6 +75,22 @@ pub enum ListTableSummariesResponse {
     Error(ErrorResponse),
 }
 
+/// List tables response
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum ListTablesResponse {
+    /// Successful response
+    Success {
+        /// The tables within the parent schema
+        #[serde(default)]
+        tables: Vec<Table>,
+        /// Continuation token
+        next_page_token: Option<String>,
+    },
+    /// Error response
+    Error(ErrorResponse),
+}
+
 #[derive(Deserialize, Default)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[allow(missing_docs)]
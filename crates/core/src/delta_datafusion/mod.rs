@@ -40,9 +40,8 @@ use arrow_cast::display::array_value_to_string;
 use arrow_schema::Field;
 use async_trait::async_trait;
 use chrono::{NaiveDateTime, TimeZone, Utc};
-use datafusion::datasource::file_format::{parquet::ParquetFormat, FileFormat};
 use datafusion::datasource::physical_plan::{
-    wrap_partition_type_in_dict, wrap_partition_value_in_dict, FileScanConfig,
+    wrap_partition_type_in_dict, wrap_partition_value_in_dict, FileScanConfig, ParquetExec,
 };
 use datafusion::datasource::provider::TableProviderFactory;
 use datafusion::datasource::{listing::PartitionedFile, MemTable, TableProvider, TableType};
@@ -57,14 +56,15 @@ use datafusion::physical_plan::{
     DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream,
     Statistics,
 };
+use datafusion_common::cast::as_boolean_array;
 use datafusion_common::scalar::ScalarValue;
 use datafusion_common::tree_node::{TreeNode, TreeNodeVisitor, VisitRecursion};
 use datafusion_common::{
-    Column, DFSchema, DataFusionError, Result as DataFusionResult, ToDFSchema,
+    Column, ColumnStatistics, DFSchema, DataFusionError, Result as DataFusionResult, ToDFSchema,
 };
 use datafusion_expr::expr::ScalarFunction;
 use datafusion_expr::logical_plan::CreateExternalTable;
-use datafusion_expr::utils::conjunction;
+use datafusion_expr::utils::{conjunction, split_conjunction};
 use datafusion_expr::{col, Expr, Extension, LogicalPlan, TableProviderFilterPushDown, Volatility};
 use datafusion_physical_expr::execution_props::ExecutionProps;
 use datafusion_physical_expr::{create_physical_expr, PhysicalExpr};
@@ -82,8 +82,10 @@ use url::Url;
 use crate::delta_datafusion::expr::parse_predicate_expression;
 use crate::errors::{DeltaResult, DeltaTableError};
 use crate::kernel::{Add, DataCheck, EagerSnapshot, Invariant, Snapshot};
-use crate::logstore::LogStoreRef;
+use crate::logstore::{logstore_for, LogStoreRef};
+use crate::storage::footer_cache::{CachingParquetFileReaderFactory, ParquetFooterCache};
 use crate::table::builder::ensure_table_uri;
+use crate::table::file_tags::{self, FileTagFilter};
 use crate::table::state::DeltaTableState;
 use crate::table::Constraint;
 use crate::{open_table, open_table_with_storage_options, DeltaTable};
@@ -93,6 +95,7 @@ const PATH_COLUMN: &str = "__delta_rs_path";
 pub mod expr;
 pub mod logical;
 pub mod physical;
+pub mod udtf;
 
 impl From<DeltaTableError> for DataFusionError {
     fn from(err: DeltaTableError) -> Self {
@@ -342,6 +345,10 @@ pub struct DeltaScanConfigBuilder {
     /// If include_file_column is true and the name is None then it will be auto-generated
     /// Otherwise the user provided name will be used
     file_column_name: Option<String>,
+    /// Only scan files whose tags match all filters
+    file_tag_filters: Vec<FileTagFilter>,
+    /// Scan every file in its own partition
+    partition_per_file: bool,
 }
 
 impl DeltaScanConfigBuilder {
@@ -365,6 +372,19 @@ impl DeltaScanConfigBuilder {
         self
     }
 
+    /// Only scan files whose tags match `filter`, in addition to the filters set before
+    pub fn with_file_tag_filter(mut self, filter: FileTagFilter) -> Self {
+        self.file_tag_filters.push(filter);
+        self
+    }
+
+    /// Scan every data file in its own partition rather than grouping files by their partition
+    /// values, so that distributed schedulers can run an independent task per file
+    pub fn with_partition_per_file(mut self, partition_per_file: bool) -> Self {
+        self.partition_per_file = partition_per_file;
+        self
+    }
+
     /// Build a DeltaScanConfig and ensure no column name conflicts occur during downstream processing
     pub fn build(&self, snapshot: &DeltaTableState) -> DeltaResult<DeltaScanConfig> {
         let input_schema = snapshot.input_schema()?;
@@ -401,7 +421,11 @@ impl DeltaScanConfigBuilder {
             }
         }
 
-        Ok(DeltaScanConfig { file_column_name })
+        Ok(DeltaScanConfig {
+            file_column_name,
+            file_tag_filters: self.file_tag_filters.clone(),
+            partition_per_file: self.partition_per_file,
+        })
     }
 }
 
@@ -410,6 +434,12 @@ impl DeltaScanConfigBuilder {
 pub struct DeltaScanConfig {
     /// Include the source path for each record
     pub file_column_name: Option<String>,
+    /// Only scan files whose tags match all filters
+    #[serde(default)]
+    pub file_tag_filters: Vec<FileTagFilter>,
+    /// Scan every data file in its own partition
+    #[serde(default)]
+    pub partition_per_file: bool,
 }
 
 #[derive(Debug)]
@@ -491,9 +521,20 @@ impl<'a> DeltaScanBuilder<'a> {
             logical_schema
         };
 
-        let logical_filter = self
+        // filters on partition columns are evaluated exactly on the partition values of the
+        // files, their columns may not be part of the projection
+        let table_partition_cols = &self.snapshot.metadata().partition_columns;
+        let (partition_filters, filters): (Vec<Expr>, Vec<Expr>) = self
             .filter
-            .map(|expr| logical_expr_to_physical_expr(&expr, &logical_schema));
+            .as_ref()
+            .map(split_conjunction)
+            .unwrap_or_default()
+            .into_iter()
+            .cloned()
+            .partition(|expr| is_partition_filter(expr, table_partition_cols));
+
+        let logical_filter =
+            conjunction(filters).map(|expr| logical_expr_to_physical_expr(&expr, &logical_schema));
 
         // Perform Pruning of files to scan
         let files = match self.files {
@@ -522,14 +563,26 @@ impl<'a> DeltaScanBuilder<'a> {
                 }
             }
         };
+        let files = match conjunction(partition_filters) {
+            Some(predicate) => {
+                filter_by_partition_values(files, &predicate, table_partition_cols, &schema)?
+            }
+            None => files,
+        };
+        let files = if config.file_tag_filters.is_empty() {
+            files
+        } else {
+            files
+                .into_iter()
+                .filter(|add| file_tags::matches_all(&config.file_tag_filters, add.tags.as_ref()))
+                .collect()
+        };
 
         // TODO we group files together by their partition values. If the table is partitioned
         // and partitions are somewhat evenly distributed, probably not the worst choice ...
         // However we may want to do some additional balancing in case we are far off from the above.
         let mut file_groups: HashMap<Vec<ScalarValue>, Vec<PartitionedFile>> = HashMap::new();
 
-        let table_partition_cols = &self.snapshot.metadata().partition_columns;
-
         for action in files.iter() {
             let mut part = partitioned_file_from_action(action, table_partition_cols, &schema);
 
@@ -540,10 +593,12 @@ impl<'a> DeltaScanBuilder<'a> {
                     ))));
             }
 
-            file_groups
-                .entry(part.partition_values.clone())
-                .or_default()
-                .push(part);
+            let key = if config.partition_per_file {
+                vec![ScalarValue::Utf8(Some(action.path.clone()))]
+            } else {
+                part.partition_values.clone()
+            };
+            file_groups.entry(key).or_default().push(part);
         }
 
         let file_schema = Arc::new(ArrowSchema::new(
@@ -568,14 +623,30 @@ impl<'a> DeltaScanBuilder<'a> {
             ));
         }
 
-        let stats = self
+        let mut stats = self
             .snapshot
             .datafusion_table_statistics()
             .unwrap_or(Statistics::new_unknown(&schema));
+        // the statistics of the snapshot only bound the statistics of a subset of its files
+        if files.len() != self.snapshot.files_count()? {
+            stats = stats.into_inexact();
+        }
+        if config.file_column_name.is_some() {
+            stats
+                .column_statistics
+                .push(ColumnStatistics::new_unknown());
+        }
 
-        let scan = ParquetFormat::new()
-            .create_physical_plan(
-                self.state,
+        // same as `ParquetFormat::create_physical_plan`, but footers are served from the
+        // process-wide footer cache
+        let parquet_options = &self.state.config_options().execution.parquet;
+        let predicate = if parquet_options.pruning {
+            logical_filter
+        } else {
+            None
+        };
+        let scan = Arc::new(
+            ParquetExec::new(
                 FileScanConfig {
                     object_store_url: self.log_store.object_store_url(),
                     file_schema,
@@ -586,9 +657,16 @@ impl<'a> DeltaScanBuilder<'a> {
                     table_partition_cols,
                     output_ordering: vec![],
                 },
-                logical_filter.as_ref(),
+                predicate,
+                parquet_options.metadata_size_hint,
             )
-            .await?;
+            .with_parquet_file_reader_factory(Arc::new(
+                CachingParquetFileReaderFactory::new(
+                    self.log_store.object_store(),
+                    ParquetFooterCache::global(),
+                ),
+            )),
+        );
 
         Ok(DeltaScan {
             table_uri: ensure_table_uri(self.log_store.root_uri())?.as_str().into(),
@@ -644,9 +722,12 @@ impl TableProvider for DeltaTable {
 
     fn supports_filter_pushdown(
         &self,
-        _filter: &Expr,
+        filter: &Expr,
     ) -> DataFusionResult<TableProviderFilterPushDown> {
-        Ok(TableProviderFilterPushDown::Inexact)
+        Ok(filter_pushdown(
+            filter,
+            &self.snapshot()?.metadata().partition_columns,
+        ))
     }
 
     fn statistics(&self) -> Option<Statistics> {
@@ -660,6 +741,7 @@ pub struct DeltaTableProvider {
     log_store: LogStoreRef,
     config: DeltaScanConfig,
     schema: Arc<ArrowSchema>,
+    files: Option<Vec<Add>>,
 }
 
 impl DeltaTableProvider {
@@ -674,8 +756,15 @@ impl DeltaTableProvider {
             snapshot,
             log_store,
             config,
+            files: None,
         })
     }
+
+    /// Only scan `files` instead of the files of the snapshot
+    pub(crate) fn with_files(mut self, files: Vec<Add>) -> Self {
+        self.files = Some(files);
+        self
+    }
 }
 
 #[async_trait]
@@ -710,26 +799,37 @@ impl TableProvider for DeltaTableProvider {
         register_store(self.log_store.clone(), session.runtime_env().clone());
         let filter_expr = conjunction(filters.iter().cloned());
 
-        let scan = DeltaScanBuilder::new(&self.snapshot, self.log_store.clone(), session)
+        let mut scan = DeltaScanBuilder::new(&self.snapshot, self.log_store.clone(), session)
             .with_projection(projection)
             .with_limit(limit)
             .with_filter(filter_expr)
-            .with_scan_config(self.config.clone())
-            .build()
-            .await?;
+            .with_scan_config(self.config.clone());
+        if let Some(files) = &self.files {
+            scan = scan.with_files(files);
+        }
+        let scan = scan.build().await?;
 
         Ok(Arc::new(scan))
     }
 
     fn supports_filter_pushdown(
         &self,
-        _filter: &Expr,
+        filter: &Expr,
     ) -> DataFusionResult<TableProviderFilterPushDown> {
-        Ok(TableProviderFilterPushDown::Inexact)
+        Ok(filter_pushdown(
+            filter,
+            &self.snapshot.metadata().partition_columns,
+        ))
     }
 
     fn statistics(&self) -> Option<Statistics> {
-        self.snapshot.datafusion_table_statistics()
+        let mut stats = self.snapshot.datafusion_table_statistics()?;
+        if self.config.file_column_name.is_some() {
+            stats
+                .column_statistics
+                .push(ColumnStatistics::new_unknown());
+        }
+        Some(stats)
     }
 }
 
@@ -785,7 +885,18 @@ impl ExecutionPlan for DeltaScan {
         self: Arc<Self>,
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
-        ExecutionPlan::with_new_children(self.parquet_scan.clone(), children)
+        if children.len() != 1 {
+            return Err(DataFusionError::Plan(format!(
+                "DeltaScan wraps a single plan, got {} children",
+                children.len()
+            )));
+        }
+        Ok(Arc::new(DeltaScan {
+            table_uri: self.table_uri.clone(),
+            config: self.config.clone(),
+            parquet_scan: children[0].clone(),
+            logical_schema: self.logical_schema.clone(),
+        }))
     }
 
     fn execute(
@@ -862,12 +973,13 @@ pub(crate) fn get_null_of_arrow_type(t: &ArrowDataType) -> DeltaResult<ScalarVal
     }
 }
 
-pub(crate) fn partitioned_file_from_action(
+/// The values of the partition columns of the file added by `action`
+fn partition_values_from_action(
     action: &Add,
     partition_columns: &[String],
     schema: &ArrowSchema,
-) -> PartitionedFile {
-    let partition_values = partition_columns
+) -> Vec<ScalarValue> {
+    partition_columns
         .iter()
         .map(|part| {
             action
@@ -890,7 +1002,15 @@ pub(crate) fn partitioned_file_from_action(
                 })
                 .unwrap_or(ScalarValue::Null)
         })
-        .collect::<Vec<_>>();
+        .collect()
+}
+
+pub(crate) fn partitioned_file_from_action(
+    action: &Add,
+    partition_columns: &[String],
+    schema: &ArrowSchema,
+) -> PartitionedFile {
+    let partition_values = partition_values_from_action(action, partition_columns, schema);
 
     let ts_secs = action.modification_time / 1000;
     let ts_ns = (action.modification_time % 1000) * 1_000_000;
@@ -1115,6 +1235,10 @@ impl DeltaDataChecker {
 }
 
 /// A codec for deltalake physical plans
+///
+/// The parquet scan below a [DeltaScan] is encoded by `datafusion-proto`, which cannot decode
+/// scans with partition columns yet, so only scans of unpartitioned tables can be shipped to
+/// executors.
 #[derive(Debug)]
 pub struct DeltaPhysicalCodec {}
 
@@ -1157,6 +1281,32 @@ impl PhysicalExtensionCodec for DeltaPhysicalCodec {
     }
 }
 
+/// Register the object stores of the tables read by the [DeltaScan]s in `plan` with `env`,
+/// unless a store is registered already.
+///
+/// Executors of distributed schedulers deserializing plans with the [DeltaPhysicalCodec] have
+/// no stores registered for the tables. The stores are created for the table uri with the
+/// storage options from the environment, so the factories for the schemes of the tables must be
+/// registered in the executor process, e.g. with `deltalake::aws::register_handlers`.
+pub fn register_scan_stores(
+    plan: &Arc<dyn ExecutionPlan>,
+    env: Arc<RuntimeEnv>,
+) -> DeltaResult<()> {
+    if let Some(scan) = plan.as_any().downcast_ref::<DeltaScan>() {
+        let location = Url::parse(&scan.table_uri)
+            .map_err(|_| DeltaTableError::InvalidTableLocation(scan.table_uri.clone()))?;
+        let object_store_url = crate::logstore::object_store_url(&location);
+        if env.object_store(&object_store_url).is_err() {
+            let log_store = logstore_for(location, HashMap::<String, String>::new())?;
+            register_store(log_store, env.clone());
+        }
+    }
+    for child in plan.children() {
+        register_scan_stores(&child, env.clone())?;
+    }
+    Ok(())
+}
+
 /// Does serde on DeltaTables
 #[derive(Debug)]
 pub struct DeltaLogicalCodec {}
@@ -1300,6 +1450,85 @@ impl TreeNodeVisitor for FindFilesExprProperties {
     }
 }
 
+/// Whether `expr` only references partition columns and is deterministic, so that it can be
+/// evaluated exactly on the partition values of the files
+pub(crate) fn is_partition_filter(expr: &Expr, partition_columns: &[String]) -> bool {
+    if partition_columns.is_empty() {
+        return false;
+    }
+    let mut expr_properties = FindFilesExprProperties {
+        partition_only: true,
+        partition_columns: partition_columns.to_vec(),
+        result: Ok(()),
+    };
+    TreeNode::visit(expr, &mut expr_properties).is_ok()
+        && expr_properties.result.is_ok()
+        && expr_properties.partition_only
+}
+
+/// Keep the files whose partition values match `predicate`, which only references partition
+/// columns.
+///
+/// The predicate is evaluated on the partition values of the given files only, i.e. after
+/// they were pruned with the other filters of the scan.
+fn filter_by_partition_values(
+    files: Vec<Add>,
+    predicate: &Expr,
+    partition_columns: &[String],
+    schema: &ArrowSchema,
+) -> DeltaResult<Vec<Add>> {
+    if files.is_empty() {
+        return Ok(files);
+    }
+    let partition_schema = Arc::new(ArrowSchema::new(
+        partition_columns
+            .iter()
+            .map(|name| schema.field_with_name(name).cloned())
+            .collect::<Result<Vec<_>, _>>()?,
+    ));
+    let values = files
+        .iter()
+        .map(|action| partition_values_from_action(action, partition_columns, schema))
+        .collect::<Vec<_>>();
+    let columns = partition_schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let null = ScalarValue::try_from(field.data_type())?;
+            ScalarValue::iter_to_array(values.iter().map(|values| match &values[idx] {
+                ScalarValue::Null => null.clone(),
+                value => value.clone(),
+            }))
+        })
+        .collect::<DataFusionResult<Vec<_>>>()?;
+    let batch = RecordBatch::try_new(partition_schema.clone(), columns)?;
+
+    let predicate = create_physical_expr(
+        predicate,
+        &partition_schema.to_dfschema()?,
+        &ExecutionProps::new(),
+    )?;
+    let matches = predicate.evaluate(&batch)?.into_array(batch.num_rows())?;
+    let matches = as_boolean_array(&matches)?;
+    Ok(files
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| matches.is_valid(*idx) && matches.value(*idx))
+        .map(|(_, action)| action)
+        .collect())
+}
+
+/// Filters on partition columns are applied exactly by the scan, others are only used to
+/// prune files and row groups
+fn filter_pushdown(expr: &Expr, partition_columns: &[String]) -> TableProviderFilterPushDown {
+    if is_partition_filter(expr, partition_columns) {
+        TableProviderFilterPushDown::Exact
+    } else {
+        TableProviderFilterPushDown::Inexact
+    }
+}
+
 /// Representing the result of the [find_files] function.
 pub struct FindFiles {
     /// A list of `Add` objects that match the given predicate
@@ -1360,11 +1589,25 @@ pub(crate) async fn find_files_scan<'a>(
     state: &SessionState,
     expression: Expr,
 ) -> DeltaResult<Vec<Add>> {
-    let candidate_map: HashMap<String, Add> = snapshot
-        .file_actions()?
-        .iter()
-        .map(|add| (add.path.clone(), add.to_owned()))
-        .collect();
+    scan_files_matching(snapshot, log_store, state, expression, None).await
+}
+
+/// Scan the `candidates`, or all files of the snapshot, and return the files containing rows
+/// matching `expression`
+pub(crate) async fn scan_files_matching(
+    snapshot: &DeltaTableState,
+    log_store: LogStoreRef,
+    state: &SessionState,
+    expression: Expr,
+    candidates: Option<&[Add]>,
+) -> DeltaResult<Vec<Add>> {
+    let candidate_map: HashMap<String, Add> = match candidates {
+        Some(candidates) => candidates.to_vec(),
+        None => snapshot.file_actions()?,
+    }
+    .into_iter()
+    .map(|add| (add.path.clone(), add))
+    .collect();
 
     let scan_config = DeltaScanConfigBuilder {
         include_file_column: true,
@@ -1383,12 +1626,14 @@ pub(crate) async fn find_files_scan<'a>(
     // Add path column
     used_columns.push(logical_schema.index_of(scan_config.file_column_name.as_ref().unwrap())?);
 
-    let scan = DeltaScanBuilder::new(snapshot, log_store, state)
+    let mut scan = DeltaScanBuilder::new(snapshot, log_store, state)
         .with_filter(Some(expression.clone()))
         .with_projection(Some(&used_columns))
-        .with_scan_config(scan_config)
-        .build()
-        .await?;
+        .with_scan_config(scan_config);
+    if let Some(candidates) = candidates {
+        scan = scan.with_files(candidates);
+    }
+    let scan = scan.build().await?;
     let scan = Arc::new(scan);
 
     let config = &scan.config;
@@ -1563,9 +1808,9 @@ pub struct DeltaSessionContext {
 
 impl Default for DeltaSessionContext {
     fn default() -> Self {
-        DeltaSessionContext {
-            inner: SessionContext::new_with_config(DeltaSessionConfig::default().into()),
-        }
+        let inner = SessionContext::new_with_config(DeltaSessionConfig::default().into());
+        udtf::register_table_functions(&inner);
+        DeltaSessionContext { inner }
     }
 }
 
@@ -1750,6 +1995,49 @@ mod tests {
         assert_eq!(file.partition_values, ref_file.partition_values)
     }
 
+    #[tokio::test]
+    async fn test_filter_by_partition_values() {
+        use datafusion_expr::{cast, lit};
+
+        let table = crate::open_table("../test/tests/data/delta-0.8.0-partitioned")
+            .await
+            .unwrap();
+        let snapshot = table.snapshot().unwrap();
+        let schema = snapshot.arrow_schema().unwrap();
+        let partition_columns = &snapshot.metadata().partition_columns;
+        let predicate = col("year")
+            .eq(lit("2021"))
+            .and(cast(col("month"), ArrowDataType::Int32).eq(lit(12)));
+
+        let mut expected = scan_memory_table(snapshot, &predicate)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|add| add.path)
+            .collect::<Vec<_>>();
+        expected.sort();
+        let files = snapshot.file_actions().unwrap();
+        let mut actual =
+            filter_by_partition_values(files.clone(), &predicate, partition_columns, &schema)
+                .unwrap()
+                .into_iter()
+                .map(|add| add.path)
+                .collect::<Vec<_>>();
+        actual.sort();
+        assert_eq!(actual.len(), 2);
+        assert_eq!(actual, expected);
+
+        // only the given candidates are evaluated
+        let candidates = files
+            .into_iter()
+            .filter(|add| add.path != expected[0])
+            .collect();
+        let actual =
+            filter_by_partition_values(candidates, &predicate, partition_columns, &schema).unwrap();
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].path, expected[1]);
+    }
+
     #[tokio::test]
     async fn test_enforce_invariants() {
         let schema = Arc::new(Schema::new(vec![
@@ -1846,6 +2134,59 @@ mod tests {
         assert_eq!(format!("{exec_plan:?}"), format!("{result_exec_plan:?}"));
     }
 
+    #[tokio::test]
+    async fn delta_scan_distributed() {
+        let table = crate::open_table("../test/tests/data/simple_table")
+            .await
+            .unwrap();
+        let config = DeltaScanConfigBuilder::new()
+            .with_partition_per_file(true)
+            .build(table.snapshot().unwrap())
+            .unwrap();
+        let ctx = SessionContext::new();
+        let state = ctx.state();
+        let scan = DeltaScanBuilder::new(table.snapshot().unwrap(), table.log_store(), &state)
+            .with_scan_config(config)
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(
+            scan.output_partitioning().partition_count(),
+            table.snapshot().unwrap().files_count().unwrap()
+        );
+        let scan: Arc<dyn ExecutionPlan> = Arc::new(scan);
+        register_store(table.log_store(), ctx.runtime_env());
+        let expected = datafusion::physical_plan::collect(scan.clone(), ctx.task_ctx())
+            .await
+            .unwrap();
+
+        // rewriting the parquet scan keeps the delta scan
+        let rewritten = scan.clone().with_new_children(scan.children()).unwrap();
+        assert!(rewritten.as_any().downcast_ref::<DeltaScan>().is_some());
+
+        // an executor registers the stores of the deserialized scans
+        let bytes = datafusion_proto::bytes::physical_plan_to_bytes_with_extension_codec(
+            scan,
+            &DeltaPhysicalCodec {},
+        )
+        .unwrap();
+        let ctx = SessionContext::new();
+        let scan = datafusion_proto::bytes::physical_plan_from_bytes_with_extension_codec(
+            &bytes,
+            &ctx,
+            &DeltaPhysicalCodec {},
+        )
+        .unwrap();
+        register_scan_stores(&scan, ctx.runtime_env()).unwrap();
+        let actual = datafusion::physical_plan::collect(scan, ctx.task_ctx())
+            .await
+            .unwrap();
+        assert_eq!(
+            actual.iter().map(|b| b.num_rows()).sum::<usize>(),
+            expected.iter().map(|b| b.num_rows()).sum::<usize>()
+        );
+    }
+
     #[tokio::test]
     async fn delta_table_provider_with_config() {
         let table = crate::open_table("../test/tests/data/delta-2.2.0-partitioned-types")
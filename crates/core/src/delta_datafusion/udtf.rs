@@ -0,0 +1,1069 @@
+//! Table functions to inspect Delta tables from SQL.
+//!
+//! [`register_table_functions`] registers the following functions with a [`SessionContext`],
+//! sessions created from a [`DeltaSessionContext`](super::DeltaSessionContext) have them
+//! registered already:
+//!
+//! - `delta_history(uri [, limit])`: the commits of the table, most recent first, optionally
+//!   only the last `limit` ones
+//! - `delta_file_metadata(uri [, version])`: the data files of the latest or the given version
+//!   of the table
+//! - `delta_cdf(uri, starting_version [, ending_version])`: the rows changed by the commits
+//!   from `starting_version` up to `ending_version` or the latest version, see [`DeltaCdfFunction`]
+//!
+//! The table is loaded when the query is executed, not when it is planned. Only the schema of
+//! the table read by `delta_cdf` is loaded while planning, as it determines the columns of the
+//! function, which requires a multi-threaded tokio runtime.
+//!
+//! # Example
+//! ```rust ignore
+//! let ctx: SessionContext = DeltaSessionContext::default().into();
+//! let batches = ctx
+//!     .sql("SELECT version, operation FROM delta_history('s3://bucket/table', 10)")
+//!     .await?
+//!     .collect()
+//!     .await?;
+//! ````
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, Schema as ArrowSchema, SchemaRef, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use arrow_array::{
+    new_null_array, ArrayRef, BooleanArray, Int64Array, StringArray, TimestampMillisecondArray,
+};
+use arrow_select::filter::filter_record_batch;
+use async_trait::async_trait;
+use datafusion::datasource::function::TableFunctionImpl;
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::execution::context::{SessionContext, SessionState, TaskContext};
+use datafusion::physical_plan::memory::MemoryExec;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::streaming::{PartitionStream, StreamingTableExec};
+use datafusion::physical_plan::{ExecutionPlan, SendableRecordBatchStream};
+use datafusion_common::{DataFusionError, Result as DataFusionResult, ScalarValue};
+use datafusion_expr::Expr;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use indexmap::IndexMap;
+use object_store::path::Path;
+use parquet::arrow::async_reader::{ParquetObjectReader, ParquetRecordBatchStreamBuilder};
+use tokio::runtime::{Handle, RuntimeFlavor};
+use url::Url;
+
+use super::{to_correct_scalar_value, DataFusionMixins};
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::kernel::{Action, CommitInfo, DeletionVectorDescriptor};
+use crate::logstore::{get_actions, LogStoreRef};
+use crate::operations::compact_deletion_vectors::table_root;
+use crate::storage::external::external_file_path;
+use crate::table::config::{DeltaConfigKey, TableConfig};
+use crate::{open_table, open_table_with_version, DeltaTableBuilder};
+
+/// Name of the table function listing the commits of a table
+pub const DELTA_HISTORY: &str = "delta_history";
+
+/// Name of the table function listing the data files of a table
+pub const DELTA_FILE_METADATA: &str = "delta_file_metadata";
+
+/// Name of the table function reading the change data feed of a table
+pub const DELTA_CDF: &str = "delta_cdf";
+
+/// Number of commits read concurrently by `delta_history`
+const HISTORY_CONCURRENCY: usize = 16;
+
+/// Column of `delta_cdf` holding the kind of change of a row
+pub const CHANGE_TYPE_COL: &str = "_change_type";
+
+/// Column of `delta_cdf` holding the version of the commit which changed a row
+pub const COMMIT_VERSION_COL: &str = "_commit_version";
+
+/// Column of `delta_cdf` holding the timestamp of the commit which changed a row
+pub const COMMIT_TIMESTAMP_COL: &str = "_commit_timestamp";
+
+/// Register the Delta table functions with `ctx`, see the [module documentation](self)
+pub fn register_table_functions(ctx: &SessionContext) {
+    ctx.register_udtf(DELTA_HISTORY, Arc::new(DeltaHistoryFunction {}));
+    ctx.register_udtf(DELTA_FILE_METADATA, Arc::new(DeltaFileMetadataFunction {}));
+    ctx.register_udtf(DELTA_CDF, Arc::new(DeltaCdfFunction {}));
+}
+
+/// Implementation of `delta_history(uri [, limit])`
+#[derive(Debug, Default)]
+pub struct DeltaHistoryFunction {}
+
+impl TableFunctionImpl for DeltaHistoryFunction {
+    fn call(&self, args: &[Expr]) -> DataFusionResult<Arc<dyn TableProvider>> {
+        let uri = string_arg(DELTA_HISTORY, args)?;
+        let limit = int_arg(DELTA_HISTORY, args, "limit")?
+            .map(|limit| {
+                usize::try_from(limit).map_err(|_| {
+                    DataFusionError::Plan(format!("{DELTA_HISTORY}: limit must not be negative"))
+                })
+            })
+            .transpose()?;
+        Ok(Arc::new(DeltaMetadataTable {
+            uri,
+            kind: MetadataKind::History { limit },
+        }))
+    }
+}
+
+/// Implementation of `delta_file_metadata(uri [, version])`
+#[derive(Debug, Default)]
+pub struct DeltaFileMetadataFunction {}
+
+impl TableFunctionImpl for DeltaFileMetadataFunction {
+    fn call(&self, args: &[Expr]) -> DataFusionResult<Arc<dyn TableProvider>> {
+        let uri = string_arg(DELTA_FILE_METADATA, args)?;
+        let version = int_arg(DELTA_FILE_METADATA, args, "version")?;
+        Ok(Arc::new(DeltaMetadataTable {
+            uri,
+            kind: MetadataKind::FileMetadata { version },
+        }))
+    }
+}
+
+/// Implementation of `delta_cdf(uri, starting_version [, ending_version])`
+///
+/// Returns the columns of the table followed by [`CHANGE_TYPE_COL`], [`COMMIT_VERSION_COL`] and
+/// [`COMMIT_TIMESTAMP_COL`]. The rows of a commit are read from its change data files if it has
+/// any, with the change types recorded by the writer, e.g. `update_preimage`. Otherwise the rows
+/// of the data files added and removed by the commit are returned as `insert` and `delete`, and
+/// for files whose deletion vector changed only the rows it newly marks are returned as `delete`.
+///
+/// Reading the changes fails if `delta.enableChangeDataFeed` was not set for any of the commits.
+/// The changes are streamed file by file when the query is executed.
+#[derive(Debug, Default)]
+pub struct DeltaCdfFunction {}
+
+impl TableFunctionImpl for DeltaCdfFunction {
+    fn call(&self, args: &[Expr]) -> DataFusionResult<Arc<dyn TableProvider>> {
+        let uri = string_arg(DELTA_CDF, args)?;
+        let (starting_version, ending_version) = match args {
+            [_, start] => (int_value(DELTA_CDF, start, "starting_version")?, None),
+            [_, start, end] => (
+                int_value(DELTA_CDF, start, "starting_version")?,
+                Some(int_value(DELTA_CDF, end, "ending_version")?),
+            ),
+            _ => {
+                return Err(DataFusionError::Plan(format!(
+                    "{DELTA_CDF}: expected the table uri, the starting version and an optional ending version"
+                )))
+            }
+        };
+        if starting_version < 0 || ending_version.is_some_and(|end| end < starting_version) {
+            return Err(DataFusionError::Plan(format!(
+                "{DELTA_CDF}: ending_version must not be less than starting_version, which must not be negative"
+            )));
+        }
+        let table_schema = block_on(async {
+            let table = DeltaTableBuilder::from_uri(&uri)
+                .without_files()
+                .load()
+                .await?;
+            table.snapshot()?.input_schema()
+        })??;
+        Ok(Arc::new(DeltaCdfTable::new(
+            uri,
+            starting_version,
+            ending_version,
+            table_schema,
+        )))
+    }
+}
+
+/// Run `future` to completion from the synchronous planning of a table function.
+///
+/// Planning can't yield to the runtime, so the current thread is handed over to blocking work
+/// while the future runs, which requires a multi-threaded tokio runtime.
+fn block_on<F: Future>(future: F) -> DataFusionResult<F::Output> {
+    match Handle::try_current() {
+        Ok(handle) if matches!(handle.runtime_flavor(), RuntimeFlavor::MultiThread) => {
+            Ok(tokio::task::block_in_place(move || handle.block_on(future)))
+        }
+        _ => Err(DataFusionError::Plan(format!(
+            "{DELTA_CDF}: reading the table schema requires a multi-threaded tokio runtime"
+        ))),
+    }
+}
+
+/// The table uri, which must be the first argument
+fn string_arg(function: &str, args: &[Expr]) -> DataFusionResult<String> {
+    match args.first() {
+        Some(Expr::Literal(ScalarValue::Utf8(Some(uri))))
+        | Some(Expr::Literal(ScalarValue::LargeUtf8(Some(uri)))) => Ok(uri.clone()),
+        _ => Err(DataFusionError::Plan(format!(
+            "{function}: the first argument must be the table uri as a string literal"
+        ))),
+    }
+}
+
+/// The optional integer second argument
+fn int_arg(function: &str, args: &[Expr], name: &str) -> DataFusionResult<Option<i64>> {
+    match args {
+        [_] => Ok(None),
+        [_, value] => int_value(function, value, name).map(Some),
+        _ => Err(DataFusionError::Plan(format!(
+            "{function}: expected the table uri and an optional {name}"
+        ))),
+    }
+}
+
+fn int_value(function: &str, expr: &Expr, name: &str) -> DataFusionResult<i64> {
+    match expr {
+        Expr::Literal(ScalarValue::Int8(Some(v))) => Ok(*v as i64),
+        Expr::Literal(ScalarValue::Int16(Some(v))) => Ok(*v as i64),
+        Expr::Literal(ScalarValue::Int32(Some(v))) => Ok(*v as i64),
+        Expr::Literal(ScalarValue::Int64(Some(v))) => Ok(*v),
+        _ => Err(DataFusionError::Plan(format!(
+            "{function}: {name} must be an integer literal"
+        ))),
+    }
+}
+
+#[derive(Debug, Clone)]
+enum MetadataKind {
+    History { limit: Option<usize> },
+    FileMetadata { version: Option<i64> },
+}
+
+impl MetadataKind {
+    fn schema(&self) -> SchemaRef {
+        let timestamp = DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into()));
+        let fields = match self {
+            Self::History { .. } => vec![
+                Field::new("version", DataType::Int64, false),
+                Field::new("timestamp", timestamp, true),
+                Field::new("operation", DataType::Utf8, true),
+                Field::new("operation_parameters", DataType::Utf8, true),
+                Field::new("user_name", DataType::Utf8, true),
+                Field::new("read_version", DataType::Int64, true),
+                Field::new("is_blind_append", DataType::Boolean, true),
+                Field::new("engine_info", DataType::Utf8, true),
+                Field::new("info", DataType::Utf8, true),
+            ],
+            Self::FileMetadata { .. } => vec![
+                Field::new("path", DataType::Utf8, false),
+                Field::new("size_bytes", DataType::Int64, false),
+                Field::new("modification_time", timestamp, false),
+                Field::new("partition_values", DataType::Utf8, false),
+                Field::new("num_records", DataType::Int64, true),
+                Field::new("stats", DataType::Utf8, true),
+                Field::new("tags", DataType::Utf8, true),
+            ],
+        };
+        Arc::new(ArrowSchema::new(fields))
+    }
+}
+
+/// Metadata of a Delta table, loaded when scanned
+struct DeltaMetadataTable {
+    uri: String,
+    kind: MetadataKind,
+}
+
+impl DeltaMetadataTable {
+    async fn load(&self) -> DeltaResult<RecordBatch> {
+        match self.kind {
+            MetadataKind::History { limit } => self.history(limit).await,
+            MetadataKind::FileMetadata { version } => self.file_metadata(version).await,
+        }
+    }
+
+    async fn history(&self, limit: Option<usize>) -> DeltaResult<RecordBatch> {
+        let table = open_table(&self.uri).await?;
+        let log_store = table.log_store();
+        let version = table.version();
+        let earliest = limit
+            .map(|limit| (version - limit as i64 + 1).max(0))
+            .unwrap_or(0);
+        // commits removed by log cleanup are skipped
+        let commits = futures::stream::iter((earliest..=version).rev())
+            .map(|version| {
+                let log_store = log_store.clone();
+                async move {
+                    let Some(commit) = log_store.read_commit_entry(version).await? else {
+                        return Ok(None);
+                    };
+                    let info = get_actions(version, commit)
+                        .await?
+                        .into_iter()
+                        .find_map(|action| match action {
+                            Action::CommitInfo(info) => Some(info),
+                            _ => None,
+                        })
+                        .unwrap_or_default();
+                    Ok::<_, DeltaTableError>(Some((version, info)))
+                }
+            })
+            .buffered(HISTORY_CONCURRENCY)
+            .try_filter_map(|commit| futures::future::ready(Ok(commit)))
+            .try_collect::<Vec<(i64, CommitInfo)>>()
+            .await?;
+
+        Ok(RecordBatch::try_new(
+            self.kind.schema(),
+            vec![
+                Arc::new(Int64Array::from_iter_values(
+                    commits.iter().map(|(version, _)| *version),
+                )),
+                Arc::new(
+                    commits
+                        .iter()
+                        .map(|(_, info)| info.timestamp)
+                        .collect::<TimestampMillisecondArray>()
+                        .with_timezone("UTC"),
+                ),
+                Arc::new(
+                    commits
+                        .iter()
+                        .map(|(_, info)| info.operation.clone())
+                        .collect::<StringArray>(),
+                ),
+                Arc::new(
+                    commits
+                        .iter()
+                        .map(|(_, info)| {
+                            info.operation_parameters
+                                .as_ref()
+                                .and_then(|params| serde_json::to_string(params).ok())
+                        })
+                        .collect::<StringArray>(),
+                ),
+                Arc::new(
+                    commits
+                        .iter()
+                        .map(|(_, info)| info.user_name.clone())
+                        .collect::<StringArray>(),
+                ),
+                Arc::new(
+                    commits
+                        .iter()
+                        .map(|(_, info)| info.read_version)
+                        .collect::<Int64Array>(),
+                ),
+                Arc::new(
+                    commits
+                        .iter()
+                        .map(|(_, info)| info.is_blind_append)
+                        .collect::<BooleanArray>(),
+                ),
+                Arc::new(
+                    commits
+                        .iter()
+                        .map(|(_, info)| info.engine_info.clone())
+                        .collect::<StringArray>(),
+                ),
+                Arc::new(
+                    commits
+                        .iter()
+                        .map(|(_, info)| {
+                            (!info.info.is_empty())
+                                .then(|| serde_json::to_string(&info.info).ok())
+                                .flatten()
+                        })
+                        .collect::<StringArray>(),
+                ),
+            ],
+        )?)
+    }
+
+    async fn file_metadata(&self, version: Option<i64>) -> DeltaResult<RecordBatch> {
+        let table = match version {
+            Some(version) => open_table_with_version(&self.uri, version).await?,
+            None => open_table(&self.uri).await?,
+        };
+        let files = table.snapshot()?.file_actions()?;
+        Ok(RecordBatch::try_new(
+            self.kind.schema(),
+            vec![
+                Arc::new(
+                    files
+                        .iter()
+                        .map(|add| Some(add.path.as_str()))
+                        .collect::<StringArray>(),
+                ),
+                Arc::new(Int64Array::from_iter_values(
+                    files.iter().map(|add| add.size),
+                )),
+                Arc::new(
+                    TimestampMillisecondArray::from_iter_values(
+                        files.iter().map(|add| add.modification_time),
+                    )
+                    .with_timezone("UTC"),
+                ),
+                Arc::new(
+                    files
+                        .iter()
+                        .map(|add| serde_json::to_string(&add.partition_values).ok())
+                        .collect::<StringArray>(),
+                ),
+                Arc::new(
+                    files
+                        .iter()
+                        .map(|add| {
+                            add.get_stats()
+                                .ok()
+                                .flatten()
+                                .map(|stats| stats.num_records)
+                        })
+                        .collect::<Int64Array>(),
+                ),
+                Arc::new(
+                    files
+                        .iter()
+                        .map(|add| add.stats.clone())
+                        .collect::<StringArray>(),
+                ),
+                Arc::new(
+                    files
+                        .iter()
+                        .map(|add| {
+                            add.tags
+                                .as_ref()
+                                .and_then(|tags| serde_json::to_string(tags).ok())
+                        })
+                        .collect::<StringArray>(),
+                ),
+            ],
+        )?)
+    }
+}
+
+#[async_trait]
+impl TableProvider for DeltaMetadataTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.kind.schema()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::View
+    }
+
+    async fn scan(
+        &self,
+        _state: &SessionState,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        let batch = self.load().await?;
+        Ok(Arc::new(MemoryExec::try_new(
+            &[vec![batch]],
+            self.schema(),
+            projection.cloned(),
+        )?))
+    }
+}
+
+/// A data or change data file read by `delta_cdf`
+struct ChangeFile {
+    path: String,
+    partition_values: HashMap<String, Option<String>>,
+    /// The change type of all rows of the file, `None` for change data files which record the
+    /// change type of each row
+    change_type: Option<&'static str>,
+    /// Only the rows marked by this deletion vector changed, all rows if `None`
+    include: Option<DeletionVectorDescriptor>,
+    /// The rows marked by this deletion vector did not change
+    exclude: Option<DeletionVectorDescriptor>,
+    version: i64,
+    timestamp: Option<i64>,
+}
+
+/// Change data feed of a Delta table, read when the scan is executed
+#[derive(Clone)]
+struct DeltaCdfTable {
+    uri: String,
+    starting_version: i64,
+    ending_version: Option<i64>,
+    table_schema: SchemaRef,
+    schema: SchemaRef,
+}
+
+impl DeltaCdfTable {
+    fn new(
+        uri: String,
+        starting_version: i64,
+        ending_version: Option<i64>,
+        table_schema: SchemaRef,
+    ) -> Self {
+        let timestamp = DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into()));
+        let mut fields = table_schema.fields().to_vec();
+        fields.extend([
+            Arc::new(Field::new(CHANGE_TYPE_COL, DataType::Utf8, false)),
+            Arc::new(Field::new(COMMIT_VERSION_COL, DataType::Int64, false)),
+            Arc::new(Field::new(COMMIT_TIMESTAMP_COL, timestamp, true)),
+        ]);
+        Self {
+            uri,
+            starting_version,
+            ending_version,
+            table_schema,
+            schema: Arc::new(ArrowSchema::new(fields)),
+        }
+    }
+
+    /// The files changed by the commits of the range.
+    ///
+    /// Like Spark, this fails if the change data feed was not enabled for any of the commits.
+    async fn change_files(&self) -> DeltaResult<(LogStoreRef, Vec<String>, Vec<ChangeFile>)> {
+        let table = DeltaTableBuilder::from_uri(&self.uri)
+            .with_version(self.starting_version)
+            .without_files()
+            .load()
+            .await?;
+        let log_store = table.log_store();
+        let latest_version = log_store.get_latest_version(table.version()).await?;
+        let ending_version = self.ending_version.unwrap_or(latest_version);
+        if ending_version > latest_version {
+            return Err(DeltaTableError::InvalidVersion(ending_version));
+        }
+        let mut metadata = table.metadata()?.clone();
+
+        let mut files = Vec::new();
+        for version in self.starting_version..=ending_version {
+            let commit = log_store
+                .read_commit_entry(version)
+                .await?
+                .ok_or(DeltaTableError::InvalidVersion(version))?;
+            let actions = get_actions(version, commit).await?;
+            if let Some(updated) = actions.iter().find_map(|action| match action {
+                Action::Metadata(metadata) => Some(metadata),
+                _ => None,
+            }) {
+                metadata = updated.clone();
+            }
+            if !TableConfig(&metadata.configuration).enable_change_data_feed() {
+                return Err(DeltaTableError::Generic(format!(
+                    "Error getting change data for range [{}, {ending_version}] as change data \
+                     was not recorded for version [{version}], set {} to record it",
+                    self.starting_version,
+                    DeltaConfigKey::EnableChangeDataFeed.as_ref()
+                )));
+            }
+
+            let timestamp = actions.iter().find_map(|action| match action {
+                Action::CommitInfo(info) => info.timestamp,
+                _ => None,
+            });
+            let change_file =
+                |path: String,
+                 partition_values: HashMap<String, Option<String>>,
+                 change_type: Option<&'static str>,
+                 include: Option<DeletionVectorDescriptor>,
+                 exclude: Option<DeletionVectorDescriptor>| ChangeFile {
+                    path,
+                    partition_values,
+                    change_type,
+                    include,
+                    exclude,
+                    version,
+                    timestamp,
+                };
+
+            let has_change_data = actions
+                .iter()
+                .any(|action| matches!(action, Action::Cdc(_)));
+            if has_change_data {
+                files.extend(actions.into_iter().filter_map(|action| match action {
+                    Action::Cdc(cdc) => Some(change_file(
+                        cdc.path,
+                        cdc.partition_values,
+                        None,
+                        None,
+                        None,
+                    )),
+                    _ => None,
+                }));
+                continue;
+            }
+
+            // a file which is removed and added again by the same commit had its deletion
+            // vector updated, only the rows marked by one of the deletion vectors changed
+            let mut added = Vec::new();
+            let mut removed = IndexMap::new();
+            for action in actions {
+                match action {
+                    Action::Add(add) if add.data_change => added.push(add),
+                    Action::Remove(remove) if remove.data_change => {
+                        removed.insert(remove.path.clone(), remove);
+                    }
+                    _ => {}
+                }
+            }
+            for add in added {
+                match removed.shift_remove(&add.path) {
+                    Some(remove) if remove.deletion_vector != add.deletion_vector => {
+                        if add.deletion_vector.is_some() {
+                            files.push(change_file(
+                                add.path.clone(),
+                                add.partition_values.clone(),
+                                Some("delete"),
+                                add.deletion_vector.clone(),
+                                remove.deletion_vector.clone(),
+                            ));
+                        }
+                        if remove.deletion_vector.is_some() {
+                            files.push(change_file(
+                                add.path,
+                                add.partition_values,
+                                Some("insert"),
+                                remove.deletion_vector,
+                                add.deletion_vector,
+                            ));
+                        }
+                    }
+                    Some(_) => {}
+                    None => files.push(change_file(
+                        add.path,
+                        add.partition_values,
+                        Some("insert"),
+                        None,
+                        add.deletion_vector,
+                    )),
+                }
+            }
+            for remove in removed.into_values() {
+                files.push(change_file(
+                    remove.path,
+                    remove.partition_values.unwrap_or_default(),
+                    Some("delete"),
+                    None,
+                    remove.deletion_vector,
+                ));
+            }
+        }
+        Ok((log_store, metadata.partition_columns, files))
+    }
+
+    /// Stream the changed rows of the range, reading one file at a time
+    fn changes(self: Arc<Self>) -> BoxStream<'static, DeltaResult<RecordBatch>> {
+        futures::stream::once(async move {
+            let (log_store, partition_columns, files) = self.change_files().await?;
+            let table_root = Arc::new(table_root(&log_store));
+            let partition_columns = Arc::new(partition_columns);
+            Ok::<_, DeltaTableError>(
+                futures::stream::iter(files.into_iter().map(Ok))
+                    .and_then(move |file| {
+                        self.clone().read_file(
+                            log_store.clone(),
+                            table_root.clone(),
+                            partition_columns.clone(),
+                            file,
+                        )
+                    })
+                    .try_flatten(),
+            )
+        })
+        .try_flatten()
+        .boxed()
+    }
+
+    /// Stream the changed rows of `file`
+    async fn read_file(
+        self: Arc<Self>,
+        log_store: LogStoreRef,
+        table_root: Arc<Url>,
+        partition_columns: Arc<Vec<String>>,
+        file: ChangeFile,
+    ) -> DeltaResult<BoxStream<'static, DeltaResult<RecordBatch>>> {
+        let store = log_store.object_store();
+        let location = match external_file_path(&file.path) {
+            Some(location) => location,
+            None => Path::parse(&file.path)?,
+        };
+        let meta = store.head(&location).await?;
+        let include = match &file.include {
+            Some(dv) => Some(dv.read(store.as_ref(), &table_root).await?),
+            None => None,
+        };
+        let exclude = match &file.exclude {
+            Some(dv) => Some(dv.read(store.as_ref(), &table_root).await?),
+            None => None,
+        };
+        let stream = ParquetRecordBatchStreamBuilder::new(ParquetObjectReader::new(store, meta))
+            .await?
+            .build()?;
+
+        let mut offset = 0;
+        Ok(stream
+            .map(move |batch| {
+                let batch = batch?;
+                let rows = offset..offset + batch.num_rows() as u64;
+                offset = rows.end;
+                let batch = if include.is_none() && exclude.is_none() {
+                    batch
+                } else {
+                    let changed = BooleanArray::from_iter(rows.map(|row| {
+                        Some(
+                            include.as_ref().map_or(true, |dv| dv.contains(row))
+                                && !exclude.as_ref().is_some_and(|dv| dv.contains(row)),
+                        )
+                    }));
+                    filter_record_batch(&batch, &changed)?
+                };
+                self.change_batch(&batch, &file, &partition_columns)
+            })
+            .boxed())
+    }
+
+    /// Align `batch` read from `file` with the schema of the function
+    fn change_batch(
+        &self,
+        batch: &RecordBatch,
+        file: &ChangeFile,
+        partition_columns: &[String],
+    ) -> DeltaResult<RecordBatch> {
+        let num_rows = batch.num_rows();
+        let mut columns = self
+            .table_schema
+            .fields()
+            .iter()
+            .map(|field| {
+                if partition_columns.contains(field.name()) {
+                    let value = file.partition_values.get(field.name()).cloned().flatten();
+                    return match value.and_then(|value| {
+                        to_correct_scalar_value(
+                            &serde_json::Value::String(value),
+                            field.data_type(),
+                        )
+                        .transpose()
+                    }) {
+                        Some(value) => Ok(value?.to_array_of_size(num_rows)?),
+                        None => Ok(new_null_array(field.data_type(), num_rows)),
+                    };
+                }
+                match batch.column_by_name(field.name()) {
+                    Some(column) if column.data_type() == field.data_type() => Ok(column.clone()),
+                    Some(column) => Ok(cast(column, field.data_type())?),
+                    None => Ok(new_null_array(field.data_type(), num_rows)),
+                }
+            })
+            .collect::<DeltaResult<Vec<ArrayRef>>>()?;
+
+        let change_type = match file.change_type {
+            Some(change_type) => Arc::new(StringArray::from(vec![change_type; num_rows])),
+            None => batch
+                .column_by_name(CHANGE_TYPE_COL)
+                .cloned()
+                .ok_or_else(|| {
+                    DeltaTableError::Generic(format!(
+                        "Change data file {} has no {CHANGE_TYPE_COL} column",
+                        file.path
+                    ))
+                })?,
+        };
+        columns.push(change_type);
+        columns.push(Arc::new(Int64Array::from_value(file.version, num_rows)));
+        columns.push(Arc::new(
+            TimestampMillisecondArray::from(vec![file.timestamp; num_rows]).with_timezone("UTC"),
+        ));
+        Ok(RecordBatch::try_new(self.schema.clone(), columns)?)
+    }
+}
+
+#[async_trait]
+impl TableProvider for DeltaCdfTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::View
+    }
+
+    async fn scan(
+        &self,
+        _state: &SessionState,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(StreamingTableExec::try_new(
+            self.schema(),
+            vec![Arc::new(DeltaCdfStream(Arc::new(self.clone())))],
+            projection,
+            None,
+            false,
+        )?))
+    }
+}
+
+/// The changes of a [`DeltaCdfTable`] as a single partition, read when executed
+struct DeltaCdfStream(Arc<DeltaCdfTable>);
+
+impl PartitionStream for DeltaCdfStream {
+    fn schema(&self) -> &SchemaRef {
+        &self.0.schema
+    }
+
+    fn execute(&self, _ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        let stream = self.0.clone().changes().map_err(DataFusionError::from);
+        Box::pin(RecordBatchStreamAdapter::new(self.0.schema.clone(), stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::AsArray;
+    use arrow::datatypes::Int64Type;
+
+    use datafusion::assert_batches_eq;
+
+    use super::*;
+    use crate::protocol::SaveMode;
+    use crate::writer::test_utils::get_record_batch;
+    use crate::DeltaOps;
+
+    #[tokio::test]
+    async fn test_table_functions() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let uri = tmp_dir.path().to_str().unwrap();
+        let batch = get_record_batch(None, false);
+        let table = DeltaOps::try_from_uri(uri)
+            .await
+            .unwrap()
+            .write(vec![batch.clone()])
+            .await
+            .unwrap();
+        let table = DeltaOps(table).write(vec![batch]).await.unwrap();
+        assert_eq!(table.version(), 1);
+
+        let ctx = SessionContext::new();
+        register_table_functions(&ctx);
+
+        let batches = ctx
+            .sql(&format!(
+                "SELECT version, operation FROM delta_history('{uri}')"
+            ))
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        let versions = batches[0].column(0).as_primitive::<Int64Type>();
+        assert_eq!(versions.values().to_vec(), vec![1, 0]);
+        assert_eq!(batches[0].column(1).as_string::<i32>().value(0), "WRITE");
+
+        let batches = ctx
+            .sql(&format!("SELECT version FROM delta_history('{uri}', 1)"))
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(batches[0].num_rows(), 1);
+
+        let batches = ctx
+            .sql(&format!(
+                "SELECT count(*), sum(num_records) FROM delta_file_metadata('{uri}', 0)"
+            ))
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(batches[0].column(0).as_primitive::<Int64Type>().value(0), 1);
+        assert_eq!(
+            batches[0].column(1).as_primitive::<Int64Type>().value(0),
+            11
+        );
+
+        let err = ctx.sql("SELECT * FROM delta_history(1)").await.unwrap_err();
+        assert!(err.to_string().contains("table uri"));
+    }
+
+    fn copy_dir(from: &std::path::Path, to: &std::path::Path) {
+        std::fs::create_dir_all(to).unwrap();
+        for entry in std::fs::read_dir(from).unwrap() {
+            let entry = entry.unwrap();
+            let target = to.join(entry.file_name());
+            if entry.file_type().unwrap().is_dir() {
+                copy_dir(&entry.path(), &target);
+            } else {
+                std::fs::copy(entry.path(), target).unwrap();
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_delta_cdf() {
+        let ctx = SessionContext::new();
+        register_table_functions(&ctx);
+
+        // the update of version 2 wrote a change data file
+        let batches = ctx
+            .sql(
+                "SELECT id, name, _change_type, _commit_version \
+                 FROM delta_cdf('../test/tests/data/simple_table_with_cdc', 0, 2) \
+                 ORDER BY _commit_version, _change_type",
+            )
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        let expected = vec![
+            "+----+-------+------------------+-----------------+",
+            "| id | name  | _change_type     | _commit_version |",
+            "+----+-------+------------------+-----------------+",
+            "| 0  | Mario | insert           | 1               |",
+            "| 0  | Mino  | update_postimage | 2               |",
+            "| 0  | Mario | update_preimage  | 2               |",
+            "+----+-------+------------------+-----------------+",
+        ];
+        assert_batches_eq!(&expected, &batches);
+
+        // without change data files, added and removed files are inserted and deleted rows
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let uri = tmp_dir.path().to_str().unwrap();
+        let batch = get_record_batch(None, false);
+        let table = DeltaOps::try_from_uri(uri)
+            .await
+            .unwrap()
+            .write(vec![batch.clone()])
+            .with_configuration(vec![(
+                DeltaConfigKey::EnableChangeDataFeed.as_ref(),
+                Some("true"),
+            )])
+            .await
+            .unwrap();
+        let table = DeltaOps(table)
+            .write(vec![batch])
+            .with_save_mode(SaveMode::Overwrite)
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 1);
+
+        let batches = ctx
+            .sql(&format!(
+                "SELECT _change_type, _commit_version, count(*) \
+                 FROM delta_cdf('{uri}', 1) GROUP BY 1, 2 ORDER BY 1"
+            ))
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        let expected = vec![
+            "+--------------+-----------------+----------+",
+            "| _change_type | _commit_version | COUNT(*) |",
+            "+--------------+-----------------+----------+",
+            "| delete       | 1               | 11       |",
+            "| insert       | 1               | 11       |",
+            "+--------------+-----------------+----------+",
+        ];
+        assert_batches_eq!(&expected, &batches);
+
+        let err = ctx
+            .sql(&format!("SELECT * FROM delta_cdf('{uri}', 1, 0)"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("ending_version"));
+        let err = ctx
+            .sql(&format!("SELECT * FROM delta_cdf('{uri}', 0, 5)"))
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("5"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_delta_cdf_requires_change_data_feed() {
+        let ctx = SessionContext::new();
+        register_table_functions(&ctx);
+
+        let err = ctx
+            .sql("SELECT * FROM delta_cdf('../test/tests/data/table-with-dv-small', 0)")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("change data was not recorded for version [0]"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_delta_cdf_deletion_vectors() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        copy_dir(
+            std::path::Path::new("../test/tests/data/table-with-dv-small"),
+            tmp_dir.path(),
+        );
+        let commit = tmp_dir.path().join("_delta_log/00000000000000000000.json");
+        let content = std::fs::read_to_string(&commit).unwrap().replace(
+            "\"configuration\":{",
+            "\"configuration\":{\"delta.enableChangeDataFeed\":\"true\",",
+        );
+        std::fs::write(commit, content).unwrap();
+        let uri = tmp_dir.path().to_str().unwrap();
+
+        let ctx = SessionContext::new();
+        register_table_functions(&ctx);
+
+        // version 1 added a deletion vector marking two rows of the file
+        let batches = ctx
+            .sql(&format!(
+                "SELECT value, _change_type, _commit_version FROM delta_cdf('{uri}', 0) \
+                 WHERE _commit_version = 1 ORDER BY value"
+            ))
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        let expected = vec![
+            "+-------+--------------+-----------------+",
+            "| value | _change_type | _commit_version |",
+            "+-------+--------------+-----------------+",
+            "| 0     | delete       | 1               |",
+            "| 9     | delete       | 1               |",
+            "+-------+--------------+-----------------+",
+        ];
+        assert_batches_eq!(&expected, &batches);
+
+        let batches = ctx
+            .sql(&format!(
+                "SELECT count(*) FROM delta_cdf('{uri}', 0, 0) WHERE _change_type = 'insert'"
+            ))
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(
+            batches[0].column(0).as_primitive::<Int64Type>().value(0),
+            10
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delta_cdf_current_thread_runtime() {
+        let ctx = SessionContext::new();
+        register_table_functions(&ctx);
+
+        let err = ctx
+            .sql("SELECT * FROM delta_cdf('../test/tests/data/simple_table_with_cdc', 0)")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("multi-threaded"));
+    }
+}
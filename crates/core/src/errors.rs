@@ -217,6 +217,30 @@ pub enum DeltaTableError {
 
     #[error("Table has not yet been initialized")]
     NotInitialized,
+
+    /// The table was loaded without files, see [`DeltaTableBuilder::without_files`](crate::DeltaTableBuilder::without_files)
+    #[error("Table has not been loaded with files, therefore {0} is not supported")]
+    NotInitializedWithFiles(String),
+
+    /// The operation was cancelled through its cancellation token
+    #[error("Operation was cancelled")]
+    Cancelled,
+
+    /// The operation did not finish before its timeout
+    #[error("Operation timed out after {timeout:?}")]
+    Timeout {
+        /// Timeout of the operation
+        timeout: std::time::Duration,
+        /// Progress of the operation when it timed out
+        progress: Box<crate::operations::progress::Progress>,
+    },
+
+    /// Strict validation found actions violating the JSON schema of the protocol
+    #[error("Delta log violates the protocol schema: {} violation(s), first: {}", .report.violations.len(), .report.violations[0])]
+    InvalidLogSchema {
+        /// All violations found in the replayed commits
+        report: Box<crate::table::log_validation::LogValidationReport>,
+    },
 }
 
 impl From<object_store::path::Error> for DeltaTableError {
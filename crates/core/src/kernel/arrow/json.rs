@@ -63,7 +63,7 @@ pub(crate) fn parse_json(
         if json_strings.is_null(it) {
             if value_count > 0 {
                 let slice = json_strings.slice(value_start, value_count);
-                let batch = decode_reader(&mut decoder, get_reader(slice.value_data()))
+                let batch = decode_reader(&mut decoder, get_reader(sliced_value_data(&slice)))
                     .collect::<Result<Vec<_>, _>>()?;
                 batches.extend(batch);
                 value_count = 0;
@@ -87,7 +87,7 @@ pub(crate) fn parse_json(
 
     if value_count > 0 {
         let slice = json_strings.slice(value_start, value_count);
-        let batch = decode_reader(&mut decoder, get_reader(slice.value_data()))
+        let batch = decode_reader(&mut decoder, get_reader(sliced_value_data(&slice)))
             .collect::<Result<Vec<_>, _>>()?;
         batches.extend(batch);
     }
@@ -95,6 +95,14 @@ pub(crate) fn parse_json(
     Ok(concat_batches(&output_schema, &batches)?)
 }
 
+/// The bytes of the values of a sliced string array, `value_data` covers the whole array
+fn sliced_value_data(slice: &StringArray) -> &[u8] {
+    let offsets = slice.value_offsets();
+    let start = offsets[0] as usize;
+    let end = offsets[offsets.len() - 1] as usize;
+    &slice.value_data()[start..end]
+}
+
 /// Decode a stream of bytes into a stream of record batches.
 pub(crate) fn decode_stream<S: Stream<Item = ObjectStoreResult<Bytes>> + Unpin>(
     mut decoder: Decoder,
@@ -125,6 +133,51 @@ pub(crate) fn decode_stream<S: Stream<Item = ObjectStoreResult<Bytes>> + Unpin>(
     })
 }
 
+/// Decode a stream of commit files into record batches, parsing the lines of each commit
+/// with simd-json.
+///
+/// Every commit is parsed in place from a single copy of its content, and the parsed lines
+/// are serialized into the decoder in chunks of `batch_size` rows.
+#[cfg(feature = "simd-json")]
+pub(crate) fn decode_commits<S: Stream<Item = ObjectStoreResult<Bytes>>>(
+    mut decoder: Decoder,
+    input: S,
+    batch_size: usize,
+) -> impl Stream<Item = Result<RecordBatch, DeltaTableError>> {
+    use futures::TryStreamExt;
+
+    input
+        .map(move |data| decode_commit(&mut decoder, &data?, batch_size))
+        .map_ok(|batches| futures::stream::iter(batches.into_iter().map(Ok)))
+        .try_flatten()
+}
+
+#[cfg(feature = "simd-json")]
+fn decode_commit(
+    decoder: &mut Decoder,
+    data: &[u8],
+    batch_size: usize,
+) -> DeltaResult<Vec<RecordBatch>> {
+    let mut buffer = data.to_vec();
+    let values = buffer
+        .split_mut(|b| *b == b'\n')
+        .map(|line| match line {
+            [line @ .., b'\r'] => line,
+            line => line,
+        })
+        .filter(|line| !line.is_empty())
+        .map(simd_json::to_borrowed_value)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| ArrowError::JsonError(err.to_string()))?;
+
+    let mut batches = Vec::new();
+    for chunk in values.chunks(batch_size.max(1)) {
+        decoder.serialize(chunk)?;
+        batches.extend(decoder.flush()?);
+    }
+    Ok(batches)
+}
+
 /// Decode data prvided by a reader into an iterator of record batches.
 pub(crate) fn decode_reader<'a, R: BufRead + 'a>(
     decoder: &'a mut Decoder,
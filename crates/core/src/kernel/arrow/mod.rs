@@ -451,11 +451,18 @@ pub(crate) fn delta_log_schema_for_table(
                 ],
                 protocol[
                     minReaderVersion:Int32,
-                    minWriterVersion:Int32
+                    minWriterVersion:Int32,
+                    readerFeatures[element]{Utf8},
+                    writerFeatures[element]{Utf8}
                 ],
                 txn[
                     appId:Utf8,
                     version:Int64
+                ],
+                domainMetadata[
+                    domain:Utf8,
+                    configuration:Utf8,
+                    removed:Boolean
                 ]
         ];
         static ref ADD_FIELDS: Vec<ArrowField> = arrow_defs![
@@ -576,7 +583,11 @@ fn max_min_schema_for_fields(dest: &mut Vec<ArrowField>, f: &ArrowField) {
             }
         }
         // don't compute min or max for list, map or binary types
-        ArrowDataType::List(_) | ArrowDataType::Map(_, _) | ArrowDataType::Binary => { /* noop */ }
+        ArrowDataType::List(_)
+        | ArrowDataType::Map(_, _)
+        | ArrowDataType::Binary
+        | ArrowDataType::LargeBinary
+        | ArrowDataType::FixedSizeBinary(_) => { /* noop */ }
         _ => {
             let f = f.clone();
             dest.push(f);
@@ -632,11 +643,18 @@ mod tests {
             delta_log_schema_for_table(table_schema.clone(), partition_columns.as_slice(), false);
 
         // verify top-level schema contains all expected fields and they are named correctly.
-        let expected_fields = ["metaData", "protocol", "txn", "remove", "add"];
+        let expected_fields = [
+            "metaData",
+            "protocol",
+            "txn",
+            "domainMetadata",
+            "remove",
+            "add",
+        ];
         for f in log_schema.fields().iter() {
             assert!(expected_fields.contains(&f.name().as_str()));
         }
-        assert_eq!(5, log_schema.fields().len());
+        assert_eq!(6, log_schema.fields().len());
 
         // verify add fields match as expected. a lot of transformation goes into these.
         let add_fields: Vec<_> = log_schema
@@ -1,15 +1,15 @@
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::str::FromStr;
-// use std::io::{Cursor, Read};
-// use std::sync::Arc;
 
-// use roaring::RoaringTreemap;
+use object_store::path::Path;
+use object_store::ObjectStore;
+use roaring::RoaringTreemap;
 use serde::{Deserialize, Serialize};
 use tracing::warn;
 use url::Url;
 
-use super::schema::StructType;
+use super::schema::{DataType, PrimitiveType, StructType};
 use crate::kernel::{error::Error, DeltaResult};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -79,11 +79,32 @@ impl Metadata {
         partition_columns: impl IntoIterator<Item = impl Into<String>>,
         configuration: HashMap<String, Option<String>>,
     ) -> DeltaResult<Self> {
+        let partition_columns: Vec<String> =
+            partition_columns.into_iter().map(|c| c.into()).collect();
+        for column in partition_columns.iter() {
+            // Partition values are stored as strings in the log and used as directory names,
+            // so only non-binary primitive columns can be used to partition a table.
+            if let Ok(field) = schema.field_with_name(column) {
+                match field.data_type() {
+                    DataType::Primitive(PrimitiveType::Binary)
+                    | DataType::Array(_)
+                    | DataType::Struct(_)
+                    | DataType::Map(_) => {
+                        return Err(Error::MetadataError(format!(
+                            "Partition column '{}' has type {} which is not supported for partitioning",
+                            column,
+                            field.data_type()
+                        )));
+                    }
+                    _ => {}
+                }
+            }
+        }
         Ok(Self {
             id: uuid::Uuid::new_v4().to_string(),
             format: Default::default(),
             schema_string: serde_json::to_string(&schema)?,
-            partition_columns: partition_columns.into_iter().map(|c| c.into()).collect(),
+            partition_columns,
             configuration,
             name: None,
             description: None,
@@ -109,12 +130,165 @@ impl Metadata {
         self
     }
 
+    /// Create a [`MetadataBuilder`] for a table with the given schema
+    pub fn builder(schema: StructType) -> MetadataBuilder {
+        MetadataBuilder::new(schema)
+    }
+
     /// get the table schema
     pub fn schema(&self) -> DeltaResult<StructType> {
         Ok(serde_json::from_str(&self.schema_string)?)
     }
 }
 
+/// Configuration key prefixes whose suffix is user defined, e.g. the name of a constraint
+const DYNAMIC_CONFIG_PREFIXES: [&str; 3] = [
+    "delta.constraints.",
+    "delta.feature.",
+    "delta.columnMapping.",
+];
+
+/// Fluent builder for [`Metadata`] actions.
+///
+/// In contrast to assembling a [`Metadata`] by hand, all inputs are validated when calling
+/// [`build`](Self::build), so invalid metadata is rejected before it can be committed.
+#[derive(Debug, Clone)]
+pub struct MetadataBuilder {
+    schema: StructType,
+    name: Option<String>,
+    description: Option<String>,
+    format: Format,
+    partition_columns: Vec<String>,
+    configuration: HashMap<String, Option<String>>,
+    created_time: Option<i64>,
+}
+
+impl MetadataBuilder {
+    /// Create a new builder for a table with the given schema
+    pub fn new(schema: StructType) -> Self {
+        Self {
+            schema,
+            name: None,
+            description: None,
+            format: Default::default(),
+            partition_columns: Vec::new(),
+            configuration: HashMap::new(),
+            created_time: None,
+        }
+    }
+
+    /// Set the table name
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Set the table description
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Set the file format, defaults to parquet
+    pub fn with_format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Set the columns the table is partitioned by
+    pub fn with_partition_columns(
+        mut self,
+        partition_columns: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.partition_columns = partition_columns.into_iter().map(|c| c.into()).collect();
+        self
+    }
+
+    /// Add a single table configuration entry
+    pub fn with_configuration_property(
+        mut self,
+        key: impl Into<String>,
+        value: Option<impl Into<String>>,
+    ) -> Self {
+        self.configuration
+            .insert(key.into(), value.map(|value| value.into()));
+        self
+    }
+
+    /// Add multiple table configuration entries
+    pub fn with_configuration(
+        mut self,
+        configuration: impl IntoIterator<Item = (impl Into<String>, Option<impl Into<String>>)>,
+    ) -> Self {
+        self.configuration.extend(
+            configuration
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.map(|value| value.into()))),
+        );
+        self
+    }
+
+    /// Set the creation time, in milliseconds since the Unix epoch
+    pub fn with_created_time(mut self, created_time: i64) -> Self {
+        self.created_time = Some(created_time);
+        self
+    }
+
+    fn validate_partition_columns(&self) -> DeltaResult<()> {
+        let mut seen = HashSet::new();
+        for column in &self.partition_columns {
+            if self.schema.field_with_name(column).is_err() {
+                return Err(Error::MetadataError(format!(
+                    "Partition column '{column}' does not exist in the table schema"
+                )));
+            }
+            if !seen.insert(column) {
+                return Err(Error::MetadataError(format!(
+                    "Partition column '{column}' is specified more than once"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_configuration(&self) -> DeltaResult<()> {
+        for key in self.configuration.keys() {
+            let well_formed = !key.is_empty()
+                && !key.chars().any(|c| c.is_whitespace() || c.is_control())
+                && key.split('.').all(|segment| !segment.is_empty());
+            if !well_formed {
+                return Err(Error::MetadataError(format!(
+                    "Invalid table configuration key '{key}'"
+                )));
+            }
+            let known = !key.starts_with("delta.")
+                || crate::table::config::DeltaConfigKey::from_str(key).is_ok()
+                || DYNAMIC_CONFIG_PREFIXES
+                    .iter()
+                    .any(|prefix| key.starts_with(prefix));
+            if !known {
+                return Err(Error::MetadataError(format!(
+                    "Unknown delta table configuration key '{key}'"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate all inputs and create the [`Metadata`] action
+    pub fn build(self) -> DeltaResult<Metadata> {
+        self.validate_partition_columns()?;
+        self.validate_configuration()?;
+        let mut metadata =
+            Metadata::try_new(self.schema, self.partition_columns, self.configuration)?;
+        metadata.name = self.name;
+        metadata.description = self.description;
+        metadata.format = self.format;
+        metadata.created_time = self.created_time;
+        Ok(metadata)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
 #[serde(rename_all = "camelCase")]
 /// Defines a protocol action
@@ -179,6 +353,8 @@ pub enum ReaderFeatures {
     TimestampWithoutTimezone,
     /// version 2 of checkpointing
     V2Checkpoint,
+    /// gzip or zstd compressed commit files
+    LogCompression,
     /// If we do not match any other reader features
     #[serde(untagged)]
     Other(String),
@@ -194,6 +370,7 @@ impl From<&parquet::record::Field> for ReaderFeatures {
                 }
                 "timestampNtz" => ReaderFeatures::TimestampWithoutTimezone,
                 "v2Checkpoint" => ReaderFeatures::V2Checkpoint,
+                "logCompression" => ReaderFeatures::LogCompression,
                 f => ReaderFeatures::Other(f.to_string()),
             },
             f => ReaderFeatures::Other(f.to_string()),
@@ -214,6 +391,7 @@ impl From<&str> for ReaderFeatures {
             "deletionVectors" => ReaderFeatures::DeletionVectors,
             "timestampNtz" => ReaderFeatures::TimestampWithoutTimezone,
             "v2Checkpoint" => ReaderFeatures::V2Checkpoint,
+            "logCompression" => ReaderFeatures::LogCompression,
             f => ReaderFeatures::Other(f.to_string()),
         }
     }
@@ -226,6 +404,7 @@ impl AsRef<str> for ReaderFeatures {
             ReaderFeatures::DeletionVectors => "deletionVectors",
             ReaderFeatures::TimestampWithoutTimezone => "timestampNtz",
             ReaderFeatures::V2Checkpoint => "v2Checkpoint",
+            ReaderFeatures::LogCompression => "logCompression",
             ReaderFeatures::Other(f) => f,
         }
     }
@@ -269,6 +448,8 @@ pub enum WriterFeatures {
     V2Checkpoint,
     /// Iceberg compatibility support
     IcebergCompatV1,
+    /// gzip or zstd compressed commit files
+    LogCompression,
     /// If we do not match any other reader features
     #[serde(untagged)]
     Other(String),
@@ -296,6 +477,7 @@ impl From<&str> for WriterFeatures {
             "domainMetadata" => WriterFeatures::DomainMetadata,
             "v2Checkpoint" => WriterFeatures::V2Checkpoint,
             "icebergCompatV1" => WriterFeatures::IcebergCompatV1,
+            "logCompression" => WriterFeatures::LogCompression,
             f => WriterFeatures::Other(f.to_string()),
         }
     }
@@ -317,6 +499,7 @@ impl AsRef<str> for WriterFeatures {
             WriterFeatures::DomainMetadata => "domainMetadata",
             WriterFeatures::V2Checkpoint => "v2Checkpoint",
             WriterFeatures::IcebergCompatV1 => "icebergCompatV1",
+            WriterFeatures::LogCompression => "logCompression",
             WriterFeatures::Other(f) => f,
         }
     }
@@ -345,6 +528,7 @@ impl From<&parquet::record::Field> for WriterFeatures {
                 "domainMetadata" => WriterFeatures::DomainMetadata,
                 "v2Checkpoint" => WriterFeatures::V2Checkpoint,
                 "icebergCompatV1" => WriterFeatures::IcebergCompatV1,
+                "logCompression" => WriterFeatures::LogCompression,
                 f => WriterFeatures::Other(f.to_string()),
             },
             f => WriterFeatures::Other(f.to_string()),
@@ -473,56 +657,55 @@ impl DeletionVectorDescriptor {
         }
     }
 
-    // TODO read only required byte ranges
-    // pub fn read(
-    //     &self,
-    //     fs_client: Arc<dyn FileSystemClient>,
-    //     parent: Url,
-    // ) -> DeltaResult<RoaringTreemap> {
-    //     match self.absolute_path(&parent)? {
-    //         None => {
-    //             let bytes = z85::decode(&self.path_or_inline_dv)
-    //                 .map_err(|_| Error::DeletionVector("Failed to decode DV".to_string()))?;
-    //             RoaringTreemap::deserialize_from(&bytes[12..])
-    //                 .map_err(|err| Error::DeletionVector(err.to_string()))
-    //         }
-    //         Some(path) => {
-    //             let offset = self.offset;
-    //             let size_in_bytes = self.size_in_bytes;
-    //
-    //             let dv_data = fs_client
-    //                 .read_files(vec![(path, None)])?
-    //                 .next()
-    //                 .ok_or(Error::MissingData("No deletion Vector data".to_string()))??;
-    //
-    //             let mut cursor = Cursor::new(dv_data);
-    //             if let Some(offset) = offset {
-    //                 // TODO should we read the datasize from the DV file?
-    //                 // offset plus datasize bytes
-    //                 cursor.set_position((offset + 4) as u64);
-    //             }
-    //
-    //             let mut buf = vec![0; 4];
-    //             cursor
-    //                 .read(&mut buf)
-    //                 .map_err(|err| Error::DeletionVector(err.to_string()))?;
-    //             let magic =
-    //                 i32::from_le_bytes(buf.try_into().map_err(|_| {
-    //                     Error::DeletionVector("filed to read magic bytes".to_string())
-    //                 })?);
-    //             println!("magic  --> : {}", magic);
-    //             // assert!(magic == 1681511377);
-    //
-    //             let mut buf = vec![0; size_in_bytes as usize];
-    //             cursor
-    //                 .read(&mut buf)
-    //                 .map_err(|err| Error::DeletionVector(err.to_string()))?;
-    //
-    //             RoaringTreemap::deserialize_from(Cursor::new(buf))
-    //                 .map_err(|err| Error::DeletionVector(err.to_string()))
-    //         }
-    //     }
-    // }
+    /// Read the rows marked as deleted by this deletion vector.
+    ///
+    /// `store` must be rooted at the table located at `table_root`. Only the byte range of the
+    /// deletion vector is fetched from deletion vector files.
+    pub async fn read(
+        &self,
+        store: &dyn ObjectStore,
+        table_root: &Url,
+    ) -> DeltaResult<RoaringTreemap> {
+        let Some(url) = self.absolute_path(table_root)? else {
+            let bytes = z85::decode(&self.path_or_inline_dv)
+                .map_err(|_| Error::DeletionVector("Failed to decode DV".to_string()))?;
+            return deserialize_bitmap(&bytes);
+        };
+        let location = url
+            .as_str()
+            .strip_prefix(table_root.as_str())
+            .map(|path| path.trim_start_matches('/'))
+            .ok_or_else(|| Error::DeletionVector(format!("DV outside of the table root: {url}")))?;
+        let location =
+            Path::parse(location).map_err(|err| Error::DeletionVector(err.to_string()))?;
+
+        // DV files start with a format version byte, each DV is prefixed with its size
+        let offset = self.offset.unwrap_or(1) as usize;
+        let size = self.size_in_bytes as usize;
+        let bytes = store
+            .get_range(&location, offset..offset + 4 + size)
+            .await?;
+        let stored_size = u32::from_be_bytes(bytes[..4].try_into().unwrap()) as usize;
+        if stored_size != size {
+            return Err(Error::DeletionVector(format!(
+                "Expected DV of {size} bytes, found {stored_size} bytes"
+            )));
+        }
+        deserialize_bitmap(&bytes[4..])
+    }
+}
+
+/// Magic number preceding serialized deletion vector bitmaps
+const DV_MAGIC: u32 = 1681511377;
+
+fn deserialize_bitmap(bytes: &[u8]) -> DeltaResult<RoaringTreemap> {
+    if bytes.len() < 4 || u32::from_le_bytes(bytes[..4].try_into().unwrap()) != DV_MAGIC {
+        return Err(Error::DeletionVector(
+            "Invalid magic bytes of DV bitmap".to_string(),
+        ));
+    }
+    RoaringTreemap::deserialize_from(&bytes[4..])
+        .map_err(|err| Error::DeletionVector(err.to_string()))
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -718,6 +901,30 @@ pub struct CommitInfo {
     pub info: HashMap<String, serde_json::Value>,
 }
 
+impl CommitInfo {
+    /// Time the commit was created, as [`chrono::DateTime`]
+    pub fn commit_datetime(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.timestamp
+            .and_then(chrono::DateTime::<chrono::Utc>::from_timestamp_millis)
+    }
+
+    /// Time the commit was created, as [`std::time::SystemTime`]
+    pub fn commit_system_time(&self) -> Option<std::time::SystemTime> {
+        self.timestamp
+            .map(crate::protocol::time_utils::system_time_from_millis)
+    }
+
+    /// Lineage of the committed data, if recorded by the writer
+    pub fn lineage(
+        &self,
+    ) -> Result<Option<crate::operations::transaction::LineageContext>, serde_json::Error> {
+        self.info
+            .get(crate::operations::transaction::LINEAGE_KEY)
+            .map(|lineage| serde_json::from_value(lineage.clone()))
+            .transpose()
+    }
+}
+
 /// The domain metadata action contains a configuration (string) for a named metadata domain
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
@@ -891,7 +1098,7 @@ mod tests {
 
     // use object_store::local::LocalFileSystem;
 
-    use crate::kernel::PrimitiveType;
+    use crate::kernel::{PrimitiveType, StructField};
 
     use super::*;
     // use crate::client::filesystem::ObjectStoreFileSystemClient;
@@ -967,30 +1174,100 @@ mod tests {
         assert_eq!(dv_url, example.absolute_path(&parent).unwrap().unwrap());
     }
 
+    fn metadata_schema() -> StructType {
+        StructType::new(vec![
+            StructField::new("id", DataType::Primitive(PrimitiveType::Long), false),
+            StructField::new("date", DataType::Primitive(PrimitiveType::Date), true),
+        ])
+    }
+
+    #[test]
+    fn test_metadata_builder() {
+        let metadata = Metadata::builder(metadata_schema())
+            .with_name("events")
+            .with_description("all events")
+            .with_partition_columns(["date"])
+            .with_configuration_property("delta.appendOnly", Some("true"))
+            .with_configuration([
+                ("delta.constraints.id_positive", Some("id > 0")),
+                ("owner", None),
+            ])
+            .build()
+            .unwrap();
+        assert_eq!(metadata.name.as_deref(), Some("events"));
+        assert_eq!(metadata.description.as_deref(), Some("all events"));
+        assert_eq!(metadata.format, Format::default());
+        assert_eq!(metadata.partition_columns, vec!["date".to_string()]);
+        assert_eq!(metadata.configuration.len(), 3);
+        assert_eq!(metadata.schema().unwrap(), metadata_schema());
+    }
+
+    #[test]
+    fn test_metadata_builder_validation() {
+        let missing = Metadata::builder(metadata_schema())
+            .with_partition_columns(["region"])
+            .build();
+        assert!(matches!(missing, Err(Error::MetadataError(_))));
+
+        let duplicate = Metadata::builder(metadata_schema())
+            .with_partition_columns(["date", "date"])
+            .build();
+        assert!(matches!(duplicate, Err(Error::MetadataError(_))));
+
+        for key in ["", "delta..appendOnly", "my key", "delta.appendonly"] {
+            let result = Metadata::builder(metadata_schema())
+                .with_configuration_property(key, Some("true"))
+                .build();
+            assert!(
+                matches!(result, Err(Error::MetadataError(_))),
+                "expected '{key}' to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_metadata_rejects_binary_partition_columns() {
+        let schema = StructType::new(vec![
+            StructField::new("id", DataType::Primitive(PrimitiveType::String), true),
+            StructField::new("payload", DataType::Primitive(PrimitiveType::Binary), true),
+        ]);
+        assert!(Metadata::try_new(schema.clone(), ["id"], HashMap::new()).is_ok());
+        assert!(matches!(
+            Metadata::try_new(schema, ["payload"], HashMap::new()),
+            Err(Error::MetadataError(_))
+        ));
+    }
+
     #[test]
     fn test_primitive() {
         let types: PrimitiveType = serde_json::from_str("\"string\"").unwrap();
         println!("{:?}", types);
     }
 
-    // #[test]
-    // fn test_deletion_vector_read() {
-    //     let store = Arc::new(LocalFileSystem::new());
-    //     let path =
-    //         std::fs::canonicalize(PathBuf::from("../deltalake-test/tests/data/table-with-dv-small/")).unwrap();
-    //     let parent = url::Url::from_directory_path(path).unwrap();
-    //     let root = object_store::path::Path::from(parent.path());
-    //     let fs_client = Arc::new(ObjectStoreFileSystemClient::new(
-    //         store,
-    //         root,
-    //         Arc::new(TokioBackgroundExecutor::new()),
-    //     ));
-    //
-    //     let example = dv_example();
-    //     let tree_map = example.read(fs_client, parent).unwrap();
-    //
-    //     let expected: Vec<u64> = vec![0, 9];
-    //     let found = tree_map.iter().collect::<Vec<_>>();
-    //     assert_eq!(found, expected)
-    // }
+    #[tokio::test]
+    async fn test_deletion_vector_read() {
+        let path = std::fs::canonicalize(PathBuf::from("../test/tests/data/table-with-dv-small/"))
+            .unwrap();
+        let store = object_store::local::LocalFileSystem::new_with_prefix(&path).unwrap();
+        let parent = url::Url::from_directory_path(path).unwrap();
+
+        let example = dv_example();
+        let tree_map = example.read(&store, &parent).await.unwrap();
+        assert_eq!(tree_map.iter().collect::<Vec<_>>(), vec![0, 9]);
+        assert_eq!(tree_map.len(), example.cardinality as u64);
+
+        let mut bytes = DV_MAGIC.to_le_bytes().to_vec();
+        RoaringTreemap::from_iter([3u64, 5])
+            .serialize_into(&mut bytes)
+            .unwrap();
+        let inline = DeletionVectorDescriptor {
+            storage_type: StorageType::Inline,
+            size_in_bytes: bytes.len() as i32,
+            path_or_inline_dv: z85::encode(&bytes),
+            offset: None,
+            cardinality: 2,
+        };
+        let tree_map = inline.read(&store, &parent).await.unwrap();
+        assert_eq!(tree_map.iter().collect::<Vec<_>>(), vec![3, 5]);
+    }
 }
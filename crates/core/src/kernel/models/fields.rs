@@ -188,15 +188,7 @@ lazy_static! {
         "domainMetadata",
         StructType::new(vec![
             StructField::new("domain", DataType::STRING, false),
-            StructField::new(
-                "configuration",
-                MapType::new(
-                    DataType::STRING,
-                    DataType::STRING,
-                    true,
-                ),
-                true,
-            ),
+            StructField::new("configuration", DataType::STRING, false),
             StructField::new("removed", DataType::BOOLEAN, false),
         ]),
         true,
@@ -91,7 +91,8 @@ impl<'a> DeletionVectorView<'a> {
         }
     }
 
-    fn descriptor(&self) -> DeletionVectorDescriptor {
+    /// The descriptor of the deletion vector, as stored in the `add` action
+    pub fn descriptor(&self) -> DeletionVectorDescriptor {
         DeletionVectorDescriptor {
             storage_type: self.storage_type().parse().unwrap(),
             path_or_inline_dv: self.path_or_inline_dv().to_string(),
@@ -110,13 +111,14 @@ impl<'a> DeletionVectorView<'a> {
     fn size_in_bytes(&self) -> i32 {
         self.data.size_in_bytes.value(self.index)
     }
-    fn cardinality(&self) -> i64 {
+    /// Number of rows the deletion vector marks as deleted
+    pub fn cardinality(&self) -> i64 {
         self.data.cardinality.value(self.index)
     }
     fn offset(&self) -> Option<i32> {
         self.data
             .offset
-            .and_then(|a| a.is_null(self.index).then(|| a.value(self.index)))
+            .and_then(|a| a.is_valid(self.index).then(|| a.value(self.index)))
     }
 }
 
@@ -140,6 +142,8 @@ pub struct LogicalFile<'a> {
     stats: &'a StructArray,
     /// Array containing the deletion vector data.
     deletion_vector: Option<DeletionVector<'a>>,
+    /// The tags of the file, if the log data contains any.
+    tags: Option<&'a MapArray>,
 
     /// Pointer to a specific row in the log data.
     index: usize,
@@ -156,9 +160,13 @@ impl LogicalFile<'_> {
     /// An object store [`Path`] to the file.
     ///
     /// this tries to parse the file string and if that fails, it will return the string as is.
+    /// Absolute file URIs are encoded as paths under the `_delta_external` prefix.
     // TODO assert consisent handling of the paths encoding when reading log data so this logic can be removed.
     pub fn object_store_path(&self) -> Path {
         let path = self.path();
+        if let Some(path) = crate::storage::external::external_file_path(path.as_ref()) {
+            return path;
+        }
         // Try to preserve percent encoding if possible
         match Path::parse(path.as_ref()) {
             Ok(path) => path,
@@ -176,6 +184,11 @@ impl LogicalFile<'_> {
         self.modification_time.value(self.index)
     }
 
+    /// Last modification time of the file, as [`std::time::SystemTime`].
+    pub fn modification_system_time(&self) -> std::time::SystemTime {
+        crate::protocol::time_utils::system_time_from_millis(self.modification_time())
+    }
+
     /// Datetime of the last modification time of the file.
     pub fn modification_datetime(&self) -> DeltaResult<chrono::DateTime<Utc>> {
         Ok(Utc.from_utc_datetime(
@@ -251,14 +264,38 @@ impl LogicalFile<'_> {
         })
     }
 
+    /// The tags of the file, such as its ingestion source.
+    pub fn tags(&self) -> Option<HashMap<String, Option<String>>> {
+        let tags = self.tags.filter(|tags| tags.is_valid(self.index))?;
+        let map_value = tags.value(self.index);
+        let keys = map_value.column(0).as_any().downcast_ref::<StringArray>()?;
+        let values = map_value.column(1).as_any().downcast_ref::<StringArray>()?;
+        Some(
+            keys.iter()
+                .zip(values.iter())
+                .filter_map(|(k, v)| Some((k?.to_string(), v.map(String::from))))
+                .collect(),
+        )
+    }
+
     /// The number of records stored in the data file.
     pub fn num_records(&self) -> Option<usize> {
         self.stats
             .column_by_name(COL_NUM_RECORDS)
             .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+            .filter(|a| a.is_valid(self.index))
             .map(|a| a.value(self.index) as usize)
     }
 
+    /// Fraction of the records of the data file marked as deleted by its deletion vector.
+    /// Files without deletion vector have no deleted records
+    pub fn deletion_vector_coverage(&self) -> Option<f64> {
+        let deleted = self.deletion_vector().map_or(0, |dv| dv.cardinality());
+        self.num_records()
+            .filter(|num_records| *num_records > 0)
+            .map(|num_records| deleted as f64 / num_records as f64)
+    }
+
     /// Struct containing all available null counts for the columns in this file.
     pub fn null_counts(&self) -> Option<Scalar> {
         self.stats
@@ -304,7 +341,7 @@ impl LogicalFile<'_> {
                     .collect()
             }),
             deletion_vector: self.deletion_vector().map(|dv| dv.descriptor()),
-            tags: None,
+            tags: self.tags(),
             base_row_id: None,
             default_row_commit_version: None,
         }
@@ -334,6 +371,7 @@ pub struct FileStatsAccessor<'a> {
     stats: &'a StructArray,
     deletion_vector: Option<DeletionVector<'a>>,
     partition_values: &'a MapArray,
+    tags: Option<&'a MapArray>,
     length: usize,
     pointer: usize,
 }
@@ -349,6 +387,7 @@ impl<'a> FileStatsAccessor<'a> {
         let modification_times = extract_and_cast::<Int64Array>(data, "add.modificationTime")?;
         let stats = extract_and_cast::<StructArray>(data, "add.stats_parsed")?;
         let partition_values = extract_and_cast::<MapArray>(data, "add.partitionValues")?;
+        let tags = extract_and_cast_opt::<MapArray>(data, "add.tags");
         let partition_fields = Arc::new(
             metadata
                 .partition_columns
@@ -380,6 +419,7 @@ impl<'a> FileStatsAccessor<'a> {
             stats,
             deletion_vector,
             partition_values,
+            tags,
             length: data.num_rows(),
             pointer: 0,
         })
@@ -400,6 +440,7 @@ impl<'a> FileStatsAccessor<'a> {
             partition_fields: self.partition_fields.clone(),
             stats: self.stats,
             deletion_vector: self.deletion_vector.clone(),
+            tags: self.tags,
             index,
         })
     }
@@ -463,6 +504,7 @@ impl<'a> IntoIterator for LogDataHandler<'a> {
 mod datafusion {
     use std::sync::Arc;
 
+    use ::datafusion::datasource::physical_plan::wrap_partition_value_in_dict;
     use arrow_arith::aggregate::sum;
     use arrow_array::Int64Array;
     use arrow_schema::DataType as ArrowDataType;
@@ -512,7 +554,13 @@ mod datafusion {
                 return Precision::Absent;
             };
 
-            if array.data_type().is_primitive() {
+            // string statistics may be truncated by writers, so they are only bounds
+            // on the actual values rather than exact minimums and maximums.
+            let is_string = matches!(
+                array.data_type(),
+                ArrowDataType::Utf8 | ArrowDataType::LargeUtf8
+            );
+            if array.data_type().is_primitive() || is_string {
                 let agg: Box<dyn AggregateExpr> = match fun {
                     AggregateFunction::Min => Box::new(Min::new(
                         // NOTE: this is just a placeholder, we never evalutae this expression
@@ -533,7 +581,13 @@ mod datafusion {
                     .update_batch(&[array.clone()])
                     .ok()
                     .and_then(|_| accum.evaluate().ok())
-                    .map(Precision::Exact)
+                    .map(|value| {
+                        if is_string {
+                            Precision::Inexact(value)
+                        } else {
+                            Precision::Exact(value)
+                        }
+                    })
                     .unwrap_or(Precision::Absent);
             }
 
@@ -576,7 +630,9 @@ mod datafusion {
             let min_value =
                 self.column_bounds(COL_MIN_VALUES, name.as_ref(), &AggregateFunction::Min);
             let min_value = match &min_value {
-                Precision::Exact(value) if value.is_null() => Precision::Absent,
+                Precision::Exact(value) | Precision::Inexact(value) if value.is_null() => {
+                    Precision::Absent
+                }
                 // TODO this is a hack, we should not be casting here but rather when we read the checkpoint data.
                 // it seems sometimes the min/max values are stored as nanoseconds and sometimes as microseconds?
                 Precision::Exact(ScalarValue::TimestampNanosecond(a, b)) => Precision::Exact(
@@ -588,7 +644,9 @@ mod datafusion {
             let max_value =
                 self.column_bounds(COL_MAX_VALUES, name.as_ref(), &AggregateFunction::Max);
             let max_value = match &max_value {
-                Precision::Exact(value) if value.is_null() => Precision::Absent,
+                Precision::Exact(value) | Precision::Inexact(value) if value.is_null() => {
+                    Precision::Absent
+                }
                 Precision::Exact(ScalarValue::TimestampNanosecond(a, b)) => Precision::Exact(
                     ScalarValue::TimestampMicrosecond(a.map(|v| v / 1000), b.clone()),
                 ),
@@ -604,6 +662,50 @@ mod datafusion {
         }
     }
 
+    impl FileStatsAccessor<'_> {
+        /// Statistics of a partition column, derived from the partition values of the files
+        fn partition_column_stats(&self, name: &str) -> DeltaResult<ColumnStatistics> {
+            let mut null_count = Precision::Exact(0);
+            let mut min_value = None::<ScalarValue>;
+            let mut max_value = None::<ScalarValue>;
+            for index in 0..self.length {
+                let file = self.get(index)?;
+                let value = match file.partition_values()?.get(name) {
+                    Some(value) if !value.is_null() => value.clone(),
+                    _ => {
+                        let num_records = file
+                            .num_records()
+                            .map(Precision::Exact)
+                            .unwrap_or(Precision::Absent);
+                        null_count = null_count.add(&num_records);
+                        continue;
+                    }
+                };
+                let value = ScalarValue::try_from_array(&value.to_array(1)?, 0)?;
+                // string and binary partition columns are dictionary encoded in scans
+                let value = match value.data_type() {
+                    ArrowDataType::Utf8
+                    | ArrowDataType::LargeUtf8
+                    | ArrowDataType::Binary
+                    | ArrowDataType::LargeBinary => wrap_partition_value_in_dict(value),
+                    _ => value,
+                };
+                if min_value.as_ref().map_or(true, |min| value < *min) {
+                    min_value = Some(value.clone());
+                }
+                if max_value.as_ref().map_or(true, |max| value > *max) {
+                    max_value = Some(value);
+                }
+            }
+            Ok(ColumnStatistics {
+                null_count,
+                max_value: max_value.map(Precision::Exact).unwrap_or(Precision::Absent),
+                min_value: min_value.map(Precision::Exact).unwrap_or(Precision::Absent),
+                distinct_count: Precision::Absent,
+            })
+        }
+    }
+
     trait StatsExt {
         fn add(&self, other: &Self) -> Self;
     }
@@ -658,15 +760,44 @@ mod datafusion {
                 })
         }
 
+        pub(crate) fn partition_column_stats(&self, name: &str) -> Option<ColumnStatistics> {
+            self.data
+                .iter()
+                .flat_map(|b| {
+                    FileStatsAccessor::try_new(b, self.metadata, self.schema)
+                        .map(|a| a.partition_column_stats(name))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .ok()?
+                .iter()
+                .fold(None::<ColumnStatistics>, |acc, stats| match (acc, stats) {
+                    (None, stats) => Some(stats.clone()),
+                    (Some(acc), stats) => Some(acc.add(stats)),
+                })
+        }
+
+        /// Table statistics with the column statistics ordered like the scanned schema, i.e.
+        /// with the partition columns last in the order they are partitioned by.
         pub(crate) fn statistics(&self) -> Option<Statistics> {
+            if self.data.iter().all(|b| b.num_rows() == 0) {
+                return None;
+            }
             let num_rows = self.num_records();
             let total_byte_size = self.total_size_files();
+            let partition_columns = &self.metadata.partition_columns;
             let column_statistics = self
                 .schema
                 .fields()
                 .iter()
+                .filter(|f| !partition_columns.contains(f.name()))
                 .map(|f| self.column_stats(f.name()))
-                .collect::<Option<Vec<_>>>()?;
+                .chain(
+                    partition_columns
+                        .iter()
+                        .map(|name| self.partition_column_stats(name)),
+                )
+                .map(|stats| stats.unwrap_or_else(ColumnStatistics::new_unknown))
+                .collect();
             Some(Statistics {
                 num_rows,
                 total_byte_size,
@@ -690,6 +821,7 @@ mod tests {
             .unwrap()
             .snapshot
             .files()
+            .unwrap()
             .find(|f| {
                 f.path().ends_with(
                     "part-00000-7a509247-4f58-4453-9202-51d75dee59af-c000.snappy.parquet",
@@ -702,6 +834,7 @@ mod tests {
             .unwrap()
             .snapshot
             .files()
+            .unwrap()
             .find(|f| {
                 f.path().ends_with(
                     "part-00000-7a509247-4f58-4453-9202-51d75dee59af-c000.snappy.parquet",
@@ -724,6 +857,35 @@ mod tests {
         // );
     }
 
+    #[tokio::test]
+    async fn read_deletion_vector_coverage() {
+        let table = crate::open_table("../test/tests/data/table-with-dv-small")
+            .await
+            .unwrap();
+        let snapshot = table.snapshot().unwrap();
+        let coverage = snapshot
+            .log_data()
+            .unwrap()
+            .into_iter()
+            .map(|file| file.deletion_vector_coverage())
+            .collect::<Vec<_>>();
+        assert_eq!(coverage, vec![Some(0.2)]);
+        assert_eq!(
+            snapshot
+                .files_by_deletion_vector_coverage(0.1)
+                .unwrap()
+                .count(),
+            1
+        );
+        assert_eq!(
+            snapshot
+                .files_by_deletion_vector_coverage(0.5)
+                .unwrap()
+                .count(),
+            0
+        );
+    }
+
     #[tokio::test]
     async fn df_stats_delta_1_2_1_struct_stats_table() {
         let table_uri = "../test/tests/data/delta-1.2.1-only-struct-stats";
@@ -733,7 +895,8 @@ mod tests {
             .snapshot()
             .unwrap()
             .snapshot
-            .log_data();
+            .log_data()
+            .unwrap();
 
         let col_stats = file_stats.statistics();
         println!("{:?}", col_stats);
@@ -1,5 +1,5 @@
 use std::cmp::Ordering;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 
 use arrow_array::RecordBatch;
@@ -16,7 +16,10 @@ use serde::{Deserialize, Serialize};
 use tracing::debug;
 
 use super::parse;
-use crate::kernel::{arrow::json, ActionType, Metadata, Protocol, Schema, StructType};
+use crate::kernel::{
+    arrow::json, ActionType, DomainMetadata, Metadata, Protocol, Schema, StructType,
+};
+use crate::logstore::compression::decompress_commit;
 use crate::logstore::LogStore;
 use crate::operations::transaction::CommitData;
 use crate::{DeltaResult, DeltaTableConfig, DeltaTableError};
@@ -154,7 +157,7 @@ impl LogSegment {
         log_store.refresh().await?;
         let log_url = table_root.child("_delta_log");
         let (mut commit_files, checkpoint_files) = list_log_files(
-            log_store.object_store().as_ref(),
+            log_store.log_object_store().as_ref(),
             &log_url,
             end_version,
             Some(start_version),
@@ -244,13 +247,33 @@ impl LogSegment {
         config: &DeltaTableConfig,
     ) -> DeltaResult<BoxStream<'_, DeltaResult<RecordBatch>>> {
         let decoder = json::get_decoder(Arc::new(read_schema.try_into()?), config)?;
+        let policy = config.unknown_action_policy;
         let stream = futures::stream::iter(self.commit_files.iter())
             .map(move |meta| {
                 let store = store.clone();
-                async move { store.get(&meta.location).await?.bytes().await }
+                async move {
+                    let data = store.get(&meta.location).await?.bytes().await?;
+                    let data =
+                        decompress_commit(data).map_err(|err| ObjectStoreError::Generic {
+                            store: "DeltaLog",
+                            source: Box::new(err),
+                        })?;
+                    let version = meta.location.commit_version().unwrap_or_default();
+                    policy.check_commit(version, &data).map_err(|err| {
+                        ObjectStoreError::Generic {
+                            store: "DeltaLog",
+                            source: Box::new(err),
+                        }
+                    })?;
+                    Ok(data)
+                }
             })
             .buffered(config.log_buffer_size);
-        Ok(json::decode_stream(decoder, stream).boxed())
+        #[cfg(feature = "simd-json")]
+        let batches = json::decode_commits(decoder, stream, config.log_batch_size);
+        #[cfg(not(feature = "simd-json"))]
+        let batches = json::decode_stream(decoder, stream);
+        Ok(batches.boxed())
     }
 
     pub(super) fn checkpoint_stream(
@@ -332,6 +355,38 @@ impl LogSegment {
         Ok((maybe_protocol, maybe_metadata))
     }
 
+    /// Read the latest [`DomainMetadata`] action of every domain which hasn't been removed
+    pub(super) async fn read_domain_metadata(
+        &self,
+        store: Arc<dyn ObjectStore>,
+        config: &DeltaTableConfig,
+    ) -> DeltaResult<HashMap<String, DomainMetadata>> {
+        lazy_static::lazy_static! {
+            static ref READ_SCHEMA: StructType = StructType::new(vec![
+                ActionType::DomainMetadata.schema_field().clone(),
+            ]);
+        }
+
+        // commits are read newest first, so the first action seen for a domain is its latest
+        let mut domains = HashMap::new();
+        let mut commit_stream = self.commit_stream(store.clone(), &READ_SCHEMA, config)?;
+        while let Some(batch) = commit_stream.next().await {
+            for domain in parse::read_domain_metadata(&batch?)? {
+                domains.entry(domain.domain.clone()).or_insert(domain);
+            }
+        }
+
+        let mut checkpoint_stream = self.checkpoint_stream(store.clone(), &READ_SCHEMA, config);
+        while let Some(batch) = checkpoint_stream.next().await {
+            for domain in parse::read_domain_metadata(&batch?)? {
+                domains.entry(domain.domain.clone()).or_insert(domain);
+            }
+        }
+
+        domains.retain(|_, domain| !domain.removed);
+        Ok(domains)
+    }
+
     /// Advance the log segment with new commits
     ///
     /// Returns an iterator over record batches, as if the commits were read from the log.
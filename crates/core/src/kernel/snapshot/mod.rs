@@ -15,11 +15,12 @@
 //!
 //!
 
-use std::io::{BufRead, BufReader, Cursor};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use ::serde::{Deserialize, Serialize};
 use arrow_array::RecordBatch;
+use arrow_select::concat::concat_batches;
 use futures::stream::BoxStream;
 use futures::{StreamExt, TryStreamExt};
 use object_store::path::Path;
@@ -28,9 +29,12 @@ use object_store::ObjectStore;
 use self::log_segment::{LogSegment, PathExt};
 use self::parse::{read_adds, read_removes};
 use self::replay::{LogMapper, LogReplayScanner, ReplayStream};
-use super::{Action, Add, CommitInfo, DataType, Metadata, Protocol, Remove, StructField};
+use super::{
+    Action, Add, CommitInfo, DataType, DomainMetadata, Metadata, Protocol, Remove, StructField,
+};
 use crate::kernel::StructType;
-use crate::logstore::LogStore;
+use crate::logstore::compression::decompress_commit;
+use crate::logstore::{log_lines, parse_action, LogStore};
 use crate::operations::transaction::CommitData;
 use crate::table::config::TableConfig;
 use crate::{DeltaResult, DeltaTableConfig, DeltaTableError};
@@ -87,7 +91,6 @@ impl Snapshot {
     pub fn new_test<'a>(
         commits: impl IntoIterator<Item = &'a CommitData>,
     ) -> DeltaResult<(Self, RecordBatch)> {
-        use arrow_select::concat::concat_batches;
         let (log_segment, batches) = LogSegment::new_test(commits)?;
         let batch = batches.into_iter().collect::<Result<Vec<_>, _>>()?;
         let batch = concat_batches(&batch[0].schema(), &batch)?;
@@ -142,7 +145,7 @@ impl Snapshot {
         }
 
         let (protocol, metadata) = log_segment
-            .read_metadata(log_store.object_store().clone(), &self.config)
+            .read_metadata(log_store.log_object_store().clone(), &self.config)
             .await?;
         if let Some(protocol) = protocol {
             self.protocol = protocol;
@@ -171,6 +174,15 @@ impl Snapshot {
         self.log_segment.version()
     }
 
+    /// Versions of the commits replayed on top of the checkpoint of the snapshot
+    pub(crate) fn commit_versions(&self) -> Vec<i64> {
+        self.log_segment
+            .commit_files
+            .iter()
+            .filter_map(|file| file.location.commit_version())
+            .collect()
+    }
+
     /// Get the table schema of the snapshot
     pub fn schema(&self) -> &StructType {
         &self.schema
@@ -186,11 +198,28 @@ impl Snapshot {
         &self.protocol
     }
 
+    /// Get the latest configuration of every metadata domain of the table, keyed by domain.
+    ///
+    /// Removed domains are not included.
+    pub async fn domain_metadata(
+        &self,
+        store: Arc<dyn ObjectStore>,
+    ) -> DeltaResult<HashMap<String, DomainMetadata>> {
+        self.log_segment
+            .read_domain_metadata(store, &self.config)
+            .await
+    }
+
     /// Get the table root of the snapshot
     pub fn table_root(&self) -> Path {
         Path::from(self.table_url.clone())
     }
 
+    /// The configuration used to load the snapshot
+    pub(crate) fn load_config(&self) -> &DeltaTableConfig {
+        &self.config
+    }
+
     /// Well known table configuration
     pub fn table_config(&self) -> TableConfig<'_> {
         TableConfig(&self.metadata.configuration)
@@ -214,6 +243,22 @@ impl Snapshot {
         ReplayStream::try_new(log_stream, checkpoint_stream, self)
     }
 
+    /// Stream the [`Add`] actions of the files in the snapshot.
+    ///
+    /// The log is replayed one record batch at a time, so the file list is never held in
+    /// memory as a whole.
+    pub fn file_actions_stream(
+        &self,
+        store: Arc<dyn ObjectStore>,
+    ) -> DeltaResult<BoxStream<'_, DeltaResult<Add>>> {
+        Ok(self
+            .files(store)?
+            .map(|batch| batch.and_then(|batch| read_adds(&batch)))
+            .map_ok(|adds| futures::stream::iter(adds.into_iter().map(Ok)))
+            .try_flatten()
+            .boxed())
+    }
+
     /// Get the commit infos in the snapshot
     pub(crate) async fn commit_infos(
         &self,
@@ -246,10 +291,11 @@ impl Snapshot {
             .map(move |meta| {
                 let store = store.clone();
                 async move {
-                    let commit_log_bytes = store.get(&meta.location).await?.bytes().await?;
-                    let reader = BufReader::new(Cursor::new(commit_log_bytes));
-                    for line in reader.lines() {
-                        let action: Action = serde_json::from_str(line?.as_str())?;
+                    let commit_log_bytes =
+                        decompress_commit(store.get(&meta.location).await?.bytes().await?)?;
+                    let mut scratch = Vec::new();
+                    for line in log_lines(&commit_log_bytes) {
+                        let action = parse_action(line, &mut scratch)?;
                         if let Action::CommitInfo(commit_info) = action {
                             return Ok::<_, DeltaTableError>(Some(commit_info));
                         }
@@ -355,7 +401,11 @@ impl EagerSnapshot {
         version: Option<i64>,
     ) -> DeltaResult<Self> {
         let snapshot = Snapshot::try_new(table_root, store.clone(), config, version).await?;
-        let files = snapshot.files(store)?.try_collect().await?;
+        let files = if snapshot.config.require_files {
+            snapshot.files(store)?.try_collect().await?
+        } else {
+            Vec::new()
+        };
         Ok(Self { snapshot, files })
     }
 
@@ -386,10 +436,13 @@ impl EagerSnapshot {
             .snapshot
             .update_inner(log_store.clone(), target_version)
             .await?;
+        if !self.snapshot.config.require_files {
+            return Ok(());
+        }
         if let Some(new_slice) = new_slice {
             let files = std::mem::take(&mut self.files);
             let log_stream = new_slice.commit_stream(
-                log_store.object_store().clone(),
+                log_store.log_object_store().clone(),
                 &log_segment::COMMIT_SCHEMA,
                 &self.snapshot.config,
             )?;
@@ -398,7 +451,7 @@ impl EagerSnapshot {
             } else {
                 new_slice
                     .checkpoint_stream(
-                        log_store.object_store(),
+                        log_store.log_object_store(),
                         &log_segment::CHECKPOINT_SCHEMA,
                         &self.snapshot.config,
                     )
@@ -410,7 +463,7 @@ impl EagerSnapshot {
                 .try_collect()
                 .await?;
 
-            self.files = files;
+            self.files = coalesce_file_batches(files)?;
         }
         Ok(())
     }
@@ -453,29 +506,55 @@ impl EagerSnapshot {
         self.snapshot.table_root()
     }
 
+    /// The configuration used to load the snapshot
+    pub(crate) fn load_config(&self) -> &DeltaTableConfig {
+        self.snapshot.load_config()
+    }
+
     /// Well known table configuration
     pub fn table_config(&self) -> TableConfig<'_> {
         self.snapshot.table_config()
     }
 
     /// Get a [`LogDataHandler`] for the snapshot to inspect the currently loaded state of the log.
-    pub fn log_data(&self) -> LogDataHandler<'_> {
-        LogDataHandler::new(&self.files, self.metadata(), self.schema())
+    pub fn log_data(&self) -> DeltaResult<LogDataHandler<'_>> {
+        self.require_files("log_data")?;
+        Ok(LogDataHandler::new(
+            &self.files,
+            self.metadata(),
+            self.schema(),
+        ))
     }
 
     /// Get the number of files in the snapshot
-    pub fn files_count(&self) -> usize {
-        self.files.iter().map(|f| f.num_rows()).sum()
+    pub fn files_count(&self) -> DeltaResult<usize> {
+        self.require_files("files_count")?;
+        Ok(self.files.iter().map(|f| f.num_rows()).sum())
+    }
+
+    /// Whether the files of the snapshot are loaded, see [`DeltaTableConfig::require_files`]
+    pub fn files_loaded(&self) -> bool {
+        self.snapshot.config.require_files
+    }
+
+    /// Fail accessing the files with `method` if they are not loaded, rather than pretending the
+    /// table has no files
+    fn require_files(&self, method: &str) -> DeltaResult<()> {
+        if !self.files_loaded() {
+            return Err(DeltaTableError::NotInitializedWithFiles(method.into()));
+        }
+        Ok(())
     }
 
     /// Get the files in the snapshot
     pub fn file_actions(&self) -> DeltaResult<impl Iterator<Item = Add> + '_> {
+        self.require_files("file_actions")?;
         Ok(self.files.iter().flat_map(|b| read_adds(b)).flatten())
     }
 
     /// Get a file action iterator for the given version
-    pub fn files(&self) -> impl Iterator<Item = LogicalFile<'_>> {
-        self.log_data().into_iter()
+    pub fn files(&self) -> DeltaResult<impl Iterator<Item = LogicalFile<'_>>> {
+        Ok(self.log_data()?.into_iter())
     }
 
     /// Advance the snapshot based on the given commit actions
@@ -501,6 +580,7 @@ impl EagerSnapshot {
             }
             send.push(commit);
         }
+        let files_loaded = self.files_loaded();
         let actions = self.snapshot.log_segment.advance(
             send,
             &self.table_root(),
@@ -512,19 +592,25 @@ impl EagerSnapshot {
         let mut scanner = LogReplayScanner::new();
 
         for batch in actions {
-            files.push(scanner.process_files_batch(&batch?, true)?);
+            let batch = batch?;
+            if files_loaded {
+                files.push(scanner.process_files_batch(&batch, true)?);
+            }
         }
 
-        let mapper = LogMapper::try_new(&self.snapshot)?;
-        self.files = files
-            .into_iter()
-            .chain(
-                self.files
-                    .iter()
-                    .flat_map(|batch| scanner.process_files_batch(batch, false)),
-            )
-            .map(|b| mapper.map_batch(b))
-            .collect::<DeltaResult<Vec<_>>>()?;
+        if files_loaded {
+            let mapper = LogMapper::try_new(&self.snapshot)?;
+            let files = files
+                .into_iter()
+                .chain(
+                    self.files
+                        .iter()
+                        .flat_map(|batch| scanner.process_files_batch(batch, false)),
+                )
+                .map(|b| mapper.map_batch(b))
+                .collect::<DeltaResult<Vec<_>>>()?;
+            self.files = coalesce_file_batches(files)?;
+        }
 
         if let Some(metadata) = metadata {
             self.snapshot.metadata = metadata;
@@ -538,6 +624,45 @@ impl EagerSnapshot {
     }
 }
 
+/// Number of rows up to which consecutive file batches are concatenated
+const COALESCE_TARGET_ROWS: usize = 64 * 1024;
+
+/// Drop empty file batches and concatenate consecutive small batches of the same schema.
+///
+/// Every incremental update adds the files of each new commit as separate batches, and files
+/// removed by later commits leave empty batches behind. Without coalescing, long-running
+/// readers polling a busy table accumulate a batch per commit, making every further update and
+/// scan slower.
+fn coalesce_file_batches(files: Vec<RecordBatch>) -> DeltaResult<Vec<RecordBatch>> {
+    let Some(first) = files.first().cloned() else {
+        return Ok(files);
+    };
+    let mut coalesced: Vec<RecordBatch> = Vec::new();
+    let mut pending: Vec<RecordBatch> = Vec::new();
+    let mut pending_rows = 0;
+    for batch in files.into_iter().filter(|batch| batch.num_rows() > 0) {
+        let fits = pending.first().map_or(true, |pending| {
+            pending.schema() == batch.schema()
+                && pending_rows + batch.num_rows() <= COALESCE_TARGET_ROWS
+        });
+        if !fits {
+            coalesced.push(concat_batches(&pending[0].schema(), &pending)?);
+            pending.clear();
+            pending_rows = 0;
+        }
+        pending_rows += batch.num_rows();
+        pending.push(batch);
+    }
+    if !pending.is_empty() {
+        coalesced.push(concat_batches(&pending[0].schema(), &pending)?);
+    }
+    if coalesced.is_empty() {
+        // keep the schema of the files around when all files were removed
+        coalesced.push(first.slice(0, 0));
+    }
+    Ok(coalesced)
+}
+
 fn to_count_field(field: &StructField) -> Option<StructField> {
     match field.data_type() {
         DataType::Map(_) | DataType::Array(_) | &DataType::BINARY => None,
@@ -564,7 +689,7 @@ mod datafusion {
     impl EagerSnapshot {
         /// Provide table level statistics to Datafusion
         pub fn datafusion_table_statistics(&self) -> Option<Statistics> {
-            self.log_data().statistics()
+            self.log_data().ok()?.statistics()
         }
     }
 }
@@ -584,6 +709,90 @@ mod tests {
     use crate::kernel::Remove;
     use crate::protocol::{DeltaOperation, SaveMode};
 
+    #[tokio::test]
+    async fn test_incremental_updates_coalesce_files() -> TestResult {
+        use crate::operations::DeltaOps;
+        use crate::writer::test_utils::{get_delta_schema, get_record_batch};
+
+        let mut table = DeltaOps::new_in_memory()
+            .create()
+            .with_columns(get_delta_schema().fields().clone())
+            .await?;
+        let mut reader = crate::DeltaTable::new(table.log_store(), Default::default());
+        reader.load().await?;
+        for _ in 0..20 {
+            table = DeltaOps(table)
+                .write(vec![get_record_batch(None, false)])
+                .await?;
+            reader.update().await?;
+        }
+        for snapshot in [table.snapshot()?.snapshot(), reader.snapshot()?.snapshot()] {
+            assert_eq!(snapshot.version(), 20);
+            assert_eq!(snapshot.files_count().unwrap(), 20);
+            assert_eq!(snapshot.files.len(), 1);
+        }
+
+        let table = DeltaOps(table)
+            .write(vec![get_record_batch(None, false)])
+            .with_save_mode(SaveMode::Overwrite)
+            .await?;
+        reader.update().await?;
+        assert_eq!(reader.snapshot()?.files_count()?, 1);
+        assert_eq!(table.snapshot()?.files_count()?, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_coalesce_file_batches() {
+        use arrow_array::{Int32Array, StringArray};
+        use arrow_schema::{DataType as ArrowDataType, Field, Schema as ArrowSchema};
+
+        let ints = Arc::new(ArrowSchema::new(vec![Field::new(
+            "a",
+            ArrowDataType::Int32,
+            true,
+        )]));
+        let strings = Arc::new(ArrowSchema::new(vec![Field::new(
+            "a",
+            ArrowDataType::Utf8,
+            true,
+        )]));
+        let int_batch = |n: i32| {
+            RecordBatch::try_new(
+                ints.clone(),
+                vec![Arc::new(Int32Array::from_iter_values(0..n))],
+            )
+            .unwrap()
+        };
+        let string_batch = RecordBatch::try_new(
+            strings.clone(),
+            vec![Arc::new(StringArray::from(vec!["x"]))],
+        )
+        .unwrap();
+
+        let coalesced = coalesce_file_batches(vec![
+            int_batch(2),
+            int_batch(0),
+            int_batch(3),
+            string_batch.clone(),
+            int_batch(1),
+        ])
+        .unwrap();
+        let rows = coalesced.iter().map(|b| b.num_rows()).collect_vec();
+        assert_eq!(rows, vec![5, 1, 1]);
+
+        let coalesced =
+            coalesce_file_batches(vec![int_batch(COALESCE_TARGET_ROWS as i32), int_batch(1)])
+                .unwrap();
+        assert_eq!(coalesced.len(), 2);
+
+        let coalesced = coalesce_file_batches(vec![int_batch(0), int_batch(0)]).unwrap();
+        assert_eq!(coalesced.len(), 1);
+        assert_eq!(coalesced[0].num_rows(), 0);
+        assert!(coalesce_file_batches(vec![]).unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_snapshots() -> TestResult {
         let context = IntegrationContext::new(Box::<LocalStorageIntegration>::default())?;
@@ -6,7 +6,7 @@ use arrow_array::{
 use percent_encoding::percent_decode_str;
 
 use crate::kernel::arrow::extract::{self as ex, ProvidesColumnByName};
-use crate::kernel::{Add, DeletionVectorDescriptor, Metadata, Protocol, Remove};
+use crate::kernel::{Add, DeletionVectorDescriptor, DomainMetadata, Metadata, Protocol, Remove};
 use crate::{DeltaResult, DeltaTableError};
 
 pub(super) fn read_metadata(batch: &dyn ProvidesColumnByName) -> DeltaResult<Option<Metadata>> {
@@ -64,6 +64,30 @@ pub(super) fn read_protocol(batch: &dyn ProvidesColumnByName) -> DeltaResult<Opt
     Ok(None)
 }
 
+pub(super) fn read_domain_metadata(
+    batch: &dyn ProvidesColumnByName,
+) -> DeltaResult<Vec<DomainMetadata>> {
+    let mut result = Vec::new();
+
+    if let Some(arr) = ex::extract_and_cast_opt::<StructArray>(batch, "domainMetadata") {
+        let domain = ex::extract_and_cast::<StringArray>(arr, "domain")?;
+        let configuration = ex::extract_and_cast::<StringArray>(arr, "configuration")?;
+        let removed = ex::extract_and_cast::<BooleanArray>(arr, "removed")?;
+
+        for idx in 0..arr.len() {
+            if arr.is_valid(idx) {
+                result.push(DomainMetadata {
+                    domain: ex::read_str(domain, idx)?.to_string(),
+                    configuration: ex::read_str(configuration, idx)?.to_string(),
+                    removed: ex::read_bool(removed, idx)?,
+                });
+            }
+        }
+    }
+
+    Ok(result)
+}
+
 pub(super) fn read_adds(array: &dyn ProvidesColumnByName) -> DeltaResult<Vec<Add>> {
     let mut result = Vec::new();
 
@@ -4,17 +4,20 @@ use std::task::Context;
 use std::task::Poll;
 
 use arrow_arith::boolean::{is_not_null, or};
+use arrow_array::cast::AsArray;
 use arrow_array::{
-    Array, ArrayRef, BooleanArray, Int32Array, RecordBatch, StringArray, StructArray,
+    new_null_array, Array, ArrayRef, BooleanArray, Int32Array, RecordBatch, StringArray,
+    StructArray,
 };
+use arrow_buffer::NullBuffer;
+use arrow_cast::cast;
 use arrow_schema::{
-    DataType as ArrowDataType, Field as ArrowField, Schema as ArrowSchema,
+    DataType as ArrowDataType, Field as ArrowField, Fields, Schema as ArrowSchema,
     SchemaRef as ArrowSchemaRef,
 };
 use arrow_select::filter::filter_record_batch;
 use futures::Stream;
 use hashbrown::HashSet;
-use itertools::Itertools;
 use percent_encoding::percent_decode_str;
 use pin_project_lite::pin_project;
 use tracing::debug;
@@ -78,53 +81,98 @@ fn map_batch(
     stats_schema: ArrowSchemaRef,
     config: &DeltaTableConfig,
 ) -> DeltaResult<RecordBatch> {
+    let stats_type = ArrowDataType::Struct(stats_schema.fields().clone());
     let stats_col = ex::extract_and_cast_opt::<StringArray>(&batch, "add.stats");
-    let stats_parsed_col = ex::extract_and_cast_opt::<StringArray>(&batch, "add.stats_parsed");
-    if stats_parsed_col.is_some() {
+    let stats_parsed_col = ex::extract_and_cast_opt::<StructArray>(&batch, "add.stats_parsed");
+    // batches that were already mapped with the current stats schema
+    if stats_parsed_col.is_some_and(|parsed| parsed.data_type() == &stats_type) {
         return Ok(batch);
     }
-    if let Some(stats) = stats_col {
-        let stats: Arc<StructArray> =
-            Arc::new(json::parse_json(stats, stats_schema.clone(), config)?.into());
-        let schema = batch.schema();
-        let add_col = ex::extract_and_cast::<StructArray>(&batch, "add")?;
-        let (add_idx, _) = schema.column_with_name("add").unwrap();
-        let add_type = add_col
-            .fields()
-            .iter()
-            .cloned()
-            .chain(std::iter::once(Arc::new(ArrowField::new(
-                "stats_parsed",
-                ArrowDataType::Struct(stats_schema.fields().clone()),
-                true,
-            ))))
-            .collect_vec();
-        let new_add = Arc::new(StructArray::try_new(
-            add_type.clone().into(),
-            add_col
-                .columns()
+    let stats: Arc<StructArray> = match (stats_col, stats_parsed_col) {
+        (Some(stats), _) => Arc::new(json::parse_json(stats, stats_schema.clone(), config)?.into()),
+        // stats parsed with the stats schema of an earlier version of the table
+        (None, Some(parsed)) => Arc::new(align_struct(parsed, stats_schema.fields())?),
+        (None, None) => return Ok(batch),
+    };
+    let schema = batch.schema();
+    let add_col = ex::extract_and_cast::<StructArray>(&batch, "add")?;
+    let (add_idx, _) = schema.column_with_name("add").unwrap();
+    let (add_type, add_columns): (Vec<_>, Vec<_>) = add_col
+        .fields()
+        .iter()
+        .cloned()
+        .zip(add_col.columns().iter().cloned())
+        .filter(|(field, _)| field.name() != "stats_parsed")
+        .chain(std::iter::once((
+            Arc::new(ArrowField::new("stats_parsed", stats_type, true)),
+            stats as ArrayRef,
+        )))
+        .unzip();
+    let new_add = Arc::new(StructArray::try_new(
+        add_type.clone().into(),
+        add_columns,
+        add_col.nulls().cloned(),
+    )?);
+    let new_add_field = Arc::new(ArrowField::new(
+        "add",
+        ArrowDataType::Struct(add_type.into()),
+        true,
+    ));
+    let mut fields = schema.fields().to_vec();
+    let _ = std::mem::replace(&mut fields[add_idx], new_add_field);
+    let mut columns = batch.columns().to_vec();
+    let _ = std::mem::replace(&mut columns[add_idx], new_add);
+    Ok(RecordBatch::try_new(
+        Arc::new(ArrowSchema::new(fields)),
+        columns,
+    )?)
+}
+
+/// Reshape `array` to `fields`, matching its children by name. Children that are missing or
+/// can't be cast to their new type are null, i.e. no statistics are known for them.
+fn align_struct(array: &StructArray, fields: &Fields) -> DeltaResult<StructArray> {
+    let columns = fields
+        .iter()
+        .map(
+            |field| match (array.column_by_name(field.name()), field.data_type()) {
+                // structs without fields don't know their length
+                (_, ArrowDataType::Struct(children)) if children.is_empty() => {
+                    Ok(Arc::new(StructArray::new_empty_fields(array.len(), None)) as ArrayRef)
+                }
+                (Some(column), ArrowDataType::Struct(children))
+                    if matches!(column.data_type(), ArrowDataType::Struct(_)) =>
+                {
+                    Ok(Arc::new(align_struct(column.as_struct(), children)?) as ArrayRef)
+                }
+                (Some(column), data_type) => {
+                    Ok(cast(column, data_type).or_else(|_| null_array(data_type, array.len()))?)
+                }
+                (None, data_type) => null_array(data_type, array.len()),
+            },
+        )
+        .collect::<DeltaResult<Vec<_>>>()?;
+    Ok(StructArray::try_new(
+        fields.clone(),
+        columns,
+        array.nulls().cloned(),
+    )?)
+}
+
+fn null_array(data_type: &ArrowDataType, len: usize) -> DeltaResult<ArrayRef> {
+    match data_type {
+        ArrowDataType::Struct(fields) if fields.is_empty() => Ok(Arc::new(
+            StructArray::new_empty_fields(len, Some(NullBuffer::new_null(len))),
+        )),
+        ArrowDataType::Struct(fields) => Ok(Arc::new(StructArray::try_new(
+            fields.clone(),
+            fields
                 .iter()
-                .cloned()
-                .chain(std::iter::once(stats as ArrayRef))
-                .collect(),
-            add_col.nulls().cloned(),
-        )?);
-        let new_add_field = Arc::new(ArrowField::new(
-            "add",
-            ArrowDataType::Struct(add_type.into()),
-            true,
-        ));
-        let mut fields = schema.fields().to_vec();
-        let _ = std::mem::replace(&mut fields[add_idx], new_add_field);
-        let mut columns = batch.columns().to_vec();
-        let _ = std::mem::replace(&mut columns[add_idx], new_add);
-        return Ok(RecordBatch::try_new(
-            Arc::new(ArrowSchema::new(fields)),
-            columns,
-        )?);
+                .map(|field| null_array(field.data_type(), len))
+                .collect::<DeltaResult<_>>()?,
+            Some(NullBuffer::new_null(len)),
+        )?)),
+        _ => Ok(new_null_array(data_type, len)),
     }
-
-    Ok(batch)
 }
 
 impl<S> Stream for ReplayStream<S>
@@ -393,4 +441,87 @@ pub(super) mod tests {
 
         Ok(())
     }
+
+    fn stats_schema(min_value_fields: Vec<ArrowField>) -> ArrowSchemaRef {
+        Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("numRecords", ArrowDataType::Int64, true),
+            ArrowField::new(
+                "minValues",
+                ArrowDataType::Struct(min_value_fields.into()),
+                true,
+            ),
+        ]))
+    }
+
+    /// A file batch mapped with `old_schema`, with or without the raw json stats. Batches
+    /// without them only carry the parsed stats, like checkpoints written with `stats_parsed`.
+    fn file_batch(stats: Option<&str>, old_schema: &ArrowSchemaRef) -> TestResult<RecordBatch> {
+        let config = DeltaTableConfig::default();
+        let json = StringArray::from(vec![
+            stats.unwrap_or(r#"{"numRecords":2,"minValues":{"a":1}}"#)
+        ]);
+        let parsed: StructArray = json::parse_json(&json, old_schema.clone(), &config)?.into();
+        let mut fields = vec![ArrowField::new("path", ArrowDataType::Utf8, true)];
+        let mut columns: Vec<ArrayRef> = vec![Arc::new(StringArray::from(vec!["part-0.parquet"]))];
+        if stats.is_some() {
+            fields.push(ArrowField::new("stats", ArrowDataType::Utf8, true));
+            columns.push(Arc::new(json));
+        }
+        fields.push(ArrowField::new(
+            "stats_parsed",
+            parsed.data_type().clone(),
+            true,
+        ));
+        columns.push(Arc::new(parsed));
+        let add = StructArray::try_new(fields.into(), columns, None)?;
+        Ok(RecordBatch::try_from_iter(vec![(
+            "add",
+            Arc::new(add) as ArrayRef,
+        )])?)
+    }
+
+    #[test]
+    fn test_map_batch_with_changed_stats_schema() -> TestResult {
+        let config = DeltaTableConfig::default();
+        let old_schema = stats_schema(vec![ArrowField::new("a", ArrowDataType::Int32, true)]);
+        let new_schema = stats_schema(vec![
+            ArrowField::new("a", ArrowDataType::Int64, true),
+            ArrowField::new("b", ArrowDataType::Utf8, true),
+        ]);
+        let new_type = ArrowDataType::Struct(new_schema.fields().clone());
+
+        for stats in [
+            Some(r#"{"numRecords":2,"minValues":{"a":1,"b":"x"}}"#),
+            None,
+        ] {
+            let batch = file_batch(stats, &old_schema)?;
+            let mapped = map_batch(batch, new_schema.clone(), &config)?;
+            let add = ex::extract_and_cast::<StructArray>(&mapped, "add")?;
+            assert_eq!(
+                add.fields()
+                    .iter()
+                    .filter(|f| f.name() == "stats_parsed")
+                    .count(),
+                1
+            );
+            let parsed = ex::extract_and_cast::<StructArray>(&mapped, "add.stats_parsed")?;
+            assert_eq!(parsed.data_type(), &new_type);
+            let min_values = parsed.column_by_name("minValues").unwrap().as_struct();
+            let a = min_values.column_by_name("a").unwrap();
+            assert_eq!(
+                a.as_primitive::<arrow_array::types::Int64Type>().value(0),
+                1
+            );
+            let b = min_values.column_by_name("b").unwrap().as_string::<i32>();
+            match stats {
+                Some(_) => assert_eq!(b.value(0), "x"),
+                None => assert!(b.is_null(0)),
+            }
+
+            // mapping again with the same schema leaves the batch as it is
+            let remapped = map_batch(mapped.clone(), new_schema.clone(), &config)?;
+            assert_eq!(remapped, mapped);
+        }
+        Ok(())
+    }
 }
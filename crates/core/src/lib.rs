@@ -69,6 +69,7 @@
 #![allow(rustdoc::invalid_html_tags)]
 #![allow(clippy::nonminimal_bool)]
 
+pub mod bench_utils;
 pub mod data_catalog;
 pub mod errors;
 pub mod kernel;
@@ -76,6 +77,7 @@ pub mod logstore;
 pub mod operations;
 pub mod protocol;
 pub mod schema;
+pub mod service;
 pub mod storage;
 pub mod table;
 
@@ -159,6 +161,51 @@ pub async fn open_table_with_ds(
     Ok(table)
 }
 
+/// Creates and loads a DeltaTable registered as `database_name.table_name` in a data catalog,
+/// such as Unity Catalog or the Glue Data Catalog.
+///
+/// The storage options are used to access the storage location resolved by the catalog.
+pub async fn open_table_from_catalog(
+    catalog: &dyn DataCatalog,
+    catalog_id: Option<String>,
+    database_name: &str,
+    table_name: &str,
+    storage_options: HashMap<String, String>,
+) -> Result<DeltaTable, DeltaTableError> {
+    let table_uri = catalog
+        .get_table_storage_location(catalog_id, database_name, table_name)
+        .await
+        .map_err(|err| DeltaTableError::GenericError {
+            source: Box::new(err),
+        })?;
+    open_table_with_storage_options(table_uri, storage_options).await
+}
+
+/// Creates and loads a DeltaTable addressed by a catalog URI such as `glue://my_db.my_table` or
+/// `unity://catalog.schema.table`.
+///
+/// The scheme selects the catalog registered with
+/// [`register_catalog`](crate::data_catalog::register_catalog), which resolves the storage
+/// location of the table. The storage options are used to access that location.
+pub async fn open_table_from_catalog_uri(
+    uri: &str,
+    storage_options: HashMap<String, String>,
+) -> Result<DeltaTable, DeltaTableError> {
+    let to_delta_error = |err: DataCatalogError| DeltaTableError::GenericError {
+        source: Box::new(err),
+    };
+    let uri = data_catalog::CatalogUri::parse(uri).map_err(to_delta_error)?;
+    let catalog = uri.catalog().map_err(to_delta_error)?;
+    open_table_from_catalog(
+        catalog.as_ref(),
+        uri.catalog_id,
+        &uri.database_name,
+        &uri.table_name,
+        storage_options,
+    )
+    .await
+}
+
 /// Returns rust crate version, can be use used in language bindings to expose Rust core version
 pub fn crate_version() -> &'static str {
     env!("CARGO_PKG_VERSION")
@@ -172,6 +219,65 @@ mod tests {
     use crate::table::PeekCommit;
     use std::collections::HashMap;
 
+    #[derive(Debug)]
+    struct StaticCatalog;
+
+    #[async_trait::async_trait]
+    impl DataCatalog for StaticCatalog {
+        async fn get_table_storage_location(
+            &self,
+            _catalog_id: Option<String>,
+            database_name: &str,
+            table_name: &str,
+        ) -> Result<String, DataCatalogError> {
+            match (database_name, table_name) {
+                ("default", "simple") => Ok("../test/tests/data/simple_table".to_string()),
+                _ => Err(DataCatalogError::InvalidDataCatalog {
+                    data_catalog: format!("{database_name}.{table_name}"),
+                }),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn open_table_from_static_catalog() {
+        let table = crate::open_table_from_catalog(
+            &StaticCatalog,
+            None,
+            "default",
+            "simple",
+            HashMap::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(table.version(), 4);
+
+        let result = crate::open_table_from_catalog(
+            &StaticCatalog,
+            None,
+            "default",
+            "other",
+            HashMap::new(),
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn open_table_from_registered_catalog() {
+        crate::data_catalog::register_catalog("static", std::sync::Arc::new(StaticCatalog));
+        let table = crate::open_table_from_catalog_uri("static://default.simple", HashMap::new())
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 4);
+
+        assert!(
+            crate::open_table_from_catalog_uri("unknown://default.simple", HashMap::new())
+                .await
+                .is_err()
+        );
+    }
+
     #[tokio::test]
     async fn read_delta_2_0_table_without_version() {
         let table = crate::open_table("../test/tests/data/delta-0.2.0")
@@ -191,7 +297,7 @@ mod tests {
         let tombstones = table
             .snapshot()
             .unwrap()
-            .all_tombstones(table.object_store().clone())
+            .all_tombstones(table.log_store().log_object_store())
             .await
             .unwrap()
             .collect_vec();
@@ -286,7 +392,7 @@ mod tests {
                 Path::from("part-00000-c9b90f86-73e6-46c8-93ba-ff6bfaf892a1-c000.snappy.parquet"),
             ]
         );
-        assert_eq!(table.get_files_count(), 2);
+        assert_eq!(table.get_files_count().unwrap(), 2);
 
         let stats = table.snapshot().unwrap().add_actions_table(true).unwrap();
 
@@ -308,7 +414,7 @@ mod tests {
         let tombstones = table
             .snapshot()
             .unwrap()
-            .all_tombstones(table.object_store().clone())
+            .all_tombstones(table.log_store().log_object_store())
             .await
             .unwrap()
             .collect_vec();
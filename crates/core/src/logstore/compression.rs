@@ -0,0 +1,303 @@
+//! Compression of commit files in the `_delta_log`.
+//!
+//! Tables with very high commit rates spend most of their log storage and replay bandwidth on
+//! JSON commits. Such tables may store commits compressed with gzip or zstd. Compressed commits
+//! keep their `<version>.json` name, and readers recognize the compression from the leading
+//! magic bytes of a commit, which can never start a plain JSON commit.
+//!
+//! Writers only compress commits of tables which set the [`LOG_COMPRESSION_KEY`] property and
+//! whose protocol lists the `logCompression` reader and writer feature, so that readers which
+//! cannot decompress commits refuse to read the table. Reading and writing such tables requires
+//! the `log-compression` feature.
+
+use std::str::FromStr;
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::DeltaTableError;
+#[cfg(feature = "log-compression")]
+use crate::kernel::{ReaderFeatures, WriterFeatures};
+use crate::operations::transaction::TableReference;
+
+/// Table property setting the compression of new commit files, `none`, `gzip` or `zstd`.
+///
+/// The property is specific to delta-rs and therefore lives outside of the reserved `delta.`
+/// namespace.
+pub const LOG_COMPRESSION_KEY: &str = "delta-rs.logCompression";
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+#[cfg(feature = "log-compression")]
+const ZSTD_LEVEL: i32 = 3;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// The compression applied to commit files
+#[serde(rename_all = "camelCase")]
+pub enum LogCompression {
+    /// Plain JSON commits
+    #[default]
+    None,
+    /// gzip compressed commits
+    Gzip,
+    /// zstd compressed commits
+    Zstd,
+}
+
+impl AsRef<str> for LogCompression {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::None => "none",
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+        }
+    }
+}
+
+impl FromStr for LogCompression {
+    type Err = DeltaTableError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" | "uncompressed" => Ok(Self::None),
+            "gzip" => Ok(Self::Gzip),
+            "zstd" => Ok(Self::Zstd),
+            _ => Err(DeltaTableError::Generic(
+                "Invalid string for LogCompression".into(),
+            )),
+        }
+    }
+}
+
+/// The compression of a commit file, detected from its leading bytes
+pub fn detect_compression(data: &[u8]) -> LogCompression {
+    if data.starts_with(&GZIP_MAGIC) {
+        LogCompression::Gzip
+    } else if data.starts_with(&ZSTD_MAGIC) {
+        LogCompression::Zstd
+    } else {
+        LogCompression::None
+    }
+}
+
+/// Decompress the content of a commit file, returning plain commits unchanged
+#[cfg(feature = "log-compression")]
+pub fn decompress_commit(data: Bytes) -> std::io::Result<Bytes> {
+    use std::io::Read;
+
+    match detect_compression(&data) {
+        LogCompression::None => Ok(data),
+        LogCompression::Gzip => {
+            let mut decompressed = Vec::new();
+            flate2::read::GzDecoder::new(data.as_ref()).read_to_end(&mut decompressed)?;
+            Ok(decompressed.into())
+        }
+        LogCompression::Zstd => Ok(zstd::decode_all(data.as_ref())?.into()),
+    }
+}
+
+/// Decompress the content of a commit file, returning plain commits unchanged
+#[cfg(not(feature = "log-compression"))]
+pub fn decompress_commit(data: Bytes) -> std::io::Result<Bytes> {
+    match detect_compression(&data) {
+        LogCompression::None => Ok(data),
+        compression => Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!(
+                "Commit is compressed with {}, reading it requires the `log-compression` feature",
+                compression.as_ref()
+            ),
+        )),
+    }
+}
+
+/// Compress the content of a commit file
+#[cfg(feature = "log-compression")]
+pub fn compress_commit(data: Bytes, compression: LogCompression) -> std::io::Result<Bytes> {
+    use std::io::Write;
+
+    match compression {
+        LogCompression::None => Ok(data),
+        LogCompression::Gzip => {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(&data)?;
+            Ok(encoder.finish()?.into())
+        }
+        LogCompression::Zstd => Ok(zstd::encode_all(data.as_ref(), ZSTD_LEVEL)?.into()),
+    }
+}
+
+/// The compression to apply to new commits of a table, `None` unless the table sets
+/// [`LOG_COMPRESSION_KEY`] and its protocol supports compressed commits
+#[cfg(feature = "log-compression")]
+pub(crate) fn commit_compression(table: &dyn TableReference) -> LogCompression {
+    let compression = table
+        .metadata()
+        .configuration
+        .get(LOG_COMPRESSION_KEY)
+        .and_then(|value| value.as_deref())
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_default();
+    if compression == LogCompression::None {
+        return compression;
+    }
+    let protocol = table.protocol();
+    let enabled = protocol.min_reader_version >= 3
+        && protocol.min_writer_version >= 7
+        && protocol
+            .reader_features
+            .as_ref()
+            .is_some_and(|features| features.contains(&ReaderFeatures::LogCompression))
+        && protocol
+            .writer_features
+            .as_ref()
+            .is_some_and(|features| features.contains(&WriterFeatures::LogCompression));
+    if enabled {
+        compression
+    } else {
+        LogCompression::None
+    }
+}
+
+/// Encode a new commit of `table` as it is written to the log
+pub(crate) fn encode_commit(
+    table: Option<&dyn TableReference>,
+    data: Bytes,
+) -> std::io::Result<Bytes> {
+    #[cfg(feature = "log-compression")]
+    if let Some(table) = table {
+        return compress_commit(data, commit_compression(table));
+    }
+    #[cfg(not(feature = "log-compression"))]
+    let _ = table;
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_compression() {
+        assert_eq!(
+            "zstd".parse::<LogCompression>().unwrap(),
+            LogCompression::Zstd
+        );
+        assert_eq!(
+            "GZIP".parse::<LogCompression>().unwrap(),
+            LogCompression::Gzip
+        );
+        assert_eq!(
+            "none".parse::<LogCompression>().unwrap(),
+            LogCompression::None
+        );
+        assert!("lz4".parse::<LogCompression>().is_err());
+        assert!(!LOG_COMPRESSION_KEY.starts_with("delta."));
+    }
+
+    #[cfg(not(feature = "log-compression"))]
+    #[test]
+    fn test_compressed_commit_requires_feature() {
+        let commit = Bytes::from_static(b"{\"commitInfo\":{}}\n");
+        assert_eq!(decompress_commit(commit.clone()).unwrap(), commit);
+        let compressed = Bytes::from_static(&[0x28, 0xb5, 0x2f, 0xfd, 0x00]);
+        assert!(decompress_commit(compressed).is_err());
+    }
+
+    #[cfg(feature = "log-compression")]
+    #[test]
+    fn test_compression_roundtrip() {
+        let commit = Bytes::from_static(b"{\"commitInfo\":{}}\n{\"txn\":{}}\n");
+        assert_eq!(detect_compression(&commit), LogCompression::None);
+        assert_eq!(decompress_commit(commit.clone()).unwrap(), commit);
+
+        for compression in [LogCompression::Gzip, LogCompression::Zstd] {
+            let compressed = compress_commit(commit.clone(), compression).unwrap();
+            assert_ne!(compressed, commit);
+            assert_eq!(detect_compression(&compressed), compression);
+            assert_eq!(decompress_commit(compressed).unwrap(), commit);
+        }
+    }
+
+    #[cfg(feature = "log-compression")]
+    #[tokio::test]
+    async fn test_compressed_commits() {
+        use crate::kernel::{Action, Protocol};
+        use crate::operations::DeltaOps;
+        use crate::storage::commit_uri_from_version;
+        use crate::writer::test_utils::{get_delta_schema, get_record_batch};
+        use crate::writer::{DeltaWriter, RecordBatchWriter};
+        use crate::DeltaTable;
+
+        let mut table = DeltaOps::new_in_memory()
+            .create()
+            .with_columns(get_delta_schema().fields().clone())
+            .with_configuration(vec![(LOG_COMPRESSION_KEY, Some("zstd"))])
+            .await
+            .unwrap();
+        let snapshot = table.snapshot().unwrap();
+        assert_eq!(commit_compression(snapshot), LogCompression::Zstd);
+        let protocol = snapshot.protocol();
+        assert_eq!(protocol.min_reader_version, 3);
+        assert_eq!(protocol.min_writer_version, 7);
+        assert!(protocol
+            .reader_features
+            .as_ref()
+            .unwrap()
+            .contains(&ReaderFeatures::LogCompression));
+        assert!(protocol
+            .writer_features
+            .as_ref()
+            .unwrap()
+            .contains(&WriterFeatures::LogCompression));
+
+        let mut writer = RecordBatchWriter::for_table(&table).unwrap();
+        writer.write(get_record_batch(None, false)).await.unwrap();
+        writer.flush_and_commit(&mut table).await.unwrap();
+
+        let store = table.object_store();
+        let raw = store.get(&commit_uri_from_version(1)).await.unwrap();
+        assert_eq!(
+            detect_compression(&raw.bytes().await.unwrap()),
+            LogCompression::Zstd
+        );
+
+        let mut loaded = DeltaTable::new(table.log_store(), Default::default());
+        loaded.load().await.unwrap();
+        assert_eq!(loaded.version(), 1);
+        assert_eq!(loaded.get_files_iter().unwrap().count(), 1);
+        assert_eq!(loaded.history(None).await.unwrap().len(), 2);
+        let commit = table.log_store().read_commit_entry(1).await.unwrap();
+        assert!(commit.unwrap().starts_with(b"{"));
+
+        // without the protocol feature, commits stay plain JSON
+        let mut table = DeltaOps::new_in_memory()
+            .create()
+            .with_columns(get_delta_schema().fields().clone())
+            .with_actions(vec![Action::Protocol(Protocol {
+                min_reader_version: 1,
+                min_writer_version: 2,
+                ..Default::default()
+            })])
+            .with_configuration(vec![(LOG_COMPRESSION_KEY, Some("zstd"))])
+            .await
+            .unwrap();
+        assert_eq!(
+            commit_compression(table.snapshot().unwrap()),
+            LogCompression::None
+        );
+        let mut writer = RecordBatchWriter::for_table(&table).unwrap();
+        writer.write(get_record_batch(None, false)).await.unwrap();
+        writer.flush_and_commit(&mut table).await.unwrap();
+        let raw = table
+            .object_store()
+            .get(&commit_uri_from_version(1))
+            .await
+            .unwrap();
+        assert_eq!(
+            detect_compression(&raw.bytes().await.unwrap()),
+            LogCompression::None
+        );
+    }
+}
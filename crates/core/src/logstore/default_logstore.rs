@@ -34,7 +34,7 @@ impl LogStore for DefaultLogStore {
     }
 
     async fn read_commit_entry(&self, version: i64) -> DeltaResult<Option<Bytes>> {
-        super::read_commit_entry(self.storage.as_ref(), version).await
+        super::read_commit_entry(self.log_object_store().as_ref(), version).await
     }
 
     /// Tries to commit a prepared commit file. Returns [`TransactionError`]
@@ -47,7 +47,7 @@ impl LogStore for DefaultLogStore {
         version: i64,
         tmp_commit: &Path,
     ) -> Result<(), TransactionError> {
-        super::write_commit_entry(self.storage.as_ref(), version, tmp_commit).await
+        super::write_commit_entry(self.log_object_store().as_ref(), version, tmp_commit).await
     }
 
     async fn get_latest_version(&self, current_version: i64) -> DeltaResult<i64> {
@@ -62,3 +62,88 @@ impl LogStore for DefaultLogStore {
         &self.config
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use object_store::memory::InMemory;
+    use url::Url;
+
+    use super::*;
+    use crate::operations::DeltaOps;
+    use crate::storage::commit_uri_from_version;
+    use crate::writer::test_utils::get_delta_schema;
+    use crate::DeltaTable;
+
+    /// Keeps the log in a store of its own, apart from the data files
+    #[derive(Debug)]
+    struct SeparateLogStore {
+        data: ObjectStoreRef,
+        log: ObjectStoreRef,
+        config: LogStoreConfig,
+    }
+
+    #[async_trait::async_trait]
+    impl LogStore for SeparateLogStore {
+        fn name(&self) -> String {
+            "SeparateLogStore".into()
+        }
+
+        async fn read_commit_entry(&self, version: i64) -> DeltaResult<Option<Bytes>> {
+            super::super::read_commit_entry(self.log.as_ref(), version).await
+        }
+
+        async fn write_commit_entry(
+            &self,
+            version: i64,
+            tmp_commit: &Path,
+        ) -> Result<(), TransactionError> {
+            super::super::write_commit_entry(self.log.as_ref(), version, tmp_commit).await
+        }
+
+        async fn get_latest_version(&self, current_version: i64) -> DeltaResult<i64> {
+            super::super::get_latest_version(self, current_version).await
+        }
+
+        fn object_store(&self) -> Arc<dyn ObjectStore> {
+            self.data.clone()
+        }
+
+        fn log_object_store(&self) -> Arc<dyn ObjectStore> {
+            self.log.clone()
+        }
+
+        fn config(&self) -> &LogStoreConfig {
+            &self.config
+        }
+    }
+
+    #[tokio::test]
+    async fn test_separate_log_storage() {
+        let data: ObjectStoreRef = Arc::new(InMemory::new());
+        let log: ObjectStoreRef = Arc::new(InMemory::new());
+        let log_store: Arc<dyn LogStore> = Arc::new(SeparateLogStore {
+            data: data.clone(),
+            log: log.clone(),
+            config: LogStoreConfig {
+                location: Url::parse("memory:///").unwrap(),
+                options: Default::default(),
+            },
+        });
+
+        let table = DeltaOps(DeltaTable::new(log_store.clone(), Default::default()))
+            .create()
+            .with_columns(get_delta_schema().fields().clone())
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 0);
+
+        let commit = commit_uri_from_version(0);
+        assert!(log.head(&commit).await.is_ok());
+        assert!(data.head(&commit).await.is_err());
+
+        let mut table = DeltaTable::new(log_store, Default::default());
+        table.load().await.unwrap();
+        assert_eq!(table.version(), 0);
+        assert_eq!(table.get_schema().unwrap(), &get_delta_schema());
+    }
+}
@@ -0,0 +1,272 @@
+//! Mirroring of the `_delta_log` of a table between stores.
+//!
+//! Inspecting the schema and metadata of a table, or planning queries against it, only requires
+//! its log. In air-gapped environments the log can be mirrored to a local directory and carried
+//! over, without moving any data files. The [`LogMirrorBuilder`] copies the files of the log of
+//! the source to the target, skipping files already mirrored, so repeated runs only transfer
+//! new commits.
+//!
+//! Every copied file is read back from the target and compared with the source. Commits and
+//! checkpoints are immutable, a file of the target with the same name but a different size
+//! means the logs diverged and fails the mirroring. `_last_checkpoint` is copied last, so the
+//! target never points at a checkpoint which hasn't been mirrored yet.
+//!
+//! # Example
+//! ```rust ignore
+//! let target = logstore_for(Url::parse("file:///mnt/usb/table")?, HashMap::new())?;
+//! let metrics = LogMirrorBuilder::new(table.log_store(), target).await?;
+//! ````
+
+use std::collections::HashMap;
+
+use futures::future::BoxFuture;
+use futures::{StreamExt, TryStreamExt};
+use object_store::path::Path;
+use object_store::ObjectMeta;
+use tracing::debug;
+
+use super::LogStoreRef;
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::storage::ObjectStoreRef;
+
+/// Number of log files copied concurrently by default
+const DEFAULT_MIRROR_CONCURRENCY: usize = 16;
+
+/// Metrics of mirroring a log
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LogMirrorMetrics {
+    /// Number of files copied to the target
+    pub num_copied_files: usize,
+    /// Number of files already present in the target
+    pub num_skipped_files: usize,
+    /// Number of bytes copied to the target
+    pub num_copied_bytes: usize,
+}
+
+/// Copy the `_delta_log` of a table to another store
+pub struct LogMirrorBuilder {
+    source: LogStoreRef,
+    target: LogStoreRef,
+    until_version: Option<i64>,
+    concurrency: usize,
+}
+
+impl LogMirrorBuilder {
+    /// Mirror the log of `source` to `target`
+    pub fn new(source: LogStoreRef, target: LogStoreRef) -> Self {
+        Self {
+            source,
+            target,
+            until_version: None,
+            concurrency: DEFAULT_MIRROR_CONCURRENCY,
+        }
+    }
+
+    /// Only mirror commits and checkpoints up to and including `version`
+    pub fn with_until_version(mut self, version: i64) -> Self {
+        self.until_version = Some(version);
+        self
+    }
+
+    /// Number of files to copy concurrently
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+}
+
+/// Whether `location` is part of the mirrored log
+fn is_mirrored(log_path: &Path, location: &Path, until_version: Option<i64>) -> bool {
+    let Some(name) = location.filename() else {
+        return false;
+    };
+    // in-flight commits of concurrent writers
+    if name.ends_with(".tmp") {
+        return false;
+    }
+    // `_last_checkpoint` may reference a checkpoint after `until_version`
+    if name == "_last_checkpoint" {
+        return until_version.is_none();
+    }
+    match (until_version, location.prefix_match(log_path)) {
+        (Some(until_version), Some(mut parts)) => {
+            let version = parts
+                .next()
+                .and_then(|part| part.as_ref().get(..20).map(str::to_string))
+                .and_then(|prefix| prefix.parse::<i64>().ok());
+            version.map_or(true, |version| version <= until_version)
+        }
+        _ => true,
+    }
+}
+
+/// Copy `meta` from `source` to `target` and verify the copy
+async fn copy_file(
+    source: ObjectStoreRef,
+    target: ObjectStoreRef,
+    meta: ObjectMeta,
+) -> DeltaResult<usize> {
+    let data = source.get(&meta.location).await?.bytes().await?;
+    target.put(&meta.location, data.clone()).await?;
+    let copied = target.get(&meta.location).await?.bytes().await?;
+    if copied != data {
+        return Err(DeltaTableError::Generic(format!(
+            "Mirrored log file {} differs from the source",
+            meta.location
+        )));
+    }
+    Ok(data.len())
+}
+
+impl std::future::IntoFuture for LogMirrorBuilder {
+    type Output = DeltaResult<LogMirrorMetrics>;
+    type IntoFuture = BoxFuture<'static, Self::Output>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        let this = self;
+
+        Box::pin(async move {
+            let source = this.source.log_object_store();
+            let target = this.target.log_object_store();
+            let log_path = this.source.log_path().clone();
+
+            let existing = target
+                .list(Some(&log_path))
+                .map_ok(|meta| (meta.location, meta.size))
+                .try_collect::<HashMap<_, _>>()
+                .await?;
+            let (last_checkpoint, files): (Vec<_>, Vec<_>) = source
+                .list(Some(&log_path))
+                .try_filter(|meta| {
+                    futures::future::ready(is_mirrored(
+                        &log_path,
+                        &meta.location,
+                        this.until_version,
+                    ))
+                })
+                .try_collect::<Vec<_>>()
+                .await?
+                .into_iter()
+                .partition(|meta| meta.location.filename() == Some("_last_checkpoint"));
+
+            let mut metrics = LogMirrorMetrics::default();
+            let mut to_copy = Vec::new();
+            for meta in files {
+                match existing.get(&meta.location) {
+                    Some(size) if *size == meta.size => metrics.num_skipped_files += 1,
+                    Some(_) => {
+                        return Err(DeltaTableError::Generic(format!(
+                            "Log file {} of the target diverges from the source",
+                            meta.location
+                        )))
+                    }
+                    None => to_copy.push(meta),
+                }
+            }
+            debug!(
+                "mirroring {} log files, {} already mirrored",
+                to_copy.len(),
+                metrics.num_skipped_files
+            );
+
+            let copied = futures::stream::iter(
+                to_copy
+                    .into_iter()
+                    .map(|meta| copy_file(source.clone(), target.clone(), meta)),
+            )
+            .buffer_unordered(this.concurrency)
+            .try_collect::<Vec<_>>()
+            .await?;
+            metrics.num_copied_files += copied.len();
+            metrics.num_copied_bytes += copied.into_iter().sum::<usize>();
+
+            for meta in last_checkpoint {
+                metrics.num_copied_bytes += copy_file(source.clone(), target.clone(), meta).await?;
+                metrics.num_copied_files += 1;
+            }
+            Ok(metrics)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bytes::Bytes;
+    use object_store::memory::InMemory;
+    use object_store::ObjectStore;
+    use url::Url;
+
+    use super::*;
+    use crate::logstore::logstore_with;
+    use crate::operations::DeltaOps;
+    use crate::storage::commit_uri_from_version;
+    use crate::writer::test_utils::get_delta_schema;
+    use crate::DeltaTable;
+
+    #[tokio::test]
+    async fn test_mirror_log() {
+        let table = DeltaOps::new_in_memory()
+            .create()
+            .with_columns(get_delta_schema().fields().clone())
+            .await
+            .unwrap();
+        let store = Arc::new(InMemory::new());
+        let target = logstore_with(
+            store.clone(),
+            Url::parse("memory:///").unwrap(),
+            HashMap::new(),
+        )
+        .unwrap();
+
+        let metrics = LogMirrorBuilder::new(table.log_store(), target.clone())
+            .await
+            .unwrap();
+        assert_eq!(metrics.num_copied_files, 1);
+        assert_eq!(metrics.num_skipped_files, 0);
+        assert!(metrics.num_copied_bytes > 0);
+
+        let mut mirrored = DeltaTable::new(target.clone(), Default::default());
+        mirrored.load().await.unwrap();
+        assert_eq!(mirrored.version(), 0);
+        assert_eq!(mirrored.get_schema().unwrap(), &get_delta_schema());
+
+        let metrics = LogMirrorBuilder::new(table.log_store(), target.clone())
+            .await
+            .unwrap();
+        assert_eq!(metrics.num_copied_files, 0);
+        assert_eq!(metrics.num_skipped_files, 1);
+
+        store
+            .put(&commit_uri_from_version(0), Bytes::from("{}"))
+            .await
+            .unwrap();
+        let result = LogMirrorBuilder::new(table.log_store(), target).await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_is_mirrored() {
+        let log_path = Path::from("_delta_log");
+        let path = |name: &str| log_path.child(name);
+        assert!(is_mirrored(
+            &log_path,
+            &path("00000000000000000001.json"),
+            None
+        ));
+        assert!(!is_mirrored(&log_path, &path("_commit_abc.json.tmp"), None));
+        assert!(is_mirrored(
+            &log_path,
+            &path("00000000000000000001.json"),
+            Some(1)
+        ));
+        assert!(!is_mirrored(
+            &log_path,
+            &path("00000000000000000002.checkpoint.parquet"),
+            Some(1)
+        ));
+        assert!(!is_mirrored(&log_path, &path("_last_checkpoint"), Some(1)));
+        assert!(is_mirrored(&log_path, &path("_last_checkpoint"), None));
+    }
+}
@@ -8,8 +8,8 @@ use serde::{
     ser::SerializeSeq,
     Deserialize, Serialize,
 };
-use std::io::{BufRead, BufReader, Cursor};
 use std::sync::OnceLock;
+use std::time::Duration;
 use std::{cmp::max, collections::HashMap, sync::Arc};
 use url::Url;
 
@@ -18,6 +18,7 @@ use crate::{
     kernel::Action,
     operations::transaction::TransactionError,
     protocol::{get_last_checkpoint, ProtocolError},
+    storage::external::{CredentialResolver, ExternalFilesStore},
     storage::{commit_uri_from_version, ObjectStoreRef, StorageOptions},
     DeltaTableError,
 };
@@ -28,7 +29,9 @@ use tracing::{debug, warn};
 #[cfg(feature = "datafusion")]
 use datafusion::datasource::object_store::ObjectStoreUrl;
 
+pub mod compression;
 pub(crate) mod default_logstore;
+pub mod mirror;
 
 /// Trait for generating [LogStore] implementations
 pub trait LogStoreFactory: Send + Sync {
@@ -59,7 +62,7 @@ pub fn default_logstore(
 }
 
 #[derive(Clone, Debug, Default)]
-struct DefaultLogStoreFactory {}
+pub(crate) struct DefaultLogStoreFactory {}
 impl LogStoreFactory for DefaultLogStoreFactory {}
 
 /// Registry of [LogStoreFactory] instances
@@ -106,18 +109,29 @@ pub fn logstore_for(
     location: Url,
     options: impl Into<StorageOptions> + Clone,
 ) -> DeltaResult<LogStoreRef> {
+    let store = store_for_location(&location, &options.clone().into())?;
+    logstore_with(store, location, options)
+}
+
+/// Return the [ObjectStoreRef] of the registered storage provider for the [Url] location
+pub(crate) fn store_for_location(
+    location: &Url,
+    storage_options: &StorageOptions,
+) -> DeltaResult<ObjectStoreRef> {
     // turn location into scheme
     let scheme = Url::parse(&format!("{}://", location.scheme()))
         .map_err(|_| DeltaTableError::InvalidTableLocation(location.clone().into()))?;
 
     if let Some(entry) = crate::storage::factories().get(&scheme) {
         debug!("Found a storage provider for {scheme} ({location})");
-        let (store, _prefix) = entry
-            .value()
-            .parse_url_opts(&location, &options.clone().into())?;
-        return logstore_with(store, location, options);
+        let (store, _prefix) = entry.value().parse_url_opts(location, storage_options)?;
+        let store = crate::storage::retry::retry_store(store, storage_options)?;
+        let store = crate::storage::adaptive::adaptive_store(store, storage_options)?;
+        return crate::storage::hedged::hedge_store(store, storage_options);
     }
-    Err(DeltaTableError::InvalidTableLocation(location.into()))
+    Err(DeltaTableError::InvalidTableLocation(
+        location.clone().into(),
+    ))
 }
 
 /// Return the [LogStoreRef] using the given [ObjectStoreRef]
@@ -125,9 +139,25 @@ pub fn logstore_with(
     store: ObjectStoreRef,
     location: Url,
     options: impl Into<StorageOptions> + Clone,
+) -> DeltaResult<LogStoreRef> {
+    logstore_with_resolver(store, location, options, None)
+}
+
+/// Return the [LogStoreRef] using the given [ObjectStoreRef], resolving the storage options of
+/// data files in other buckets with the [CredentialResolver]
+pub fn logstore_with_resolver(
+    store: ObjectStoreRef,
+    location: Url,
+    options: impl Into<StorageOptions> + Clone,
+    resolver: Option<Arc<dyn CredentialResolver>>,
 ) -> DeltaResult<LogStoreRef> {
     let scheme = Url::parse(&format!("{}://", location.scheme()))
         .map_err(|_| DeltaTableError::InvalidTableLocation(location.clone().into()))?;
+    let mut external = ExternalFilesStore::new(store, options.clone().into());
+    if let Some(resolver) = resolver {
+        external = external.with_credential_resolver(resolver);
+    }
+    let store = crate::storage::deadline::deadline_store(Arc::new(external));
 
     if let Some(factory) = logstores().get(&scheme) {
         debug!("Found a logstore provider for {scheme}");
@@ -189,6 +219,16 @@ pub trait LogStore: Sync + Send {
     /// Get underlying object store.
     fn object_store(&self) -> Arc<dyn ObjectStore>;
 
+    /// Get the object store holding the log of the table, i.e. its commits, checkpoints and
+    /// `_last_checkpoint`.
+    ///
+    /// Defaults to the object store of the data files. Log stores keeping the log in a separate
+    /// system, e.g. a key value store, while data files are written to object storage, return
+    /// a store backed by that system.
+    fn log_object_store(&self) -> Arc<dyn ObjectStore> {
+        self.object_store()
+    }
+
     /// [Path] to Delta log
     fn to_uri(&self, location: &Path) -> String {
         let root = &self.config().location;
@@ -208,7 +248,7 @@ pub trait LogStore: Sync + Send {
     /// Check if the location is a delta table location
     async fn is_delta_table_location(&self) -> DeltaResult<bool> {
         // TODO We should really be using HEAD here, but this fails in windows tests
-        let object_store = self.object_store();
+        let object_store = self.log_object_store();
         let mut stream = object_store.list(Some(self.log_path()));
         if let Some(res) = stream.next().await {
             match res {
@@ -236,7 +276,7 @@ pub trait LogStore: Sync + Send {
 }
 
 #[cfg(feature = "datafusion")]
-fn object_store_url(location: &Url) -> ObjectStoreUrl {
+pub(crate) fn object_store_url(location: &Url) -> ObjectStoreUrl {
     use object_store::path::DELIMITER;
     ObjectStoreUrl::parse(format!(
         "delta-rs://{}-{}{}",
@@ -283,22 +323,82 @@ pub async fn get_actions(
     commit_log_bytes: bytes::Bytes,
 ) -> Result<Vec<Action>, DeltaTableError> {
     debug!("parsing commit with version {version}...");
-    let reader = BufReader::new(Cursor::new(commit_log_bytes));
-
+    let mut scratch = Vec::new();
     let mut actions = Vec::new();
-    for re_line in reader.lines() {
-        let line = re_line?;
-        let lstr = line.as_str();
-        let action = serde_json::from_str(lstr).map_err(|e| DeltaTableError::InvalidJsonLog {
-            json_err: e,
-            line,
-            version,
-        })?;
+    for line in log_lines(&commit_log_bytes) {
+        let action =
+            parse_action(line, &mut scratch).map_err(|e| DeltaTableError::InvalidJsonLog {
+                json_err: e,
+                line: String::from_utf8_lossy(line).into_owned(),
+                version,
+            })?;
         actions.push(action);
     }
     Ok(actions)
 }
 
+/// The version of the latest `txn` action of `app_id` in the commits up to `version`.
+///
+/// Commits are read from newest to oldest, stopping at the first commit no longer in the log.
+pub(crate) async fn latest_transaction_version(
+    log_store: &dyn LogStore,
+    version: i64,
+    app_id: &str,
+) -> DeltaResult<Option<i64>> {
+    for version in (0..=version).rev() {
+        let Some(commit) = log_store.read_commit_entry(version).await? else {
+            break;
+        };
+        let txn = get_actions(version, commit)
+            .await?
+            .into_iter()
+            .find_map(|action| match action {
+                Action::Txn(txn) if txn.app_id == app_id => Some(txn.version),
+                _ => None,
+            });
+        if txn.is_some() {
+            return Ok(txn);
+        }
+    }
+    Ok(None)
+}
+
+/// Split the content of a commit file into its non-empty lines.
+pub(crate) fn log_lines(data: &[u8]) -> impl Iterator<Item = &[u8]> {
+    data.split(|b| *b == b'\n')
+        .map(|line| match line {
+            [line @ .., b'\r'] => line,
+            line => line,
+        })
+        .filter(|line| !line.is_empty())
+}
+
+/// Parse a single line of a commit file into an [`Action`].
+///
+/// Lines are parsed from borrowed slices, without copying them into owned strings first.
+#[cfg(not(feature = "simd-json"))]
+pub(crate) fn parse_action(
+    line: &[u8],
+    _scratch: &mut Vec<u8>,
+) -> Result<Action, serde_json::Error> {
+    serde_json::from_slice(line)
+}
+
+/// Parse a single line of a commit file into an [`Action`].
+///
+/// simd-json parses in place, so the line is copied into `scratch` first, which can be
+/// reused across lines to avoid allocating for every action.
+#[cfg(feature = "simd-json")]
+pub(crate) fn parse_action(
+    line: &[u8],
+    scratch: &mut Vec<u8>,
+) -> Result<Action, serde_json::Error> {
+    scratch.clear();
+    scratch.extend_from_slice(line);
+    simd_json::serde::from_slice(scratch.as_mut_slice())
+        .map_err(<serde_json::Error as serde::de::Error>::custom)
+}
+
 // TODO: maybe a bit of a hack, required to `#[derive(Debug)]` for the operation builders
 impl std::fmt::Debug for dyn LogStore + '_ {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -365,11 +465,33 @@ pub fn extract_version_from_filename(name: &str) -> Option<i64> {
         .map(|captures| captures.get(1).unwrap().as_str().parse().unwrap())
 }
 
+/// Storage option selecting how the latest table version is discovered.
+///
+/// Supported values are `list` (the default), which lists the `_delta_log` directory, and
+/// `probe`, which issues HEAD requests for commit files instead. Probing is useful for stores
+/// where LIST requests are slow or rate limited, e.g. S3 buckets with very large prefixes.
+pub const VERSION_DISCOVERY_KEY: &str = "DELTA_VERSION_DISCOVERY";
+
+/// Maximum number of attempts for a single HEAD request while probing for versions
+const PROBE_MAX_ATTEMPTS: u32 = 5;
+/// Delay before the first retry of a failed HEAD request, doubled for every further retry
+const PROBE_INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+
 /// Default implementation for retrieving the latest version
 pub async fn get_latest_version(
     log_store: &dyn LogStore,
     current_version: i64,
 ) -> DeltaResult<i64> {
+    let probe = log_store
+        .config()
+        .options
+        .0
+        .get(VERSION_DISCOVERY_KEY)
+        .is_some_and(|v| v.eq_ignore_ascii_case("probe"));
+    if probe {
+        return get_latest_version_by_probing(log_store, current_version).await;
+    }
+
     let version_start = match get_last_checkpoint(log_store).await {
         Ok(last_check_point) => last_check_point.version,
         Err(ProtocolError::CheckpointNotFound) => {
@@ -390,7 +512,7 @@ pub async fn get_latest_version(
         let mut max_version: i64 = version_start;
         let prefix = Some(log_store.log_path());
         let offset_path = commit_uri_from_version(max_version);
-        let object_store = log_store.object_store();
+        let object_store = log_store.log_object_store();
         let mut files = object_store.list_with_offset(prefix, &offset_path);
 
         while let Some(obj_meta) = files.next().await {
@@ -414,6 +536,71 @@ pub async fn get_latest_version(
     Ok(version)
 }
 
+/// Retrieve the latest version using HEAD requests only, without listing the log directory.
+///
+/// Starting from the most recent of `current_version` and the last checkpoint, the commit
+/// files at exponentially increasing distances are probed until one is missing, and the
+/// latest version is then located by binary search. Since versions are contiguous, this
+/// takes a logarithmic number of requests in the number of commits since the start version.
+pub async fn get_latest_version_by_probing(
+    log_store: &dyn LogStore,
+    current_version: i64,
+) -> DeltaResult<i64> {
+    let checkpoint_version = match get_last_checkpoint(log_store).await {
+        Ok(last_check_point) => last_check_point.version,
+        Err(ProtocolError::CheckpointNotFound) => -1,
+        Err(e) => return Err(DeltaTableError::from(e)),
+    };
+    let object_store = log_store.log_object_store();
+
+    // the latest version known to exist, and the earliest version known to be missing
+    let mut lower = max(current_version, checkpoint_version);
+    if lower < 0 {
+        if !commit_exists(object_store.as_ref(), 0).await? {
+            return Err(DeltaTableError::not_a_table(log_store.root_uri()));
+        }
+        lower = 0;
+    }
+    let mut step = 1;
+    let mut upper = lower + step;
+    while commit_exists(object_store.as_ref(), upper).await? {
+        lower = upper;
+        step *= 2;
+        upper = lower + step;
+    }
+
+    while upper - lower > 1 {
+        let pivot = lower + (upper - lower) / 2;
+        if commit_exists(object_store.as_ref(), pivot).await? {
+            lower = pivot;
+        } else {
+            upper = pivot;
+        }
+    }
+    debug!("latest version found by probing: {lower}");
+    Ok(lower)
+}
+
+/// Check whether the commit file for `version` exists, retrying transient errors with backoff
+async fn commit_exists(storage: &dyn ObjectStore, version: i64) -> DeltaResult<bool> {
+    let commit_uri = commit_uri_from_version(version);
+    let mut backoff = PROBE_INITIAL_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match storage.head(&commit_uri).await {
+            Ok(_) => return Ok(true),
+            Err(ObjectStoreError::NotFound { .. }) => return Ok(false),
+            Err(err @ ObjectStoreError::Generic { .. }) if attempt < PROBE_MAX_ATTEMPTS => {
+                debug!("probing version {version} failed on attempt {attempt}: {err}");
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+                attempt += 1;
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
 /// Read delta log for a specific version
 pub async fn read_commit_entry(
     storage: &dyn ObjectStore,
@@ -421,7 +608,7 @@ pub async fn read_commit_entry(
 ) -> DeltaResult<Option<Bytes>> {
     let commit_uri = commit_uri_from_version(version);
     match storage.get(&commit_uri).await {
-        Ok(res) => Ok(Some(res.bytes().await?)),
+        Ok(res) => Ok(Some(compression::decompress_commit(res.bytes().await?)?)),
         Err(ObjectStoreError::NotFound { .. }) => Ok(None),
         Err(err) => Err(err.into()),
     }
@@ -466,6 +653,31 @@ mod tests {
         let store = logstore_for(location, HashMap::default());
         assert!(store.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_get_latest_version_by_probing() {
+        let location = Url::parse("memory://table").unwrap();
+        let options = HashMap::from([(VERSION_DISCOVERY_KEY.to_string(), "probe".to_string())]);
+        let store = logstore_for(location, options).unwrap();
+
+        assert!(matches!(
+            store.get_latest_version(-1).await,
+            Err(DeltaTableError::NotATable(_))
+        ));
+
+        for version in 0..=37 {
+            store
+                .object_store()
+                .put(&commit_uri_from_version(version), Bytes::new())
+                .await
+                .unwrap();
+            assert_eq!(store.get_latest_version(-1).await.unwrap(), version);
+            assert_eq!(
+                store.get_latest_version(version / 2).await.unwrap(),
+                version
+            );
+        }
+    }
 }
 
 #[cfg(feature = "datafusion")]
@@ -0,0 +1,619 @@
+//! Incrementally maintain an aggregate of a source table in a target table
+//!
+//! The target table holds one row per group of the source table, with the group keys and the
+//! configured aggregations as columns. Each sync reads the changes committed to the source
+//! table since the previous sync, aggregates them per group and merges the result into the
+//! target table. The last synced source version is recorded in a `txn` action of each sync
+//! commit, so syncs are exactly once even when interrupted.
+//!
+//! Changes are derived from the data files added and removed by each commit: rows of added
+//! files count positively and rows of removed files negatively. This yields the same net
+//! changes as the change data feed, without requiring it to be enabled, but requires the
+//! removed files to still be present, i.e. syncs must run more often than vacuum removes them.
+//! Only invertible aggregations are supported. Groups whose count drops to zero are deleted
+//! when a [`Aggregation::Count`] is maintained. Rows with null group keys are ignored.
+//!
+//! # Example
+//! ```rust ignore
+//! let source = open_table("../path/to/orders")?;
+//! let target = DeltaOps::try_from_uri("../path/to/orders_per_customer").await?;
+//! let (table, metrics) = target
+//!     .aggregate_sync(source)
+//!     .with_group_by(["customer_id"])
+//!     .with_aggregation(Aggregation::count("num_orders"))
+//!     .with_aggregation(Aggregation::sum("amount", "total_amount"))
+//!     .await?;
+//! ````
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::Utc;
+use datafusion::dataframe::DataFrame;
+use datafusion::execution::context::{SessionContext, SessionState};
+use datafusion_common::Column;
+use datafusion_expr::utils::conjunction;
+use datafusion_expr::{col, lit, sum, Expr};
+use futures::future::BoxFuture;
+use object_store::path::Path;
+use parquet::file::properties::WriterProperties;
+use serde::Serialize;
+
+use super::merge::MergeBuilder;
+use super::transaction::{CommitProperties, PROTOCOL};
+use super::write::WriteBuilder;
+use crate::delta_datafusion::{
+    register_store, DeltaScanConfig, DeltaSessionContext, DeltaTableProvider,
+};
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::kernel::{Action, Add, Remove, Txn};
+use crate::logstore::{get_actions, latest_transaction_version, LogStoreRef};
+use crate::table::state::DeltaTableState;
+use crate::DeltaTable;
+
+const SIGN_COLUMN: &str = "__delta_rs_sign";
+const SOURCE_ALIAS: &str = "source";
+const TARGET_ALIAS: &str = "target";
+
+/// Errors that can occur while syncing an aggregate
+#[derive(thiserror::Error, Debug)]
+enum AggregateSyncError {
+    #[error("At least one group by column and one aggregation must be specified")]
+    MissingAggregation,
+
+    #[error("Target table exists but no sync of {0} was found in its log")]
+    UnknownSyncState(String),
+
+    #[error("Source table was synced up to version {synced}, but is at version {current}")]
+    SourceBehind { synced: i64, current: i64 },
+
+    #[error("Cannot sync changes of file {0} with a deletion vector")]
+    DeletionVector(String),
+}
+
+impl From<AggregateSyncError> for DeltaTableError {
+    fn from(err: AggregateSyncError) -> Self {
+        DeltaTableError::GenericError {
+            source: Box::new(err),
+        }
+    }
+}
+
+/// An aggregation maintained in the target table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Aggregation {
+    /// The number of rows in the group
+    Count {
+        /// Name of the target column
+        output: String,
+    },
+    /// The sum of a source column over the rows in the group
+    Sum {
+        /// Name of the source column
+        column: String,
+        /// Name of the target column
+        output: String,
+    },
+}
+
+impl Aggregation {
+    /// Count the rows of each group into the column `output`
+    pub fn count(output: impl Into<String>) -> Self {
+        Self::Count {
+            output: output.into(),
+        }
+    }
+
+    /// Sum `column` over the rows of each group into the column `output`
+    pub fn sum(column: impl Into<String>, output: impl Into<String>) -> Self {
+        Self::Sum {
+            column: column.into(),
+            output: output.into(),
+        }
+    }
+
+    /// Name of the target column
+    pub fn output(&self) -> &str {
+        match self {
+            Self::Count { output } | Self::Sum { output, .. } => output,
+        }
+    }
+
+    /// The aggregate expression over the signed changes
+    fn signed_expr(&self) -> Expr {
+        match self {
+            Self::Count { output } => sum(col(SIGN_COLUMN)).alias(output),
+            Self::Sum { column, output } => {
+                sum(Expr::Column(Column::from_name(column)) * col(SIGN_COLUMN)).alias(output)
+            }
+        }
+    }
+}
+
+/// Incrementally maintain an aggregate of a source table.
+/// See this module's documentation for more information
+pub struct AggregateSyncBuilder {
+    /// The table to aggregate
+    source: DeltaTable,
+    /// A snapshot of the target table, if it exists
+    snapshot: Option<DeltaTableState>,
+    /// Delta object store of the target table
+    log_store: LogStoreRef,
+    /// Columns to group the source rows by
+    group_by: Vec<String>,
+    /// Aggregations to maintain
+    aggregations: Vec<Aggregation>,
+    /// Application id used to track the synced source version
+    app_id: Option<String>,
+    /// Datafusion session state relevant for executing the input plan
+    state: Option<SessionState>,
+    /// Properties passed to underlying parquet writer
+    writer_properties: Option<WriterProperties>,
+    /// Commit properties and configuration
+    commit_properties: CommitProperties,
+}
+
+/// Metrics for the Aggregate Sync Operation
+#[derive(Default, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AggregateSyncMetrics {
+    /// Source version of the previous sync, if any
+    pub previous_source_version: Option<i64>,
+    /// Source version the target is synced to
+    pub source_version: i64,
+    /// Number of source files whose rows were added to the aggregate
+    pub num_source_files_added: usize,
+    /// Number of source files whose rows were subtracted from the aggregate
+    pub num_source_files_removed: usize,
+    /// Number of groups inserted into the target table
+    pub num_target_rows_inserted: usize,
+    /// Number of groups updated in the target table
+    pub num_target_rows_updated: usize,
+    /// Number of groups deleted from the target table
+    pub num_target_rows_deleted: usize,
+}
+
+impl AggregateSyncBuilder {
+    /// Create a new [`AggregateSyncBuilder`]
+    pub fn new(
+        log_store: LogStoreRef,
+        snapshot: Option<DeltaTableState>,
+        source: DeltaTable,
+    ) -> Self {
+        Self {
+            source,
+            snapshot,
+            log_store,
+            group_by: Vec::new(),
+            aggregations: Vec::new(),
+            app_id: None,
+            state: None,
+            writer_properties: None,
+            commit_properties: CommitProperties::default(),
+        }
+    }
+
+    /// Group the source rows by `columns`
+    pub fn with_group_by(mut self, columns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.group_by = columns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Maintain `aggregation` in the target table
+    pub fn with_aggregation(mut self, aggregation: Aggregation) -> Self {
+        self.aggregations.push(aggregation);
+        self
+    }
+
+    /// Application id under which the synced source version is recorded.
+    ///
+    /// Defaults to an id derived from the source table uri.
+    pub fn with_app_id(mut self, app_id: impl Into<String>) -> Self {
+        self.app_id = Some(app_id.into());
+        self
+    }
+
+    /// The Datafusion session state to use
+    pub fn with_session_state(mut self, state: SessionState) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Writer properties passed to parquet writer
+    pub fn with_writer_properties(mut self, writer_properties: WriterProperties) -> Self {
+        self.writer_properties = Some(writer_properties);
+        self
+    }
+
+    /// Additional information to write to the commit
+    pub fn with_commit_properties(mut self, commit_properties: CommitProperties) -> Self {
+        self.commit_properties = commit_properties;
+        self
+    }
+}
+
+/// The files added and removed by the data changing commits after `from` up to `to`
+async fn changed_files(
+    log_store: &LogStoreRef,
+    from: i64,
+    to: i64,
+) -> DeltaResult<(Vec<Add>, Vec<Add>)> {
+    let mut added: HashMap<String, Add> = HashMap::new();
+    let mut removed: HashMap<String, Remove> = HashMap::new();
+    for version in from + 1..=to {
+        let commit = log_store
+            .read_commit_entry(version)
+            .await?
+            .ok_or(DeltaTableError::InvalidVersion(version))?;
+        for action in get_actions(version, commit).await? {
+            match action {
+                // a file removed and added again within the range did not change
+                Action::Add(add) if add.data_change && removed.remove(&add.path).is_none() => {
+                    added.insert(add.path.clone(), add);
+                }
+                Action::Remove(remove)
+                    if remove.data_change && added.remove(&remove.path).is_none() =>
+                {
+                    removed.insert(remove.path.clone(), remove);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let store = log_store.object_store();
+    let mut removed_files = Vec::with_capacity(removed.len());
+    for remove in removed.into_values() {
+        if remove.deletion_vector.is_some() {
+            return Err(AggregateSyncError::DeletionVector(remove.path).into());
+        }
+        let size = match remove.size {
+            Some(size) => size,
+            None => store.head(&Path::parse(&remove.path)?).await?.size as i64,
+        };
+        removed_files.push(Add {
+            path: remove.path,
+            partition_values: remove.partition_values.unwrap_or_default(),
+            size,
+            modification_time: remove.deletion_timestamp.unwrap_or_default(),
+            data_change: true,
+            ..Default::default()
+        });
+    }
+    let added_files = added.into_values().collect::<Vec<_>>();
+    if let Some(add) = added_files.iter().find(|add| add.deletion_vector.is_some()) {
+        return Err(AggregateSyncError::DeletionVector(add.path.clone()).into());
+    }
+    Ok((added_files, removed_files))
+}
+
+impl std::future::IntoFuture for AggregateSyncBuilder {
+    type Output = DeltaResult<(DeltaTable, AggregateSyncMetrics)>;
+    type IntoFuture = BoxFuture<'static, Self::Output>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        let this = self;
+
+        Box::pin(async move {
+            if this.group_by.is_empty() || this.aggregations.is_empty() {
+                return Err(AggregateSyncError::MissingAggregation.into());
+            }
+            if let Some(snapshot) = &this.snapshot {
+                PROTOCOL.can_write_to(snapshot)?;
+            }
+
+            let source_snapshot = this.source.snapshot()?.clone();
+            let source_log_store = this.source.log_store();
+            let source_version = source_snapshot.version();
+            let app_id = this
+                .app_id
+                .unwrap_or_else(|| format!("delta-rs.aggregate_sync.{}", this.source.table_uri()));
+
+            let previous = match &this.snapshot {
+                Some(snapshot) => Some(
+                    latest_transaction_version(
+                        this.log_store.as_ref(),
+                        snapshot.version(),
+                        &app_id,
+                    )
+                    .await?
+                    .ok_or_else(|| AggregateSyncError::UnknownSyncState(app_id.clone()))?,
+                ),
+                None => None,
+            };
+            let mut metrics = AggregateSyncMetrics {
+                previous_source_version: previous,
+                source_version,
+                ..Default::default()
+            };
+            if let Some(previous) = previous {
+                if previous > source_version {
+                    return Err(AggregateSyncError::SourceBehind {
+                        synced: previous,
+                        current: source_version,
+                    }
+                    .into());
+                }
+            }
+
+            let state = this.state.unwrap_or_else(|| {
+                let session: SessionContext = DeltaSessionContext::default().into();
+                session.state()
+            });
+            // If a user provides their own their DF state then they must register the stores themselves
+            register_store(source_log_store.clone(), state.runtime_env().clone());
+            register_store(this.log_store.clone(), state.runtime_env().clone());
+            let ctx = SessionContext::new_with_state(state.clone());
+
+            let scan = |files: Option<Vec<Add>>, sign: i64| -> DeltaResult<DataFrame> {
+                let mut provider = DeltaTableProvider::try_new(
+                    source_snapshot.clone(),
+                    source_log_store.clone(),
+                    DeltaScanConfig::default(),
+                )?;
+                if let Some(files) = files {
+                    provider = provider.with_files(files);
+                }
+                Ok(ctx
+                    .read_table(Arc::new(provider))?
+                    .with_column(SIGN_COLUMN, lit(sign))?)
+            };
+            let changes = match previous {
+                None => {
+                    metrics.num_source_files_added = source_snapshot.files_count()?;
+                    scan(None, 1)?
+                }
+                Some(previous) => {
+                    let (added, removed) =
+                        changed_files(&source_log_store, previous, source_version).await?;
+                    metrics.num_source_files_added = added.len();
+                    metrics.num_source_files_removed = removed.len();
+                    match (added.is_empty(), removed.is_empty()) {
+                        (true, true) => {
+                            let table = DeltaTable::new_with_state(
+                                this.log_store,
+                                this.snapshot.expect("target exists when previously synced"),
+                            );
+                            return Ok((table, metrics));
+                        }
+                        (false, true) => scan(Some(added), 1)?,
+                        (true, false) => scan(Some(removed), -1)?,
+                        (false, false) => scan(Some(added), 1)?.union(scan(Some(removed), -1)?)?,
+                    }
+                }
+            };
+
+            let group_by = this
+                .group_by
+                .iter()
+                .map(|column| Expr::Column(Column::from_name(column)))
+                .collect::<Vec<_>>();
+            let not_null = conjunction(group_by.iter().map(|key| key.clone().is_not_null()));
+            let changes = match not_null {
+                Some(predicate) => changes.filter(predicate)?,
+                None => changes,
+            };
+            let aggregated = changes.aggregate(
+                group_by,
+                this.aggregations
+                    .iter()
+                    .map(Aggregation::signed_expr)
+                    .collect(),
+            )?;
+
+            let txn = Txn {
+                app_id,
+                version: source_version,
+                last_updated: Some(Utc::now().timestamp_millis()),
+            };
+            let commit_properties = this.commit_properties.with_application_transaction(txn);
+
+            let Some(snapshot) = this.snapshot else {
+                let plan = aggregated.create_physical_plan().await?;
+                let mut write = WriteBuilder::new(this.log_store, None)
+                    .with_input_execution_plan(plan)
+                    .with_input_session_state(state)
+                    .with_commit_properties(commit_properties);
+                if let Some(writer_properties) = this.writer_properties {
+                    write = write.with_writer_properties(writer_properties);
+                }
+                let table = write.await?;
+                for add in table.snapshot()?.file_actions()? {
+                    if let Some(stats) = add.get_stats()? {
+                        metrics.num_target_rows_inserted += stats.num_records as usize;
+                    }
+                }
+                return Ok((table, metrics));
+            };
+
+            let target = |column: &str| Expr::Column(Column::new(Some(TARGET_ALIAS), column));
+            let source = |column: &str| Expr::Column(Column::new(Some(SOURCE_ALIAS), column));
+            let predicate = conjunction(
+                this.group_by
+                    .iter()
+                    .map(|column| target(column).eq(source(column))),
+            )
+            .expect("group by columns are not empty");
+            let count = this
+                .aggregations
+                .iter()
+                .find_map(|aggregation| match aggregation {
+                    Aggregation::Count { output } => Some(output.as_str()),
+                    _ => None,
+                });
+
+            let mut merge = MergeBuilder::new(this.log_store, snapshot, predicate, aggregated)
+                .with_source_alias(SOURCE_ALIAS)
+                .with_target_alias(TARGET_ALIAS)
+                .with_session_state(state)
+                .with_commit_properties(commit_properties);
+            if let Some(writer_properties) = this.writer_properties {
+                merge = merge.with_writer_properties(writer_properties);
+            }
+            if let Some(count) = count {
+                merge = merge.when_matched_delete(|delete| {
+                    delete.predicate((target(count) + source(count)).eq(lit(0i64)))
+                })?;
+            }
+            merge = merge.when_matched_update(|update| {
+                this.aggregations
+                    .iter()
+                    .fold(update, |update, aggregation| {
+                        let output = aggregation.output();
+                        update.update(output, target(output) + source(output))
+                    })
+            })?;
+            merge = merge.when_not_matched_insert(|insert| {
+                let insert = match count {
+                    Some(count) => insert.predicate(source(count).not_eq(lit(0i64))),
+                    None => insert,
+                };
+                let insert = this
+                    .group_by
+                    .iter()
+                    .fold(insert, |insert, column| insert.set(column, source(column)));
+                this.aggregations
+                    .iter()
+                    .fold(insert, |insert, aggregation| {
+                        insert.set(aggregation.output(), source(aggregation.output()))
+                    })
+            })?;
+
+            let (table, merge_metrics) = merge.await?;
+            metrics.num_target_rows_inserted = merge_metrics.num_target_rows_inserted;
+            metrics.num_target_rows_updated = merge_metrics.num_target_rows_updated;
+            metrics.num_target_rows_deleted = merge_metrics.num_target_rows_deleted;
+            Ok((table, metrics))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Int64Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema as ArrowSchema};
+    use arrow::record_batch::RecordBatch;
+    use datafusion::assert_batches_sorted_eq;
+
+    use super::*;
+    use crate::operations::DeltaOps;
+    use crate::protocol::SaveMode;
+    use crate::writer::test_utils::datafusion::get_data_sorted;
+
+    fn orders(customers: Vec<&str>, amounts: Vec<i64>) -> RecordBatch {
+        let schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("customer", DataType::Utf8, true),
+            Field::new("amount", DataType::Int64, true),
+        ]));
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(customers)),
+                Arc::new(Int64Array::from(amounts)),
+            ],
+        )
+        .unwrap()
+    }
+
+    async fn sync(
+        source: &DeltaTable,
+        target: DeltaOps,
+    ) -> DeltaResult<(DeltaTable, AggregateSyncMetrics)> {
+        target
+            .aggregate_sync(source.clone())
+            .with_group_by(["customer"])
+            .with_aggregation(Aggregation::count("num_orders"))
+            .with_aggregation(Aggregation::sum("amount", "total_amount"))
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_sync() {
+        let source_dir = tempfile::tempdir().unwrap();
+        let target_dir = tempfile::tempdir().unwrap();
+        let target_uri = target_dir.path().to_str().unwrap();
+
+        let source = DeltaOps::try_from_uri(source_dir.path().to_str().unwrap())
+            .await
+            .unwrap()
+            .write(vec![orders(vec!["a", "a", "b"], vec![10, 5, 7])])
+            .await
+            .unwrap();
+
+        let target = DeltaOps::try_from_uri(target_uri).await.unwrap();
+        let (table, metrics) = sync(&source, target).await.unwrap();
+        assert_eq!(metrics.previous_source_version, None);
+        assert_eq!(metrics.source_version, 0);
+        assert_eq!(metrics.num_target_rows_inserted, 2);
+
+        let expected = vec![
+            "+----------+------------+--------------+",
+            "| customer | num_orders | total_amount |",
+            "+----------+------------+--------------+",
+            "| a        | 2          | 15           |",
+            "| b        | 1          | 7            |",
+            "+----------+------------+--------------+",
+        ];
+        let actual = get_data_sorted(&table, "customer, num_orders, total_amount").await;
+        assert_batches_sorted_eq!(&expected, &actual);
+
+        let source = DeltaOps(source)
+            .write(vec![orders(vec!["b", "c"], vec![3, 1])])
+            .with_save_mode(SaveMode::Append)
+            .await
+            .unwrap();
+        let (source, _) = DeltaOps(source)
+            .delete()
+            .with_predicate("customer = 'a'")
+            .await
+            .unwrap();
+
+        let (table, metrics) = sync(&source, DeltaOps(table)).await.unwrap();
+        assert_eq!(metrics.previous_source_version, Some(0));
+        assert_eq!(metrics.source_version, 2);
+        assert_eq!(metrics.num_target_rows_inserted, 1);
+        assert_eq!(metrics.num_target_rows_updated, 1);
+        assert_eq!(metrics.num_target_rows_deleted, 1);
+
+        let expected = vec![
+            "+----------+------------+--------------+",
+            "| customer | num_orders | total_amount |",
+            "+----------+------------+--------------+",
+            "| b        | 2          | 10           |",
+            "| c        | 1          | 1            |",
+            "+----------+------------+--------------+",
+        ];
+        let actual = get_data_sorted(&table, "customer, num_orders, total_amount").await;
+        assert_batches_sorted_eq!(&expected, &actual);
+
+        // syncing again without source changes does not commit
+        let version = table.version();
+        let (table, metrics) = sync(&source, DeltaOps(table)).await.unwrap();
+        assert_eq!(metrics.previous_source_version, Some(2));
+        assert_eq!(metrics.num_source_files_added, 0);
+        assert_eq!(metrics.num_source_files_removed, 0);
+        assert_eq!(table.version(), version);
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_sync_unknown_state() {
+        let source_dir = tempfile::tempdir().unwrap();
+        let target_dir = tempfile::tempdir().unwrap();
+
+        let source = DeltaOps::try_from_uri(source_dir.path().to_str().unwrap())
+            .await
+            .unwrap()
+            .write(vec![orders(vec!["a"], vec![1])])
+            .await
+            .unwrap();
+        let target = DeltaOps::try_from_uri(target_dir.path().to_str().unwrap())
+            .await
+            .unwrap()
+            .write(vec![orders(vec!["a"], vec![1])])
+            .await
+            .unwrap();
+
+        assert!(sync(&source, DeltaOps(target.clone())).await.is_err());
+        assert!(DeltaOps(target).aggregate_sync(source).await.is_err());
+    }
+}
@@ -0,0 +1,778 @@
+//! Compute an approximate statistics summary for a Delta table
+//!
+//! The analyze operation scans (a sample of) the data files of the current snapshot and
+//! computes per-column sketches:
+//!
+//! - an approximate number of distinct values (NDV) using a HyperLogLog sketch,
+//! - an equi-depth histogram for numeric columns, built from a reservoir sample,
+//! - the most frequent values (top-K) tracked with the Misra-Gries summary.
+//!
+//! The resulting [`TableStatisticsSummary`] is stored in the table as a [`DomainMetadata`]
+//! action in the [`TABLE_STATISTICS_DOMAIN`] domain, so that embedding engines can retrieve
+//! the summary through [`get_table_statistics`] and use it for cost-based decisions.
+//!
+//! # Example
+//! ```rust ignore
+//! let table = open_table("../path/to/table")?;
+//! let (table, summary) = DeltaOps(table)
+//!     .analyze()
+//!     .with_columns(["id", "value"])
+//!     .with_sample_fraction(0.1)
+//!     .await?;
+//! let stats = get_table_statistics(&table).await?;
+//! ````
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use arrow_array::{Array, Float64Array, RecordBatch};
+use arrow_cast::cast;
+use arrow_cast::display::{ArrayFormatter, FormatOptions};
+use arrow_schema::DataType as ArrowDataType;
+use chrono::Utc;
+use futures::future::BoxFuture;
+use futures::TryStreamExt;
+use object_store::ObjectMeta;
+use parquet::arrow::async_reader::{ParquetObjectReader, ParquetRecordBatchStreamBuilder};
+use parquet::arrow::ProjectionMask;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use super::transaction::{with_writer_feature, CommitBuilder, CommitProperties};
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::kernel::{Action, DomainMetadata, WriterFeatures};
+use crate::logstore::LogStoreRef;
+use crate::protocol::DeltaOperation;
+use crate::table::state::DeltaTableState;
+use crate::DeltaTable;
+
+/// Name of the metadata domain in which the table statistics summary is stored
+pub const TABLE_STATISTICS_DOMAIN: &str = "delta-rs.tableStatistics";
+
+const DEFAULT_HLL_PRECISION: u8 = 12;
+const DEFAULT_HISTOGRAM_BUCKETS: usize = 16;
+const DEFAULT_TOP_K: usize = 10;
+const RESERVOIR_SIZE: usize = 10_000;
+
+/// Errors that can occur while analyzing a table
+#[derive(thiserror::Error, Debug)]
+enum AnalyzeError {
+    #[error("Sample fraction must be in the range (0, 1], got {0}")]
+    InvalidSampleFraction(f64),
+
+    #[error("HyperLogLog precision must be in the range [4, 18], got {0}")]
+    InvalidPrecision(u8),
+
+    #[error("Column {0} not found in table schema")]
+    ColumnNotFound(String),
+}
+
+impl From<AnalyzeError> for DeltaTableError {
+    fn from(err: AnalyzeError) -> Self {
+        DeltaTableError::GenericError {
+            source: Box::new(err),
+        }
+    }
+}
+
+/// A single bucket of an equi-depth histogram
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramBucket {
+    /// Inclusive lower bound of the bucket
+    pub lower: f64,
+    /// Inclusive upper bound of the bucket
+    pub upper: f64,
+    /// Estimated number of rows falling into the bucket
+    pub count: u64,
+}
+
+/// A value that frequently occurs in a column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrequentValue {
+    /// String representation of the value
+    pub value: String,
+    /// Estimated number of occurrences in the table
+    pub count: u64,
+}
+
+/// Approximate statistics for a single column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnStatistics {
+    /// Approximate number of distinct non-null values
+    pub distinct_count: u64,
+    /// Estimated number of null values
+    pub null_count: u64,
+    /// Equi-depth histogram, only computed for numeric columns
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub histogram: Option<Vec<HistogramBucket>>,
+    /// Most frequent values ordered by descending count
+    pub top_k: Vec<FrequentValue>,
+}
+
+/// Approximate statistics summary of a Delta table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableStatisticsSummary {
+    /// Version of the table the statistics were computed for
+    pub table_version: i64,
+    /// Time the statistics were computed, as milliseconds since the epoch
+    pub computed_at: i64,
+    /// Fraction of rows that were sampled
+    pub sample_fraction: f64,
+    /// Number of data files that were scanned
+    pub num_files_scanned: u64,
+    /// Number of rows that were sampled
+    pub num_rows_sampled: u64,
+    /// Statistics for every analyzed column
+    pub columns: HashMap<String, ColumnStatistics>,
+}
+
+/// Analyze a Delta table and persist an approximate statistics summary.
+/// See this module's documentation for more information
+pub struct AnalyzeBuilder {
+    /// A snapshot of the to-be-analyzed table's state
+    snapshot: DeltaTableState,
+    /// Delta object store for handling data files
+    log_store: LogStoreRef,
+    /// Columns to analyze, all top-level columns when not set
+    columns: Option<Vec<String>>,
+    /// Fraction of rows included in the sketches
+    sample_fraction: f64,
+    /// Maximum number of data files to scan
+    max_files: Option<usize>,
+    /// Precision of the HyperLogLog sketches
+    hll_precision: u8,
+    /// Number of histogram buckets for numeric columns
+    histogram_buckets: usize,
+    /// Number of frequent values to keep per column
+    top_k: usize,
+    /// Only compute the summary without committing it to the table
+    dry_run: bool,
+    /// Additional information to add to the commit
+    commit_properties: CommitProperties,
+}
+
+impl AnalyzeBuilder {
+    /// Create a new [`AnalyzeBuilder`]
+    pub fn new(log_store: LogStoreRef, snapshot: DeltaTableState) -> Self {
+        Self {
+            snapshot,
+            log_store,
+            columns: None,
+            sample_fraction: 1.0,
+            max_files: None,
+            hll_precision: DEFAULT_HLL_PRECISION,
+            histogram_buckets: DEFAULT_HISTOGRAM_BUCKETS,
+            top_k: DEFAULT_TOP_K,
+            dry_run: false,
+            commit_properties: CommitProperties::default(),
+        }
+    }
+
+    /// Only analyze the given top-level columns
+    pub fn with_columns(mut self, columns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.columns = Some(columns.into_iter().map(|c| c.into()).collect());
+        self
+    }
+
+    /// Fraction of rows to include in the sketches. Defaults to `1.0`
+    pub fn with_sample_fraction(mut self, sample_fraction: f64) -> Self {
+        self.sample_fraction = sample_fraction;
+        self
+    }
+
+    /// Limit the number of data files that are scanned
+    pub fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = Some(max_files);
+        self
+    }
+
+    /// Precision of the HyperLogLog sketches, trading memory for accuracy. Defaults to `12`
+    pub fn with_hll_precision(mut self, precision: u8) -> Self {
+        self.hll_precision = precision;
+        self
+    }
+
+    /// Number of buckets in the histograms of numeric columns. Defaults to `16`
+    pub fn with_histogram_buckets(mut self, buckets: usize) -> Self {
+        self.histogram_buckets = buckets;
+        self
+    }
+
+    /// Number of most frequent values to keep per column. Defaults to `10`
+    pub fn with_top_k(mut self, top_k: usize) -> Self {
+        self.top_k = top_k;
+        self
+    }
+
+    /// Only compute the summary. A dry run will not commit the summary to the Delta log
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Additional metadata to be added to commit info
+    pub fn with_commit_properties(mut self, commit_properties: CommitProperties) -> Self {
+        self.commit_properties = commit_properties;
+        self
+    }
+
+    fn validate(&self) -> DeltaResult<Vec<String>> {
+        if !(self.sample_fraction > 0.0 && self.sample_fraction <= 1.0) {
+            return Err(AnalyzeError::InvalidSampleFraction(self.sample_fraction).into());
+        }
+        if !(4..=18).contains(&self.hll_precision) {
+            return Err(AnalyzeError::InvalidPrecision(self.hll_precision).into());
+        }
+        let schema = self.snapshot.schema();
+        match &self.columns {
+            Some(columns) => {
+                for column in columns {
+                    if schema.field_with_name(column).is_err() {
+                        return Err(AnalyzeError::ColumnNotFound(column.clone()).into());
+                    }
+                }
+                Ok(columns.clone())
+            }
+            None => Ok(schema.fields().iter().map(|f| f.name().clone()).collect()),
+        }
+    }
+
+    async fn compute_summary(&self, columns: &[String]) -> DeltaResult<TableStatisticsSummary> {
+        let partition_columns = &self.snapshot.metadata().partition_columns;
+        let mut sketches: HashMap<String, ColumnSketch> = columns
+            .iter()
+            .filter(|c| !partition_columns.contains(c))
+            .map(|c| (c.clone(), ColumnSketch::new(self.hll_precision, self.top_k)))
+            .collect();
+
+        let object_store = self.log_store.object_store();
+        let mut files = self.snapshot.file_actions()?;
+        if let Some(max_files) = self.max_files {
+            files.truncate(max_files);
+        }
+
+        let mut num_rows_sampled = 0;
+        let mut rng = StdRng::from_entropy();
+        for add in files.iter() {
+            let meta = ObjectMeta::try_from(add)?;
+            let reader = ParquetObjectReader::new(object_store.clone(), meta);
+            let builder = ParquetRecordBatchStreamBuilder::new(reader).await?;
+            let indices = builder
+                .schema()
+                .fields()
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| sketches.contains_key(f.name()))
+                .map(|(idx, _)| idx)
+                .collect::<Vec<_>>();
+            let mask = ProjectionMask::roots(builder.parquet_schema(), indices);
+            let mut stream = builder.with_projection(mask).build()?;
+
+            while let Some(batch) = stream.try_next().await? {
+                let sampled = sample_indices(&batch, self.sample_fraction, &mut rng);
+                num_rows_sampled += sampled.len() as u64;
+                for (name, sketch) in sketches.iter_mut() {
+                    if let Some(array) = batch.column_by_name(name) {
+                        sketch.update(array.as_ref(), &sampled, &mut rng)?;
+                    }
+                }
+            }
+        }
+
+        // Partition values are constant per file, so they can be sketched from the log
+        for column in columns.iter().filter(|c| partition_columns.contains(c)) {
+            let mut sketch = ColumnSketch::new(self.hll_precision, self.top_k);
+            for add in files.iter() {
+                let rows = add
+                    .get_stats()?
+                    .map(|s| s.num_records as u64)
+                    .unwrap_or_default();
+                match add.partition_values.get(column).cloned().flatten() {
+                    Some(value) => sketch.update_value(&value, rows),
+                    None => sketch.null_count += rows,
+                }
+            }
+            sketches.insert(column.clone(), sketch);
+        }
+
+        // Partition sketches are computed from every file's exact row count and are not scaled
+        let scale = 1.0 / self.sample_fraction;
+        Ok(TableStatisticsSummary {
+            table_version: self.snapshot.version(),
+            computed_at: Utc::now().timestamp_millis(),
+            sample_fraction: self.sample_fraction,
+            num_files_scanned: files.len() as u64,
+            num_rows_sampled,
+            columns: sketches
+                .into_iter()
+                .map(|(name, sketch)| {
+                    let scale = if partition_columns.contains(&name) {
+                        1.0
+                    } else {
+                        scale
+                    };
+                    (name, sketch.finish(self.histogram_buckets, scale))
+                })
+                .collect(),
+        })
+    }
+}
+
+impl std::future::IntoFuture for AnalyzeBuilder {
+    type Output = DeltaResult<(DeltaTable, TableStatisticsSummary)>;
+    type IntoFuture = BoxFuture<'static, Self::Output>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        let this = self;
+
+        Box::pin(async move {
+            let columns = this.validate()?;
+            let summary = this.compute_summary(&columns).await?;
+            if this.dry_run {
+                return Ok((
+                    DeltaTable::new_with_state(this.log_store, this.snapshot),
+                    summary,
+                ));
+            }
+
+            let mut actions = Vec::new();
+            let protocol = this.snapshot.protocol();
+            let has_domain_metadata = protocol.min_writer_version >= 7
+                && protocol
+                    .writer_features
+                    .as_ref()
+                    .is_some_and(|f| f.contains(&WriterFeatures::DomainMetadata));
+            if !has_domain_metadata {
+                actions.push(Action::Protocol(with_writer_feature(
+                    protocol,
+                    WriterFeatures::DomainMetadata,
+                )));
+            }
+            actions.push(Action::DomainMetadata(DomainMetadata {
+                domain: TABLE_STATISTICS_DOMAIN.to_string(),
+                configuration: serde_json::to_string(&summary)?,
+                removed: false,
+            }));
+            let operation = DeltaOperation::Analyze {
+                columns: this.columns.clone(),
+                sample_fraction: this.sample_fraction,
+            };
+
+            let mut commit_properties = this.commit_properties.clone();
+            commit_properties
+                .app_metadata
+                .insert("readVersion".to_owned(), this.snapshot.version().into());
+
+            CommitBuilder::from(commit_properties)
+                .with_actions(actions)
+                .build(Some(&this.snapshot), this.log_store.clone(), operation)?
+                .await?;
+
+            let mut table = DeltaTable::new_with_state(this.log_store, this.snapshot);
+            table.update().await?;
+            Ok((table, summary))
+        })
+    }
+}
+
+/// Retrieve the most recent statistics summary written by the analyze operation.
+///
+/// Returns `None` if the table has never been analyzed, or the summary was removed.
+pub async fn get_table_statistics(
+    table: &DeltaTable,
+) -> DeltaResult<Option<TableStatisticsSummary>> {
+    let domains = table
+        .snapshot()?
+        .domain_metadata(table.log_store().log_object_store())
+        .await?;
+    domains
+        .get(TABLE_STATISTICS_DOMAIN)
+        .map(|domain| Ok(serde_json::from_str(&domain.configuration)?))
+        .transpose()
+}
+
+fn sample_indices(batch: &RecordBatch, fraction: f64, rng: &mut impl Rng) -> Vec<usize> {
+    if fraction >= 1.0 {
+        return (0..batch.num_rows()).collect();
+    }
+    (0..batch.num_rows())
+        .filter(|_| rng.gen::<f64>() < fraction)
+        .collect()
+}
+
+/// Accumulates the sketches for a single column
+#[derive(Debug)]
+struct ColumnSketch {
+    hll: HyperLogLog,
+    frequent: MisraGries,
+    reservoir: Reservoir,
+    null_count: u64,
+    numeric: bool,
+}
+
+impl ColumnSketch {
+    fn new(hll_precision: u8, top_k: usize) -> Self {
+        Self {
+            hll: HyperLogLog::new(hll_precision),
+            frequent: MisraGries::new(top_k),
+            reservoir: Reservoir::new(RESERVOIR_SIZE),
+            null_count: 0,
+            numeric: false,
+        }
+    }
+
+    fn update(&mut self, array: &dyn Array, rows: &[usize], rng: &mut impl Rng) -> DeltaResult<()> {
+        let formatter = ArrayFormatter::try_new(array, &FormatOptions::default())?;
+        let numeric = if array.data_type().is_numeric() {
+            self.numeric = true;
+            let values = cast(array, &ArrowDataType::Float64)?;
+            values.as_any().downcast_ref::<Float64Array>().cloned()
+        } else {
+            None
+        };
+
+        for &row in rows {
+            if array.is_null(row) {
+                self.null_count += 1;
+                continue;
+            }
+            self.update_value(&formatter.value(row).to_string(), 1);
+            if let Some(values) = &numeric {
+                let value = values.value(row);
+                if value.is_finite() {
+                    self.reservoir.insert(value, rng);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn update_value(&mut self, value: &str, count: u64) {
+        self.hll.insert(value);
+        self.frequent.insert(value, count);
+    }
+
+    fn finish(self, histogram_buckets: usize, scale: f64) -> ColumnStatistics {
+        let histogram = if self.numeric {
+            Some(
+                self.reservoir
+                    .histogram(histogram_buckets)
+                    .into_iter()
+                    .map(|mut bucket| {
+                        bucket.count = (bucket.count as f64 * scale).round() as u64;
+                        bucket
+                    })
+                    .collect(),
+            )
+        } else {
+            None
+        };
+        ColumnStatistics {
+            distinct_count: self.hll.estimate(),
+            null_count: (self.null_count as f64 * scale).round() as u64,
+            histogram,
+            top_k: self
+                .frequent
+                .top()
+                .into_iter()
+                .map(|(value, count)| FrequentValue {
+                    value,
+                    count: (count as f64 * scale).round() as u64,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// HyperLogLog sketch for estimating the number of distinct values
+#[derive(Debug, Clone)]
+struct HyperLogLog {
+    precision: u8,
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    fn new(precision: u8) -> Self {
+        Self {
+            precision,
+            registers: vec![0; 1 << precision],
+        }
+    }
+
+    fn insert(&mut self, value: impl Hash) {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+        let index = (hash >> (64 - self.precision)) as usize;
+        let rest = (hash << self.precision) | (1 << (self.precision - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+        }
+    }
+
+    fn estimate(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let sum: f64 = self.registers.iter().map(|r| 2f64.powi(-(*r as i32))).sum();
+        let raw = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|r| **r == 0).count();
+        // small range correction using linear counting
+        if raw <= 2.5 * m && zeros > 0 {
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            raw.round() as u64
+        }
+    }
+}
+
+/// Misra-Gries summary for finding the most frequent values
+#[derive(Debug, Clone)]
+struct MisraGries {
+    k: usize,
+    capacity: usize,
+    counters: HashMap<String, u64>,
+}
+
+impl MisraGries {
+    fn new(k: usize) -> Self {
+        Self {
+            k,
+            // tracking more candidates than requested keeps the estimates of the top values tight
+            capacity: (k * 10).max(1),
+            counters: HashMap::new(),
+        }
+    }
+
+    fn insert(&mut self, value: &str, count: u64) {
+        if let Some(counter) = self.counters.get_mut(value) {
+            *counter += count;
+            return;
+        }
+        if self.counters.len() < self.capacity {
+            self.counters.insert(value.to_string(), count);
+            return;
+        }
+        let decrement = self
+            .counters
+            .values()
+            .copied()
+            .min()
+            .unwrap_or(0)
+            .min(count);
+        self.counters.retain(|_, c| {
+            *c -= decrement;
+            *c > 0
+        });
+        if count > decrement {
+            self.counters.insert(value.to_string(), count - decrement);
+        }
+    }
+
+    fn top(self) -> Vec<(String, u64)> {
+        let mut values = self.counters.into_iter().collect::<Vec<_>>();
+        values.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        values.truncate(self.k);
+        values
+    }
+}
+
+/// Uniform reservoir sample of numeric values used to build histograms
+#[derive(Debug, Clone)]
+struct Reservoir {
+    capacity: usize,
+    seen: u64,
+    values: Vec<f64>,
+}
+
+impl Reservoir {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            seen: 0,
+            values: Vec::new(),
+        }
+    }
+
+    fn insert(&mut self, value: f64, rng: &mut impl Rng) {
+        self.seen += 1;
+        if self.values.len() < self.capacity {
+            self.values.push(value);
+        } else {
+            let idx = rng.gen_range(0..self.seen) as usize;
+            if idx < self.capacity {
+                self.values[idx] = value;
+            }
+        }
+    }
+
+    /// Build an equi-depth histogram scaled to the number of values seen
+    fn histogram(mut self, buckets: usize) -> Vec<HistogramBucket> {
+        if self.values.is_empty() || buckets == 0 {
+            return Vec::new();
+        }
+        self.values.sort_by(|a, b| a.total_cmp(b));
+        let len = self.values.len();
+        let per_bucket = (len + buckets - 1) / buckets;
+        let scale = self.seen as f64 / len as f64;
+        self.values
+            .chunks(per_bucket)
+            .map(|chunk| HistogramBucket {
+                lower: chunk[0],
+                upper: chunk[chunk.len() - 1],
+                count: (chunk.len() as f64 * scale).round() as u64,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hyperloglog_estimate() {
+        let mut hll = HyperLogLog::new(12);
+        for i in 0..10_000 {
+            hll.insert(i.to_string());
+            // duplicates must not change the estimate
+            hll.insert(i.to_string());
+        }
+        let estimate = hll.estimate() as f64;
+        assert!((estimate - 10_000.0).abs() / 10_000.0 < 0.05, "{estimate}");
+
+        let mut hll = HyperLogLog::new(12);
+        for i in 0..10 {
+            hll.insert(i.to_string());
+        }
+        assert_eq!(hll.estimate(), 10);
+    }
+
+    #[test]
+    fn test_misra_gries_top() {
+        let mut mg = MisraGries::new(2);
+        for _ in 0..100 {
+            mg.insert("a", 1);
+        }
+        for _ in 0..50 {
+            mg.insert("b", 1);
+        }
+        for i in 0..30 {
+            mg.insert(&i.to_string(), 1);
+        }
+        let top = mg.top();
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].0, "a");
+        assert_eq!(top[1].0, "b");
+    }
+
+    #[test]
+    fn test_reservoir_histogram() {
+        let mut rng = rand::thread_rng();
+        let mut reservoir = Reservoir::new(1000);
+        for i in 0..100 {
+            reservoir.insert(i as f64, &mut rng);
+        }
+        let histogram = reservoir.histogram(4);
+        assert_eq!(histogram.len(), 4);
+        assert_eq!(histogram[0].lower, 0.0);
+        assert_eq!(histogram[3].upper, 99.0);
+        assert_eq!(histogram.iter().map(|b| b.count).sum::<u64>(), 100);
+    }
+
+    #[test]
+    fn test_summary_roundtrip() {
+        let summary = TableStatisticsSummary {
+            table_version: 1,
+            computed_at: 0,
+            sample_fraction: 0.5,
+            num_files_scanned: 1,
+            num_rows_sampled: 10,
+            columns: HashMap::from([(
+                "id".to_string(),
+                ColumnStatistics {
+                    distinct_count: 10,
+                    null_count: 0,
+                    histogram: None,
+                    top_k: vec![FrequentValue {
+                        value: "1".to_string(),
+                        count: 2,
+                    }],
+                },
+            )]),
+        };
+        let json = serde_json::to_string(&summary).unwrap();
+        assert!(json.contains("\"distinctCount\":10"));
+        let parsed: TableStatisticsSummary = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, summary);
+    }
+
+    #[cfg(feature = "datafusion")]
+    #[tokio::test]
+    async fn test_analyze_roundtrip() {
+        use crate::checkpoints::create_checkpoint;
+        use crate::writer::test_utils::get_record_batch;
+        use crate::DeltaOps;
+
+        let table = DeltaOps::new_in_memory()
+            .write(vec![get_record_batch(None, false)])
+            .with_partition_columns(["modified"])
+            .await
+            .unwrap();
+        assert!(get_table_statistics(&table).await.unwrap().is_none());
+
+        let (table, summary) = DeltaOps(table)
+            .analyze()
+            .with_sample_fraction(0.5)
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 1);
+        assert_eq!(
+            get_table_statistics(&table).await.unwrap(),
+            Some(summary.clone())
+        );
+
+        // partition values are counted from the log and must not be scaled
+        let modified = &summary.columns["modified"];
+        assert_eq!(modified.distinct_count, 2);
+        assert_eq!(modified.null_count, 0);
+        assert_eq!(
+            modified.top_k,
+            vec![
+                FrequentValue {
+                    value: "2021-02-01".to_string(),
+                    count: 8,
+                },
+                FrequentValue {
+                    value: "2021-02-02".to_string(),
+                    count: 3,
+                },
+            ]
+        );
+
+        let protocol = table.protocol().unwrap();
+        assert_eq!(protocol.min_writer_version, 7);
+        assert!(protocol
+            .writer_features
+            .as_ref()
+            .unwrap()
+            .contains(&WriterFeatures::DomainMetadata));
+
+        // the summary is carried over into checkpoints
+        create_checkpoint(&table).await.unwrap();
+        let mut table = DeltaTable::new(table.log_store(), Default::default());
+        table.load().await.unwrap();
+        assert_eq!(get_table_statistics(&table).await.unwrap(), Some(summary));
+    }
+}
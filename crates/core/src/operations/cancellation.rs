@@ -0,0 +1,287 @@
+//! Cooperative cancellation of long running operations
+//!
+//! Loading, optimizing, vacuuming and merging a table can take a long time. These operations
+//! accept a [`CancellationToken`] through `with_cancellation_token`. Once the token is
+//! cancelled, the operation stops at the next batch or file it processes and returns
+//! [`DeltaTableError::Cancelled`]. A cancelled operation never commits the work it was doing,
+//! and deletes the data files it wrote but did not commit, so the table is left as if the
+//! operation had not run. Commits made before the cancellation, such as the intermediate
+//! commits of an optimize with a minimum commit interval, are kept.
+//!
+//! Operations also accept a timeout through `with_timeout`. The timeout cancels the operation
+//! once it has elapsed, and its deadline is propagated to the storage requests the operation
+//! issues, see [`crate::storage::deadline`]. An operation which runs out of time returns
+//! [`DeltaTableError::Timeout`] with the progress it made until then.
+//!
+//! # Example
+//! ```rust ignore
+//! let token = CancellationToken::new();
+//! let optimize = DeltaOps(table)
+//!     .optimize()
+//!     .with_cancellation_token(token.clone());
+//! let handle = tokio::spawn(optimize.into_future());
+//! token.cancel();
+//! assert!(matches!(handle.await?, Err(DeltaTableError::Cancelled)));
+//! ````
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use object_store::path::Path;
+use object_store::Error as ObjectStoreError;
+use tracing::warn;
+
+pub use tokio_util::sync::CancellationToken;
+
+use super::progress::ProgressTracker;
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::kernel::Action;
+use crate::storage::deadline::{with_deadline, without_deadline};
+use crate::storage::ObjectStoreRef;
+
+/// Whether the optional token has been cancelled
+pub(crate) fn is_cancelled(token: Option<&CancellationToken>) -> bool {
+    token.map(|token| token.is_cancelled()).unwrap_or(false)
+}
+
+/// Return [`DeltaTableError::Cancelled`] if the optional token has been cancelled
+pub(crate) fn check_cancelled(token: Option<&CancellationToken>) -> DeltaResult<()> {
+    if is_cancelled(token) {
+        Err(DeltaTableError::Cancelled)
+    } else {
+        Ok(())
+    }
+}
+
+/// Run `future` until it completes or the optional token is cancelled.
+///
+/// The future is dropped on cancellation, so it must not have side effects which need to be
+/// cleaned up.
+pub(crate) async fn run_cancellable<T>(
+    token: Option<&CancellationToken>,
+    future: impl Future<Output = DeltaResult<T>>,
+) -> DeltaResult<T> {
+    match token {
+        Some(token) => tokio::select! {
+            biased;
+            _ = token.cancelled() => Err(DeltaTableError::Cancelled),
+            result = future => result,
+        },
+        None => future.await,
+    }
+}
+
+/// Delete the data files of add actions which were written, but will not be committed.
+///
+/// The deletion is best effort: the files are not referenced by the log, so failing to delete
+/// them only leaves files behind for vacuum to remove.
+pub(crate) async fn delete_uncommitted_files(object_store: &ObjectStoreRef, actions: &[Action]) {
+    let locations = actions
+        .iter()
+        .filter_map(|action| match action {
+            Action::Add(add) => Path::parse(&add.path).ok(),
+            _ => None,
+        })
+        .map(Ok)
+        .collect::<Vec<_>>();
+    if locations.is_empty() {
+        return;
+    }
+
+    // cleaning up must not be cut short by the deadline of a timed out operation
+    without_deadline(async {
+        let mut results = object_store.delete_stream(futures::stream::iter(locations).boxed());
+        while let Some(result) = results.next().await {
+            match result {
+                Ok(_) | Err(ObjectStoreError::NotFound { .. }) => {}
+                Err(err) => warn!("Failed to delete uncommitted file: {err}"),
+            }
+        }
+    })
+    .await
+}
+
+/// Timeout of an operation, which cancels the operation's token once it has elapsed
+#[derive(Debug)]
+pub(crate) struct OperationTimeout {
+    timeout: Duration,
+    deadline: Instant,
+    parent: Option<CancellationToken>,
+    token: CancellationToken,
+}
+
+impl OperationTimeout {
+    /// Start the timer of an operation, which may also be cancelled through `parent`
+    pub(crate) fn start(timeout: Duration, parent: Option<CancellationToken>) -> Self {
+        let token = parent
+            .as_ref()
+            .map(CancellationToken::child_token)
+            .unwrap_or_default();
+        let deadline = Instant::now() + timeout;
+        let timer = token.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = tokio::time::sleep_until(deadline.into()) => timer.cancel(),
+                _ = timer.cancelled() => {}
+            }
+        });
+        Self {
+            timeout,
+            deadline,
+            parent,
+            token,
+        }
+    }
+
+    /// Start the timer of an operation if it has a timeout, and return the token the operation
+    /// should observe
+    pub(crate) fn maybe_start(
+        timeout: Option<Duration>,
+        parent: Option<CancellationToken>,
+    ) -> (Option<Self>, Option<CancellationToken>) {
+        match timeout {
+            Some(timeout) => {
+                let timeout = Self::start(timeout, parent);
+                let token = timeout.token.clone();
+                (Some(timeout), Some(token))
+            }
+            None => (None, parent),
+        }
+    }
+
+    /// Whether the operation ran out of time, rather than being cancelled by the caller
+    fn expired(&self) -> bool {
+        Instant::now() >= self.deadline && !is_cancelled(self.parent.as_ref())
+    }
+
+    fn error(&self, progress: &ProgressTracker) -> DeltaTableError {
+        DeltaTableError::Timeout {
+            timeout: self.timeout,
+            progress: Box::new(progress.snapshot(false)),
+        }
+    }
+
+    /// Run `future` with the deadline propagated to its storage requests. Failures after the
+    /// deadline are reported as [`DeltaTableError::Timeout`]
+    pub(crate) async fn run<T>(
+        self,
+        progress: &ProgressTracker,
+        future: impl Future<Output = DeltaResult<T>>,
+    ) -> DeltaResult<T> {
+        let result = with_deadline(self.deadline, future).await;
+        // stops the timer
+        self.token.cancel();
+        match result {
+            Err(_) if self.expired() => Err(self.error(progress)),
+            result => result,
+        }
+    }
+
+    /// Run `future` with the timeout if there is one
+    pub(crate) async fn maybe_run<T>(
+        timeout: Option<Self>,
+        progress: &ProgressTracker,
+        future: impl Future<Output = DeltaResult<T>>,
+    ) -> DeltaResult<T> {
+        match timeout {
+            Some(timeout) => timeout.run(progress, future).await,
+            None => future.await,
+        }
+    }
+
+    /// End `stream` once the timeout has elapsed
+    #[cfg(feature = "datafusion")]
+    pub(crate) fn stream(
+        self,
+        stream: datafusion::physical_plan::SendableRecordBatchStream,
+        progress: ProgressTracker,
+    ) -> datafusion::physical_plan::SendableRecordBatchStream {
+        let token = self.token.clone();
+        cancellable_stream_with(stream, token, move || {
+            if self.expired() {
+                self.error(&progress)
+            } else {
+                DeltaTableError::Cancelled
+            }
+        })
+    }
+}
+
+/// Stop a record batch stream once the token is cancelled, ending it with
+/// [`DeltaTableError::Cancelled`]
+#[cfg(feature = "datafusion")]
+pub(crate) fn cancellable_stream(
+    stream: datafusion::physical_plan::SendableRecordBatchStream,
+    token: CancellationToken,
+) -> datafusion::physical_plan::SendableRecordBatchStream {
+    cancellable_stream_with(stream, token, || DeltaTableError::Cancelled)
+}
+
+/// Stop a record batch stream once the token is cancelled, ending it with the given error
+#[cfg(feature = "datafusion")]
+fn cancellable_stream_with(
+    stream: datafusion::physical_plan::SendableRecordBatchStream,
+    token: CancellationToken,
+    error: impl FnOnce() -> DeltaTableError + Send + 'static,
+) -> datafusion::physical_plan::SendableRecordBatchStream {
+    use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+    use datafusion_common::DataFusionError;
+
+    let schema = stream.schema();
+    let cancelled = token.clone();
+    let stream = stream
+        .take_until(async move { token.cancelled().await })
+        .chain(
+            futures::stream::once(async move {
+                cancelled
+                    .is_cancelled()
+                    .then(|| Err(DataFusionError::External(Box::new(error()))))
+            })
+            .filter_map(futures::future::ready),
+        );
+    Box::pin(RecordBatchStreamAdapter::new(schema, stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::Add;
+    use object_store::memory::InMemory;
+    use object_store::ObjectStore;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_run_cancellable() {
+        let token = CancellationToken::new();
+        let result = run_cancellable(Some(&token), async { Ok(1) }).await;
+        assert_eq!(result.unwrap(), 1);
+
+        token.cancel();
+        assert!(check_cancelled(Some(&token)).is_err());
+        assert!(check_cancelled(None).is_ok());
+        let result = run_cancellable(Some(&token), futures::future::pending::<DeltaResult<()>>());
+        assert!(matches!(result.await, Err(DeltaTableError::Cancelled)));
+    }
+
+    #[tokio::test]
+    async fn test_delete_uncommitted_files() {
+        let store: ObjectStoreRef = Arc::new(InMemory::new());
+        store
+            .put(&Path::from("part-0.parquet"), vec![0u8; 8].into())
+            .await
+            .unwrap();
+        let actions = vec![
+            Action::Add(Add {
+                path: "part-0.parquet".to_string(),
+                ..Default::default()
+            }),
+            Action::Add(Add {
+                path: "missing.parquet".to_string(),
+                ..Default::default()
+            }),
+        ];
+        delete_uncommitted_files(&store, &actions).await;
+        assert!(store.head(&Path::from("part-0.parquet")).await.is_err());
+    }
+}
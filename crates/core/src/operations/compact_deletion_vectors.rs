@@ -0,0 +1,427 @@
+//! Purge deletion vectors by rewriting the data files they apply to
+//!
+//! Deletion vectors make deletes cheap, as rows are only marked as deleted instead of rewriting
+//! the data files containing them. Readers however still have to read the deleted rows and
+//! filter them out, so read amplification grows with every delete. This operation rewrites the
+//! files whose deletion vectors mark at least a threshold fraction of their rows as deleted, so
+//! that the rewritten files contain only the remaining rows and no longer need a deletion vector.
+//!
+//! The threshold is set by the [`DELETION_VECTOR_THRESHOLD_KEY`] table property, and
+//! can be overridden per run. The rewritten files are sized according to `delta.targetFileSize`.
+//!
+//! # Example
+//! ```rust ignore
+//! let table = open_table("../path/to/table")?;
+//! let (table, metrics) = DeltaOps(table)
+//!     .compact_deletion_vectors()
+//!     .with_threshold(0.1)
+//!     .await?;
+//! ````
+
+use std::sync::Arc;
+
+use arrow_array::{BooleanArray, RecordBatch};
+use arrow_select::filter::filter_record_batch;
+use futures::future::BoxFuture;
+use futures::TryStreamExt;
+use indexmap::IndexMap;
+use object_store::ObjectMeta;
+use parquet::arrow::async_reader::{ParquetObjectReader, ParquetRecordBatchStreamBuilder};
+use parquet::file::properties::WriterProperties;
+use roaring::RoaringTreemap;
+use serde::Serialize;
+use url::Url;
+
+use super::transaction::{CommitBuilder, CommitProperties, PROTOCOL};
+use super::writer::{FileLayout, PartitionWriter, PartitionWriterConfig};
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::kernel::{Action, LogicalFile, Remove, Scalar};
+use crate::logstore::LogStoreRef;
+use crate::protocol::DeltaOperation;
+use crate::table::state::DeltaTableState;
+use crate::writer::utils::arrow_schema_without_partitions;
+use crate::DeltaTable;
+
+/// Table property setting the fraction of deleted rows above which files are rewritten.
+///
+/// The property is specific to delta-rs and therefore lives outside of the reserved `delta.`
+/// namespace.
+pub const DELETION_VECTOR_THRESHOLD_KEY: &str = "delta-rs.deletionVectors.compactionThreshold";
+
+/// Threshold used for tables which do not set [`DELETION_VECTOR_THRESHOLD_KEY`]
+pub const DEFAULT_DELETION_VECTOR_THRESHOLD: f64 = 0.3;
+
+/// Errors that can occur while compacting deletion vectors
+#[derive(thiserror::Error, Debug)]
+enum CompactDeletionVectorsError {
+    #[error("Deletion vector threshold must be in the range (0, 1], got {0}")]
+    InvalidThreshold(f64),
+
+    #[error("Invalid value '{0}' for table property {key}", key = DELETION_VECTOR_THRESHOLD_KEY)]
+    InvalidThresholdProperty(String),
+}
+
+impl From<CompactDeletionVectorsError> for DeltaTableError {
+    fn from(err: CompactDeletionVectorsError) -> Self {
+        DeltaTableError::GenericError {
+            source: Box::new(err),
+        }
+    }
+}
+
+/// Metrics from compacting deletion vectors
+#[derive(Default, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactDeletionVectorsMetrics {
+    /// Number of files with deletion vectors that were rewritten
+    pub num_files_removed: u64,
+    /// Number of files written
+    pub num_files_added: u64,
+    /// Number of rows marked as deleted that were dropped from the rewritten files
+    pub num_deleted_rows_purged: u64,
+    /// Number of remaining rows written to the new files
+    pub num_rows_rewritten: u64,
+}
+
+/// Rewrite the files whose deletion vectors exceed a threshold.
+/// See this module's documentation for more information
+pub struct CompactDeletionVectorsBuilder {
+    /// A snapshot of the to-be-compacted table's state
+    snapshot: DeltaTableState,
+    /// Delta object store for handling data files
+    log_store: LogStoreRef,
+    /// Fraction of deleted rows above which files are rewritten, overriding the table property
+    threshold: Option<f64>,
+    /// Properties passed to underlying parquet writer
+    writer_properties: Option<WriterProperties>,
+    /// Additional information to add to the commit
+    commit_properties: CommitProperties,
+}
+
+impl CompactDeletionVectorsBuilder {
+    /// Create a new [`CompactDeletionVectorsBuilder`]
+    pub fn new(log_store: LogStoreRef, snapshot: DeltaTableState) -> Self {
+        Self {
+            snapshot,
+            log_store,
+            threshold: None,
+            writer_properties: None,
+            commit_properties: CommitProperties::default(),
+        }
+    }
+
+    /// Rewrite files whose deletion vectors mark at least `threshold`, a fraction between 0
+    /// and 1, of their rows as deleted. Defaults to the [`DELETION_VECTOR_THRESHOLD_KEY`]
+    /// table property
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    /// Writer properties passed to parquet writer for when files are rewritten
+    pub fn with_writer_properties(mut self, writer_properties: WriterProperties) -> Self {
+        self.writer_properties = Some(writer_properties);
+        self
+    }
+
+    /// Additional metadata to be added to commit info
+    pub fn with_commit_properties(mut self, commit_properties: CommitProperties) -> Self {
+        self.commit_properties = commit_properties;
+        self
+    }
+
+    fn threshold(&self) -> DeltaResult<f64> {
+        let threshold = match self.threshold {
+            Some(threshold) => threshold,
+            None => match self
+                .snapshot
+                .metadata()
+                .configuration
+                .get(DELETION_VECTOR_THRESHOLD_KEY)
+                .and_then(|value| value.as_deref())
+            {
+                Some(value) => value.parse().map_err(|_| {
+                    CompactDeletionVectorsError::InvalidThresholdProperty(value.to_string())
+                })?,
+                None => DEFAULT_DELETION_VECTOR_THRESHOLD,
+            },
+        };
+        if !(threshold > 0.0 && threshold <= 1.0) {
+            return Err(CompactDeletionVectorsError::InvalidThreshold(threshold).into());
+        }
+        Ok(threshold)
+    }
+
+    /// Rewrite the file of `candidate` without the rows its deletion vector marks as deleted
+    async fn rewrite_file(
+        &self,
+        candidate: Candidate,
+        table_root: &Url,
+        metrics: &mut CompactDeletionVectorsMetrics,
+    ) -> DeltaResult<Vec<Action>> {
+        let object_store = self.log_store.object_store();
+        let deleted = match &candidate.remove.deletion_vector {
+            Some(dv) => dv.read(object_store.as_ref(), table_root).await?,
+            None => RoaringTreemap::new(),
+        };
+
+        let table_config = self.snapshot.table_config();
+        let file_schema = arrow_schema_without_partitions(
+            &Arc::new(self.snapshot.schema().try_into()?),
+            &self.snapshot.metadata().partition_columns,
+        );
+        let writer_config = PartitionWriterConfig::try_new(
+            file_schema.clone(),
+            candidate.partition_values,
+            self.writer_properties.clone(),
+            Some(table_config.target_file_size() as usize),
+            None,
+        )?
+        .with_file_layout(FileLayout::from_table_config(&table_config));
+        let mut writer = PartitionWriter::try_with_config(object_store.clone(), writer_config)?;
+
+        let reader = ParquetObjectReader::new(object_store, candidate.meta);
+        let mut stream = ParquetRecordBatchStreamBuilder::new(reader)
+            .await?
+            .build()?;
+        let mut row_index = 0;
+        while let Some(batch) = stream.try_next().await? {
+            let keep = BooleanArray::from_iter(
+                (row_index..row_index + batch.num_rows() as u64)
+                    .map(|idx| Some(!deleted.contains(idx))),
+            );
+            row_index += batch.num_rows() as u64;
+            let batch: RecordBatch = filter_record_batch(&batch, &keep)?;
+            metrics.num_rows_rewritten += batch.num_rows() as u64;
+            let batch = super::cast::cast_record_batch(&batch, file_schema.clone(), false, false)?;
+            writer.write(&batch).await?;
+        }
+        metrics.num_deleted_rows_purged += deleted.len();
+        metrics.num_files_removed += 1;
+
+        let mut actions = vec![Action::Remove(candidate.remove)];
+        for mut add in writer.close().await? {
+            add.data_change = false;
+            metrics.num_files_added += 1;
+            actions.push(Action::Add(add));
+        }
+        Ok(actions)
+    }
+}
+
+/// A file to rewrite, detached from the snapshot
+struct Candidate {
+    remove: Remove,
+    meta: ObjectMeta,
+    partition_values: IndexMap<String, Scalar>,
+}
+
+impl Candidate {
+    fn try_new(file: &LogicalFile<'_>) -> DeltaResult<Self> {
+        Ok(Self {
+            remove: file.remove_action(false),
+            meta: ObjectMeta::try_from(file)?,
+            partition_values: file
+                .partition_values()?
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+        })
+    }
+}
+
+/// The table root as a directory URL, which deletion vector paths are relative to
+pub(crate) fn table_root(log_store: &LogStoreRef) -> Url {
+    let mut root = log_store.config().location.clone();
+    if !root.path().ends_with('/') {
+        root.set_path(&format!("{}/", root.path()));
+    }
+    root
+}
+
+impl std::future::IntoFuture for CompactDeletionVectorsBuilder {
+    type Output = DeltaResult<(DeltaTable, CompactDeletionVectorsMetrics)>;
+    type IntoFuture = BoxFuture<'static, Self::Output>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        let this = self;
+
+        Box::pin(async move {
+            PROTOCOL
+                .with_deletion_vectors()
+                .can_write_to(&this.snapshot.snapshot)?;
+            let threshold = this.threshold()?;
+
+            let table_root = table_root(&this.log_store);
+            let mut metrics = CompactDeletionVectorsMetrics::default();
+            let mut actions = Vec::new();
+            let candidates = this
+                .snapshot
+                .files_by_deletion_vector_coverage(threshold)?
+                .map(|file| Candidate::try_new(&file))
+                .collect::<DeltaResult<Vec<_>>>()?;
+            for candidate in candidates {
+                actions.extend(
+                    this.rewrite_file(candidate, &table_root, &mut metrics)
+                        .await?,
+                );
+            }
+            if actions.is_empty() {
+                return Ok((
+                    DeltaTable::new_with_state(this.log_store, this.snapshot),
+                    metrics,
+                ));
+            }
+
+            let operation = DeltaOperation::Reorg {
+                deletion_vector_threshold: threshold,
+            };
+            let mut commit_properties = this.commit_properties.clone();
+            commit_properties.app_metadata.insert(
+                "operationMetrics".to_owned(),
+                serde_json::to_value(&metrics)?,
+            );
+            commit_properties
+                .app_metadata
+                .insert("readVersion".to_owned(), this.snapshot.version().into());
+
+            CommitBuilder::from(commit_properties)
+                .with_actions(actions)
+                .build(Some(&this.snapshot), this.log_store.clone(), operation)?
+                .await?;
+
+            let mut table = DeltaTable::new_with_state(this.log_store, this.snapshot);
+            table.update().await?;
+            Ok((table, metrics))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::operations::DeltaOps;
+    use crate::DeltaTableBuilder;
+
+    fn copy_dir(from: &Path, to: &Path) {
+        std::fs::create_dir_all(to).unwrap();
+        for entry in std::fs::read_dir(from).unwrap() {
+            let entry = entry.unwrap();
+            let target = to.join(entry.file_name());
+            if entry.file_type().unwrap().is_dir() {
+                copy_dir(&entry.path(), &target);
+            } else {
+                std::fs::copy(entry.path(), target).unwrap();
+            }
+        }
+    }
+
+    /// A copy of a table with a deletion vector covering 20% of the rows of its only file
+    async fn dv_table(dir: &tempfile::TempDir, threshold: Option<&str>) -> DeltaTable {
+        copy_dir(
+            Path::new("../test/tests/data/table-with-dv-small"),
+            dir.path(),
+        );
+        if let Some(threshold) = threshold {
+            let commit = dir.path().join("_delta_log/00000000000000000000.json");
+            let content = std::fs::read_to_string(&commit).unwrap().replace(
+                "\"configuration\":{",
+                &format!(
+                    "\"configuration\":{{\"{DELETION_VECTOR_THRESHOLD_KEY}\":\"{threshold}\","
+                ),
+            );
+            std::fs::write(commit, content).unwrap();
+        }
+        let mut table = DeltaTableBuilder::from_uri(dir.path().to_str().unwrap())
+            .build()
+            .unwrap();
+        table.load().await.unwrap();
+        table
+    }
+
+    async fn read_values(table: &DeltaTable) -> Vec<i32> {
+        use arrow_array::cast::AsArray;
+        use arrow_array::types::Int32Type;
+
+        let store = table.object_store();
+        let mut values = Vec::new();
+        for add in table.snapshot().unwrap().file_actions().unwrap() {
+            let meta = ObjectMeta::try_from(&add).unwrap();
+            let reader = ParquetObjectReader::new(store.clone(), meta);
+            let batches: Vec<RecordBatch> = ParquetRecordBatchStreamBuilder::new(reader)
+                .await
+                .unwrap()
+                .build()
+                .unwrap()
+                .try_collect()
+                .await
+                .unwrap();
+            for batch in batches {
+                let column =
+                    arrow_cast::cast(batch.column(0), &arrow_schema::DataType::Int32).unwrap();
+                values.extend(column.as_primitive::<Int32Type>().values().iter());
+            }
+        }
+        values.sort();
+        values
+    }
+
+    #[tokio::test]
+    async fn test_compact_deletion_vectors() {
+        let dir = tempfile::tempdir().unwrap();
+        let table = dv_table(&dir, Some("0.2")).await;
+        assert_eq!(table.version(), 1);
+
+        let (table, metrics) = DeltaOps(table).compact_deletion_vectors().await.unwrap();
+        assert_eq!(table.version(), 2);
+        assert_eq!(metrics.num_files_removed, 1);
+        assert_eq!(metrics.num_files_added, 1);
+        assert_eq!(metrics.num_deleted_rows_purged, 2);
+        assert_eq!(metrics.num_rows_rewritten, 8);
+
+        let snapshot = table.snapshot().unwrap();
+        assert!(snapshot
+            .log_data()
+            .unwrap()
+            .into_iter()
+            .all(|file| file.deletion_vector().is_none()));
+        assert_eq!(read_values(&table).await, (1..9).collect::<Vec<_>>());
+
+        let history = table.history(Some(1)).await.unwrap();
+        assert_eq!(history[0].operation.as_deref(), Some("REORG"));
+    }
+
+    #[tokio::test]
+    async fn test_compact_deletion_vectors_threshold() {
+        let dir = tempfile::tempdir().unwrap();
+        let table = dv_table(&dir, None).await;
+
+        let (table, metrics) = DeltaOps(table).compact_deletion_vectors().await.unwrap();
+        assert_eq!(table.version(), 1);
+        assert_eq!(metrics.num_files_removed, 0);
+
+        let (table, metrics) = DeltaOps(table)
+            .compact_deletion_vectors()
+            .with_threshold(0.5)
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 1);
+        assert_eq!(metrics.num_files_removed, 0);
+
+        let (table, metrics) = DeltaOps(table)
+            .compact_deletion_vectors()
+            .with_threshold(0.2)
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 2);
+        assert_eq!(metrics.num_files_removed, 1);
+
+        let result = DeltaOps(table)
+            .compact_deletion_vectors()
+            .with_threshold(0.0)
+            .await;
+        assert!(result.is_err());
+    }
+}
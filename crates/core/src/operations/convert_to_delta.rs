@@ -5,6 +5,8 @@ use crate::{
     kernel::{Add, DataType, Schema, StructField},
     logstore::{LogStore, LogStoreRef},
     operations::create::CreateBuilder,
+    operations::progress::{ProgressListenerRef, ProgressTracker},
+    operations::transaction::CommitProperties,
     protocol::SaveMode,
     table::builder::ensure_table_uri,
     table::config::DeltaConfigKey,
@@ -108,6 +110,8 @@ pub struct ConvertToDeltaBuilder {
     comment: Option<String>,
     configuration: HashMap<String, Option<String>>,
     metadata: Option<Map<String, Value>>,
+    commit_properties: CommitProperties,
+    progress_listener: Option<ProgressListenerRef>,
 }
 
 impl Default for ConvertToDeltaBuilder {
@@ -130,6 +134,8 @@ impl ConvertToDeltaBuilder {
             comment: None,
             configuration: Default::default(),
             metadata: Default::default(),
+            commit_properties: CommitProperties::default(),
+            progress_listener: None,
         }
     }
 
@@ -225,8 +231,20 @@ impl ConvertToDeltaBuilder {
         self
     }
 
+    /// Additional information to write to the commit
+    pub fn with_commit_properties(mut self, commit_properties: CommitProperties) -> Self {
+        self.commit_properties = commit_properties;
+        self
+    }
+
+    /// Report the parquet files inspected to the listener
+    pub fn with_progress_listener(mut self, listener: ProgressListenerRef) -> Self {
+        self.progress_listener = Some(listener);
+        self
+    }
+
     /// Consume self into CreateBuilder with corresponding add actions, schemas and operation meta
-    async fn into_create_builder(self) -> Result<CreateBuilder, Error> {
+    async fn into_create_builder(self, progress: &ProgressTracker) -> Result<CreateBuilder, Error> {
         // Use the specified log store. If a log store is not provided, create a new store from the specified path.
         // Return an error if neither log store nor path is provided
         let log_store = if let Some(log_store) = self.log_store {
@@ -266,6 +284,10 @@ impl ConvertToDeltaBuilder {
         if files.is_empty() {
             return Err(Error::ParquetFileNotFound);
         }
+        progress.set_totals(
+            files.len() as u64,
+            files.iter().map(|file| file.size as u64).sum(),
+        );
 
         // Iterate over the parquet files. Parse partition columns, generate add actions and collect parquet file schemas
         let mut arrow_schemas = Vec::new();
@@ -283,6 +305,7 @@ impl ConvertToDeltaBuilder {
         let mut partition_schema_fields = HashMap::new();
 
         for file in files {
+            let file_size = file.size as u64;
             // A HashMap from partition column to value for this parquet file only
             let mut partition_values = HashMap::new();
             let location = file.location.clone().to_string();
@@ -365,6 +388,7 @@ impl ConvertToDeltaBuilder {
             // Since Arrow schema metadata is not used to generate Delta table schema, we set the metadata field to an empty HashMap
             arrow_schema.metadata = HashMap::new();
             arrow_schemas.push(arrow_schema);
+            progress.advance(1, file_size);
         }
 
         if !expected_partitions.is_empty() {
@@ -391,7 +415,8 @@ impl ConvertToDeltaBuilder {
             .with_partition_columns(partition_columns.into_iter())
             .with_actions(actions)
             .with_save_mode(self.mode)
-            .with_configuration(self.configuration);
+            .with_configuration(self.configuration)
+            .with_commit_properties(self.commit_properties);
         if let Some(name) = self.name {
             builder = builder.with_table_name(name);
         }
@@ -414,11 +439,13 @@ impl std::future::IntoFuture for ConvertToDeltaBuilder {
         let this = self;
 
         Box::pin(async move {
+            let progress = ProgressTracker::new(this.progress_listener.clone(), "CONVERT");
             let builder = this
-                .into_create_builder()
+                .into_create_builder(&progress)
                 .await
                 .map_err(DeltaTableError::from)?;
             let table = builder.await?;
+            progress.finish();
             Ok(table)
         })
     }
@@ -430,6 +457,7 @@ mod tests {
     use crate::{
         kernel::{DataType, PrimitiveType, Scalar},
         open_table,
+        operations::progress::RecordingListener,
         storage::StorageOptions,
         Path,
     };
@@ -553,6 +581,7 @@ mod tests {
             .snapshot()
             .unwrap()
             .log_data()
+            .unwrap()
             .into_iter()
             .flat_map(|add| {
                 add.partition_values()
@@ -847,6 +876,36 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_convert_to_delta_progress() {
+        let temp_dir = tempdir().expect("Failed to create a temp directory");
+        let temp_dir = temp_dir
+            .path()
+            .to_str()
+            .expect("Failed to convert to string slice");
+        copy_files(
+            format!(
+                "{}/../test/tests/data/delta-0.8.0",
+                env!("CARGO_MANIFEST_DIR")
+            ),
+            temp_dir,
+        );
+        let listener = Arc::new(RecordingListener::default());
+        ConvertToDeltaBuilder::new()
+            .with_log_store(log_store(temp_dir))
+            .with_progress_listener(listener.clone())
+            .await
+            .expect("Failed to convert to Delta table");
+
+        let updates = listener.updates.lock().unwrap();
+        let last = updates.last().unwrap();
+        assert!(last.finished);
+        assert_eq!(last.operation, "CONVERT");
+        assert_eq!(Some(last.files_processed), last.files_total);
+        assert_eq!(Some(last.bytes_processed), last.bytes_total);
+        assert_eq!(updates.len() as u64, last.files_processed + 1);
+    }
+
     #[tokio::test]
     async fn test_missing_location() {
         let _table = ConvertToDeltaBuilder::new()
@@ -7,11 +7,12 @@ use std::sync::Arc;
 use futures::future::BoxFuture;
 use serde_json::Value;
 
-use super::transaction::{CommitBuilder, TableReference, PROTOCOL};
+use super::transaction::{CommitBuilder, CommitProperties, TableReference, PROTOCOL};
 use crate::errors::{DeltaResult, DeltaTableError};
 use crate::kernel::{
     Action, DataType, Metadata, Protocol, ReaderFeatures, StructField, StructType, WriterFeatures,
 };
+use crate::logstore::compression::{LogCompression, LOG_COMPRESSION_KEY};
 use crate::logstore::{LogStore, LogStoreRef};
 use crate::protocol::{DeltaOperation, SaveMode};
 use crate::table::builder::ensure_table_uri;
@@ -58,6 +59,7 @@ pub struct CreateBuilder {
     log_store: Option<LogStoreRef>,
     configuration: HashMap<String, Option<String>>,
     metadata: Option<HashMap<String, Value>>,
+    commit_properties: CommitProperties,
 }
 
 impl Default for CreateBuilder {
@@ -81,6 +83,7 @@ impl CreateBuilder {
             log_store: None,
             configuration: Default::default(),
             metadata: Default::default(),
+            commit_properties: CommitProperties::default(),
         }
     }
 
@@ -190,6 +193,14 @@ impl CreateBuilder {
         self
     }
 
+    /// Additional information to write to the commit
+    ///
+    /// Metadata passed via [`Self::with_metadata`] is added to the metadata of the properties.
+    pub fn with_commit_properties(mut self, commit_properties: CommitProperties) -> Self {
+        self.commit_properties = commit_properties;
+        self
+    }
+
     /// Specify additional actions to be added to the commit.
     ///
     /// This method is mainly meant for internal use. Manually adding inconsistent
@@ -243,8 +254,15 @@ impl CreateBuilder {
         // TODO configure more permissive versions based on configuration. Also how should this ideally be handled?
         // We set the lowest protocol we can, and if subsequent writes use newer features we update metadata?
 
+        let log_compression = self
+            .configuration
+            .get(LOG_COMPRESSION_KEY)
+            .and_then(|value| value.as_deref())
+            .and_then(|value| value.parse::<LogCompression>().ok())
+            .unwrap_or_default();
+
         let (min_reader_version, min_writer_version, writer_features, reader_features) =
-            if *contains_timestampntz {
+            if *contains_timestampntz || log_compression != LogCompression::None {
                 let mut converted_writer_features = self
                     .configuration
                     .keys()
@@ -258,8 +276,14 @@ impl CreateBuilder {
                     .map(|key| key.clone().into())
                     .filter(|v| !matches!(v, ReaderFeatures::Other(_)))
                     .collect::<HashSet<ReaderFeatures>>();
-                converted_writer_features.insert(WriterFeatures::TimestampWithoutTimezone);
-                converted_reader_features.insert(ReaderFeatures::TimestampWithoutTimezone);
+                if *contains_timestampntz {
+                    converted_writer_features.insert(WriterFeatures::TimestampWithoutTimezone);
+                    converted_reader_features.insert(ReaderFeatures::TimestampWithoutTimezone);
+                }
+                if log_compression != LogCompression::None {
+                    converted_writer_features.insert(WriterFeatures::LogCompression);
+                    converted_reader_features.insert(ReaderFeatures::LogCompression);
+                }
                 (
                     3,
                     7,
@@ -328,7 +352,10 @@ impl std::future::IntoFuture for CreateBuilder {
         let this = self;
         Box::pin(async move {
             let mode = this.mode.clone();
-            let app_metadata = this.metadata.clone().unwrap_or_default();
+            let mut commit_properties = this.commit_properties.clone();
+            commit_properties
+                .app_metadata
+                .extend(this.metadata.clone().unwrap_or_default());
             let (mut table, actions, operation) = this.into_table_and_actions()?;
             let log_store = table.log_store();
 
@@ -349,9 +376,8 @@ impl std::future::IntoFuture for CreateBuilder {
                 None
             };
 
-            let version = CommitBuilder::default()
+            let version = CommitBuilder::from(commit_properties)
                 .with_actions(actions)
-                .with_app_metadata(app_metadata)
                 .build(
                     table_state.map(|f| f as &dyn TableReference),
                     table.log_store.clone(),
@@ -388,6 +414,23 @@ mod tests {
         assert_eq!(table.get_schema().unwrap(), &table_schema)
     }
 
+    #[tokio::test]
+    async fn test_create_with_commit_properties() {
+        let table = DeltaOps::new_in_memory()
+            .create()
+            .with_columns(get_delta_schema().fields().clone())
+            .with_commit_properties(CommitProperties::default().with_metadata([
+                ("jobId".to_string(), Value::from("job-1")),
+                ("pipeline".to_string(), Value::from("nightly")),
+            ]))
+            .with_metadata([("traceId".to_string(), Value::from("trace-1"))])
+            .await
+            .unwrap();
+        let info = &table.history(None).await.unwrap()[0].info;
+        assert_eq!(info["jobId"], Value::from("job-1"));
+        assert_eq!(info["traceId"], Value::from("trace-1"));
+    }
+
     #[tokio::test]
     async fn test_create_local_relative_path() {
         let table_schema = get_delta_schema();
@@ -0,0 +1,206 @@
+//! Drop appended records whose key already exists in a Delta Table
+//!
+//! Upstreams with at-least-once delivery may deliver a record more than once. Appending with a
+//! dedup key drops the incoming records whose key is already part of the table, without the
+//! cost of a full merge. Like [`delete_keys`](super::delete_keys), the files which may contain
+//! an incoming key are narrowed down by file statistics and the bloom filters of the key
+//! column, and only these files are read to verify which keys exist.
+//!
+//! Only keys of the table are checked, duplicate keys within the appended data are kept.
+//! Records with a null key are always appended.
+//!
+//! # Example
+//! ```rust ignore
+//! let table = DeltaOps(table)
+//!     .write(batches)
+//!     .with_dedup_key("event_id")
+//!     .await?;
+//! ````
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use arrow::compute::cast;
+use arrow_array::{Array, BooleanArray, RecordBatch};
+use arrow_select::filter::filter_record_batch;
+use datafusion::execution::context::SessionState;
+use datafusion::physical_expr::create_physical_expr;
+use datafusion::physical_plan::filter::FilterExec;
+use datafusion::physical_plan::{collect, ExecutionPlan};
+use datafusion_common::scalar::ScalarValue;
+use datafusion_common::{Column, DFSchema};
+use datafusion_expr::{lit, Expr};
+use futures::{StreamExt, TryStreamExt};
+use tracing::debug;
+
+use super::delete_keys::{may_contain_keys, BLOOM_FILTER_CONCURRENCY};
+use crate::delta_datafusion::{DataFusionFileMixins, DataFusionMixins, DeltaScanBuilder};
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::logstore::LogStoreRef;
+use crate::table::state::DeltaTableState;
+
+/// Keys of the records in `batches`, cast to the type of the key column of the table
+fn incoming_keys(
+    batches: &[RecordBatch],
+    column: &str,
+    data_type: &arrow_schema::DataType,
+) -> DeltaResult<Vec<Vec<Option<ScalarValue>>>> {
+    batches
+        .iter()
+        .map(|batch| {
+            let keys = batch.column_by_name(column).ok_or_else(|| {
+                DeltaTableError::Generic(format!("Dedup key column '{column}' not found in data"))
+            })?;
+            let keys = cast(keys, data_type)?;
+            (0..keys.len())
+                .map(|idx| {
+                    if keys.is_null(idx) {
+                        return Ok(None);
+                    }
+                    Ok(Some(ScalarValue::try_from_array(&keys, idx)?))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Drop the records of `batches` whose value of `column` is a key of the table, returning the
+/// remaining records and the number of records dropped
+pub(crate) async fn drop_existing_keys(
+    snapshot: &DeltaTableState,
+    log_store: LogStoreRef,
+    state: &SessionState,
+    column: &str,
+    batches: Vec<RecordBatch>,
+) -> DeltaResult<(Vec<RecordBatch>, usize)> {
+    let input_schema = snapshot.input_schema()?;
+    let data_type = input_schema.field_with_name(column)?.data_type().clone();
+    let keys = incoming_keys(&batches, column, &data_type)?;
+    let distinct = keys
+        .iter()
+        .flatten()
+        .flatten()
+        .cloned()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    if distinct.is_empty() {
+        return Ok((batches, 0));
+    }
+
+    let predicate = Expr::Column(Column::from_name(column))
+        .in_list(distinct.iter().cloned().map(lit).collect(), false);
+    let candidates = snapshot
+        .snapshot
+        .files_matching_predicate(std::slice::from_ref(&predicate))?
+        .collect::<Vec<_>>();
+    let object_store = log_store.object_store();
+    let candidates = futures::stream::iter(candidates)
+        .map(|file| {
+            let object_store = object_store.clone();
+            let keys = &distinct;
+            async move {
+                let keep = may_contain_keys(object_store, &file, column, keys).await?;
+                Ok::<_, DeltaTableError>(keep.then_some(file))
+            }
+        })
+        .buffer_unordered(BLOOM_FILTER_CONCURRENCY)
+        .try_filter_map(|file| futures::future::ready(Ok(file)))
+        .try_collect::<Vec<_>>()
+        .await?;
+    if candidates.is_empty() {
+        return Ok((batches, 0));
+    }
+    debug!("verifying dedup keys in {} files", candidates.len());
+
+    // read the keys of the candidate files to verify which incoming keys exist
+    let scan = DeltaScanBuilder::new(snapshot, log_store, state)
+        .with_files(&candidates)
+        .build()
+        .await?;
+    let input_dfschema: DFSchema = input_schema.as_ref().clone().try_into()?;
+    let predicate_expr = create_physical_expr(
+        &Expr::IsTrue(Box::new(predicate)),
+        &input_dfschema,
+        state.execution_props(),
+    )?;
+    let filter: Arc<dyn ExecutionPlan> =
+        Arc::new(FilterExec::try_new(predicate_expr, Arc::new(scan))?);
+    let mut existing = HashSet::new();
+    for batch in collect(filter, state.task_ctx()).await? {
+        let values = batch.column_by_name(column).ok_or_else(|| {
+            DeltaTableError::Generic(format!("Dedup key column '{column}' not found in table"))
+        })?;
+        for idx in 0..values.len() {
+            existing.insert(ScalarValue::try_from_array(values, idx)?);
+        }
+    }
+
+    let mut dropped = 0;
+    let batches = batches
+        .iter()
+        .zip(keys)
+        .map(|(batch, keys)| {
+            let keep = keys
+                .iter()
+                .map(|key| Some(key.as_ref().map_or(true, |key| !existing.contains(key))))
+                .collect::<BooleanArray>();
+            dropped += keep.false_count();
+            Ok(filter_record_batch(batch, &keep)?)
+        })
+        .collect::<DeltaResult<Vec<_>>>()?;
+    Ok((batches, dropped))
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::{Int64Array, StringArray};
+    use arrow_schema::{DataType, Field, Schema as ArrowSchema};
+    use datafusion::prelude::SessionContext;
+
+    use super::*;
+    use crate::operations::DeltaOps;
+    use crate::writer::test_utils::get_record_batch;
+
+    #[tokio::test]
+    async fn test_drop_existing_keys() {
+        let table = DeltaOps::new_in_memory()
+            .write(vec![get_record_batch(None, false)])
+            .await
+            .unwrap();
+
+        // the table holds the values 1 to 11, incoming keys are cast to the type of the table
+        let schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("value", DataType::Int64, true),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![Some(1), Some(42), Some(5), None])),
+                Arc::new(StringArray::from(vec!["a", "b", "c", "d"])),
+            ],
+        )
+        .unwrap();
+
+        let state = SessionContext::new().state();
+        let (batches, dropped) = drop_existing_keys(
+            table.snapshot().unwrap(),
+            table.log_store(),
+            &state,
+            "value",
+            vec![batch],
+        )
+        .await
+        .unwrap();
+        assert_eq!(dropped, 2);
+        assert_eq!(batches.len(), 1);
+        let names = batches[0]
+            .column_by_name("name")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(names, &StringArray::from(vec!["b", "d"]));
+    }
+}
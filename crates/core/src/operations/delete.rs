@@ -18,6 +18,7 @@
 //! ````
 
 use core::panic;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
@@ -25,19 +26,24 @@ use crate::logstore::LogStoreRef;
 use datafusion::execution::context::{SessionContext, SessionState};
 use datafusion::physical_expr::create_physical_expr;
 use datafusion::physical_plan::filter::FilterExec;
+use datafusion::physical_plan::metrics::MetricValue;
 use datafusion::physical_plan::ExecutionPlan;
 use datafusion::prelude::Expr;
 use datafusion_common::scalar::ScalarValue;
+use datafusion_common::tree_node::TreeNode;
 use datafusion_common::DFSchema;
 use futures::future::BoxFuture;
 use parquet::file::properties::WriterProperties;
 use serde::Serialize;
 
-use super::datafusion_utils::Expression;
+use super::datafusion_utils::{drain_plan, Expression};
+use super::estimate::CostEstimate;
 use super::transaction::{CommitBuilder, CommitProperties, PROTOCOL};
 use crate::delta_datafusion::expr::fmt_expr_to_sql;
 use crate::delta_datafusion::{
-    find_files, register_store, DataFusionMixins, DeltaScanBuilder, DeltaSessionContext,
+    find_files, register_store, scan_memory_table, DataFusionFileMixins, DataFusionMixins,
+    DeltaScanBuilder, DeltaScanConfig, DeltaScanConfigBuilder, DeltaSessionContext,
+    FindFilesExprProperties,
 };
 use crate::errors::DeltaResult;
 use crate::kernel::{Action, Add, Remove};
@@ -61,6 +67,8 @@ pub struct DeleteBuilder {
     writer_properties: Option<WriterProperties>,
     /// Commit properties and configuration
     commit_properties: CommitProperties,
+    /// Don't rewrite files or commit. Just determine which files would be affected
+    dry_run: bool,
 }
 
 #[derive(Default, Debug, Serialize)]
@@ -70,6 +78,8 @@ pub struct DeleteMetrics {
     pub num_added_files: usize,
     /// Number of files removed
     pub num_removed_files: usize,
+    /// Number of removed files whose remaining rows were rewritten into the added files
+    pub num_rewritten_files: usize,
     /// Number of rows removed
     pub num_deleted_rows: Option<usize>,
     /// Number of rows copied in the process of deleting files
@@ -80,6 +90,12 @@ pub struct DeleteMetrics {
     pub scan_time_ms: u128,
     /// Time taken to rewrite the matched files
     pub rewrite_time_ms: u128,
+    /// Was this a dry run
+    #[serde(skip)]
+    pub dry_run: bool,
+    /// Files that would be removed or rewritten, only reported for dry runs
+    #[serde(skip)]
+    pub files_to_remove: Vec<String>,
 }
 
 impl DeleteBuilder {
@@ -92,6 +108,7 @@ impl DeleteBuilder {
             state: None,
             commit_properties: CommitProperties::default(),
             writer_properties: None,
+            dry_run: false,
         }
     }
 
@@ -118,9 +135,133 @@ impl DeleteBuilder {
         self.writer_properties = Some(writer_properties);
         self
     }
+
+    /// Only determine which files and rows would be deleted, without modifying the table
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Estimate the storage IO of the delete from the log, without reading any data.
+    ///
+    /// Files which may contain matching rows are determined from their statistics, so the
+    /// estimate is an upper bound unless the predicate only references partition columns.
+    /// See [`super::estimate`] for the assumptions made
+    pub async fn estimate(&self) -> DeltaResult<CostEstimate> {
+        let state = self.state.clone().unwrap_or_else(|| {
+            let session: SessionContext = DeltaSessionContext::default().into();
+            register_store(self.log_store.clone(), session.runtime_env());
+            session.state()
+        });
+        let predicate = match &self.predicate {
+            Some(Expression::DataFusion(expr)) => Some(expr.clone()),
+            Some(Expression::String(s)) => {
+                Some(self.snapshot.parse_predicate_expression(s, &state)?)
+            }
+            None => None,
+        };
+
+        let (candidates, rewrite) = match predicate {
+            Some(predicate) => {
+                let mut expr_properties = FindFilesExprProperties {
+                    partition_only: true,
+                    partition_columns: self.snapshot.metadata().partition_columns.clone(),
+                    result: Ok(()),
+                };
+                TreeNode::visit(&predicate, &mut expr_properties)?;
+                expr_properties.result?;
+
+                if expr_properties.partition_only {
+                    (scan_memory_table(&self.snapshot, &predicate).await?, false)
+                } else {
+                    let candidates = self
+                        .snapshot
+                        .snapshot
+                        .files_matching_predicate(&[predicate])?
+                        .collect::<Vec<_>>();
+                    (candidates, true)
+                }
+            }
+            None => (self.snapshot.file_actions()?, false),
+        };
+
+        let mut estimate = CostEstimate::default();
+        if candidates.is_empty() {
+            return Ok(estimate);
+        }
+        if rewrite {
+            let files = candidates.len() as u64;
+            let bytes = candidates.iter().map(|add| add.size as u64).sum();
+            // candidates are scanned for matches before the matching files are rewritten
+            estimate.read_files(files, bytes);
+            estimate.read_files(files, bytes);
+            estimate.write_files(files, bytes);
+        }
+        estimate.commit();
+        Ok(estimate)
+    }
 }
 
-async fn excute_non_empty_expr(
+/// Count the rows matching `expression` in the files that would be rewritten
+async fn count_deleted_rows(
+    snapshot: &DeltaTableState,
+    log_store: LogStoreRef,
+    state: &SessionState,
+    expression: &Expr,
+    metrics: &mut DeleteMetrics,
+    rewrite: &[Add],
+) -> DeltaResult<()> {
+    let input_schema = snapshot.input_schema()?;
+    let input_dfschema: DFSchema = input_schema.as_ref().clone().try_into()?;
+
+    let scan = DeltaScanBuilder::new(snapshot, log_store, state)
+        .with_files(rewrite)
+        .with_scan_config(file_per_partition(snapshot)?)
+        .build()
+        .await?;
+    let scan = Arc::new(scan);
+
+    let matching = Expr::IsTrue(Box::new(expression.clone()));
+    let predicate_expr = create_physical_expr(&matching, &input_dfschema, state.execution_props())?;
+    let filter: Arc<dyn ExecutionPlan> =
+        Arc::new(FilterExec::try_new(predicate_expr, scan.clone())?);
+
+    let deleted = drain_plan(filter.clone(), state).await?;
+    let read_records = scan.parquet_scan.metrics().and_then(|m| m.output_rows());
+    metrics.num_deleted_rows = Some(deleted);
+    metrics.num_copied_rows = read_records.map(|read| read - deleted);
+
+    // files where not every row matches would be rewritten
+    let deleted_per_file = output_rows_per_partition(filter.as_ref());
+    metrics.num_rewritten_files = output_rows_per_partition(scan.parquet_scan.as_ref())
+        .into_iter()
+        .filter(|(file, read)| *read > deleted_per_file.get(file).copied().unwrap_or(0))
+        .count();
+    Ok(())
+}
+
+/// Scan config which reads every file in its own partition, so the metrics of each partition
+/// describe a single file
+fn file_per_partition(snapshot: &DeltaTableState) -> DeltaResult<DeltaScanConfig> {
+    DeltaScanConfigBuilder::new()
+        .with_partition_per_file(true)
+        .build(snapshot)
+}
+
+/// Number of rows output by each partition of an executed plan
+fn output_rows_per_partition(plan: &dyn ExecutionPlan) -> HashMap<usize, usize> {
+    let mut rows = HashMap::new();
+    for metric in plan.metrics().iter().flat_map(|metrics| metrics.iter()) {
+        if let (MetricValue::OutputRows(count), Some(partition)) =
+            (metric.value(), metric.partition())
+        {
+            *rows.entry(partition).or_default() += count.value();
+        }
+    }
+    rows
+}
+
+pub(super) async fn excute_non_empty_expr(
     snapshot: &DeltaTableState,
     log_store: LogStoreRef,
     state: &SessionState,
@@ -139,6 +280,7 @@ async fn excute_non_empty_expr(
 
     let scan = DeltaScanBuilder::new(snapshot, log_store.clone(), state)
         .with_files(rewrite)
+        .with_scan_config(file_per_partition(snapshot)?)
         .build()
         .await?;
     let scan = Arc::new(scan);
@@ -165,6 +307,7 @@ async fn excute_non_empty_expr(
         writer_properties,
         false,
         None,
+        None,
     )
     .await?
     .into_iter()
@@ -177,6 +320,11 @@ async fn excute_non_empty_expr(
     let read_records = scan.parquet_scan.metrics().and_then(|m| m.output_rows());
     let filter_records = filter.metrics().and_then(|m| m.output_rows());
     metrics.num_copied_rows = filter_records;
+    // only files with remaining rows have them written to a replacement file
+    metrics.num_rewritten_files = output_rows_per_partition(filter.as_ref())
+        .into_values()
+        .filter(|rows| *rows > 0)
+        .count();
     metrics.num_deleted_rows = read_records
         .zip(filter_records)
         .map(|(read, filter)| read - filter);
@@ -191,6 +339,7 @@ async fn execute(
     state: SessionState,
     writer_properties: Option<WriterProperties>,
     mut commit_properties: CommitProperties,
+    dry_run: bool,
 ) -> DeltaResult<((Vec<Action>, i64, Option<DeltaOperation>), DeleteMetrics)> {
     let exec_start = Instant::now();
     let mut metrics = DeleteMetrics::default();
@@ -201,6 +350,42 @@ async fn execute(
 
     let predicate = predicate.unwrap_or(Expr::Literal(ScalarValue::Boolean(Some(true))));
 
+    if dry_run {
+        if candidates.partition_scan {
+            // entire files are removed, so the row count can be taken from the statistics
+            metrics.num_deleted_rows = candidates
+                .candidates
+                .iter()
+                .map(|add| {
+                    add.get_stats()
+                        .ok()
+                        .flatten()
+                        .map(|stats| stats.num_records as usize)
+                })
+                .sum();
+            metrics.num_copied_rows = Some(0);
+        } else {
+            count_deleted_rows(
+                snapshot,
+                log_store.clone(),
+                &state,
+                &predicate,
+                &mut metrics,
+                &candidates.candidates,
+            )
+            .await?;
+        }
+        metrics.dry_run = true;
+        metrics.num_removed_files = candidates.candidates.len();
+        metrics.files_to_remove = candidates
+            .candidates
+            .into_iter()
+            .map(|add| add.path)
+            .collect();
+        metrics.execution_time_ms = Instant::now().duration_since(exec_start).as_millis();
+        return Ok(((Vec::new(), snapshot.version(), None), metrics));
+    }
+
     let add = if candidates.partition_scan {
         Vec::new()
     } else {
@@ -286,6 +471,9 @@ impl std::future::IntoFuture for DeleteBuilder {
         Box::pin(async move {
             PROTOCOL.check_append_only(&this.snapshot.snapshot)?;
             PROTOCOL.can_write_to(&this.snapshot.snapshot)?;
+            this.commit_properties
+                .check_idempotency(&this.log_store, this.snapshot.version())
+                .await?;
 
             let state = this.state.unwrap_or_else(|| {
                 let session: SessionContext = DeltaSessionContext::default().into();
@@ -313,6 +501,7 @@ impl std::future::IntoFuture for DeleteBuilder {
                 state,
                 this.writer_properties,
                 this.commit_properties,
+                this.dry_run,
             )
             .await?;
 
@@ -401,12 +590,12 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 1);
+        assert_eq!(table.get_files_count().unwrap(), 1);
 
         let (table, metrics) = DeltaOps(table).delete().await.unwrap();
 
         assert_eq!(table.version(), 2);
-        assert_eq!(table.get_files_count(), 0);
+        assert_eq!(table.get_files_count().unwrap(), 0);
         assert_eq!(metrics.num_added_files, 0);
         assert_eq!(metrics.num_removed_files, 1);
         assert_eq!(metrics.num_deleted_rows, None);
@@ -463,7 +652,7 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 1);
+        assert_eq!(table.get_files_count().unwrap(), 1);
 
         let batch = RecordBatch::try_new(
             Arc::clone(&schema),
@@ -487,7 +676,7 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(table.version(), 2);
-        assert_eq!(table.get_files_count(), 2);
+        assert_eq!(table.get_files_count().unwrap(), 2);
 
         let (table, metrics) = DeltaOps(table)
             .delete()
@@ -495,10 +684,11 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(table.version(), 3);
-        assert_eq!(table.get_files_count(), 2);
+        assert_eq!(table.get_files_count().unwrap(), 2);
 
         assert_eq!(metrics.num_added_files, 1);
         assert_eq!(metrics.num_removed_files, 1);
+        assert_eq!(metrics.num_rewritten_files, 1);
         assert!(metrics.scan_time_ms > 0);
         assert_eq!(metrics.num_deleted_rows, Some(1));
         assert_eq!(metrics.num_copied_rows, Some(3));
@@ -643,7 +833,7 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 2);
+        assert_eq!(table.get_files_count().unwrap(), 2);
 
         let (table, metrics) = DeltaOps(table)
             .delete()
@@ -651,10 +841,11 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(table.version(), 2);
-        assert_eq!(table.get_files_count(), 1);
+        assert_eq!(table.get_files_count().unwrap(), 1);
 
         assert_eq!(metrics.num_added_files, 0);
         assert_eq!(metrics.num_removed_files, 1);
+        assert_eq!(metrics.num_rewritten_files, 0);
         assert_eq!(metrics.num_deleted_rows, None);
         assert_eq!(metrics.num_copied_rows, None);
         assert!(metrics.scan_time_ms > 0);
@@ -701,7 +892,7 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 3);
+        assert_eq!(table.get_files_count().unwrap(), 3);
 
         let (table, metrics) = DeltaOps(table)
             .delete()
@@ -713,7 +904,7 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(table.version(), 2);
-        assert_eq!(table.get_files_count(), 2);
+        assert_eq!(table.get_files_count().unwrap(), 2);
 
         assert_eq!(metrics.num_added_files, 0);
         assert_eq!(metrics.num_removed_files, 1);
@@ -801,4 +992,89 @@ mod tests {
             .await;
         assert!(res.is_err());
     }
+
+    #[tokio::test]
+    async fn test_delete_dry_run() {
+        let schema = get_arrow_schema(&None);
+        let table = setup_table(None).await;
+
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(arrow::array::StringArray::from(vec!["A", "B", "A", "A"])),
+                Arc::new(arrow::array::Int32Array::from(vec![1, 10, 10, 100])),
+                Arc::new(arrow::array::StringArray::from(vec![
+                    "2021-02-02",
+                    "2021-02-02",
+                    "2021-02-02",
+                    "2021-02-02",
+                ])),
+            ],
+        )
+        .unwrap();
+        let table = write_batch(table, batch).await;
+        assert_eq!(table.version(), 1);
+        let files = table.get_files_iter().unwrap().collect::<Vec<_>>();
+
+        let (table, metrics) = DeltaOps(table)
+            .delete()
+            .with_predicate(col("value").eq(lit(10)))
+            .with_dry_run(true)
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 1);
+        assert_eq!(table.get_files_iter().unwrap().collect::<Vec<_>>(), files);
+
+        assert!(metrics.dry_run);
+        assert_eq!(metrics.num_added_files, 0);
+        assert_eq!(metrics.num_removed_files, 1);
+        assert_eq!(metrics.files_to_remove, vec![files[0].to_string()]);
+        assert_eq!(metrics.num_rewritten_files, 1);
+        assert_eq!(metrics.num_deleted_rows, Some(2));
+        assert_eq!(metrics.num_copied_rows, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_delete_all_rows_of_file() {
+        // files whose rows are all deleted are removed without being rewritten
+        let schema = get_arrow_schema(&None);
+        let mut table = setup_table(None).await;
+        for values in [vec![1, 1], vec![1, 2]] {
+            let batch = RecordBatch::try_new(
+                Arc::clone(&schema),
+                vec![
+                    Arc::new(arrow::array::StringArray::from(vec!["A", "B"])),
+                    Arc::new(arrow::array::Int32Array::from(values)),
+                    Arc::new(arrow::array::StringArray::from(vec![
+                        "2021-02-02",
+                        "2021-02-02",
+                    ])),
+                ],
+            )
+            .unwrap();
+            table = write_batch(table, batch).await;
+        }
+        assert_eq!(table.get_files_count().unwrap(), 2);
+
+        let (table, metrics) = DeltaOps(table)
+            .delete()
+            .with_predicate(col("value").eq(lit(1)))
+            .with_dry_run(true)
+            .await
+            .unwrap();
+        assert_eq!(metrics.num_removed_files, 2);
+        assert_eq!(metrics.num_rewritten_files, 1);
+
+        let (table, metrics) = DeltaOps(table)
+            .delete()
+            .with_predicate(col("value").eq(lit(1)))
+            .await
+            .unwrap();
+        assert_eq!(table.get_files_count().unwrap(), 1);
+        assert_eq!(metrics.num_added_files, 1);
+        assert_eq!(metrics.num_removed_files, 2);
+        assert_eq!(metrics.num_rewritten_files, 1);
+        assert_eq!(metrics.num_deleted_rows, Some(3));
+        assert_eq!(metrics.num_copied_rows, Some(1));
+    }
 }
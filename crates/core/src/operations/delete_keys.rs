@@ -0,0 +1,545 @@
+//! Delete the records with a key in a given set of keys from a Delta Table
+//!
+//! Point deletes, such as erasing the records of a user for a right-to-be-forgotten request,
+//! typically affect few rows spread over few files. Rather than scanning all files matching a
+//! general predicate, candidate files are narrowed down in stages, each cheaper than the next:
+//!
+//! 1. file statistics exclude files whose min/max range of the key column contains no key,
+//! 2. parquet bloom filters of the key column exclude files not containing any key,
+//! 3. the remaining files are scanned to determine the files actually containing a key.
+//!
+//! Only the files containing a key are rewritten without the matching records. Keys in a
+//! partition column remove the matching files without rewriting them.
+//!
+//! Deletion vectors cannot be written yet, so matched files are always rewritten. Bloom filters
+//! are only consulted for files written with bloom filters enabled for the key column.
+//!
+//! # Example
+//! ```rust ignore
+//! let table = open_table("../path/to/table")?;
+//! let (table, metrics) = DeltaOps(table)
+//!     .delete_keys("user_id", ["a2b5c1", "f00d42"])
+//!     .await?;
+//! ````
+
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use arrow::compute::cast;
+use arrow::datatypes::DataType;
+use datafusion::execution::context::{SessionContext, SessionState};
+use datafusion_common::scalar::ScalarValue;
+use datafusion_common::Column;
+use datafusion_expr::{lit, Expr};
+use futures::future::BoxFuture;
+use futures::{StreamExt, TryStreamExt};
+use itertools::Itertools;
+use object_store::ObjectMeta;
+use parquet::arrow::async_reader::{ParquetObjectReader, ParquetRecordBatchStreamBuilder};
+use parquet::bloom_filter::Sbbf;
+use parquet::file::properties::WriterProperties;
+use serde::Serialize;
+
+use super::delete::{excute_non_empty_expr, DeleteMetrics};
+use super::transaction::{CommitBuilder, CommitProperties, PROTOCOL};
+use crate::delta_datafusion::expr::fmt_expr_to_sql;
+use crate::delta_datafusion::{
+    register_store, scan_files_matching, scan_memory_table, DataFusionFileMixins, DataFusionMixins,
+    DeltaSessionContext,
+};
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::kernel::{Action, Add, Remove};
+use crate::logstore::LogStoreRef;
+use crate::protocol::DeltaOperation;
+use crate::storage::ObjectStoreRef;
+use crate::table::state::DeltaTableState;
+use crate::DeltaTable;
+
+pub(super) const BLOOM_FILTER_CONCURRENCY: usize = 10;
+
+/// Delete the records with a key in a set of keys.
+/// See this module's documentation for more information
+pub struct DeleteKeysBuilder {
+    /// Name of the key column
+    column: String,
+    /// Keys of the records to delete
+    keys: Vec<ScalarValue>,
+    /// A snapshot of the table's state
+    snapshot: DeltaTableState,
+    /// Delta object store for handling data files
+    log_store: LogStoreRef,
+    /// Datafusion session state relevant for executing the input plan
+    state: Option<SessionState>,
+    /// Properties passed to underlying parquet writer for when files are rewritten
+    writer_properties: Option<WriterProperties>,
+    /// Commit properties and configuration
+    commit_properties: CommitProperties,
+}
+
+#[derive(Default, Debug, Serialize)]
+/// Metrics for the Delete Keys Operation
+pub struct DeleteKeysMetrics {
+    /// Number of distinct keys to delete
+    pub num_keys: usize,
+    /// Number of files whose statistics may contain a key
+    pub num_candidate_files: usize,
+    /// Number of candidate files excluded by their bloom filters
+    pub num_files_pruned_by_bloom_filter: usize,
+    /// Number of files added
+    pub num_added_files: usize,
+    /// Number of files removed
+    pub num_removed_files: usize,
+    /// Number of rows removed
+    pub num_deleted_rows: Option<usize>,
+    /// Number of rows copied in the process of deleting files
+    pub num_copied_rows: Option<usize>,
+    /// Time taken to execute the entire operation
+    pub execution_time_ms: u128,
+    /// Time taken to find the files containing a key
+    pub scan_time_ms: u128,
+    /// Time taken to rewrite the matched files
+    pub rewrite_time_ms: u128,
+}
+
+impl DeleteKeysBuilder {
+    /// Create a new [`DeleteKeysBuilder`]
+    pub fn new(
+        log_store: LogStoreRef,
+        snapshot: DeltaTableState,
+        column: impl Into<String>,
+        keys: impl IntoIterator<Item = impl Into<ScalarValue>>,
+    ) -> Self {
+        Self {
+            column: column.into(),
+            keys: keys.into_iter().map(Into::into).collect(),
+            snapshot,
+            log_store,
+            state: None,
+            writer_properties: None,
+            commit_properties: CommitProperties::default(),
+        }
+    }
+
+    /// The Datafusion session state to use
+    pub fn with_session_state(mut self, state: SessionState) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Additonal information to write to the commit
+    pub fn with_commit_properties(mut self, commit_properties: CommitProperties) -> Self {
+        self.commit_properties = commit_properties;
+        self
+    }
+
+    /// Writer properties passed to parquet writer for when files are rewritten
+    pub fn with_writer_properties(mut self, writer_properties: WriterProperties) -> Self {
+        self.writer_properties = Some(writer_properties);
+        self
+    }
+}
+
+/// Whether a bloom filter may contain `key`, which has the type of the key column
+fn bloom_filter_may_contain(filter: &Sbbf, key: &ScalarValue) -> bool {
+    // values are hashed using the plain encoding of their parquet physical type
+    match key {
+        ScalarValue::Int8(Some(v)) => filter.check(&(*v as i32)),
+        ScalarValue::Int16(Some(v)) => filter.check(&(*v as i32)),
+        ScalarValue::Int32(Some(v)) | ScalarValue::Date32(Some(v)) => filter.check(v),
+        ScalarValue::UInt8(Some(v)) => filter.check(&(*v as i32)),
+        ScalarValue::UInt16(Some(v)) => filter.check(&(*v as i32)),
+        ScalarValue::UInt32(Some(v)) => filter.check(&(*v as i32)),
+        ScalarValue::Int64(Some(v)) | ScalarValue::TimestampMicrosecond(Some(v), _) => {
+            filter.check(v)
+        }
+        ScalarValue::UInt64(Some(v)) => filter.check(&(*v as i64)),
+        ScalarValue::Utf8(Some(v)) | ScalarValue::LargeUtf8(Some(v)) => filter.check(&v.as_str()),
+        ScalarValue::Binary(Some(v)) | ScalarValue::LargeBinary(Some(v)) => filter.check(v),
+        // nulls never match a key
+        key if key.is_null() => false,
+        _ => true,
+    }
+}
+
+/// Whether the bloom filters of `column` in `file` may contain any of `keys`.
+///
+/// Files without bloom filters for the column may contain any key.
+pub(super) async fn may_contain_keys(
+    object_store: ObjectStoreRef,
+    file: &Add,
+    column: &str,
+    keys: &[ScalarValue],
+) -> DeltaResult<bool> {
+    let meta = ObjectMeta::try_from(file)?;
+    let mut builder =
+        ParquetRecordBatchStreamBuilder::new(ParquetObjectReader::new(object_store, meta)).await?;
+    let Some(column_idx) = builder
+        .parquet_schema()
+        .columns()
+        .iter()
+        .position(|descr| descr.path().string() == column)
+    else {
+        return Ok(true);
+    };
+    for row_group in 0..builder.metadata().num_row_groups() {
+        let may_contain = match builder
+            .get_row_group_column_bloom_filter(row_group, column_idx)
+            .await?
+        {
+            Some(filter) => keys
+                .iter()
+                .any(|key| bloom_filter_may_contain(&filter, key)),
+            None => true,
+        };
+        if may_contain {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+async fn execute(
+    column: String,
+    keys: Vec<ScalarValue>,
+    log_store: LogStoreRef,
+    snapshot: &DeltaTableState,
+    state: SessionState,
+    writer_properties: Option<WriterProperties>,
+    mut commit_properties: CommitProperties,
+) -> DeltaResult<(
+    (Vec<Action>, i64, Option<DeltaOperation>),
+    DeleteKeysMetrics,
+)> {
+    let exec_start = Instant::now();
+    let mut metrics = DeleteKeysMetrics::default();
+
+    // cast the keys to the column type, so they compare and hash like the stored values
+    let schema = snapshot.arrow_schema()?;
+    let data_type = match schema.field_with_name(&column)?.data_type() {
+        // partition columns are dictionary encoded in the scan schema
+        DataType::Dictionary(_, value_type) => value_type.as_ref(),
+        data_type => data_type,
+    };
+    let keys = keys
+        .iter()
+        .map(|key| -> DeltaResult<_> {
+            let key = cast(&key.to_array()?, data_type)?;
+            Ok(ScalarValue::try_from_array(&key, 0)?)
+        })
+        .collect::<DeltaResult<Vec<_>>>()?
+        .into_iter()
+        .unique()
+        .collect::<Vec<_>>();
+    metrics.num_keys = keys.len();
+    if keys.is_empty() {
+        return Ok(((Vec::new(), snapshot.version(), None), metrics));
+    }
+
+    let predicate = Expr::Column(Column::from_name(column.clone()))
+        .in_list(keys.iter().cloned().map(lit).collect(), false);
+
+    let scan_start = Instant::now();
+    let partition_scan = snapshot.metadata().partition_columns.contains(&column);
+    let matching = if partition_scan {
+        let matching = scan_memory_table(snapshot, &predicate).await?;
+        metrics.num_candidate_files = matching.len();
+        matching
+    } else {
+        let candidates = snapshot
+            .snapshot
+            .files_matching_predicate(std::slice::from_ref(&predicate))?
+            .collect::<Vec<_>>();
+        metrics.num_candidate_files = candidates.len();
+
+        let object_store = log_store.object_store();
+        let candidates = futures::stream::iter(candidates)
+            .map(|file| {
+                let object_store = object_store.clone();
+                let (column, keys) = (&column, &keys);
+                async move {
+                    let keep = may_contain_keys(object_store, &file, column, keys).await?;
+                    Ok::<_, DeltaTableError>(keep.then_some(file))
+                }
+            })
+            .buffer_unordered(BLOOM_FILTER_CONCURRENCY)
+            .try_filter_map(|file| futures::future::ready(Ok(file)))
+            .try_collect::<Vec<_>>()
+            .await?;
+        metrics.num_files_pruned_by_bloom_filter = metrics.num_candidate_files - candidates.len();
+
+        if candidates.is_empty() {
+            Vec::new()
+        } else {
+            scan_files_matching(
+                snapshot,
+                log_store.clone(),
+                &state,
+                predicate.clone(),
+                Some(&candidates),
+            )
+            .await?
+        }
+    };
+    metrics.scan_time_ms = Instant::now().duration_since(scan_start).as_millis();
+
+    let add = if partition_scan {
+        metrics.num_deleted_rows = matching
+            .iter()
+            .map(|add| {
+                add.get_stats()
+                    .ok()
+                    .flatten()
+                    .map(|stats| stats.num_records as usize)
+            })
+            .sum();
+        metrics.num_copied_rows = Some(0);
+        Vec::new()
+    } else if matching.is_empty() {
+        Vec::new()
+    } else {
+        let write_start = Instant::now();
+        let mut delete_metrics = DeleteMetrics::default();
+        let add = excute_non_empty_expr(
+            snapshot,
+            log_store.clone(),
+            &state,
+            &predicate,
+            &mut delete_metrics,
+            &matching,
+            writer_properties,
+        )
+        .await?;
+        metrics.num_deleted_rows = delete_metrics.num_deleted_rows;
+        metrics.num_copied_rows = delete_metrics.num_copied_rows;
+        metrics.rewrite_time_ms = Instant::now().duration_since(write_start).as_millis();
+        add
+    };
+
+    let deletion_timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64;
+
+    let mut actions: Vec<Action> = add.into_iter().map(Action::Add).collect();
+    metrics.num_removed_files = matching.len();
+    metrics.num_added_files = actions.len();
+
+    for action in matching {
+        actions.push(Action::Remove(Remove {
+            path: action.path,
+            deletion_timestamp: Some(deletion_timestamp),
+            data_change: true,
+            extended_file_metadata: Some(true),
+            partition_values: Some(action.partition_values),
+            size: Some(action.size),
+            deletion_vector: action.deletion_vector,
+            tags: None,
+            base_row_id: action.base_row_id,
+            default_row_commit_version: action.default_row_commit_version,
+        }))
+    }
+
+    metrics.execution_time_ms = Instant::now().duration_since(exec_start).as_millis();
+
+    commit_properties
+        .app_metadata
+        .insert("readVersion".to_owned(), snapshot.version().into());
+    commit_properties.app_metadata.insert(
+        "operationMetrics".to_owned(),
+        serde_json::to_value(&metrics)?,
+    );
+
+    // Do not make a commit when there are zero updates to the state
+    if actions.is_empty() {
+        return Ok(((actions, snapshot.version(), None), metrics));
+    }
+    let operation = DeltaOperation::Delete {
+        predicate: Some(fmt_expr_to_sql(&predicate)?),
+    };
+
+    let commit = CommitBuilder::from(commit_properties)
+        .with_actions(actions)
+        .build(Some(snapshot), log_store, operation)?
+        .await?;
+    Ok((
+        (
+            commit.data.actions,
+            commit.version,
+            Some(commit.data.operation),
+        ),
+        metrics,
+    ))
+}
+
+impl std::future::IntoFuture for DeleteKeysBuilder {
+    type Output = DeltaResult<(DeltaTable, DeleteKeysMetrics)>;
+    type IntoFuture = BoxFuture<'static, Self::Output>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        let mut this = self;
+
+        Box::pin(async move {
+            PROTOCOL.check_append_only(&this.snapshot.snapshot)?;
+            PROTOCOL.can_write_to(&this.snapshot.snapshot)?;
+
+            let state = this.state.unwrap_or_else(|| {
+                let session: SessionContext = DeltaSessionContext::default().into();
+
+                // If a user provides their own their DF state then they must register the store themselves
+                register_store(this.log_store.clone(), session.runtime_env());
+
+                session.state()
+            });
+
+            let ((actions, version, operation), metrics) = execute(
+                this.column,
+                this.keys,
+                this.log_store.clone(),
+                &this.snapshot,
+                state,
+                this.writer_properties,
+                this.commit_properties,
+            )
+            .await?;
+
+            if let Some(op) = &operation {
+                this.snapshot.merge(actions, op, version)?;
+            }
+
+            let table = DeltaTable::new_with_state(this.log_store, this.snapshot);
+            Ok((table, metrics))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{Int32Array, StringArray};
+    use arrow::datatypes::{DataType as ArrowDataType, Field, Schema as ArrowSchema};
+    use arrow::record_batch::RecordBatch;
+    use datafusion::assert_batches_sorted_eq;
+
+    use super::*;
+    use crate::kernel::{DataType, PrimitiveType};
+    use crate::operations::DeltaOps;
+    use crate::writer::test_utils::datafusion::get_data_sorted;
+    use crate::writer::test_utils::{get_delta_schema, get_record_batch};
+
+    async fn setup_table(values: &[&[i32]]) -> DeltaTable {
+        let mut table = DeltaOps::new_in_memory()
+            .create()
+            .with_column("id", DataType::Primitive(PrimitiveType::String), true, None)
+            .with_column(
+                "value",
+                DataType::Primitive(PrimitiveType::Integer),
+                true,
+                None,
+            )
+            .await
+            .unwrap();
+        let schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("id", ArrowDataType::Utf8, true),
+            Field::new("value", ArrowDataType::Int32, true),
+        ]));
+        for values in values {
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(StringArray::from(
+                        values.iter().map(|v| format!("id-{v}")).collect::<Vec<_>>(),
+                    )),
+                    Arc::new(Int32Array::from(values.to_vec())),
+                ],
+            )
+            .unwrap();
+            table = DeltaOps(table)
+                .write(vec![batch])
+                .with_writer_properties(
+                    WriterProperties::builder()
+                        .set_bloom_filter_enabled(true)
+                        .build(),
+                )
+                .await
+                .unwrap();
+        }
+        table
+    }
+
+    #[tokio::test]
+    async fn test_delete_keys_bloom_filter() {
+        let table = setup_table(&[&[1, 3], &[10, 12], &[20, 22]]).await;
+
+        // statistics of all files match some key, but only the last file contains one
+        let (table, metrics) = DeltaOps(table)
+            .delete_keys("value", [2, 11, 22])
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 4);
+        assert_eq!(metrics.num_keys, 3);
+        assert_eq!(metrics.num_candidate_files, 3);
+        assert_eq!(metrics.num_files_pruned_by_bloom_filter, 2);
+        assert_eq!(metrics.num_removed_files, 1);
+        assert_eq!(metrics.num_added_files, 1);
+        assert_eq!(metrics.num_deleted_rows, Some(1));
+        assert_eq!(metrics.num_copied_rows, Some(1));
+
+        let expected = vec![
+            "+-------+",
+            "| value |",
+            "+-------+",
+            "| 1     |",
+            "| 3     |",
+            "| 10    |",
+            "| 12    |",
+            "| 20    |",
+            "+-------+",
+        ];
+        let actual = get_data_sorted(&table, "value").await;
+        assert_batches_sorted_eq!(&expected, &actual);
+    }
+
+    #[tokio::test]
+    async fn test_delete_keys_without_match() {
+        let table = setup_table(&[&[1, 3]]).await;
+
+        let (table, metrics) = DeltaOps(table)
+            .delete_keys("id", ["id-2", "id-4"])
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 1);
+        assert_eq!(metrics.num_removed_files, 0);
+        assert_eq!(metrics.num_deleted_rows, None);
+
+        let (table, metrics) = DeltaOps(table)
+            .delete_keys("id", Vec::<String>::new())
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 1);
+        assert_eq!(metrics.num_keys, 0);
+    }
+
+    #[tokio::test]
+    async fn test_delete_keys_partition_column() {
+        let table = DeltaOps::new_in_memory()
+            .create()
+            .with_columns(get_delta_schema().fields().clone())
+            .with_partition_columns(["modified"])
+            .await
+            .unwrap();
+        let table = DeltaOps(table)
+            .write(vec![get_record_batch(None, false)])
+            .await
+            .unwrap();
+
+        let (table, metrics) = DeltaOps(table)
+            .delete_keys("modified", ["2021-02-02"])
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 2);
+        assert_eq!(metrics.num_removed_files, 1);
+        assert_eq!(metrics.num_added_files, 0);
+        assert_eq!(metrics.num_deleted_rows, Some(3));
+        assert_eq!(table.get_files_count().unwrap(), 1);
+    }
+}
@@ -0,0 +1,242 @@
+//! Estimate the storage IO and cost of operations before running them
+//!
+//! The `estimate` methods of the optimize, vacuum and delete builders predict the bytes read and
+//! written as well as the number of object store requests an operation would issue, based on
+//! the log and file listings only. Data files are never read. Combined with a
+//! [`PricingProfile`] the estimate gives an approximate cloud cost, e.g. to schedule maintenance
+//! within a budget.
+//!
+//! Estimates assume every file is read with [`READ_REQUESTS_PER_FILE`] requests and written
+//! with one request per [`MULTIPART_PART_SIZE`] bytes. Estimates for deletes with predicates on
+//! non-partition columns are upper bounds, since files are only pruned using their statistics.
+//!
+//! # Example
+//! ```rust ignore
+//! let table = open_table("../path/to/table")?;
+//! let estimate = DeltaOps(table).optimize().estimate()?;
+//! println!("{} bytes, ${:.2}", estimate.bytes_read, estimate.cost(&PricingProfile::aws_s3_standard()));
+//! ````
+
+use serde::Serialize;
+
+/// Requests assumed to read a data file, fetching the footer and the column chunks
+pub const READ_REQUESTS_PER_FILE: u64 = 2;
+
+/// Size of the parts of multipart uploads
+pub const MULTIPART_PART_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Objects returned by a single list request
+pub const LIST_PAGE_SIZE: u64 = 1000;
+
+const GIB: f64 = (1024 * 1024 * 1024) as f64;
+
+fn div_ceil(value: u64, divisor: u64) -> u64 {
+    (value + divisor - 1) / divisor
+}
+
+/// Prices of object store requests and transfers, in an arbitrary currency
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PricingProfile {
+    /// Price per 1000 GET or HEAD requests
+    pub read_request_cost: f64,
+    /// Price per 1000 PUT, COPY or POST requests
+    pub write_request_cost: f64,
+    /// Price per 1000 LIST requests
+    pub list_request_cost: f64,
+    /// Price per 1000 DELETE requests
+    pub delete_request_cost: f64,
+    /// Price per GiB read
+    pub read_gib_cost: f64,
+    /// Price per GiB written
+    pub write_gib_cost: f64,
+}
+
+impl PricingProfile {
+    /// Request prices of AWS S3 standard storage in us-east-1 in USD, without data transfer
+    pub fn aws_s3_standard() -> Self {
+        Self {
+            read_request_cost: 0.0004,
+            write_request_cost: 0.005,
+            list_request_cost: 0.005,
+            delete_request_cost: 0.0,
+            read_gib_cost: 0.0,
+            write_gib_cost: 0.0,
+        }
+    }
+}
+
+/// Predicted storage IO of an operation
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostEstimate {
+    /// Bytes read from data files
+    pub bytes_read: u64,
+    /// Bytes written to data files
+    pub bytes_written: u64,
+    /// Number of GET or HEAD requests
+    pub read_requests: u64,
+    /// Number of PUT, COPY or POST requests
+    pub write_requests: u64,
+    /// Number of LIST requests
+    pub list_requests: u64,
+    /// Number of DELETE requests
+    pub delete_requests: u64,
+}
+
+impl CostEstimate {
+    /// Approximate cost of the operation given the prices in `pricing`
+    pub fn cost(&self, pricing: &PricingProfile) -> f64 {
+        let requests = |count: u64, price: f64| count as f64 / 1000.0 * price;
+        requests(self.read_requests, pricing.read_request_cost)
+            + requests(self.write_requests, pricing.write_request_cost)
+            + requests(self.list_requests, pricing.list_request_cost)
+            + requests(self.delete_requests, pricing.delete_request_cost)
+            + self.bytes_read as f64 / GIB * pricing.read_gib_cost
+            + self.bytes_written as f64 / GIB * pricing.write_gib_cost
+    }
+
+    /// Account for reading `files` data files with a total size of `bytes`
+    pub(crate) fn read_files(&mut self, files: u64, bytes: u64) {
+        self.bytes_read += bytes;
+        self.read_requests += files * READ_REQUESTS_PER_FILE;
+    }
+
+    /// Account for writing `files` data files with a total size of `bytes`
+    pub(crate) fn write_files(&mut self, files: u64, bytes: u64) {
+        self.bytes_written += bytes;
+        self.write_requests += if files == 0 {
+            0
+        } else {
+            // files are assumed to be of equal size
+            files * div_ceil(bytes / files, MULTIPART_PART_SIZE).max(1)
+        };
+    }
+
+    /// Account for listing `objects` objects
+    pub(crate) fn list_objects(&mut self, objects: u64) {
+        self.list_requests += div_ceil(objects, LIST_PAGE_SIZE).max(1);
+    }
+
+    /// Account for a commit, which writes a temporary commit file and moves it into the log
+    pub(crate) fn commit(&mut self) {
+        self.write_requests += 2;
+        self.delete_requests += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::DeltaOps;
+    use crate::writer::test_utils::{create_initialized_table, get_record_batch};
+    use crate::writer::{DeltaWriter, RecordBatchWriter};
+    use crate::DeltaTable;
+
+    async fn setup_table() -> (DeltaTable, u64) {
+        let mut table = create_initialized_table(&[]).await;
+        let mut writer = RecordBatchWriter::for_table(&table).unwrap();
+        for _ in 0..2 {
+            writer.write(get_record_batch(None, false)).await.unwrap();
+            writer.flush_and_commit(&mut table).await.unwrap();
+        }
+        let size = table
+            .snapshot()
+            .unwrap()
+            .file_actions()
+            .unwrap()
+            .iter()
+            .map(|add| add.size as u64)
+            .sum();
+        (table, size)
+    }
+
+    #[tokio::test]
+    async fn test_estimate_optimize_and_vacuum() {
+        let (table, size) = setup_table().await;
+
+        let estimate = DeltaOps(table.clone()).optimize().estimate().unwrap();
+        assert_eq!(
+            estimate,
+            CostEstimate {
+                bytes_read: size,
+                bytes_written: size,
+                read_requests: 4,
+                write_requests: 3,
+                list_requests: 0,
+                delete_requests: 1,
+            }
+        );
+        assert!(estimate.cost(&PricingProfile::aws_s3_standard()) > 0.0);
+
+        let estimate = DeltaOps(table).vacuum().estimate().await.unwrap();
+        assert_eq!(
+            estimate,
+            CostEstimate {
+                list_requests: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[cfg(feature = "datafusion")]
+    #[tokio::test]
+    async fn test_estimate_delete() {
+        use datafusion_expr::{col, lit};
+
+        let (table, size) = setup_table().await;
+
+        let estimate = DeltaOps(table.clone())
+            .delete()
+            .with_predicate(col("value").gt(lit(5)))
+            .estimate()
+            .await
+            .unwrap();
+        assert_eq!(estimate.bytes_read, 2 * size);
+        assert_eq!(estimate.bytes_written, size);
+        assert_eq!(estimate.read_requests, 8);
+
+        let estimate = DeltaOps(table.clone())
+            .delete()
+            .with_predicate(col("value").gt(lit(100)))
+            .estimate()
+            .await
+            .unwrap();
+        assert_eq!(estimate, CostEstimate::default());
+
+        let estimate = DeltaOps(table).delete().estimate().await.unwrap();
+        assert_eq!(estimate.bytes_read, 0);
+        assert_eq!(estimate.write_requests, 2);
+    }
+
+    #[test]
+    fn test_cost_estimate() {
+        let mut estimate = CostEstimate::default();
+        estimate.read_files(10, 100);
+        estimate.write_files(2, 3 * MULTIPART_PART_SIZE);
+        estimate.list_objects(1500);
+        estimate.commit();
+        assert_eq!(
+            estimate,
+            CostEstimate {
+                bytes_read: 100,
+                bytes_written: 3 * MULTIPART_PART_SIZE,
+                read_requests: 20,
+                write_requests: 6,
+                list_requests: 2,
+                delete_requests: 1,
+            }
+        );
+
+        let pricing = PricingProfile {
+            read_request_cost: 1.0,
+            write_request_cost: 10.0,
+            list_request_cost: 100.0,
+            delete_request_cost: 0.0,
+            read_gib_cost: 0.0,
+            write_gib_cost: 1000.0,
+        };
+        let expected = 0.02 + 0.06 + 0.2 + 3.0 * MULTIPART_PART_SIZE as f64 / GIB * 1000.0;
+        assert!((estimate.cost(&pricing) - expected).abs() < 1e-9);
+    }
+}
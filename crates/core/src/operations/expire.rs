@@ -0,0 +1,527 @@
+//! Delete rows whose time to live has expired
+//!
+//! Tables opt into row expiration by setting two table properties:
+//!
+//! - `delta-rs.ttl.column`: a timestamp or date column holding the time each row was created
+//! - `delta-rs.ttl.duration`: the time to live of rows, as an interval such as `interval 30 days`
+//!
+//! Rows whose TTL column is older than the current time minus the TTL are deleted. The
+//! operation works through the table one partition at a time, committing each partition
+//! separately, so a run can be bounded with [`ExpireRowsBuilder::with_max_partitions`] and
+//! interrupted at any time. Runs are resumable without any bookkeeping: once a partition has
+//! been processed, the statistics of its files show that it contains no expired rows, so it is
+//! skipped until rows in it expire again.
+//!
+//! Files are selected using their statistics and only rewritten if they contain expired rows.
+//! When the TTL column is a partition column, expired partitions are removed without rewriting.
+//!
+//! # Example
+//! ```rust ignore
+//! let table = open_table("../path/to/table")?;
+//! let (table, metrics) = DeltaOps(table).expire_rows().with_max_partitions(10).await?;
+//! ````
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use arrow::compute::cast;
+use arrow_schema::DataType;
+use chrono::{DateTime, Utc};
+use datafusion::execution::context::{SessionContext, SessionState};
+use datafusion_common::scalar::ScalarValue;
+use datafusion_common::Column;
+use datafusion_expr::{lit, Cast, Expr};
+use futures::future::BoxFuture;
+use parquet::file::properties::WriterProperties;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::delete::{excute_non_empty_expr, DeleteMetrics};
+use super::transaction::{CommitBuilder, CommitProperties, PROTOCOL};
+use super::vacuum::Clock;
+use crate::delta_datafusion::expr::fmt_expr_to_sql;
+use crate::delta_datafusion::{
+    register_store, scan_files_matching, scan_memory_table, DataFusionFileMixins, DataFusionMixins,
+    DeltaSessionContext,
+};
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::kernel::{Action, Add, Remove};
+use crate::logstore::LogStoreRef;
+use crate::protocol::DeltaOperation;
+use crate::table::config::parse_interval;
+use crate::table::state::DeltaTableState;
+use crate::DeltaTable;
+
+/// Table property naming the column holding the creation time of rows
+pub const TTL_COLUMN_PROPERTY: &str = "delta-rs.ttl.column";
+/// Table property holding the time to live of rows
+pub const TTL_DURATION_PROPERTY: &str = "delta-rs.ttl.duration";
+
+/// Errors that can occur while expiring rows
+#[derive(thiserror::Error, Debug)]
+enum ExpireError {
+    #[error("Table property {0} must be set to expire rows")]
+    MissingProperty(&'static str),
+
+    #[error("Invalid time to live in {TTL_DURATION_PROPERTY}: {0}")]
+    InvalidDuration(String),
+
+    #[error("TTL column {column} must be a timestamp or date column, found {data_type}")]
+    InvalidColumn { column: String, data_type: DataType },
+}
+
+impl From<ExpireError> for DeltaTableError {
+    fn from(err: ExpireError) -> Self {
+        DeltaTableError::GenericError {
+            source: Box::new(err),
+        }
+    }
+}
+
+/// Delete rows whose time to live has expired.
+/// See this module's documentation for more information
+pub struct ExpireRowsBuilder {
+    /// A snapshot of the table's state
+    snapshot: DeltaTableState,
+    /// Delta object store for handling data files
+    log_store: LogStoreRef,
+    /// Maximum number of partitions to process
+    max_partitions: Option<usize>,
+    /// Override the source of time
+    clock: Option<Arc<dyn Clock>>,
+    /// Datafusion session state relevant for executing the input plan
+    state: Option<SessionState>,
+    /// Properties passed to underlying parquet writer for when files are rewritten
+    writer_properties: Option<WriterProperties>,
+    /// Commit properties and configuration
+    commit_properties: CommitProperties,
+}
+
+/// Metrics for the Expire Rows Operation
+#[derive(Default, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpireRowsMetrics {
+    /// Rows created before this timestamp, in milliseconds since epoch, were expired
+    pub cutoff_timestamp: i64,
+    /// Number of partitions rows were expired in
+    pub num_partitions_expired: usize,
+    /// Number of partitions which may contain expired rows but were not processed
+    pub num_partitions_remaining: usize,
+    /// Number of commits created, one per expired partition
+    pub num_commits: usize,
+    /// Number of files added
+    pub num_added_files: usize,
+    /// Number of files removed
+    pub num_removed_files: usize,
+    /// Number of rows removed
+    pub num_deleted_rows: usize,
+    /// Number of rows copied in the process of rewriting files
+    pub num_copied_rows: usize,
+    /// Time taken to execute the entire operation
+    pub execution_time_ms: u128,
+}
+
+impl ExpireRowsBuilder {
+    /// Create a new [`ExpireRowsBuilder`]
+    pub fn new(log_store: LogStoreRef, snapshot: DeltaTableState) -> Self {
+        Self {
+            snapshot,
+            log_store,
+            max_partitions: None,
+            clock: None,
+            state: None,
+            writer_properties: None,
+            commit_properties: CommitProperties::default(),
+        }
+    }
+
+    /// Process at most `max_partitions` partitions, leaving the remaining ones to later runs
+    pub fn with_max_partitions(mut self, max_partitions: usize) -> Self {
+        self.max_partitions = Some(max_partitions);
+        self
+    }
+
+    /// Override the source of time
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// The Datafusion session state to use
+    pub fn with_session_state(mut self, state: SessionState) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Additonal information to write to the commits
+    pub fn with_commit_properties(mut self, commit_properties: CommitProperties) -> Self {
+        self.commit_properties = commit_properties;
+        self
+    }
+
+    /// Writer properties passed to parquet writer for when files are rewritten
+    pub fn with_writer_properties(mut self, writer_properties: WriterProperties) -> Self {
+        self.writer_properties = Some(writer_properties);
+        self
+    }
+}
+
+/// The expiration predicate of the table and its SQL representation, given the current time in
+/// milliseconds
+fn expiration_predicate(
+    snapshot: &DeltaTableState,
+    now_millis: i64,
+) -> DeltaResult<(Expr, String, i64)> {
+    let configuration = &snapshot.metadata().configuration;
+    let property = |key: &'static str| {
+        configuration
+            .get(key)
+            .and_then(|value| value.as_deref())
+            .ok_or(ExpireError::MissingProperty(key))
+    };
+    let column = property(TTL_COLUMN_PROPERTY)?;
+    let ttl = parse_interval(property(TTL_DURATION_PROPERTY)?)
+        .map_err(|err| ExpireError::InvalidDuration(err.to_string()))?;
+    let ttl = i64::try_from(ttl.as_millis())
+        .map_err(|err| ExpireError::InvalidDuration(err.to_string()))?;
+    let cutoff = now_millis.saturating_sub(ttl);
+
+    let schema = snapshot.arrow_schema()?;
+    let data_type = schema.field_with_name(column)?.data_type();
+    if !matches!(
+        data_type,
+        DataType::Timestamp(_, _) | DataType::Date32 | DataType::Date64
+    ) {
+        return Err(ExpireError::InvalidColumn {
+            column: column.to_string(),
+            data_type: data_type.clone(),
+        }
+        .into());
+    }
+    let cutoff_value =
+        ScalarValue::TimestampMillisecond(Some(cutoff), Some("UTC".into())).to_array()?;
+    let cutoff_value = ScalarValue::try_from_array(&cast(&cutoff_value, data_type)?, 0)?;
+    let predicate = Expr::Column(Column::from_name(column)).lt(lit(cutoff_value));
+
+    // timestamp literals have no SQL representation, so the cutoff is written as a cast
+    let cutoff_string = DateTime::<Utc>::from_timestamp_millis(cutoff)
+        .ok_or_else(|| ExpireError::InvalidDuration(format!("cutoff {cutoff} out of range")))?
+        .to_rfc3339();
+    let predicate_sql = fmt_expr_to_sql(&Expr::Column(Column::from_name(column)).lt(Expr::Cast(
+        Cast::new(Box::new(lit(cutoff_string)), data_type.clone()),
+    )))?;
+    Ok((predicate, predicate_sql, cutoff))
+}
+
+fn remove_actions(files: Vec<Add>) -> impl Iterator<Item = Action> {
+    let deletion_timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64;
+    files.into_iter().map(move |action| {
+        Action::Remove(Remove {
+            path: action.path,
+            deletion_timestamp: Some(deletion_timestamp),
+            data_change: true,
+            extended_file_metadata: Some(true),
+            partition_values: Some(action.partition_values),
+            size: Some(action.size),
+            deletion_vector: action.deletion_vector,
+            tags: None,
+            base_row_id: action.base_row_id,
+            default_row_commit_version: action.default_row_commit_version,
+        })
+    })
+}
+
+fn partition_path(partition: &[(String, Option<String>)]) -> String {
+    partition
+        .iter()
+        .map(|(key, value)| format!("{key}={}", value.as_deref().unwrap_or("null")))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+async fn execute(
+    log_store: LogStoreRef,
+    snapshot: &mut DeltaTableState,
+    state: SessionState,
+    max_partitions: Option<usize>,
+    now_millis: i64,
+    writer_properties: Option<WriterProperties>,
+    commit_properties: CommitProperties,
+) -> DeltaResult<ExpireRowsMetrics> {
+    let exec_start = Instant::now();
+    let mut metrics = ExpireRowsMetrics::default();
+
+    let (predicate, predicate_sql, cutoff) = expiration_predicate(snapshot, now_millis)?;
+    metrics.cutoff_timestamp = cutoff;
+    let partition_scan = predicate
+        .to_columns()?
+        .iter()
+        .all(|column| snapshot.metadata().partition_columns.contains(&column.name));
+
+    // candidate files grouped by partition, in a stable order so runs make progress
+    let candidates: Vec<Add> = if partition_scan {
+        scan_memory_table(snapshot, &predicate).await?
+    } else {
+        snapshot
+            .snapshot
+            .files_matching_predicate(std::slice::from_ref(&predicate))?
+            .collect()
+    };
+    let mut partitions: BTreeMap<Vec<(String, Option<String>)>, Vec<Add>> = BTreeMap::new();
+    for file in candidates {
+        let mut partition = file
+            .partition_values
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<Vec<_>>();
+        partition.sort();
+        partitions.entry(partition).or_default().push(file);
+    }
+
+    let max_partitions = max_partitions.unwrap_or(usize::MAX);
+    let num_partitions = partitions.len();
+    for (partition, files) in partitions.into_iter().take(max_partitions) {
+        let (matching, add) = if partition_scan {
+            metrics.num_deleted_rows += files
+                .iter()
+                .filter_map(|add| add.get_stats().ok().flatten())
+                .map(|stats| stats.num_records as usize)
+                .sum::<usize>();
+            (files, Vec::new())
+        } else {
+            let matching = scan_files_matching(
+                snapshot,
+                log_store.clone(),
+                &state,
+                predicate.clone(),
+                Some(&files),
+            )
+            .await?;
+            if matching.is_empty() {
+                continue;
+            }
+            let mut delete_metrics = DeleteMetrics::default();
+            let add = excute_non_empty_expr(
+                snapshot,
+                log_store.clone(),
+                &state,
+                &predicate,
+                &mut delete_metrics,
+                &matching,
+                writer_properties.clone(),
+            )
+            .await?;
+            metrics.num_deleted_rows += delete_metrics.num_deleted_rows.unwrap_or_default();
+            metrics.num_copied_rows += delete_metrics.num_copied_rows.unwrap_or_default();
+            (matching, add)
+        };
+
+        metrics.num_removed_files += matching.len();
+        metrics.num_added_files += add.len();
+        let actions: Vec<Action> = add
+            .into_iter()
+            .map(Action::Add)
+            .chain(remove_actions(matching))
+            .collect();
+
+        let mut commit_properties = commit_properties.clone();
+        commit_properties
+            .app_metadata
+            .insert("readVersion".to_owned(), snapshot.version().into());
+        commit_properties.app_metadata.insert(
+            "ttlPartition".to_owned(),
+            Value::String(partition_path(&partition)),
+        );
+        let operation = DeltaOperation::Delete {
+            predicate: Some(predicate_sql.clone()),
+        };
+        let commit = CommitBuilder::from(commit_properties)
+            .with_actions(actions)
+            .build(Some(&*snapshot), log_store.clone(), operation)?
+            .await?;
+        snapshot.merge(commit.data.actions, &commit.data.operation, commit.version)?;
+        metrics.num_partitions_expired += 1;
+        metrics.num_commits += 1;
+    }
+    metrics.num_partitions_remaining = num_partitions.saturating_sub(max_partitions);
+    metrics.execution_time_ms = Instant::now().duration_since(exec_start).as_millis();
+    Ok(metrics)
+}
+
+impl std::future::IntoFuture for ExpireRowsBuilder {
+    type Output = DeltaResult<(DeltaTable, ExpireRowsMetrics)>;
+    type IntoFuture = BoxFuture<'static, Self::Output>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        let mut this = self;
+
+        Box::pin(async move {
+            PROTOCOL.check_append_only(&this.snapshot.snapshot)?;
+            PROTOCOL.can_write_to(&this.snapshot.snapshot)?;
+
+            let state = this.state.unwrap_or_else(|| {
+                let session: SessionContext = DeltaSessionContext::default().into();
+
+                // If a user provides their own their DF state then they must register the store themselves
+                register_store(this.log_store.clone(), session.runtime_env());
+
+                session.state()
+            });
+            let now_millis = match &this.clock {
+                Some(clock) => clock.current_timestamp_millis(),
+                None => Utc::now().timestamp_millis(),
+            };
+
+            let metrics = execute(
+                this.log_store.clone(),
+                &mut this.snapshot,
+                state,
+                this.max_partitions,
+                now_millis,
+                this.writer_properties,
+                this.commit_properties,
+            )
+            .await?;
+
+            let table = DeltaTable::new_with_state(this.log_store, this.snapshot);
+            Ok((table, metrics))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Int32Array, StringArray, TimestampMicrosecondArray};
+    use arrow::datatypes::{Field, Schema as ArrowSchema, TimeUnit};
+    use arrow::record_batch::RecordBatch;
+    use datafusion::assert_batches_sorted_eq;
+
+    use super::*;
+    use crate::kernel::{DataType as DeltaDataType, PrimitiveType};
+    use crate::operations::DeltaOps;
+    use crate::writer::test_utils::datafusion::get_data_sorted;
+
+    const DAY_MILLIS: i64 = 24 * 60 * 60 * 1000;
+    const NOW_MILLIS: i64 = 100 * DAY_MILLIS;
+
+    #[derive(Debug)]
+    struct FixedClock(i64);
+
+    impl Clock for FixedClock {
+        fn current_timestamp_millis(&self) -> i64 {
+            self.0
+        }
+    }
+
+    async fn setup_table(ttl: Option<&str>) -> DeltaTable {
+        let mut configuration = vec![(TTL_COLUMN_PROPERTY, Some("created_at"))];
+        if let Some(ttl) = ttl {
+            configuration.push((TTL_DURATION_PROPERTY, Some(ttl)));
+        }
+        let table = DeltaOps::new_in_memory()
+            .create()
+            .with_column(
+                "id",
+                DeltaDataType::Primitive(PrimitiveType::Integer),
+                true,
+                None,
+            )
+            .with_column(
+                "region",
+                DeltaDataType::Primitive(PrimitiveType::String),
+                true,
+                None,
+            )
+            .with_column(
+                "created_at",
+                DeltaDataType::Primitive(PrimitiveType::Timestamp),
+                true,
+                None,
+            )
+            .with_partition_columns(["region"])
+            .with_configuration(configuration)
+            .await
+            .unwrap();
+
+        let schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("id", DataType::Int32, true),
+            Field::new("region", DataType::Utf8, true),
+            Field::new(
+                "created_at",
+                DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+                true,
+            ),
+        ]));
+        // rows created 1, 20 and 50 days before now in each region
+        let days = [99, 80, 50, 99, 80, 50, 99, 80, 50];
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int32Array::from((0..9).collect::<Vec<_>>())),
+                Arc::new(StringArray::from(vec![
+                    "eu", "eu", "eu", "us", "us", "us", "ap", "ap", "ap",
+                ])),
+                Arc::new(
+                    TimestampMicrosecondArray::from(
+                        days.iter()
+                            .map(|day| day * DAY_MILLIS * 1000)
+                            .collect::<Vec<_>>(),
+                    )
+                    .with_timezone("UTC"),
+                ),
+            ],
+        )
+        .unwrap();
+        DeltaOps(table).write(vec![batch]).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_expire_rows_incrementally() {
+        let table = setup_table(Some("interval 30 days")).await;
+        let expire = |table: DeltaTable| {
+            DeltaOps(table)
+                .expire_rows()
+                .with_clock(Arc::new(FixedClock(NOW_MILLIS)))
+                .with_max_partitions(2)
+        };
+
+        let (table, metrics) = expire(table).await.unwrap();
+        assert_eq!(metrics.cutoff_timestamp, 70 * DAY_MILLIS);
+        assert_eq!(metrics.num_partitions_expired, 2);
+        assert_eq!(metrics.num_partitions_remaining, 1);
+        assert_eq!(metrics.num_commits, 2);
+        assert_eq!(metrics.num_deleted_rows, 2);
+        assert_eq!(table.version(), 3);
+
+        // the next run resumes with the remaining partition
+        let (table, metrics) = expire(table).await.unwrap();
+        assert_eq!(metrics.num_partitions_expired, 1);
+        assert_eq!(metrics.num_partitions_remaining, 0);
+        assert_eq!(metrics.num_deleted_rows, 1);
+        assert_eq!(table.version(), 4);
+
+        let (table, metrics) = expire(table).await.unwrap();
+        assert_eq!(metrics.num_commits, 0);
+        assert_eq!(table.version(), 4);
+
+        let expected = vec![
+            "+----+", "| id |", "+----+", "| 0  |", "| 1  |", "| 3  |", "| 4  |", "| 6  |",
+            "| 7  |", "+----+",
+        ];
+        let actual = get_data_sorted(&table, "id").await;
+        assert_batches_sorted_eq!(&expected, &actual);
+    }
+
+    #[tokio::test]
+    async fn test_expire_rows_requires_properties() {
+        let table = setup_table(None).await;
+        assert!(DeltaOps(table).expire_rows().await.is_err());
+
+        let table = setup_table(Some("30 days")).await;
+        assert!(DeltaOps(table).expire_rows().await.is_err());
+    }
+}
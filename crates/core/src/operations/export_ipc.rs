@@ -0,0 +1,330 @@
+//! Export a snapshot of a Delta Table to Arrow IPC files
+//!
+//! Rows of the current snapshot, optionally filtered by a predicate and projected to a subset
+//! of columns, are streamed into Arrow IPC files (Feather v2) at a destination url. A new file
+//! is started whenever the current file reaches the configured size, so consumers such as ML
+//! training jobs can read the files in parallel. Files are named
+//! `<prefix>-<index>.arrow` and buffered in memory until complete.
+//!
+//! # Example
+//! ```rust ignore
+//! let table = open_table("../path/to/table")?;
+//! let (table, metrics) = DeltaOps(table)
+//!     .export_ipc("s3://bucket/training/", IpcExportOptions::default())
+//!     .with_predicate("label IS NOT NULL")
+//!     .await?;
+//! ````
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::record_batch::RecordBatch;
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{Schema as ArrowSchema, SchemaRef as ArrowSchemaRef};
+use bytes::Bytes;
+use datafusion::execution::context::{SessionContext, SessionState};
+use futures::future::BoxFuture;
+use futures::StreamExt;
+use object_store::path::Path;
+use serde::Serialize;
+
+use super::datafusion_utils::Expression;
+use super::transaction::PROTOCOL;
+use crate::delta_datafusion::{
+    register_store, DataFusionMixins, DeltaScanConfig, DeltaSessionContext, DeltaTableProvider,
+};
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::logstore::LogStoreRef;
+use crate::storage::ObjectStoreRef;
+use crate::table::builder::DeltaTableBuilder;
+use crate::table::state::DeltaTableState;
+use crate::DeltaTable;
+
+/// Default size after which a new file is started
+pub const DEFAULT_MAX_FILE_SIZE: usize = 256 * 1024 * 1024;
+
+/// Options of an Arrow IPC export
+#[derive(Debug, Clone)]
+pub struct IpcExportOptions {
+    /// Size in bytes after which a new file is started. Files may exceed it by one batch
+    pub max_file_size: usize,
+    /// Prefix of the file names
+    pub file_prefix: String,
+    /// Options of the object store at the destination
+    pub storage_options: HashMap<String, String>,
+}
+
+impl Default for IpcExportOptions {
+    fn default() -> Self {
+        Self {
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            file_prefix: "part".to_string(),
+            storage_options: HashMap::new(),
+        }
+    }
+}
+
+/// Export a snapshot to Arrow IPC files.
+/// See this module's documentation for more information
+pub struct ExportIpcBuilder {
+    /// Url of the destination directory
+    destination: String,
+    /// Export options
+    options: IpcExportOptions,
+    /// Only export rows matching the predicate
+    predicate: Option<Expression>,
+    /// Columns to export, all columns by default
+    columns: Option<Vec<String>>,
+    /// A snapshot of the table's state
+    snapshot: DeltaTableState,
+    /// Delta object store for handling data files
+    log_store: LogStoreRef,
+    /// Datafusion session state relevant for executing the input plan
+    state: Option<SessionState>,
+}
+
+/// Metrics for the Export IPC Operation
+#[derive(Default, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IpcExportMetrics {
+    /// Paths of the written files relative to the destination
+    pub files: Vec<String>,
+    /// Number of rows exported
+    pub num_rows: usize,
+    /// Total size of the written files in bytes
+    pub num_bytes: usize,
+}
+
+impl ExportIpcBuilder {
+    /// Create a new [`ExportIpcBuilder`]
+    pub fn new(
+        log_store: LogStoreRef,
+        snapshot: DeltaTableState,
+        destination: impl Into<String>,
+        options: IpcExportOptions,
+    ) -> Self {
+        Self {
+            destination: destination.into(),
+            options,
+            predicate: None,
+            columns: None,
+            snapshot,
+            log_store,
+            state: None,
+        }
+    }
+
+    /// Only export rows matching the predicate
+    pub fn with_predicate<E: Into<Expression>>(mut self, predicate: E) -> Self {
+        self.predicate = Some(predicate.into());
+        self
+    }
+
+    /// Only export the given columns
+    pub fn with_columns(mut self, columns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.columns = Some(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// The Datafusion session state to use
+    pub fn with_session_state(mut self, state: SessionState) -> Self {
+        self.state = Some(state);
+        self
+    }
+}
+
+/// Writes batches to a sequence of size bounded IPC files
+struct IpcFileSink {
+    store: ObjectStoreRef,
+    schema: ArrowSchemaRef,
+    options: IpcExportOptions,
+    writer: Option<FileWriter<Vec<u8>>>,
+    metrics: IpcExportMetrics,
+}
+
+impl IpcFileSink {
+    async fn write(&mut self, batch: &RecordBatch) -> DeltaResult<()> {
+        let writer = match &mut self.writer {
+            Some(writer) => writer,
+            None => self
+                .writer
+                .insert(FileWriter::try_new(Vec::new(), &self.schema)?),
+        };
+        writer.write(batch)?;
+        self.metrics.num_rows += batch.num_rows();
+        if writer.get_ref().len() >= self.options.max_file_size {
+            self.finish_file().await?;
+        }
+        Ok(())
+    }
+
+    async fn finish_file(&mut self) -> DeltaResult<()> {
+        let Some(mut writer) = self.writer.take() else {
+            return Ok(());
+        };
+        writer.finish()?;
+        let bytes = writer.into_inner()?;
+        let name = format!(
+            "{}-{:05}.arrow",
+            self.options.file_prefix,
+            self.metrics.files.len()
+        );
+        self.metrics.num_bytes += bytes.len();
+        self.store
+            .put(&Path::from(name.as_str()), Bytes::from(bytes))
+            .await?;
+        self.metrics.files.push(name);
+        Ok(())
+    }
+}
+
+impl std::future::IntoFuture for ExportIpcBuilder {
+    type Output = DeltaResult<(DeltaTable, IpcExportMetrics)>;
+    type IntoFuture = BoxFuture<'static, Self::Output>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        let this = self;
+
+        Box::pin(async move {
+            PROTOCOL.can_read_from(&this.snapshot.snapshot)?;
+            if this.options.max_file_size == 0 {
+                return Err(DeltaTableError::Generic(
+                    "The maximum file size of an IPC export must be positive".to_string(),
+                ));
+            }
+
+            let state = this.state.unwrap_or_else(|| {
+                let session: SessionContext = DeltaSessionContext::default().into();
+
+                // If a user provides their own their DF state then they must register the store themselves
+                register_store(this.log_store.clone(), session.runtime_env());
+
+                session.state()
+            });
+            let predicate = match this.predicate {
+                Some(Expression::DataFusion(expr)) => Some(expr),
+                Some(Expression::String(s)) => {
+                    Some(this.snapshot.parse_predicate_expression(s, &state)?)
+                }
+                None => None,
+            };
+
+            let ctx = SessionContext::new_with_state(state);
+            let provider = DeltaTableProvider::try_new(
+                this.snapshot.clone(),
+                this.log_store.clone(),
+                DeltaScanConfig::default(),
+            )?;
+            let mut df = ctx.read_table(Arc::new(provider))?;
+            if let Some(predicate) = predicate {
+                df = df.filter(predicate)?;
+            }
+            if let Some(columns) = &this.columns {
+                let columns = columns.iter().map(String::as_str).collect::<Vec<_>>();
+                df = df.select_columns(&columns)?;
+            }
+
+            let store = DeltaTableBuilder::from_uri(&this.destination)
+                .with_storage_options(this.options.storage_options.clone())
+                .build_storage()?
+                .object_store();
+            let mut sink = IpcFileSink {
+                store,
+                schema: Arc::new(ArrowSchema::from(df.schema())),
+                options: this.options,
+                writer: None,
+                metrics: IpcExportMetrics::default(),
+            };
+
+            let mut batches = df.execute_stream().await?;
+            while let Some(batch) = batches.next().await {
+                sink.write(&batch?).await?;
+            }
+            sink.finish_file().await?;
+
+            let table = DeltaTable::new_with_state(this.log_store, this.snapshot);
+            Ok((table, sink.metrics))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_ipc::reader::FileReader;
+
+    use super::*;
+    use crate::operations::DeltaOps;
+    use crate::writer::test_utils::{get_delta_schema, get_record_batch};
+
+    async fn setup_table() -> DeltaTable {
+        let table = DeltaOps::new_in_memory()
+            .create()
+            .with_columns(get_delta_schema().fields().clone())
+            .await
+            .unwrap();
+        DeltaOps(table)
+            .write(vec![get_record_batch(None, false)])
+            .await
+            .unwrap()
+    }
+
+    fn read_rows(dir: &std::path::Path, files: &[String]) -> usize {
+        files
+            .iter()
+            .map(|file| {
+                let reader =
+                    FileReader::try_new(std::fs::File::open(dir.join(file)).unwrap(), None)
+                        .unwrap();
+                reader.map(|batch| batch.unwrap().num_rows()).sum::<usize>()
+            })
+            .sum()
+    }
+
+    #[tokio::test]
+    async fn test_export_ipc() {
+        let table = setup_table().await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let (_, metrics) = DeltaOps(table)
+            .export_ipc(dir.path().to_str().unwrap(), IpcExportOptions::default())
+            .with_predicate("value > 5")
+            .with_columns(["id", "value"])
+            .await
+            .unwrap();
+        assert_eq!(metrics.files, vec!["part-00000.arrow".to_string()]);
+        assert_eq!(metrics.num_rows, 6);
+        assert_eq!(read_rows(dir.path(), &metrics.files), 6);
+
+        let reader = FileReader::try_new(
+            std::fs::File::open(dir.path().join(&metrics.files[0])).unwrap(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(reader.schema().fields().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_export_ipc_splits_files() {
+        let mut table = setup_table().await;
+        for _ in 0..2 {
+            table = DeltaOps(table)
+                .write(vec![get_record_batch(None, false)])
+                .await
+                .unwrap();
+        }
+        let dir = tempfile::tempdir().unwrap();
+
+        let options = IpcExportOptions {
+            max_file_size: 1,
+            file_prefix: "train".to_string(),
+            ..Default::default()
+        };
+        let (_, metrics) = DeltaOps(table)
+            .export_ipc(dir.path().to_str().unwrap(), options)
+            .await
+            .unwrap();
+        assert_eq!(metrics.files.len(), 3);
+        assert!(metrics.files.iter().all(|file| file.starts_with("train-")));
+        assert_eq!(read_rows(dir.path(), &metrics.files), 33);
+    }
+}
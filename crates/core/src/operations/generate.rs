@@ -0,0 +1,200 @@
+//! Generate a symlink format manifest for the current snapshot of a Delta table
+//!
+//! Query engines without native Delta support, such as BigQuery, Presto or Athena in their
+//! manifest based integrations, can read a Delta table through manifest files listing the
+//! data files of a snapshot. Manifests are written to the `_symlink_format_manifest` directory
+//! of the table, with one manifest per partition directory for partitioned tables. Manifests of
+//! partitions no longer present in the snapshot are removed.
+//!
+//! Generating a manifest does not create a commit, and must be repeated after every commit to
+//! keep the manifests in sync with the table.
+//!
+//! # Example
+//! ```rust ignore
+//! let table = open_table("../path/to/table")?;
+//! let (table, metrics) = DeltaOps(table).generate().await?;
+//! ````
+
+use std::collections::{BTreeMap, HashSet};
+
+use futures::future::BoxFuture;
+use futures::TryStreamExt;
+use object_store::path::Path;
+use serde::Serialize;
+
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::kernel::PartitionsExt;
+use crate::logstore::LogStoreRef;
+use crate::table::state::DeltaTableState;
+use crate::DeltaTable;
+
+/// Directory, relative to the table root, manifests are written to
+pub const MANIFEST_DIR: &str = "_symlink_format_manifest";
+
+const MANIFEST_FILE_NAME: &str = "manifest";
+
+/// Errors that can occur while generating manifests
+#[derive(thiserror::Error, Debug)]
+enum GenerateError {
+    #[error("Cannot generate manifests for file {0} with a deletion vector, since manifests cannot express deleted rows")]
+    DeletionVector(String),
+}
+
+impl From<GenerateError> for DeltaTableError {
+    fn from(err: GenerateError) -> Self {
+        DeltaTableError::GenericError {
+            source: Box::new(err),
+        }
+    }
+}
+
+/// Generate a symlink format manifest for the table.
+/// See this module's documentation for more information
+#[derive(Debug)]
+pub struct GenerateBuilder {
+    /// A snapshot of the table to generate the manifest for
+    snapshot: DeltaTableState,
+    /// Delta object store for handling data files
+    log_store: LogStoreRef,
+}
+
+/// Details of the generate operation
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateMetrics {
+    /// Number of manifest files written
+    pub num_manifests_written: usize,
+    /// Number of stale manifest files removed
+    pub num_manifests_removed: usize,
+    /// Number of data files listed in the manifests
+    pub num_files: usize,
+}
+
+impl GenerateBuilder {
+    /// Create a new [`GenerateBuilder`]
+    pub fn new(log_store: LogStoreRef, snapshot: DeltaTableState) -> Self {
+        Self {
+            snapshot,
+            log_store,
+        }
+    }
+}
+
+/// The manifest contents of the snapshot, keyed by manifest path
+fn manifest_contents(
+    log_store: &LogStoreRef,
+    snapshot: &DeltaTableState,
+) -> DeltaResult<BTreeMap<Path, Vec<String>>> {
+    let mut manifests: BTreeMap<Path, Vec<String>> = BTreeMap::new();
+    for file in snapshot.log_data()? {
+        if file.deletion_vector().is_some() {
+            return Err(GenerateError::DeletionVector(file.path().to_string()).into());
+        }
+        let partition_path = file.partition_values()?.hive_partition_path();
+        let manifest_path = if partition_path.is_empty() {
+            format!("{MANIFEST_DIR}/{MANIFEST_FILE_NAME}")
+        } else {
+            format!("{MANIFEST_DIR}/{partition_path}/{MANIFEST_FILE_NAME}")
+        };
+        let path = file.path();
+        let uri = if path.contains("://") {
+            path.to_string()
+        } else {
+            log_store.to_uri(&file.object_store_path())
+        };
+        manifests
+            .entry(Path::parse(manifest_path)?)
+            .or_default()
+            .push(uri);
+    }
+    Ok(manifests)
+}
+
+/// Write the manifests for `snapshot` and remove manifests of partitions no longer present
+pub(crate) async fn write_manifests(
+    log_store: &LogStoreRef,
+    snapshot: &DeltaTableState,
+) -> DeltaResult<GenerateMetrics> {
+    let manifests = manifest_contents(log_store, snapshot)?;
+    let store = log_store.object_store();
+
+    let mut metrics = GenerateMetrics::default();
+    for (path, files) in &manifests {
+        let mut content = files.join("\n");
+        content.push('\n');
+        store.put(path, content.into_bytes().into()).await?;
+        metrics.num_manifests_written += 1;
+        metrics.num_files += files.len();
+    }
+
+    let current = manifests.keys().collect::<HashSet<_>>();
+    let stale = store
+        .list(Some(&Path::from(MANIFEST_DIR)))
+        .try_filter(|meta| futures::future::ready(!current.contains(&meta.location)))
+        .try_collect::<Vec<_>>()
+        .await?;
+    for meta in stale {
+        store.delete(&meta.location).await?;
+        metrics.num_manifests_removed += 1;
+    }
+    Ok(metrics)
+}
+
+impl std::future::IntoFuture for GenerateBuilder {
+    type Output = DeltaResult<(DeltaTable, GenerateMetrics)>;
+    type IntoFuture = BoxFuture<'static, Self::Output>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        let this = self;
+
+        Box::pin(async move {
+            let metrics = write_manifests(&this.log_store, &this.snapshot).await?;
+            Ok((
+                DeltaTable::new_with_state(this.log_store, this.snapshot),
+                metrics,
+            ))
+        })
+    }
+}
+
+#[cfg(all(test, feature = "datafusion"))]
+mod tests {
+    use super::*;
+    use crate::operations::DeltaOps;
+    use crate::writer::test_utils::{get_delta_schema, get_record_batch};
+
+    #[tokio::test]
+    async fn test_generate_partitioned() {
+        let table = DeltaOps::new_in_memory()
+            .create()
+            .with_columns(get_delta_schema().fields().clone())
+            .with_partition_columns(["modified"])
+            .await
+            .unwrap();
+        let table = DeltaOps(table)
+            .write(vec![get_record_batch(None, false)])
+            .await
+            .unwrap();
+        let files = table.get_files_count().unwrap();
+
+        let (table, metrics) = DeltaOps(table).generate().await.unwrap();
+        assert_eq!(metrics.num_files, files);
+        assert_eq!(metrics.num_manifests_written, 2);
+        assert_eq!(metrics.num_manifests_removed, 0);
+
+        let store = table.object_store();
+        let manifest = store
+            .get(&Path::from(format!(
+                "{MANIFEST_DIR}/modified=2021-02-01/{MANIFEST_FILE_NAME}"
+            )))
+            .await
+            .unwrap()
+            .bytes()
+            .await
+            .unwrap();
+        let manifest = String::from_utf8(manifest.to_vec()).unwrap();
+        assert!(manifest
+            .lines()
+            .all(|line| line.starts_with("memory:///modified=2021-02-01/")));
+    }
+}
@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use datafusion::datasource::TableProvider;
 use datafusion::execution::context::{SessionContext, TaskContext};
@@ -6,6 +7,10 @@ use datafusion::physical_plan::coalesce_partitions::CoalescePartitionsExec;
 use datafusion::physical_plan::{ExecutionPlan, SendableRecordBatchStream};
 use futures::future::BoxFuture;
 
+use super::cancellation::{
+    cancellable_stream, check_cancelled, CancellationToken, OperationTimeout,
+};
+use super::progress::{tracked_stream, ProgressListenerRef, ProgressTracker};
 use super::transaction::PROTOCOL;
 use crate::delta_datafusion::DataFusionMixins;
 use crate::errors::{DeltaResult, DeltaTableError};
@@ -21,6 +26,12 @@ pub struct LoadBuilder {
     log_store: LogStoreRef,
     /// A sub-selection of columns to be loaded
     columns: Option<Vec<String>>,
+    /// Token to cancel loading the table
+    cancellation_token: Option<CancellationToken>,
+    /// Listener notified of the rows loaded
+    progress_listener: Option<ProgressListenerRef>,
+    /// Time after which to end the returned stream
+    timeout: Option<Duration>,
 }
 
 impl LoadBuilder {
@@ -30,6 +41,9 @@ impl LoadBuilder {
             snapshot,
             log_store,
             columns: None,
+            cancellation_token: None,
+            progress_listener: None,
+            timeout: None,
         }
     }
 
@@ -38,6 +52,25 @@ impl LoadBuilder {
         self.columns = Some(columns.into_iter().map(|s| s.into()).collect());
         self
     }
+
+    /// Cancel the load, and end the returned stream, once the token is cancelled
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Report the rows loaded to the listener while the returned stream is consumed
+    pub fn with_progress_listener(mut self, listener: ProgressListenerRef) -> Self {
+        self.progress_listener = Some(listener);
+        self
+    }
+
+    /// End the returned stream with [`DeltaTableError::Timeout`] once the timeout has elapsed,
+    /// counted from when the load starts
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 }
 
 impl std::future::IntoFuture for LoadBuilder {
@@ -49,6 +82,8 @@ impl std::future::IntoFuture for LoadBuilder {
 
         Box::pin(async move {
             PROTOCOL.can_read_from(&this.snapshot.snapshot)?;
+            let (timeout, token) =
+                OperationTimeout::maybe_start(this.timeout, this.cancellation_token);
 
             let table = DeltaTable::new_with_state(this.log_store, this.snapshot);
             let schema = table.snapshot()?.arrow_schema()?;
@@ -67,6 +102,7 @@ impl std::future::IntoFuture for LoadBuilder {
                 })
                 .transpose()?;
 
+            check_cancelled(token.as_ref())?;
             let ctx = SessionContext::new();
             let scan_plan = table
                 .scan(&ctx.state(), projection.as_ref(), &[], None)
@@ -74,6 +110,21 @@ impl std::future::IntoFuture for LoadBuilder {
             let plan = CoalescePartitionsExec::new(scan_plan);
             let task_ctx = Arc::new(TaskContext::from(&ctx.state()));
             let stream = plan.execute(0, task_ctx)?;
+            let (mut files, mut bytes, mut rows) = (0, 0, Some(0));
+            for file in table.snapshot()?.log_data()? {
+                files += 1;
+                bytes += file.size() as u64;
+                rows = rows.zip(file.num_records()).map(|(a, b)| a + b as u64);
+            }
+            let tracker = ProgressTracker::new(this.progress_listener, "LOAD")
+                .with_totals(files, bytes)
+                .with_rows_total(rows);
+            let stream = tracked_stream(stream, tracker.clone());
+            let stream = match (timeout, token) {
+                (Some(timeout), _) => timeout.stream(stream, tracker),
+                (None, Some(token)) => cancellable_stream(stream, token),
+                (None, None) => stream,
+            };
 
             Ok((table, stream))
         })
@@ -82,10 +133,14 @@ impl std::future::IntoFuture for LoadBuilder {
 
 #[cfg(test)]
 mod tests {
+    use crate::operations::cancellation::CancellationToken;
+    use crate::operations::progress::RecordingListener;
     use crate::operations::{collect_sendable_stream, DeltaOps};
     use crate::writer::test_utils::{get_record_batch, TestResult};
     use crate::DeltaTableBuilder;
+    use crate::DeltaTableError;
     use datafusion::assert_batches_sorted_eq;
+    use std::sync::Arc;
 
     #[tokio::test]
     async fn test_load_local() -> TestResult {
@@ -172,4 +227,50 @@ mod tests {
         assert_batches_sorted_eq!(&expected, &data);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_load_cancelled() -> TestResult {
+        let batch = get_record_batch(None, false);
+        let table = DeltaOps::new_in_memory().write(vec![batch.clone()]).await?;
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let result = DeltaOps(table.clone())
+            .load()
+            .with_cancellation_token(token)
+            .await;
+        assert!(matches!(result, Err(DeltaTableError::Cancelled)));
+
+        let token = CancellationToken::new();
+        let (_table, stream) = DeltaOps(table)
+            .load()
+            .with_cancellation_token(token.clone())
+            .await?;
+        token.cancel();
+        let result = collect_sendable_stream(stream).await;
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_progress() -> TestResult {
+        let batch = get_record_batch(None, false);
+        let table = DeltaOps::new_in_memory().write(vec![batch.clone()]).await?;
+
+        let listener = Arc::new(RecordingListener::default());
+        let (_table, stream) = DeltaOps(table)
+            .load()
+            .with_progress_listener(listener.clone())
+            .await?;
+        collect_sendable_stream(stream).await?;
+
+        let updates = listener.updates.lock().unwrap();
+        let last = updates.last().unwrap();
+        assert!(last.finished);
+        assert_eq!(last.operation, "LOAD");
+        assert_eq!(last.rows_processed, 11);
+        assert_eq!(last.rows_total, Some(11));
+        assert_eq!(last.files_total, Some(1));
+        Ok(())
+    }
 }
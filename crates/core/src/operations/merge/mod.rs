@@ -30,7 +30,7 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use datafusion::datasource::provider_as_source;
@@ -61,7 +61,11 @@ use serde::Serialize;
 
 use self::barrier::{MergeBarrier, MergeBarrierExec};
 
-use super::datafusion_utils::{into_expr, maybe_into_expr, Expression};
+use super::cancellation::{
+    check_cancelled, delete_uncommitted_files, run_cancellable, CancellationToken, OperationTimeout,
+};
+use super::datafusion_utils::{drain_plan, into_expr, maybe_into_expr, Expression};
+use super::progress::ProgressTracker;
 use super::transaction::{CommitProperties, PROTOCOL};
 use crate::delta_datafusion::expr::{fmt_expr_to_sql, parse_predicate_expression};
 use crate::delta_datafusion::logical::MetricObserver;
@@ -76,6 +80,7 @@ use crate::operations::merge::barrier::find_barrier_node;
 use crate::operations::transaction::CommitBuilder;
 use crate::operations::write::write_execution_plan;
 use crate::protocol::{DeltaOperation, MergePredicate};
+use crate::storage::deadline::deadline_passed;
 use crate::table::state::DeltaTableState;
 use crate::{DeltaResult, DeltaTable, DeltaTableError};
 
@@ -131,6 +136,12 @@ pub struct MergeBuilder {
     /// safe_cast determines how data types that do not match the underlying table are handled
     /// By default an error is returned
     safe_cast: bool,
+    /// Don't write files or commit. Just determine which files and rows would be affected
+    dry_run: bool,
+    /// Token to cancel the merge before it commits
+    cancellation_token: Option<CancellationToken>,
+    /// Time after which to cancel the merge
+    timeout: Option<Duration>,
 }
 
 impl MergeBuilder {
@@ -156,6 +167,9 @@ impl MergeBuilder {
             not_match_operations: Vec::new(),
             not_match_source_operations: Vec::new(),
             safe_cast: false,
+            dry_run: false,
+            cancellation_token: None,
+            timeout: None,
         }
     }
 
@@ -375,6 +389,28 @@ impl MergeBuilder {
         self.safe_cast = safe_cast;
         self
     }
+
+    /// Only determine which files and rows would be affected, without modifying the table.
+    ///
+    /// The source and target are still joined, but no data files are written.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Cancel the merge once the token is cancelled. Files written by a cancelled merge are
+    /// deleted and nothing is committed
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Cancel the merge once the timeout has elapsed, and fail storage requests issued after
+    /// it. Like a cancelled merge, a timed out merge commits nothing
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 }
 
 #[derive(Default)]
@@ -570,6 +606,12 @@ pub struct MergeMetrics {
     pub scan_time_ms: u64,
     /// Time taken to rewrite the matched files
     pub rewrite_time_ms: u64,
+    /// Was this a dry run
+    #[serde(skip)]
+    pub dry_run: bool,
+    /// Files that would be rewritten, only reported for dry runs
+    #[serde(skip)]
+    pub files_to_remove: Vec<String>,
 }
 
 struct MergeMetricExtensionPlanner {}
@@ -948,7 +990,10 @@ async fn execute(
     match_operations: Vec<MergeOperationConfig>,
     not_match_target_operations: Vec<MergeOperationConfig>,
     not_match_source_operations: Vec<MergeOperationConfig>,
+    dry_run: bool,
+    cancellation_token: Option<CancellationToken>,
 ) -> DeltaResult<((Vec<Action>, i64, Option<DeltaOperation>), MergeMetrics)> {
+    check_cancelled(cancellation_token.as_ref())?;
     let mut metrics = MergeMetrics::default();
     let exec_start = Instant::now();
 
@@ -1366,19 +1411,25 @@ async fn execute(
     let table_partition_cols = current_metadata.partition_columns.clone();
 
     let rewrite_start = Instant::now();
-    let add_actions = write_execution_plan(
-        Some(snapshot),
-        state.clone(),
-        write,
-        table_partition_cols.clone(),
-        log_store.object_store(),
-        Some(snapshot.table_config().target_file_size() as usize),
-        None,
-        writer_properties,
-        safe_cast,
-        None,
-    )
-    .await?;
+    let add_actions = if dry_run {
+        run_cancellable(cancellation_token.as_ref(), drain_plan(write, &state)).await?;
+        Vec::new()
+    } else {
+        write_execution_plan(
+            Some(snapshot),
+            state.clone(),
+            write,
+            table_partition_cols.clone(),
+            log_store.object_store(),
+            Some(snapshot.table_config().target_file_size() as usize),
+            None,
+            writer_properties,
+            safe_cast,
+            None,
+            cancellation_token.as_ref(),
+        )
+        .await?
+    };
 
     metrics.rewrite_time_ms = Instant::now().duration_since(rewrite_start).as_millis() as u64;
 
@@ -1393,9 +1444,12 @@ async fn execute(
 
     {
         let lock = survivors.lock().unwrap();
-        for action in snapshot.log_data() {
+        for action in snapshot.log_data()? {
             if lock.contains(action.path().as_ref()) {
                 metrics.num_target_files_removed += 1;
+                if dry_run {
+                    metrics.files_to_remove.push(action.path().to_string());
+                }
                 actions.push(action.remove_action(true).into());
             }
         }
@@ -1418,6 +1472,11 @@ async fn execute(
 
     metrics.execution_time_ms = Instant::now().duration_since(exec_start).as_millis() as u64;
 
+    if dry_run {
+        metrics.dry_run = true;
+        return Ok(((Vec::new(), snapshot.version(), None), metrics));
+    }
+
     let app_metadata = &mut commit_properties.app_metadata;
     app_metadata.insert("readVersion".to_owned(), snapshot.version().into());
     if let Ok(map) = serde_json::to_value(&metrics) {
@@ -1436,6 +1495,11 @@ async fn execute(
         return Ok(((actions, snapshot.version(), None), metrics));
     }
 
+    if check_cancelled(cancellation_token.as_ref()).is_err() || deadline_passed() {
+        delete_uncommitted_files(&log_store.object_store(), &add_actions).await;
+        return Err(DeltaTableError::Cancelled);
+    }
+
     let commit = CommitBuilder::from(commit_properties)
         .with_actions(actions)
         .build(Some(snapshot), log_store.clone(), operation)?
@@ -1478,6 +1542,9 @@ impl std::future::IntoFuture for MergeBuilder {
 
         Box::pin(async move {
             PROTOCOL.can_write_to(&this.snapshot.snapshot)?;
+            this.commit_properties
+                .check_idempotency(&this.log_store, this.snapshot.version())
+                .await?;
 
             let state = this.state.unwrap_or_else(|| {
                 let config: SessionConfig = DeltaSessionConfig::default().into();
@@ -1489,7 +1556,10 @@ impl std::future::IntoFuture for MergeBuilder {
                 session.state()
             });
 
-            let ((actions, version, operation), metrics) = execute(
+            let (timeout, token) =
+                OperationTimeout::maybe_start(this.timeout, this.cancellation_token);
+            let progress = ProgressTracker::new(None, "MERGE");
+            let merge = execute(
                 this.predicate,
                 this.source,
                 this.log_store.clone(),
@@ -1503,8 +1573,11 @@ impl std::future::IntoFuture for MergeBuilder {
                 this.match_operations,
                 this.not_match_operations,
                 this.not_match_source_operations,
-            )
-            .await?;
+                this.dry_run,
+                token,
+            );
+            let ((actions, version, operation), metrics) =
+                OperationTimeout::maybe_run(timeout, &progress, merge).await?;
 
             if let Some(op) = &operation {
                 this.snapshot.merge(actions, op, version)?;
@@ -1521,6 +1594,7 @@ mod tests {
     use crate::kernel::DataType;
     use crate::kernel::PrimitiveType;
     use crate::kernel::StructField;
+    use crate::operations::cancellation::CancellationToken;
     use crate::operations::merge::generalize_filter;
     use crate::operations::merge::try_construct_early_filter;
     use crate::operations::DeltaOps;
@@ -1531,6 +1605,7 @@ mod tests {
     use crate::writer::test_utils::setup_table_with_configuration;
     use crate::DeltaConfigKey;
     use crate::DeltaTable;
+    use crate::DeltaTableError;
     use arrow::datatypes::Schema as ArrowSchema;
     use arrow::record_batch::RecordBatch;
     use arrow_schema::DataType as ArrowDataType;
@@ -1634,14 +1709,14 @@ mod tests {
 
         let table = write_data(table, &schema).await;
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 1);
+        assert_eq!(table.get_files_count().unwrap(), 1);
 
         (table, merge_source(schema))
     }
 
     async fn assert_merge(table: DeltaTable, metrics: MergeMetrics) {
         assert_eq!(table.version(), 2);
-        assert!(table.get_files_count() >= 1);
+        assert!(table.get_files_count().unwrap() >= 1);
         assert!(metrics.num_target_files_added >= 1);
         assert_eq!(metrics.num_target_files_removed, 1);
         assert_eq!(metrics.num_target_rows_copied, 1);
@@ -1716,6 +1791,97 @@ mod tests {
         assert_merge(table, metrics).await;
     }
 
+    #[tokio::test]
+    async fn test_merge_batches() {
+        let (table, source) = setup().await;
+        let batches = source.collect().await.unwrap();
+
+        let (table, metrics) = DeltaOps(table)
+            .merge_batches(batches, col("target.id").eq(col("source.id")))
+            .unwrap()
+            .with_source_alias("source")
+            .with_target_alias("target")
+            .when_matched_update(|update| {
+                update
+                    .update("value", col("source.value"))
+                    .update("modified", col("source.modified"))
+            })
+            .unwrap()
+            .when_not_matched_by_source_update(|update| {
+                update
+                    .predicate(col("target.value").eq(lit(1)))
+                    .update("value", col("target.value") + lit(1))
+            })
+            .unwrap()
+            .when_not_matched_insert(|insert| {
+                insert
+                    .set("id", col("source.id"))
+                    .set("value", col("source.value"))
+                    .set("modified", col("source.modified"))
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+        assert_merge(table, metrics).await;
+    }
+
+    #[tokio::test]
+    async fn test_merge_dry_run() {
+        let (table, source) = setup().await;
+        let files = table.get_files_iter().unwrap().collect::<Vec<_>>();
+
+        let (table, metrics) = DeltaOps(table)
+            .merge(source, col("target.id").eq(col("source.id")))
+            .with_source_alias("source")
+            .with_target_alias("target")
+            .when_matched_update(|update| update.update("value", col("source.value")))
+            .unwrap()
+            .when_not_matched_insert(|insert| {
+                insert
+                    .set("id", col("source.id"))
+                    .set("value", col("source.value"))
+                    .set("modified", col("source.modified"))
+            })
+            .unwrap()
+            .with_dry_run(true)
+            .await
+            .unwrap();
+
+        assert_eq!(table.version(), 1);
+        assert_eq!(table.get_files_iter().unwrap().collect::<Vec<_>>(), files);
+        assert!(metrics.dry_run);
+        assert_eq!(metrics.num_target_files_added, 0);
+        assert_eq!(metrics.num_target_files_removed, 1);
+        assert_eq!(metrics.files_to_remove, vec![files[0].to_string()]);
+        assert_eq!(metrics.num_target_rows_updated, 2);
+        assert_eq!(metrics.num_target_rows_inserted, 1);
+        assert_eq!(metrics.num_target_rows_copied, 2);
+        assert_eq!(metrics.num_source_rows, 3);
+    }
+
+    #[tokio::test]
+    async fn test_merge_cancelled() {
+        let (mut table, source) = setup().await;
+        let files = table.get_files_iter().unwrap().collect::<Vec<_>>();
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let result = DeltaOps(table.clone())
+            .merge(source, col("target.id").eq(col("source.id")))
+            .with_source_alias("source")
+            .with_target_alias("target")
+            .when_matched_update(|update| update.update("value", col("source.value")))
+            .unwrap()
+            .with_cancellation_token(token)
+            .await;
+        assert!(matches!(result, Err(DeltaTableError::Cancelled)));
+
+        table.update().await.unwrap();
+        assert_eq!(table.version(), 1);
+        assert_eq!(table.get_files_iter().unwrap().collect::<Vec<_>>(), files);
+    }
+
     #[tokio::test]
     async fn test_merge_str() {
         // Validate that users can use string predicates
@@ -1889,7 +2055,7 @@ mod tests {
 
         let table = write_data(table, &schema).await;
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 2);
+        assert_eq!(table.get_files_count().unwrap(), 2);
 
         let ctx = SessionContext::new();
         let batch = RecordBatch::try_new(
@@ -1945,7 +2111,7 @@ mod tests {
             .unwrap();
 
         assert_eq!(table.version(), 2);
-        assert!(table.get_files_count() >= 3);
+        assert!(table.get_files_count().unwrap() >= 3);
         assert!(metrics.num_target_files_added >= 3);
         assert_eq!(metrics.num_target_files_removed, 2);
         assert_eq!(metrics.num_target_rows_copied, 1);
@@ -1979,7 +2145,7 @@ mod tests {
 
         let table = write_data(table, &schema).await;
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 4);
+        assert_eq!(table.get_files_count().unwrap(), 4);
 
         let ctx = SessionContext::new();
         let batch = RecordBatch::try_new(
@@ -2018,7 +2184,7 @@ mod tests {
             .unwrap();
 
         assert_eq!(table.version(), 2);
-        assert!(table.get_files_count() >= 3);
+        assert!(table.get_files_count().unwrap() >= 3);
         assert_eq!(metrics.num_target_files_added, 3);
         assert_eq!(metrics.num_target_files_removed, 2);
         assert_eq!(metrics.num_target_rows_copied, 0);
@@ -2052,7 +2218,7 @@ mod tests {
 
         let table = write_data(table, &schema).await;
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 2);
+        assert_eq!(table.get_files_count().unwrap(), 2);
 
         let ctx = SessionContext::new();
         let batch = RecordBatch::try_new(
@@ -2080,7 +2246,7 @@ mod tests {
             .unwrap();
 
         assert_eq!(table.version(), 2);
-        assert!(table.get_files_count() >= 2);
+        assert!(table.get_files_count().unwrap() >= 2);
         assert_eq!(metrics.num_target_files_added, 2);
         assert_eq!(metrics.num_target_files_removed, 2);
         assert_eq!(metrics.num_target_rows_copied, 2);
@@ -2121,7 +2287,7 @@ mod tests {
 
         let table = write_data(table, &schema).await;
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 2);
+        assert_eq!(table.get_files_count().unwrap(), 2);
 
         let ctx = SessionContext::new();
         let batch = RecordBatch::try_new(
@@ -2149,7 +2315,7 @@ mod tests {
             .unwrap();
 
         assert_eq!(table.version(), 2);
-        assert!(table.get_files_count() >= 2);
+        assert!(table.get_files_count().unwrap() >= 2);
         assert_eq!(metrics.num_target_files_added, 1);
         assert_eq!(metrics.num_target_files_removed, 1);
         assert_eq!(metrics.num_target_rows_copied, 1);
@@ -2190,7 +2356,7 @@ mod tests {
 
         let table = write_data(table, &schema).await;
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 2);
+        assert_eq!(table.get_files_count().unwrap(), 2);
 
         let ctx = SessionContext::new();
         let batch = RecordBatch::try_new(
@@ -2218,7 +2384,7 @@ mod tests {
             .unwrap();
 
         assert_eq!(table.version(), 2);
-        assert_eq!(table.get_files_count(), 2);
+        assert_eq!(table.get_files_count().unwrap(), 2);
         assert_eq!(metrics.num_target_files_added, 2);
         assert_eq!(metrics.num_target_files_removed, 2);
         assert_eq!(metrics.num_target_rows_copied, 2);
@@ -2253,7 +2419,7 @@ mod tests {
 
         let table = write_data(table, &schema).await;
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 2);
+        assert_eq!(table.get_files_count().unwrap(), 2);
 
         let ctx = SessionContext::new();
         let batch = RecordBatch::try_new(
@@ -2320,7 +2486,7 @@ mod tests {
         let table = setup_table(Some(vec!["modified"])).await;
 
         assert_eq!(table.version(), 0);
-        assert_eq!(table.get_files_count(), 0);
+        assert_eq!(table.get_files_count().unwrap(), 0);
 
         let ctx = SessionContext::new();
         let batch = RecordBatch::try_new(
@@ -2364,7 +2530,7 @@ mod tests {
             .unwrap();
 
         assert_eq!(table.version(), 1);
-        assert!(table.get_files_count() >= 2);
+        assert!(table.get_files_count().unwrap() >= 2);
         assert!(metrics.num_target_files_added >= 2);
         assert_eq!(metrics.num_target_files_removed, 0);
         assert_eq!(metrics.num_target_rows_copied, 0);
@@ -2437,7 +2603,7 @@ mod tests {
 
         let table = write_data(table, &arrow_schema).await;
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 1);
+        assert_eq!(table.get_files_count().unwrap(), 1);
 
         let (table, _metrics) = DeltaOps(table)
             .merge(source, "target.Id = source.Id")
@@ -2647,7 +2813,7 @@ mod tests {
         let table = setup_table(Some(vec!["id"])).await;
 
         assert_eq!(table.version(), 0);
-        assert_eq!(table.get_files_count(), 0);
+        assert_eq!(table.get_files_count().unwrap(), 0);
 
         let ctx = SessionContext::new();
         let batch = RecordBatch::try_new(
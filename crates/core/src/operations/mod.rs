@@ -7,42 +7,78 @@
 //! with a [data stream][datafusion::physical_plan::SendableRecordBatchStream],
 //! if the operation returns data as well.
 
+use self::analyze::AnalyzeBuilder;
+use self::audit::AuditExportBuilder;
+use self::compact_deletion_vectors::CompactDeletionVectorsBuilder;
 use self::create::CreateBuilder;
 use self::filesystem_check::FileSystemCheckBuilder;
+use self::generate::GenerateBuilder;
+use self::remove_orphans::RemoveOrphansBuilder;
 use self::vacuum::VacuumBuilder;
 use crate::errors::{DeltaResult, DeltaTableError};
 use crate::table::builder::DeltaTableBuilder;
 use crate::DeltaTable;
 use std::collections::HashMap;
+use std::ops::RangeBounds;
 
+pub mod analyze;
+pub mod audit;
+pub mod cancellation;
 pub mod cast;
+pub mod compact_deletion_vectors;
 pub mod convert_to_delta;
 pub mod create;
 pub mod drop_constraints;
+pub mod estimate;
 pub mod filesystem_check;
+pub mod generate;
 pub mod optimize;
+pub mod partition_by;
+pub mod progress;
+pub mod remove_orphans;
 pub mod restore;
 pub mod transaction;
 pub mod vacuum;
 
 #[cfg(feature = "datafusion")]
 use self::{
-    constraints::ConstraintBuilder, datafusion_utils::Expression, delete::DeleteBuilder,
-    drop_constraints::DropConstraintBuilder, load::LoadBuilder, merge::MergeBuilder,
-    update::UpdateBuilder, write::WriteBuilder,
+    aggregate_sync::AggregateSyncBuilder,
+    constraints::ConstraintBuilder,
+    datafusion_utils::Expression,
+    delete::DeleteBuilder,
+    delete_keys::DeleteKeysBuilder,
+    drop_constraints::DropConstraintBuilder,
+    expire::ExpireRowsBuilder,
+    export_ipc::{ExportIpcBuilder, IpcExportOptions},
+    load::LoadBuilder,
+    merge::MergeBuilder,
+    update::UpdateBuilder,
+    write::WriteBuilder,
 };
 #[cfg(feature = "datafusion")]
 pub use ::datafusion::physical_plan::common::collect as collect_sendable_stream;
 #[cfg(feature = "datafusion")]
 use arrow::record_batch::RecordBatch;
+#[cfg(feature = "datafusion")]
+use datafusion_common::ScalarValue;
 use optimize::OptimizeBuilder;
 use restore::RestoreBuilder;
 
+#[cfg(feature = "datafusion")]
+pub mod aggregate_sync;
 #[cfg(feature = "datafusion")]
 pub mod constraints;
 #[cfg(feature = "datafusion")]
+mod dedup;
+#[cfg(feature = "datafusion")]
 pub mod delete;
 #[cfg(feature = "datafusion")]
+pub mod delete_keys;
+#[cfg(feature = "datafusion")]
+pub mod expire;
+#[cfg(feature = "datafusion")]
+pub mod export_ipc;
+#[cfg(feature = "datafusion")]
 mod load;
 #[cfg(feature = "datafusion")]
 pub mod merge;
@@ -147,18 +183,48 @@ impl DeltaOps {
         VacuumBuilder::new(self.0.log_store, self.0.state.unwrap())
     }
 
+    /// Delete data files older than `older_than` which were never referenced by the log
+    #[must_use]
+    pub fn remove_orphans(self, older_than: chrono::Duration) -> RemoveOrphansBuilder {
+        RemoveOrphansBuilder::new(self.0.log_store, self.0.state.unwrap(), older_than)
+    }
+
     /// Audit active files with files present on the filesystem
     #[must_use]
     pub fn filesystem_check(self) -> FileSystemCheckBuilder {
         FileSystemCheckBuilder::new(self.0.log_store, self.0.state.unwrap())
     }
 
+    /// Generate a symlink format manifest listing the active files of the table
+    #[must_use]
+    pub fn generate(self) -> GenerateBuilder {
+        GenerateBuilder::new(self.0.log_store, self.0.state.unwrap())
+    }
+
+    /// Export the actions committed in a range of versions as an audit log
+    #[must_use]
+    pub fn export_audit(self, versions: impl RangeBounds<i64>) -> AuditExportBuilder {
+        AuditExportBuilder::new(self.0.log_store, self.0.state.unwrap()).with_versions(versions)
+    }
+
     /// Audit active files with files present on the filesystem
     #[must_use]
     pub fn optimize<'a>(self) -> OptimizeBuilder<'a> {
         OptimizeBuilder::new(self.0.log_store, self.0.state.unwrap())
     }
 
+    /// Compute an approximate statistics summary of the table
+    #[must_use]
+    pub fn analyze(self) -> AnalyzeBuilder {
+        AnalyzeBuilder::new(self.0.log_store, self.0.state.unwrap())
+    }
+
+    /// Rewrite files whose deletion vectors mark many of their rows as deleted
+    #[must_use]
+    pub fn compact_deletion_vectors(self) -> CompactDeletionVectorsBuilder {
+        CompactDeletionVectorsBuilder::new(self.0.log_store, self.0.state.unwrap())
+    }
+
     /// Delete data from Delta table
     #[cfg(feature = "datafusion")]
     #[must_use]
@@ -166,6 +232,47 @@ impl DeltaOps {
         DeleteBuilder::new(self.0.log_store, self.0.state.unwrap())
     }
 
+    /// Delete the records with a key in `keys` from Delta table
+    #[cfg(feature = "datafusion")]
+    #[must_use]
+    pub fn delete_keys(
+        self,
+        column: impl Into<String>,
+        keys: impl IntoIterator<Item = impl Into<ScalarValue>>,
+    ) -> DeleteKeysBuilder {
+        DeleteKeysBuilder::new(self.0.log_store, self.0.state.unwrap(), column, keys)
+    }
+
+    /// Delete rows whose time to live, configured in the table properties, has expired
+    #[cfg(feature = "datafusion")]
+    #[must_use]
+    pub fn expire_rows(self) -> ExpireRowsBuilder {
+        ExpireRowsBuilder::new(self.0.log_store, self.0.state.unwrap())
+    }
+
+    /// Incrementally maintain an aggregate of `source` in this table
+    #[cfg(feature = "datafusion")]
+    #[must_use]
+    pub fn aggregate_sync(self, source: DeltaTable) -> AggregateSyncBuilder {
+        AggregateSyncBuilder::new(self.0.log_store, self.0.state, source)
+    }
+
+    /// Export the current snapshot to Arrow IPC files at `destination`
+    #[cfg(feature = "datafusion")]
+    #[must_use]
+    pub fn export_ipc(
+        self,
+        destination: impl Into<String>,
+        options: IpcExportOptions,
+    ) -> ExportIpcBuilder {
+        ExportIpcBuilder::new(
+            self.0.log_store,
+            self.0.state.unwrap(),
+            destination,
+            options,
+        )
+    }
+
     /// Update data from Delta table
     #[cfg(feature = "datafusion")]
     #[must_use]
@@ -195,6 +302,23 @@ impl DeltaOps {
         )
     }
 
+    /// Merge record batches into the Delta table, see [`merge`](Self::merge)
+    #[cfg(feature = "datafusion")]
+    pub fn merge_batches<E: Into<Expression>>(
+        self,
+        source: impl IntoIterator<Item = RecordBatch>,
+        predicate: E,
+    ) -> DeltaResult<MergeBuilder> {
+        let source = source.into_iter().collect::<Vec<_>>();
+        let schema = source
+            .first()
+            .ok_or_else(|| DeltaTableError::Generic("No record batches to merge".to_string()))?
+            .schema();
+        let batch = arrow::compute::concat_batches(&schema, &source)?;
+        let source = datafusion::prelude::SessionContext::new().read_batch(batch)?;
+        Ok(self.merge(source, predicate))
+    }
+
     /// Add a check constraint to a table
     #[cfg(feature = "datafusion")]
     #[must_use]
@@ -230,9 +354,13 @@ impl AsRef<DeltaTable> for DeltaOps {
 
 #[cfg(feature = "datafusion")]
 mod datafusion_utils {
+    use std::sync::Arc;
+
     use datafusion::execution::context::SessionState;
+    use datafusion::physical_plan::{execute_stream, ExecutionPlan};
     use datafusion_common::DFSchema;
     use datafusion_expr::Expr;
+    use futures::TryStreamExt;
 
     use crate::{delta_datafusion::expr::parse_predicate_expression, DeltaResult};
 
@@ -282,4 +410,18 @@ mod datafusion_utils {
             None => None,
         })
     }
+
+    /// Execute `plan` discarding its output and return the number of rows produced.
+    ///
+    /// Used by dry runs, which rely on the metrics collected while executing the plan.
+    pub(crate) async fn drain_plan(
+        plan: Arc<dyn ExecutionPlan>,
+        state: &SessionState,
+    ) -> DeltaResult<usize> {
+        let stream = execute_stream(plan, state.task_ctx())?;
+        let rows = stream
+            .try_fold(0, |rows, batch| async move { Ok(rows + batch.num_rows()) })
+            .await?;
+        Ok(rows)
+    }
 }
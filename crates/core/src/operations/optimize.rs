@@ -39,14 +39,21 @@ use parquet::file::properties::WriterProperties;
 use serde::{Deserialize, Serialize};
 use tracing::debug;
 
+use super::cancellation::{
+    check_cancelled, delete_uncommitted_files, is_cancelled, CancellationToken, OperationTimeout,
+};
+use super::estimate::CostEstimate;
+use super::progress::{ProgressListenerRef, ProgressTracker};
 use super::transaction::PROTOCOL;
-use super::writer::{PartitionWriter, PartitionWriterConfig};
+use super::writer::{FileLayout, PartitionWriter, PartitionWriterConfig};
 use crate::errors::{DeltaResult, DeltaTableError};
 use crate::kernel::{Action, PartitionsExt, Remove, Scalar};
 use crate::logstore::LogStoreRef;
-use crate::operations::transaction::{CommitBuilder, CommitProperties};
+use crate::operations::transaction::{CommitBuilder, CommitProperties, TransactionError};
 use crate::protocol::DeltaOperation;
+use crate::storage::deadline::{deadline_passed, propagate_deadline};
 use crate::storage::ObjectStoreRef;
+use crate::table::file_tags::OPTIMIZE_TARGET_SIZE;
 use crate::table::state::DeltaTableState;
 use crate::writer::utils::arrow_schema_without_partitions;
 use crate::{crate_version, DeltaTable, ObjectMeta, PartitionFilter};
@@ -63,6 +70,9 @@ pub struct Metrics {
     pub files_added: MetricDetails,
     /// Detailed metrics for the remove operation
     pub files_removed: MetricDetails,
+    /// Number of bytes of the files which were read and rewritten into the added files,
+    /// zero for dry runs
+    pub bytes_rewritten: i64,
     /// Number of partitions that had at least one file optimized
     pub partitions_optimized: u64,
     /// The number of batches written
@@ -73,6 +83,12 @@ pub struct Metrics {
     pub total_files_skipped: usize,
     /// The order of records from source files is preserved
     pub preserve_insertion_order: bool,
+    /// Was this a dry run
+    #[serde(skip)]
+    pub dry_run: bool,
+    /// Files that would be compacted, only reported for dry runs
+    #[serde(skip)]
+    pub files_to_remove: Vec<String>,
 }
 
 /// Statistics on files for a particular operation
@@ -113,6 +129,8 @@ pub struct PartialMetrics {
     pub files_added: MetricDetails,
     /// Detailed metrics for the remove operation
     pub files_removed: MetricDetails,
+    /// Number of bytes of the files which were read and rewritten
+    pub bytes_rewritten: i64,
     /// The number of batches written
     pub num_batches: u64,
 }
@@ -124,6 +142,7 @@ impl Metrics {
         self.num_files_removed += partial.num_files_removed;
         self.files_added.add(&partial.files_added);
         self.files_removed.add(&partial.files_removed);
+        self.bytes_rewritten += partial.bytes_rewritten;
         self.num_batches += partial.num_batches;
     }
 }
@@ -141,7 +160,7 @@ impl Default for MetricDetails {
 }
 
 /// Type of optimization to perform.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum OptimizeType {
     /// Compact files into pre-determined bins
     Compact,
@@ -176,6 +195,14 @@ pub struct OptimizeBuilder<'a> {
     /// Optimize type
     optimize_type: OptimizeType,
     min_commit_interval: Option<Duration>,
+    /// Don't rewrite files or commit. Just determine which files would be optimized
+    dry_run: bool,
+    /// Token to cancel the optimization
+    cancellation_token: Option<CancellationToken>,
+    /// Listener notified of the files rewritten
+    progress_listener: Option<ProgressListenerRef>,
+    /// Time after which to cancel the optimization
+    timeout: Option<Duration>,
 }
 
 impl<'a> OptimizeBuilder<'a> {
@@ -193,6 +220,10 @@ impl<'a> OptimizeBuilder<'a> {
             max_spill_size: 20 * 1024 * 1024 * 2014, // 20 GB.
             optimize_type: OptimizeType::Compact,
             min_commit_interval: None,
+            dry_run: false,
+            cancellation_token: None,
+            progress_listener: None,
+            timeout: None,
         }
     }
 
@@ -202,6 +233,14 @@ impl<'a> OptimizeBuilder<'a> {
         self
     }
 
+    /// Cluster the data on `columns` with a Z-order curve, shorthand for
+    /// `with_type(OptimizeType::ZOrder(columns))`
+    pub fn with_z_order(self, columns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.with_type(OptimizeType::ZOrder(
+            columns.into_iter().map(Into::into).collect(),
+        ))
+    }
+
     /// Only optimize files that return true for the specified partition filter
     pub fn with_filters(mut self, filters: &'a [PartitionFilter]) -> Self {
         self.filters = filters;
@@ -249,6 +288,57 @@ impl<'a> OptimizeBuilder<'a> {
         self.min_commit_interval = Some(min_commit_interval);
         self
     }
+
+    /// Only determine which files would be optimized, without modifying the table.
+    ///
+    /// The number of added files is estimated as one file per bin.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Cancel the optimization once the token is cancelled. Files rewritten since the last
+    /// commit are deleted and not committed
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Report the files rewritten to the listener
+    pub fn with_progress_listener(mut self, listener: ProgressListenerRef) -> Self {
+        self.progress_listener = Some(listener);
+        self
+    }
+
+    /// Cancel the optimization once the timeout has elapsed, and fail storage requests issued
+    /// after it. Files committed before the timeout are kept
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Estimate the storage IO of the optimization from the log, without reading any data.
+    /// See [`super::estimate`] for the assumptions made
+    pub fn estimate(&self) -> DeltaResult<CostEstimate> {
+        let plan = create_merge_plan(
+            self.optimize_type.clone(),
+            &self.snapshot,
+            self.filters,
+            self.target_size,
+            WriterProperties::default(),
+        )?;
+        let metrics = plan.dry_run_metrics();
+
+        // compaction and z-ordering rewrite the data without changing its size much
+        let bytes = metrics.files_removed.total_size as u64;
+        let mut estimate = CostEstimate::default();
+        estimate.read_files(metrics.num_files_removed, bytes);
+        estimate.write_files(metrics.num_files_added, bytes);
+        if metrics.num_files_added > 0 {
+            estimate.commit();
+        }
+        Ok(estimate)
+    }
 }
 
 impl<'a> std::future::IntoFuture for OptimizeBuilder<'a> {
@@ -260,6 +350,7 @@ impl<'a> std::future::IntoFuture for OptimizeBuilder<'a> {
 
         Box::pin(async move {
             PROTOCOL.can_write_to(&this.snapshot.snapshot)?;
+            check_cancelled(this.cancellation_token.as_ref())?;
 
             let writer_properties = this.writer_properties.unwrap_or_else(|| {
                 WriterProperties::builder()
@@ -274,6 +365,24 @@ impl<'a> std::future::IntoFuture for OptimizeBuilder<'a> {
                 this.target_size.to_owned(),
                 writer_properties,
             )?;
+            if this.dry_run {
+                return Ok((
+                    DeltaTable::new_with_state(this.log_store, this.snapshot),
+                    plan.dry_run_metrics(),
+                ));
+            }
+            let plan = match this.cancellation_token {
+                Some(token) => plan.with_cancellation_token(token),
+                None => plan,
+            };
+            let plan = match this.progress_listener {
+                Some(listener) => plan.with_progress_listener(listener),
+                None => plan,
+            };
+            let plan = match this.timeout {
+                Some(timeout) => plan.with_timeout(timeout),
+                None => plan,
+            };
             let metrics = plan
                 .execute(
                     this.log_store.clone(),
@@ -378,10 +487,12 @@ pub struct MergePlan {
     task_parameters: Arc<MergeTaskParameters>,
     /// Version of the table at beginning of optimization. Used for conflict resolution.
     read_table_version: i64,
+    /// Time after which to stop executing the plan
+    timeout: Option<Duration>,
 }
 
 /// Parameters passed to individual merge tasks
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct MergeTaskParameters {
     /// Parameters passed to optimize operation
     input_parameters: OptimizeInput,
@@ -389,12 +500,37 @@ pub struct MergeTaskParameters {
     file_schema: ArrowSchemaRef,
     /// Properties passed to parquet writer
     writer_properties: WriterProperties,
+    /// Directory layout of written files
+    file_layout: FileLayout,
+    /// Token to cancel the merge tasks
+    cancellation_token: Option<CancellationToken>,
+    /// Listener notified of completed merge tasks
+    progress_listener: Option<ProgressListenerRef>,
 }
 
 /// A stream of record batches, with a ParquetError on failure.
 type ParquetReadStream = BoxStream<'static, Result<RecordBatch, ParquetError>>;
 
 impl MergePlan {
+    /// Stop executing the plan once the token is cancelled
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        Arc::make_mut(&mut self.task_parameters).cancellation_token = Some(token);
+        self
+    }
+
+    /// Report the files rewritten while executing the plan to the listener
+    pub fn with_progress_listener(mut self, listener: ProgressListenerRef) -> Self {
+        Arc::make_mut(&mut self.task_parameters).progress_listener = Some(listener);
+        self
+    }
+
+    /// Stop executing the plan once the timeout has elapsed. The plan then fails with
+    /// [`DeltaTableError::Timeout`]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     /// Rewrites files in a single partition.
     ///
     /// Returns a vector of add and remove actions, as well as the partial metrics
@@ -409,6 +545,7 @@ impl MergePlan {
     where
         F: Future<Output = Result<ParquetReadStream, DeltaTableError>> + Send + 'static,
     {
+        check_cancelled(task_parameters.cancellation_token.as_ref())?;
         debug!("Rewriting files in partition: {:?}", partition_values);
         // First, initialize metrics
         let mut partial_actions = files
@@ -422,21 +559,14 @@ impl MergePlan {
             })
             .collect::<Result<Vec<_>, DeltaTableError>>()?;
 
-        let files_removed = files
-            .iter()
-            .fold(MetricDetails::default(), |mut curr, file| {
-                curr.total_files += 1;
-                curr.total_size += file.size as i64;
-                curr.max = std::cmp::max(curr.max, file.size as i64);
-                curr.min = std::cmp::min(curr.min, file.size as i64);
-                curr
-            });
+        let files_removed = files.metric_details();
 
         let mut partial_metrics = PartialMetrics {
             num_files_added: 0,
             num_files_removed: files.len() as u64,
             files_added: MetricDetails::default(),
             files_removed,
+            bytes_rewritten: 0,
             num_batches: 0,
         };
 
@@ -447,12 +577,17 @@ impl MergePlan {
             Some(task_parameters.writer_properties.clone()),
             Some(task_parameters.input_parameters.target_size as usize),
             None,
-        )?;
-        let mut writer = PartitionWriter::try_with_config(object_store, writer_config)?;
+        )?
+        .with_file_layout(task_parameters.file_layout);
+        let mut writer = PartitionWriter::try_with_config(object_store.clone(), writer_config)?;
 
         let mut read_stream = read_stream.await?;
 
+        let cancellation_token = task_parameters.cancellation_token.as_ref();
         while let Some(maybe_batch) = read_stream.next().await {
+            if is_cancelled(cancellation_token) {
+                break;
+            }
             let mut batch = maybe_batch?;
 
             batch = super::cast::cast_record_batch(
@@ -465,8 +600,18 @@ impl MergePlan {
             writer.write(&batch).await.map_err(DeltaTableError::from)?;
         }
 
+        if is_cancelled(cancellation_token) {
+            let written = writer.close().await?.into_iter().map(Action::Add);
+            delete_uncommitted_files(&object_store, &written.collect::<Vec<_>>()).await;
+            return Err(DeltaTableError::Cancelled);
+        }
+
         let add_actions = writer.close().await?.into_iter().map(|mut add| {
             add.data_change = false;
+            add.tags.get_or_insert_with(HashMap::new).insert(
+                OPTIMIZE_TARGET_SIZE.to_string(),
+                Some(task_parameters.input_parameters.target_size.to_string()),
+            );
 
             let size = add.size;
 
@@ -479,6 +624,8 @@ impl MergePlan {
             Action::Add(add)
         });
         partial_actions.extend(add_actions);
+        // all files of the bin were read to completion and written out again
+        partial_metrics.bytes_rewritten = partial_metrics.files_removed.total_size;
 
         debug!(
             "Finished rewriting files in partition: {:?}",
@@ -611,6 +758,43 @@ impl MergePlan {
         max_spill_size: usize,
         min_commit_interval: Option<Duration>,
         commit_properties: CommitProperties,
+    ) -> Result<Metrics, DeltaTableError> {
+        let planned = self.dry_run_metrics();
+        let progress =
+            ProgressTracker::new(self.task_parameters.progress_listener.clone(), "OPTIMIZE")
+                .with_totals(
+                    planned.num_files_removed,
+                    planned.files_removed.total_size as u64,
+                );
+        let (timeout, token) = OperationTimeout::maybe_start(
+            self.timeout,
+            self.task_parameters.cancellation_token.clone(),
+        );
+        Arc::make_mut(&mut self.task_parameters).cancellation_token = token;
+
+        let optimize = self.run(
+            progress.clone(),
+            log_store,
+            snapshot,
+            max_concurrent_tasks,
+            max_spill_size,
+            min_commit_interval,
+            commit_properties,
+        );
+        OperationTimeout::maybe_run(timeout, &progress, optimize).await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn run(
+        mut self,
+        progress: ProgressTracker,
+        log_store: LogStoreRef,
+        snapshot: &DeltaTableState,
+        max_concurrent_tasks: usize,
+        #[allow(unused_variables)] // used behind a feature flag
+        max_spill_size: usize,
+        min_commit_interval: Option<Duration>,
+        commit_properties: CommitProperties,
     ) -> Result<Metrics, DeltaTableError> {
         let operations = std::mem::take(&mut self.operations);
 
@@ -642,13 +826,14 @@ impl MergePlan {
                         .try_flatten()
                         .boxed();
 
-                    let rewrite_result = tokio::task::spawn(Self::rewrite_files(
-                        self.task_parameters.clone(),
-                        partition,
-                        files,
-                        log_store.object_store().clone(),
-                        futures::future::ready(Ok(batch_stream)),
-                    ));
+                    let rewrite_result =
+                        tokio::task::spawn(propagate_deadline(Self::rewrite_files(
+                            self.task_parameters.clone(),
+                            partition,
+                            files,
+                            log_store.object_store().clone(),
+                            futures::future::ready(Ok(batch_stream)),
+                        )));
                     util::flatten_join_error(rewrite_result)
                 })
                 .boxed(),
@@ -675,13 +860,14 @@ impl MergePlan {
                 futures::stream::iter(bins)
                     .map(move |(_, (partition, files))| {
                         let batch_stream = Self::read_zorder(files.clone(), exec_context.clone());
-                        let rewrite_result = tokio::task::spawn(Self::rewrite_files(
-                            task_parameters.clone(),
-                            partition,
-                            files,
-                            log_store.object_store(),
-                            batch_stream,
-                        ));
+                        let rewrite_result =
+                            tokio::task::spawn(propagate_deadline(Self::rewrite_files(
+                                task_parameters.clone(),
+                                partition,
+                                files,
+                                log_store.object_store(),
+                                batch_stream,
+                            )));
                         util::flatten_join_error(rewrite_result)
                     })
                     .boxed()
@@ -692,26 +878,48 @@ impl MergePlan {
 
         let mut table = DeltaTable::new_with_state(log_store.clone(), snapshot.clone());
 
-        // Actions buffered so far. These will be flushed either at the end
-        // or when we reach the commit interval.
-        let mut actions = vec![];
+        // Actions and metrics of the bins buffered so far. These will be flushed either at the
+        // end or when we reach the commit interval.
+        let mut bins = vec![];
 
-        // Each time we commit, we'll reset buffered_metrics to orig_metrics.
+        // Each commit starts its metrics from orig_metrics.
         let orig_metrics = std::mem::take(&mut self.metrics);
-        let mut buffered_metrics = orig_metrics.clone();
         let mut total_metrics = orig_metrics.clone();
 
+        let cancellation_token = self.task_parameters.cancellation_token.clone();
         let mut last_commit = Instant::now();
         loop {
-            let next = stream.next().await.transpose()?;
+            let next = stream.next().await.transpose();
+            if is_cancelled(cancellation_token.as_ref()) || deadline_passed() {
+                // Tasks still running stop at their next batch and delete their own files, the
+                // files of finished tasks have not been committed yet and are deleted here
+                let mut actions = bins
+                    .into_iter()
+                    .flat_map(|(partial_actions, _)| partial_actions)
+                    .collect::<Vec<_>>();
+                if let Ok(Some((partial_actions, _))) = next {
+                    actions.extend(partial_actions);
+                }
+                while let Some(result) = stream.next().await {
+                    if let Ok((partial_actions, _)) = result {
+                        actions.extend(partial_actions);
+                    }
+                }
+                delete_uncommitted_files(&log_store.object_store(), &actions).await;
+                return Err(DeltaTableError::Cancelled);
+            }
+            let next = next?;
 
             let end = next.is_none();
 
             if let Some((partial_actions, partial_metrics)) = next {
                 debug!("Recording metrics for a completed partition");
-                actions.extend(partial_actions);
-                buffered_metrics.add(&partial_metrics);
+                progress.advance(
+                    partial_metrics.num_files_removed,
+                    partial_metrics.files_removed.total_size as u64,
+                );
                 total_metrics.add(&partial_metrics);
+                bins.push((partial_actions, partial_metrics));
             }
 
             let now = Instant::now();
@@ -719,45 +927,24 @@ impl MergePlan {
                 None => false,
                 Some(i) => now.duration_since(last_commit) > i,
             };
-            if !actions.is_empty() && (mature || end) {
-                let actions = std::mem::take(&mut actions);
+            if !bins.is_empty() && (mature || end) {
                 last_commit = now;
-
-                buffered_metrics.preserve_insertion_order = true;
-                let mut properties = CommitProperties::default();
-                properties.app_metadata = commit_properties.app_metadata.clone();
-                properties
-                    .app_metadata
-                    .insert("readVersion".to_owned(), self.read_table_version.into());
-                let maybe_map_metrics = serde_json::to_value(std::mem::replace(
-                    &mut buffered_metrics,
-                    orig_metrics.clone(),
-                ));
-                if let Ok(map) = maybe_map_metrics {
-                    properties
-                        .app_metadata
-                        .insert("operationMetrics".to_owned(), map);
-                }
-
-                table.update().await?;
-                debug!("committing {} actions", actions.len());
-                //// TODO: Check for remove actions on optimized partitions. If a
-                //// optimized partition was updated then abort the commit. Requires (#593).
-
-                CommitBuilder::from(properties)
-                    .with_actions(actions)
-                    .build(
-                        Some(snapshot),
-                        log_store.clone(),
-                        self.task_parameters.input_parameters.clone().into(),
-                    )?
-                    .await?;
+                self.commit_bins(
+                    &mut table,
+                    snapshot,
+                    &log_store,
+                    &commit_properties,
+                    std::mem::take(&mut bins),
+                    &orig_metrics,
+                )
+                .await?;
             }
 
             if end {
                 break;
             }
         }
+        progress.finish();
 
         total_metrics.preserve_insertion_order = true;
         if total_metrics.num_files_added == 0 {
@@ -769,6 +956,116 @@ impl MergePlan {
 
         Ok(total_metrics)
     }
+
+    /// Metrics predicted for executing the plan, without reading or writing any data
+    pub fn dry_run_metrics(&self) -> Metrics {
+        let bins: Vec<&MergeBin> = match &self.operations {
+            OptimizeOperations::Compact(bins) => {
+                bins.values().flat_map(|(_, bins)| bins.iter()).collect()
+            }
+            OptimizeOperations::ZOrder(_, bins) => bins.values().map(|(_, bin)| bin).collect(),
+        };
+
+        let mut metrics = self.metrics.clone();
+        for bin in bins {
+            metrics.num_files_added += 1;
+            metrics.num_files_removed += bin.len() as u64;
+            metrics.files_removed.add(&bin.metric_details());
+            metrics
+                .files_to_remove
+                .extend(bin.iter().map(|file| file.location.to_string()));
+        }
+        metrics.files_added.min = 0;
+        if metrics.num_files_removed == 0 {
+            metrics.files_removed.min = 0;
+        }
+        metrics.preserve_insertion_order = true;
+        metrics.dry_run = true;
+        metrics
+    }
+
+    /// Commit the actions of the buffered bins. Optimize does not change the data of the table,
+    /// so bins which exceed the maximum commit size together are split across several commits.
+    /// The actions of a single bin are always committed together.
+    async fn commit_bins(
+        &self,
+        table: &mut DeltaTable,
+        snapshot: &DeltaTableState,
+        log_store: &LogStoreRef,
+        commit_properties: &CommitProperties,
+        bins: Vec<(Vec<Action>, PartialMetrics)>,
+        orig_metrics: &Metrics,
+    ) -> Result<(), DeltaTableError> {
+        let mut pending = vec![bins];
+        while let Some(mut bins) = pending.pop() {
+            let mut metrics = orig_metrics.clone();
+            for (_, partial_metrics) in &bins {
+                metrics.add(partial_metrics);
+            }
+            let actions = bins
+                .iter()
+                .flat_map(|(actions, _)| actions.iter().cloned())
+                .collect();
+            let result = self
+                .commit_actions(
+                    table,
+                    snapshot,
+                    log_store,
+                    commit_properties,
+                    actions,
+                    metrics,
+                )
+                .await;
+            match result {
+                Err(DeltaTableError::Transaction {
+                    source: TransactionError::CommitTooLarge { .. },
+                }) if bins.len() > 1 => {
+                    debug!("splitting commit of {} bins", bins.len());
+                    let rest = bins.split_off(bins.len() / 2);
+                    pending.push(rest);
+                    pending.push(bins);
+                }
+                result => result?,
+            }
+        }
+        Ok(())
+    }
+
+    async fn commit_actions(
+        &self,
+        table: &mut DeltaTable,
+        snapshot: &DeltaTableState,
+        log_store: &LogStoreRef,
+        commit_properties: &CommitProperties,
+        actions: Vec<Action>,
+        mut metrics: Metrics,
+    ) -> Result<(), DeltaTableError> {
+        metrics.preserve_insertion_order = true;
+        let mut properties = commit_properties.clone();
+        properties
+            .app_metadata
+            .insert("readVersion".to_owned(), self.read_table_version.into());
+        if let Ok(map) = serde_json::to_value(metrics) {
+            properties
+                .app_metadata
+                .insert("operationMetrics".to_owned(), map);
+        }
+
+        table.update().await?;
+        debug!("committing {} actions", actions.len());
+        //// TODO: Check for remove actions on optimized partitions. If a
+        //// optimized partition was updated then abort the commit. Requires (#593).
+
+        CommitBuilder::from(properties)
+            .with_actions(actions)
+            .build(
+                Some(snapshot),
+                log_store.clone(),
+                self.task_parameters.input_parameters.clone().into(),
+            )?
+            .await?;
+        Ok(())
+    }
 }
 
 /// Build a Plan on which files to merge together. See [OptimizeBuilder]
@@ -803,8 +1100,12 @@ pub fn create_merge_plan(
             input_parameters,
             file_schema,
             writer_properties,
+            file_layout: FileLayout::from_table_config(&snapshot.table_config()),
+            cancellation_token: None,
+            progress_listener: None,
         }),
         read_table_version: snapshot.version(),
+        timeout: None,
     })
 }
 
@@ -841,6 +1142,17 @@ impl MergeBin {
     fn iter(&self) -> impl Iterator<Item = &ObjectMeta> {
         self.files.iter()
     }
+
+    fn metric_details(&self) -> MetricDetails {
+        self.iter()
+            .fold(MetricDetails::default(), |mut curr, file| {
+                curr.total_files += 1;
+                curr.total_size += file.size as i64;
+                curr.max = std::cmp::max(curr.max, file.size as i64);
+                curr.min = std::cmp::min(curr.min, file.size as i64);
+                curr
+            })
+    }
 }
 
 impl IntoIterator for MergeBin {
@@ -0,0 +1,238 @@
+//! Partition tables by time
+//!
+//! The most common partitioning of append heavy tables is by the date, or the hour, an event
+//! happened or was ingested. Rather than deriving these columns in every writer, a
+//! [`PartitionBy`] derives `year`, `month`, `day` and `hour` partition columns at write time,
+//! either from a timestamp or date column of the written data or from the time of the write.
+//! The derived columns are stored as integers and appended to the written batches, similar to
+//! generated columns.
+//!
+//! # Example
+//! ```rust ignore
+//! let table = DeltaOps(table)
+//!     .write(batches)
+//!     .with_partition_by(PartitionBy::time("event_time", TimeGranularity::Day))
+//!     .await?;
+//! // files are written to `year=2024/month=1/day=31/`
+//! ````
+
+use std::sync::Arc;
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::TimestampMicrosecondType;
+use arrow_array::{Array, ArrayRef, Int32Array, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use chrono::{Datelike, NaiveDateTime, Timelike, Utc};
+
+use crate::errors::{DeltaResult, DeltaTableError};
+
+/// The finest unit of time tables are partitioned by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TimeGranularity {
+    /// Partitioned by `year`
+    Year,
+    /// Partitioned by `year` and `month`
+    Month,
+    /// Partitioned by `year`, `month` and `day`
+    Day,
+    /// Partitioned by `year`, `month`, `day` and `hour`
+    Hour,
+}
+
+impl TimeGranularity {
+    /// Names of the partition columns, from the coarsest to the finest
+    pub fn partition_columns(&self) -> Vec<String> {
+        let columns = ["year", "month", "day", "hour"];
+        columns[..=*self as usize]
+            .iter()
+            .map(|column| column.to_string())
+            .collect()
+    }
+}
+
+/// Partitioning of a table derived from time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartitionBy {
+    /// Timestamp or date column the partition values are derived from, the time of the write
+    /// when not set
+    column: Option<String>,
+    granularity: TimeGranularity,
+}
+
+impl PartitionBy {
+    /// Partition by the time of the timestamp or date `column`, in UTC
+    pub fn time(column: impl Into<String>, granularity: TimeGranularity) -> Self {
+        Self {
+            column: Some(column.into()),
+            granularity,
+        }
+    }
+
+    /// Partition by the time the data is written, in UTC, clustering the files of an ingestion
+    pub fn ingestion_time(granularity: TimeGranularity) -> Self {
+        Self {
+            column: None,
+            granularity,
+        }
+    }
+
+    /// Names of the derived partition columns
+    pub fn partition_columns(&self) -> Vec<String> {
+        self.granularity.partition_columns()
+    }
+
+    /// Append the derived partition columns to `batch`
+    pub fn apply(&self, batch: &RecordBatch) -> DeltaResult<RecordBatch> {
+        let columns = self.partition_columns();
+        if let Some(existing) = columns
+            .iter()
+            .find(|column| batch.schema().column_with_name(column).is_some())
+        {
+            return Err(DeltaTableError::Generic(format!(
+                "Cannot derive partition column '{existing}', which is already part of the data"
+            )));
+        }
+
+        let times = match &self.column {
+            Some(column) => {
+                let array = batch.column_by_name(column).ok_or_else(|| {
+                    DeltaTableError::Generic(format!(
+                        "Time partition column '{column}' not found in data"
+                    ))
+                })?;
+                let micros = timestamp_micros(column, array)?;
+                let micros = micros.as_primitive::<TimestampMicrosecondType>();
+                micros
+                    .iter()
+                    .map(|value| value.and_then(NaiveDateTime::from_timestamp_micros))
+                    .collect::<Vec<_>>()
+            }
+            None => vec![Some(Utc::now().naive_utc()); batch.num_rows()],
+        };
+
+        let mut fields = batch.schema().fields().to_vec();
+        let mut arrays = batch.columns().to_vec();
+        for column in columns {
+            let part: fn(&NaiveDateTime) -> i32 = match column.as_str() {
+                "year" => |time| time.year(),
+                "month" => |time| time.month() as i32,
+                "day" => |time| time.day() as i32,
+                _ => |time| time.hour() as i32,
+            };
+            let values = times
+                .iter()
+                .map(|time| time.as_ref().map(part))
+                .collect::<Int32Array>();
+            fields.push(Arc::new(Field::new(column, DataType::Int32, true)));
+            arrays.push(Arc::new(values));
+        }
+
+        Ok(RecordBatch::try_new(
+            Arc::new(Schema::new_with_metadata(
+                fields,
+                batch.schema().metadata().clone(),
+            )),
+            arrays,
+        )?)
+    }
+}
+
+/// Cast a timestamp or date column to microseconds since the epoch in UTC
+fn timestamp_micros(column: &str, array: &ArrayRef) -> DeltaResult<ArrayRef> {
+    match array.data_type() {
+        DataType::Timestamp(_, _) => Ok(arrow_cast::cast(
+            array,
+            &DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+        )?),
+        // dates cannot be cast to timestamps with a time zone, days start at midnight UTC
+        DataType::Date32 | DataType::Date64 => Ok(arrow_cast::cast(
+            array,
+            &DataType::Timestamp(TimeUnit::Microsecond, None),
+        )?),
+        data_type => Err(DeltaTableError::Generic(format!(
+            "Time partition column '{column}' must be a timestamp or date, found {data_type}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{Date32Array, StringArray, TimestampMicrosecondArray};
+
+    #[test]
+    fn test_partition_by_time() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Utf8, true),
+            Field::new(
+                "event_time",
+                DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+                true,
+            ),
+            Field::new("event_date", DataType::Date32, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(vec!["a", "b", "c"])),
+                Arc::new(
+                    TimestampMicrosecondArray::from(vec![
+                        Some(1_706_745_600_000_000),
+                        Some(1_709_287_200_000_000),
+                        None,
+                    ])
+                    .with_timezone("UTC"),
+                ),
+                Arc::new(Date32Array::from(vec![Some(19_753), Some(0), None])),
+            ],
+        )
+        .unwrap();
+
+        let partition_by = PartitionBy::time("event_time", TimeGranularity::Hour);
+        assert_eq!(
+            partition_by.partition_columns(),
+            vec!["year", "month", "day", "hour"]
+        );
+        let result = partition_by.apply(&batch).unwrap();
+        assert_eq!(result.num_columns(), 7);
+        let column = |name: &str| {
+            result
+                .column_by_name(name)
+                .unwrap()
+                .as_primitive::<arrow_array::types::Int32Type>()
+                .iter()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(column("year"), vec![Some(2024), Some(2024), None]);
+        assert_eq!(column("month"), vec![Some(2), Some(3), None]);
+        assert_eq!(column("day"), vec![Some(1), Some(1), None]);
+        assert_eq!(column("hour"), vec![Some(0), Some(10), None]);
+
+        let result = PartitionBy::time("event_date", TimeGranularity::Month)
+            .apply(&batch)
+            .unwrap();
+        assert_eq!(result.num_columns(), 5);
+        let years = result.column_by_name("year").unwrap();
+        assert_eq!(
+            years
+                .as_primitive::<arrow_array::types::Int32Type>()
+                .value(1),
+            1970
+        );
+
+        let result = PartitionBy::ingestion_time(TimeGranularity::Day)
+            .apply(&batch)
+            .unwrap();
+        assert_eq!(result.column_by_name("day").unwrap().null_count(), 0);
+
+        assert!(PartitionBy::time("id", TimeGranularity::Day)
+            .apply(&batch)
+            .is_err());
+        assert!(PartitionBy::time("missing", TimeGranularity::Day)
+            .apply(&batch)
+            .is_err());
+        assert!(PartitionBy::time("event_time", TimeGranularity::Year)
+            .apply(&result)
+            .is_err());
+    }
+}
@@ -0,0 +1,320 @@
+//! Progress reporting for long running operations
+//!
+//! Loading, optimizing, vacuuming and converting large tables can run for hours without any
+//! visible sign of life. Operations accept a [`ProgressListener`] through
+//! `with_progress_listener`, which is called with a [`Progress`] snapshot whenever a file or
+//! batch has been processed, and once more when the operation finishes. Command line tools and
+//! user interfaces can use these updates to render progress bars and estimate the remaining time.
+//!
+//! # Example
+//! ```rust ignore
+//! #[derive(Debug)]
+//! struct PrintProgress;
+//!
+//! impl ProgressListener for PrintProgress {
+//!     fn on_progress(&self, progress: &Progress) {
+//!         println!("{}: {:?} done, {:?} left", progress.operation, progress.fraction(), progress.eta());
+//!     }
+//! }
+//!
+//! let (table, metrics) = DeltaOps(table)
+//!     .optimize()
+//!     .with_progress_listener(Arc::new(PrintProgress))
+//!     .await?;
+//! ````
+
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A snapshot of the progress of an operation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    /// Name of the operation, such as `OPTIMIZE`
+    pub operation: &'static str,
+    /// Number of files processed so far
+    pub files_processed: u64,
+    /// Total number of files to process, if known
+    pub files_total: Option<u64>,
+    /// Number of bytes processed so far
+    pub bytes_processed: u64,
+    /// Total number of bytes to process, if known
+    pub bytes_total: Option<u64>,
+    /// Number of rows processed so far
+    pub rows_processed: u64,
+    /// Total number of rows to process, if known
+    pub rows_total: Option<u64>,
+    /// Time since the operation started
+    pub elapsed: Duration,
+    /// Whether the operation has finished
+    pub finished: bool,
+}
+
+impl Progress {
+    /// Processed and total amount of work, in bytes, rows or files, whichever is known first
+    fn work(&self) -> Option<(u64, u64)> {
+        [
+            (self.bytes_processed, self.bytes_total),
+            (self.rows_processed, self.rows_total),
+            (self.files_processed, self.files_total),
+        ]
+        .into_iter()
+        .find_map(|(processed, total)| total.filter(|total| *total > 0).map(|t| (processed, t)))
+    }
+
+    /// Fraction of the work done, between 0 and 1, if the total amount of work is known
+    pub fn fraction(&self) -> Option<f64> {
+        if self.finished {
+            return Some(1.0);
+        }
+        self.work()
+            .map(|(processed, total)| (processed as f64 / total as f64).min(1.0))
+    }
+
+    /// Estimated time until the operation finishes, extrapolated from the rate of progress so far
+    pub fn eta(&self) -> Option<Duration> {
+        if self.finished {
+            return Some(Duration::ZERO);
+        }
+        let (processed, total) = self.work()?;
+        if processed == 0 {
+            return None;
+        }
+        let remaining = total.saturating_sub(processed) as f64 / processed as f64;
+        Some(self.elapsed.mul_f64(remaining))
+    }
+}
+
+/// Receives progress updates of long running operations
+pub trait ProgressListener: Debug + Send + Sync {
+    /// Called whenever a file or batch has been processed
+    fn on_progress(&self, progress: &Progress);
+
+    /// Called once when the operation has finished successfully
+    fn on_finish(&self, progress: &Progress) {
+        self.on_progress(progress)
+    }
+}
+
+/// A shared [`ProgressListener`]
+pub type ProgressListenerRef = Arc<dyn ProgressListener>;
+
+/// Amount of some unit of work processed, and the total amount if known
+#[derive(Debug)]
+struct Counter {
+    processed: AtomicU64,
+    total: AtomicU64,
+}
+
+impl Counter {
+    /// Sentinel of an unknown total
+    const UNKNOWN: u64 = u64::MAX;
+
+    fn new() -> Self {
+        Self {
+            processed: AtomicU64::new(0),
+            total: AtomicU64::new(Self::UNKNOWN),
+        }
+    }
+
+    fn set_total(&self, total: Option<u64>) {
+        let total = total.unwrap_or(Self::UNKNOWN);
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    fn add(&self, amount: u64) {
+        self.processed.fetch_add(amount, Ordering::Relaxed);
+    }
+
+    fn load(&self) -> (u64, Option<u64>) {
+        let total = self.total.load(Ordering::Relaxed);
+        let processed = self.processed.load(Ordering::Relaxed);
+        (processed, (total != Self::UNKNOWN).then_some(total))
+    }
+}
+
+/// Counts the work done by an operation, and reports it to an optional listener. Clones share
+/// their counts
+#[derive(Debug, Clone)]
+pub(crate) struct ProgressTracker {
+    listener: Option<ProgressListenerRef>,
+    operation: &'static str,
+    start: Instant,
+    files: Arc<Counter>,
+    bytes: Arc<Counter>,
+    rows: Arc<Counter>,
+}
+
+impl ProgressTracker {
+    pub(crate) fn new(listener: Option<ProgressListenerRef>, operation: &'static str) -> Self {
+        Self {
+            listener,
+            operation,
+            start: Instant::now(),
+            files: Arc::new(Counter::new()),
+            bytes: Arc::new(Counter::new()),
+            rows: Arc::new(Counter::new()),
+        }
+    }
+
+    /// Set the total number of files and bytes to process
+    pub(crate) fn with_totals(self, files: u64, bytes: u64) -> Self {
+        self.set_totals(files, bytes);
+        self
+    }
+
+    /// Set the total number of files and bytes to process, once known
+    pub(crate) fn set_totals(&self, files: u64, bytes: u64) {
+        self.files.set_total(Some(files));
+        self.bytes.set_total(Some(bytes));
+    }
+
+    /// Set the total number of rows to process
+    pub(crate) fn with_rows_total(self, rows: Option<u64>) -> Self {
+        self.rows.set_total(rows);
+        self
+    }
+
+    /// Record processed files and bytes
+    pub(crate) fn advance(&self, files: u64, bytes: u64) {
+        self.files.add(files);
+        self.bytes.add(bytes);
+        self.report(false);
+    }
+
+    /// Record processed rows
+    pub(crate) fn advance_rows(&self, rows: u64) {
+        self.rows.add(rows);
+        self.report(false);
+    }
+
+    /// Report that the operation has finished
+    pub(crate) fn finish(&self) {
+        self.report(true);
+    }
+
+    /// The progress made so far
+    pub(crate) fn snapshot(&self, finished: bool) -> Progress {
+        let (files_processed, files_total) = self.files.load();
+        let (bytes_processed, bytes_total) = self.bytes.load();
+        let (rows_processed, rows_total) = self.rows.load();
+        Progress {
+            operation: self.operation,
+            files_processed,
+            files_total,
+            bytes_processed,
+            bytes_total,
+            rows_processed,
+            rows_total,
+            elapsed: self.start.elapsed(),
+            finished,
+        }
+    }
+
+    fn report(&self, finished: bool) {
+        if let Some(listener) = &self.listener {
+            let progress = self.snapshot(finished);
+            if finished {
+                listener.on_finish(&progress);
+            } else {
+                listener.on_progress(&progress);
+            }
+        }
+    }
+}
+
+/// Count the rows of a record batch stream, and report the end of the stream as the end of the
+/// operation
+#[cfg(feature = "datafusion")]
+pub(crate) fn tracked_stream(
+    stream: datafusion::physical_plan::SendableRecordBatchStream,
+    tracker: ProgressTracker,
+) -> datafusion::physical_plan::SendableRecordBatchStream {
+    use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+    use futures::StreamExt;
+
+    let schema = stream.schema();
+    let finished = tracker.clone();
+    let stream = stream
+        .inspect(move |batch| {
+            if let Ok(batch) = batch {
+                tracker.advance_rows(batch.num_rows() as u64);
+            }
+        })
+        .chain(
+            futures::stream::once(async move { finished.finish() }).filter_map(|_| async { None }),
+        );
+    Box::pin(RecordBatchStreamAdapter::new(schema, stream))
+}
+
+/// A [`ProgressListener`] recording all updates, for tests
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub(crate) struct RecordingListener {
+    pub(crate) updates: std::sync::Mutex<Vec<Progress>>,
+}
+
+#[cfg(test)]
+impl ProgressListener for RecordingListener {
+    fn on_progress(&self, progress: &Progress) {
+        self.updates.lock().unwrap().push(progress.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(processed: u64, total: Option<u64>, elapsed: Duration) -> Progress {
+        Progress {
+            operation: "TEST",
+            files_processed: processed,
+            files_total: total,
+            bytes_processed: 0,
+            bytes_total: None,
+            rows_processed: 0,
+            rows_total: None,
+            elapsed,
+            finished: false,
+        }
+    }
+
+    #[test]
+    fn test_eta() {
+        let half = progress(5, Some(10), Duration::from_secs(30));
+        assert_eq!(half.fraction(), Some(0.5));
+        assert_eq!(half.eta(), Some(Duration::from_secs(30)));
+
+        let unknown = progress(5, None, Duration::from_secs(30));
+        assert_eq!(unknown.fraction(), None);
+        assert_eq!(unknown.eta(), None);
+
+        let started = progress(0, Some(10), Duration::from_secs(1));
+        assert_eq!(started.fraction(), Some(0.0));
+        assert_eq!(started.eta(), None);
+
+        // bytes take precedence over files
+        let mut bytes = progress(9, Some(10), Duration::from_secs(10));
+        bytes.bytes_processed = 10;
+        bytes.bytes_total = Some(100);
+        assert_eq!(bytes.eta(), Some(Duration::from_secs(90)));
+    }
+
+    #[test]
+    fn test_tracker() {
+        let listener = Arc::new(RecordingListener::default());
+        let tracker = ProgressTracker::new(Some(listener.clone()), "TEST").with_totals(2, 20);
+        tracker.advance(1, 15);
+        tracker.clone().advance(1, 5);
+        tracker.finish();
+
+        let updates = listener.updates.lock().unwrap();
+        assert_eq!(updates.len(), 3);
+        assert_eq!(updates[0].bytes_processed, 15);
+        assert_eq!(updates[0].fraction(), Some(0.75));
+        assert_eq!(updates[1].files_processed, 2);
+        assert!(updates[2].finished);
+        assert_eq!(updates[2].eta(), Some(Duration::ZERO));
+    }
+}
@@ -0,0 +1,336 @@
+//! Remove orphaned data files from a Delta table
+//!
+//! Orphaned files are present in the table's storage but were never referenced by the log,
+//! typically written by writers that failed before committing. Vacuum does not delete them,
+//! since it only removes tombstoned files. A file counts as referenced while it is part of the
+//! table or tracked as a tombstone, so tombstoned files are left to vacuum.
+//!
+//! Only files last modified before `older_than` are deleted, so that files of concurrent writers
+//! which did not commit yet are kept. Hidden directories such as `_delta_log` and
+//! `_change_data` are never touched. No commit is created.
+//!
+//! # Example
+//! ```rust ignore
+//! let table = open_table("../path/to/table")?;
+//! let (table, metrics) = DeltaOps(table)
+//!     .remove_orphans(Duration::days(7))
+//!     .with_dry_run(true)
+//!     .await?;
+//! ````
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use futures::future::BoxFuture;
+use futures::{StreamExt, TryStreamExt};
+use object_store::path::Path;
+use object_store::Error as ObjectStoreError;
+use serde::Serialize;
+
+use super::vacuum::Clock;
+use crate::errors::DeltaResult;
+use crate::logstore::LogStoreRef;
+use crate::operations::transaction::TransactionError;
+use crate::table::state::DeltaTableState;
+use crate::DeltaTable;
+
+/// Remove files never referenced by the log.
+/// See this module's documentation for more information
+#[derive(Debug)]
+pub struct RemoveOrphansBuilder {
+    /// A snapshot of the table's state
+    snapshot: DeltaTableState,
+    /// Delta object store for handling data files
+    log_store: LogStoreRef,
+    /// Minimum age of deleted files
+    older_than: Duration,
+    /// Don't delete the files. Just determine which files can be deleted
+    dry_run: bool,
+    /// Override the source of time
+    clock: Option<Arc<dyn Clock>>,
+}
+
+/// Metrics for the Remove Orphans Operation
+#[derive(Default, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveOrphansMetrics {
+    /// Was this a dry run
+    pub dry_run: bool,
+    /// Orphaned files deleted, or to be deleted in a dry run
+    pub files_deleted: Vec<String>,
+    /// Total size of the orphaned files in bytes
+    pub bytes_deleted: usize,
+}
+
+impl RemoveOrphansBuilder {
+    /// Create a new [`RemoveOrphansBuilder`]
+    pub fn new(log_store: LogStoreRef, snapshot: DeltaTableState, older_than: Duration) -> Self {
+        Self {
+            snapshot,
+            log_store,
+            older_than,
+            dry_run: false,
+            clock: None,
+        }
+    }
+
+    /// Only determine which files should be deleted
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// add a time source for testing
+    #[doc(hidden)]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// List orphaned files older than the threshold along with their sizes
+    async fn find_orphans(&self) -> DeltaResult<Vec<(Path, usize)>> {
+        let now_millis = match &self.clock {
+            Some(clock) => clock.current_timestamp_millis(),
+            None => Utc::now().timestamp_millis(),
+        };
+        let cutoff_millis = now_millis - self.older_than.num_milliseconds();
+
+        let object_store = self.log_store.object_store();
+        let mut referenced = self.snapshot.file_paths_iter()?.collect::<HashSet<_>>();
+        referenced.extend(
+            self.snapshot
+                .all_tombstones(self.log_store.log_object_store())
+                .await?
+                .map(|tombstone| match Path::parse(&tombstone.path) {
+                    Ok(path) => path,
+                    Err(_) => Path::from(tombstone.path.as_str()),
+                }),
+        );
+        let partition_columns = &self.snapshot.metadata().partition_columns;
+
+        let mut orphans = Vec::new();
+        let mut all_files = object_store.list(None);
+        while let Some(obj_meta) = all_files.next().await {
+            let obj_meta = obj_meta?;
+            if referenced.contains(&obj_meta.location)
+                || obj_meta.last_modified.timestamp_millis() >= cutoff_millis
+                || is_hidden(partition_columns, &obj_meta.location)
+            {
+                continue;
+            }
+            orphans.push((obj_meta.location, obj_meta.size));
+        }
+        Ok(orphans)
+    }
+}
+
+/// Whether a path is inside a directory which is not managed by data file writers
+fn is_hidden(partition_columns: &[String], path: &Path) -> bool {
+    let Some(first) = path.parts().next() else {
+        return true;
+    };
+    let name = first.as_ref();
+    (name.starts_with('.') || name.starts_with('_'))
+        && !partition_columns
+            .iter()
+            .any(|column| name.starts_with(&format!("{column}=")))
+}
+
+impl std::future::IntoFuture for RemoveOrphansBuilder {
+    type Output = DeltaResult<(DeltaTable, RemoveOrphansMetrics)>;
+    type IntoFuture = BoxFuture<'static, Self::Output>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        let this = self;
+
+        Box::pin(async move {
+            if !this.dry_run && this.snapshot.snapshot().load_config().read_only {
+                return Err(TransactionError::ReadOnlyTable.into());
+            }
+            let orphans = this.find_orphans().await?;
+            let bytes_deleted = orphans.iter().map(|(_, size)| size).sum();
+
+            let files_deleted = if this.dry_run {
+                orphans
+                    .into_iter()
+                    .map(|(path, _)| path.to_string())
+                    .collect()
+            } else {
+                let locations = futures::stream::iter(orphans)
+                    .map(|(path, _)| Ok(path))
+                    .boxed();
+                this.log_store
+                    .object_store()
+                    .delete_stream(locations)
+                    .map(|res| match res {
+                        Ok(path) => Ok(path.to_string()),
+                        Err(ObjectStoreError::NotFound { path, .. }) => Ok(path),
+                        Err(err) => Err(err),
+                    })
+                    .try_collect::<Vec<_>>()
+                    .await?
+            };
+
+            Ok((
+                DeltaTable::new_with_state(this.log_store, this.snapshot),
+                RemoveOrphansMetrics {
+                    dry_run: this.dry_run,
+                    files_deleted,
+                    bytes_deleted,
+                },
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::DeltaOps;
+    use crate::writer::test_utils::{create_initialized_table, get_record_batch};
+    use crate::writer::{DeltaWriter, RecordBatchWriter};
+
+    #[derive(Debug)]
+    struct FixedClock(i64);
+
+    impl Clock for FixedClock {
+        fn current_timestamp_millis(&self) -> i64 {
+            self.0
+        }
+    }
+
+    async fn setup_table() -> (DeltaTable, String) {
+        let mut table = create_initialized_table(&[]).await;
+        let mut writer = RecordBatchWriter::for_table(&table).unwrap();
+        writer.write(get_record_batch(None, false)).await.unwrap();
+        writer.flush_and_commit(&mut table).await.unwrap();
+
+        // a writer which fails before committing leaves an orphaned file behind
+        writer.write(get_record_batch(None, false)).await.unwrap();
+        let orphan = writer.flush().await.unwrap().remove(0).path;
+        (table, orphan)
+    }
+
+    #[tokio::test]
+    async fn test_remove_orphans() {
+        let (table, orphan) = setup_table().await;
+        let later = Utc::now().timestamp_millis() + Duration::hours(2).num_milliseconds();
+
+        let (table, metrics) = DeltaOps(table)
+            .remove_orphans(Duration::hours(1))
+            .with_clock(Arc::new(FixedClock(later)))
+            .with_dry_run(true)
+            .await
+            .unwrap();
+        assert!(metrics.dry_run);
+        assert_eq!(metrics.files_deleted, vec![orphan.clone()]);
+        assert!(metrics.bytes_deleted > 0);
+
+        let (table, metrics) = DeltaOps(table)
+            .remove_orphans(Duration::hours(1))
+            .with_clock(Arc::new(FixedClock(later)))
+            .await
+            .unwrap();
+        assert_eq!(metrics.files_deleted, vec![orphan.clone()]);
+        let store = table.object_store();
+        assert!(store.head(&Path::parse(&orphan).unwrap()).await.is_err());
+        for file in table.get_files_iter().unwrap() {
+            assert!(store.head(&file).await.is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remove_orphans_read_only_table() {
+        let (table, orphan) = setup_table().await;
+        let later = Utc::now().timestamp_millis() + Duration::hours(2).num_milliseconds();
+        let table = crate::DeltaTableBuilder::from_uri("memory://")
+            .with_storage_backend(table.object_store(), url::Url::parse("memory://").unwrap())
+            .read_only()
+            .load()
+            .await
+            .unwrap();
+
+        let (table, metrics) = DeltaOps(table)
+            .remove_orphans(Duration::hours(1))
+            .with_clock(Arc::new(FixedClock(later)))
+            .with_dry_run(true)
+            .await
+            .unwrap();
+        assert_eq!(metrics.files_deleted, vec![orphan.clone()]);
+
+        let store = table.object_store();
+        let err = DeltaOps(table)
+            .remove_orphans(Duration::hours(1))
+            .with_clock(Arc::new(FixedClock(later)))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::DeltaTableError::Transaction {
+                source: TransactionError::ReadOnlyTable
+            }
+        ));
+        assert!(store.head(&Path::parse(&orphan).unwrap()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_remove_orphans_table_without_files() {
+        let (table, orphan) = setup_table().await;
+        let later = Utc::now().timestamp_millis() + Duration::hours(2).num_milliseconds();
+        let files = table.get_files_iter().unwrap().collect::<Vec<_>>();
+        let table = crate::DeltaTableBuilder::from_uri("memory://")
+            .with_storage_backend(table.object_store(), url::Url::parse("memory://").unwrap())
+            .without_files()
+            .load()
+            .await
+            .unwrap();
+        assert!(matches!(
+            table.get_files_count(),
+            Err(crate::DeltaTableError::NotInitializedWithFiles(_))
+        ));
+
+        // without the files of the table, every data file would look like an orphan
+        let store = table.object_store();
+        let err = DeltaOps(table)
+            .remove_orphans(Duration::hours(1))
+            .with_clock(Arc::new(FixedClock(later)))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::DeltaTableError::NotInitializedWithFiles(_)
+        ));
+        assert!(store.head(&Path::parse(&orphan).unwrap()).await.is_ok());
+        for file in files {
+            assert!(store.head(&file).await.is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remove_orphans_keeps_recent_files() {
+        let (table, _) = setup_table().await;
+
+        let (_, metrics) = DeltaOps(table)
+            .remove_orphans(Duration::hours(1))
+            .await
+            .unwrap();
+        assert!(metrics.files_deleted.is_empty());
+        assert_eq!(metrics.bytes_deleted, 0);
+    }
+
+    #[test]
+    fn test_is_hidden() {
+        let partition_columns = vec!["_date".to_string()];
+        assert!(is_hidden(
+            &partition_columns,
+            &Path::from("_delta_log/00000000000000000000.json")
+        ));
+        assert!(is_hidden(&partition_columns, &Path::from("_change_data/a")));
+        assert!(!is_hidden(&partition_columns, &Path::from("_date=2021/a")));
+        assert!(!is_hidden(
+            &partition_columns,
+            &Path::from("part-0.parquet")
+        ));
+    }
+}
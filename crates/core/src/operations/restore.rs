@@ -27,13 +27,14 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use chrono::{DateTime, Utc};
 use futures::future::BoxFuture;
+use futures::{StreamExt, TryStreamExt};
 use object_store::path::Path;
-use object_store::ObjectStore;
 use serde::Serialize;
 
 use crate::kernel::{Action, Add, Protocol, Remove};
 use crate::logstore::LogStoreRef;
 use crate::protocol::DeltaOperation;
+use crate::storage::ObjectStoreRef;
 use crate::table::state::DeltaTableState;
 use crate::{DeltaResult, DeltaTable, DeltaTableConfig, DeltaTableError, ObjectStoreError};
 
@@ -48,8 +49,8 @@ enum RestoreError {
     #[error("Version to restore {0} should be less then last available version {1}.")]
     TooLargeRestoreVersion(i64, i64),
 
-    #[error("Find missing file {0} when restore.")]
-    MissingDataFile(String),
+    #[error("Found {} missing files when restoring: {}", .0.len(), .0.join(", "))]
+    MissingDataFiles(Vec<String>),
 }
 
 impl From<RestoreError> for DeltaTableError {
@@ -68,6 +69,15 @@ pub struct RestoreMetrics {
     pub num_removed_file: usize,
     /// Number of files restored
     pub num_restored_file: usize,
+    /// Was this a dry run
+    #[serde(skip)]
+    pub dry_run: bool,
+    /// Files that would be removed, only reported for dry runs
+    #[serde(skip)]
+    pub files_to_remove: Vec<String>,
+    /// Files that would be restored, only reported for dry runs
+    #[serde(skip)]
+    pub files_to_restore: Vec<String>,
 }
 
 /// Restore a Delta table with given version
@@ -87,6 +97,8 @@ pub struct RestoreBuilder {
     protocol_downgrade_allowed: bool,
     /// Additional information to add to the commit
     commit_properties: CommitProperties,
+    /// Don't commit. Just determine which files would be removed and restored
+    dry_run: bool,
 }
 
 impl RestoreBuilder {
@@ -100,6 +112,7 @@ impl RestoreBuilder {
             ignore_missing_files: false,
             protocol_downgrade_allowed: false,
             commit_properties: CommitProperties::default(),
+            dry_run: false,
         }
     }
 
@@ -110,8 +123,8 @@ impl RestoreBuilder {
     }
 
     /// Set the datetime to restore
-    pub fn with_datetime_to_restore(mut self, datetime: DateTime<Utc>) -> Self {
-        self.datetime_to_restore = Some(datetime);
+    pub fn with_datetime_to_restore(mut self, datetime: impl Into<DateTime<Utc>>) -> Self {
+        self.datetime_to_restore = Some(datetime.into());
         self
     }
 
@@ -133,8 +146,15 @@ impl RestoreBuilder {
         self.commit_properties = commit_properties;
         self
     }
+
+    /// Only determine which files would be removed and restored, without modifying the table
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn execute(
     log_store: LogStoreRef,
     snapshot: DeltaTableState,
@@ -143,6 +163,7 @@ async fn execute(
     ignore_missing_files: bool,
     protocol_downgrade_allowed: bool,
     mut commit_properties: CommitProperties,
+    dry_run: bool,
 ) -> DeltaResult<RestoreMetrics> {
     if !(version_to_restore
         .is_none()
@@ -208,12 +229,23 @@ async fn execute(
         .collect();
 
     if !ignore_missing_files {
-        check_files_available(log_store.object_store().as_ref(), &files_to_add).await?;
+        check_files_available(log_store.object_store(), &files_to_add).await?;
+    }
+
+    if dry_run {
+        return Ok(RestoreMetrics {
+            num_removed_file: files_to_remove.len(),
+            num_restored_file: files_to_add.len(),
+            dry_run: true,
+            files_to_remove: files_to_remove.into_iter().map(|r| r.path).collect(),
+            files_to_restore: files_to_add.into_iter().map(|a| a.path).collect(),
+        });
     }
 
     let metrics = RestoreMetrics {
         num_removed_file: files_to_remove.len(),
         num_restored_file: files_to_add.len(),
+        ..Default::default()
     };
 
     let mut actions = vec![];
@@ -277,28 +309,42 @@ async fn execute(
             return Err(err.into());
         }
         Err(err) => {
-            log_store.object_store().delete(commit).await?;
+            log_store.log_object_store().delete(commit).await?;
             return Err(err.into());
         }
     }
     Ok(metrics)
 }
 
-async fn check_files_available(
-    object_store: &dyn ObjectStore,
-    files: &Vec<Add>,
-) -> DeltaResult<()> {
-    for file in files {
-        let file_path = Path::parse(file.path.clone())?;
-        match object_store.head(&file_path).await {
-            Ok(_) => {}
-            Err(ObjectStoreError::NotFound { .. }) => {
-                return Err(DeltaTableError::from(RestoreError::MissingDataFile(
-                    file.path.clone(),
-                )))
+/// Number of files checked for existence at the same time
+const CHECK_FILES_CONCURRENCY: usize = 16;
+
+/// Check that all files to restore exist, reporting all missing files at once
+async fn check_files_available(object_store: ObjectStoreRef, files: &[Add]) -> DeltaResult<()> {
+    let paths = files
+        .iter()
+        .map(|file| file.path.clone())
+        .collect::<Vec<_>>();
+    let mut missing = futures::stream::iter(paths.into_iter().map(|path| {
+        let object_store = object_store.clone();
+        async move {
+            let file_path = Path::parse(&path)?;
+            match object_store.head(&file_path).await {
+                Ok(_) => Ok(None),
+                Err(ObjectStoreError::NotFound { .. }) => Ok(Some(path)),
+                Err(e) => Err(DeltaTableError::from(e)),
             }
-            Err(e) => return Err(DeltaTableError::from(e)),
         }
+    }))
+    .buffer_unordered(CHECK_FILES_CONCURRENCY)
+    .try_filter_map(|path| futures::future::ready(Ok(path)))
+    .try_collect::<Vec<_>>()
+    .await?;
+    if !missing.is_empty() {
+        missing.sort();
+        return Err(DeltaTableError::from(RestoreError::MissingDataFiles(
+            missing,
+        )));
     }
     Ok(())
 }
@@ -319,10 +365,13 @@ impl std::future::IntoFuture for RestoreBuilder {
                 this.ignore_missing_files,
                 this.protocol_downgrade_allowed,
                 this.commit_properties,
+                this.dry_run,
             )
             .await?;
             let mut table = DeltaTable::new_with_state(this.log_store, this.snapshot);
-            table.update().await?;
+            if !metrics.dry_run {
+                table.update().await?;
+            }
             Ok((table, metrics))
         })
     }
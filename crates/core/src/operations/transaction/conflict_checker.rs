@@ -339,28 +339,27 @@ impl<'a> ConflictChecker<'a> {
         transaction_info: TransactionInfo<'a>,
         winning_commit_summary: WinningCommitSummary,
         operation: Option<&DeltaOperation>,
+        isolation_level: Option<IsolationLevel>,
     ) -> ConflictChecker<'a> {
-        let isolation_level = operation
-            .and_then(|op| {
+        // the isolation level requested for the commit takes precedence over the table's
+        let isolation_level = isolation_level.unwrap_or_else(|| {
+            transaction_info
+                .read_snapshot
+                .table_config()
+                .isolation_level()
+        });
+        let isolation_level = match operation {
+            Some(op)
                 if can_downgrade_to_snapshot_isolation(
                     &winning_commit_summary.actions,
                     op,
-                    &transaction_info
-                        .read_snapshot
-                        .table_config()
-                        .isolation_level(),
-                ) {
-                    Some(IsolationLevel::SnapshotIsolation)
-                } else {
-                    None
-                }
-            })
-            .unwrap_or_else(|| {
-                transaction_info
-                    .read_snapshot
-                    .table_config()
-                    .isolation_level()
-            });
+                    &isolation_level,
+                ) =>
+            {
+                IsolationLevel::SnapshotIsolation
+            }
+            _ => isolation_level,
+        };
 
         Self {
             txn_info: transaction_info,
@@ -679,6 +678,22 @@ mod tests {
         concurrent: Vec<Action>,
         actions: Vec<Action>,
         read_whole_table: bool,
+    ) -> Result<(), CommitConflictError> {
+        let summary = WinningCommitSummary {
+            actions: concurrent,
+            commit_info: None,
+        };
+        execute_test_with_isolation(setup, reads, summary, actions, read_whole_table, None)
+    }
+
+    #[cfg(feature = "datafusion")]
+    fn execute_test_with_isolation(
+        setup: Option<Vec<Action>>,
+        reads: Option<Expr>,
+        summary: WinningCommitSummary,
+        actions: Vec<Action>,
+        read_whole_table: bool,
+        isolation_level: Option<IsolationLevel>,
     ) -> Result<(), CommitConflictError> {
         use crate::table::state::DeltaTableState;
 
@@ -686,14 +701,49 @@ mod tests {
         let state = DeltaTableState::from_actions(setup_actions).unwrap();
         let snapshot = state.snapshot();
         let transaction_info = TransactionInfo::new(snapshot, reads, &actions, read_whole_table);
-        let summary = WinningCommitSummary {
-            actions: concurrent,
-            commit_info: None,
-        };
-        let checker = ConflictChecker::new(transaction_info, summary, None);
+        let checker = ConflictChecker::new(transaction_info, summary, None, isolation_level);
         checker.check_conflicts()
     }
 
+    #[tokio::test]
+    #[cfg(feature = "datafusion")]
+    async fn test_commit_isolation_level() {
+        // a concurrent blind append only conflicts with a transaction reading the table when
+        // the transaction is serializable
+        let summary = || WinningCommitSummary {
+            actions: vec![tu::create_add_action("appended", true, get_stats(1, 10))],
+            commit_info: Some(CommitInfo {
+                is_blind_append: Some(true),
+                ..Default::default()
+            }),
+        };
+        let actions = || vec![tu::create_add_action("written", true, get_stats(1, 10))];
+
+        // the table's default isolation level is serializable
+        let result = execute_test_with_isolation(None, None, summary(), actions(), true, None);
+        assert!(matches!(result, Err(CommitConflictError::ConcurrentAppend)));
+
+        let result = execute_test_with_isolation(
+            None,
+            None,
+            summary(),
+            actions(),
+            true,
+            Some(IsolationLevel::WriteSerializable),
+        );
+        assert!(result.is_ok());
+
+        let result = execute_test_with_isolation(
+            None,
+            None,
+            summary(),
+            actions(),
+            true,
+            Some(IsolationLevel::SnapshotIsolation),
+        );
+        assert!(result.is_ok());
+    }
+
     #[tokio::test]
     #[cfg(feature = "datafusion")]
     // tests adopted from https://github.com/delta-io/delta/blob/24c025128612a4ae02d0ad958621f928cda9a3ec/core/src/test/scala/org/apache/spark/sql/delta/OptimisticTransactionSuite.scala#L40-L94
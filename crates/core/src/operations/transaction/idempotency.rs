@@ -0,0 +1,73 @@
+//! Idempotency tokens of operations.
+//!
+//! Orchestrators retry tasks which failed, even if the failure happened after the task's
+//! commit was written, e.g. when the connection dropped while waiting for the response.
+//! Retrying a delete or an overwrite would then apply it twice. Operations given an
+//! idempotency token via
+//! [`CommitProperties::with_idempotency_token`](super::CommitProperties::with_idempotency_token)
+//! record it in the `commitInfo` under [`IDEMPOTENCY_TOKEN_KEY`]. Before executing, and again
+//! when committing, the last [`IDEMPOTENCY_LOOKBACK`] commits are checked for the token, and
+//! the operation fails with [`TransactionError::DuplicateOperation`] if it was already
+//! committed.
+//!
+//! # Example
+//! ```rust ignore
+//! let result = DeltaOps(table)
+//!     .delete()
+//!     .with_predicate(col("date").lt(lit("2024-01-01")))
+//!     .with_commit_properties(CommitProperties::default().with_idempotency_token(task_id))
+//!     .await;
+//! match result {
+//!     Err(DeltaTableError::Transaction {
+//!         source: TransactionError::DuplicateOperation { version, .. },
+//!     }) => println!("already deleted in version {version}"),
+//!     result => result?,
+//! };
+//! ````
+
+use super::TransactionError;
+use crate::errors::DeltaResult;
+use crate::kernel::{Action, CommitInfo};
+use crate::logstore::{get_actions, LogStore};
+
+/// Key of the idempotency token in the `commitInfo`
+pub const IDEMPOTENCY_TOKEN_KEY: &str = "idempotencyToken";
+
+/// Number of most recent commits checked for an idempotency token
+pub const IDEMPOTENCY_LOOKBACK: i64 = 100;
+
+/// The idempotency token recorded in `info`, if any
+pub(crate) fn idempotency_token(info: &CommitInfo) -> Option<&str> {
+    info.info
+        .get(IDEMPOTENCY_TOKEN_KEY)
+        .and_then(|token| token.as_str())
+}
+
+/// Fail if one of the last [`IDEMPOTENCY_LOOKBACK`] commits up to `version` recorded `token`
+pub(crate) async fn check_idempotency_token(
+    log_store: &dyn LogStore,
+    token: &str,
+    version: i64,
+) -> DeltaResult<()> {
+    let earliest = (version - IDEMPOTENCY_LOOKBACK + 1).max(0);
+    for version in (earliest..=version).rev() {
+        let Some(commit) = log_store.read_commit_entry(version).await? else {
+            continue;
+        };
+        let committed = get_actions(version, commit)
+            .await?
+            .iter()
+            .any(|action| match action {
+                Action::CommitInfo(info) => idempotency_token(info) == Some(token),
+                _ => false,
+            });
+        if committed {
+            return Err(TransactionError::DuplicateOperation {
+                token: token.to_string(),
+                version,
+            }
+            .into());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,119 @@
+//! Lineage of the data written by a commit.
+//!
+//! Data governance tooling tracks which tables and columns the data of a table was derived
+//! from. Writers describe the inputs of an operation with a [`LineageContext`], passed via
+//! [`CommitProperties::with_lineage`](super::CommitProperties::with_lineage), which is stored in
+//! the `commitInfo` of the commit under [`LINEAGE_KEY`]. Readers of the table history get it
+//! back with [`CommitInfo::lineage`](crate::kernel::CommitInfo::lineage).
+//!
+//! Lineage is a hint recorded by the writer, it is not validated against the written data.
+//!
+//! # Example
+//! ```rust ignore
+//! let lineage = LineageContext::new()
+//!     .with_source(LineageSource::new("s3://bucket/orders").with_version(42))
+//!     .with_column("total", ["s3://bucket/orders.amount", "s3://bucket/orders.tax"])
+//!     .with_job_id("nightly-aggregation")
+//!     .with_code_version("a1b2c3d");
+//! let table = DeltaOps(table)
+//!     .write(batches)
+//!     .with_commit_properties(CommitProperties::default().with_lineage(lineage))
+//!     .await?;
+//! ````
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Key of the lineage in the `commitInfo`
+pub const LINEAGE_KEY: &str = "lineage";
+
+/// A table read by the operation creating a commit
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineageSource {
+    /// Uri or name of the source table
+    pub table: String,
+    /// Version of the source table that was read, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<i64>,
+    /// Columns read from the source table, empty if unknown
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<String>,
+}
+
+impl LineageSource {
+    /// A source reading from `table`
+    pub fn new(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            ..Default::default()
+        }
+    }
+
+    /// The version of the source table that was read
+    pub fn with_version(mut self, version: i64) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// The columns read from the source table
+    pub fn with_columns(mut self, columns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.columns = columns.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+/// Where the data written by a commit came from
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineageContext {
+    /// Tables read by the operation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<LineageSource>,
+    /// Source columns each written column was derived from, as `<table>.<column>`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub columns: BTreeMap<String, Vec<String>>,
+    /// Id of the job or pipeline run which created the commit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    /// Version of the code which created the commit, e.g. a git revision
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_version: Option<String>,
+}
+
+impl LineageContext {
+    /// Create an empty lineage context
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a table read by the operation
+    pub fn with_source(mut self, source: LineageSource) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Record that the written `column` was derived from the `sources` columns
+    pub fn with_column(
+        mut self,
+        column: impl Into<String>,
+        sources: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.columns
+            .insert(column.into(), sources.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Set the id of the job or pipeline run
+    pub fn with_job_id(mut self, job_id: impl Into<String>) -> Self {
+        self.job_id = Some(job_id.into());
+        self
+    }
+
+    /// Set the version of the code creating the commit
+    pub fn with_code_version(mut self, code_version: impl Into<String>) -> Self {
+        self.code_version = Some(code_version.into());
+        self
+    }
+}
@@ -64,8 +64,27 @@
 //!       │                               │                   
 //!       └───────────────────────────────┘                   
 //!</pre>
+//!
+//! Applications coordinating a commit with an external system, e.g. a Kafka transaction or a
+//! database, can use the [`PreparedCommit`] as the prepare phase of a two-phase commit: once the
+//! commit marker is written, the external system is prepared, and depending on its outcome the
+//! prepared commit is either awaited to finalize it or aborted with [`PreparedCommit::abort`].
+//!
+//! # Example
+//! ```rust ignore
+//! let prepared = CommitBuilder::default()
+//!     .with_actions(actions)
+//!     .build(Some(table.snapshot()?), table.log_store(), operation)?
+//!     .into_prepared_commit_future()
+//!     .await?;
+//! match producer.commit_transaction().await {
+//!     Ok(()) => prepared.await?,
+//!     Err(_) => prepared.abort().await?,
+//! };
+//! ````
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use chrono::Utc;
 use conflict_checker::ConflictChecker;
@@ -73,23 +92,43 @@ use futures::future::BoxFuture;
 use object_store::path::Path;
 use object_store::{Error as ObjectStoreError, ObjectStore};
 use serde_json::Value;
+use tracing::{debug, warn};
 
 use self::conflict_checker::{CommitConflictError, TransactionInfo, WinningCommitSummary};
+use self::retry::RetryState;
 use crate::errors::DeltaTableError;
 use crate::kernel::{
-    Action, CommitInfo, EagerSnapshot, Metadata, Protocol, ReaderFeatures, WriterFeatures,
+    Action, CommitInfo, EagerSnapshot, Metadata, Protocol, ReaderFeatures, Txn, WriterFeatures,
 };
+use crate::logstore::compression::encode_commit;
 use crate::logstore::LogStoreRef;
+use crate::protocol::checkpoints::{checkpoint_after_commit, CheckpointSchedule};
 use crate::protocol::DeltaOperation;
 use crate::storage::ObjectStoreRetryExt;
-use crate::table::config::TableConfig;
+use crate::table::config::{IsolationLevel, TableConfig};
 use crate::table::state::DeltaTableState;
 use crate::{crate_version, DeltaResult};
 
+pub use self::idempotency::{IDEMPOTENCY_LOOKBACK, IDEMPOTENCY_TOKEN_KEY};
+pub use self::lineage::{LineageContext, LineageSource, LINEAGE_KEY};
+#[cfg(feature = "commit-webhooks")]
+pub use self::observer::WebhookObserver;
+pub use self::observer::{CommitEvent, CommitObserver, OBSERVER_TIMEOUT};
+pub(crate) use self::protocol::with_writer_feature;
 pub use self::protocol::INSTANCE as PROTOCOL;
+pub use self::retry::{CommitRetryBudget, CommitRetryTelemetry};
+pub use self::signing::{
+    read_signature, verify_signature, verify_signatures, write_signature, CommitSignature,
+    CommitSigner, CommitVerifier,
+};
 
 mod conflict_checker;
+mod idempotency;
+mod lineage;
+mod observer;
 mod protocol;
+pub mod retry;
+mod signing;
 #[cfg(feature = "datafusion")]
 mod state;
 #[cfg(test)]
@@ -97,6 +136,8 @@ pub(crate) mod test_utils;
 
 const DELTA_LOG_FOLDER: &str = "_delta_log";
 const DEFAULT_RETRIES: usize = 15;
+/// Commits larger than this are known to cause problems for some readers
+const DEFAULT_COMMIT_SIZE_WARNING: usize = 100 * 1024 * 1024;
 
 /// Error raised while commititng transaction
 #[derive(thiserror::Error, Debug)]
@@ -128,6 +169,41 @@ pub enum TransactionError {
     #[error("Failed to commit transaction: {0}")]
     MaxCommitAttempts(i32),
 
+    /// The time budget for retrying the commit ran out
+    #[error(
+        "Failed to commit transaction within its retry budget: {} attempts in {:?}",
+        telemetry.attempts,
+        telemetry.elapsed
+    )]
+    RetryBudgetExhausted {
+        /// How the commit used its retry budget
+        telemetry: CommitRetryTelemetry,
+    },
+
+    /// The serialized commit exceeds the configured maximum commit size
+    #[error("Commit of {size} bytes exceeds the maximum commit size of {max_size} bytes")]
+    CommitTooLarge {
+        /// Size of the serialized commit in bytes
+        size: usize,
+        /// Configured maximum commit size in bytes
+        max_size: usize,
+    },
+
+    /// An operation with the same idempotency token was already committed
+    #[error(
+        "Operation with idempotency token '{token}' was already committed in version {version}"
+    )]
+    DuplicateOperation {
+        /// The idempotency token of the operation
+        token: String,
+        /// The version which committed the operation
+        version: i64,
+    },
+
+    /// The table was opened in read-only mode
+    #[error("Table was opened in read-only mode, commits are not allowed")]
+    ReadOnlyTable,
+
     /// The transaction includes Remove action with data change but Delta table is append-only
     #[error(
         "The transaction includes Remove action with data change but Delta table is append-only"
@@ -298,14 +374,32 @@ impl CommitData {
 /// Enable controling commit behaviour and modifying metadata that is written during a commit.
 pub struct CommitProperties {
     pub(crate) app_metadata: HashMap<String, Value>,
-    max_retries: usize,
+    retry_budget: CommitRetryBudget,
+    commit_size_warning: Option<usize>,
+    max_commit_size: Option<usize>,
+    create_checkpoint: bool,
+    observers: Vec<Arc<dyn CommitObserver>>,
+    signer: Option<Arc<dyn CommitSigner>>,
+    app_transactions: Vec<Txn>,
+    isolation_level: Option<IsolationLevel>,
+    lineage: Option<LineageContext>,
+    idempotency_token: Option<String>,
 }
 
 impl Default for CommitProperties {
     fn default() -> Self {
         Self {
             app_metadata: Default::default(),
-            max_retries: DEFAULT_RETRIES,
+            retry_budget: CommitRetryBudget::default(),
+            commit_size_warning: Some(DEFAULT_COMMIT_SIZE_WARNING),
+            max_commit_size: None,
+            create_checkpoint: false,
+            observers: Vec::new(),
+            signer: None,
+            app_transactions: Vec::new(),
+            isolation_level: None,
+            lineage: None,
+            idempotency_token: None,
         }
     }
 }
@@ -319,13 +413,105 @@ impl CommitProperties {
         self.app_metadata = HashMap::from_iter(metadata);
         self
     }
+
+    /// Log a warning when the serialized commit is larger than `bytes`
+    ///
+    /// Defaults to 100 MiB, pass `None` to disable the warning.
+    pub fn with_commit_size_warning(mut self, bytes: Option<usize>) -> Self {
+        self.commit_size_warning = bytes;
+        self
+    }
+
+    /// Fail the commit before uploading it when the serialized commit is larger than `bytes`
+    ///
+    /// Operations that don't change the data of the table, like optimize, will split
+    /// their actions into several commits to stay below this limit.
+    pub fn with_max_commit_size(mut self, bytes: usize) -> Self {
+        self.max_commit_size = Some(bytes);
+        self
+    }
+
+    /// Create a checkpoint after committing a version which is a multiple of the table's
+    /// `delta.checkpointInterval`
+    pub fn with_create_checkpoint(mut self, create_checkpoint: bool) -> Self {
+        self.create_checkpoint = create_checkpoint;
+        self
+    }
+
+    /// Notify `observer` after the commit was successfully written to the log
+    pub fn with_commit_observer(mut self, observer: Arc<dyn CommitObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    /// Store a detached signature of the commit file created with `signer`
+    pub fn with_commit_signer(mut self, signer: Arc<dyn CommitSigner>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Record the progress of an application in the commit using a `txn` action
+    pub fn with_application_transaction(mut self, txn: Txn) -> Self {
+        self.app_transactions.push(txn);
+        self
+    }
+
+    /// Limit the attempts and time spent retrying the commit after conflicts
+    pub fn with_retry_budget(mut self, budget: CommitRetryBudget) -> Self {
+        self.retry_budget = budget;
+        self
+    }
+
+    /// Check conflicts with concurrent commits at `isolation_level` rather than the isolation
+    /// level configured for the table
+    pub fn with_isolation_level(mut self, isolation_level: IsolationLevel) -> Self {
+        self.isolation_level = Some(isolation_level);
+        self
+    }
+
+    /// Record where the committed data came from in the `commitInfo`, see [`LineageContext`]
+    pub fn with_lineage(mut self, lineage: LineageContext) -> Self {
+        self.lineage = Some(lineage);
+        self
+    }
+
+    /// Record `token` in the `commitInfo` and fail if an operation with the same token was
+    /// already committed, see [`idempotency`]
+    pub fn with_idempotency_token(mut self, token: impl Into<String>) -> Self {
+        self.idempotency_token = Some(token.into());
+        self
+    }
+
+    /// Fail if an operation with the configured idempotency token was committed in one of the
+    /// recent versions up to `version`
+    pub async fn check_idempotency(
+        &self,
+        log_store: &LogStoreRef,
+        version: i64,
+    ) -> DeltaResult<()> {
+        match &self.idempotency_token {
+            Some(token) => {
+                idempotency::check_idempotency_token(log_store.as_ref(), token, version).await
+            }
+            None => Ok(()),
+        }
+    }
 }
 
 impl From<CommitProperties> for CommitBuilder {
     fn from(value: CommitProperties) -> Self {
         CommitBuilder {
-            max_retries: value.max_retries,
+            retry_budget: value.retry_budget,
             app_metadata: value.app_metadata,
+            commit_size_warning: value.commit_size_warning,
+            max_commit_size: value.max_commit_size,
+            create_checkpoint: value.create_checkpoint,
+            observers: value.observers,
+            signer: value.signer,
+            app_transactions: value.app_transactions,
+            isolation_level: value.isolation_level,
+            lineage: value.lineage,
+            idempotency_token: value.idempotency_token,
             ..Default::default()
         }
     }
@@ -335,7 +521,16 @@ impl From<CommitProperties> for CommitBuilder {
 pub struct CommitBuilder {
     actions: Vec<Action>,
     app_metadata: HashMap<String, Value>,
-    max_retries: usize,
+    retry_budget: CommitRetryBudget,
+    commit_size_warning: Option<usize>,
+    max_commit_size: Option<usize>,
+    create_checkpoint: bool,
+    observers: Vec<Arc<dyn CommitObserver>>,
+    signer: Option<Arc<dyn CommitSigner>>,
+    app_transactions: Vec<Txn>,
+    isolation_level: Option<IsolationLevel>,
+    lineage: Option<LineageContext>,
+    idempotency_token: Option<String>,
 }
 
 impl Default for CommitBuilder {
@@ -343,7 +538,16 @@ impl Default for CommitBuilder {
         CommitBuilder {
             actions: Vec::new(),
             app_metadata: HashMap::new(),
-            max_retries: DEFAULT_RETRIES,
+            retry_budget: CommitRetryBudget::default(),
+            commit_size_warning: Some(DEFAULT_COMMIT_SIZE_WARNING),
+            max_commit_size: None,
+            create_checkpoint: false,
+            observers: Vec::new(),
+            signer: None,
+            app_transactions: Vec::new(),
+            isolation_level: None,
+            lineage: None,
+            idempotency_token: None,
         }
     }
 }
@@ -361,6 +565,69 @@ impl<'a> CommitBuilder {
         self
     }
 
+    /// Log a warning when the serialized commit is larger than `bytes`
+    pub fn with_commit_size_warning(mut self, bytes: Option<usize>) -> Self {
+        self.commit_size_warning = bytes;
+        self
+    }
+
+    /// Fail the commit before uploading it when the serialized commit is larger than `bytes`
+    pub fn with_max_commit_size(mut self, bytes: usize) -> Self {
+        self.max_commit_size = Some(bytes);
+        self
+    }
+
+    /// Create a checkpoint after committing a version which is a multiple of the table's
+    /// `delta.checkpointInterval`
+    pub fn with_create_checkpoint(mut self, create_checkpoint: bool) -> Self {
+        self.create_checkpoint = create_checkpoint;
+        self
+    }
+
+    /// Notify `observer` after the commit was successfully written to the log
+    pub fn with_commit_observer(mut self, observer: Arc<dyn CommitObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    /// Store a detached signature of the commit file created with `signer`
+    pub fn with_commit_signer(mut self, signer: Arc<dyn CommitSigner>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Record the progress of an application in the commit using a `txn` action
+    pub fn with_application_transaction(mut self, txn: Txn) -> Self {
+        self.app_transactions.push(txn);
+        self
+    }
+
+    /// Limit the attempts and time spent retrying the commit after conflicts
+    pub fn with_retry_budget(mut self, budget: CommitRetryBudget) -> Self {
+        self.retry_budget = budget;
+        self
+    }
+
+    /// Check conflicts with concurrent commits at `isolation_level` rather than the isolation
+    /// level configured for the table
+    pub fn with_isolation_level(mut self, isolation_level: IsolationLevel) -> Self {
+        self.isolation_level = Some(isolation_level);
+        self
+    }
+
+    /// Record where the committed data came from in the `commitInfo`, see [`LineageContext`]
+    pub fn with_lineage(mut self, lineage: LineageContext) -> Self {
+        self.lineage = Some(lineage);
+        self
+    }
+
+    /// Record `token` in the `commitInfo` and fail if an operation with the same token was
+    /// already committed, see [`idempotency`]
+    pub fn with_idempotency_token(mut self, token: impl Into<String>) -> Self {
+        self.idempotency_token = Some(token.into());
+        self
+    }
+
     /// Prepare a Commit operation using the configured builder
     pub fn build(
         self,
@@ -368,11 +635,33 @@ impl<'a> CommitBuilder {
         log_store: LogStoreRef,
         operation: DeltaOperation,
     ) -> Result<PreCommit<'a>, CommitBuilderError> {
-        let data = CommitData::new(self.actions, operation, self.app_metadata)?;
+        let mut actions = self.actions;
+        actions.extend(self.app_transactions.into_iter().map(Action::Txn));
+        let mut app_metadata = self.app_metadata;
+        if let Some(lineage) = self.lineage {
+            app_metadata.insert(
+                LINEAGE_KEY.to_string(),
+                serde_json::to_value(lineage).expect("lineage only contains strings and numbers"),
+            );
+        }
+        if let Some(token) = &self.idempotency_token {
+            app_metadata.insert(
+                IDEMPOTENCY_TOKEN_KEY.to_string(),
+                Value::from(token.as_str()),
+            );
+        }
+        let data = CommitData::new(actions, operation, app_metadata)?;
         Ok(PreCommit {
             log_store,
             table_data,
-            max_retries: self.max_retries,
+            retry_budget: self.retry_budget,
+            commit_size_warning: self.commit_size_warning,
+            max_commit_size: self.max_commit_size,
+            create_checkpoint: self.create_checkpoint,
+            observers: self.observers,
+            signer: self.signer,
+            isolation_level: self.isolation_level,
+            idempotency_token: self.idempotency_token,
             data,
         })
     }
@@ -383,7 +672,14 @@ pub struct PreCommit<'a> {
     log_store: LogStoreRef,
     table_data: Option<&'a dyn TableReference>,
     data: CommitData,
-    max_retries: usize,
+    retry_budget: CommitRetryBudget,
+    commit_size_warning: Option<usize>,
+    max_commit_size: Option<usize>,
+    create_checkpoint: bool,
+    observers: Vec<Arc<dyn CommitObserver>>,
+    signer: Option<Arc<dyn CommitSigner>>,
+    isolation_level: Option<IsolationLevel>,
+    idempotency_token: Option<String>,
 }
 
 impl<'a> std::future::IntoFuture for PreCommit<'a> {
@@ -405,36 +701,85 @@ impl<'a> PreCommit<'a> {
         Box::pin(async move {
             if let Some(table_reference) = this.table_data {
                 PROTOCOL.can_commit(table_reference, &this.data.actions, &this.data.operation)?;
+                let read_version = table_reference.eager_snapshot().map(|s| s.version());
+                if let (Some(token), Some(read_version)) = (&this.idempotency_token, read_version) {
+                    idempotency::check_idempotency_token(
+                        this.log_store.as_ref(),
+                        token,
+                        read_version,
+                    )
+                    .await?;
+                }
             }
 
             // Serialize all actions that are part of this log entry.
             let log_entry = this.data.get_bytes()?;
+            check_commit_size(
+                log_entry.len(),
+                this.commit_size_warning,
+                this.max_commit_size,
+            )?;
 
             // Write delta log entry as temporary file to storage. For the actual commit,
             // the temporary file is moved (atomic rename) to the delta log folder within `commit` function.
             let token = uuid::Uuid::new_v4().to_string();
             let file_name = format!("_commit_{token}.json.tmp");
             let path = Path::from_iter([DELTA_LOG_FOLDER, &file_name]);
-            this.log_store.object_store().put(&path, log_entry).await?;
+            this.log_store
+                .log_object_store()
+                .put(&path, encode_commit(this.table_data, log_entry.clone())?)
+                .await?;
 
             Ok(PreparedCommit {
                 path,
                 log_store: this.log_store,
                 table_data: this.table_data,
-                max_retries: this.max_retries,
+                retry_budget: this.retry_budget,
+                create_checkpoint: this.create_checkpoint,
+                observers: this.observers,
+                signer: this.signer.map(|signer| (signer, log_entry)),
+                isolation_level: this.isolation_level,
+                idempotency_token: this.idempotency_token,
                 data: this.data,
             })
         })
     }
 }
 
+/// Validate the size of a serialized commit before it is uploaded
+fn check_commit_size(
+    size: usize,
+    warning: Option<usize>,
+    max_size: Option<usize>,
+) -> Result<(), TransactionError> {
+    if let Some(max_size) = max_size {
+        if size > max_size {
+            return Err(TransactionError::CommitTooLarge { size, max_size });
+        }
+    }
+    if let Some(warning) = warning {
+        if size > warning {
+            warn!(
+                "Commit of {size} bytes exceeds {warning} bytes, some readers may fail to read it"
+            );
+        }
+    }
+    Ok(())
+}
+
 /// Represents a inflight commit with a temporary commit marker on the log store
 pub struct PreparedCommit<'a> {
     path: Path,
     log_store: LogStoreRef,
     data: CommitData,
     table_data: Option<&'a dyn TableReference>,
-    max_retries: usize,
+    retry_budget: CommitRetryBudget,
+    create_checkpoint: bool,
+    observers: Vec<Arc<dyn CommitObserver>>,
+    /// The signer and the serialized commit to sign once its version is known
+    signer: Option<(Arc<dyn CommitSigner>, bytes::Bytes)>,
+    isolation_level: Option<IsolationLevel>,
+    idempotency_token: Option<String>,
 }
 
 impl<'a> PreparedCommit<'a> {
@@ -442,13 +787,66 @@ impl<'a> PreparedCommit<'a> {
     pub fn path(&self) -> &Path {
         &self.path
     }
+
+    /// Data that will be written by the commit
+    pub fn data(&self) -> &CommitData {
+        &self.data
+    }
+
+    /// Discard the commit by deleting its temporary commit file, leaving the table unchanged
+    pub async fn abort(self) -> DeltaResult<()> {
+        self.log_store
+            .log_object_store()
+            .delete_with_retries(&self.path, 15)
+            .await?;
+        Ok(())
+    }
 }
 
 impl<'a> std::future::IntoFuture for PreparedCommit<'a> {
     type Output = DeltaResult<FinalizedCommit>;
     type IntoFuture = BoxFuture<'a, Self::Output>;
 
-    fn into_future(self) -> Self::IntoFuture {
+    fn into_future(mut self) -> Self::IntoFuture {
+        let log_store = self.log_store.clone();
+        let observers = std::mem::take(&mut self.observers);
+        let signer = self.signer.take();
+        let checkpoint = self
+            .table_data
+            .and_then(|table| table.eager_snapshot())
+            .filter(|_| self.create_checkpoint)
+            .map(|snapshot| CheckpointSchedule::from_config(snapshot.table_config()));
+        let commit = self.write_commit();
+
+        Box::pin(async move {
+            let commit = commit.await?;
+            if let Some((signer, log_entry)) = signer {
+                // the commit is already durable, a missing signature is detected on verification
+                if let Err(err) =
+                    write_signature(&log_store, signer.as_ref(), commit.version, &log_entry).await
+                {
+                    warn!("failed to sign commit {}: {err}", commit.version);
+                }
+            }
+            if let Some(schedule) = checkpoint {
+                // the commit is already durable, a missing checkpoint only slows down loading
+                if let Err(err) =
+                    checkpoint_after_commit(&log_store, commit.version, schedule).await
+                {
+                    warn!("failed to checkpoint version {}: {err}", commit.version);
+                }
+            }
+            if !observers.is_empty() {
+                let event = CommitEvent::new(log_store.root_uri(), commit.version, &commit.data);
+                observer::notify_observers(&observers, event, OBSERVER_TIMEOUT).await;
+            }
+            Ok(commit)
+        })
+    }
+}
+
+impl<'a> PreparedCommit<'a> {
+    fn write_commit(self) -> BoxFuture<'a, DeltaResult<FinalizedCommit>> {
         let this = self;
 
         Box::pin(async move {
@@ -459,6 +857,10 @@ impl<'a> std::future::IntoFuture for PreparedCommit<'a> {
                 return Ok(FinalizedCommit {
                     version: 0,
                     data: this.data,
+                    retry_telemetry: CommitRetryTelemetry {
+                        attempts: 1,
+                        ..Default::default()
+                    },
                 });
             }
 
@@ -472,23 +874,49 @@ impl<'a> std::future::IntoFuture for PreparedCommit<'a> {
                         "Expected an instance of EagerSnapshot".to_owned(),
                     ))?;
 
-            let mut attempt_number = 1;
-            while attempt_number <= this.max_retries {
-                let version = read_snapshot.version() + attempt_number as i64;
+            let mut retry = RetryState::new(this.retry_budget.clone());
+            let mut version = read_snapshot.version() + 1;
+            while retry.next_attempt().await {
                 match this.log_store.write_commit_entry(version, tmp_commit).await {
                     Ok(()) => {
+                        let retry_telemetry = retry.telemetry();
+                        debug!(
+                            version,
+                            attempts = retry_telemetry.attempts,
+                            conflicts = retry_telemetry.conflicting_versions.len(),
+                            elapsed_ms = retry_telemetry.elapsed.as_millis() as u64,
+                            "committed transaction"
+                        );
                         return Ok(FinalizedCommit {
                             version,
                             data: this.data,
-                        })
+                            retry_telemetry,
+                        });
                     }
-                    Err(TransactionError::VersionAlreadyExists(version)) => {
+                    Err(TransactionError::VersionAlreadyExists(version_exists)) => {
+                        retry.conflict(version_exists);
                         let summary = WinningCommitSummary::try_new(
                             this.log_store.as_ref(),
-                            version - 1,
-                            version,
+                            version_exists - 1,
+                            version_exists,
                         )
                         .await?;
+                        let duplicate = summary.commit_info.as_ref().is_some_and(|info| {
+                            this.idempotency_token.is_some()
+                                && idempotency::idempotency_token(info)
+                                    == this.idempotency_token.as_deref()
+                        });
+                        if duplicate {
+                            this.log_store
+                                .log_object_store()
+                                .delete_with_retries(tmp_commit, 15)
+                                .await?;
+                            return Err(TransactionError::DuplicateOperation {
+                                token: this.idempotency_token.clone().unwrap_or_default(),
+                                version: version_exists,
+                            }
+                            .into());
+                        }
                         let transaction_info = TransactionInfo::try_new(
                             read_snapshot,
                             this.data.operation.read_predicate(),
@@ -500,14 +928,15 @@ impl<'a> std::future::IntoFuture for PreparedCommit<'a> {
                             transaction_info,
                             summary,
                             Some(&this.data.operation),
+                            this.isolation_level.clone(),
                         );
                         match conflict_checker.check_conflicts() {
                             Ok(_) => {
-                                attempt_number += 1;
+                                version = version_exists + 1;
                             }
                             Err(err) => {
                                 this.log_store
-                                    .object_store()
+                                    .log_object_store()
                                     .delete_with_retries(&tmp_commit, 15)
                                     .await?;
                                 return Err(TransactionError::CommitConflict(err).into());
@@ -516,7 +945,7 @@ impl<'a> std::future::IntoFuture for PreparedCommit<'a> {
                     }
                     Err(err) => {
                         this.log_store
-                            .object_store()
+                            .log_object_store()
                             .delete_with_retries(&tmp_commit, 15)
                             .await?;
                         return Err(err.into());
@@ -524,7 +953,25 @@ impl<'a> std::future::IntoFuture for PreparedCommit<'a> {
                 }
             }
 
-            Err(TransactionError::MaxCommitAttempts(this.max_retries as i32).into())
+            let telemetry = retry.telemetry();
+            warn!(
+                attempts = telemetry.attempts,
+                conflicts = telemetry.conflicting_versions.len(),
+                elapsed_ms = telemetry.elapsed.as_millis() as u64,
+                "giving up on committing transaction"
+            );
+            this.log_store
+                .log_object_store()
+                .delete_with_retries(tmp_commit, 15)
+                .await?;
+            if retry.attempts_exhausted() {
+                Err(
+                    TransactionError::MaxCommitAttempts(this.retry_budget.max_attempts as i32)
+                        .into(),
+                )
+            } else {
+                Err(TransactionError::RetryBudgetExhausted { telemetry }.into())
+            }
         })
     }
 }
@@ -535,6 +982,8 @@ pub struct FinalizedCommit {
     pub version: i64,
     /// The data that was comitted to the log store
     pub data: CommitData,
+    /// How the commit used its retry budget
+    pub retry_telemetry: CommitRetryTelemetry,
 }
 
 impl FinalizedCommit {
@@ -547,10 +996,16 @@ impl FinalizedCommit {
     pub fn data(&self) -> &CommitData {
         &self.data
     }
+
+    /// How the commit used its retry budget
+    pub fn retry_telemetry(&self) -> &CommitRetryTelemetry {
+        &self.retry_telemetry
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
     use std::{collections::HashMap, sync::Arc};
 
     use self::test_utils::init_table_actions;
@@ -579,6 +1034,50 @@ mod tests {
         assert_eq!(actions.len(), lines.len())
     }
 
+    #[tokio::test]
+    async fn test_read_only_table_rejects_commits() {
+        let table = crate::DeltaTableBuilder::from_uri("../test/tests/data/simple_table")
+            .read_only()
+            .load()
+            .await
+            .unwrap();
+        let err = CommitBuilder::default()
+            .with_actions(vec![])
+            .build(
+                Some(table.snapshot().unwrap()),
+                table.log_store(),
+                DeltaOperation::Write {
+                    mode: crate::protocol::SaveMode::Append,
+                    partition_by: None,
+                    predicate: None,
+                },
+            )
+            .unwrap()
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err,
+            DeltaTableError::Transaction {
+                source: TransactionError::ReadOnlyTable
+            }
+        ));
+    }
+
+    #[test]
+    fn test_check_commit_size() {
+        assert!(check_commit_size(100, None, None).is_ok());
+        assert!(check_commit_size(100, Some(10), None).is_ok());
+        assert!(check_commit_size(100, None, Some(100)).is_ok());
+        assert!(matches!(
+            check_commit_size(101, None, Some(100)),
+            Err(TransactionError::CommitTooLarge {
+                size: 101,
+                max_size: 100
+            })
+        ));
+    }
+
     #[tokio::test]
     async fn test_try_commit_transaction() {
         let store = Arc::new(InMemory::new());
@@ -602,4 +1101,227 @@ mod tests {
         // succeeds for next version
         log_store.write_commit_entry(1, &tmp_path).await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_prepared_commit_abort() {
+        let table = crate::DeltaOps::new_in_memory()
+            .create()
+            .with_column(
+                "id",
+                crate::kernel::DataType::Primitive(crate::kernel::PrimitiveType::Long),
+                true,
+                None,
+            )
+            .await
+            .unwrap();
+        let append = || DeltaOperation::Write {
+            mode: crate::protocol::SaveMode::Append,
+            partition_by: None,
+            predicate: None,
+        };
+        let store = table.log_store().object_store();
+
+        let prepared = CommitBuilder::default()
+            .build(Some(table.snapshot().unwrap()), table.log_store(), append())
+            .unwrap()
+            .into_prepared_commit_future()
+            .await
+            .unwrap();
+        let path = prepared.path().clone();
+        assert!(store.head(&path).await.is_ok());
+        prepared.abort().await.unwrap();
+        assert!(store.head(&path).await.is_err());
+        assert_eq!(table.log_store().get_latest_version(0).await.unwrap(), 0);
+
+        let prepared = CommitBuilder::default()
+            .build(Some(table.snapshot().unwrap()), table.log_store(), append())
+            .unwrap()
+            .into_prepared_commit_future()
+            .await
+            .unwrap();
+        let path = prepared.path().clone();
+        assert_eq!(prepared.await.unwrap().version(), 1);
+        assert!(store.head(&path).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_token() {
+        let mut table = crate::DeltaOps::new_in_memory()
+            .create()
+            .with_column(
+                "id",
+                crate::kernel::DataType::Primitive(crate::kernel::PrimitiveType::Long),
+                true,
+                None,
+            )
+            .await
+            .unwrap();
+        let stale = table.snapshot().unwrap().clone();
+        let append = || DeltaOperation::Write {
+            mode: crate::protocol::SaveMode::Append,
+            partition_by: None,
+            predicate: None,
+        };
+        let properties = CommitProperties::default().with_idempotency_token("task-1");
+        let commit = CommitBuilder::from(properties.clone())
+            .build(Some(&stale), table.log_store(), append())
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(commit.version(), 1);
+        table.load().await.unwrap();
+        assert_eq!(
+            table.history(Some(1)).await.unwrap()[0].info[IDEMPOTENCY_TOKEN_KEY],
+            Value::from("task-1")
+        );
+
+        let is_duplicate = |err: DeltaTableError| {
+            matches!(
+                err,
+                DeltaTableError::Transaction {
+                    source: TransactionError::DuplicateOperation { version: 1, .. }
+                }
+            )
+        };
+        assert!(is_duplicate(
+            properties
+                .check_idempotency(&table.log_store(), table.version())
+                .await
+                .unwrap_err()
+        ));
+        // retried against the current snapshot
+        let err = CommitBuilder::from(properties.clone())
+            .build(Some(table.snapshot().unwrap()), table.log_store(), append())
+            .unwrap()
+            .await
+            .err()
+            .unwrap();
+        assert!(is_duplicate(err));
+        // retried against the snapshot the first attempt read
+        let err = CommitBuilder::from(properties)
+            .build(Some(&stale), table.log_store(), append())
+            .unwrap()
+            .await
+            .err()
+            .unwrap();
+        assert!(is_duplicate(err));
+
+        let other = CommitProperties::default().with_idempotency_token("task-2");
+        other
+            .check_idempotency(&table.log_store(), table.version())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_commit_lineage() {
+        let mut table = crate::DeltaOps::new_in_memory()
+            .create()
+            .with_column(
+                "id",
+                crate::kernel::DataType::Primitive(crate::kernel::PrimitiveType::Long),
+                true,
+                None,
+            )
+            .await
+            .unwrap();
+        let lineage = LineageContext::new()
+            .with_source(
+                LineageSource::new("memory:///orders")
+                    .with_version(3)
+                    .with_columns(["order_id"]),
+            )
+            .with_column("id", ["memory:///orders.order_id"])
+            .with_job_id("job-1")
+            .with_code_version("abc123");
+        let properties = CommitProperties::default()
+            .with_metadata([("owner".to_string(), Value::from("team"))])
+            .with_lineage(lineage.clone());
+        CommitBuilder::from(properties)
+            .build(
+                Some(table.snapshot().unwrap()),
+                table.log_store(),
+                DeltaOperation::Write {
+                    mode: crate::protocol::SaveMode::Append,
+                    partition_by: None,
+                    predicate: None,
+                },
+            )
+            .unwrap()
+            .await
+            .unwrap();
+
+        table.load().await.unwrap();
+        let history = table.history(None).await.unwrap();
+        assert_eq!(history[0].lineage().unwrap(), Some(lineage));
+        assert_eq!(history[0].info["owner"], Value::from("team"));
+        assert_eq!(history[1].lineage().unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_retry_budget() {
+        let table = crate::DeltaOps::new_in_memory()
+            .create()
+            .with_column(
+                "id",
+                crate::kernel::DataType::Primitive(crate::kernel::PrimitiveType::Long),
+                true,
+                None,
+            )
+            .await
+            .unwrap();
+        let append = || DeltaOperation::Write {
+            mode: crate::protocol::SaveMode::Append,
+            partition_by: None,
+            predicate: None,
+        };
+
+        let first = CommitBuilder::default()
+            .build(Some(table.snapshot().unwrap()), table.log_store(), append())
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(first.retry_telemetry().attempts, 1);
+
+        // commits against the stale snapshot conflict with version 1
+        let second = CommitBuilder::default()
+            .build(Some(table.snapshot().unwrap()), table.log_store(), append())
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(second.version(), 2);
+        assert_eq!(second.retry_telemetry().attempts, 2);
+        assert_eq!(second.retry_telemetry().conflicting_versions, vec![1]);
+
+        let err = CommitBuilder::default()
+            .with_retry_budget(CommitRetryBudget::new(2))
+            .build(Some(table.snapshot().unwrap()), table.log_store(), append())
+            .unwrap()
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err,
+            DeltaTableError::Transaction {
+                source: TransactionError::MaxCommitAttempts(2)
+            }
+        ));
+
+        let err = CommitBuilder::default()
+            .with_retry_budget(CommitRetryBudget::new(10).with_max_elapsed(Duration::ZERO))
+            .build(Some(table.snapshot().unwrap()), table.log_store(), append())
+            .unwrap()
+            .await
+            .err()
+            .unwrap();
+        match err {
+            DeltaTableError::Transaction {
+                source: TransactionError::RetryBudgetExhausted { telemetry },
+            } => {
+                assert_eq!(telemetry.attempts, 1);
+                assert_eq!(telemetry.conflicting_versions, vec![1]);
+            }
+            other => panic!("expected an exhausted retry budget, got {other:?}"),
+        }
+    }
 }
@@ -0,0 +1,225 @@
+//! Notify external systems about successful commits.
+//!
+//! Observers registered via [`CommitProperties::with_commit_observer`](super::CommitProperties::with_commit_observer)
+//! are invoked once a commit was written to the log. This allows data catalogs and orchestrators
+//! to react to table changes without polling the log. Failing observers never fail the commit,
+//! since the commit is already durable at the time observers are notified. Observers are notified
+//! concurrently, and one that doesn't respond within [`OBSERVER_TIMEOUT`] is abandoned.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::time::Duration;
+
+use chrono::Utc;
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::warn;
+
+use super::CommitData;
+use crate::DeltaResult;
+
+/// Summary of a successful commit handed to [`CommitObserver`]s
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitEvent {
+    /// Uri of the table root
+    pub table_uri: String,
+    /// The version created by the commit
+    pub version: i64,
+    /// Name of the operation, e.g. `WRITE` or `OPTIMIZE`
+    pub operation: String,
+    /// Parameters of the operation
+    pub operation_parameters: HashMap<String, Value>,
+    /// Metrics reported by the operation, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_metrics: Option<Value>,
+    /// Time the commit was observed, in milliseconds since the epoch
+    pub timestamp: i64,
+}
+
+impl CommitEvent {
+    pub(crate) fn new(table_uri: String, version: i64, data: &CommitData) -> Self {
+        Self {
+            table_uri,
+            version,
+            operation: data.operation.name().to_string(),
+            operation_parameters: data.operation.operation_parameters().unwrap_or_default(),
+            operation_metrics: data.app_metadata.get("operationMetrics").cloned(),
+            timestamp: Utc::now().timestamp_millis(),
+        }
+    }
+}
+
+/// Receives a notification for every successful commit
+#[async_trait::async_trait]
+pub trait CommitObserver: Debug + Send + Sync {
+    /// Called after the commit described by `event` was written to the log
+    async fn on_commit(&self, event: &CommitEvent) -> DeltaResult<()>;
+}
+
+/// Time an observer may take to handle a commit before the committing writer moves on
+pub const OBSERVER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Notify all observers about a commit, logging failures instead of returning them
+pub(crate) async fn notify_observers(
+    observers: &[std::sync::Arc<dyn CommitObserver>],
+    event: CommitEvent,
+    timeout: Duration,
+) {
+    let event = &event;
+    join_all(observers.iter().map(|observer| async move {
+        match tokio::time::timeout(timeout, observer.on_commit(event)).await {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => warn!(
+                "commit observer {observer:?} failed for version {}: {err}",
+                event.version
+            ),
+            Err(_) => warn!(
+                "commit observer {observer:?} timed out after {timeout:?} for version {}",
+                event.version
+            ),
+        }
+    }))
+    .await;
+}
+
+/// Observer that POSTs each [`CommitEvent`] as JSON to a webhook url
+#[cfg(feature = "commit-webhooks")]
+#[derive(Debug, Clone)]
+pub struct WebhookObserver {
+    url: url::Url,
+    headers: reqwest::header::HeaderMap,
+    timeout: Duration,
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "commit-webhooks")]
+impl WebhookObserver {
+    /// Create a new observer posting to `url`, giving up on requests after ten seconds
+    pub fn new(url: url::Url) -> Self {
+        Self {
+            url,
+            headers: Default::default(),
+            timeout: Duration::from_secs(10),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Set the time after which a request to the webhook is given up
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Add a header sent with every request, e.g. for authorization
+    pub fn with_header(
+        mut self,
+        name: reqwest::header::HeaderName,
+        value: reqwest::header::HeaderValue,
+    ) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+}
+
+#[cfg(feature = "commit-webhooks")]
+#[async_trait::async_trait]
+impl CommitObserver for WebhookObserver {
+    async fn on_commit(&self, event: &CommitEvent) -> DeltaResult<()> {
+        self.client
+            .post(self.url.clone())
+            .headers(self.headers.clone())
+            .timeout(self.timeout)
+            .json(event)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| crate::DeltaTableError::GenericError {
+                source: Box::new(err),
+            })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::kernel::Action;
+    use crate::protocol::{DeltaOperation, SaveMode};
+
+    #[derive(Debug, Default)]
+    struct RecordingObserver {
+        events: Mutex<Vec<CommitEvent>>,
+    }
+
+    #[async_trait::async_trait]
+    impl CommitObserver for RecordingObserver {
+        async fn on_commit(&self, event: &CommitEvent) -> DeltaResult<()> {
+            self.events.lock().unwrap().push(event.clone());
+            Ok(())
+        }
+    }
+
+    #[derive(Debug)]
+    struct FailingObserver;
+
+    #[derive(Debug)]
+    struct HangingObserver;
+
+    #[async_trait::async_trait]
+    impl CommitObserver for HangingObserver {
+        async fn on_commit(&self, _event: &CommitEvent) -> DeltaResult<()> {
+            futures::future::pending().await
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl CommitObserver for FailingObserver {
+        async fn on_commit(&self, _event: &CommitEvent) -> DeltaResult<()> {
+            Err(crate::DeltaTableError::Generic("unavailable".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_notify_observers() {
+        let data = CommitData::new(
+            Vec::<Action>::new(),
+            DeltaOperation::Write {
+                mode: SaveMode::Append,
+                partition_by: None,
+                predicate: None,
+            },
+            HashMap::from([(
+                "operationMetrics".to_string(),
+                serde_json::json!({"numFiles": 1}),
+            )]),
+        )
+        .unwrap();
+
+        let recording = Arc::new(RecordingObserver::default());
+        let observers: Vec<Arc<dyn CommitObserver>> = vec![
+            Arc::new(FailingObserver),
+            Arc::new(HangingObserver),
+            recording.clone(),
+        ];
+        notify_observers(
+            &observers,
+            CommitEvent::new("memory:///".into(), 3, &data),
+            Duration::from_millis(10),
+        )
+        .await;
+
+        let events = recording.events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].version, 3);
+        assert_eq!(events[0].operation, "WRITE");
+        assert_eq!(events[0].operation_parameters["mode"], "Append");
+        assert_eq!(
+            events[0].operation_metrics,
+            Some(serde_json::json!({"numFiles": 1}))
+        );
+    }
+}
@@ -4,7 +4,9 @@ use lazy_static::lazy_static;
 use once_cell::sync::Lazy;
 
 use super::{TableReference, TransactionError};
-use crate::kernel::{Action, DataType, EagerSnapshot, ReaderFeatures, Schema, WriterFeatures};
+use crate::kernel::{
+    Action, DataType, EagerSnapshot, Protocol, ReaderFeatures, Schema, WriterFeatures,
+};
 use crate::protocol::DeltaOperation;
 use crate::table::state::DeltaTableState;
 
@@ -44,6 +46,35 @@ lazy_static! {
     ]);
 }
 
+/// Writer features implied by a legacy (pre table features) writer version
+fn legacy_writer_features(min_writer_version: i32) -> HashSet<WriterFeatures> {
+    match min_writer_version {
+        2 => WRITER_V2.clone(),
+        3 => WRITER_V3.clone(),
+        4 => WRITER_V4.clone(),
+        5 => WRITER_V5.clone(),
+        6 => WRITER_V6.clone(),
+        _ => HashSet::new(),
+    }
+}
+
+/// Return a copy of `protocol` which lists `feature` as a required writer feature.
+///
+/// Legacy writer versions are upgraded to writer version 7, explicitly listing the
+/// features implied by the previous writer version.
+pub(crate) fn with_writer_feature(protocol: &Protocol, feature: WriterFeatures) -> Protocol {
+    let mut protocol = protocol.clone();
+    if protocol.min_writer_version < 7 {
+        protocol.writer_features = Some(legacy_writer_features(protocol.min_writer_version));
+        protocol.min_writer_version = 7;
+    }
+    protocol
+        .writer_features
+        .get_or_insert_with(HashSet::new)
+        .insert(feature);
+    protocol
+}
+
 pub struct ProtocolChecker {
     reader_features: HashSet<ReaderFeatures>,
     writer_features: HashSet<WriterFeatures>,
@@ -61,6 +92,18 @@ impl ProtocolChecker {
         }
     }
 
+    /// A protocol checker additionally supporting deletion vectors.
+    ///
+    /// Operations which purge deletion vectors by rewriting the affected files can safely write
+    /// to tables using deletion vectors, even though delta-rs does not create them.
+    pub fn with_deletion_vectors(&self) -> Self {
+        let mut reader_features = self.reader_features.clone();
+        reader_features.insert(ReaderFeatures::DeletionVectors);
+        let mut writer_features = self.writer_features.clone();
+        writer_features.insert(WriterFeatures::DeletionVectors);
+        Self::new(reader_features, writer_features)
+    }
+
     pub fn default_reader_version(&self) -> i32 {
         1
     }
@@ -131,6 +174,13 @@ impl ProtocolChecker {
 
     /// Check if delta-rs can write to the given delta table.
     pub fn can_write_to(&self, snapshot: &dyn TableReference) -> Result<(), TransactionError> {
+        if snapshot
+            .eager_snapshot()
+            .is_some_and(|snapshot| snapshot.load_config().read_only)
+        {
+            return Err(TransactionError::ReadOnlyTable);
+        }
+
         // NOTE: writers must always support all required reader features
         self.can_read_from(snapshot)?;
 
@@ -162,7 +212,10 @@ impl ProtocolChecker {
         actions: &[Action],
         operation: &DeltaOperation,
     ) -> Result<(), TransactionError> {
-        self.can_write_to(snapshot)?;
+        match operation {
+            DeltaOperation::Reorg { .. } => self.with_deletion_vectors().can_write_to(snapshot)?,
+            _ => self.can_write_to(snapshot)?,
+        }
 
         // https://github.com/delta-io/delta/blob/master/PROTOCOL.md#append-only-tables
         let append_only_enabled = if snapshot.protocol().min_writer_version < 2 {
@@ -213,6 +266,12 @@ pub static INSTANCE: Lazy<ProtocolChecker> = Lazy::new(|| {
     let mut writer_features = HashSet::new();
     writer_features.insert(WriterFeatures::AppendOnly);
     writer_features.insert(WriterFeatures::TimestampWithoutTimezone);
+    writer_features.insert(WriterFeatures::DomainMetadata);
+    #[cfg(feature = "log-compression")]
+    {
+        reader_features.insert(ReaderFeatures::LogCompression);
+        writer_features.insert(WriterFeatures::LogCompression);
+    }
     #[cfg(feature = "datafusion")]
     {
         writer_features.insert(WriterFeatures::Invariants);
@@ -539,4 +598,48 @@ mod tests {
         assert!(checker_7.can_read_from(eager_7).is_ok());
         assert!(checker_7.can_write_to(eager_7).is_ok());
     }
+
+    #[test]
+    fn test_can_commit_deletion_vectors() {
+        let actions = vec![
+            Action::Protocol(Protocol {
+                min_reader_version: 3,
+                min_writer_version: 7,
+                reader_features: Some(HashSet::from_iter([ReaderFeatures::DeletionVectors])),
+                writer_features: Some(HashSet::from_iter([WriterFeatures::DeletionVectors])),
+            }),
+            create_metadata_action(None, Some(HashMap::new())),
+        ];
+        let snapshot = DeltaTableState::from_actions(actions).unwrap();
+        let eager = snapshot.snapshot();
+
+        let checker = ProtocolChecker::new(HashSet::new(), HashSet::new());
+        assert!(checker.can_write_to(eager).is_err());
+        assert!(checker.with_deletion_vectors().can_write_to(eager).is_ok());
+
+        let reorg = DeltaOperation::Reorg {
+            deletion_vector_threshold: 0.5,
+        };
+        let update = DeltaOperation::Update { predicate: None };
+        assert!(checker.can_commit(eager, &[], &reorg).is_ok());
+        assert!(checker.can_commit(eager, &[], &update).is_err());
+    }
+
+    #[test]
+    fn test_with_writer_feature() {
+        let legacy = Protocol {
+            min_reader_version: 1,
+            min_writer_version: 3,
+            ..Default::default()
+        };
+        let upgraded = with_writer_feature(&legacy, WriterFeatures::DomainMetadata);
+        assert_eq!(upgraded.min_reader_version, 1);
+        assert_eq!(upgraded.min_writer_version, 7);
+        let mut expected = WRITER_V3.clone();
+        expected.insert(WriterFeatures::DomainMetadata);
+        assert_eq!(upgraded.writer_features, Some(expected));
+
+        let upgraded_again = with_writer_feature(&upgraded, WriterFeatures::DomainMetadata);
+        assert_eq!(upgraded_again, upgraded);
+    }
 }
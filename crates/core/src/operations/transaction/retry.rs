@@ -0,0 +1,207 @@
+//! Budgets for retrying commits.
+//!
+//! A commit which loses the race for a version to a concurrent writer is checked for conflicts
+//! and retried against the next version. By default a commit is attempted up to 15 times, back
+//! to back. Latency sensitive writers rather bound the total time spent committing, and writers
+//! contending for the same table back off between attempts so they do not retry in lockstep.
+//! A [`CommitRetryBudget`] configures both, see
+//! [`CommitProperties::with_retry_budget`](super::CommitProperties::with_retry_budget).
+//!
+//! Every commit reports how it used its budget in a [`CommitRetryTelemetry`], available from
+//! the [`FinalizedCommit`](super::FinalizedCommit) of a successful commit and from the
+//! [`TransactionError::RetryBudgetExhausted`](super::TransactionError::RetryBudgetExhausted)
+//! error of a commit which ran out of budget.
+//!
+//! # Example
+//! ```rust ignore
+//! let budget = CommitRetryBudget::new(50)
+//!     .with_max_elapsed(Duration::from_secs(2))
+//!     .with_backoff(Duration::from_millis(10), Duration::from_millis(200));
+//! let (table, _) = DeltaOps(table)
+//!     .write(batches)
+//!     .with_commit_properties(CommitProperties::default().with_retry_budget(budget))
+//!     .await?;
+//! ````
+
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::DEFAULT_RETRIES;
+
+/// Limits on the attempts and time spent committing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitRetryBudget {
+    /// Maximum number of attempts, including the first one
+    pub max_attempts: usize,
+    /// Time after which no further attempt is started, if any
+    pub max_elapsed: Option<Duration>,
+    /// Backoff before the first retry, doubled for every further retry
+    pub initial_backoff: Duration,
+    /// Upper bound of the backoff between two attempts
+    pub max_backoff: Duration,
+}
+
+impl Default for CommitRetryBudget {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_RETRIES,
+            max_elapsed: None,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+        }
+    }
+}
+
+impl CommitRetryBudget {
+    /// Attempt to commit up to `max_attempts` times, without a time limit or backoff
+    pub fn new(max_attempts: usize) -> Self {
+        Self {
+            max_attempts,
+            ..Default::default()
+        }
+    }
+
+    /// Don't start another attempt once `max_elapsed` has passed since the first one
+    pub fn with_max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+
+    /// Back off between attempts, starting at `initial` and doubling up to `max`. The actual
+    /// backoff is jittered between half and all of it
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Backoff before the `retry`th retry, without jitter
+    fn nominal_backoff(&self, retry: usize) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1).min(31) as u32);
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// Backoff before the `retry`th retry
+    fn backoff(&self, retry: usize) -> Duration {
+        let nominal = self.nominal_backoff(retry);
+        if nominal.is_zero() {
+            return nominal;
+        }
+        nominal.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+    }
+}
+
+/// How a commit used its [`CommitRetryBudget`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitRetryTelemetry {
+    /// Number of attempts made, including the last one
+    pub attempts: usize,
+    /// Versions committed concurrently, for which this commit had to be retried
+    pub conflicting_versions: Vec<i64>,
+    /// Total time spent backing off between attempts
+    pub backoff: Duration,
+    /// Total time spent committing
+    pub elapsed: Duration,
+}
+
+/// Tracks the budget of a commit in progress
+#[derive(Debug)]
+pub(crate) struct RetryState {
+    budget: CommitRetryBudget,
+    start: Instant,
+    telemetry: CommitRetryTelemetry,
+}
+
+impl RetryState {
+    pub(crate) fn new(budget: CommitRetryBudget) -> Self {
+        Self {
+            budget,
+            start: Instant::now(),
+            telemetry: CommitRetryTelemetry::default(),
+        }
+    }
+
+    /// Record the start of an attempt. Returns false, without recording an attempt, if the
+    /// budget does not allow another one
+    pub(crate) async fn next_attempt(&mut self) -> bool {
+        if self.telemetry.attempts >= self.budget.max_attempts {
+            return false;
+        }
+        if self.telemetry.attempts > 0 {
+            let backoff = self.budget.backoff(self.telemetry.attempts);
+            if let Some(max_elapsed) = self.budget.max_elapsed {
+                if self.start.elapsed() + backoff >= max_elapsed {
+                    return false;
+                }
+            }
+            if !backoff.is_zero() {
+                tokio::time::sleep(backoff).await;
+                self.telemetry.backoff += backoff;
+            }
+        }
+        self.telemetry.attempts += 1;
+        true
+    }
+
+    /// Record that `version` was committed concurrently
+    pub(crate) fn conflict(&mut self, version: i64) {
+        self.telemetry.conflicting_versions.push(version);
+    }
+
+    /// Whether the attempts, rather than the time, of the budget were exhausted
+    pub(crate) fn attempts_exhausted(&self) -> bool {
+        self.telemetry.attempts >= self.budget.max_attempts
+    }
+
+    /// The telemetry of the commit so far
+    pub(crate) fn telemetry(&self) -> CommitRetryTelemetry {
+        CommitRetryTelemetry {
+            elapsed: self.start.elapsed(),
+            ..self.telemetry.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nominal_backoff() {
+        let budget = CommitRetryBudget::new(10)
+            .with_backoff(Duration::from_millis(10), Duration::from_millis(50));
+        assert_eq!(budget.nominal_backoff(1), Duration::from_millis(10));
+        assert_eq!(budget.nominal_backoff(2), Duration::from_millis(20));
+        assert_eq!(budget.nominal_backoff(3), Duration::from_millis(40));
+        assert_eq!(budget.nominal_backoff(4), Duration::from_millis(50));
+        assert_eq!(budget.nominal_backoff(100), Duration::from_millis(50));
+
+        let jittered = budget.backoff(2);
+        assert!(jittered >= Duration::from_millis(10) && jittered <= Duration::from_millis(20));
+        assert_eq!(CommitRetryBudget::default().backoff(3), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_retry_state() {
+        let mut state = RetryState::new(CommitRetryBudget::new(2));
+        assert!(state.next_attempt().await);
+        state.conflict(4);
+        assert!(state.next_attempt().await);
+        assert!(!state.next_attempt().await);
+        assert!(state.attempts_exhausted());
+        let telemetry = state.telemetry();
+        assert_eq!(telemetry.attempts, 2);
+        assert_eq!(telemetry.conflicting_versions, vec![4]);
+
+        let budget = CommitRetryBudget::new(10).with_max_elapsed(Duration::ZERO);
+        let mut state = RetryState::new(budget);
+        assert!(state.next_attempt().await);
+        assert!(!state.next_attempt().await);
+        assert!(!state.attempts_exhausted());
+    }
+}
@@ -0,0 +1,316 @@
+//! Detached signatures for commit files.
+//!
+//! Commits created with a [`CommitSigner`] registered via
+//! [`CommitProperties::with_commit_signer`](super::CommitProperties::with_commit_signer) get a
+//! signature object stored next to the log at `_delta_log/_signatures/<version>.sig`. The
+//! signed message is the commit version followed by the raw bytes of the commit file, so
+//! commit files can neither be modified nor swapped between versions without invalidating
+//! their signature.
+//!
+//! Tables loaded with a [`CommitVerifier`] registered via
+//! [`DeltaTableBuilder::with_commit_verifier`](crate::table::builder::DeltaTableBuilder::with_commit_verifier)
+//! verify the signatures of all commits replayed while loading. Signing and verification are
+//! delegated to the traits, so keys can be kept in a KMS or HSM.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+use futures::{StreamExt, TryStreamExt};
+use object_store::path::Path;
+use object_store::ObjectStore;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::logstore::LogStoreRef;
+
+const SIGNATURE_DIR: &str = "_signatures";
+const VERIFY_CONCURRENCY: usize = 8;
+
+/// Errors raised while verifying commit signatures
+#[derive(thiserror::Error, Debug)]
+enum SigningError {
+    #[error("Commit {0} is not signed")]
+    MissingSignature(i64),
+
+    #[error("Commit {0} has an invalid signature, the log may have been tampered with")]
+    InvalidSignature(i64),
+
+    #[error("Signature of commit {version} could not be decoded: {message}")]
+    MalformedSignature { version: i64, message: String },
+
+    #[error("Commit {0} does not exist")]
+    MissingCommit(i64),
+}
+
+impl From<SigningError> for DeltaTableError {
+    fn from(err: SigningError) -> Self {
+        DeltaTableError::GenericError {
+            source: Box::new(err),
+        }
+    }
+}
+
+/// A detached signature of a single commit file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitSignature {
+    /// Version of the signed commit
+    pub version: i64,
+    /// Identifier of the key used to sign the commit
+    pub key_id: String,
+    /// Name of the signing algorithm
+    pub algorithm: String,
+    /// The base64 encoded signature
+    pub signature: String,
+}
+
+impl CommitSignature {
+    /// The decoded signature bytes
+    pub fn signature_bytes(&self) -> DeltaResult<Vec<u8>> {
+        STANDARD.decode(&self.signature).map_err(|err| {
+            SigningError::MalformedSignature {
+                version: self.version,
+                message: err.to_string(),
+            }
+            .into()
+        })
+    }
+}
+
+/// Signs commit files when they are written
+#[async_trait::async_trait]
+pub trait CommitSigner: Debug + Send + Sync {
+    /// Identifier of the signing key, stored with the signature to select the verification key
+    fn key_id(&self) -> String;
+
+    /// Name of the signing algorithm, e.g. `RSASSA_PSS_SHA_256`
+    fn algorithm(&self) -> String;
+
+    /// Sign `message`
+    async fn sign(&self, message: &[u8]) -> DeltaResult<Vec<u8>>;
+}
+
+/// Verifies commit signatures when a table is loaded
+#[async_trait::async_trait]
+pub trait CommitVerifier: Debug + Send + Sync {
+    /// Whether `signature` is a valid signature of `message`
+    async fn verify(&self, signature: &CommitSignature, message: &[u8]) -> DeltaResult<bool>;
+}
+
+fn signature_path(version: i64) -> Path {
+    Path::from_iter(["_delta_log", SIGNATURE_DIR, &format!("{version:020}.sig")])
+}
+
+/// The signed message, binding the commit contents to its version
+fn signed_message(version: i64, commit: &[u8]) -> Vec<u8> {
+    let mut message = format!("{version}\n").into_bytes();
+    message.extend_from_slice(commit);
+    message
+}
+
+/// Sign the commit `version` with contents `commit` and store the signature object.
+///
+/// Besides signing new commits, this can be used to sign the existing commits of a table
+/// before enabling verification.
+pub async fn write_signature(
+    log_store: &LogStoreRef,
+    signer: &dyn CommitSigner,
+    version: i64,
+    commit: &[u8],
+) -> DeltaResult<()> {
+    let signature = signer.sign(&signed_message(version, commit)).await?;
+    let signature = CommitSignature {
+        version,
+        key_id: signer.key_id(),
+        algorithm: signer.algorithm(),
+        signature: STANDARD.encode(signature),
+    };
+    log_store
+        .log_object_store()
+        .put(
+            &signature_path(version),
+            Bytes::from(serde_json::to_vec(&signature)?),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Read the signature object of commit `version`, if it exists
+pub async fn read_signature(
+    log_store: &LogStoreRef,
+    version: i64,
+) -> DeltaResult<Option<CommitSignature>> {
+    match log_store
+        .log_object_store()
+        .get(&signature_path(version))
+        .await
+    {
+        Ok(result) => Ok(Some(serde_json::from_slice(&result.bytes().await?)?)),
+        Err(object_store::Error::NotFound { .. }) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Verify the signature of commit `version`, failing if it is missing or invalid
+pub async fn verify_signature(
+    log_store: &LogStoreRef,
+    verifier: &dyn CommitVerifier,
+    version: i64,
+) -> DeltaResult<()> {
+    let commit = log_store
+        .read_commit_entry(version)
+        .await?
+        .ok_or(SigningError::MissingCommit(version))?;
+    let signature = read_signature(log_store, version)
+        .await?
+        .ok_or(SigningError::MissingSignature(version))?;
+    // the version is part of the message, but check it to give a more helpful error
+    if signature.version != version
+        || !verifier
+            .verify(&signature, &signed_message(version, &commit))
+            .await?
+    {
+        return Err(SigningError::InvalidSignature(version).into());
+    }
+    Ok(())
+}
+
+/// Verify the signatures of all `versions`
+pub async fn verify_signatures(
+    log_store: &LogStoreRef,
+    verifier: Arc<dyn CommitVerifier>,
+    versions: impl IntoIterator<Item = i64>,
+) -> DeltaResult<()> {
+    futures::stream::iter(versions)
+        .map(|version| {
+            let verifier = verifier.clone();
+            async move { verify_signature(log_store, verifier.as_ref(), version).await }
+        })
+        .buffer_unordered(VERIFY_CONCURRENCY)
+        .try_collect::<Vec<_>>()
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::{Action, Add, DataType, PrimitiveType};
+    use crate::operations::transaction::{CommitBuilder, CommitProperties};
+    use crate::operations::DeltaOps;
+    use crate::protocol::{DeltaOperation, SaveMode};
+    use crate::storage::commit_uri_from_version;
+    use crate::table::builder::DeltaTableBuilder;
+
+    /// Toy signature reversing the message, good enough to detect modifications
+    #[derive(Debug)]
+    struct ReversingSigner;
+
+    #[async_trait::async_trait]
+    impl CommitSigner for ReversingSigner {
+        fn key_id(&self) -> String {
+            "test-key".to_string()
+        }
+
+        fn algorithm(&self) -> String {
+            "REVERSE".to_string()
+        }
+
+        async fn sign(&self, message: &[u8]) -> DeltaResult<Vec<u8>> {
+            Ok(message.iter().rev().copied().collect())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl CommitVerifier for ReversingSigner {
+        async fn verify(&self, signature: &CommitSignature, message: &[u8]) -> DeltaResult<bool> {
+            Ok(signature.key_id == self.key_id()
+                && signature.signature_bytes()? == self.sign(message).await?)
+        }
+    }
+
+    fn load_verified(log_store: &LogStoreRef) -> DeltaTableBuilder {
+        DeltaTableBuilder::from_uri("memory:///")
+            .with_storage_backend(
+                log_store.object_store(),
+                url::Url::parse("memory:///").unwrap(),
+            )
+            .with_commit_verifier(Arc::new(ReversingSigner))
+    }
+
+    #[tokio::test]
+    async fn test_sign_and_verify_commits() {
+        let table = DeltaOps::new_in_memory()
+            .create()
+            .with_column("id", DataType::Primitive(PrimitiveType::Long), true, None)
+            .await
+            .unwrap();
+        let log_store = table.log_store();
+
+        // the initial commit was not signed
+        assert!(load_verified(&log_store).load().await.is_err());
+
+        let add = Action::Add(Add {
+            path: "part-0.parquet".to_string(),
+            size: 10,
+            data_change: true,
+            ..Default::default()
+        });
+        let operation = DeltaOperation::Write {
+            mode: SaveMode::Append,
+            partition_by: None,
+            predicate: None,
+        };
+        let version = CommitBuilder::from(
+            CommitProperties::default().with_commit_signer(Arc::new(ReversingSigner)),
+        )
+        .with_actions(vec![add])
+        .build(
+            Some(table.snapshot().unwrap()),
+            log_store.clone(),
+            operation,
+        )
+        .unwrap()
+        .await
+        .unwrap()
+        .version();
+        assert_eq!(version, 1);
+
+        let signature = read_signature(&log_store, 1).await.unwrap().unwrap();
+        assert_eq!(signature.key_id, "test-key");
+        assert_eq!(signature.algorithm, "REVERSE");
+        verify_signatures(&log_store, Arc::new(ReversingSigner), [1])
+            .await
+            .unwrap();
+
+        // sign the initial commit after the fact
+        let commit = log_store.read_commit_entry(0).await.unwrap().unwrap();
+        write_signature(&log_store, &ReversingSigner, 0, &commit)
+            .await
+            .unwrap();
+        let verified = load_verified(&log_store).load().await.unwrap();
+        assert_eq!(verified.version(), 1);
+
+        // tamper with the last commit
+        let store = log_store.object_store();
+        let path = commit_uri_from_version(1);
+        let mut commit = store
+            .get(&path)
+            .await
+            .unwrap()
+            .bytes()
+            .await
+            .unwrap()
+            .to_vec();
+        commit.extend_from_slice(b"\n");
+        store.put(&path, commit.into()).await.unwrap();
+
+        assert!(verify_signature(&log_store, &ReversingSigner, 1)
+            .await
+            .is_err());
+        assert!(load_verified(&log_store).load().await.is_err());
+    }
+}
@@ -11,7 +11,7 @@ use datafusion_common::Column;
 use datafusion_expr::Expr;
 use itertools::Itertools;
 use object_store::ObjectStore;
-use parquet::arrow::async_reader::{ParquetObjectReader, ParquetRecordBatchStreamBuilder};
+use parquet::arrow::async_reader::ParquetRecordBatchStreamBuilder;
 
 use crate::delta_datafusion::{
     get_null_of_arrow_type, logical_expr_to_physical_expr, to_correct_scalar_value,
@@ -19,6 +19,7 @@ use crate::delta_datafusion::{
 };
 use crate::errors::DeltaResult;
 use crate::kernel::{Add, EagerSnapshot};
+use crate::storage::footer_cache::{CachingParquetReader, ParquetFooterCache};
 use crate::table::state::DeltaTableState;
 
 impl DeltaTableState {
@@ -36,7 +37,8 @@ impl DeltaTableState {
             .max_by_key(|obj| obj.modification_time)
         {
             let file_meta = add.try_into()?;
-            let file_reader = ParquetObjectReader::new(object_store, file_meta);
+            let file_reader =
+                CachingParquetReader::new(object_store, file_meta, ParquetFooterCache::global());
             let file_schema = ParquetRecordBatchStreamBuilder::new(file_reader)
                 .await?
                 .build()?
@@ -239,7 +241,7 @@ impl PruningStatistics for EagerSnapshot {
     /// return the number of containers (e.g. row groups) being
     /// pruned with these statistics
     fn num_containers(&self) -> usize {
-        self.files_count()
+        self.files_count().unwrap_or_default()
     }
 
     /// return the number of null values for the named column as an
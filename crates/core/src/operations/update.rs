@@ -45,7 +45,7 @@ use serde::Serialize;
 use super::transaction::PROTOCOL;
 use super::write::write_execution_plan;
 use super::{
-    datafusion_utils::Expression,
+    datafusion_utils::{drain_plan, Expression},
     transaction::{CommitBuilder, CommitProperties},
 };
 use crate::delta_datafusion::{
@@ -79,6 +79,8 @@ pub struct UpdateBuilder {
     /// safe_cast determines how data types that do not match the underlying table are handled
     /// By default an error is returned
     safe_cast: bool,
+    /// Don't rewrite files or commit. Just determine which files and rows would be updated
+    dry_run: bool,
 }
 
 #[derive(Default, Serialize, Debug)]
@@ -96,6 +98,12 @@ pub struct UpdateMetrics {
     pub execution_time_ms: u64,
     /// Time taken to scan the files for matches.
     pub scan_time_ms: u64,
+    /// Was this a dry run
+    #[serde(skip)]
+    pub dry_run: bool,
+    /// Files that would be rewritten, only reported for dry runs
+    #[serde(skip)]
+    pub files_to_remove: Vec<String>,
 }
 
 impl UpdateBuilder {
@@ -110,6 +118,7 @@ impl UpdateBuilder {
             writer_properties: None,
             commit_properties: CommitProperties::default(),
             safe_cast: false,
+            dry_run: false,
         }
     }
 
@@ -129,6 +138,22 @@ impl UpdateBuilder {
         self
     }
 
+    /// Perform the update expressions of all `updates`, a map of column to expression, e.g.
+    /// the SET clause of a SQL UPDATE statement
+    pub fn with_updates<I, S, E>(mut self, updates: I) -> Self
+    where
+        I: IntoIterator<Item = (S, E)>,
+        S: Into<DeltaColumn>,
+        E: Into<Expression>,
+    {
+        self.updates.extend(
+            updates
+                .into_iter()
+                .map(|(column, expression)| (column.into().into(), expression.into())),
+        );
+        self
+    }
+
     /// The Datafusion session state to use
     pub fn with_session_state(mut self, state: SessionState) -> Self {
         self.state = Some(state);
@@ -160,6 +185,12 @@ impl UpdateBuilder {
         self.safe_cast = safe_cast;
         self
     }
+
+    /// Only determine which files and rows would be updated, without modifying the table
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -172,6 +203,7 @@ async fn execute(
     writer_properties: Option<WriterProperties>,
     mut commit_properties: CommitProperties,
     safe_cast: bool,
+    dry_run: bool,
 ) -> DeltaResult<((Vec<Action>, i64, Option<DeltaOperation>), UpdateMetrics)> {
     // Validate the predicate and update expressions.
     //
@@ -287,6 +319,28 @@ async fn execute(
         },
     ));
 
+    if dry_run {
+        drain_plan(count_plan.clone(), &state).await?;
+        let count_metrics = count_plan.metrics().unwrap();
+        metrics.num_updated_rows = count_metrics
+            .sum_by_name("num_updated_rows")
+            .map(|m| m.as_usize())
+            .unwrap_or(0);
+        metrics.num_copied_rows = count_metrics
+            .sum_by_name("num_copied_rows")
+            .map(|m| m.as_usize())
+            .unwrap_or(0);
+        metrics.dry_run = true;
+        metrics.num_removed_files = candidates.candidates.len();
+        metrics.files_to_remove = candidates
+            .candidates
+            .into_iter()
+            .map(|add| add.path)
+            .collect();
+        metrics.execution_time_ms = Instant::now().duration_since(exec_start).as_millis() as u64;
+        return Ok(((Vec::new(), version, None), metrics));
+    }
+
     // Perform another projection but instead calculate updated values based on
     // the predicate value.  If the predicate is true then evalute the user
     // provided expression otherwise return the original column value
@@ -358,6 +412,7 @@ async fn execute(
         writer_properties,
         safe_cast,
         None,
+        None,
     )
     .await?;
 
@@ -437,6 +492,9 @@ impl std::future::IntoFuture for UpdateBuilder {
         Box::pin(async move {
             PROTOCOL.check_append_only(&this.snapshot.snapshot)?;
             PROTOCOL.can_write_to(&this.snapshot.snapshot)?;
+            this.commit_properties
+                .check_idempotency(&this.log_store, this.snapshot.version())
+                .await?;
 
             let state = this.state.unwrap_or_else(|| {
                 let session: SessionContext = DeltaSessionContext::default().into();
@@ -456,6 +514,7 @@ impl std::future::IntoFuture for UpdateBuilder {
                 this.writer_properties,
                 this.commit_properties,
                 this.safe_cast,
+                this.dry_run,
             )
             .await?;
 
@@ -491,6 +550,7 @@ mod tests {
     use datafusion::assert_batches_sorted_eq;
     use datafusion::prelude::*;
     use serde_json::json;
+    use std::collections::HashMap;
     use std::sync::Arc;
 
     async fn setup_table(partitions: Option<Vec<&str>>) -> DeltaTable {
@@ -563,7 +623,7 @@ mod tests {
 
         let table = write_batch(table, batch).await;
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 1);
+        assert_eq!(table.get_files_count().unwrap(), 1);
 
         let (table, metrics) = DeltaOps(table)
             .update()
@@ -572,7 +632,7 @@ mod tests {
             .unwrap();
 
         assert_eq!(table.version(), 2);
-        assert_eq!(table.get_files_count(), 1);
+        assert_eq!(table.get_files_count().unwrap(), 1);
         assert_eq!(metrics.num_added_files, 1);
         assert_eq!(metrics.num_removed_files, 1);
         assert_eq!(metrics.num_updated_rows, 4);
@@ -592,6 +652,108 @@ mod tests {
         assert_batches_sorted_eq!(&expected, &actual);
     }
 
+    #[tokio::test]
+    async fn test_update_assignments() {
+        let schema = get_arrow_schema(&None);
+        let table = setup_table(None).await;
+
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(arrow::array::StringArray::from(vec!["A", "B", "A", "A"])),
+                Arc::new(arrow::array::Int32Array::from(vec![1, 10, 10, 100])),
+                Arc::new(arrow::array::StringArray::from(vec![
+                    "2021-02-02",
+                    "2021-02-02",
+                    "2021-02-02",
+                    "2021-02-02",
+                ])),
+            ],
+        )
+        .unwrap();
+
+        let table = write_batch(table, batch).await;
+        let (table, metrics) = DeltaOps(table)
+            .update()
+            .with_predicate(col("value").gt(lit(1)))
+            .with_updates(HashMap::from([
+                ("value", col("value") * lit(2)),
+                ("modified", lit("2023-05-14")),
+            ]))
+            .await
+            .unwrap();
+
+        assert_eq!(table.version(), 2);
+        assert_eq!(metrics.num_updated_rows, 3);
+        assert_eq!(metrics.num_copied_rows, 1);
+
+        let expected = vec![
+            "+----+-------+------------+",
+            "| id | value | modified   |",
+            "+----+-------+------------+",
+            "| A  | 1     | 2021-02-02 |",
+            "| A  | 20    | 2023-05-14 |",
+            "| A  | 200   | 2023-05-14 |",
+            "| B  | 20    | 2023-05-14 |",
+            "+----+-------+------------+",
+        ];
+        let actual = get_data(&table).await;
+        assert_batches_sorted_eq!(&expected, &actual);
+    }
+
+    #[tokio::test]
+    async fn test_update_dry_run() {
+        let schema = get_arrow_schema(&None);
+        let table = setup_table(None).await;
+
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(arrow::array::StringArray::from(vec!["A", "B", "A", "A"])),
+                Arc::new(arrow::array::Int32Array::from(vec![1, 10, 10, 100])),
+                Arc::new(arrow::array::StringArray::from(vec![
+                    "2021-02-02",
+                    "2021-02-02",
+                    "2021-02-02",
+                    "2021-02-02",
+                ])),
+            ],
+        )
+        .unwrap();
+
+        let table = write_batch(table, batch).await;
+        let files = table.get_files_iter().unwrap().collect::<Vec<_>>();
+
+        let (table, metrics) = DeltaOps(table)
+            .update()
+            .with_predicate(col("value").eq(lit(10)))
+            .with_update("modified", lit("2023-05-14"))
+            .with_dry_run(true)
+            .await
+            .unwrap();
+
+        assert_eq!(table.version(), 1);
+        assert!(metrics.dry_run);
+        assert_eq!(metrics.num_added_files, 0);
+        assert_eq!(metrics.num_removed_files, 1);
+        assert_eq!(metrics.files_to_remove, vec![files[0].to_string()]);
+        assert_eq!(metrics.num_updated_rows, 2);
+        assert_eq!(metrics.num_copied_rows, 2);
+
+        let expected = vec![
+            "+----+-------+------------+",
+            "| id | value | modified   |",
+            "+----+-------+------------+",
+            "| A  | 1     | 2021-02-02 |",
+            "| A  | 10    | 2021-02-02 |",
+            "| A  | 100   | 2021-02-02 |",
+            "| B  | 10    | 2021-02-02 |",
+            "+----+-------+------------+",
+        ];
+        let actual = get_data(&table).await;
+        assert_batches_sorted_eq!(&expected, &actual);
+    }
+
     #[tokio::test]
     async fn test_update_non_partition() {
         let schema = get_arrow_schema(&None);
@@ -617,7 +779,7 @@ mod tests {
 
         let table = write_batch(table, batch).await;
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 1);
+        assert_eq!(table.get_files_count().unwrap(), 1);
 
         let (table, metrics) = DeltaOps(table)
             .update()
@@ -627,7 +789,7 @@ mod tests {
             .unwrap();
 
         assert_eq!(table.version(), 2);
-        assert_eq!(table.get_files_count(), 1);
+        assert_eq!(table.get_files_count().unwrap(), 1);
         assert_eq!(metrics.num_added_files, 1);
         assert_eq!(metrics.num_removed_files, 1);
         assert_eq!(metrics.num_updated_rows, 2);
@@ -674,7 +836,7 @@ mod tests {
 
         let table = write_batch(table, batch.clone()).await;
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 2);
+        assert_eq!(table.get_files_count().unwrap(), 2);
 
         let (table, metrics) = DeltaOps(table)
             .update()
@@ -685,7 +847,7 @@ mod tests {
             .unwrap();
 
         assert_eq!(table.version(), 2);
-        assert_eq!(table.get_files_count(), 2);
+        assert_eq!(table.get_files_count().unwrap(), 2);
         assert_eq!(metrics.num_added_files, 1);
         assert_eq!(metrics.num_removed_files, 1);
         assert_eq!(metrics.num_updated_rows, 2);
@@ -709,7 +871,7 @@ mod tests {
         let table = setup_table(Some(vec!["modified"])).await;
         let table = write_batch(table, batch).await;
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 2);
+        assert_eq!(table.get_files_count().unwrap(), 2);
 
         let (table, metrics) = DeltaOps(table)
             .update()
@@ -724,7 +886,7 @@ mod tests {
             .unwrap();
 
         assert_eq!(table.version(), 2);
-        assert_eq!(table.get_files_count(), 3);
+        assert_eq!(table.get_files_count().unwrap(), 3);
         assert_eq!(metrics.num_added_files, 2);
         assert_eq!(metrics.num_removed_files, 1);
         assert_eq!(metrics.num_updated_rows, 1);
@@ -820,7 +982,7 @@ mod tests {
     async fn test_update_null() {
         let table = prepare_values_table().await;
         assert_eq!(table.version(), 0);
-        assert_eq!(table.get_files_count(), 1);
+        assert_eq!(table.get_files_count().unwrap(), 1);
 
         let (table, metrics) = DeltaOps(table)
             .update()
@@ -828,7 +990,7 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 1);
+        assert_eq!(table.get_files_count().unwrap(), 1);
         assert_eq!(metrics.num_added_files, 1);
         assert_eq!(metrics.num_removed_files, 1);
         assert_eq!(metrics.num_updated_rows, 5);
@@ -858,7 +1020,7 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 1);
+        assert_eq!(table.get_files_count().unwrap(), 1);
         assert_eq!(metrics.num_added_files, 1);
         assert_eq!(metrics.num_removed_files, 1);
         assert_eq!(metrics.num_updated_rows, 2);
@@ -894,7 +1056,7 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 1);
+        assert_eq!(table.get_files_count().unwrap(), 1);
         assert_eq!(metrics.num_added_files, 1);
         assert_eq!(metrics.num_removed_files, 1);
         assert_eq!(metrics.num_updated_rows, 2);
@@ -21,21 +21,30 @@
 //! let (table, metrics) = VacuumBuilder::new(table.object_store(). table.state).await?;
 //! ````
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::sync::Arc;
 
 use chrono::{Duration, Utc};
 use futures::future::BoxFuture;
+use futures::stream::BoxStream;
 use futures::{StreamExt, TryStreamExt};
 use object_store::Error;
 use object_store::{path::Path, ObjectStore};
 use serde::Serialize;
+use tracing::debug;
 
+use super::cancellation::{
+    check_cancelled, is_cancelled, run_cancellable, CancellationToken, OperationTimeout,
+};
+use super::estimate::CostEstimate;
+use super::progress::{ProgressListenerRef, ProgressTracker};
 use super::transaction::{CommitBuilder, CommitProperties};
 use crate::errors::{DeltaResult, DeltaTableError};
 use crate::logstore::LogStoreRef;
 use crate::protocol::DeltaOperation;
+use crate::storage::deadline::{deadline_passed, is_deadline_exceeded, without_deadline};
+use crate::table::pins::{files_referenced_since, PinRegistryRef};
 use crate::table::state::DeltaTableState;
 use crate::DeltaTable;
 
@@ -93,6 +102,18 @@ pub struct VacuumBuilder {
     clock: Option<Arc<dyn Clock>>,
     /// Additional information to add to the commit
     commit_properties: CommitProperties,
+    /// Registry of versions pinned by active readers
+    pin_registry: Option<PinRegistryRef>,
+    /// Token to stop deleting files
+    cancellation_token: Option<CancellationToken>,
+    /// Listener notified of the files deleted
+    progress_listener: Option<ProgressListenerRef>,
+    /// Time after which to stop deleting files
+    timeout: Option<std::time::Duration>,
+    /// Maximum number of files deleted concurrently
+    max_concurrent_deletes: Option<usize>,
+    /// Delete directories left empty after deleting files
+    delete_empty_directories: bool,
 }
 
 /// Details for the Vacuum operation including which files were
@@ -136,6 +157,12 @@ impl VacuumBuilder {
             dry_run: false,
             clock: None,
             commit_properties: CommitProperties::default(),
+            pin_registry: None,
+            cancellation_token: None,
+            progress_listener: None,
+            timeout: None,
+            max_concurrent_deletes: None,
+            delete_empty_directories: false,
         }
     }
 
@@ -145,6 +172,14 @@ impl VacuumBuilder {
         self
     }
 
+    /// Override the default rention period for which files are deleted, given as
+    /// [`std::time::Duration`]. Periods exceeding the supported range are saturated.
+    pub fn with_retention_duration(mut self, retention_period: std::time::Duration) -> Self {
+        self.retention_period =
+            Some(Duration::from_std(retention_period).unwrap_or(Duration::max_value()));
+        self
+    }
+
     /// Only determine which files should be deleted
     pub fn with_dry_run(mut self, dry_run: bool) -> Self {
         self.dry_run = dry_run;
@@ -170,6 +205,65 @@ impl VacuumBuilder {
         self
     }
 
+    /// Keep all files of versions pinned by readers in `registry`, see [`crate::table::pins`]
+    pub fn with_pin_registry(mut self, registry: PinRegistryRef) -> Self {
+        self.pin_registry = Some(registry);
+        self
+    }
+
+    /// Stop deleting files once the token is cancelled. The vacuum is then recorded as
+    /// cancelled in the log, along with the number of files deleted until then
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Report the files deleted to the listener
+    pub fn with_progress_listener(mut self, listener: ProgressListenerRef) -> Self {
+        self.progress_listener = Some(listener);
+        self
+    }
+
+    /// Stop deleting files once the timeout has elapsed, and fail storage requests issued after
+    /// it. Like a cancelled vacuum, the vacuum is recorded as cancelled in the log
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Delete files with at most `max_concurrent_deletes` concurrent delete requests, rather
+    /// than with the bulk deletes of the object store. Limits the request rate of vacuuming large
+    /// tables on throttled storage
+    pub fn with_max_concurrent_deletes(mut self, max_concurrent_deletes: usize) -> Self {
+        self.max_concurrent_deletes = Some(max_concurrent_deletes);
+        self
+    }
+
+    /// Delete the directories of the deleted files which are left empty
+    ///
+    /// Stores with a hierarchical namespace, like ADLS Gen2 accounts, keep directories as
+    /// objects of their own, so partitions without files remain visible after vacuuming. Every
+    /// directory of a deleted file costs an additional head and list request.
+    pub fn with_delete_empty_directories(mut self, delete_empty_directories: bool) -> Self {
+        self.delete_empty_directories = delete_empty_directories;
+        self
+    }
+
+    /// Estimate the storage IO of the vacuum by listing the table's files, without deleting
+    /// any. See [`super::estimate`] for the assumptions made
+    pub async fn estimate(&self) -> DeltaResult<CostEstimate> {
+        let plan = self.create_vacuum_plan().await?;
+        let mut estimate = CostEstimate::default();
+        estimate.list_objects(plan.num_files_listed);
+        if !plan.files_to_delete.is_empty() {
+            estimate.delete_requests += plan.files_to_delete.len() as u64;
+            // vacuum records its start and end in separate commits
+            estimate.commit();
+            estimate.commit();
+        }
+        Ok(estimate)
+    }
+
     /// Determine which files can be deleted. Does not actually peform the deletion
     async fn create_vacuum_plan(&self) -> Result<VacuumPlan, VacuumError> {
         let min_retention = Duration::milliseconds(
@@ -197,13 +291,26 @@ impl VacuumBuilder {
             &self.snapshot,
             retention_period,
             now_millis,
-            self.log_store.object_store().clone(),
+            self.log_store.log_object_store(),
         )
         .await?;
-        let valid_files = self.snapshot.file_paths_iter().collect::<HashSet<Path>>();
+        let mut valid_files = self.snapshot.file_paths_iter()?.collect::<HashSet<Path>>();
+        if let Some(registry) = &self.pin_registry {
+            let pinned = registry
+                .min_pinned_version(&self.log_store.root_uri())
+                .await?;
+            if let Some(pinned) = pinned.filter(|pinned| *pinned < self.snapshot.version()) {
+                debug!("keeping files referenced since pinned version {pinned}");
+                valid_files.extend(
+                    files_referenced_since(&self.log_store, pinned, self.snapshot.version())
+                        .await?,
+                );
+            }
+        }
 
         let mut files_to_delete = vec![];
         let mut file_sizes = vec![];
+        let mut num_files_listed = 0;
         let object_store = self.log_store.object_store();
         let mut all_files = object_store.list(None);
         let partition_columns = &self.snapshot.metadata().partition_columns;
@@ -211,6 +318,7 @@ impl VacuumBuilder {
         while let Some(obj_meta) = all_files.next().await {
             // TODO should we allow NotFound here in case we have a temporary commit file in the list
             let obj_meta = obj_meta.map_err(DeltaTableError::from)?;
+            num_files_listed += 1;
             if valid_files.contains(&obj_meta.location) // file is still being tracked in table
             || !expired_tombstones.contains(obj_meta.location.as_ref()) // file is not an expired tombstone
             || is_hidden_directory(partition_columns, &obj_meta.location)?
@@ -225,6 +333,7 @@ impl VacuumBuilder {
         Ok(VacuumPlan {
             files_to_delete,
             file_sizes,
+            num_files_listed,
             retention_check_enabled: enforce_retention_duration,
             default_retention_millis: min_retention.num_milliseconds(),
             specified_retention_millis: Some(retention_period.num_milliseconds()),
@@ -240,24 +349,33 @@ impl std::future::IntoFuture for VacuumBuilder {
         let this = self;
 
         Box::pin(async move {
-            let plan = this.create_vacuum_plan().await?;
-            if this.dry_run {
-                return Ok((
-                    DeltaTable::new_with_state(this.log_store, this.snapshot),
-                    VacuumMetrics {
+            let (timeout, token) =
+                OperationTimeout::maybe_start(this.timeout, this.cancellation_token.clone());
+            let progress = ProgressTracker::new(this.progress_listener.clone(), "VACUUM");
+            let vacuum = async {
+                let plan = run_cancellable(token.as_ref(), async {
+                    Ok(this.create_vacuum_plan().await?)
+                })
+                .await?;
+                if this.dry_run {
+                    return Ok(VacuumMetrics {
                         files_deleted: plan.files_to_delete.iter().map(|f| f.to_string()).collect(),
                         dry_run: true,
-                    },
-                ));
-            }
+                    });
+                }
 
-            let metrics = plan
-                .execute(
+                plan.execute(
                     this.log_store.clone(),
                     &this.snapshot,
-                    this.commit_properties,
+                    this.commit_properties.clone(),
+                    token.as_ref(),
+                    progress.clone(),
+                    this.max_concurrent_deletes,
+                    this.delete_empty_directories,
                 )
-                .await?;
+                .await
+            };
+            let metrics = OperationTimeout::maybe_run(timeout, &progress, vacuum).await?;
             Ok((
                 DeltaTable::new_with_state(this.log_store, this.snapshot),
                 metrics,
@@ -272,6 +390,8 @@ struct VacuumPlan {
     pub files_to_delete: Vec<Path>,
     /// Size of each file which to delete
     pub file_sizes: Vec<i64>,
+    /// Number of files listed to determine the files to delete
+    pub num_files_listed: u64,
     /// If retention check is enabled
     pub retention_check_enabled: bool,
     /// Default retention in milliseconds
@@ -287,8 +407,14 @@ impl VacuumPlan {
         store: LogStoreRef,
         snapshot: &DeltaTableState,
         mut commit_properties: CommitProperties,
+        cancellation_token: Option<&CancellationToken>,
+        progress: ProgressTracker,
+        max_concurrent_deletes: Option<usize>,
+        delete_empty_directories: bool,
     ) -> Result<VacuumMetrics, DeltaTableError> {
+        check_cancelled(cancellation_token)?;
         if self.files_to_delete.is_empty() {
+            progress.finish();
             return Ok(VacuumMetrics {
                 dry_run: false,
                 files_deleted: Vec::new(),
@@ -301,10 +427,6 @@ impl VacuumPlan {
             default_retention_millis: self.default_retention_millis,
         };
 
-        let end_operation = DeltaOperation::VacuumEnd {
-            status: String::from("COMPLETED"), // Maybe this should be FAILED when vacuum has error during the files, not sure how to check for this
-        };
-
         let start_metrics = VacuumStartOperationMetrics {
             num_files_to_delete: self.files_to_delete.len() as i64,
             size_of_data_to_delete: self.file_sizes.iter().sum(),
@@ -323,25 +445,66 @@ impl VacuumPlan {
             .await?;
         // Finish VACUUM START COMMIT
 
+        let progress = progress.with_totals(
+            self.files_to_delete.len() as u64,
+            self.file_sizes.iter().sum::<i64>() as u64,
+        );
+        let sizes: HashMap<String, i64> = self
+            .files_to_delete
+            .iter()
+            .map(|path| path.to_string())
+            .zip(self.file_sizes.iter().copied())
+            .collect();
+
+        let token = cancellation_token.cloned();
         let locations = futures::stream::iter(self.files_to_delete)
-            .map(Result::Ok)
-            .boxed();
-
-        let files_deleted = store
-            .object_store()
-            .delete_stream(locations)
-            .map(|res| match res {
-                Ok(path) => Ok(path.to_string()),
-                Err(Error::NotFound { path, .. }) => Ok(path),
-                Err(err) => Err(err),
+            .take_while(move |_| futures::future::ready(!is_cancelled(token.as_ref())));
+
+        let object_store = store.object_store();
+        let deleted: BoxStream<'_, Result<Path, Error>> = match max_concurrent_deletes {
+            Some(limit) => locations
+                .map(|location| {
+                    let object_store = object_store.clone();
+                    async move { object_store.delete(&location).await.map(|_| location) }
+                })
+                .buffer_unordered(limit.max(1))
+                .boxed(),
+            None => object_store.delete_stream(locations.map(Result::Ok).boxed()),
+        };
+
+        let files_deleted = deleted
+            .filter_map(|res| {
+                futures::future::ready(match res {
+                    Ok(path) => Some(Ok(path.to_string())),
+                    Err(Error::NotFound { path, .. }) => Some(Ok(path)),
+                    // files left behind by a timed out vacuum are deleted by the next one
+                    Err(err) if is_deadline_exceeded(&err) => None,
+                    Err(err) => Some(Err(err)),
+                })
+            })
+            .inspect_ok(|path| {
+                let size = sizes.get(path).copied().unwrap_or_default();
+                progress.advance(1, size as u64);
             })
             .try_collect::<Vec<_>>()
             .await?;
 
+        let cancelled = is_cancelled(cancellation_token) || deadline_passed();
+        let num_vacuumed_directories = if delete_empty_directories && !cancelled {
+            delete_empty_parents(object_store.as_ref(), &files_deleted).await?
+        } else {
+            0
+        };
+        // Maybe this should be FAILED when vacuum has error during the files, not sure how to check for this
+        let status = if cancelled { "CANCELLED" } else { "COMPLETED" };
+        let end_operation = DeltaOperation::VacuumEnd {
+            status: String::from(status),
+        };
+
         // Create end metadata
         let end_metrics = VacuumEndOperationMetrics {
             num_deleted_files: files_deleted.len() as i64,
-            num_vacuumed_directories: 0, // Set to zero since we only remove files not dirs
+            num_vacuumed_directories,
         };
 
         // Begin VACUUM END COMMIT
@@ -349,11 +512,24 @@ impl VacuumPlan {
             "operationMetrics".to_owned(),
             serde_json::to_value(end_metrics)?,
         );
-        CommitBuilder::from(commit_properties)
-            .build(Some(snapshot), store.clone(), end_operation)?
-            .await?;
+        let end_commit = async {
+            CommitBuilder::from(commit_properties)
+                .build(Some(snapshot), store.clone(), end_operation)?
+                .await
+        };
+        if cancelled {
+            // a timed out vacuum is still recorded in the log
+            without_deadline(end_commit).await?;
+        } else {
+            end_commit.await?;
+        }
         // Finish VACUUM END COMMIT
 
+        if cancelled {
+            return Err(DeltaTableError::Cancelled);
+        }
+        progress.finish();
+
         Ok(VacuumMetrics {
             files_deleted,
             dry_run: false,
@@ -361,6 +537,50 @@ impl VacuumPlan {
     }
 }
 
+/// Delete the directories of the `deleted` files which are left empty, deepest first, and
+/// return the number of deleted directories. Directories which don't exist as objects of their
+/// own, as on stores without a hierarchical namespace, are skipped.
+async fn delete_empty_parents(store: &dyn ObjectStore, deleted: &[String]) -> DeltaResult<i64> {
+    let mut directories = HashSet::new();
+    for file in deleted {
+        let path = Path::parse(file).unwrap_or_else(|_| Path::from(file.as_str()));
+        let mut parts = path.parts().collect::<Vec<_>>();
+        parts.pop();
+        while !parts.is_empty() {
+            directories.insert(parts.iter().cloned().collect::<Path>());
+            parts.pop();
+        }
+    }
+    let mut directories = directories.into_iter().collect::<Vec<_>>();
+    directories.sort_by_key(|dir| std::cmp::Reverse(dir.parts().count()));
+
+    let mut num_deleted = 0;
+    for dir in directories {
+        match store.head(&dir).await {
+            Ok(_) => {}
+            Err(Error::NotFound { .. }) => continue,
+            Err(err) => return Err(err.into()),
+        }
+        let has_children = store
+            .list(Some(&dir))
+            .try_filter(|meta| futures::future::ready(meta.location != dir))
+            .boxed()
+            .try_next()
+            .await?
+            .is_some();
+        if has_children {
+            continue;
+        }
+        match store.delete(&dir).await {
+            Ok(()) => num_deleted += 1,
+            Err(Error::NotFound { .. }) => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+    debug!("deleted {num_deleted} empty directories");
+    Ok(num_deleted)
+}
+
 /// Whether a path should be hidden for delta-related file operations, such as Vacuum.
 /// Names of the form partitionCol=[value] are partition directories, and should be
 /// deleted even if they'd normally be hidden. The _db_index directory contains (bloom filter)
@@ -401,8 +621,36 @@ async fn get_stale_files(
 mod tests {
     use super::*;
     use crate::open_table;
+    use bytes::Bytes;
+    use object_store::memory::InMemory;
     use std::time::SystemTime;
 
+    #[tokio::test]
+    async fn test_delete_empty_parents() {
+        let store = InMemory::new();
+        for (path, data) in [
+            ("a=1", ""),
+            ("a=1/b=2", ""),
+            ("c=1", ""),
+            ("c=1/kept.parquet", "data"),
+        ] {
+            store
+                .put(&Path::from(path), Bytes::from(data))
+                .await
+                .unwrap();
+        }
+        let deleted = vec![
+            "a=1/b=2/deleted.parquet".to_string(),
+            "c=1/deleted.parquet".to_string(),
+            "d=1/deleted.parquet".to_string(),
+        ];
+        let num_deleted = delete_empty_parents(&store, &deleted).await.unwrap();
+        assert_eq!(num_deleted, 2);
+        assert!(store.head(&Path::from("a=1")).await.is_err());
+        assert!(store.head(&Path::from("a=1/b=2")).await.is_err());
+        assert!(store.head(&Path::from("c=1")).await.is_ok());
+    }
+
     #[tokio::test]
     async fn vacuum_delta_8_0_table() {
         let table = open_table("../test/tests/data/delta-0.8.0").await.unwrap();
@@ -456,4 +704,38 @@ mod tests {
 
         assert_eq!(result.files_deleted, empty);
     }
+
+    #[tokio::test]
+    async fn vacuum_keeps_pinned_files() {
+        use crate::table::pins::{PinRegistry, SnapshotPin, SnapshotPinRegistry};
+
+        let table = open_table("../test/tests/data/delta-0.8.0").await.unwrap();
+        let registry = Arc::new(SnapshotPinRegistry::default());
+        let pin = SnapshotPin::new(table.table_uri(), 0, std::time::Duration::from_secs(60));
+        registry.register(&pin).await.unwrap();
+
+        let (table, result) =
+            VacuumBuilder::new(table.log_store(), table.snapshot().unwrap().clone())
+                .with_retention_period(Duration::hours(0))
+                .with_dry_run(true)
+                .with_enforce_retention_duration(false)
+                .with_pin_registry(registry.clone())
+                .await
+                .unwrap();
+        assert!(result.files_deleted.is_empty());
+
+        registry.release(&pin).await.unwrap();
+        let (_table, result) =
+            VacuumBuilder::new(table.log_store(), table.snapshot().unwrap().clone())
+                .with_retention_period(Duration::hours(0))
+                .with_dry_run(true)
+                .with_enforce_retention_duration(false)
+                .with_pin_registry(registry)
+                .await
+                .unwrap();
+        assert_eq!(
+            result.files_deleted,
+            vec!["part-00001-911a94a2-43f6-4acb-8620-5e68c2654989-c000.snappy.parquet"]
+        );
+    }
 }
@@ -42,10 +42,14 @@ use datafusion_expr::Expr;
 use futures::future::BoxFuture;
 use futures::StreamExt;
 use parquet::file::properties::WriterProperties;
+use tracing::debug;
 
+use super::cancellation::{delete_uncommitted_files, is_cancelled, CancellationToken};
 use super::datafusion_utils::Expression;
+use super::dedup::drop_existing_keys;
+use super::partition_by::PartitionBy;
 use super::transaction::{CommitBuilder, CommitProperties, TableReference, PROTOCOL};
-use super::writer::{DeltaWriter, WriterConfig};
+use super::writer::{DeltaWriter, FileLayout, WriterConfig};
 use super::CreateBuilder;
 use crate::delta_datafusion::expr::fmt_expr_to_sql;
 use crate::delta_datafusion::expr::parse_predicate_expression;
@@ -56,9 +60,13 @@ use crate::kernel::{Action, Add, Metadata, PartitionsExt, Remove, StructType};
 use crate::logstore::LogStoreRef;
 use crate::operations::cast::{cast_record_batch, merge_schema};
 use crate::protocol::{DeltaOperation, SaveMode};
+use crate::storage::deadline::propagate_deadline;
 use crate::storage::ObjectStoreRef;
+use crate::table::config::TableConfig;
+use crate::table::encryption::check_can_write;
 use crate::table::state::DeltaTableState;
 use crate::table::Constraint as DeltaConstraint;
+use crate::writer::file_name::FileNameStrategy;
 use crate::writer::record_batch::divide_by_partition_values;
 use crate::DeltaTable;
 
@@ -150,6 +158,16 @@ pub struct WriteBuilder {
     description: Option<String>,
     /// Configurations of the delta table, only used when table doesn't exist
     configuration: HashMap<String, Option<String>>,
+    /// Tags set on every written file
+    file_tags: HashMap<String, Option<String>>,
+    /// Directory layout of the written files, configured by the table by default
+    file_layout: Option<FileLayout>,
+    /// Naming scheme of the written files
+    file_name_strategy: Option<Arc<dyn FileNameStrategy>>,
+    /// Time partitioning the partition columns are derived with
+    partition_by: Option<PartitionBy>,
+    /// Column whose values already in the table are dropped from appended data
+    dedup_key: Option<String>,
 }
 
 impl WriteBuilder {
@@ -173,6 +191,11 @@ impl WriteBuilder {
             name: None,
             description: None,
             configuration: Default::default(),
+            file_tags: Default::default(),
+            file_layout: None,
+            file_name_strategy: None,
+            partition_by: None,
+            dedup_key: None,
         }
     }
 
@@ -204,6 +227,23 @@ impl WriteBuilder {
         self
     }
 
+    /// Partition by columns derived from time, see [`partition_by`](super::partition_by).
+    /// The derived columns are appended to the written batches and replace the partition columns
+    pub fn with_partition_by(mut self, partition_by: PartitionBy) -> Self {
+        self.partition_columns = Some(partition_by.partition_columns());
+        self.partition_by = Some(partition_by);
+        self
+    }
+
+    /// Drop the appended records whose value of `column` is already a key of the table, for
+    /// upstreams delivering records at least once. Files which may contain an appended key are
+    /// narrowed down by statistics and bloom filters before reading them. Only applies to
+    /// appends to existing tables
+    pub fn with_dedup_key(mut self, column: impl Into<String>) -> Self {
+        self.dedup_key = Some(column.into());
+        self
+    }
+
     /// Execution plan that produces the data to be written to the delta table
     pub fn with_input_execution_plan(mut self, plan: Arc<dyn ExecutionPlan>) -> Self {
         self.input = Some(plan);
@@ -278,10 +318,37 @@ impl WriteBuilder {
         self
     }
 
+    /// Tag every written file, e.g. with its ingestion source, see [`file_tags`](crate::table::file_tags)
+    pub fn with_file_tags(
+        mut self,
+        tags: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) -> Self {
+        self.file_tags
+            .extend(tags.into_iter().map(|(k, v)| (k.into(), Some(v.into()))));
+        self
+    }
+
+    /// Set the directory layout of the written files, overriding the layout configured with the
+    /// `delta.randomizeFilePrefixes` table property
+    pub fn with_file_layout(mut self, file_layout: FileLayout) -> Self {
+        self.file_layout = Some(file_layout);
+        self
+    }
+
+    /// Set the naming scheme of the written files, see [`file_name`](crate::writer::file_name)
+    pub fn with_file_name_strategy(
+        mut self,
+        file_name_strategy: Arc<dyn FileNameStrategy>,
+    ) -> Self {
+        self.file_name_strategy = Some(file_name_strategy);
+        self
+    }
+
     async fn check_preconditions(&self) -> DeltaResult<Vec<Action>> {
         match &self.snapshot {
             Some(snapshot) => {
                 PROTOCOL.can_write_to(snapshot)?;
+                check_can_write(&snapshot.metadata().configuration)?;
 
                 if let Some(plan) = &self.input {
                     let schema: StructType = (plan.schema()).try_into()?;
@@ -302,6 +369,7 @@ impl WriteBuilder {
                 }
             }
             None => {
+                check_can_write(&self.configuration)?;
                 let schema: StructType = if let Some(plan) = &self.input {
                     Ok(plan.schema().try_into()?)
                 } else if let Some(batches) = &self.batches {
@@ -348,7 +416,13 @@ async fn write_execution_plan_with_predicate(
     writer_properties: Option<WriterProperties>,
     safe_cast: bool,
     schema_mode: Option<SchemaMode>,
+    file_layout: Option<FileLayout>,
+    file_name_strategy: Option<Arc<dyn FileNameStrategy>>,
+    cancellation_token: Option<&CancellationToken>,
 ) -> DeltaResult<Vec<Action>> {
+    let file_layout = file_layout
+        .or_else(|| snapshot.map(|s| FileLayout::from_table_config(&s.table_config())))
+        .unwrap_or_default();
     let schema: ArrowSchemaRef = if schema_mode.is_some() {
         plan.schema()
     } else {
@@ -377,19 +451,27 @@ async fn write_execution_plan_with_predicate(
         let inner_plan = plan.clone();
         let inner_schema = schema.clone();
         let task_ctx = Arc::new(TaskContext::from(&state));
-        let config = WriterConfig::new(
+        let mut config = WriterConfig::new(
             inner_schema.clone(),
             partition_columns.clone(),
             writer_properties.clone(),
             target_file_size,
             write_batch_size,
-        );
+        )
+        .with_file_layout(file_layout);
+        if let Some(file_name_strategy) = &file_name_strategy {
+            config = config.with_file_name_strategy(file_name_strategy.clone());
+        }
         let mut writer = DeltaWriter::new(object_store.clone(), config);
         let checker_stream = checker.clone();
         let mut stream = inner_plan.execute(i, task_ctx)?;
+        let token = cancellation_token.cloned();
         let handle: tokio::task::JoinHandle<DeltaResult<Vec<Action>>> =
-            tokio::task::spawn(async move {
+            tokio::task::spawn(propagate_deadline(async move {
                 while let Some(maybe_batch) = stream.next().await {
+                    if is_cancelled(token.as_ref()) {
+                        break;
+                    }
                     let batch = maybe_batch?;
                     checker_stream.check_batch(&batch).await?;
                     let arr = super::cast::cast_record_batch(
@@ -405,7 +487,7 @@ async fn write_execution_plan_with_predicate(
                     Ok(actions) => Ok(actions.into_iter().map(Action::Add).collect::<Vec<_>>()),
                     Err(err) => Err(err),
                 }
-            });
+            }));
 
         tasks.push(handle);
     }
@@ -419,6 +501,13 @@ async fn write_execution_plan_with_predicate(
         .concat()
         .into_iter()
         .collect::<Vec<_>>();
+
+    // Writers stop early once cancelled, the files they wrote must never be committed
+    if is_cancelled(cancellation_token) {
+        delete_uncommitted_files(&object_store, &actions).await;
+        return Err(DeltaTableError::Cancelled);
+    }
+
     // Collect add actions to add to commit
     Ok(actions)
 }
@@ -435,6 +524,7 @@ pub(crate) async fn write_execution_plan(
     writer_properties: Option<WriterProperties>,
     safe_cast: bool,
     schema_mode: Option<SchemaMode>,
+    cancellation_token: Option<&CancellationToken>,
 ) -> DeltaResult<Vec<Action>> {
     write_execution_plan_with_predicate(
         None,
@@ -448,6 +538,9 @@ pub(crate) async fn write_execution_plan(
         writer_properties,
         safe_cast,
         schema_mode,
+        None,
+        None,
+        cancellation_token,
     )
     .await
 }
@@ -496,6 +589,7 @@ async fn execute_non_empty_expr(
         writer_properties,
         false,
         None,
+        None,
     )
     .await?;
 
@@ -555,9 +649,55 @@ impl std::future::IntoFuture for WriteBuilder {
     type IntoFuture = BoxFuture<'static, Self::Output>;
 
     fn into_future(self) -> Self::IntoFuture {
-        let this = self;
+        let mut this = self;
 
         Box::pin(async move {
+            if let Some(snapshot) = &this.snapshot {
+                this.commit_properties
+                    .check_idempotency(&this.log_store, snapshot.version())
+                    .await?;
+            }
+            if let Some(partition_by) = &this.partition_by {
+                if this.input.is_some() {
+                    return Err(DeltaTableError::Generic(
+                        "Time partitioning not supported yet for Datafusion".to_string(),
+                    ));
+                }
+                if let Some(batches) = this.batches.take() {
+                    this.batches = Some(
+                        batches
+                            .iter()
+                            .map(|batch| partition_by.apply(batch))
+                            .collect::<DeltaResult<_>>()?,
+                    );
+                }
+            }
+            if let (Some(column), Some(snapshot)) = (&this.dedup_key, &this.snapshot) {
+                if this.mode == SaveMode::Append {
+                    if this.input.is_some() {
+                        return Err(DeltaTableError::Generic(
+                            "Dedup on append not supported yet for Datafusion".to_string(),
+                        ));
+                    }
+                    if let Some(batches) = this.batches.take() {
+                        let state = this.state.clone().unwrap_or_else(|| {
+                            let ctx = SessionContext::new();
+                            register_store(this.log_store.clone(), ctx.runtime_env());
+                            ctx.state()
+                        });
+                        let (batches, dropped) = drop_existing_keys(
+                            snapshot,
+                            this.log_store.clone(),
+                            &state,
+                            column,
+                            batches,
+                        )
+                        .await?;
+                        debug!("dropped {dropped} records with existing keys");
+                        this.batches = Some(batches);
+                    }
+                }
+            }
             if this.mode == SaveMode::Overwrite {
                 if let Some(snapshot) = &this.snapshot {
                     PROTOCOL.check_append_only(&snapshot.snapshot)?;
@@ -725,6 +865,13 @@ impl std::future::IntoFuture for WriteBuilder {
                 _ => (None, None),
             };
 
+            // Tables created by this write take the layout from the configuration they are created with
+            let file_layout = this.file_layout.or_else(|| {
+                this.snapshot
+                    .is_none()
+                    .then(|| FileLayout::from_table_config(&TableConfig(&this.configuration)))
+            });
+
             // Here we need to validate if the new data conforms to a predicate if one is provided
             let add_actions = write_execution_plan_with_predicate(
                 predicate.clone(),
@@ -738,9 +885,20 @@ impl std::future::IntoFuture for WriteBuilder {
                 this.writer_properties.clone(),
                 this.safe_cast,
                 this.schema_mode,
+                file_layout,
+                this.file_name_strategy.clone(),
+                None,
             )
             .await?;
-            actions.extend(add_actions);
+            actions.extend(add_actions.into_iter().map(|action| match action {
+                Action::Add(mut add) if !this.file_tags.is_empty() => {
+                    add.tags
+                        .get_or_insert_with(HashMap::new)
+                        .extend(this.file_tags.clone());
+                    Action::Add(add)
+                }
+                action => action,
+            }));
 
             // Collect remove actions if we are overwriting the table
             if let Some(snapshot) = &this.snapshot {
@@ -782,7 +940,7 @@ impl std::future::IntoFuture for WriteBuilder {
                         }
                         _ => {
                             let remove_actions = snapshot
-                                .log_data()
+                                .log_data()?
                                 .into_iter()
                                 .map(|p| p.remove_action(true).into());
                             actions.extend(remove_actions);
@@ -919,7 +1077,7 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(table.version(), 1);
-        assert_eq!(table.get_files_count(), 1);
+        assert_eq!(table.get_files_count().unwrap(), 1);
         table.load().await.unwrap();
         assert_eq!(table.history(None).await.unwrap().len(), 2);
         assert_eq!(
@@ -942,7 +1100,7 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(table.version(), 2);
-        assert_eq!(table.get_files_count(), 2);
+        assert_eq!(table.get_files_count().unwrap(), 2);
         table.load().await.unwrap();
         assert_eq!(table.history(None).await.unwrap().len(), 3);
         assert_eq!(
@@ -965,7 +1123,7 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(table.version(), 3);
-        assert_eq!(table.get_files_count(), 1);
+        assert_eq!(table.get_files_count().unwrap(), 1);
         table.load().await.unwrap();
         assert_eq!(table.history(None).await.unwrap().len(), 4);
         assert_eq!(
@@ -1088,7 +1246,7 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(table.version(), 0);
-        assert_eq!(table.get_files_count(), 1)
+        assert_eq!(table.get_files_count().unwrap(), 1)
     }
 
     #[tokio::test]
@@ -1101,7 +1259,7 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(table.version(), 0);
-        assert_eq!(table.get_files_count(), 2);
+        assert_eq!(table.get_files_count().unwrap(), 2);
 
         let table = DeltaOps::new_in_memory()
             .write(vec![batch])
@@ -1110,7 +1268,196 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(table.version(), 0);
-        assert_eq!(table.get_files_count(), 4)
+        assert_eq!(table.get_files_count().unwrap(), 4)
+    }
+
+    #[tokio::test]
+    async fn test_write_file_tags() {
+        use crate::delta_datafusion::{DeltaScanConfigBuilder, DeltaTableProvider};
+        use crate::table::file_tags::FileTagFilter;
+
+        let batch = get_record_batch(None, false);
+        let table = DeltaOps::new_in_memory()
+            .write(vec![batch.clone()])
+            .with_file_tags([("ingestion.source", "kafka")])
+            .await
+            .unwrap();
+        let tagged = table.get_files_iter().unwrap().collect::<Vec<_>>();
+        let table = DeltaOps(table).write(vec![batch.clone()]).await.unwrap();
+        assert_eq!(table.version(), 1);
+
+        let kafka = FileTagFilter::equals("ingestion.source", "kafka");
+        assert_eq!(table.get_files_by_tags(&[kafka.clone()]).unwrap(), tagged);
+        let untagged = table
+            .get_files_by_tags(&[FileTagFilter::missing("ingestion.source")])
+            .unwrap();
+        assert_eq!(
+            untagged.len(),
+            table.get_files_count().unwrap() - tagged.len()
+        );
+
+        let config = DeltaScanConfigBuilder::new()
+            .with_file_tag_filter(kafka)
+            .build(table.snapshot().unwrap())
+            .unwrap();
+        let provider = DeltaTableProvider::try_new(
+            table.snapshot().unwrap().clone(),
+            table.log_store(),
+            config,
+        )
+        .unwrap();
+        let ctx = SessionContext::new();
+        ctx.register_table("tagged", Arc::new(provider)).unwrap();
+        let count = ctx.table("tagged").await.unwrap().count().await.unwrap();
+        assert_eq!(count, batch.num_rows());
+    }
+
+    #[tokio::test]
+    async fn test_write_random_prefixes() {
+        let batch = get_record_batch(None, false);
+        let table = DeltaOps::new_in_memory()
+            .write(vec![batch.clone()])
+            .with_partition_columns(["modified"])
+            .with_configuration([
+                ("delta.randomizeFilePrefixes", Some("true")),
+                ("delta.randomPrefixLength", Some("3")),
+            ])
+            .await
+            .unwrap();
+        let table = DeltaOps(table)
+            .write(vec![batch.clone()])
+            .with_file_layout(FileLayout::Hive)
+            .await
+            .unwrap();
+
+        let files = table.snapshot().unwrap().file_actions().unwrap();
+        assert_eq!(files.len(), 4);
+        let (hive, random): (Vec<_>, Vec<_>) = files
+            .iter()
+            .partition(|add| add.path.starts_with("modified="));
+        assert_eq!(hive.len(), 2);
+        for add in random {
+            let (prefix, _) = add.path.split_once('/').unwrap();
+            assert_eq!(prefix.len(), 3);
+            assert!(add.partition_values.contains_key("modified"));
+        }
+
+        let (_, stream) = DeltaOps(table).load().await.unwrap();
+        let data = collect_sendable_stream(stream).await.unwrap();
+        let rows = data.iter().map(|batch| batch.num_rows()).sum::<usize>();
+        assert_eq!(rows, 2 * batch.num_rows());
+    }
+
+    #[tokio::test]
+    async fn test_write_file_name_strategy() {
+        use crate::writer::file_name::CustomFileNames;
+
+        let batch = get_record_batch(None, false);
+        let names = CustomFileNames::default()
+            .with_prefix("ingest-")
+            .with_counter_width(0)
+            .with_time_sortable_ids(true);
+        let table = DeltaOps::new_in_memory()
+            .write(vec![batch.clone()])
+            .with_partition_columns(["modified"])
+            .with_file_name_strategy(Arc::new(names))
+            .await
+            .unwrap();
+
+        let files = table.snapshot().unwrap().file_actions().unwrap();
+        assert_eq!(files.len(), 2);
+        for add in files {
+            let (partition, name) = add.path.split_once('/').unwrap();
+            assert!(partition.starts_with("modified="));
+            assert!(name.starts_with("ingest-"));
+            assert!(name.ends_with(".snappy.parquet"));
+        }
+
+        let (_, stream) = DeltaOps(table).load().await.unwrap();
+        let data = collect_sendable_stream(stream).await.unwrap();
+        let rows = data.iter().map(|batch| batch.num_rows()).sum::<usize>();
+        assert_eq!(rows, batch.num_rows());
+    }
+
+    #[tokio::test]
+    async fn test_write_ingestion_time_partitions() {
+        use crate::operations::partition_by::{PartitionBy, TimeGranularity};
+
+        let batch = get_record_batch(None, false);
+        let table = DeltaOps::new_in_memory()
+            .write(vec![batch.clone()])
+            .with_partition_by(PartitionBy::ingestion_time(TimeGranularity::Day))
+            .await
+            .unwrap();
+        assert_eq!(
+            table.metadata().unwrap().partition_columns,
+            vec!["year", "month", "day"]
+        );
+
+        let table = DeltaOps(table)
+            .write(vec![batch.clone()])
+            .with_partition_by(PartitionBy::ingestion_time(TimeGranularity::Day))
+            .await
+            .unwrap();
+        let files = table.snapshot().unwrap().file_actions().unwrap();
+        for add in files {
+            assert!(add.path.starts_with("year="));
+            assert!(add.partition_values["day"].is_some());
+        }
+
+        let result = DeltaOps(table)
+            .write(vec![batch])
+            .with_partition_by(PartitionBy::time("id", TimeGranularity::Day))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_write_dedup_key() {
+        let batch = get_record_batch(None, false);
+        let table = DeltaOps::new_in_memory()
+            .write(vec![batch.clone()])
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 0);
+
+        // value 11 is part of the table, value 12 and the null value are not
+        let appended = RecordBatch::try_new(
+            batch.schema(),
+            vec![
+                Arc::new(StringArray::from(vec!["A", "B", "B"])),
+                Arc::new(Int32Array::from(vec![Some(11), Some(12), None])),
+                Arc::new(StringArray::from(vec![
+                    "2021-02-01",
+                    "2021-02-01",
+                    "2021-02-01",
+                ])),
+            ],
+        )
+        .unwrap();
+        let table = DeltaOps(table)
+            .write(vec![appended.clone()])
+            .with_dedup_key("value")
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 1);
+        let rows: usize = get_data(&table).await.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(rows, 13);
+
+        // appending the same records again drops all but the null key
+        let table = DeltaOps(table)
+            .write(vec![appended])
+            .with_dedup_key("value")
+            .await
+            .unwrap();
+        let rows: usize = get_data(&table).await.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(rows, 14);
+
+        let result = DeltaOps(table)
+            .write(vec![batch])
+            .with_dedup_key("missing")
+            .await;
+        assert!(result.is_err());
     }
 
     #[tokio::test]
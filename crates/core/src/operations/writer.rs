@@ -1,6 +1,7 @@
 //! Abstractions and implementations for writing data to delta tables
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use arrow::datatypes::SchemaRef as ArrowSchemaRef;
 use arrow::error::ArrowError;
@@ -11,23 +12,95 @@ use object_store::{path::Path, ObjectStore};
 use parquet::arrow::ArrowWriter;
 use parquet::basic::Compression;
 use parquet::file::properties::WriterProperties;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
 use tracing::debug;
 
 use crate::crate_version;
 use crate::errors::{DeltaResult, DeltaTableError};
 use crate::kernel::{Add, PartitionsExt, Scalar};
 use crate::storage::ObjectStoreRef;
+use crate::table::config::TableConfig;
+use crate::writer::file_name::{DeltaFileNames, FileNameContext, FileNameStrategy};
 use crate::writer::record_batch::{divide_by_partition_values, PartitionResult};
 use crate::writer::stats::create_add;
 use crate::writer::utils::{
-    arrow_schema_without_partitions, next_data_path, record_batch_without_partitions,
-    ShareableBuffer,
+    arrow_schema_without_partitions, record_batch_without_partitions, ShareableBuffer,
 };
 
 // TODO databricks often suggests a file size of 100mb, should we set this default?
 const DEFAULT_TARGET_FILE_SIZE: usize = 104_857_600;
 const DEFAULT_WRITE_BATCH_SIZE: usize = 1024;
 
+/// Directory layout of the data files written to a table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FileLayout {
+    /// Files are written into hive-style partition directories, e.g. `date=2024-01-01/`
+    #[default]
+    Hive,
+    /// Every file is written under a random prefix of alphanumeric characters rather than its
+    /// partition directory, spreading the keys of hot tables across storage prefixes. The
+    /// partition values are only recorded in the log
+    RandomPrefix {
+        /// Number of characters of the prefix
+        length: usize,
+    },
+    /// Every file is written under one of a fixed set of hashed prefixes, followed by its
+    /// partition directory, e.g. `3f9a01c2/date=2024-01-01/`. Prefixes are derived from the
+    /// shard number, so the same `num_prefixes` always yields the same set of prefixes
+    HashedPrefix {
+        /// Number of distinct prefixes the files are spread across
+        num_prefixes: u32,
+    },
+}
+
+impl FileLayout {
+    /// The layout configured with the `delta.randomizeFilePrefixes` and
+    /// `delta.randomPrefixLength` table properties
+    pub fn from_table_config(config: &TableConfig<'_>) -> Self {
+        if config.randomize_file_prefixes() {
+            Self::RandomPrefix {
+                length: config.random_prefix_length().max(1) as usize,
+            }
+        } else {
+            Self::Hive
+        }
+    }
+
+    /// Directory of the next file of the partition stored in `partition_path`
+    fn file_prefix(&self, partition_path: &Path) -> Path {
+        match self {
+            Self::Hive => partition_path.clone(),
+            Self::RandomPrefix { length } => Path::from(
+                rand::thread_rng()
+                    .sample_iter(&Alphanumeric)
+                    .take(*length)
+                    .map(char::from)
+                    .collect::<String>(),
+            ),
+            Self::HashedPrefix { num_prefixes } => {
+                let shard = rand::thread_rng().gen_range(0..(*num_prefixes).max(1));
+                let prefix = Path::from(shard_prefix(shard));
+                partition_path
+                    .parts()
+                    .fold(prefix, |path, part| path.child(part))
+            }
+        }
+    }
+}
+
+/// Stable hex prefix of `shard`, spreading consecutive shards across the key space
+fn shard_prefix(shard: u32) -> String {
+    // FNV-1a, which unlike the std hasher is stable across releases
+    let hash = shard
+        .to_le_bytes()
+        .iter()
+        .fold(0x811c9dc5_u32, |hash, byte| {
+            (hash ^ *byte as u32).wrapping_mul(0x01000193)
+        });
+    format!("{hash:08x}")
+}
+
 #[derive(thiserror::Error, Debug)]
 enum WriteError {
     #[error("Unexpected Arrow schema: got: {schema}, expected: {expected_schema}")]
@@ -79,6 +152,10 @@ pub struct WriterConfig {
     /// Row chunks passed to parquet writer. This and the internal parquet writer settings
     /// determine how fine granular we can track / control the size of resulting files.
     write_batch_size: usize,
+    /// Directory layout of the written files
+    file_layout: FileLayout,
+    /// Naming scheme of the written files
+    file_name_strategy: Arc<dyn FileNameStrategy>,
 }
 
 impl WriterConfig {
@@ -104,9 +181,26 @@ impl WriterConfig {
             writer_properties,
             target_file_size,
             write_batch_size,
+            file_layout: FileLayout::default(),
+            file_name_strategy: Arc::new(DeltaFileNames),
         }
     }
 
+    /// Set the directory layout of the written files, hive-style partition directories by default
+    pub fn with_file_layout(mut self, file_layout: FileLayout) -> Self {
+        self.file_layout = file_layout;
+        self
+    }
+
+    /// Set the naming scheme of the written files, [`DeltaFileNames`] by default
+    pub fn with_file_name_strategy(
+        mut self,
+        file_name_strategy: Arc<dyn FileNameStrategy>,
+    ) -> Self {
+        self.file_name_strategy = file_name_strategy;
+        self
+    }
+
     /// Schema of files written to disk
     pub fn file_schema(&self) -> ArrowSchemaRef {
         arrow_schema_without_partitions(&self.table_schema, &self.partition_columns)
@@ -176,7 +270,9 @@ impl DeltaWriter {
                     Some(self.config.writer_properties.clone()),
                     Some(self.config.target_file_size),
                     Some(self.config.write_batch_size),
-                )?;
+                )?
+                .with_file_layout(self.config.file_layout)
+                .with_file_name_strategy(self.config.file_name_strategy.clone());
                 let mut writer =
                     PartitionWriter::try_with_config(self.object_store.clone(), config)?;
                 writer.write(&record_batch).await?;
@@ -229,6 +325,10 @@ pub(crate) struct PartitionWriterConfig {
     /// Row chunks passed to parquet writer. This and the internal parquet writer settings
     /// determine how fine granular we can track / control the size of resulting files.
     write_batch_size: usize,
+    /// Directory layout of the written files
+    file_layout: FileLayout,
+    /// Naming scheme of the written files
+    file_name_strategy: Arc<dyn FileNameStrategy>,
 }
 
 impl PartitionWriterConfig {
@@ -256,8 +356,23 @@ impl PartitionWriterConfig {
             writer_properties,
             target_file_size,
             write_batch_size,
+            file_layout: FileLayout::default(),
+            file_name_strategy: Arc::new(DeltaFileNames),
         })
     }
+
+    pub fn with_file_layout(mut self, file_layout: FileLayout) -> Self {
+        self.file_layout = file_layout;
+        self
+    }
+
+    pub fn with_file_name_strategy(
+        mut self,
+        file_name_strategy: Arc<dyn FileNameStrategy>,
+    ) -> Self {
+        self.file_name_strategy = file_name_strategy;
+        self
+    }
 }
 
 #[derive(Debug)]
@@ -298,12 +413,15 @@ impl PartitionWriter {
     fn next_data_path(&mut self) -> Path {
         self.part_counter += 1;
 
-        next_data_path(
-            &self.config.prefix,
-            self.part_counter,
-            &self.writer_id,
-            &self.config.writer_properties,
-        )
+        let context = FileNameContext {
+            writer_id: &self.writer_id,
+            part_count: self.part_counter,
+            writer_properties: &self.config.writer_properties,
+        };
+        self.config
+            .file_layout
+            .file_prefix(&self.config.prefix)
+            .child(self.config.file_name_strategy.file_name(&context))
     }
 
     fn reset_writer(&mut self) -> DeltaResult<(ArrowWriter<ShareableBuffer>, ShareableBuffer)> {
@@ -594,4 +712,29 @@ mod tests {
             }
         };
     }
+
+    #[test]
+    fn test_hashed_prefix_layout() {
+        let layout = FileLayout::HashedPrefix { num_prefixes: 4 };
+        let partition = Path::from("modified=2021-02-01");
+        let prefixes = (0..4).map(shard_prefix).collect::<Vec<_>>();
+        assert_eq!(
+            prefixes
+                .iter()
+                .collect::<std::collections::HashSet<_>>()
+                .len(),
+            4
+        );
+
+        for _ in 0..32 {
+            let path = layout.file_prefix(&partition);
+            let parts = path.parts().collect::<Vec<_>>();
+            assert_eq!(parts.len(), 2);
+            assert!(prefixes.contains(&parts[0].as_ref().to_string()));
+            assert_eq!(parts[1].as_ref(), "modified=2021-02-01");
+        }
+
+        let path = layout.file_prefix(&Path::default());
+        assert_eq!(path.parts().count(), 1);
+    }
 }
@@ -9,22 +9,27 @@ use arrow_schema::ArrowError;
 use chrono::{Datelike, Utc};
 use futures::{StreamExt, TryStreamExt};
 use lazy_static::lazy_static;
+use object_store::path::Path;
 use object_store::{Error, ObjectStore};
 use parquet::arrow::ArrowWriter;
 use parquet::errors::ParquetError;
 use regex::Regex;
 use serde_json::Value;
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 use super::{time_utils, ProtocolError};
 use crate::kernel::arrow::delta_log_schema_for_table;
 use crate::kernel::{
-    Action, Add as AddAction, DataType, PrimitiveType, Protocol, Remove, StructField, Txn,
+    Action, Add as AddAction, DataType, DomainMetadata, PrimitiveType, Remove, StructField, Txn,
 };
-use crate::logstore::LogStore;
+use crate::logstore::{LogStore, LogStoreRef};
+use crate::storage::read_only::is_read_only_error;
+use crate::table::config::TableConfig;
+use crate::table::log_validation::{validate_log, UnknownActionPolicy};
+use crate::table::pins::PinRegistry;
 use crate::table::state::DeltaTableState;
 use crate::table::{get_partition_col_data_types, CheckPoint, CheckPointBuilder};
-use crate::{open_table_with_version, DeltaTable};
+use crate::{open_table_with_version, DeltaResult, DeltaTable};
 
 type SchemaPath = Vec<String>;
 
@@ -80,6 +85,7 @@ pub const CHECKPOINT_RECORD_BATCH_SIZE: usize = 5000;
 
 /// Creates checkpoint at current table version
 pub async fn create_checkpoint(table: &DeltaTable) -> Result<(), ProtocolError> {
+    check_writable(table)?;
     create_checkpoint_for(
         table.version(),
         table.snapshot().map_err(|_| ProtocolError::NoMetaData)?,
@@ -89,9 +95,18 @@ pub async fn create_checkpoint(table: &DeltaTable) -> Result<(), ProtocolError>
     Ok(())
 }
 
+/// Tables opened in read-only mode must not have their log modified
+fn check_writable(table: &DeltaTable) -> Result<(), ProtocolError> {
+    if table.config.read_only {
+        return Err(ProtocolError::ReadOnlyTable);
+    }
+    Ok(())
+}
+
 /// Delete expires log files before given version from table. The table log retention is based on
 /// the `logRetentionDuration` property of the Delta Table, 30 days by default.
 pub async fn cleanup_metadata(table: &DeltaTable) -> Result<usize, ProtocolError> {
+    check_writable(table)?;
     let log_retention_timestamp = Utc::now().timestamp_millis()
         - table
             .snapshot()
@@ -107,6 +122,68 @@ pub async fn cleanup_metadata(table: &DeltaTable) -> Result<usize, ProtocolError
     .await
 }
 
+/// Delete expired log files like [`cleanup_metadata`], but keep the checkpoint and commits
+/// required to load the oldest version pinned in `registry`, see [`crate::table::pins`].
+pub async fn cleanup_metadata_with_pins(
+    table: &DeltaTable,
+    registry: &dyn PinRegistry,
+) -> Result<usize, ProtocolError> {
+    check_writable(table)?;
+    let log_retention_timestamp = Utc::now().timestamp_millis()
+        - table
+            .snapshot()
+            .map_err(|_| ProtocolError::NoMetaData)?
+            .table_config()
+            .log_retention_duration()
+            .as_millis() as i64;
+    let pinned = registry
+        .min_pinned_version(&table.table_uri())
+        .await
+        .map_err(|err| ProtocolError::Generic(err.to_string()))?;
+    let until_version = match pinned {
+        Some(pinned) if pinned < table.version() => {
+            debug!("keeping logs required to load pinned version {pinned}");
+            latest_checkpoint_version(table.log_store.as_ref(), pinned)
+                .await?
+                .unwrap_or(0)
+        }
+        _ => table.version(),
+    };
+    cleanup_expired_logs_for(
+        until_version,
+        table.log_store.as_ref(),
+        log_retention_timestamp,
+    )
+    .await
+}
+
+/// The version of the latest checkpoint written at or before `version`
+async fn latest_checkpoint_version(
+    log_store: &dyn LogStore,
+    version: i64,
+) -> Result<Option<i64>, ProtocolError> {
+    lazy_static! {
+        static ref CHECKPOINT_REGEX: Regex =
+            Regex::new(r"_delta_log/(\d{20})\.checkpoint.*\.parquet$").unwrap();
+    }
+
+    let mut latest = None;
+    let object_store = log_store.log_object_store();
+    let mut files = object_store.list(Some(log_store.log_path()));
+    while let Some(meta) = files.next().await {
+        let meta = meta?;
+        let checkpoint_version = CHECKPOINT_REGEX
+            .captures(meta.location.as_ref())
+            .and_then(|captures| captures.get(1)?.as_str().parse::<i64>().ok());
+        if let Some(checkpoint_version) = checkpoint_version {
+            if checkpoint_version <= version {
+                latest = latest.max(Some(checkpoint_version));
+            }
+        }
+    }
+    Ok(latest)
+}
+
 /// Loads table from given `table_uri` at given `version` and creates checkpoint for it.
 /// The `cleanup` param decides whether to run metadata cleanup of obsolete logs.
 /// If it's empty then the table's `enableExpiredLogCleanup` is used.
@@ -137,6 +214,49 @@ pub async fn create_checkpoint_for(
     version: i64,
     state: &DeltaTableState,
     log_store: &dyn LogStore,
+) -> Result<(), ProtocolError> {
+    create_checkpoint_with_tombstone_retention(version, state, log_store, false).await
+}
+
+/// Creates checkpoint for a given table version, table state and object store.
+///
+/// Tombstones older than the table's `deletedFileRetentionDuration` are not written to the
+/// checkpoint, unless `retain_expired_tombstones` is set, e.g. to keep them available for audits.
+pub async fn create_checkpoint_with_tombstone_retention(
+    version: i64,
+    state: &DeltaTableState,
+    log_store: &dyn LogStore,
+    retain_expired_tombstones: bool,
+) -> Result<(), ProtocolError> {
+    write_checkpoint(version, state, log_store, retain_expired_tombstones, None).await
+}
+
+/// Creates a checkpoint for a given table version, table state and object store, split into
+/// multiple parts of at most `max_actions_per_part` actions.
+///
+/// Writing and reading the parts of large checkpoints can be parallelized, and no single part
+/// has to be held in memory at once by readers. Checkpoints with fewer actions are written as
+/// a single file.
+pub async fn create_multi_part_checkpoint_for(
+    version: i64,
+    state: &DeltaTableState,
+    log_store: &dyn LogStore,
+    max_actions_per_part: usize,
+) -> Result<(), ProtocolError> {
+    if max_actions_per_part == 0 {
+        return Err(ProtocolError::Generic(
+            "A checkpoint part must hold at least one action".to_string(),
+        ));
+    }
+    write_checkpoint(version, state, log_store, false, Some(max_actions_per_part)).await
+}
+
+async fn write_checkpoint(
+    version: i64,
+    state: &DeltaTableState,
+    log_store: &dyn LogStore,
+    retain_expired_tombstones: bool,
+    max_actions_per_part: Option<usize>,
 ) -> Result<(), ProtocolError> {
     if version != state.version() {
         error!(
@@ -145,26 +265,46 @@ pub async fn create_checkpoint_for(
         );
         return Err(CheckpointError::StaleTableVersion(version, state.version()).into());
     }
+    check_unknown_actions(state, log_store).await?;
 
-    // TODO: checkpoints _can_ be multi-part... haven't actually found a good reference for
-    // an appropriate split point yet though so only writing a single part currently.
-    // See https://github.com/delta-io/delta-rs/issues/288
     let last_checkpoint_path = log_store.log_path().child("_last_checkpoint");
 
     debug!("Writing parquet bytes to checkpoint buffer.");
-    let tombstones = state
-        .unexpired_tombstones(log_store.object_store().clone())
+    let tombstones = if retain_expired_tombstones {
+        state
+            .all_tombstones(log_store.log_object_store().clone())
+            .await
+            .map_err(|_| ProtocolError::Generic("filed to get tombstones".into()))?
+            .collect::<Vec<_>>()
+    } else {
+        state
+            .unexpired_tombstones(log_store.log_object_store().clone())
+            .await
+            .map_err(|_| ProtocolError::Generic("filed to get tombstones".into()))?
+            .collect::<Vec<_>>()
+    };
+    let domain_metadata = state
+        .domain_metadata(log_store.log_object_store())
         .await
-        .map_err(|_| ProtocolError::Generic("filed to get tombstones".into()))?
-        .collect::<Vec<_>>();
-    let (checkpoint, parquet_bytes) = parquet_bytes_from_state(state, tombstones)?;
-
-    let file_name = format!("{version:020}.checkpoint.parquet");
-    let checkpoint_path = log_store.log_path().child(file_name);
-
-    let object_store = log_store.object_store();
-    debug!("Writing checkpoint to {:?}.", checkpoint_path);
-    object_store.put(&checkpoint_path, parquet_bytes).await?;
+        .map_err(|_| ProtocolError::Generic("failed to get domain metadata".into()))?;
+    let (checkpoint, parts) =
+        parquet_bytes_from_state(state, tombstones, domain_metadata, max_actions_per_part)?;
+
+    let object_store = log_store.log_object_store();
+    let num_parts = parts.len();
+    for (index, parquet_bytes) in parts.into_iter().enumerate() {
+        let file_name = if num_parts == 1 {
+            format!("{version:020}.checkpoint.parquet")
+        } else {
+            format!(
+                "{version:020}.checkpoint.{:010}.{num_parts:010}.parquet",
+                index + 1
+            )
+        };
+        let checkpoint_path = log_store.log_path().child(file_name);
+        debug!("Writing checkpoint to {:?}.", checkpoint_path);
+        object_store.put(&checkpoint_path, parquet_bytes).await?;
+    }
 
     let last_checkpoint_content: Value = serde_json::to_value(checkpoint)?;
     let last_checkpoint_content = bytes::Bytes::from(serde_json::to_vec(&last_checkpoint_content)?);
@@ -177,6 +317,29 @@ pub async fn create_checkpoint_for(
     Ok(())
 }
 
+/// Refuse to write a checkpoint which would drop actions or fields unknown to this crate from
+/// the commits it replaces, unless the table ignores them
+async fn check_unknown_actions(
+    state: &DeltaTableState,
+    log_store: &dyn LogStore,
+) -> Result<(), ProtocolError> {
+    if state.snapshot().load_config().unknown_action_policy == UnknownActionPolicy::Ignore {
+        return Ok(());
+    }
+    let versions = state.snapshot().snapshot().commit_versions();
+    let report = validate_log(log_store, versions)
+        .await
+        .map_err(|err| ProtocolError::Generic(err.to_string()))?
+        .into_unknown();
+    match report.violations.first() {
+        Some(violation) => Err(ProtocolError::Generic(format!(
+            "Checkpoint would drop {} unsupported actions or fields, first: {violation}",
+            report.violations.len()
+        ))),
+        None => Ok(()),
+    }
+}
+
 /// Deletes all delta log commits that are older than the cutoff time
 /// and less than the specified version.
 pub async fn cleanup_expired_logs_for(
@@ -189,7 +352,7 @@ pub async fn cleanup_expired_logs_for(
             Regex::new(r"_delta_log/(\d{20})\.(json|checkpoint).*$").unwrap();
     }
 
-    let object_store = log_store.object_store();
+    let object_store = log_store.log_object_store();
     let maybe_last_checkpoint = object_store
         .get(&log_store.log_path().child("_last_checkpoint"))
         .await;
@@ -205,7 +368,7 @@ pub async fn cleanup_expired_logs_for(
     // Feed a stream of candidate deletion files directly into the delete_stream
     // function to try to improve the speed of cleanup and reduce the need for
     // intermediate memory.
-    let object_store = log_store.object_store();
+    let object_store = log_store.log_object_store();
     let deleted = object_store
         .delete_stream(
             object_store
@@ -237,7 +400,11 @@ pub async fn cleanup_expired_logs_for(
                 .boxed(),
         )
         .try_collect::<Vec<_>>()
-        .await?;
+        .await
+        .map_err(|err| match err {
+            err if is_read_only_error(&err) => ProtocolError::ReadOnlyTable,
+            err => err.into(),
+        })?;
 
     debug!("Deleted {} expired logs", deleted.len());
     Ok(deleted.len())
@@ -246,7 +413,9 @@ pub async fn cleanup_expired_logs_for(
 fn parquet_bytes_from_state(
     state: &DeltaTableState,
     mut tombstones: Vec<Remove>,
-) -> Result<(CheckPoint, bytes::Bytes), ProtocolError> {
+    domain_metadata: HashMap<String, DomainMetadata>,
+    max_actions_per_part: Option<usize>,
+) -> Result<(CheckPoint, Vec<bytes::Bytes>), ProtocolError> {
     let current_metadata = state.metadata();
     let schema = current_metadata.schema()?;
 
@@ -256,93 +425,179 @@ fn parquet_bytes_from_state(
     let mut stats_conversions: Vec<(SchemaPath, DataType)> = Vec::new();
     collect_stats_conversions(&mut stats_conversions, schema.fields().as_slice());
 
-    // if any, tombstones do not include extended file metadata, we must omit the extended metadata fields from the remove schema
+    // Tombstones written by other writers may carry the extended file metadata, which vacuum
+    // and readers of the checkpoint rely on. The extended fields are therefore always part of
+    // the remove schema and kept for every tombstone which has them.
     // See https://github.com/delta-io/delta/blob/master/PROTOCOL.md#add-file-and-remove-file
     //
-    // DBR version 8.x and greater have different behaviors of reading the parquet file depending
-    // on the `extended_file_metadata` flag, hence this is safer to set `extended_file_metadata=false`
-    // and omit metadata columns if at least one remove action has `extended_file_metadata=false`.
-    // We've added the additional check on `size.is_some` because in delta-spark the primitive long type
-    // is used, hence we want to omit possible errors when `extended_file_metadata=true`, but `size=null`
-    let use_extended_remove_schema = tombstones
-        .iter()
-        .all(|r| r.extended_file_metadata == Some(true) && r.size.is_some());
-
-    // If use_extended_remove_schema=false for some of the tombstones, then it should be for each.
-    if !use_extended_remove_schema {
-        for remove in tombstones.iter_mut() {
-            remove.extended_file_metadata = Some(false);
-        }
+    // DBR version 8.x and greater read the extended fields depending on the `extendedFileMetadata`
+    // flag, so it is only set where the fields are complete. We've added the additional check on
+    // `size.is_some` because in delta-spark the primitive long type is used, hence we want to
+    // omit possible errors when `extended_file_metadata=true`, but `size=null`
+    for remove in tombstones.iter_mut() {
+        remove.extended_file_metadata =
+            Some(remove.extended_file_metadata == Some(true) && remove.size.is_some());
     }
     let files = state.file_actions().unwrap();
     // protocol
-    let jsons = std::iter::once(Action::Protocol(Protocol {
-        min_reader_version: state.protocol().min_reader_version,
-        min_writer_version: state.protocol().min_writer_version,
-        writer_features: None,
-        reader_features: None,
-    }))
-    // metaData
-    .chain(std::iter::once(Action::Metadata(current_metadata.clone())))
-    // txns
-    .chain(
-        state
-            .app_transaction_version()
-            .iter()
-            .map(|(app_id, version)| {
-                Action::Txn(Txn {
-                    app_id: app_id.clone(),
-                    version: *version,
-                    last_updated: None,
-                })
-            }),
-    )
-    // removes
-    .chain(tombstones.iter().map(|r| {
-        let mut r = (*r).clone();
-
-        // As a "new writer", we should always set `extendedFileMetadata` when writing, and include/ignore the other three fields accordingly.
-        // https://github.com/delta-io/delta/blob/fb0452c2fb142310211c6d3604eefb767bb4a134/core/src/main/scala/org/apache/spark/sql/delta/actions/actions.scala#L311-L314
-        if r.extended_file_metadata.is_none() {
-            r.extended_file_metadata = Some(false);
-        }
+    let jsons = std::iter::once(Action::Protocol(state.protocol().clone()))
+        // metaData
+        .chain(std::iter::once(Action::Metadata(current_metadata.clone())))
+        // txns
+        .chain(
+            state
+                .app_transaction_version()
+                .iter()
+                .map(|(app_id, version)| {
+                    Action::Txn(Txn {
+                        app_id: app_id.clone(),
+                        version: *version,
+                        last_updated: None,
+                    })
+                }),
+        )
+        // domain metadata
+        .chain(domain_metadata.into_values().map(Action::DomainMetadata))
+        // removes
+        .chain(tombstones.iter().map(|r| {
+            let mut r = (*r).clone();
+
+            // As a "new writer", we always set `extendedFileMetadata` when writing, see above.
+            // https://github.com/delta-io/delta/blob/fb0452c2fb142310211c6d3604eefb767bb4a134/core/src/main/scala/org/apache/spark/sql/delta/actions/actions.scala#L311-L314
+            // Absent maps are read as empty maps, which are written as null again. The extended
+            // fields are only written along with the flag.
+            if r.extended_file_metadata != Some(true) {
+                r.partition_values = None;
+                r.size = None;
+            }
+            if r.extended_file_metadata != Some(true)
+                || r.tags.as_ref().is_some_and(|t| t.is_empty())
+            {
+                r.tags = None;
+            }
 
-        Action::Remove(r)
-    }))
-    .map(|a| serde_json::to_value(a).map_err(ProtocolError::from))
-    // adds
-    .chain(files.iter().map(|f| {
-        checkpoint_add_from_state(f, partition_col_data_types.as_slice(), &stats_conversions)
-    }));
+            Action::Remove(r)
+        }))
+        .map(|a| serde_json::to_value(a).map_err(ProtocolError::from))
+        // adds
+        .chain(files.iter().map(|f| {
+            checkpoint_add_from_state(f, partition_col_data_types.as_slice(), &stats_conversions)
+        }));
 
     // Create the arrow schema that represents the Checkpoint parquet file.
     let arrow_schema = delta_log_schema_for_table(
         (&schema).try_into()?,
         current_metadata.partition_columns.as_slice(),
-        use_extended_remove_schema,
+        true,
     );
 
     debug!("Writing to checkpoint parquet buffer...");
-    // Write the Checkpoint parquet file.
-    let mut bytes = vec![];
-    let mut writer = ArrowWriter::try_new(&mut bytes, arrow_schema.clone(), None)?;
-    let mut decoder = ReaderBuilder::new(arrow_schema)
-        .with_batch_size(CHECKPOINT_RECORD_BATCH_SIZE)
-        .build_decoder()?;
     let jsons = jsons.collect::<Result<Vec<serde_json::Value>, _>>()?;
-    decoder.serialize(&jsons)?;
+    let part_size = max_actions_per_part.unwrap_or(jsons.len()).max(1);
+    let mut parts = Vec::new();
+    for part in jsons.chunks(part_size) {
+        // Write the Checkpoint parquet file.
+        let mut bytes = vec![];
+        let mut writer = ArrowWriter::try_new(&mut bytes, arrow_schema.clone(), None)?;
+        let mut decoder = ReaderBuilder::new(arrow_schema.clone())
+            .with_batch_size(CHECKPOINT_RECORD_BATCH_SIZE)
+            .build_decoder()?;
+        decoder.serialize(part)?;
+
+        while let Some(batch) = decoder.flush()? {
+            writer.write(&batch)?;
+        }
 
-    while let Some(batch) = decoder.flush()? {
-        writer.write(&batch)?;
+        let _ = writer.close()?;
+        parts.push(bytes::Bytes::from(bytes));
     }
+    debug!(
+        "Finished writing {} checkpoint parquet buffers.",
+        parts.len()
+    );
 
-    let _ = writer.close()?;
-    debug!("Finished writing checkpoint parquet buffer.");
+    let size_in_bytes = parts.iter().map(|part| part.len() as i64).sum();
+    let mut checkpoint = CheckPointBuilder::new(state.version(), jsons.len() as i64)
+        .with_size_in_bytes(size_in_bytes);
+    if parts.len() > 1 {
+        checkpoint = checkpoint.with_parts(parts.len() as u32);
+    }
+    Ok((checkpoint.build(), parts))
+}
+
+/// Table property splitting the checkpoints created after commits into parts of at most this
+/// many actions.
+///
+/// The property is specific to delta-rs and therefore lives outside of the reserved `delta.`
+/// namespace.
+pub const CHECKPOINT_PART_SIZE_KEY: &str = "delta-rs.checkpoint.maxActionsPerPart";
+
+/// When to create checkpoints after commits and how to split them, as configured for a table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CheckpointSchedule {
+    interval: i64,
+    max_actions_per_part: Option<usize>,
+}
+
+impl CheckpointSchedule {
+    /// Read the schedule from the table's `delta.checkpointInterval` and
+    /// [`CHECKPOINT_PART_SIZE_KEY`] properties
+    pub(crate) fn from_config(config: TableConfig<'_>) -> Self {
+        let max_actions_per_part = config
+            .0
+            .get(CHECKPOINT_PART_SIZE_KEY)
+            .and_then(|value| value.as_deref())
+            .and_then(|value| match value.parse::<usize>() {
+                Ok(size) if size > 0 => Some(size),
+                _ => {
+                    warn!("Ignoring invalid value '{value}' for table property {CHECKPOINT_PART_SIZE_KEY}");
+                    None
+                }
+            });
+        Self {
+            interval: config.checkpoint_interval().max(1) as i64,
+            max_actions_per_part,
+        }
+    }
+
+    fn is_due(&self, version: i64) -> bool {
+        version > 0 && version % self.interval == 0
+    }
+}
 
-    let checkpoint = CheckPointBuilder::new(state.version(), jsons.len() as i64)
-        .with_size_in_bytes(bytes.len() as i64)
-        .build();
-    Ok((checkpoint, bytes::Bytes::from(bytes)))
+/// Create a checkpoint of a committed `version` if it is due according to `schedule`. Returns
+/// whether a checkpoint was written.
+///
+/// The state of the committed version is loaded from the log store.
+pub(crate) async fn checkpoint_after_commit(
+    log_store: &LogStoreRef,
+    version: i64,
+    schedule: CheckpointSchedule,
+) -> DeltaResult<bool> {
+    if !schedule.is_due(version) {
+        return Ok(false);
+    }
+    let state = DeltaTableState::try_new(
+        &Path::default(),
+        log_store.log_object_store(),
+        Default::default(),
+        Some(version),
+    )
+    .await?;
+    match schedule.max_actions_per_part {
+        Some(max_actions_per_part) => {
+            create_multi_part_checkpoint_for(
+                version,
+                &state,
+                log_store.as_ref(),
+                max_actions_per_part,
+            )
+            .await?
+        }
+        None => create_checkpoint_for(version, &state, log_store.as_ref()).await?,
+    }
+    debug!("created checkpoint for version {version}");
+    Ok(true)
 }
 
 fn checkpoint_add_from_state(
@@ -556,7 +811,8 @@ mod tests {
         // Look at the "files" and verify that the _last_checkpoint has the right version
         let path = Path::from("_delta_log/_last_checkpoint");
         let last_checkpoint = table
-            .object_store()
+            .log_store()
+            .log_object_store()
             .get(&path)
             .await
             .expect("Failed to get the _last_checkpoint")
@@ -567,6 +823,80 @@ mod tests {
         assert_eq!(last_checkpoint.version, 0);
     }
 
+    #[tokio::test]
+    async fn test_create_multi_part_checkpoint() {
+        use crate::operations::transaction::CommitProperties;
+        use crate::writer::test_utils::get_record_batch;
+
+        let mut table = DeltaOps::new_in_memory()
+            .create()
+            .with_columns(get_delta_schema().fields().clone())
+            .with_configuration(HashMap::from([
+                (
+                    "delta.checkpointInterval".to_string(),
+                    Some("3".to_string()),
+                ),
+                (CHECKPOINT_PART_SIZE_KEY.to_string(), Some("2".to_string())),
+            ]))
+            .await
+            .unwrap();
+        for _ in 0..3 {
+            table = DeltaOps(table)
+                .write(vec![get_record_batch(None, false)])
+                .with_commit_properties(CommitProperties::default().with_create_checkpoint(true))
+                .await
+                .unwrap();
+        }
+        assert_eq!(table.version(), 3);
+
+        // protocol, metadata and three adds
+        let mut parts = table
+            .log_store()
+            .log_object_store()
+            .list(Some(&Path::from("_delta_log")))
+            .map_ok(|meta| meta.location.to_string())
+            .try_filter(|path| futures::future::ready(path.contains(".checkpoint.")))
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        parts.sort();
+        assert_eq!(
+            parts,
+            vec![
+                "_delta_log/00000000000000000003.checkpoint.0000000001.0000000003.parquet",
+                "_delta_log/00000000000000000003.checkpoint.0000000002.0000000003.parquet",
+                "_delta_log/00000000000000000003.checkpoint.0000000003.0000000003.parquet",
+            ]
+        );
+        let last_checkpoint = table
+            .log_store()
+            .log_object_store()
+            .get(&Path::from("_delta_log/_last_checkpoint"))
+            .await
+            .unwrap()
+            .bytes()
+            .await
+            .unwrap();
+        let last_checkpoint: CheckPoint = serde_json::from_slice(&last_checkpoint).unwrap();
+        assert_eq!(last_checkpoint.version, 3);
+        assert_eq!(last_checkpoint.size, 5);
+        assert_eq!(last_checkpoint.parts, Some(3));
+
+        let mut loaded = DeltaTable::new(table.log_store(), Default::default());
+        loaded.load().await.unwrap();
+        assert_eq!(loaded.version(), 3);
+        assert_eq!(loaded.get_files_count().unwrap(), 3);
+
+        let res = create_multi_part_checkpoint_for(
+            3,
+            table.snapshot().unwrap(),
+            table.log_store.as_ref(),
+            0,
+        )
+        .await;
+        assert!(res.is_err());
+    }
+
     /// This test validates that a checkpoint can be written and re-read with the minimum viable
     /// Metadata. There was a bug which didn't handle the optionality of createdTime.
     #[tokio::test]
@@ -627,7 +957,8 @@ mod tests {
         // Look at the "files" and verify that the _last_checkpoint has the right version
         let path = Path::from("_delta_log/_last_checkpoint");
         let last_checkpoint = table
-            .object_store()
+            .log_store()
+            .log_object_store()
             .get(&path)
             .await
             .expect("Failed to get the _last_checkpoint")
@@ -646,6 +977,166 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_create_checkpoint_expires_tombstones() {
+        let table_schema = get_delta_schema();
+
+        let mut table = DeltaOps::new_in_memory()
+            .create()
+            .with_columns(table_schema.fields().clone())
+            .await
+            .unwrap();
+
+        let remove = |path: &str, deletion_timestamp: i64| {
+            Action::Remove(Remove {
+                path: path.to_string(),
+                deletion_timestamp: Some(deletion_timestamp),
+                data_change: true,
+                extended_file_metadata: None,
+                partition_values: None,
+                size: None,
+                tags: None,
+                deletion_vector: None,
+                base_row_id: None,
+                default_row_commit_version: None,
+            })
+        };
+        let actions = vec![
+            remove("expired.parquet", 0),
+            remove("recent.parquet", Utc::now().timestamp_millis()),
+        ];
+        let operation = crate::protocol::DeltaOperation::StreamingUpdate {
+            output_mode: crate::protocol::OutputMode::Append,
+            query_id: "test".into(),
+            epoch_id: 1,
+        };
+        CommitBuilder::default()
+            .with_actions(actions)
+            .build(
+                table.state.as_ref().map(|f| f as &dyn TableReference),
+                table.log_store(),
+                operation,
+            )
+            .unwrap()
+            .await
+            .unwrap();
+        table.load().await.unwrap();
+        assert_eq!(table.version(), 1);
+
+        let checkpointed_tombstones = |retain: bool| {
+            let table = table.clone();
+            async move {
+                create_checkpoint_with_tombstone_retention(
+                    1,
+                    table.snapshot().unwrap(),
+                    table.log_store.as_ref(),
+                    retain,
+                )
+                .await
+                .unwrap();
+                let mut loaded = DeltaTable::new(table.log_store(), Default::default());
+                loaded.load().await.unwrap();
+                let mut paths = loaded
+                    .snapshot()
+                    .unwrap()
+                    .all_tombstones(loaded.log_store().log_object_store())
+                    .await
+                    .unwrap()
+                    .map(|r| r.path)
+                    .collect::<Vec<_>>();
+                paths.sort();
+                paths
+            }
+        };
+
+        assert_eq!(checkpointed_tombstones(false).await, vec!["recent.parquet"]);
+        assert_eq!(
+            checkpointed_tombstones(true).await,
+            vec!["expired.parquet", "recent.parquet"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_checkpoint_preserves_extended_tombstones() {
+        let table_schema = get_delta_schema();
+        let mut table = DeltaOps::new_in_memory()
+            .create()
+            .with_columns(table_schema.fields().clone())
+            .await
+            .unwrap();
+
+        let timestamp = Utc::now().timestamp_millis();
+        let extended = Remove {
+            path: "extended.parquet".to_string(),
+            deletion_timestamp: Some(timestamp),
+            data_change: true,
+            extended_file_metadata: Some(true),
+            partition_values: Some(HashMap::new()),
+            size: Some(100),
+            tags: Some(HashMap::from([(
+                "writer".to_string(),
+                Some("spark".to_string()),
+            )])),
+            deletion_vector: None,
+            base_row_id: None,
+            default_row_commit_version: None,
+        };
+        let plain = Remove {
+            path: "plain.parquet".to_string(),
+            extended_file_metadata: None,
+            partition_values: None,
+            size: None,
+            tags: None,
+            ..extended.clone()
+        };
+        let operation = crate::protocol::DeltaOperation::StreamingUpdate {
+            output_mode: crate::protocol::OutputMode::Append,
+            query_id: "test".into(),
+            epoch_id: 1,
+        };
+        CommitBuilder::default()
+            .with_actions(vec![
+                Action::Remove(extended.clone()),
+                Action::Remove(plain.clone()),
+            ])
+            .build(
+                table.state.as_ref().map(|f| f as &dyn TableReference),
+                table.log_store(),
+                operation,
+            )
+            .unwrap()
+            .await
+            .unwrap();
+        table.load().await.unwrap();
+        create_checkpoint_for(1, table.snapshot().unwrap(), table.log_store.as_ref())
+            .await
+            .unwrap();
+
+        let mut loaded = DeltaTable::new(table.log_store(), Default::default());
+        loaded.load().await.unwrap();
+        let mut tombstones = loaded
+            .snapshot()
+            .unwrap()
+            .all_tombstones(loaded.log_store().log_object_store())
+            .await
+            .unwrap()
+            .collect::<Vec<_>>();
+        tombstones.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(
+            tombstones,
+            vec![
+                extended,
+                // absent maps are read as empty maps
+                Remove {
+                    extended_file_metadata: Some(false),
+                    partition_values: Some(HashMap::new()),
+                    tags: Some(HashMap::new()),
+                    ..plain
+                }
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_create_checkpoint_for_invalid_version() {
         let table_schema = get_delta_schema();
@@ -853,7 +1344,7 @@ mod tests {
         println!("{:?}", count);
 
         let path = Path::from("_delta_log/00000000000000000000.json");
-        let res = table.log_store().object_store().get(&path).await;
+        let res = table.log_store().log_object_store().get(&path).await;
         assert!(res.is_ok());
     }
 
@@ -882,20 +1373,70 @@ mod tests {
         let log_store = table.log_store();
 
         let path = log_store.log_path().child("00000000000000000000.json");
-        let res = table.log_store().object_store().get(&path).await;
+        let res = table.log_store().log_object_store().get(&path).await;
         assert!(res.is_err());
 
         let path = log_store
             .log_path()
             .child("00000000000000000001.checkpoint.parquet");
-        let res = table.log_store().object_store().get(&path).await;
+        let res = table.log_store().log_object_store().get(&path).await;
         assert!(res.is_ok());
 
         let path = log_store.log_path().child("00000000000000000001.json");
-        let res = table.log_store().object_store().get(&path).await;
+        let res = table.log_store().log_object_store().get(&path).await;
         assert!(res.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_cleanup_read_only_table() {
+        let table = setup_table().await;
+        create_checkpoint(&table).await.unwrap();
+        let table = crate::DeltaTableBuilder::from_uri("memory://")
+            .with_storage_backend(table.object_store(), url::Url::parse("memory://").unwrap())
+            .read_only()
+            .load()
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            cleanup_metadata(&table).await,
+            Err(ProtocolError::ReadOnlyTable)
+        ));
+        assert!(matches!(
+            create_checkpoint(&table).await,
+            Err(ProtocolError::ReadOnlyTable)
+        ));
+
+        // the store of the table rejects deletes by callers bypassing the table
+        let log_retention_timestamp =
+            Utc::now().timestamp_millis() + Duration::days(32).num_milliseconds();
+        let res = cleanup_expired_logs_for(
+            table.version(),
+            table.log_store().as_ref(),
+            log_retention_timestamp,
+        )
+        .await;
+        assert!(matches!(res, Err(ProtocolError::ReadOnlyTable)));
+
+        let path = table
+            .log_store()
+            .log_path()
+            .child("00000000000000000000.json");
+        assert!(table.object_store().head(&path).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_latest_checkpoint_version() {
+        let table = setup_table().await;
+        create_checkpoint(&table).await.unwrap();
+
+        let log_store = table.log_store();
+        let latest = latest_checkpoint_version(log_store.as_ref(), 0).await;
+        assert_eq!(latest.unwrap(), None);
+        let latest = latest_checkpoint_version(log_store.as_ref(), 1).await;
+        assert_eq!(latest.unwrap(), Some(1));
+    }
+
     #[test]
     fn apply_stats_conversion_test() {
         let mut stats = STATS_JSON.clone();
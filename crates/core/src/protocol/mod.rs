@@ -4,7 +4,7 @@
 
 pub mod checkpoints;
 mod parquet_read;
-mod time_utils;
+pub(crate) mod time_utils;
 
 use arrow_schema::ArrowError;
 use futures::StreamExt;
@@ -54,6 +54,10 @@ pub enum ProtocolError {
     #[error("Generic action error: {0}")]
     Generic(String),
 
+    /// The table was opened in read-only mode
+    #[error("Table was opened in read-only mode, modifying its log is not allowed")]
+    ReadOnlyTable,
+
     /// Error returned when parsing checkpoint parquet using the parquet crate.
     #[error("Failed to parse parquet checkpoint: {source}")]
     ParquetParseError {
@@ -441,6 +445,22 @@ pub enum DeltaOperation {
         /// The status of the operation
         status: String,
     },
+
+    #[serde(rename_all = "camelCase")]
+    /// Represents an `Analyze` operation computing a table statistics summary
+    Analyze {
+        /// The columns that were analyzed, all columns if not set
+        columns: Option<Vec<String>>,
+        /// Fraction of rows sampled while computing the statistics
+        sample_fraction: f64,
+    },
+
+    #[serde(rename_all = "camelCase")]
+    /// Represents a `Reorg` operation purging deletion vectors by rewriting files
+    Reorg {
+        /// Fraction of deleted rows above which files were rewritten
+        deletion_vector_threshold: f64,
+    },
 }
 
 impl DeltaOperation {
@@ -465,6 +485,8 @@ impl DeltaOperation {
             DeltaOperation::VacuumEnd { .. } => "VACUUM END",
             DeltaOperation::AddConstraint { .. } => "ADD CONSTRAINT",
             DeltaOperation::DropConstraint { .. } => "DROP CONSTRAINT",
+            DeltaOperation::Analyze { .. } => "ANALYZE",
+            DeltaOperation::Reorg { .. } => "REORG",
         }
     }
 
@@ -504,7 +526,9 @@ impl DeltaOperation {
             | Self::VacuumStart { .. }
             | Self::VacuumEnd { .. }
             | Self::AddConstraint { .. }
-            | Self::DropConstraint { .. } => false,
+            | Self::DropConstraint { .. }
+            | Self::Analyze { .. }
+            | Self::Reorg { .. } => false,
             Self::Create { .. }
             | Self::FileSystemCheck {}
             | Self::StreamingUpdate { .. }
@@ -595,7 +619,11 @@ pub(crate) async fn get_last_checkpoint(
 ) -> Result<CheckPoint, ProtocolError> {
     let last_checkpoint_path = Path::from_iter(["_delta_log", "_last_checkpoint"]);
     debug!("loading checkpoint from {last_checkpoint_path}");
-    match log_store.object_store().get(&last_checkpoint_path).await {
+    match log_store
+        .log_object_store()
+        .get(&last_checkpoint_path)
+        .await
+    {
         Ok(data) => Ok(serde_json::from_slice(&data.bytes().await?)?),
         Err(ObjectStoreError::NotFound { .. }) => {
             match find_latest_check_point_for_version(log_store, i64::MAX).await {
@@ -619,7 +647,7 @@ pub(crate) async fn find_latest_check_point_for_version(
     }
 
     let mut cp: Option<CheckPoint> = None;
-    let object_store = log_store.object_store();
+    let object_store = log_store.log_object_store();
     let mut stream = object_store.list(Some(log_store.log_path()));
 
     while let Some(obj_meta) = stream.next().await {
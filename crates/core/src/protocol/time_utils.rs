@@ -1,9 +1,21 @@
 //! Utility functions for converting time formats.
 #![allow(unused)]
 
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 use arrow::temporal_conversions;
 use parquet::basic::TimeUnit;
 
+/// Convert milliseconds since the unix epoch, as used for timestamps in the log, to a [`SystemTime`].
+pub(crate) fn system_time_from_millis(millis: i64) -> SystemTime {
+    let offset = Duration::from_millis(millis.unsigned_abs());
+    if millis >= 0 {
+        UNIX_EPOCH + offset
+    } else {
+        UNIX_EPOCH - offset
+    }
+}
+
 /// Convert an ISO-8601/RFC3339 timestamp string to a numeric microsecond epoch representation.
 /// Stats strings are written with millisecond precision as described by the delta protocol.
 pub fn timestamp_micros_from_stats_string(s: &str) -> Result<i64, chrono::format::ParseError> {
@@ -26,6 +38,19 @@ mod tests {
     use super::*;
     use parquet::format::{MicroSeconds, MilliSeconds, NanoSeconds, TimeUnit};
 
+    #[test]
+    fn test_system_time_from_millis() {
+        use std::time::{Duration, UNIX_EPOCH};
+        assert_eq!(
+            system_time_from_millis(1_500),
+            UNIX_EPOCH + Duration::from_millis(1_500)
+        );
+        assert_eq!(
+            system_time_from_millis(-1_500),
+            UNIX_EPOCH - Duration::from_millis(1_500)
+        );
+    }
+
     #[test]
     fn test_timestamp_to_delta_stats_string() {
         let s =
@@ -0,0 +1,483 @@
+//! Schema compatibility checks.
+//!
+//! [`check`] compares the schema of a table with a proposed new schema and reports every
+//! difference, classified by Delta's schema evolution rules:
+//!
+//! - adding nullable columns and relaxing nullability are safe,
+//! - widening a column type is allowed on tables with the type widening feature enabled,
+//! - dropping columns, narrowing or changing types, adding non-nullable columns and
+//!   tightening nullability break existing data or readers.
+//!
+//! Columns are matched by name, ignoring case as Delta does, so reordering columns is not a
+//! change. Nested fields are reported with their dotted path, array elements as `element` and
+//! map keys and values as `key` and `value`.
+//!
+//! # Example
+//! ```rust ignore
+//! let report = compat::check(&table.get_schema()?, &proposed);
+//! if !report.is_compatible() {
+//!     panic!("Breaking schema change:\n{report}");
+//! }
+//! ````
+
+use std::fmt::{Display, Formatter};
+
+use serde::Serialize;
+
+use crate::kernel::{DataType, PrimitiveType, StructType};
+
+/// How a schema change affects a table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    /// The change can be applied to any table
+    Safe,
+    /// The change can only be applied to tables with the type widening feature enabled
+    RequiresTypeWidening,
+    /// The change is incompatible with existing data or readers
+    Breaking,
+}
+
+/// A single difference between two schemas
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ChangeKind {
+    /// A column was added
+    Added {
+        /// Whether the added column is nullable
+        nullable: bool,
+    },
+    /// A column was removed
+    Removed,
+    /// The case of a column name changed
+    Renamed {
+        /// The new column name
+        to: String,
+    },
+    /// A column type was widened without loss of information
+    TypeWidened {
+        /// The previous type
+        from: DataType,
+        /// The new type
+        to: DataType,
+    },
+    /// A column type was changed incompatibly
+    TypeChanged {
+        /// The previous type
+        from: DataType,
+        /// The new type
+        to: DataType,
+    },
+    /// A column, array element or map value became nullable
+    NullabilityRelaxed,
+    /// A column, array element or map value became non-nullable
+    NullabilityTightened,
+}
+
+impl ChangeKind {
+    /// How this change affects a table
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::Added { nullable: true } | Self::NullabilityRelaxed => Severity::Safe,
+            Self::TypeWidened { .. } => Severity::RequiresTypeWidening,
+            Self::Added { nullable: false }
+            | Self::Removed
+            | Self::Renamed { .. }
+            | Self::TypeChanged { .. }
+            | Self::NullabilityTightened => Severity::Breaking,
+        }
+    }
+}
+
+/// A difference between two schemas at a column path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SchemaChange {
+    /// Dotted path of the changed column in the old schema, or the new schema for additions
+    pub path: String,
+    /// The change
+    #[serde(flatten)]
+    pub kind: ChangeKind,
+}
+
+impl SchemaChange {
+    /// How this change affects a table
+    pub fn severity(&self) -> Severity {
+        self.kind.severity()
+    }
+}
+
+impl Display for SchemaChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            ChangeKind::Added { nullable: true } => write!(f, "{}: added", self.path),
+            ChangeKind::Added { nullable: false } => {
+                write!(f, "{}: added as non-nullable", self.path)
+            }
+            ChangeKind::Removed => write!(f, "{}: removed", self.path),
+            ChangeKind::Renamed { to } => write!(f, "{}: renamed to {}", self.path, to),
+            ChangeKind::TypeWidened { from, to } => {
+                write!(f, "{}: widened from {} to {}", self.path, from, to)
+            }
+            ChangeKind::TypeChanged { from, to } => {
+                write!(f, "{}: changed from {} to {}", self.path, from, to)
+            }
+            ChangeKind::NullabilityRelaxed => write!(f, "{}: made nullable", self.path),
+            ChangeKind::NullabilityTightened => write!(f, "{}: made non-nullable", self.path),
+        }
+    }
+}
+
+/// The differences between two schemas
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CompatReport {
+    /// All differences, in schema order
+    pub changes: Vec<SchemaChange>,
+}
+
+impl CompatReport {
+    /// The highest severity of all changes, or `None` if the schemas are equivalent
+    pub fn severity(&self) -> Option<Severity> {
+        self.changes.iter().map(SchemaChange::severity).max()
+    }
+
+    /// Whether the new schema can be applied to any table
+    pub fn is_compatible(&self) -> bool {
+        self.severity()
+            .map_or(true, |severity| severity == Severity::Safe)
+    }
+
+    /// Whether the new schema can be applied to tables with the type widening feature enabled
+    pub fn is_compatible_with_type_widening(&self) -> bool {
+        self.severity() != Some(Severity::Breaking)
+    }
+
+    /// Changes that are incompatible with existing data or readers
+    pub fn breaking_changes(&self) -> impl Iterator<Item = &SchemaChange> {
+        self.changes
+            .iter()
+            .filter(|change| change.severity() == Severity::Breaking)
+    }
+}
+
+impl Display for CompatReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare the `old` schema of a table with a proposed `new` schema
+pub fn check(old: &StructType, new: &StructType) -> CompatReport {
+    let mut report = CompatReport::default();
+    check_struct(None, old, new, &mut report.changes);
+    report
+}
+
+fn child_path(parent: Option<&str>, name: &str) -> String {
+    match parent {
+        Some(parent) => format!("{parent}.{name}"),
+        None => name.to_string(),
+    }
+}
+
+fn check_struct(
+    path: Option<&str>,
+    old: &StructType,
+    new: &StructType,
+    changes: &mut Vec<SchemaChange>,
+) {
+    for old_field in old.fields() {
+        let field_path = child_path(path, old_field.name());
+        let Some(new_field) = new
+            .fields()
+            .iter()
+            .find(|field| field.name().eq_ignore_ascii_case(old_field.name()))
+        else {
+            changes.push(SchemaChange {
+                path: field_path,
+                kind: ChangeKind::Removed,
+            });
+            continue;
+        };
+        if new_field.name() != old_field.name() {
+            changes.push(SchemaChange {
+                path: field_path.clone(),
+                kind: ChangeKind::Renamed {
+                    to: new_field.name().clone(),
+                },
+            });
+        }
+        check_nullability(
+            &field_path,
+            old_field.is_nullable(),
+            new_field.is_nullable(),
+            changes,
+        );
+        check_type(
+            &field_path,
+            old_field.data_type(),
+            new_field.data_type(),
+            changes,
+        );
+    }
+
+    for new_field in new.fields() {
+        let exists = old
+            .fields()
+            .iter()
+            .any(|field| field.name().eq_ignore_ascii_case(new_field.name()));
+        if !exists {
+            changes.push(SchemaChange {
+                path: child_path(path, new_field.name()),
+                kind: ChangeKind::Added {
+                    nullable: new_field.is_nullable(),
+                },
+            });
+        }
+    }
+}
+
+fn check_nullability(path: &str, old: bool, new: bool, changes: &mut Vec<SchemaChange>) {
+    let kind = match (old, new) {
+        (false, true) => ChangeKind::NullabilityRelaxed,
+        (true, false) => ChangeKind::NullabilityTightened,
+        _ => return,
+    };
+    changes.push(SchemaChange {
+        path: path.to_string(),
+        kind,
+    });
+}
+
+fn check_type(path: &str, old: &DataType, new: &DataType, changes: &mut Vec<SchemaChange>) {
+    match (old, new) {
+        (DataType::Struct(old), DataType::Struct(new)) => {
+            check_struct(Some(path), old, new, changes)
+        }
+        (DataType::Array(old), DataType::Array(new)) => {
+            let element_path = child_path(Some(path), "element");
+            check_nullability(
+                &element_path,
+                old.contains_null(),
+                new.contains_null(),
+                changes,
+            );
+            check_type(
+                &element_path,
+                old.element_type(),
+                new.element_type(),
+                changes,
+            );
+        }
+        (DataType::Map(old), DataType::Map(new)) => {
+            check_type(
+                &child_path(Some(path), "key"),
+                old.key_type(),
+                new.key_type(),
+                changes,
+            );
+            let value_path = child_path(Some(path), "value");
+            check_nullability(
+                &value_path,
+                old.value_contains_null(),
+                new.value_contains_null(),
+                changes,
+            );
+            check_type(&value_path, old.value_type(), new.value_type(), changes);
+        }
+        (DataType::Primitive(old), DataType::Primitive(new)) if old == new => {}
+        (DataType::Primitive(from), DataType::Primitive(to)) if is_widening(from, to) => changes
+            .push(SchemaChange {
+                path: path.to_string(),
+                kind: ChangeKind::TypeWidened {
+                    from: old.clone(),
+                    to: new.clone(),
+                },
+            }),
+        _ => changes.push(SchemaChange {
+            path: path.to_string(),
+            kind: ChangeKind::TypeChanged {
+                from: old.clone(),
+                to: new.clone(),
+            },
+        }),
+    }
+}
+
+/// Type changes supported by the Delta type widening table feature
+fn is_widening(from: &PrimitiveType, to: &PrimitiveType) -> bool {
+    use PrimitiveType::*;
+
+    match (from, to) {
+        (Byte, Short | Integer | Long)
+        | (Short, Integer | Long)
+        | (Integer, Long)
+        | (Float, Double)
+        | (Byte | Short | Integer, Double)
+        | (Date, TimestampNtz) => true,
+        (Byte | Short | Integer | Long, Decimal(precision, scale)) => {
+            let digits = match from {
+                Byte => 3,
+                Short => 5,
+                Integer => 10,
+                _ => 20,
+            };
+            (*precision as i16 - *scale as i16) >= digits
+        }
+        (Decimal(from_precision, from_scale), Decimal(to_precision, to_scale)) => {
+            to_scale >= from_scale
+                && (*to_precision as i16 - *to_scale as i16)
+                    >= (*from_precision as i16 - *from_scale as i16)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::{ArrayType, MapType, StructField};
+
+    fn schema(fields: Vec<StructField>) -> StructType {
+        StructType::new(fields)
+    }
+
+    fn kinds(report: &CompatReport) -> Vec<(&str, &ChangeKind)> {
+        report
+            .changes
+            .iter()
+            .map(|change| (change.path.as_str(), &change.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_identical_and_reordered() {
+        let old = schema(vec![
+            StructField::new("id", DataType::LONG, false),
+            StructField::new("value", DataType::STRING, true),
+        ]);
+        let new = schema(vec![
+            StructField::new("value", DataType::STRING, true),
+            StructField::new("id", DataType::LONG, false),
+        ]);
+        let report = check(&old, &new);
+        assert!(report.changes.is_empty());
+        assert_eq!(report.severity(), None);
+        assert!(report.is_compatible());
+    }
+
+    #[test]
+    fn test_safe_changes() {
+        let old = schema(vec![StructField::new("id", DataType::LONG, false)]);
+        let new = schema(vec![
+            StructField::new("id", DataType::LONG, true),
+            StructField::new("value", DataType::STRING, true),
+        ]);
+        let report = check(&old, &new);
+        assert_eq!(
+            kinds(&report),
+            vec![
+                ("id", &ChangeKind::NullabilityRelaxed),
+                ("value", &ChangeKind::Added { nullable: true }),
+            ]
+        );
+        assert!(report.is_compatible());
+    }
+
+    #[test]
+    fn test_widening() {
+        let old = schema(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new("b", DataType::decimal(10, 2), true),
+            StructField::new("c", DataType::LONG, true),
+        ]);
+        let new = schema(vec![
+            StructField::new("a", DataType::LONG, true),
+            StructField::new("b", DataType::decimal(12, 4), true),
+            StructField::new("c", DataType::INTEGER, true),
+        ]);
+        let report = check(&old, &new);
+        assert_eq!(report.changes[0].severity(), Severity::RequiresTypeWidening);
+        assert_eq!(report.changes[1].severity(), Severity::RequiresTypeWidening);
+        assert_eq!(
+            report.changes[2].kind,
+            ChangeKind::TypeChanged {
+                from: DataType::LONG,
+                to: DataType::INTEGER
+            }
+        );
+        assert!(!report.is_compatible_with_type_widening());
+        assert_eq!(report.breaking_changes().count(), 1);
+    }
+
+    #[test]
+    fn test_nested_changes() {
+        let nested = |value: DataType, nullable: bool| {
+            schema(vec![
+                StructField::new(
+                    "s",
+                    DataType::struct_type(vec![StructField::new("x", value, true)]),
+                    true,
+                ),
+                StructField::new("l", ArrayType::new(DataType::INTEGER, nullable), true),
+                StructField::new(
+                    "m",
+                    MapType::new(DataType::STRING, DataType::FLOAT, nullable),
+                    true,
+                ),
+            ])
+        };
+        let report = check(
+            &nested(DataType::INTEGER, true),
+            &nested(DataType::STRING, false),
+        );
+        assert_eq!(
+            kinds(&report),
+            vec![
+                (
+                    "s.x",
+                    &ChangeKind::TypeChanged {
+                        from: DataType::INTEGER,
+                        to: DataType::STRING
+                    }
+                ),
+                ("l.element", &ChangeKind::NullabilityTightened),
+                ("m.value", &ChangeKind::NullabilityTightened),
+            ]
+        );
+        assert_eq!(report.severity(), Some(Severity::Breaking));
+    }
+
+    #[test]
+    fn test_breaking_changes() {
+        let old = schema(vec![
+            StructField::new("id", DataType::LONG, true),
+            StructField::new("Value", DataType::STRING, true),
+        ]);
+        let new = schema(vec![
+            StructField::new("value", DataType::STRING, true),
+            StructField::new("required", DataType::STRING, false),
+        ]);
+        let report = check(&old, &new);
+        assert_eq!(
+            kinds(&report),
+            vec![
+                ("id", &ChangeKind::Removed),
+                (
+                    "Value",
+                    &ChangeKind::Renamed {
+                        to: "value".to_string()
+                    }
+                ),
+                ("required", &ChangeKind::Added { nullable: false }),
+            ]
+        );
+        assert_eq!(report.breaking_changes().count(), 3);
+        assert_eq!(
+            report.to_string(),
+            "id: removed\nValue: renamed to value\nrequired: added as non-nullable\n"
+        );
+    }
+}
@@ -0,0 +1,257 @@
+//! Schema fingerprints and the history of schema changes of a table.
+//!
+//! A [`fingerprint`] identifies a schema independently of how it was serialized: two schemas
+//! have the same fingerprint if and only if their fields, types, nullability and metadata are
+//! equal. Fingerprints are stable across releases and processes, so they can be stored by
+//! downstream systems enforcing data contracts and compared with the fingerprint of the
+//! current version of a table.
+//!
+//! [`schema_changes`] walks the log and yields a [`SchemaEvolution`] for every commit which
+//! changed the schema, with the differences to the previous schema classified by
+//! [`compat::check`](super::compat::check), so breaking changes can be alerted on.
+//!
+//! # Example
+//! ```rust ignore
+//! let mut changes = table.schema_changes(last_seen_version);
+//! while let Some(evolution) = changes.try_next().await? {
+//!     if !evolution.diff.is_compatible() {
+//!         alert(evolution.version, &evolution.diff);
+//!     }
+//! }
+//! ````
+
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::compat::{check, CompatReport};
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::kernel::{Action, StructType};
+use crate::logstore::{get_actions, LogStoreRef};
+use crate::table::builder::DeltaTableConfig;
+use crate::DeltaTable;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// A change of the schema of a table
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaEvolution {
+    /// Version of the commit which changed the schema
+    pub version: i64,
+    /// Time of the commit in milliseconds since the Unix epoch, if recorded
+    pub timestamp: Option<i64>,
+    /// Fingerprint of the schema before the commit
+    pub previous_fingerprint: String,
+    /// Fingerprint of the schema written by the commit
+    pub fingerprint: String,
+    /// The schema written by the commit
+    pub schema: StructType,
+    /// The differences to the schema before the commit
+    pub diff: CompatReport,
+}
+
+/// A stable fingerprint of `schema`, as 16 hexadecimal digits
+pub fn fingerprint(schema: &StructType) -> DeltaResult<String> {
+    let mut canonical = String::new();
+    write_canonical(&serde_json::to_value(schema)?, &mut canonical);
+    let hash = canonical.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    });
+    Ok(format!("{hash:016x}"))
+}
+
+/// Write `value` as JSON with object keys sorted, so that maps serialize identically
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Array(values) => {
+            out.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(value, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_by_key(|(key, _)| *key);
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::from(key.as_str()).to_string());
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+        value => out.push_str(&value.to_string()),
+    }
+}
+
+/// The schema of the table at `version`, or an empty schema before the table was created
+async fn schema_at(log_store: &LogStoreRef, version: i64) -> DeltaResult<StructType> {
+    if version < 0 {
+        return Ok(StructType::new(vec![]));
+    }
+    let config = DeltaTableConfig {
+        require_files: false,
+        ..Default::default()
+    };
+    let mut table = DeltaTable::new(log_store.clone(), config);
+    table.load_version(version).await?;
+    Ok(table.get_schema()?.clone())
+}
+
+/// Stream the changes of the schema of the table committed after `since_version` up to and
+/// including `until_version`.
+///
+/// Metadata updates which leave the schema untouched, e.g. changes of the table
+/// configuration, are skipped. Pass a negative `since_version` to include the schema the
+/// table was created with.
+pub fn schema_changes(
+    log_store: LogStoreRef,
+    since_version: i64,
+    until_version: i64,
+) -> BoxStream<'static, DeltaResult<SchemaEvolution>> {
+    futures::stream::try_unfold(
+        (log_store, None, since_version),
+        move |(log_store, previous, mut version)| async move {
+            let previous = match previous {
+                Some(previous) => previous,
+                None => schema_at(&log_store, since_version).await?,
+            };
+            while version < until_version {
+                version += 1;
+                let Some(commit) = log_store.read_commit_entry(version).await? else {
+                    continue;
+                };
+                let actions = get_actions(version, commit).await?;
+                let Some(metadata) = actions.iter().find_map(|action| match action {
+                    Action::Metadata(metadata) => Some(metadata),
+                    _ => None,
+                }) else {
+                    continue;
+                };
+                let schema = metadata.schema()?;
+                let previous_fingerprint = fingerprint(&previous)?;
+                let schema_fingerprint = fingerprint(&schema)?;
+                if schema_fingerprint == previous_fingerprint {
+                    continue;
+                }
+                let timestamp = actions.iter().find_map(|action| match action {
+                    Action::CommitInfo(info) => info.timestamp,
+                    _ => None,
+                });
+                let evolution = SchemaEvolution {
+                    version,
+                    timestamp,
+                    previous_fingerprint,
+                    fingerprint: schema_fingerprint,
+                    diff: check(&previous, &schema),
+                    schema: schema.clone(),
+                };
+                return Ok(Some((evolution, (log_store, Some(schema), version))));
+            }
+            Ok::<_, DeltaTableError>(None)
+        },
+    )
+    .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+
+    use super::*;
+    use crate::kernel::{DataType, PrimitiveType, StructField};
+    use crate::operations::DeltaOps;
+    use crate::protocol::SaveMode;
+    use crate::schema::compat::ChangeKind;
+
+    #[test]
+    fn test_fingerprint() {
+        let field = |metadata: Vec<(&str, i32)>| {
+            StructField::new("id", DataType::Primitive(PrimitiveType::Integer), true)
+                .with_metadata(metadata)
+        };
+        let schema = StructType::new(vec![field(vec![("a", 1), ("b", 2)])]);
+        let reordered = StructType::new(vec![field(vec![("b", 2), ("a", 1)])]);
+        let changed = StructType::new(vec![field(vec![("a", 1)])]);
+        assert_eq!(fingerprint(&schema).unwrap().len(), 16);
+        assert_eq!(
+            fingerprint(&schema).unwrap(),
+            fingerprint(&reordered).unwrap()
+        );
+        assert_ne!(
+            fingerprint(&schema).unwrap(),
+            fingerprint(&changed).unwrap()
+        );
+        assert_eq!(
+            fingerprint(&StructType::new(vec![])).unwrap(),
+            "6b7a116fa47ccacf"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_schema_changes() {
+        let schema = StructType::new(vec![StructField::new(
+            "id",
+            DataType::Primitive(PrimitiveType::Integer),
+            true,
+        )]);
+        let table = DeltaOps::new_in_memory()
+            .create()
+            .with_columns(schema.fields().clone())
+            .await
+            .unwrap();
+        // replaces the metadata without changing the schema
+        let table = DeltaOps(table)
+            .create()
+            .with_save_mode(SaveMode::Overwrite)
+            .with_columns(schema.fields().clone())
+            .await
+            .unwrap();
+        let table = DeltaOps(table)
+            .create()
+            .with_save_mode(SaveMode::Overwrite)
+            .with_columns(vec![StructField::new(
+                "value",
+                DataType::Primitive(PrimitiveType::String),
+                true,
+            )])
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 2);
+
+        let changes = table
+            .schema_changes(-1)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].version, 0);
+        assert!(changes[0].diff.is_compatible());
+        assert_eq!(changes[1].version, 2);
+        assert_eq!(changes[1].previous_fingerprint, changes[0].fingerprint);
+        assert_eq!(changes[1].fingerprint, table.schema_fingerprint().unwrap());
+        assert!(!changes[1].diff.is_compatible());
+        assert!(changes[1]
+            .diff
+            .changes
+            .iter()
+            .any(|change| change.path == "id" && change.kind == ChangeKind::Removed));
+
+        let changes = table
+            .schema_changes(2)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert!(changes.is_empty());
+    }
+}
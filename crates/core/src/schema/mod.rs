@@ -1,2 +1,4 @@
 //! Delta Table schema implementation.
+pub mod compat;
+pub mod evolution;
 pub mod partitions;
@@ -0,0 +1,497 @@
+//! Framework agnostic handlers to serve a delta table over HTTP
+//!
+//! [`TableService`] implements the requests an HTTP facade on a delta table needs: reading the
+//! schema and version, scanning the table into an Arrow IPC stream and committing data files
+//! that were staged in the table directory by the client. Handlers return a
+//! [`ServiceResponse`] with a status code, content type and body, which web frameworks like
+//! axum or actix can convert into their own response types. Errors are mapped to status codes
+//! with [`ServiceResponse::from_error`].
+//!
+//! # Example
+//! ```rust ignore
+//! let service = Arc::new(TableService::try_new("s3://bucket/table", HashMap::new())?);
+//!
+//! async fn version(State(service): State<Arc<TableService>>) -> Response {
+//!     let response = service
+//!         .get_version()
+//!         .await
+//!         .unwrap_or_else(ServiceResponse::from_error);
+//!     (
+//!         StatusCode::from_u16(response.status).unwrap(),
+//!         [(CONTENT_TYPE, response.content_type)],
+//!         Body::from_stream(response.body.into_stream()),
+//!     )
+//!         .into_response()
+//! }
+//! ````
+
+use std::collections::HashMap;
+
+use arrow::record_batch::RecordBatch;
+use arrow_ipc::writer::StreamWriter;
+use arrow_schema::SchemaRef as ArrowSchemaRef;
+use bytes::Bytes;
+use futures::stream::{self, BoxStream, StreamExt};
+use object_store::path::Path;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::kernel::{Action, Add};
+use crate::logstore::LogStoreRef;
+use crate::operations::transaction::{CommitBuilder, CommitProperties, TransactionError};
+use crate::protocol::{DeltaOperation, SaveMode};
+use crate::table::builder::DeltaTableBuilder;
+use crate::table::limits::TableLimiter;
+use crate::DeltaTable;
+
+/// Content type of JSON responses
+pub const JSON_CONTENT_TYPE: &str = "application/json";
+/// Content type of Arrow IPC stream responses
+pub const ARROW_STREAM_CONTENT_TYPE: &str = "application/vnd.apache.arrow.stream";
+
+/// Errors caused by invalid requests
+#[derive(thiserror::Error, Debug)]
+enum ServiceError {
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+}
+
+impl From<ServiceError> for DeltaTableError {
+    fn from(err: ServiceError) -> Self {
+        DeltaTableError::GenericError {
+            source: Box::new(err),
+        }
+    }
+}
+
+/// Body of a [`ServiceResponse`]
+pub enum ServiceBody {
+    /// A body available in full
+    Full(Bytes),
+    /// A body produced incrementally
+    Stream(BoxStream<'static, DeltaResult<Bytes>>),
+}
+
+impl ServiceBody {
+    /// Convert the body into a stream of chunks
+    pub fn into_stream(self) -> BoxStream<'static, DeltaResult<Bytes>> {
+        match self {
+            Self::Full(bytes) => stream::once(async move { Ok(bytes) }).boxed(),
+            Self::Stream(stream) => stream,
+        }
+    }
+}
+
+impl std::fmt::Debug for ServiceBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Full(bytes) => f.debug_tuple("Full").field(bytes).finish(),
+            Self::Stream(_) => f.write_str("Stream"),
+        }
+    }
+}
+
+/// A response of a [`TableService`] handler
+#[derive(Debug)]
+pub struct ServiceResponse {
+    /// HTTP status code
+    pub status: u16,
+    /// Value of the content type header
+    pub content_type: &'static str,
+    /// Response body
+    pub body: ServiceBody,
+}
+
+impl ServiceResponse {
+    /// A JSON response with status 200
+    pub fn json(value: &impl Serialize) -> DeltaResult<Self> {
+        Ok(Self {
+            status: 200,
+            content_type: JSON_CONTENT_TYPE,
+            body: ServiceBody::Full(Bytes::from(serde_json::to_vec(value)?)),
+        })
+    }
+
+    /// A JSON error response with a status code matching `err`
+    pub fn from_error(err: DeltaTableError) -> Self {
+        let status = match &err {
+            DeltaTableError::NotATable(_)
+            | DeltaTableError::NotInitialized
+            | DeltaTableError::InvalidVersion(_) => 404,
+            DeltaTableError::VersionAlreadyExists(_)
+            | DeltaTableError::Transaction {
+                source:
+                    TransactionError::VersionAlreadyExists(_)
+                    | TransactionError::CommitConflict(_)
+                    | TransactionError::MaxCommitAttempts(_)
+                    | TransactionError::RetryBudgetExhausted { .. },
+            } => 409,
+            DeltaTableError::SchemaMismatch { .. }
+            | DeltaTableError::InvalidData { .. }
+            | DeltaTableError::Transaction { .. } => 400,
+            DeltaTableError::GenericError { source } if source.is::<ServiceError>() => 400,
+            _ => 500,
+        };
+        Self {
+            status,
+            content_type: JSON_CONTENT_TYPE,
+            body: ServiceBody::Full(Bytes::from(
+                serde_json::json!({ "error": err.to_string() }).to_string(),
+            )),
+        }
+    }
+}
+
+/// Request to scan a table
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanRequest {
+    /// SQL predicate rows must match
+    pub predicate: Option<String>,
+    /// Columns to return, all columns by default
+    pub columns: Option<Vec<String>>,
+    /// Version of the table to scan, the latest version by default
+    pub version: Option<i64>,
+    /// Maximum number of rows to return
+    pub limit: Option<usize>,
+}
+
+/// Request to commit data files staged in the table directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitRequest {
+    /// Add actions of the staged files, with paths relative to the table root
+    pub add: Vec<Add>,
+    /// Version of the table the files were written against, the latest version by default.
+    ///
+    /// Concurrent commits since this version are checked for conflicts.
+    pub read_version: Option<i64>,
+    /// Additional metadata to write to the commit info
+    #[serde(default)]
+    pub app_metadata: HashMap<String, Value>,
+}
+
+/// Response of a commit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitResponse {
+    /// The committed table version
+    pub version: i64,
+}
+
+/// Handlers serving a single delta table
+#[derive(Clone)]
+pub struct TableService {
+    log_store: LogStoreRef,
+    limiter: TableLimiter,
+}
+
+impl std::fmt::Debug for TableService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TableService({})", self.log_store.root_uri())
+    }
+}
+
+impl TableService {
+    /// Create a service for the table at `table_uri`
+    pub fn try_new(
+        table_uri: impl AsRef<str>,
+        storage_options: HashMap<String, String>,
+    ) -> DeltaResult<Self> {
+        let log_store = DeltaTableBuilder::from_uri(table_uri)
+            .with_storage_options(storage_options)
+            .build_storage()?;
+        Ok(Self::new(log_store))
+    }
+
+    /// Create a service for the table stored in `log_store`
+    pub fn new(log_store: LogStoreRef) -> Self {
+        Self {
+            log_store,
+            limiter: TableLimiter::default(),
+        }
+    }
+
+    /// Limit the number of concurrent scans and commits served, e.g. with the limiter of a
+    /// pooled table. Requests exceeding the limits wait for a permit
+    pub fn with_limiter(mut self, limiter: TableLimiter) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    async fn load(&self, version: Option<i64>) -> DeltaResult<DeltaTable> {
+        let mut table = DeltaTable::new(self.log_store.clone(), Default::default());
+        match version {
+            Some(version) => table.load_version(version).await?,
+            None => table.load().await?,
+        }
+        Ok(table)
+    }
+
+    /// The current version of the table, as `{"version": <version>}`
+    pub async fn get_version(&self) -> DeltaResult<ServiceResponse> {
+        let version = self.log_store.get_latest_version(0).await?;
+        ServiceResponse::json(&serde_json::json!({ "version": version }))
+    }
+
+    /// The schema of the table at `version`, or the latest version, in the Delta JSON format
+    pub async fn get_schema(&self, version: Option<i64>) -> DeltaResult<ServiceResponse> {
+        let table = self.load(version).await?;
+        ServiceResponse::json(table.snapshot()?.schema())
+    }
+
+    /// Scan the table into an Arrow IPC stream
+    #[cfg(feature = "datafusion")]
+    pub async fn scan(&self, request: ScanRequest) -> DeltaResult<ServiceResponse> {
+        use std::sync::Arc;
+
+        use datafusion::execution::context::SessionContext;
+
+        use crate::delta_datafusion::{DataFusionMixins, DeltaScanConfig, DeltaTableProvider};
+
+        // the permit is held until the response stream is consumed
+        let permit = self.limiter.acquire_scan().await?;
+        let table = self.load(request.version).await?;
+        let snapshot = table.snapshot()?.clone();
+        let ctx = SessionContext::new();
+        let predicate = request
+            .predicate
+            .as_ref()
+            .map(|predicate| {
+                snapshot
+                    .parse_predicate_expression(predicate, &ctx.state())
+                    .map_err(|err| ServiceError::InvalidRequest(err.to_string()))
+            })
+            .transpose()?;
+
+        let provider =
+            DeltaTableProvider::try_new(snapshot, table.log_store(), DeltaScanConfig::default())?;
+        let mut df = ctx.read_table(Arc::new(provider))?;
+        if let Some(predicate) = predicate {
+            df = df.filter(predicate)?;
+        }
+        if let Some(columns) = &request.columns {
+            let columns = columns.iter().map(String::as_str).collect::<Vec<_>>();
+            df = df
+                .select_columns(&columns)
+                .map_err(|err| ServiceError::InvalidRequest(err.to_string()))?;
+        }
+        if let Some(limit) = request.limit {
+            df = df.limit(0, Some(limit))?;
+        }
+
+        let schema = Arc::new(arrow_schema::Schema::from(df.schema()));
+        let batches = df.execute_stream().await?.map(move |batch| {
+            let _ = &permit;
+            batch.map_err(DeltaTableError::from)
+        });
+        Ok(ServiceResponse {
+            status: 200,
+            content_type: ARROW_STREAM_CONTENT_TYPE,
+            body: ServiceBody::Stream(ipc_stream(schema, batches)),
+        })
+    }
+
+    /// Commit data files staged in the table directory, as `{"version": <version>}`
+    pub async fn commit(&self, request: CommitRequest) -> DeltaResult<ServiceResponse> {
+        let _permit = self.limiter.acquire_commit().await?;
+        let table = self.load(request.read_version).await?;
+        let snapshot = table.snapshot()?;
+
+        let store = self.log_store.object_store();
+        for add in &request.add {
+            if add.path.contains("://") || add.path.starts_with('/') {
+                return Err(ServiceError::InvalidRequest(format!(
+                    "staged file {} is not relative to the table root",
+                    add.path
+                ))
+                .into());
+            }
+            let path = Path::parse(&add.path)?;
+            if store.head(&path).await.is_err() {
+                return Err(ServiceError::InvalidRequest(format!(
+                    "staged file {} does not exist",
+                    add.path
+                ))
+                .into());
+            }
+        }
+
+        let partition_by = Some(snapshot.metadata().partition_columns.clone())
+            .filter(|columns| !columns.is_empty());
+        let operation = DeltaOperation::Write {
+            mode: SaveMode::Append,
+            partition_by,
+            predicate: None,
+        };
+        let actions = request.add.into_iter().map(Action::Add).collect();
+        let version =
+            CommitBuilder::from(CommitProperties::default().with_metadata(request.app_metadata))
+                .with_actions(actions)
+                .build(Some(snapshot), self.log_store.clone(), operation)?
+                .await?
+                .version();
+        ServiceResponse::json(&CommitResponse { version })
+    }
+}
+
+/// Encode a stream of record batches as an Arrow IPC stream, one chunk per batch
+pub fn ipc_stream(
+    schema: ArrowSchemaRef,
+    batches: impl futures::Stream<Item = DeltaResult<RecordBatch>> + Send + 'static,
+) -> BoxStream<'static, DeltaResult<Bytes>> {
+    let writer = match StreamWriter::try_new(Vec::new(), &schema) {
+        Ok(writer) => writer,
+        Err(err) => return stream::once(async move { Err(err.into()) }).boxed(),
+    };
+    stream::unfold(
+        (Some(writer), batches.boxed()),
+        |(writer, mut batches)| async move {
+            let mut writer = writer?;
+            let chunk = match batches.next().await {
+                Some(Ok(batch)) => writer.write(&batch).map(|_| true),
+                Some(Err(err)) => return Some((Err(err), (None, batches))),
+                None => writer.finish().map(|_| false),
+            };
+            match chunk {
+                Ok(more) => {
+                    let bytes = Bytes::from(std::mem::take(writer.get_mut()));
+                    Some((Ok(bytes), (more.then_some(writer), batches)))
+                }
+                Err(err) => Some((Err(err.into()), (None, batches))),
+            }
+        },
+    )
+    .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_ipc::reader::StreamReader;
+    use futures::TryStreamExt;
+
+    use super::*;
+    use crate::writer::test_utils::{create_initialized_table, get_record_batch};
+
+    async fn body_bytes(response: ServiceResponse) -> Vec<u8> {
+        response
+            .body
+            .into_stream()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap()
+            .concat()
+    }
+
+    #[tokio::test]
+    async fn test_ipc_stream() {
+        let batch = get_record_batch(None, false);
+        let batches = stream::iter(vec![Ok(batch.clone()), Ok(batch.clone())]);
+        let bytes = ipc_stream(batch.schema(), batches)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap()
+            .concat();
+        let reader = StreamReader::try_new(bytes.as_slice(), None).unwrap();
+        let read = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(read, vec![batch.clone(), batch]);
+    }
+
+    #[tokio::test]
+    async fn test_version_and_schema() {
+        let table = create_initialized_table(&[]).await;
+        let service = TableService::new(table.log_store());
+
+        let response = service.get_version().await.unwrap();
+        assert_eq!(response.status, 200);
+        let body: Value = serde_json::from_slice(&body_bytes(response).await).unwrap();
+        assert_eq!(body, serde_json::json!({ "version": 0 }));
+
+        let response = service.get_schema(None).await.unwrap();
+        let body: Value = serde_json::from_slice(&body_bytes(response).await).unwrap();
+        assert_eq!(body["fields"].as_array().unwrap().len(), 3);
+
+        assert!(service.get_schema(Some(5)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_commit_staged_files() {
+        use crate::writer::{DeltaWriter, RecordBatchWriter};
+
+        let table = create_initialized_table(&[]).await;
+        let service = TableService::new(table.log_store());
+
+        let mut writer = RecordBatchWriter::for_table(&table).unwrap();
+        writer.write(get_record_batch(None, false)).await.unwrap();
+        let add = writer.flush().await.unwrap();
+
+        let response = service
+            .commit(CommitRequest {
+                add,
+                read_version: Some(0),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let body: CommitResponse = serde_json::from_slice(&body_bytes(response).await).unwrap();
+        assert_eq!(body.version, 1);
+
+        let missing = Add {
+            path: "missing.parquet".to_string(),
+            ..Default::default()
+        };
+        let err = service
+            .commit(CommitRequest {
+                add: vec![missing],
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(ServiceResponse::from_error(err).status, 400);
+    }
+
+    #[cfg(feature = "datafusion")]
+    #[tokio::test]
+    async fn test_scan() {
+        use crate::operations::DeltaOps;
+        use crate::table::limits::ConcurrencyLimits;
+
+        let table = create_initialized_table(&[]).await;
+        let table = DeltaOps(table)
+            .write(vec![get_record_batch(None, false)])
+            .await
+            .unwrap();
+        let limiter = TableLimiter::new(ConcurrencyLimits {
+            max_concurrent_scans: Some(1),
+            max_concurrent_commits: None,
+        });
+        let service = TableService::new(table.log_store()).with_limiter(limiter.clone());
+
+        let response = service
+            .scan(ScanRequest {
+                predicate: Some("value > 10".to_string()),
+                columns: Some(vec!["id".to_string(), "value".to_string()]),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.content_type, ARROW_STREAM_CONTENT_TYPE);
+        assert_eq!(limiter.active_scans(), 1);
+        let bytes = body_bytes(response).await;
+        assert!(limiter.is_idle());
+        let reader = StreamReader::try_new(bytes.as_slice(), None).unwrap();
+        assert_eq!(reader.schema().fields().len(), 2);
+        let rows: usize = reader.map(|batch| batch.unwrap().num_rows()).sum();
+        assert_eq!(rows, 1);
+
+        let err = service
+            .scan(ScanRequest {
+                predicate: Some("missing > 1".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(ServiceResponse::from_error(err).status, 400);
+    }
+}
@@ -0,0 +1,577 @@
+//! Adaptive concurrency of read requests against object stores
+//!
+//! The number of concurrent reads which saturates the bandwidth to an object store depends on
+//! the store, the network and the machine, and issuing more requests than a store accepts
+//! leads to throttling, e.g. `SlowDown` responses from S3. Rather than guessing a static
+//! concurrency per environment, the [`AdaptiveObjectStore`] bounds the reads in flight by a
+//! limit which a [`ConcurrencyController`] adjusts from the observed throughput and throttling:
+//!
+//! - while the limit is reached and the throughput keeps up, the limit is raised
+//! - when raising the limit made the throughput drop, the limit is lowered by one
+//! - when the store throttles a request, the limit is halved
+//!
+//! Scans can then use a generous number of partitions and leave the actual number of requests
+//! to the controller. Only reads are limited, all other requests are passed to the wrapped store
+//! unchanged.
+//!
+//! The controller is configured per table through its storage options:
+//!
+//! - `DELTA_ADAPTIVE_CONCURRENCY`: set to `true` to enable adaptive concurrency
+//! - `DELTA_ADAPTIVE_MIN_CONCURRENCY`: lowest limit, defaults to 2
+//! - `DELTA_ADAPTIVE_MAX_CONCURRENCY`: highest limit, defaults to 256
+//! - `DELTA_ADAPTIVE_INITIAL_CONCURRENCY`: limit before any throughput was observed, defaults
+//!   to 16
+//!
+//! # Example
+//! ```rust ignore
+//! let table = DeltaTableBuilder::from_uri("s3://bucket/table")
+//!     .with_storage_options(HashMap::from([
+//!         ("DELTA_ADAPTIVE_CONCURRENCY".to_string(), "true".to_string()),
+//!         ("DELTA_ADAPTIVE_MAX_CONCURRENCY".to_string(), "64".to_string()),
+//!     ]))
+//!     .load()
+//!     .await?;
+//! ````
+
+use std::future::Future;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use object_store::path::Path;
+use object_store::{
+    Error as ObjectStoreError, GetOptions, GetResult, ListResult, MultipartId, ObjectMeta,
+    ObjectStore, PutOptions, PutResult, Result as ObjectStoreResult,
+};
+use parking_lot::Mutex;
+use tokio::io::AsyncWrite;
+use tokio::sync::Notify;
+
+use super::{str_is_truthy, ObjectStoreRef, StorageOptions};
+use crate::{DeltaResult, DeltaTableError};
+
+/// Storage option enabling adaptive concurrency
+pub const ADAPTIVE_CONCURRENCY_KEY: &str = "DELTA_ADAPTIVE_CONCURRENCY";
+/// Storage option setting the lowest concurrency limit
+pub const ADAPTIVE_MIN_CONCURRENCY_KEY: &str = "DELTA_ADAPTIVE_MIN_CONCURRENCY";
+/// Storage option setting the highest concurrency limit
+pub const ADAPTIVE_MAX_CONCURRENCY_KEY: &str = "DELTA_ADAPTIVE_MAX_CONCURRENCY";
+/// Storage option setting the concurrency limit before any throughput was observed
+pub const ADAPTIVE_INITIAL_CONCURRENCY_KEY: &str = "DELTA_ADAPTIVE_INITIAL_CONCURRENCY";
+
+/// Throughput drop, relative to the previous window, after which the limit is lowered
+const THROUGHPUT_DROP: f64 = 0.9;
+
+/// Markers of throttling responses in object store errors
+const THROTTLING_MARKERS: [&str; 6] = [
+    "SlowDown",
+    "Slow Down",
+    "TooManyRequests",
+    "Too Many Requests",
+    "ServerBusy",
+    "503",
+];
+
+/// Configuration of a [`ConcurrencyController`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdaptiveConfig {
+    /// Lowest limit
+    pub min_concurrency: usize,
+    /// Highest limit
+    pub max_concurrency: usize,
+    /// Limit before any throughput was observed
+    pub initial_concurrency: usize,
+    /// Interval over which the throughput is measured
+    pub window: Duration,
+}
+
+impl Default for AdaptiveConfig {
+    fn default() -> Self {
+        Self {
+            min_concurrency: 2,
+            max_concurrency: 256,
+            initial_concurrency: 16,
+            window: Duration::from_secs(1),
+        }
+    }
+}
+
+impl AdaptiveConfig {
+    /// The adaptive concurrency configuration of the storage options, `None` if adaptive
+    /// concurrency is not enabled
+    pub fn from_options(options: &StorageOptions) -> DeltaResult<Option<Self>> {
+        let options = &options.0;
+        if !options
+            .get(ADAPTIVE_CONCURRENCY_KEY)
+            .is_some_and(|value| str_is_truthy(value))
+        {
+            return Ok(None);
+        }
+        let parse = |key: &str, default: usize| -> DeltaResult<usize> {
+            match options.get(key) {
+                Some(value) => value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .ok_or_else(|| {
+                        DeltaTableError::Generic(format!(
+                            "Invalid value '{value}' for storage option {key}"
+                        ))
+                    }),
+                None => Ok(default),
+            }
+        };
+        let default = Self::default();
+        let config = Self {
+            min_concurrency: parse(ADAPTIVE_MIN_CONCURRENCY_KEY, default.min_concurrency)?,
+            max_concurrency: parse(ADAPTIVE_MAX_CONCURRENCY_KEY, default.max_concurrency)?,
+            initial_concurrency: parse(
+                ADAPTIVE_INITIAL_CONCURRENCY_KEY,
+                default.initial_concurrency,
+            )?,
+            window: default.window,
+        };
+        if config.min_concurrency > config.max_concurrency {
+            return Err(DeltaTableError::Generic(format!(
+                "{ADAPTIVE_MIN_CONCURRENCY_KEY} must not exceed {ADAPTIVE_MAX_CONCURRENCY_KEY}"
+            )));
+        }
+        Ok(Some(config))
+    }
+
+    fn bounds(&self) -> (usize, usize) {
+        let min = self.min_concurrency.max(1);
+        (min, self.max_concurrency.max(min))
+    }
+}
+
+/// Wrap `store` in an [`AdaptiveObjectStore`] if the storage options enable adaptive
+/// concurrency
+pub fn adaptive_store(
+    store: ObjectStoreRef,
+    options: &StorageOptions,
+) -> DeltaResult<ObjectStoreRef> {
+    Ok(match AdaptiveConfig::from_options(options)? {
+        Some(config) => Arc::new(AdaptiveObjectStore::new(store, config)),
+        None => store,
+    })
+}
+
+/// Whether `err` reports that the store throttled the request
+pub fn is_throttled(err: &ObjectStoreError) -> bool {
+    let message = err.to_string();
+    THROTTLING_MARKERS
+        .iter()
+        .any(|marker| message.contains(marker))
+}
+
+#[derive(Debug)]
+struct ControllerState {
+    limit: usize,
+    in_flight: usize,
+    window_start: Instant,
+    window_bytes: u64,
+    /// Whether the limit was reached during the window
+    window_saturated: bool,
+    last_throughput: Option<f64>,
+    last_decrease: Option<Instant>,
+}
+
+impl ControllerState {
+    fn reset_window(&mut self, now: Instant) {
+        self.window_start = now;
+        self.window_bytes = 0;
+        self.window_saturated = self.in_flight >= self.limit;
+    }
+}
+
+/// Limits the number of requests in flight, adjusting the limit to the observed throughput
+/// and throttling. See this module's documentation for more information
+#[derive(Debug)]
+pub struct ConcurrencyController {
+    config: AdaptiveConfig,
+    state: Mutex<ControllerState>,
+    notify: Notify,
+    throttled: AtomicU64,
+}
+
+/// Permission to issue a request, released when dropped
+#[derive(Debug)]
+pub struct RequestPermit<'a> {
+    controller: &'a ConcurrencyController,
+}
+
+impl Drop for RequestPermit<'_> {
+    fn drop(&mut self) {
+        self.controller.state.lock().in_flight -= 1;
+        self.controller.notify.notify_one();
+    }
+}
+
+impl ConcurrencyController {
+    /// Create a controller configured by `config`
+    pub fn new(config: AdaptiveConfig) -> Self {
+        let (min, max) = config.bounds();
+        let limit = config.initial_concurrency.clamp(min, max);
+        Self {
+            state: Mutex::new(ControllerState {
+                limit,
+                in_flight: 0,
+                window_start: Instant::now(),
+                window_bytes: 0,
+                window_saturated: false,
+                last_throughput: None,
+                last_decrease: None,
+            }),
+            config,
+            notify: Notify::new(),
+            throttled: AtomicU64::new(0),
+        }
+    }
+
+    /// The current limit of requests in flight
+    pub fn limit(&self) -> usize {
+        self.state.lock().limit
+    }
+
+    /// Number of requests currently in flight
+    pub fn in_flight(&self) -> usize {
+        self.state.lock().in_flight
+    }
+
+    /// Number of requests which were throttled by the store
+    pub fn throttled_requests(&self) -> u64 {
+        self.throttled.load(Ordering::Relaxed)
+    }
+
+    /// Wait until a request may be issued without exceeding the limit
+    pub async fn acquire(&self) -> RequestPermit<'_> {
+        loop {
+            let notified = self.notify.notified();
+            {
+                let mut state = self.state.lock();
+                if state.in_flight < state.limit {
+                    state.in_flight += 1;
+                    if state.in_flight == state.limit {
+                        state.window_saturated = true;
+                    }
+                    return RequestPermit { controller: self };
+                }
+                state.window_saturated = true;
+            }
+            notified.await;
+        }
+    }
+
+    /// Record a request which transferred `bytes`
+    pub fn record_success(&self, bytes: u64) {
+        self.record(bytes, false, Instant::now())
+    }
+
+    /// Record a request which was throttled by the store
+    pub fn record_throttled(&self) {
+        self.record(0, true, Instant::now())
+    }
+
+    fn record(&self, bytes: u64, throttled: bool, now: Instant) {
+        let mut state = self.state.lock();
+        let previous = state.limit;
+        let (min, max) = self.config.bounds();
+        if throttled {
+            self.throttled.fetch_add(1, Ordering::Relaxed);
+            // requests in flight were issued at the old limit, so only react once per window
+            let recently_decreased = state
+                .last_decrease
+                .is_some_and(|at| now.duration_since(at) < self.config.window);
+            if !recently_decreased {
+                state.limit = (state.limit / 2).max(min);
+                state.last_decrease = Some(now);
+                state.last_throughput = None;
+                state.reset_window(now);
+            }
+        } else {
+            state.window_bytes += bytes;
+            let elapsed = now.duration_since(state.window_start);
+            if elapsed >= self.config.window {
+                let throughput = state.window_bytes as f64 / elapsed.as_secs_f64();
+                if state.window_saturated {
+                    match state.last_throughput {
+                        Some(last) if throughput < last * THROUGHPUT_DROP => {
+                            state.limit = state.limit.saturating_sub(1).max(min);
+                        }
+                        _ => {
+                            state.limit = (state.limit + (state.limit / 8).max(1)).min(max);
+                        }
+                    }
+                }
+                state.last_throughput = Some(throughput);
+                state.reset_window(now);
+            }
+        }
+        for _ in previous..state.limit {
+            self.notify.notify_one();
+        }
+    }
+}
+
+/// [`ObjectStore`] adapting the concurrency of reads to the wrapped store.
+/// See this module's documentation for more information
+#[derive(Debug)]
+pub struct AdaptiveObjectStore {
+    inner: ObjectStoreRef,
+    controller: Arc<ConcurrencyController>,
+}
+
+impl AdaptiveObjectStore {
+    /// Adapt the concurrency of reads to `inner` according to `config`
+    pub fn new(inner: ObjectStoreRef, config: AdaptiveConfig) -> Self {
+        Self::with_controller(inner, Arc::new(ConcurrencyController::new(config)))
+    }
+
+    /// Limit reads to `inner` by `controller`, which may be shared with other stores
+    pub fn with_controller(inner: ObjectStoreRef, controller: Arc<ConcurrencyController>) -> Self {
+        Self { inner, controller }
+    }
+
+    /// The controller limiting reads
+    pub fn controller(&self) -> &Arc<ConcurrencyController> {
+        &self.controller
+    }
+
+    async fn limited<T>(
+        &self,
+        request: impl Future<Output = ObjectStoreResult<T>> + Send,
+        bytes: fn(&T) -> u64,
+    ) -> ObjectStoreResult<T> {
+        let _permit = self.controller.acquire().await;
+        let result = request.await;
+        match &result {
+            Ok(value) => self.controller.record_success(bytes(value)),
+            Err(err) if is_throttled(err) => self.controller.record_throttled(),
+            Err(_) => {}
+        }
+        result
+    }
+}
+
+impl std::fmt::Display for AdaptiveObjectStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AdaptiveObjectStore({})", self.inner)
+    }
+}
+
+#[async_trait::async_trait]
+impl ObjectStore for AdaptiveObjectStore {
+    async fn put(&self, location: &Path, bytes: Bytes) -> ObjectStoreResult<PutResult> {
+        self.inner.put(location, bytes).await
+    }
+
+    async fn put_opts(
+        &self,
+        location: &Path,
+        bytes: Bytes,
+        options: PutOptions,
+    ) -> ObjectStoreResult<PutResult> {
+        self.inner.put_opts(location, bytes, options).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> ObjectStoreResult<GetResult> {
+        self.limited(self.inner.get_opts(location, options), |result| {
+            (result.range.end - result.range.start) as u64
+        })
+        .await
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> ObjectStoreResult<Bytes> {
+        self.limited(self.inner.get_range(location, range), |bytes| {
+            bytes.len() as u64
+        })
+        .await
+    }
+
+    async fn get_ranges(
+        &self,
+        location: &Path,
+        ranges: &[Range<usize>],
+    ) -> ObjectStoreResult<Vec<Bytes>> {
+        self.limited(self.inner.get_ranges(location, ranges), |ranges| {
+            ranges.iter().map(|bytes| bytes.len() as u64).sum()
+        })
+        .await
+    }
+
+    async fn head(&self, location: &Path) -> ObjectStoreResult<ObjectMeta> {
+        self.limited(self.inner.head(location), |_| 0).await
+    }
+
+    async fn delete(&self, location: &Path) -> ObjectStoreResult<()> {
+        self.inner.delete(location).await
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, ObjectStoreResult<ObjectMeta>> {
+        self.inner.list(prefix)
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'_, ObjectStoreResult<ObjectMeta>> {
+        self.inner.list_with_offset(prefix, offset)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> ObjectStoreResult<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.inner.copy_if_not_exists(from, to).await
+    }
+
+    async fn rename_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.inner.rename_if_not_exists(from, to).await
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> ObjectStoreResult<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        self.inner.put_multipart(location).await
+    }
+
+    async fn abort_multipart(
+        &self,
+        location: &Path,
+        multipart_id: &MultipartId,
+    ) -> ObjectStoreResult<()> {
+        self.inner.abort_multipart(location, multipart_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    fn controller(initial_concurrency: usize) -> ConcurrencyController {
+        ConcurrencyController::new(AdaptiveConfig {
+            min_concurrency: 1,
+            max_concurrency: 8,
+            initial_concurrency,
+            window: Duration::from_millis(100),
+        })
+    }
+
+    #[test]
+    fn test_throttling_halves_limit() {
+        let controller = controller(8);
+        let start = Instant::now();
+        controller.record(0, true, start);
+        assert_eq!(controller.limit(), 4);
+        // requests issued at the old limit do not lower it again
+        controller.record(0, true, start + Duration::from_millis(10));
+        assert_eq!(controller.limit(), 4);
+        controller.record(0, true, start + Duration::from_millis(200));
+        assert_eq!(controller.limit(), 2);
+        controller.record(0, true, start + Duration::from_millis(400));
+        controller.record(0, true, start + Duration::from_millis(600));
+        assert_eq!(controller.limit(), 1);
+        assert_eq!(controller.throttled_requests(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_limit_follows_throughput() {
+        let controller = controller(2);
+        let start = Instant::now();
+        let window = Duration::from_millis(100);
+
+        // not saturated, the limit stays
+        controller.record(1000, false, start + window);
+        assert_eq!(controller.limit(), 2);
+
+        let first = controller.acquire().await;
+        let second = controller.acquire().await;
+        assert_eq!(controller.in_flight(), 2);
+        controller.record(1000, false, start + window * 2);
+        assert_eq!(controller.limit(), 3);
+
+        // the throughput dropped after raising the limit
+        let third = controller.acquire().await;
+        controller.record(100, false, start + window * 3);
+        assert_eq!(controller.limit(), 2);
+
+        drop((first, second, third));
+        assert_eq!(controller.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_waits_for_limit() {
+        let controller = controller(1);
+        let permit = controller.acquire().await;
+        let blocked = tokio::time::timeout(Duration::from_millis(20), controller.acquire()).await;
+        assert!(blocked.is_err());
+        drop(permit);
+        let acquired = tokio::time::timeout(Duration::from_secs(5), controller.acquire()).await;
+        assert!(acquired.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_store() {
+        let inner = Arc::new(InMemory::new());
+        let location = Path::from("data.parquet");
+        inner
+            .put(&location, Bytes::from_static(b"data"))
+            .await
+            .unwrap();
+        let store = AdaptiveObjectStore::new(inner, AdaptiveConfig::default());
+        let bytes = store.get_range(&location, 1..3).await.unwrap();
+        assert_eq!(bytes.as_ref(), b"at");
+        assert_eq!(store.controller().in_flight(), 0);
+        assert_eq!(store.controller().state.lock().window_bytes, 2);
+    }
+
+    #[test]
+    fn test_is_throttled() {
+        let throttled = ObjectStoreError::Generic {
+            store: "S3",
+            source: "Server returned 503 Service Unavailable: SlowDown".into(),
+        };
+        assert!(is_throttled(&throttled));
+        let not_found = ObjectStoreError::NotFound {
+            path: "data.parquet".to_string(),
+            source: "missing".into(),
+        };
+        assert!(!is_throttled(&not_found));
+    }
+
+    #[test]
+    fn test_adaptive_config_from_options() {
+        let options = StorageOptions(HashMap::new());
+        assert_eq!(AdaptiveConfig::from_options(&options).unwrap(), None);
+
+        let options = StorageOptions(HashMap::from([
+            (ADAPTIVE_CONCURRENCY_KEY.to_string(), "true".to_string()),
+            (ADAPTIVE_MAX_CONCURRENCY_KEY.to_string(), "64".to_string()),
+        ]));
+        let config = AdaptiveConfig::from_options(&options).unwrap().unwrap();
+        assert_eq!(config.max_concurrency, 64);
+        assert_eq!(config.min_concurrency, 2);
+
+        let options = StorageOptions(HashMap::from([
+            (ADAPTIVE_CONCURRENCY_KEY.to_string(), "true".to_string()),
+            (ADAPTIVE_MIN_CONCURRENCY_KEY.to_string(), "8".to_string()),
+            (ADAPTIVE_MAX_CONCURRENCY_KEY.to_string(), "4".to_string()),
+        ]));
+        assert!(AdaptiveConfig::from_options(&options).is_err());
+    }
+}
@@ -0,0 +1,301 @@
+//! Deadlines of operations, propagated to storage requests
+//!
+//! Services with strict latency targets give every call into delta-rs a deadline. A deadline is
+//! set for the duration of a future with [`with_deadline`]. Every request issued through a
+//! [`DeadlineObjectStore`] within that future fails with [`DeadlineExceeded`] once the deadline
+//! has passed, rather than waiting for a slow or hanging backend. Requests issued outside of a
+//! deadline are passed to the wrapped store unchanged.
+//!
+//! The deadline is kept in task local storage, so it follows the future across await points but
+//! not into spawned tasks. Futures spawned on behalf of an operation should be wrapped with
+//! [`propagate_deadline`].
+//!
+//! The stores of all log stores are wrapped in a [`DeadlineObjectStore`]. The
+//! `with_timeout` option of operations sets the deadline.
+//!
+//! # Example
+//! ```rust ignore
+//! let deadline = Instant::now() + Duration::from_secs(5);
+//! let table = with_deadline(deadline, open_table("s3://bucket/table")).await?;
+//! ````
+
+use std::future::Future;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Instant;
+
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use object_store::path::Path;
+use object_store::{
+    Error as ObjectStoreError, GetOptions, GetResult, ListResult, MultipartId, ObjectMeta,
+    ObjectStore, PutOptions, PutResult, Result as ObjectStoreResult,
+};
+use tokio::io::AsyncWrite;
+
+use super::ObjectStoreRef;
+
+tokio::task_local! {
+    static DEADLINE: Option<Instant>;
+}
+
+/// Error of storage requests issued after the deadline of their operation
+#[derive(thiserror::Error, Debug)]
+#[error("Deadline of the operation exceeded")]
+pub struct DeadlineExceeded;
+
+/// The deadline of the current task, if any
+pub fn current_deadline() -> Option<Instant> {
+    DEADLINE.try_with(|deadline| *deadline).ok().flatten()
+}
+
+/// Whether the deadline of the current task, if any, has passed
+pub fn deadline_passed() -> bool {
+    current_deadline().is_some_and(|deadline| Instant::now() >= deadline)
+}
+
+/// Run `future` with a deadline. An enclosing deadline which is earlier takes precedence
+pub async fn with_deadline<F: Future>(deadline: Instant, future: F) -> F::Output {
+    let deadline = match current_deadline() {
+        Some(current) => current.min(deadline),
+        None => deadline,
+    };
+    DEADLINE.scope(Some(deadline), future).await
+}
+
+/// Run `future` without the deadline of the current task, for work which must complete even
+/// when the operation has run out of time, such as cleaning up after it
+pub async fn without_deadline<F: Future>(future: F) -> F::Output {
+    DEADLINE.scope(None, future).await
+}
+
+/// Carry the deadline of the current task over to `future`, which is about to be spawned
+pub fn propagate_deadline<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    let deadline = current_deadline();
+    DEADLINE.scope(deadline, future)
+}
+
+fn deadline_exceeded() -> ObjectStoreError {
+    ObjectStoreError::Generic {
+        store: "DeadlineObjectStore",
+        source: Box::new(DeadlineExceeded),
+    }
+}
+
+/// Whether `err` was caused by the deadline of the operation
+pub fn is_deadline_exceeded(err: &ObjectStoreError) -> bool {
+    matches!(err, ObjectStoreError::Generic { source, .. } if source.is::<DeadlineExceeded>())
+}
+
+/// Wrap `store` so its requests observe the deadline of the calling task
+pub fn deadline_store(store: ObjectStoreRef) -> ObjectStoreRef {
+    Arc::new(DeadlineObjectStore::new(store))
+}
+
+/// [`ObjectStore`] failing requests once the deadline of the calling task has passed.
+/// See this module's documentation for more information
+#[derive(Debug)]
+pub struct DeadlineObjectStore {
+    inner: ObjectStoreRef,
+}
+
+impl DeadlineObjectStore {
+    /// Make requests to `inner` observe deadlines
+    pub fn new(inner: ObjectStoreRef) -> Self {
+        Self { inner }
+    }
+
+    async fn bounded<T>(
+        &self,
+        request: impl Future<Output = ObjectStoreResult<T>> + Send,
+    ) -> ObjectStoreResult<T> {
+        match current_deadline() {
+            Some(deadline) if Instant::now() >= deadline => Err(deadline_exceeded()),
+            Some(deadline) => tokio::time::timeout_at(deadline.into(), request)
+                .await
+                .map_err(|_| deadline_exceeded())?,
+            None => request.await,
+        }
+    }
+
+    fn bounded_stream<'a, T: Send + 'a>(
+        &self,
+        stream: BoxStream<'a, ObjectStoreResult<T>>,
+    ) -> BoxStream<'a, ObjectStoreResult<T>> {
+        match current_deadline() {
+            Some(deadline) => {
+                let expired = tokio::time::sleep_until(deadline.into());
+                let exceeded = futures::stream::once(async move {
+                    (Instant::now() >= deadline).then(deadline_exceeded)
+                })
+                .filter_map(|err| futures::future::ready(err.map(Err)));
+                stream.take_until(expired).chain(exceeded).boxed()
+            }
+            None => stream,
+        }
+    }
+}
+
+impl std::fmt::Display for DeadlineObjectStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DeadlineObjectStore({})", self.inner)
+    }
+}
+
+#[async_trait::async_trait]
+impl ObjectStore for DeadlineObjectStore {
+    async fn put(&self, location: &Path, bytes: Bytes) -> ObjectStoreResult<PutResult> {
+        self.bounded(self.inner.put(location, bytes)).await
+    }
+
+    async fn put_opts(
+        &self,
+        location: &Path,
+        bytes: Bytes,
+        options: PutOptions,
+    ) -> ObjectStoreResult<PutResult> {
+        self.bounded(self.inner.put_opts(location, bytes, options))
+            .await
+    }
+
+    async fn get(&self, location: &Path) -> ObjectStoreResult<GetResult> {
+        self.bounded(self.inner.get(location)).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> ObjectStoreResult<GetResult> {
+        self.bounded(self.inner.get_opts(location, options)).await
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> ObjectStoreResult<Bytes> {
+        self.bounded(self.inner.get_range(location, range)).await
+    }
+
+    async fn get_ranges(
+        &self,
+        location: &Path,
+        ranges: &[Range<usize>],
+    ) -> ObjectStoreResult<Vec<Bytes>> {
+        self.bounded(self.inner.get_ranges(location, ranges)).await
+    }
+
+    async fn head(&self, location: &Path) -> ObjectStoreResult<ObjectMeta> {
+        self.bounded(self.inner.head(location)).await
+    }
+
+    async fn delete(&self, location: &Path) -> ObjectStoreResult<()> {
+        self.bounded(self.inner.delete(location)).await
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, ObjectStoreResult<ObjectMeta>> {
+        self.bounded_stream(self.inner.list(prefix))
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'_, ObjectStoreResult<ObjectMeta>> {
+        self.bounded_stream(self.inner.list_with_offset(prefix, offset))
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> ObjectStoreResult<ListResult> {
+        self.bounded(self.inner.list_with_delimiter(prefix)).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.bounded(self.inner.copy(from, to)).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.bounded(self.inner.copy_if_not_exists(from, to)).await
+    }
+
+    async fn rename_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.bounded(self.inner.rename_if_not_exists(from, to))
+            .await
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> ObjectStoreResult<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        self.bounded(self.inner.put_multipart(location)).await
+    }
+
+    async fn abort_multipart(
+        &self,
+        location: &Path,
+        multipart_id: &MultipartId,
+    ) -> ObjectStoreResult<()> {
+        self.inner.abort_multipart(location, multipart_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::TryStreamExt;
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_deadline_scope() {
+        assert!(current_deadline().is_none());
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let earlier = deadline - Duration::from_secs(30);
+        with_deadline(deadline, async {
+            assert_eq!(current_deadline(), Some(deadline));
+            with_deadline(deadline + Duration::from_secs(60), async {
+                assert_eq!(current_deadline(), Some(deadline));
+            })
+            .await;
+            with_deadline(earlier, async {
+                assert_eq!(current_deadline(), Some(earlier));
+            })
+            .await;
+            without_deadline(async { assert!(current_deadline().is_none()) }).await;
+            tokio::spawn(propagate_deadline(async move {
+                assert_eq!(current_deadline(), Some(deadline));
+            }))
+            .await
+            .unwrap();
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_deadline_store() {
+        let store = deadline_store(Arc::new(InMemory::new()));
+        let location = Path::from("data.parquet");
+        store
+            .put(&location, Bytes::from_static(b"data"))
+            .await
+            .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(60);
+        with_deadline(deadline, async {
+            assert!(store.head(&location).await.is_ok());
+            assert_eq!(
+                store
+                    .list(None)
+                    .try_collect::<Vec<_>>()
+                    .await
+                    .unwrap()
+                    .len(),
+                1
+            );
+        })
+        .await;
+
+        with_deadline(Instant::now(), async {
+            let err = store.head(&location).await.unwrap_err();
+            assert!(is_deadline_exceeded(&err));
+            let err = store.list(None).try_collect::<Vec<_>>().await.unwrap_err();
+            assert!(is_deadline_exceeded(&err));
+        })
+        .await;
+    }
+}
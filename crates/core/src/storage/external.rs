@@ -0,0 +1,406 @@
+//! Access to data files outside of the table root
+//!
+//! The paths of the data files of a table are usually relative to the table root, but shallow
+//! clones and tables shared across accounts reference files by absolute URIs, which may point at
+//! other buckets or storage accounts than the table itself. Object store paths are always
+//! relative to a store, so an absolute URI is encoded as a path under the reserved
+//! [`EXTERNAL_FILES_PREFIX`], e.g. `s3://other-bucket/data/part-0.parquet` becomes
+//! `_delta_external/s3/other-bucket/data/part-0.parquet`. The [`ExternalFilesStore`] routes
+//! requests for these paths to a store for the referenced bucket, created on first use through
+//! the registered [`factories`](super::factories), and all other requests to the table store.
+//!
+//! Stores for other buckets are configured with the storage options of the table, unless a
+//! [`CredentialResolver`] provides options for the bucket, e.g. credentials of another account.
+//!
+//! # Example
+//! ```rust ignore
+//! let table = DeltaTableBuilder::from_uri("s3://bucket/clone")
+//!     .with_credential_resolver(Arc::new(resolver))
+//!     .load()
+//!     .await?;
+//! ````
+
+use std::fmt::Debug;
+use std::ops::Range;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use dashmap::DashMap;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use object_store::path::{Path, PathPart};
+use object_store::{
+    Error as ObjectStoreError, GetOptions, GetResult, ListResult, MultipartId, ObjectMeta,
+    ObjectStore, PutMode, PutOptions, PutResult, Result as ObjectStoreResult,
+};
+use percent_encoding::percent_decode_str;
+use tokio::io::AsyncWrite;
+use url::{Position, Url};
+
+use super::{factories, ObjectStoreRef, StorageOptions};
+
+/// Prefix of the object store paths encoding absolute file URIs
+pub const EXTERNAL_FILES_PREFIX: &str = "_delta_external";
+
+/// Path segment standing in for an empty URI authority, e.g. of `file:///` URIs
+const EMPTY_AUTHORITY: &str = "-";
+
+/// Provides the storage options for accessing buckets outside of the table root
+pub trait CredentialResolver: Debug + Send + Sync {
+    /// Storage options for the bucket at `url`, e.g. `s3://other-bucket/`, or `None` to use the
+    /// storage options of the table
+    fn storage_options(&self, url: &Url) -> Option<StorageOptions>;
+}
+
+/// Encode the absolute file URI `uri` as an object store path, returns `None` for relative paths
+pub fn external_file_path(uri: &str) -> Option<Path> {
+    if !uri.contains("://") {
+        return None;
+    }
+    let url = Url::parse(uri).ok()?;
+    let authority = &url[Position::BeforeUsername..Position::AfterPort];
+    let authority = if authority.is_empty() {
+        EMPTY_AUTHORITY
+    } else {
+        authority
+    };
+    let path = Path::from_url_path(url.path()).ok()?;
+    let parts = [EXTERNAL_FILES_PREFIX, url.scheme(), authority]
+        .into_iter()
+        .map(PathPart::from)
+        .chain(path.parts());
+    Some(Path::from_iter(parts))
+}
+
+/// Split an encoded path into the url of its bucket and the path within the bucket
+fn split_external_path(location: &Path) -> Option<(String, Path)> {
+    let mut parts = location.parts();
+    if parts.next()?.as_ref() != EXTERNAL_FILES_PREFIX {
+        return None;
+    }
+    let scheme = parts.next()?;
+    let authority = parts.next()?;
+    let authority = percent_decode_str(authority.as_ref()).decode_utf8_lossy();
+    let authority = if authority == EMPTY_AUTHORITY {
+        ""
+    } else {
+        authority.as_ref()
+    };
+    let bucket = format!("{}://{authority}/", scheme.as_ref());
+    Some((bucket, Path::from_iter(parts)))
+}
+
+/// Store routing requests for encoded absolute file URIs to the store of their bucket
+#[derive(Debug)]
+pub struct ExternalFilesStore {
+    inner: ObjectStoreRef,
+    options: StorageOptions,
+    resolver: Option<Arc<dyn CredentialResolver>>,
+    stores: DashMap<String, ObjectStoreRef>,
+}
+
+impl ExternalFilesStore {
+    /// Wrap the table store `inner`, accessing other buckets with the table's storage `options`
+    pub fn new(inner: ObjectStoreRef, options: StorageOptions) -> Self {
+        Self {
+            inner,
+            options,
+            resolver: None,
+            stores: DashMap::new(),
+        }
+    }
+
+    /// Resolve the storage options of other buckets with `resolver`
+    pub fn with_credential_resolver(mut self, resolver: Arc<dyn CredentialResolver>) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+
+    /// The store of the bucket at `bucket`, created on first use
+    fn bucket_store(&self, bucket: &str) -> ObjectStoreResult<ObjectStoreRef> {
+        if let Some(store) = self.stores.get(bucket) {
+            return Ok(store.clone());
+        }
+        let url = Url::parse(bucket).map_err(|err| ObjectStoreError::Generic {
+            store: "ExternalFilesStore",
+            source: Box::new(err),
+        })?;
+        let scheme = Url::parse(&format!("{}://", url.scheme())).map_err(|err| {
+            ObjectStoreError::Generic {
+                store: "ExternalFilesStore",
+                source: Box::new(err),
+            }
+        })?;
+        let factory = factories()
+            .get(&scheme)
+            .map(|entry| entry.value().clone())
+            .ok_or_else(|| ObjectStoreError::NotSupported {
+                source: format!("No object store registered for {bucket}").into(),
+            })?;
+        let options = self
+            .resolver
+            .as_ref()
+            .and_then(|resolver| resolver.storage_options(&url))
+            .unwrap_or_else(|| self.options.clone());
+        let (store, _prefix) =
+            factory
+                .parse_url_opts(&url, &options)
+                .map_err(|err| ObjectStoreError::Generic {
+                    store: "ExternalFilesStore",
+                    source: Box::new(err),
+                })?;
+        Ok(self
+            .stores
+            .entry(bucket.to_string())
+            .or_insert(store)
+            .clone())
+    }
+
+    /// The store to send requests for `location` to, and the path within that store
+    fn route(&self, location: &Path) -> ObjectStoreResult<(ObjectStoreRef, Path)> {
+        match split_external_path(location) {
+            Some((bucket, path)) => Ok((self.bucket_store(&bucket)?, path)),
+            None => Ok((self.inner.clone(), location.clone())),
+        }
+    }
+}
+
+/// Replace the path of `meta` within its bucket by the encoded path under `location`'s bucket
+fn rebase(mut meta: ObjectMeta, location: &Path) -> ObjectMeta {
+    if let Some((_, path)) = split_external_path(location) {
+        let base = location
+            .parts()
+            .take(location.parts().count() - path.parts().count());
+        meta.location = Path::from_iter(base.chain(meta.location.parts()));
+    }
+    meta
+}
+
+impl std::fmt::Display for ExternalFilesStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ExternalFilesStore({})", self.inner)
+    }
+}
+
+#[async_trait::async_trait]
+impl ObjectStore for ExternalFilesStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        bytes: Bytes,
+        options: PutOptions,
+    ) -> ObjectStoreResult<PutResult> {
+        let (store, path) = self.route(location)?;
+        store.put_opts(&path, bytes, options).await
+    }
+
+    async fn get(&self, location: &Path) -> ObjectStoreResult<GetResult> {
+        let (store, path) = self.route(location)?;
+        let mut result = store.get(&path).await?;
+        result.meta = rebase(result.meta, location);
+        Ok(result)
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> ObjectStoreResult<GetResult> {
+        let (store, path) = self.route(location)?;
+        let mut result = store.get_opts(&path, options).await?;
+        result.meta = rebase(result.meta, location);
+        Ok(result)
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> ObjectStoreResult<Bytes> {
+        let (store, path) = self.route(location)?;
+        store.get_range(&path, range).await
+    }
+
+    async fn get_ranges(
+        &self,
+        location: &Path,
+        ranges: &[Range<usize>],
+    ) -> ObjectStoreResult<Vec<Bytes>> {
+        let (store, path) = self.route(location)?;
+        store.get_ranges(&path, ranges).await
+    }
+
+    async fn head(&self, location: &Path) -> ObjectStoreResult<ObjectMeta> {
+        let (store, path) = self.route(location)?;
+        Ok(rebase(store.head(&path).await?, location))
+    }
+
+    async fn delete(&self, location: &Path) -> ObjectStoreResult<()> {
+        let (store, path) = self.route(location)?;
+        store.delete(&path).await
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, ObjectStoreResult<ObjectMeta>> {
+        let Some(prefix) = prefix.filter(|prefix| split_external_path(prefix).is_some()) else {
+            return self.inner.list(prefix);
+        };
+        let prefix = prefix.clone();
+        futures::stream::once(async move {
+            let (store, path) = self.route(&prefix)?;
+            let metas = store.list(Some(&path)).try_collect::<Vec<_>>().await?;
+            Ok::<_, ObjectStoreError>(futures::stream::iter(
+                metas.into_iter().map(move |meta| Ok(rebase(meta, &prefix))),
+            ))
+        })
+        .try_flatten()
+        .boxed()
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> ObjectStoreResult<ListResult> {
+        let Some(prefix) = prefix.filter(|prefix| split_external_path(prefix).is_some()) else {
+            return self.inner.list_with_delimiter(prefix).await;
+        };
+        let (store, path) = self.route(prefix)?;
+        let result = store.list_with_delimiter(Some(&path)).await?;
+        let rebase_path = |location: Path| {
+            let base = prefix
+                .parts()
+                .take(prefix.parts().count() - path.parts().count());
+            Path::from_iter(base.chain(location.parts()))
+        };
+        Ok(ListResult {
+            common_prefixes: result
+                .common_prefixes
+                .into_iter()
+                .map(rebase_path)
+                .collect(),
+            objects: result
+                .objects
+                .into_iter()
+                .map(|meta| rebase(meta, prefix))
+                .collect(),
+        })
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        let (from_store, from_path) = self.route(from)?;
+        let (to_store, to_path) = self.route(to)?;
+        if Arc::ptr_eq(&from_store, &to_store) {
+            return from_store.copy(&from_path, &to_path).await;
+        }
+        let bytes = from_store.get(&from_path).await?.bytes().await?;
+        to_store.put(&to_path, bytes).await?;
+        Ok(())
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        let (from_store, from_path) = self.route(from)?;
+        let (to_store, to_path) = self.route(to)?;
+        if Arc::ptr_eq(&from_store, &to_store) {
+            return from_store.copy_if_not_exists(&from_path, &to_path).await;
+        }
+        let bytes = from_store.get(&from_path).await?.bytes().await?;
+        let options = PutOptions {
+            mode: PutMode::Create,
+            ..Default::default()
+        };
+        to_store.put_opts(&to_path, bytes, options).await?;
+        Ok(())
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> ObjectStoreResult<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        let (store, path) = self.route(location)?;
+        store.put_multipart(&path).await
+    }
+
+    async fn abort_multipart(
+        &self,
+        location: &Path,
+        multipart_id: &MultipartId,
+    ) -> ObjectStoreResult<()> {
+        let (store, path) = self.route(location)?;
+        store.abort_multipart(&path, multipart_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use object_store::local::LocalFileSystem;
+    use parking_lot::Mutex;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct RecordingResolver {
+        urls: Mutex<Vec<Url>>,
+    }
+
+    impl CredentialResolver for RecordingResolver {
+        fn storage_options(&self, url: &Url) -> Option<StorageOptions> {
+            self.urls.lock().push(url.clone());
+            Some(StorageOptions(HashMap::new()))
+        }
+    }
+
+    #[test]
+    fn test_external_file_path() {
+        assert_eq!(external_file_path("part-0.parquet"), None);
+        assert_eq!(external_file_path("x=a%3A%2F%2Fb/part-0.parquet"), None);
+        let path = external_file_path("s3://other-bucket/data/part-0.parquet").unwrap();
+        assert_eq!(
+            path.as_ref(),
+            "_delta_external/s3/other-bucket/data/part-0.parquet"
+        );
+        let (bucket, path) = split_external_path(&path).unwrap();
+        assert_eq!(bucket, "s3://other-bucket/");
+        assert_eq!(path.as_ref(), "data/part-0.parquet");
+
+        let path =
+            external_file_path("abfss://container@account.dfs.core.windows.net/part.parquet")
+                .unwrap();
+        let (bucket, _) = split_external_path(&path).unwrap();
+        assert_eq!(bucket, "abfss://container@account.dfs.core.windows.net/");
+
+        let path = external_file_path("file:///tmp/part.parquet").unwrap();
+        assert_eq!(path.as_ref(), "_delta_external/file/-/tmp/part.parquet");
+        let (bucket, _) = split_external_path(&path).unwrap();
+        assert_eq!(bucket, "file:///");
+    }
+
+    #[tokio::test]
+    async fn test_external_files_store() {
+        let table_dir = tempfile::tempdir().unwrap();
+        let other_dir = tempfile::tempdir().unwrap();
+        let other = LocalFileSystem::new_with_prefix(other_dir.path()).unwrap();
+        other
+            .put(&Path::from("data/part-0.parquet"), Bytes::from("external"))
+            .await
+            .unwrap();
+
+        let inner = Arc::new(LocalFileSystem::new_with_prefix(table_dir.path()).unwrap());
+        inner
+            .put(&Path::from("part-1.parquet"), Bytes::from("local"))
+            .await
+            .unwrap();
+        let resolver = Arc::new(RecordingResolver::default());
+        let store = ExternalFilesStore::new(inner, StorageOptions::default())
+            .with_credential_resolver(resolver.clone());
+
+        let uri = Url::from_file_path(other_dir.path().join("data/part-0.parquet")).unwrap();
+        let location = external_file_path(uri.as_str()).unwrap();
+        let bytes = store.get(&location).await.unwrap().bytes().await.unwrap();
+        assert_eq!(bytes, Bytes::from("external"));
+        let meta = store.head(&location).await.unwrap();
+        assert_eq!(meta.location, location);
+        assert_eq!(meta.size, 8);
+
+        let bytes = store
+            .get(&Path::from("part-1.parquet"))
+            .await
+            .unwrap()
+            .bytes()
+            .await
+            .unwrap();
+        assert_eq!(bytes, Bytes::from("local"));
+
+        let urls = resolver.urls.lock().clone();
+        assert_eq!(urls, vec![Url::parse("file:///").unwrap()]);
+    }
+}
@@ -0,0 +1,307 @@
+//! Process-wide cache of parsed parquet footers
+//!
+//! Every scan of a table fetches and decodes the footer of each data file it reads, even when
+//! the same files were scanned moments before. Data files are immutable, so their footers can be
+//! cached by path, size and e-tag. The [`ParquetFooterCache`] is a least recently used cache of
+//! decoded [`ParquetMetaData`] shared by all scans of the process, see
+//! [`ParquetFooterCache::global`].
+//!
+//! Readers created with [`CachingParquetReader::new`] consult the cache before fetching a
+//! footer. Scans through DataFusion use the global cache automatically.
+//!
+//! # Example
+//! ```rust ignore
+//! // keep up to 10000 footers
+//! ParquetFooterCache::global().set_capacity(10_000);
+//! ````
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use object_store::path::Path;
+use object_store::{ObjectMeta, ObjectStore};
+use parking_lot::Mutex;
+use parquet::arrow::async_reader::{AsyncFileReader, ParquetObjectReader};
+use parquet::errors::Result as ParquetResult;
+use parquet::file::metadata::ParquetMetaData;
+
+/// Number of footers kept by the global cache unless configured otherwise
+pub const DEFAULT_FOOTER_CACHE_CAPACITY: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct FooterKey {
+    path: Path,
+    size: usize,
+    e_tag: Option<String>,
+}
+
+impl From<&ObjectMeta> for FooterKey {
+    fn from(meta: &ObjectMeta) -> Self {
+        Self {
+            path: meta.location.clone(),
+            size: meta.size,
+            e_tag: meta.e_tag.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<FooterKey, (Arc<ParquetMetaData>, u64)>,
+}
+
+/// Least recently used cache of parquet footers.
+/// See this module's documentation for more information
+#[derive(Debug)]
+pub struct ParquetFooterCache {
+    state: Mutex<CacheState>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ParquetFooterCache {
+    /// Create a cache holding up to `capacity` footers, a capacity of 0 disables caching
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: Mutex::new(CacheState {
+                capacity,
+                ..Default::default()
+            }),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The cache shared by all scans of the process
+    pub fn global() -> Arc<ParquetFooterCache> {
+        static GLOBAL: OnceLock<Arc<ParquetFooterCache>> = OnceLock::new();
+        GLOBAL
+            .get_or_init(|| Arc::new(ParquetFooterCache::new(DEFAULT_FOOTER_CACHE_CAPACITY)))
+            .clone()
+    }
+
+    /// Change the maximum number of cached footers, evicting footers as needed
+    pub fn set_capacity(&self, capacity: usize) {
+        let mut state = self.state.lock();
+        state.capacity = capacity;
+        while state.entries.len() > capacity {
+            evict_lru(&mut state);
+        }
+    }
+
+    /// Number of cached footers
+    pub fn len(&self) -> usize {
+        self.state.lock().entries.len()
+    }
+
+    /// Whether no footers are cached
+    pub fn is_empty(&self) -> bool {
+        self.state.lock().entries.is_empty()
+    }
+
+    /// Remove all cached footers
+    pub fn clear(&self) {
+        self.state.lock().entries.clear();
+    }
+
+    /// Number of footers served from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of footers which had to be fetched
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    fn get(&self, key: &FooterKey) -> Option<Arc<ParquetMetaData>> {
+        let mut state = self.state.lock();
+        state.tick += 1;
+        let tick = state.tick;
+        let found = state.entries.get_mut(key).map(|(metadata, last_used)| {
+            *last_used = tick;
+            metadata.clone()
+        });
+        match found {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+        found
+    }
+
+    fn insert(&self, key: FooterKey, metadata: Arc<ParquetMetaData>) {
+        let mut state = self.state.lock();
+        if state.capacity == 0 {
+            return;
+        }
+        while state.entries.len() >= state.capacity && !state.entries.contains_key(&key) {
+            evict_lru(&mut state);
+        }
+        state.tick += 1;
+        let tick = state.tick;
+        state.entries.insert(key, (metadata, tick));
+    }
+}
+
+fn evict_lru(state: &mut CacheState) {
+    let oldest = state
+        .entries
+        .iter()
+        .min_by_key(|(_, (_, last_used))| *last_used)
+        .map(|(key, _)| key.clone());
+    if let Some(oldest) = oldest {
+        state.entries.remove(&oldest);
+    }
+}
+
+/// [`AsyncFileReader`] for parquet files in an object store, serving footers from a
+/// [`ParquetFooterCache`]
+pub struct CachingParquetReader {
+    inner: ParquetObjectReader,
+    key: FooterKey,
+    cache: Arc<ParquetFooterCache>,
+}
+
+impl CachingParquetReader {
+    /// Create a reader for the file described by `meta`
+    pub fn new(
+        store: Arc<dyn ObjectStore>,
+        meta: ObjectMeta,
+        cache: Arc<ParquetFooterCache>,
+    ) -> Self {
+        Self {
+            key: FooterKey::from(&meta),
+            inner: ParquetObjectReader::new(store, meta),
+            cache,
+        }
+    }
+
+    /// Fetch at least `hint` bytes when reading the footer, see
+    /// [`ParquetObjectReader::with_footer_size_hint`]
+    pub fn with_footer_size_hint(mut self, hint: usize) -> Self {
+        self.inner = self.inner.with_footer_size_hint(hint);
+        self
+    }
+}
+
+impl AsyncFileReader for CachingParquetReader {
+    fn get_bytes(&mut self, range: Range<usize>) -> BoxFuture<'_, ParquetResult<Bytes>> {
+        self.inner.get_bytes(range)
+    }
+
+    fn get_byte_ranges(
+        &mut self,
+        ranges: Vec<Range<usize>>,
+    ) -> BoxFuture<'_, ParquetResult<Vec<Bytes>>> {
+        self.inner.get_byte_ranges(ranges)
+    }
+
+    fn get_metadata(&mut self) -> BoxFuture<'_, ParquetResult<Arc<ParquetMetaData>>> {
+        Box::pin(async move {
+            if let Some(metadata) = self.cache.get(&self.key) {
+                return Ok(metadata);
+            }
+            let metadata = self.inner.get_metadata().await?;
+            self.cache.insert(self.key.clone(), metadata.clone());
+            Ok(metadata)
+        })
+    }
+}
+
+#[cfg(feature = "datafusion")]
+mod datafusion_factory {
+    use datafusion::datasource::physical_plan::{FileMeta, ParquetFileReaderFactory};
+    use datafusion::physical_plan::metrics::ExecutionPlanMetricsSet;
+    use datafusion_common::Result as DataFusionResult;
+
+    use super::*;
+
+    /// [`ParquetFileReaderFactory`] creating [`CachingParquetReader`]s
+    #[derive(Debug)]
+    pub struct CachingParquetFileReaderFactory {
+        store: Arc<dyn ObjectStore>,
+        cache: Arc<ParquetFooterCache>,
+    }
+
+    impl CachingParquetFileReaderFactory {
+        /// Create a factory for files in `store`
+        pub fn new(store: Arc<dyn ObjectStore>, cache: Arc<ParquetFooterCache>) -> Self {
+            Self { store, cache }
+        }
+    }
+
+    impl ParquetFileReaderFactory for CachingParquetFileReaderFactory {
+        fn create_reader(
+            &self,
+            _partition_index: usize,
+            file_meta: FileMeta,
+            metadata_size_hint: Option<usize>,
+            _metrics: &ExecutionPlanMetricsSet,
+        ) -> DataFusionResult<Box<dyn AsyncFileReader + Send>> {
+            let mut reader = CachingParquetReader::new(
+                self.store.clone(),
+                file_meta.object_meta,
+                self.cache.clone(),
+            );
+            if let Some(hint) = metadata_size_hint {
+                reader = reader.with_footer_size_hint(hint);
+            }
+            Ok(Box::new(reader))
+        }
+    }
+}
+
+#[cfg(feature = "datafusion")]
+pub use datafusion_factory::CachingParquetFileReaderFactory;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::writer::test_utils::{create_initialized_table, get_record_batch};
+    use crate::writer::{DeltaWriter, RecordBatchWriter};
+
+    #[tokio::test]
+    async fn test_footer_cache() {
+        let mut table = create_initialized_table(&[]).await;
+        let mut writer = RecordBatchWriter::for_table(&table).unwrap();
+        for _ in 0..2 {
+            writer.write(get_record_batch(None, false)).await.unwrap();
+            writer.flush_and_commit(&mut table).await.unwrap();
+        }
+        let store = table.object_store();
+        let metas = table
+            .snapshot()
+            .unwrap()
+            .file_actions()
+            .unwrap()
+            .iter()
+            .map(|add| add.try_into().unwrap())
+            .collect::<Vec<ObjectMeta>>();
+
+        let cache = Arc::new(ParquetFooterCache::new(1));
+        let mut reader = CachingParquetReader::new(store.clone(), metas[0].clone(), cache.clone());
+        let fetched = reader.get_metadata().await.unwrap();
+        let mut reader = CachingParquetReader::new(store.clone(), metas[0].clone(), cache.clone());
+        let cached = reader.get_metadata().await.unwrap();
+        assert!(Arc::ptr_eq(&fetched, &cached));
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+        // the capacity is exceeded by the second file
+        let mut reader = CachingParquetReader::new(store.clone(), metas[1].clone(), cache.clone());
+        reader.get_metadata().await.unwrap();
+        assert_eq!(cache.len(), 1);
+        let mut reader = CachingParquetReader::new(store, metas[0].clone(), cache.clone());
+        let refetched = reader.get_metadata().await.unwrap();
+        assert!(!Arc::ptr_eq(&fetched, &refetched));
+        assert_eq!(cache.misses(), 3);
+
+        cache.set_capacity(0);
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,477 @@
+//! Hedged read requests against object stores
+//!
+//! Object store requests have long latency tails, a small fraction of GET requests on a flaky
+//! connection take many times longer than the rest. Scans issue many reads, so the slowest one
+//! determines the latency of the scan. The [`HedgedObjectStore`] issues a duplicate of a read
+//! request once it has taken longer than a percentile of the recently observed read latencies,
+//! and uses whichever response arrives first.
+//!
+//! Only reads are hedged, all other requests are passed to the wrapped store unchanged. For
+//! `get` requests the hedge covers the time until the response starts, not the streaming of the
+//! body.
+//!
+//! Hedging is configured per table through its storage options, so that it can be enabled for
+//! the backends that need it:
+//!
+//! - `DELTA_HEDGE_REQUESTS`: set to `true` to enable hedged reads
+//! - `DELTA_HEDGE_PERCENTILE`: latency percentile after which a request is hedged, between 0 and
+//!   1, defaults to 0.95
+//! - `DELTA_HEDGE_MIN_DELAY_MS`: minimum delay before a request is hedged, defaults to 20
+//! - `DELTA_HEDGE_INITIAL_DELAY_MS`: delay before a request is hedged while too few latencies
+//!   have been observed, defaults to 100
+//!
+//! # Example
+//! ```rust ignore
+//! let table = DeltaTableBuilder::from_uri("s3://bucket/table")
+//!     .with_storage_options(HashMap::from([
+//!         ("DELTA_HEDGE_REQUESTS".to_string(), "true".to_string()),
+//!         ("DELTA_HEDGE_PERCENTILE".to_string(), "0.9".to_string()),
+//!     ]))
+//!     .load()
+//!     .await?;
+//! ````
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use futures::future::Either;
+use futures::stream::BoxStream;
+use object_store::path::Path;
+use object_store::{
+    GetOptions, GetResult, ListResult, MultipartId, ObjectMeta, ObjectStore, PutOptions, PutResult,
+    Result as ObjectStoreResult,
+};
+use parking_lot::Mutex;
+use tokio::io::AsyncWrite;
+
+use super::utils::copy_get_options;
+use super::{str_is_truthy, ObjectStoreRef, StorageOptions};
+use crate::{DeltaResult, DeltaTableError};
+
+/// Storage option enabling hedged reads
+pub const HEDGE_REQUESTS_KEY: &str = "DELTA_HEDGE_REQUESTS";
+/// Storage option setting the latency percentile after which a read is hedged
+pub const HEDGE_PERCENTILE_KEY: &str = "DELTA_HEDGE_PERCENTILE";
+/// Storage option setting the minimum delay in milliseconds before a read is hedged
+pub const HEDGE_MIN_DELAY_KEY: &str = "DELTA_HEDGE_MIN_DELAY_MS";
+/// Storage option setting the delay in milliseconds before a read is hedged while too few
+/// latencies have been observed
+pub const HEDGE_INITIAL_DELAY_KEY: &str = "DELTA_HEDGE_INITIAL_DELAY_MS";
+
+/// Number of observed latencies required before the percentile is used
+const MIN_SAMPLES: usize = 20;
+
+/// Configuration of a [`HedgedObjectStore`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct HedgeConfig {
+    /// Latency percentile after which a read is hedged, between 0 and 1
+    pub percentile: f64,
+    /// Minimum delay before a read is hedged
+    pub min_delay: Duration,
+    /// Delay before a read is hedged while too few latencies have been observed
+    pub initial_delay: Duration,
+    /// Number of recent latencies the percentile is computed from
+    pub window: usize,
+}
+
+impl Default for HedgeConfig {
+    fn default() -> Self {
+        Self {
+            percentile: 0.95,
+            min_delay: Duration::from_millis(20),
+            initial_delay: Duration::from_millis(100),
+            window: 1000,
+        }
+    }
+}
+
+impl HedgeConfig {
+    /// The hedging configuration of the storage options, `None` if hedging is not enabled
+    pub fn from_options(options: &StorageOptions) -> DeltaResult<Option<Self>> {
+        let options = &options.0;
+        if !options
+            .get(HEDGE_REQUESTS_KEY)
+            .is_some_and(|value| str_is_truthy(value))
+        {
+            return Ok(None);
+        }
+        let mut config = Self::default();
+        if let Some(value) = options.get(HEDGE_PERCENTILE_KEY) {
+            config.percentile = value
+                .parse::<f64>()
+                .ok()
+                .filter(|percentile| (0.0..=1.0).contains(percentile))
+                .ok_or_else(|| invalid_option(HEDGE_PERCENTILE_KEY, value))?;
+        }
+        if let Some(value) = options.get(HEDGE_MIN_DELAY_KEY) {
+            config.min_delay = parse_millis(HEDGE_MIN_DELAY_KEY, value)?;
+        }
+        if let Some(value) = options.get(HEDGE_INITIAL_DELAY_KEY) {
+            config.initial_delay = parse_millis(HEDGE_INITIAL_DELAY_KEY, value)?;
+        }
+        Ok(Some(config))
+    }
+}
+
+fn invalid_option(key: &str, value: &str) -> DeltaTableError {
+    DeltaTableError::Generic(format!("Invalid value '{value}' for storage option {key}"))
+}
+
+fn parse_millis(key: &str, value: &str) -> DeltaResult<Duration> {
+    value
+        .parse::<u64>()
+        .map(Duration::from_millis)
+        .map_err(|_| invalid_option(key, value))
+}
+
+/// Wrap `store` in a [`HedgedObjectStore`] if the storage options enable hedged reads
+pub fn hedge_store(store: ObjectStoreRef, options: &StorageOptions) -> DeltaResult<ObjectStoreRef> {
+    Ok(match HedgeConfig::from_options(options)? {
+        Some(config) => Arc::new(HedgedObjectStore::new(store, config)),
+        None => store,
+    })
+}
+
+#[derive(Debug)]
+struct LatencyWindow {
+    samples: VecDeque<Duration>,
+    capacity: usize,
+}
+
+impl LatencyWindow {
+    fn record(&mut self, latency: Duration) {
+        if self.samples.len() >= self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(latency);
+    }
+
+    fn threshold(&self, config: &HedgeConfig) -> Duration {
+        if self.samples.len() < MIN_SAMPLES {
+            return config.initial_delay.max(config.min_delay);
+        }
+        let mut sorted = self.samples.iter().copied().collect::<Vec<_>>();
+        sorted.sort_unstable();
+        let index = ((sorted.len() - 1) as f64 * config.percentile).round() as usize;
+        sorted[index].max(config.min_delay)
+    }
+}
+
+/// [`ObjectStore`] hedging read requests to the wrapped store.
+/// See this module's documentation for more information
+#[derive(Debug)]
+pub struct HedgedObjectStore {
+    inner: ObjectStoreRef,
+    config: HedgeConfig,
+    latencies: Mutex<LatencyWindow>,
+    hedged: AtomicU64,
+}
+
+impl HedgedObjectStore {
+    /// Hedge reads to `inner` according to `config`
+    pub fn new(inner: ObjectStoreRef, config: HedgeConfig) -> Self {
+        let latencies = Mutex::new(LatencyWindow {
+            samples: VecDeque::with_capacity(config.window.min(MIN_SAMPLES)),
+            capacity: config.window.max(1),
+        });
+        Self {
+            inner,
+            config,
+            latencies,
+            hedged: AtomicU64::new(0),
+        }
+    }
+
+    /// Number of requests for which a duplicate request was issued
+    pub fn hedged_requests(&self) -> u64 {
+        self.hedged.load(Ordering::Relaxed)
+    }
+
+    /// The current delay after which a read is hedged
+    pub fn hedge_delay(&self) -> Duration {
+        self.latencies.lock().threshold(&self.config)
+    }
+
+    async fn hedge<T, F, Fut>(&self, request: F) -> ObjectStoreResult<T>
+    where
+        T: Send,
+        F: Fn() -> Fut + Send + Sync,
+        Fut: Future<Output = ObjectStoreResult<T>> + Send,
+    {
+        let start = Instant::now();
+        let first = request();
+        futures::pin_mut!(first);
+        let result = match tokio::time::timeout(self.hedge_delay(), &mut first).await {
+            Ok(result) => result,
+            Err(_) => {
+                self.hedged.fetch_add(1, Ordering::Relaxed);
+                let second = request();
+                futures::pin_mut!(second);
+                match futures::future::select(first, second).await {
+                    Either::Left((Ok(value), _)) | Either::Right((Ok(value), _)) => Ok(value),
+                    // one of the requests failed, the other one may still succeed
+                    Either::Left((Err(_), other)) | Either::Right((Err(_), other)) => other.await,
+                }
+            }
+        };
+        if result.is_ok() {
+            self.latencies.lock().record(start.elapsed());
+        }
+        result
+    }
+}
+
+impl std::fmt::Display for HedgedObjectStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HedgedObjectStore({})", self.inner)
+    }
+}
+
+#[async_trait::async_trait]
+impl ObjectStore for HedgedObjectStore {
+    async fn put(&self, location: &Path, bytes: Bytes) -> ObjectStoreResult<PutResult> {
+        self.inner.put(location, bytes).await
+    }
+
+    async fn put_opts(
+        &self,
+        location: &Path,
+        bytes: Bytes,
+        options: PutOptions,
+    ) -> ObjectStoreResult<PutResult> {
+        self.inner.put_opts(location, bytes, options).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> ObjectStoreResult<GetResult> {
+        self.hedge(move || self.inner.get_opts(location, copy_get_options(&options)))
+            .await
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> ObjectStoreResult<Bytes> {
+        self.hedge(move || self.inner.get_range(location, range.clone()))
+            .await
+    }
+
+    async fn get_ranges(
+        &self,
+        location: &Path,
+        ranges: &[Range<usize>],
+    ) -> ObjectStoreResult<Vec<Bytes>> {
+        self.hedge(move || self.inner.get_ranges(location, ranges))
+            .await
+    }
+
+    async fn head(&self, location: &Path) -> ObjectStoreResult<ObjectMeta> {
+        self.hedge(move || self.inner.head(location)).await
+    }
+
+    async fn delete(&self, location: &Path) -> ObjectStoreResult<()> {
+        self.inner.delete(location).await
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, ObjectStoreResult<ObjectMeta>> {
+        self.inner.list(prefix)
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'_, ObjectStoreResult<ObjectMeta>> {
+        self.inner.list_with_offset(prefix, offset)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> ObjectStoreResult<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.inner.copy_if_not_exists(from, to).await
+    }
+
+    async fn rename_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.inner.rename_if_not_exists(from, to).await
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> ObjectStoreResult<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        self.inner.put_multipart(location).await
+    }
+
+    async fn abort_multipart(
+        &self,
+        location: &Path,
+        multipart_id: &MultipartId,
+    ) -> ObjectStoreResult<()> {
+        self.inner.abort_multipart(location, multipart_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::atomic::AtomicUsize;
+
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    /// Store delaying the first read request
+    #[derive(Debug)]
+    struct FirstReadSlowStore {
+        inner: InMemory,
+        reads: AtomicUsize,
+    }
+
+    impl std::fmt::Display for FirstReadSlowStore {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "FirstReadSlowStore")
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ObjectStore for FirstReadSlowStore {
+        async fn put_opts(
+            &self,
+            location: &Path,
+            bytes: Bytes,
+            options: PutOptions,
+        ) -> ObjectStoreResult<PutResult> {
+            self.inner.put_opts(location, bytes, options).await
+        }
+
+        async fn get_opts(
+            &self,
+            location: &Path,
+            options: GetOptions,
+        ) -> ObjectStoreResult<GetResult> {
+            if self.reads.fetch_add(1, Ordering::SeqCst) == 0 {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            }
+            self.inner.get_opts(location, options).await
+        }
+
+        async fn delete(&self, location: &Path) -> ObjectStoreResult<()> {
+            self.inner.delete(location).await
+        }
+
+        fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, ObjectStoreResult<ObjectMeta>> {
+            self.inner.list(prefix)
+        }
+
+        async fn list_with_delimiter(
+            &self,
+            prefix: Option<&Path>,
+        ) -> ObjectStoreResult<ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+
+        async fn copy(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+            self.inner.copy(from, to).await
+        }
+
+        async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+            self.inner.copy_if_not_exists(from, to).await
+        }
+
+        async fn put_multipart(
+            &self,
+            location: &Path,
+        ) -> ObjectStoreResult<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+            self.inner.put_multipart(location).await
+        }
+
+        async fn abort_multipart(
+            &self,
+            location: &Path,
+            multipart_id: &MultipartId,
+        ) -> ObjectStoreResult<()> {
+            self.inner.abort_multipart(location, multipart_id).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hedged_read() {
+        let slow = Arc::new(FirstReadSlowStore {
+            inner: InMemory::new(),
+            reads: AtomicUsize::new(0),
+        });
+        let location = Path::from("data.parquet");
+        slow.put(&location, Bytes::from_static(b"data"))
+            .await
+            .unwrap();
+
+        let config = HedgeConfig {
+            min_delay: Duration::from_millis(10),
+            initial_delay: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let store = HedgedObjectStore::new(slow.clone(), config);
+        let bytes = tokio::time::timeout(Duration::from_secs(10), store.get_range(&location, 0..2))
+            .await
+            .expect("the hedged request should complete")
+            .unwrap();
+        assert_eq!(bytes.as_ref(), b"da");
+        assert_eq!(store.hedged_requests(), 1);
+        assert_eq!(slow.reads.load(Ordering::SeqCst), 2);
+
+        store.head(&location).await.unwrap();
+        assert_eq!(store.hedged_requests(), 1);
+    }
+
+    #[test]
+    fn test_hedge_delay() {
+        let config = HedgeConfig {
+            percentile: 0.5,
+            min_delay: Duration::from_millis(5),
+            initial_delay: Duration::from_millis(100),
+            window: 50,
+        };
+        let store = HedgedObjectStore::new(Arc::new(InMemory::new()), config);
+        assert_eq!(store.hedge_delay(), Duration::from_millis(100));
+
+        for millis in 1..=100 {
+            store.latencies.lock().record(Duration::from_millis(millis));
+        }
+        // only the latest 50 latencies, 51ms to 100ms, are considered
+        assert_eq!(store.hedge_delay(), Duration::from_millis(76));
+
+        for _ in 0..50 {
+            store.latencies.lock().record(Duration::from_millis(1));
+        }
+        assert_eq!(store.hedge_delay(), Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_hedge_config_from_options() {
+        let options = StorageOptions(HashMap::new());
+        assert_eq!(HedgeConfig::from_options(&options).unwrap(), None);
+
+        let options = StorageOptions(HashMap::from([
+            (HEDGE_REQUESTS_KEY.to_string(), "true".to_string()),
+            (HEDGE_PERCENTILE_KEY.to_string(), "0.99".to_string()),
+            (HEDGE_MIN_DELAY_KEY.to_string(), "50".to_string()),
+        ]));
+        let config = HedgeConfig::from_options(&options).unwrap().unwrap();
+        assert_eq!(config.percentile, 0.99);
+        assert_eq!(config.min_delay, Duration::from_millis(50));
+        assert_eq!(config.initial_delay, Duration::from_millis(100));
+
+        let options = StorageOptions(HashMap::from([
+            (HEDGE_REQUESTS_KEY.to_string(), "true".to_string()),
+            (HEDGE_PERCENTILE_KEY.to_string(), "95".to_string()),
+        ]));
+        assert!(HedgeConfig::from_options(&options).is_err());
+    }
+}
@@ -8,7 +8,14 @@ use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
+pub mod adaptive;
+pub mod deadline;
+pub mod external;
 pub mod file;
+pub mod footer_cache;
+pub mod hedged;
+pub mod read_only;
+pub mod retry;
 pub mod retry_ext;
 pub mod utils;
 
@@ -55,7 +62,10 @@ impl ObjectStoreFactory for DefaultObjectStoreFactory {
         match url.scheme() {
             "memory" => {
                 let path = Path::from_url_path(url.path())?;
-                let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new()) as ObjectStoreRef;
+                let store = match url.host_str() {
+                    Some(name) if !name.is_empty() => memory_store(name),
+                    _ => Arc::new(InMemory::new()) as ObjectStoreRef,
+                };
                 Ok((url_prefix_handler(store, path.clone())?, path))
             }
             "file" => {
@@ -69,6 +79,35 @@ impl ObjectStoreFactory for DefaultObjectStoreFactory {
     }
 }
 
+/// The in-memory store shared by all `memory://<name>` locations.
+///
+/// Locations without a name, e.g. `memory:///` or `memory://`, get a new empty store every time
+/// they are resolved. Named stores live as long as the process, so tables in them can be created
+/// and opened again by their location, e.g. in tests and examples which must not touch the disk.
+///
+/// ```rust
+/// # use deltalake_core::storage::{memory_store, Path};
+/// # async {
+/// let table = deltalake_core::DeltaOps::try_from_uri("memory://examples/sales")
+///     .await
+///     .unwrap()
+///     .create()
+///     .with_column("id", deltalake_core::kernel::DataType::LONG, true, None)
+///     .await
+///     .unwrap();
+/// let commit = Path::from("sales/_delta_log/00000000000000000000.json");
+/// assert!(memory_store("examples").head(&commit).await.is_ok());
+/// # };
+/// ```
+pub fn memory_store(name: &str) -> ObjectStoreRef {
+    static STORES: OnceLock<DashMap<String, ObjectStoreRef>> = OnceLock::new();
+    STORES
+        .get_or_init(DashMap::new)
+        .entry(name.to_string())
+        .or_insert_with(|| Arc::new(InMemory::new()))
+        .clone()
+}
+
 /// TODO
 pub type FactoryRegistry = Arc<DashMap<Url, Arc<dyn ObjectStoreFactory>>>;
 
@@ -91,6 +130,46 @@ pub fn factories() -> FactoryRegistry {
         .clone()
 }
 
+/// [ObjectStoreFactory] serving a store registered with [register_store]
+#[derive(Clone, Debug)]
+struct RegisteredStoreFactory {
+    store: ObjectStoreRef,
+}
+
+impl ObjectStoreFactory for RegisteredStoreFactory {
+    fn parse_url_opts(
+        &self,
+        url: &Url,
+        _options: &StorageOptions,
+    ) -> DeltaResult<(ObjectStoreRef, Path)> {
+        let path = Path::from_url_path(url.path())?;
+        Ok((url_prefix_handler(self.store.clone(), path.clone())?, path))
+    }
+}
+
+/// Register `store` for all table locations with the url `scheme`, e.g. `ceph`.
+///
+/// Tables are stored under the path of their location in `store`, so `ceph:///warehouse/sales`
+/// is read from `warehouse/sales` in the registered store. A store registered before for the
+/// scheme is replaced. Unless a [LogStoreFactory](crate::logstore::LogStoreFactory) was
+/// registered for the scheme, the default log store is used, which relies on
+/// [ObjectStore::rename_if_not_exists] for commits.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// # use deltalake_core::storage::{register_store, object_store::memory::InMemory};
+/// register_store("mem", Arc::new(InMemory::new())).unwrap();
+/// ```
+pub fn register_store(scheme: &str, store: ObjectStoreRef) -> DeltaResult<()> {
+    let url = Url::parse(&format!("{scheme}://"))
+        .map_err(|_| DeltaTableError::Generic(format!("Invalid url scheme: {scheme}")))?;
+    crate::logstore::logstores()
+        .entry(url.clone())
+        .or_insert_with(|| Arc::new(crate::logstore::DefaultLogStoreFactory::default()));
+    factories().insert(url, Arc::new(RegisteredStoreFactory { store }));
+    Ok(())
+}
+
 /// Simpler access pattern for the [FactoryRegistry] to get a single store
 pub fn store_for(url: &Url) -> DeltaResult<ObjectStoreRef> {
     let scheme = Url::parse(&format!("{}://", url.scheme())).unwrap();
@@ -171,4 +250,67 @@ mod tests {
         let prefixed = url_prefix_handler(store, path);
         assert!(prefixed.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_memory_store() {
+        let create = |uri: &'static str| async move {
+            crate::DeltaOps::try_from_uri(uri)
+                .await
+                .unwrap()
+                .create()
+                .with_column("id", crate::kernel::DataType::INTEGER, true, None)
+                .await
+                .unwrap()
+        };
+
+        // named stores are shared by all locations with the name
+        create("memory://test_memory_store/table").await;
+        let table = crate::open_table("memory://test_memory_store/table")
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 0);
+        assert!(memory_store("test_memory_store")
+            .head(&Path::from("table/_delta_log/00000000000000000000.json"))
+            .await
+            .is_ok());
+        assert!(crate::open_table("memory://other_memory_store/table")
+            .await
+            .is_err());
+
+        // unnamed locations get a new store
+        create("memory:///table").await;
+        assert!(crate::open_table("memory:///table").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_register_store() {
+        let store = Arc::new(InMemory::new());
+        register_store("custom", store.clone()).unwrap();
+        assert!(register_store("not a scheme", store.clone()).is_err());
+
+        let table = crate::DeltaOps::try_from_uri("custom:///warehouse/table")
+            .await
+            .unwrap()
+            .create()
+            .with_column(
+                "id",
+                crate::kernel::DataType::Primitive(crate::kernel::PrimitiveType::Integer),
+                true,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 0);
+        assert!(store
+            .head(&Path::from(
+                "warehouse/table/_delta_log/00000000000000000000.json"
+            ))
+            .await
+            .is_ok());
+
+        let table = crate::open_table("custom:///warehouse/table")
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 0);
+    }
 }
@@ -0,0 +1,188 @@
+//! Object store rejecting all modifications
+//!
+//! Tables opened in read-only mode access their storage through a [`ReadOnlyObjectStore`], so
+//! that destructive paths which don't create a commit, such as log cleanup or removing orphaned
+//! files, fail rather than modifying the table. Requests which read from the store are passed to
+//! the wrapped store unchanged.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use object_store::path::Path;
+use object_store::{
+    Error as ObjectStoreError, GetOptions, GetResult, ListResult, MultipartId, ObjectMeta,
+    ObjectStore, PutOptions, PutResult, Result as ObjectStoreResult,
+};
+use tokio::io::AsyncWrite;
+
+use super::ObjectStoreRef;
+
+/// Error of requests modifying a read-only store
+#[derive(thiserror::Error, Debug)]
+#[error("Table was opened in read-only mode, modifying its storage is not allowed")]
+pub struct ReadOnlyStore;
+
+fn read_only() -> ObjectStoreError {
+    ObjectStoreError::Generic {
+        store: "ReadOnlyObjectStore",
+        source: Box::new(ReadOnlyStore),
+    }
+}
+
+/// Whether `err` was caused by modifying a read-only store
+pub fn is_read_only_error(err: &ObjectStoreError) -> bool {
+    matches!(err, ObjectStoreError::Generic { source, .. } if source.is::<ReadOnlyStore>())
+}
+
+/// Wrap `store` so all requests modifying it fail
+pub fn read_only_store(store: ObjectStoreRef) -> ObjectStoreRef {
+    Arc::new(ReadOnlyObjectStore::new(store))
+}
+
+/// [`ObjectStore`] failing all requests which modify the wrapped store.
+/// See this module's documentation for more information
+#[derive(Debug)]
+pub struct ReadOnlyObjectStore {
+    inner: ObjectStoreRef,
+}
+
+impl ReadOnlyObjectStore {
+    /// Reject all modifications of `inner`
+    pub fn new(inner: ObjectStoreRef) -> Self {
+        Self { inner }
+    }
+}
+
+impl std::fmt::Display for ReadOnlyObjectStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ReadOnlyObjectStore({})", self.inner)
+    }
+}
+
+#[async_trait::async_trait]
+impl ObjectStore for ReadOnlyObjectStore {
+    async fn put(&self, _location: &Path, _bytes: Bytes) -> ObjectStoreResult<PutResult> {
+        Err(read_only())
+    }
+
+    async fn put_opts(
+        &self,
+        _location: &Path,
+        _bytes: Bytes,
+        _options: PutOptions,
+    ) -> ObjectStoreResult<PutResult> {
+        Err(read_only())
+    }
+
+    async fn get(&self, location: &Path) -> ObjectStoreResult<GetResult> {
+        self.inner.get(location).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> ObjectStoreResult<GetResult> {
+        self.inner.get_opts(location, options).await
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> ObjectStoreResult<Bytes> {
+        self.inner.get_range(location, range).await
+    }
+
+    async fn get_ranges(
+        &self,
+        location: &Path,
+        ranges: &[Range<usize>],
+    ) -> ObjectStoreResult<Vec<Bytes>> {
+        self.inner.get_ranges(location, ranges).await
+    }
+
+    async fn head(&self, location: &Path) -> ObjectStoreResult<ObjectMeta> {
+        self.inner.head(location).await
+    }
+
+    async fn delete(&self, _location: &Path) -> ObjectStoreResult<()> {
+        Err(read_only())
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, ObjectStoreResult<ObjectMeta>> {
+        self.inner.list(prefix)
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'_, ObjectStoreResult<ObjectMeta>> {
+        self.inner.list_with_offset(prefix, offset)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> ObjectStoreResult<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, _from: &Path, _to: &Path) -> ObjectStoreResult<()> {
+        Err(read_only())
+    }
+
+    async fn copy_if_not_exists(&self, _from: &Path, _to: &Path) -> ObjectStoreResult<()> {
+        Err(read_only())
+    }
+
+    async fn rename_if_not_exists(&self, _from: &Path, _to: &Path) -> ObjectStoreResult<()> {
+        Err(read_only())
+    }
+
+    async fn put_multipart(
+        &self,
+        _location: &Path,
+    ) -> ObjectStoreResult<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        Err(read_only())
+    }
+
+    async fn abort_multipart(
+        &self,
+        location: &Path,
+        multipart_id: &MultipartId,
+    ) -> ObjectStoreResult<()> {
+        self.inner.abort_multipart(location, multipart_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_only_store() {
+        let inner: ObjectStoreRef = Arc::new(InMemory::new());
+        let path = Path::from("a");
+        inner.put(&path, Bytes::from("data")).await.unwrap();
+
+        let store = read_only_store(inner.clone());
+        let bytes = store.get(&path).await.unwrap().bytes().await.unwrap();
+        assert_eq!(bytes, Bytes::from("data"));
+        assert_eq!(
+            store
+                .list(None)
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let other = Path::from("b");
+        let errors = [
+            store.put(&other, Bytes::from("data")).await.unwrap_err(),
+            store.delete(&path).await.unwrap_err(),
+            store.copy(&path, &other).await.unwrap_err(),
+            store.rename_if_not_exists(&path, &other).await.unwrap_err(),
+        ];
+        assert!(errors.iter().all(is_read_only_error));
+        assert!(inner.head(&path).await.is_ok());
+        assert!(inner.head(&other).await.is_err());
+    }
+}
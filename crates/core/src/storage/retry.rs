@@ -0,0 +1,579 @@
+//! Retries and timeouts of storage requests
+//!
+//! The object store clients of the cloud backends retry failed HTTP requests with their own
+//! built-in policies, and a request hanging on a stalled connection blocks for as long as the
+//! client's connection timeouts allow. The [`RetryObjectStore`] applies one policy to the stores
+//! of all backends: every attempt of a request is bounded by a timeout, and requests which failed
+//! with a transient error, e.g. throttling or a dropped connection, or timed out are retried with
+//! exponential backoff.
+//!
+//! Only requests which can be repeated safely are retried: reads, listings with a delimiter,
+//! unconditional puts, deletes and copies. Conditional puts and renames are only bounded by the
+//! timeout, a repeated attempt could fail although the first attempt succeeded. The timeout of
+//! `get` requests covers the time until the response starts, not the streaming of the body.
+//!
+//! The policy is configured per table through its storage options:
+//!
+//! - `DELTA_STORAGE_MAX_RETRIES`: number of times a request is retried, defaults to 3
+//! - `DELTA_STORAGE_BACKOFF_INIT_MS`: delay before the first retry, defaults to 100
+//! - `DELTA_STORAGE_BACKOFF_MAX_MS`: maximum delay between retries, defaults to 15000
+//! - `DELTA_STORAGE_REQUEST_TIMEOUT_MS`: timeout of every attempt of a request, requests don't
+//!   time out if not set
+//!
+//! Requests are passed to the wrapped store unchanged if none of the options are set.
+//!
+//! # Example
+//! ```rust ignore
+//! let table = DeltaTableBuilder::from_uri("s3://bucket/table")
+//!     .with_storage_options(HashMap::from([
+//!         ("DELTA_STORAGE_MAX_RETRIES".to_string(), "5".to_string()),
+//!         ("DELTA_STORAGE_REQUEST_TIMEOUT_MS".to_string(), "30000".to_string()),
+//!     ]))
+//!     .load()
+//!     .await?;
+//! ````
+
+use std::future::Future;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use object_store::path::Path;
+use object_store::{
+    Error as ObjectStoreError, GetOptions, GetResult, ListResult, MultipartId, ObjectMeta,
+    ObjectStore, PutMode, PutOptions, PutResult, Result as ObjectStoreResult,
+};
+use rand::Rng;
+use tokio::io::AsyncWrite;
+use tracing::debug;
+
+use super::deadline::{deadline_passed, is_deadline_exceeded};
+use super::utils::copy_get_options;
+use super::{ObjectStoreRef, StorageOptions};
+use crate::{DeltaResult, DeltaTableError};
+
+/// Storage option setting the number of times a request is retried
+pub const STORAGE_MAX_RETRIES_KEY: &str = "DELTA_STORAGE_MAX_RETRIES";
+/// Storage option setting the delay in milliseconds before the first retry
+pub const STORAGE_BACKOFF_INIT_KEY: &str = "DELTA_STORAGE_BACKOFF_INIT_MS";
+/// Storage option setting the maximum delay in milliseconds between retries
+pub const STORAGE_BACKOFF_MAX_KEY: &str = "DELTA_STORAGE_BACKOFF_MAX_MS";
+/// Storage option setting the timeout in milliseconds of every attempt of a request
+pub const STORAGE_REQUEST_TIMEOUT_KEY: &str = "DELTA_STORAGE_REQUEST_TIMEOUT_MS";
+
+const STORE: &str = "RetryObjectStore";
+
+/// Exponential backoff between the attempts of a request
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageBackoff {
+    /// Delay before the first retry
+    pub init_backoff: Duration,
+    /// Maximum delay between retries
+    pub max_backoff: Duration,
+    /// Factor by which the delay grows with every retry
+    pub base: f64,
+}
+
+impl Default for StorageBackoff {
+    fn default() -> Self {
+        Self {
+            init_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(15),
+            base: 2.,
+        }
+    }
+}
+
+impl StorageBackoff {
+    /// The delay before the `retry`th retry, starting at 1, with jitter so that clients
+    /// throttled at the same time don't retry at the same time
+    pub fn delay(&self, retry: usize) -> Duration {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as usize) as i32;
+        let delay = self.init_backoff.as_secs_f64() * self.base.powi(exponent);
+        let delay = delay.min(self.max_backoff.as_secs_f64());
+        Duration::from_secs_f64(delay * rand::thread_rng().gen_range(0.5..=1.0))
+    }
+}
+
+/// Configuration of a [`RetryObjectStore`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageRetryConfig {
+    /// Number of times a request is retried
+    pub max_retries: usize,
+    /// Backoff between the attempts of a request
+    pub backoff: StorageBackoff,
+    /// Timeout of every attempt of a request
+    pub timeout: Option<Duration>,
+}
+
+impl Default for StorageRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            backoff: StorageBackoff::default(),
+            timeout: None,
+        }
+    }
+}
+
+impl StorageRetryConfig {
+    /// The retry configuration of the storage options, `None` if none of the options are set
+    pub fn from_options(options: &StorageOptions) -> DeltaResult<Option<Self>> {
+        let options = &options.0;
+        let keys = [
+            STORAGE_MAX_RETRIES_KEY,
+            STORAGE_BACKOFF_INIT_KEY,
+            STORAGE_BACKOFF_MAX_KEY,
+            STORAGE_REQUEST_TIMEOUT_KEY,
+        ];
+        if !keys.iter().any(|key| options.contains_key(*key)) {
+            return Ok(None);
+        }
+        let mut config = Self::default();
+        if let Some(value) = options.get(STORAGE_MAX_RETRIES_KEY) {
+            config.max_retries = value
+                .parse::<usize>()
+                .map_err(|_| invalid_option(STORAGE_MAX_RETRIES_KEY, value))?;
+        }
+        if let Some(value) = options.get(STORAGE_BACKOFF_INIT_KEY) {
+            config.backoff.init_backoff = parse_millis(STORAGE_BACKOFF_INIT_KEY, value)?;
+        }
+        if let Some(value) = options.get(STORAGE_BACKOFF_MAX_KEY) {
+            config.backoff.max_backoff = parse_millis(STORAGE_BACKOFF_MAX_KEY, value)?;
+        }
+        if let Some(value) = options.get(STORAGE_REQUEST_TIMEOUT_KEY) {
+            config.timeout = Some(parse_millis(STORAGE_REQUEST_TIMEOUT_KEY, value)?)
+                .filter(|timeout| !timeout.is_zero());
+        }
+        Ok(Some(config))
+    }
+}
+
+fn invalid_option(key: &str, value: &str) -> DeltaTableError {
+    DeltaTableError::Generic(format!("Invalid value '{value}' for storage option {key}"))
+}
+
+fn parse_millis(key: &str, value: &str) -> DeltaResult<Duration> {
+    value
+        .parse::<u64>()
+        .map(Duration::from_millis)
+        .map_err(|_| invalid_option(key, value))
+}
+
+/// Wrap `store` in a [`RetryObjectStore`] if the storage options configure retries
+pub fn retry_store(store: ObjectStoreRef, options: &StorageOptions) -> DeltaResult<ObjectStoreRef> {
+    Ok(match StorageRetryConfig::from_options(options)? {
+        Some(config) => Arc::new(RetryObjectStore::new(store, config)),
+        None => store,
+    })
+}
+
+/// Whether a request failing with `err` may succeed when retried.
+///
+/// The clients report throttling and connection errors which persisted through their own
+/// retries as generic errors, all other errors are definite answers of the store. Requests are
+/// not retried once the deadline of their operation has passed.
+fn is_transient(err: &ObjectStoreError) -> bool {
+    matches!(err, ObjectStoreError::Generic { .. })
+        && !is_deadline_exceeded(err)
+        && !deadline_passed()
+}
+
+/// [`ObjectStore`] retrying requests to the wrapped store.
+/// See this module's documentation for more information
+#[derive(Debug)]
+pub struct RetryObjectStore {
+    inner: ObjectStoreRef,
+    config: StorageRetryConfig,
+    retries: AtomicU64,
+}
+
+impl RetryObjectStore {
+    /// Retry requests to `inner` according to `config`
+    pub fn new(inner: ObjectStoreRef, config: StorageRetryConfig) -> Self {
+        Self {
+            inner,
+            config,
+            retries: AtomicU64::new(0),
+        }
+    }
+
+    /// Number of retried requests
+    pub fn retried_requests(&self) -> u64 {
+        self.retries.load(Ordering::Relaxed)
+    }
+
+    async fn with_timeout<T>(
+        &self,
+        request: impl Future<Output = ObjectStoreResult<T>>,
+    ) -> ObjectStoreResult<T> {
+        match self.config.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, request)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(ObjectStoreError::Generic {
+                        store: STORE,
+                        source: format!("request timed out after {timeout:?}").into(),
+                    })
+                }),
+            None => request.await,
+        }
+    }
+
+    async fn retry<T, F, Fut>(&self, request: F) -> ObjectStoreResult<T>
+    where
+        T: Send,
+        F: Fn() -> Fut + Send + Sync,
+        Fut: Future<Output = ObjectStoreResult<T>> + Send,
+    {
+        let mut retry = 0;
+        loop {
+            match self.with_timeout(request()).await {
+                Err(err) if retry < self.config.max_retries && is_transient(&err) => {
+                    retry += 1;
+                    self.retries.fetch_add(1, Ordering::Relaxed);
+                    let delay = self.config.backoff.delay(retry);
+                    debug!("retrying storage request in {delay:?} after error: {err}");
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for RetryObjectStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RetryObjectStore({})", self.inner)
+    }
+}
+
+#[async_trait::async_trait]
+impl ObjectStore for RetryObjectStore {
+    async fn put(&self, location: &Path, bytes: Bytes) -> ObjectStoreResult<PutResult> {
+        self.retry(move || self.inner.put(location, bytes.clone()))
+            .await
+    }
+
+    async fn put_opts(
+        &self,
+        location: &Path,
+        bytes: Bytes,
+        options: PutOptions,
+    ) -> ObjectStoreResult<PutResult> {
+        match options.mode {
+            PutMode::Overwrite => {
+                self.retry(move || {
+                    self.inner
+                        .put_opts(location, bytes.clone(), options.clone())
+                })
+                .await
+            }
+            _ => {
+                self.with_timeout(self.inner.put_opts(location, bytes, options))
+                    .await
+            }
+        }
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> ObjectStoreResult<GetResult> {
+        self.retry(move || self.inner.get_opts(location, copy_get_options(&options)))
+            .await
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> ObjectStoreResult<Bytes> {
+        self.retry(move || self.inner.get_range(location, range.clone()))
+            .await
+    }
+
+    async fn get_ranges(
+        &self,
+        location: &Path,
+        ranges: &[Range<usize>],
+    ) -> ObjectStoreResult<Vec<Bytes>> {
+        self.retry(move || self.inner.get_ranges(location, ranges))
+            .await
+    }
+
+    async fn head(&self, location: &Path) -> ObjectStoreResult<ObjectMeta> {
+        self.retry(move || self.inner.head(location)).await
+    }
+
+    async fn delete(&self, location: &Path) -> ObjectStoreResult<()> {
+        let attempts = AtomicUsize::new(0);
+        let result = self
+            .retry(|| {
+                attempts.fetch_add(1, Ordering::Relaxed);
+                self.inner.delete(location)
+            })
+            .await;
+        match result {
+            // an earlier attempt which timed out deleted the object
+            Err(ObjectStoreError::NotFound { .. }) if attempts.load(Ordering::Relaxed) > 1 => {
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, ObjectStoreResult<ObjectMeta>> {
+        self.inner.list(prefix)
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'_, ObjectStoreResult<ObjectMeta>> {
+        self.inner.list_with_offset(prefix, offset)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> ObjectStoreResult<ListResult> {
+        self.retry(move || self.inner.list_with_delimiter(prefix))
+            .await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.retry(move || self.inner.copy(from, to)).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.with_timeout(self.inner.copy_if_not_exists(from, to))
+            .await
+    }
+
+    async fn rename_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.with_timeout(self.inner.rename_if_not_exists(from, to))
+            .await
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> ObjectStoreResult<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        self.inner.put_multipart(location).await
+    }
+
+    async fn abort_multipart(
+        &self,
+        location: &Path,
+        multipart_id: &MultipartId,
+    ) -> ObjectStoreResult<()> {
+        self.inner.abort_multipart(location, multipart_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    /// Store failing or hanging on the first requests
+    #[derive(Debug)]
+    struct FlakyStore {
+        inner: InMemory,
+        failures: AtomicUsize,
+        hang: bool,
+    }
+
+    impl std::fmt::Display for FlakyStore {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "FlakyStore")
+        }
+    }
+
+    impl FlakyStore {
+        async fn fail(&self) -> ObjectStoreResult<()> {
+            let remaining = self.failures.load(Ordering::SeqCst);
+            if remaining == 0 {
+                return Ok(());
+            }
+            self.failures.store(remaining - 1, Ordering::SeqCst);
+            if self.hang {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            }
+            Err(ObjectStoreError::Generic {
+                store: "FlakyStore",
+                source: "SlowDown".into(),
+            })
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ObjectStore for FlakyStore {
+        async fn put_opts(
+            &self,
+            location: &Path,
+            bytes: Bytes,
+            options: PutOptions,
+        ) -> ObjectStoreResult<PutResult> {
+            self.fail().await?;
+            self.inner.put_opts(location, bytes, options).await
+        }
+
+        async fn get_opts(
+            &self,
+            location: &Path,
+            options: GetOptions,
+        ) -> ObjectStoreResult<GetResult> {
+            self.fail().await?;
+            self.inner.get_opts(location, options).await
+        }
+
+        async fn delete(&self, location: &Path) -> ObjectStoreResult<()> {
+            self.inner.delete(location).await
+        }
+
+        fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, ObjectStoreResult<ObjectMeta>> {
+            self.inner.list(prefix)
+        }
+
+        async fn list_with_delimiter(
+            &self,
+            prefix: Option<&Path>,
+        ) -> ObjectStoreResult<ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+
+        async fn copy(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+            self.inner.copy(from, to).await
+        }
+
+        async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+            self.inner.copy_if_not_exists(from, to).await
+        }
+
+        async fn put_multipart(
+            &self,
+            location: &Path,
+        ) -> ObjectStoreResult<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+            self.inner.put_multipart(location).await
+        }
+
+        async fn abort_multipart(
+            &self,
+            location: &Path,
+            multipart_id: &MultipartId,
+        ) -> ObjectStoreResult<()> {
+            self.inner.abort_multipart(location, multipart_id).await
+        }
+    }
+
+    fn config(max_retries: usize, timeout: Option<Duration>) -> StorageRetryConfig {
+        StorageRetryConfig {
+            max_retries,
+            backoff: StorageBackoff {
+                init_backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(5),
+                base: 2.,
+            },
+            timeout,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_transient_errors() {
+        let flaky = Arc::new(FlakyStore {
+            inner: InMemory::new(),
+            failures: AtomicUsize::new(2),
+            hang: false,
+        });
+        let location = Path::from("data.parquet");
+        let store = RetryObjectStore::new(flaky.clone(), config(3, None));
+        store
+            .put(&location, Bytes::from_static(b"data"))
+            .await
+            .unwrap();
+        assert_eq!(store.retried_requests(), 2);
+
+        // conditional puts are not retried
+        flaky.failures.store(1, Ordering::SeqCst);
+        let create = PutOptions {
+            mode: PutMode::Create,
+            ..Default::default()
+        };
+        let err = store
+            .put_opts(&location, Bytes::from_static(b"data"), create)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ObjectStoreError::Generic { .. }));
+        assert_eq!(store.retried_requests(), 2);
+
+        // requests fail once the retries are exhausted
+        flaky.failures.store(4, Ordering::SeqCst);
+        assert!(store.head(&location).await.is_err());
+        assert_eq!(store.retried_requests(), 5);
+        let bytes = store.get_range(&location, 0..2).await.unwrap();
+        assert_eq!(bytes.as_ref(), b"da");
+
+        // definite answers are not retried
+        let missing = store.head(&Path::from("missing")).await.unwrap_err();
+        assert!(matches!(missing, ObjectStoreError::NotFound { .. }));
+        assert_eq!(store.retried_requests(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_retry_timeout() {
+        let flaky = Arc::new(FlakyStore {
+            inner: InMemory::new(),
+            failures: AtomicUsize::new(0),
+            hang: true,
+        });
+        let location = Path::from("data.parquet");
+        flaky
+            .inner
+            .put(&location, Bytes::from_static(b"data"))
+            .await
+            .unwrap();
+        flaky.failures.store(1, Ordering::SeqCst);
+
+        let store = RetryObjectStore::new(flaky, config(1, Some(Duration::from_millis(20))));
+        let bytes = tokio::time::timeout(Duration::from_secs(10), store.get_range(&location, 0..2))
+            .await
+            .expect("the hung request should time out")
+            .unwrap();
+        assert_eq!(bytes.as_ref(), b"da");
+        assert_eq!(store.retried_requests(), 1);
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        let backoff = StorageBackoff {
+            init_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(300),
+            base: 2.,
+        };
+        for (retry, max) in [(1, 100), (2, 200), (3, 300), (10, 300)] {
+            let delay = backoff.delay(retry);
+            assert!(delay >= Duration::from_millis(max / 2), "{delay:?}");
+            assert!(delay <= Duration::from_millis(max), "{delay:?}");
+        }
+    }
+
+    #[test]
+    fn test_retry_config_from_options() {
+        let options = StorageOptions(HashMap::new());
+        assert_eq!(StorageRetryConfig::from_options(&options).unwrap(), None);
+
+        let options = StorageOptions(HashMap::from([
+            (STORAGE_MAX_RETRIES_KEY.to_string(), "5".to_string()),
+            (STORAGE_REQUEST_TIMEOUT_KEY.to_string(), "30000".to_string()),
+        ]));
+        let config = StorageRetryConfig::from_options(&options).unwrap().unwrap();
+        assert_eq!(config.max_retries, 5);
+        assert_eq!(config.timeout, Some(Duration::from_secs(30)));
+        assert_eq!(config.backoff, StorageBackoff::default());
+
+        let options = StorageOptions(HashMap::from([(
+            STORAGE_BACKOFF_MAX_KEY.to_string(),
+            "soon".to_string(),
+        )]));
+        assert!(StorageRetryConfig::from_options(&options).is_err());
+    }
+}
@@ -3,7 +3,7 @@
 use chrono::{NaiveDateTime, TimeZone, Utc};
 use futures::TryStreamExt;
 use object_store::path::Path;
-use object_store::{DynObjectStore, ObjectMeta, Result as ObjectStoreResult};
+use object_store::{DynObjectStore, GetOptions, ObjectMeta, Result as ObjectStoreResult};
 
 use crate::errors::{DeltaResult, DeltaTableError};
 use crate::kernel::Add;
@@ -41,8 +41,10 @@ impl TryFrom<&Add> for ObjectMeta {
             )?,
         );
         Ok(Self {
-            // TODO this won't work for absolute paths, since Paths are always relative to store.
-            location: Path::parse(value.path.as_str())?,
+            location: match super::external::external_file_path(&value.path) {
+                Some(location) => location,
+                None => Path::parse(value.path.as_str())?,
+            },
             last_modified,
             size: value.size as usize,
             e_tag: None,
@@ -51,6 +53,19 @@ impl TryFrom<&Add> for ObjectMeta {
     }
 }
 
+/// Copy of `options`, for issuing the same `get` request more than once
+pub(crate) fn copy_get_options(options: &GetOptions) -> GetOptions {
+    GetOptions {
+        if_match: options.if_match.clone(),
+        if_none_match: options.if_none_match.clone(),
+        if_modified_since: options.if_modified_since,
+        if_unmodified_since: options.if_unmodified_since,
+        range: options.range.clone(),
+        version: options.version.clone(),
+        head: options.head,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -84,4 +99,28 @@ mod tests {
         assert_eq!(meta.size, 123);
         assert_eq!(meta.last_modified.timestamp_millis(), 123456789);
     }
+
+    #[test]
+    fn test_object_meta_from_absolute_add_action() {
+        let add = Add {
+            path: "s3://other-bucket/table/part-00000.parquet".to_string(),
+            size: 123,
+            modification_time: 123456789,
+            data_change: true,
+            stats: None,
+            partition_values: Default::default(),
+            tags: Default::default(),
+            base_row_id: None,
+            default_row_commit_version: None,
+            deletion_vector: None,
+            stats_parsed: None,
+            clustering_provider: None,
+        };
+
+        let meta: ObjectMeta = (&add).try_into().unwrap();
+        assert_eq!(
+            meta.location,
+            Path::from("_delta_external/s3/other-bucket/table/part-00000.parquet")
+        );
+    }
 }
@@ -10,9 +10,12 @@ use serde::{Deserialize, Serialize};
 use tracing::debug;
 use url::Url;
 
+use super::log_validation::UnknownActionPolicy;
 use super::DeltaTable;
 use crate::errors::{DeltaResult, DeltaTableError};
 use crate::logstore::LogStoreRef;
+use crate::operations::transaction::{verify_signatures, CommitVerifier};
+use crate::storage::external::CredentialResolver;
 use crate::storage::{factories, StorageOptions};
 
 #[allow(dead_code)]
@@ -79,6 +82,23 @@ pub struct DeltaTableConfig {
     /// Control the number of records to read / process from the commit / checkpoint files
     /// when processing record batches.
     pub log_batch_size: usize,
+    /// Local directory used to cache resolved snapshots of the table.
+    ///
+    /// When set, loading the table starts from the most recent cached snapshot and only
+    /// replays commits written after it. The cache is refreshed after every load.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot_cache_dir: Option<PathBuf>,
+    /// Reject all commits to the table.
+    /// This defaults to `false`
+    ///
+    /// Services that only need scan access can open tables read-only to guard against
+    /// accidental writes.
+    #[serde(default)]
+    pub read_only: bool,
+    /// Handling of actions and fields of the log not defined by the protocol.
+    /// This defaults to [`UnknownActionPolicy::Ignore`]
+    #[serde(default)]
+    pub unknown_action_policy: UnknownActionPolicy,
 }
 
 impl Default for DeltaTableConfig {
@@ -88,6 +108,9 @@ impl Default for DeltaTableConfig {
             require_files: true,
             log_buffer_size: num_cpus::get() * 4,
             log_batch_size: 1024,
+            snapshot_cache_dir: None,
+            read_only: false,
+            unknown_action_policy: UnknownActionPolicy::default(),
         }
     }
 }
@@ -126,6 +149,12 @@ pub struct DeltaTableLoadOptions {
     /// Control the number of records to read / process from the commit / checkpoint files
     /// when processing record batches.
     pub log_batch_size: usize,
+    /// Local directory used to cache resolved snapshots of the table
+    pub snapshot_cache_dir: Option<PathBuf>,
+    /// Reject all commits to the table
+    pub read_only: bool,
+    /// Handling of actions and fields of the log not defined by the protocol
+    pub unknown_action_policy: UnknownActionPolicy,
 }
 
 impl DeltaTableLoadOptions {
@@ -139,6 +168,9 @@ impl DeltaTableLoadOptions {
             log_buffer_size: num_cpus::get() * 4,
             version: DeltaVersion::default(),
             log_batch_size: 1024,
+            snapshot_cache_dir: None,
+            read_only: false,
+            unknown_action_policy: UnknownActionPolicy::default(),
         }
     }
 }
@@ -150,6 +182,9 @@ pub struct DeltaTableBuilder {
     storage_options: Option<HashMap<String, String>>,
     #[allow(unused_variables)]
     allow_http: Option<bool>,
+    commit_verifier: Option<Arc<dyn CommitVerifier>>,
+    credential_resolver: Option<Arc<dyn CredentialResolver>>,
+    strict_validation: bool,
 }
 
 impl DeltaTableBuilder {
@@ -193,6 +228,9 @@ impl DeltaTableBuilder {
             options: DeltaTableLoadOptions::new(url),
             storage_options: None,
             allow_http: None,
+            commit_verifier: None,
+            credential_resolver: None,
+            strict_validation: false,
         })
     }
 
@@ -203,11 +241,71 @@ impl DeltaTableBuilder {
     }
 
     /// Sets `require_files=false` to the builder
+    ///
+    /// The files of the table aren't kept in memory, and operations requiring the file list of
+    /// the loaded state fail with [`DeltaTableError::NotInitializedWithFiles`]. The files can
+    /// still be streamed from the log with
+    /// [`DeltaTableState::files_iter`](crate::table::state::DeltaTableState::files_iter).
     pub fn without_files(mut self) -> Self {
         self.options.require_files = false;
         self
     }
 
+    /// Open the table in read-only mode.
+    ///
+    /// Any operation attempting to commit to the table fails with
+    /// [`TransactionError::ReadOnlyTable`](crate::operations::transaction::TransactionError::ReadOnlyTable).
+    /// Storage of the table is accessed through a
+    /// [`ReadOnlyObjectStore`](crate::storage::read_only::ReadOnlyObjectStore), so that
+    /// operations deleting files without a commit fail as well.
+    pub fn read_only(mut self) -> Self {
+        self.options.read_only = true;
+        self
+    }
+
+    /// Verify the signatures of all commits replayed while loading the table.
+    ///
+    /// Loading fails if any of these commits is unsigned or its signature is invalid.
+    /// Commits are signed by a [`CommitSigner`](crate::operations::transaction::CommitSigner).
+    pub fn with_commit_verifier(mut self, verifier: Arc<dyn CommitVerifier>) -> Self {
+        self.commit_verifier = Some(verifier);
+        self
+    }
+
+    /// Resolve the storage options, e.g. credentials, of data files referenced by absolute URIs
+    /// in other buckets than the table.
+    ///
+    /// Without a resolver these buckets are accessed with the storage options of the table.
+    pub fn with_credential_resolver(mut self, resolver: Arc<dyn CredentialResolver>) -> Self {
+        self.credential_resolver = Some(resolver);
+        self
+    }
+
+    /// Validate every action of the commits replayed while loading the table against the JSON
+    /// schema of the protocol.
+    ///
+    /// Loading fails with [`DeltaTableError::InvalidLogSchema`] carrying a report of all
+    /// violations, such as unknown fields or wrong types, rather than ignoring them.
+    pub fn with_strict_validation(mut self) -> Self {
+        self.strict_validation = true;
+        self
+    }
+
+    /// Set how actions and fields of the log not defined by the protocol are handled while
+    /// replaying the log and writing checkpoints, see [`UnknownActionPolicy`].
+    pub fn with_unknown_action_policy(mut self, policy: UnknownActionPolicy) -> Self {
+        self.options.unknown_action_policy = policy;
+        self
+    }
+
+    /// Cache resolved snapshots of the table in the given local directory.
+    ///
+    /// Subsequent loads of the table only replay commits newer than the cached snapshot.
+    pub fn with_snapshot_cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.options.snapshot_cache_dir = Some(dir.into());
+        self
+    }
+
     /// Sets `version` to the builder
     pub fn with_version(mut self, version: i64) -> Self {
         self.options.version = DeltaVersion::Version(version);
@@ -233,9 +331,9 @@ impl DeltaTableBuilder {
         Ok(self.with_timestamp(datetime))
     }
 
-    /// specify a timestamp
-    pub fn with_timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
-        self.options.version = DeltaVersion::Timestamp(timestamp);
+    /// specify a timestamp, either as [`chrono::DateTime`] or [`std::time::SystemTime`]
+    pub fn with_timestamp(mut self, timestamp: impl Into<DateTime<Utc>>) -> Self {
+        self.options.version = DeltaVersion::Timestamp(timestamp.into());
         self
     }
 
@@ -295,14 +393,26 @@ impl DeltaTableBuilder {
             ))
         })?;
 
-        if let Some((store, _url)) = self.options.storage_backend.as_ref() {
+        let storage_options = self.storage_options();
+        let store = if let Some((store, _url)) = self.options.storage_backend.as_ref() {
             debug!("Loading a logstore with a custom store: {store:?}");
-            crate::logstore::logstore_with(store.clone(), location, self.storage_options())
+            store.clone()
         } else {
             // If there has been no backend defined just default to the normal logstore look up
             debug!("Loading a logstore based off the location: {location:?}");
-            crate::logstore::logstore_for(location, self.storage_options())
-        }
+            crate::logstore::store_for_location(&location, &storage_options)?
+        };
+        let store = if self.options.read_only {
+            crate::storage::read_only::read_only_store(store)
+        } else {
+            store
+        };
+        crate::logstore::logstore_with_resolver(
+            store,
+            location,
+            storage_options,
+            self.credential_resolver,
+        )
     }
 
     /// Build the [`DeltaTable`] from specified options.
@@ -315,6 +425,9 @@ impl DeltaTableBuilder {
             require_files: self.options.require_files,
             log_buffer_size: self.options.log_buffer_size,
             log_batch_size: self.options.log_batch_size,
+            snapshot_cache_dir: self.options.snapshot_cache_dir.clone(),
+            read_only: self.options.read_only,
+            unknown_action_policy: self.options.unknown_action_policy,
         };
         Ok(DeltaTable::new(self.build_storage()?, config))
     }
@@ -322,12 +435,26 @@ impl DeltaTableBuilder {
     /// Build the [`DeltaTable`] and load its state
     pub async fn load(self) -> DeltaResult<DeltaTable> {
         let version = self.options.version.clone();
+        let verifier = self.commit_verifier.clone();
+        let strict_validation = self.strict_validation;
         let mut table = self.build()?;
         match version {
             DeltaVersion::Newest => table.load().await?,
             DeltaVersion::Version(v) => table.load_version(v).await?,
             DeltaVersion::Timestamp(ts) => table.load_with_datetime(ts).await?,
         }
+        if let Some(verifier) = verifier {
+            let versions = table.snapshot()?.snapshot().snapshot().commit_versions();
+            verify_signatures(&table.log_store(), verifier, versions).await?;
+        }
+        if strict_validation {
+            let report = table.validate_log().await?;
+            if !report.is_valid() {
+                return Err(DeltaTableError::InvalidLogSchema {
+                    report: Box::new(report),
+                });
+            }
+        }
         Ok(table)
     }
 }
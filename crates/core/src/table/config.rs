@@ -191,6 +191,12 @@ pub enum DeltaConfigError {
     Validation(String),
 }
 
+impl From<DeltaConfigError> for DeltaTableError {
+    fn from(err: DeltaConfigError) -> Self {
+        DeltaTableError::MetadataError(err.to_string())
+    }
+}
+
 macro_rules! table_config {
     ($(($docs:literal, $key:expr, $name:ident, $ret:ty, $default:literal),)*) => {
         $(
@@ -276,6 +282,20 @@ impl<'a> TableConfig<'a> {
             i32,
             10
         ),
+        (
+            "true for Delta Lake to write files under random prefixes instead of partition directories",
+            DeltaConfigKey::RandomizeFilePrefixes,
+            randomize_file_prefixes,
+            bool,
+            false
+        ),
+        (
+            "The number of characters of the random prefixes of files",
+            DeltaConfigKey::RandomPrefixLength,
+            random_prefix_length,
+            i32,
+            2
+        ),
     );
 
     /// The shortest duration for Delta Lake to keep logically deleted data files before deleting
@@ -353,6 +373,25 @@ impl<'a> TableConfig<'a> {
             .collect()
     }
 
+    /// Check that all well known configuration values are valid.
+    ///
+    /// The accessors of [`TableConfig`] fall back to the default of a property when its value
+    /// is invalid. Validating the configuration first surfaces such values instead.
+    pub fn validate(&self) -> Result<(), DeltaConfigError> {
+        for (key, value) in self.0 {
+            let (Ok(key), Some(value)) = (key.parse::<DeltaConfigKey>(), value) else {
+                continue;
+            };
+            validate_value(&key, value).map_err(|msg| {
+                DeltaConfigError::Validation(format!(
+                    "invalid value '{value}' for {}: {msg}",
+                    key.as_ref()
+                ))
+            })?;
+        }
+        Ok(())
+    }
+
     /// Column names on which Delta Lake collects statistics to enhance data skipping functionality.
     /// This property takes precedence over [num_indexed_cols](Self::num_indexed_cols).
     pub fn stats_columns(&self) -> Option<Vec<&str>> {
@@ -503,12 +542,73 @@ impl FromStr for ColumnMappingMode {
     }
 }
 
+/// Check a single value of a well known property, returning what was expected otherwise
+fn validate_value(key: &DeltaConfigKey, value: &str) -> Result<(), String> {
+    fn parses<T: FromStr>(value: &str, expected: &str) -> Result<(), String> {
+        value
+            .parse::<T>()
+            .map(|_| ())
+            .map_err(|_| expected.to_string())
+    }
+
+    match key {
+        DeltaConfigKey::AppendOnly
+        | DeltaConfigKey::AutoOptimizeAutoCompact
+        | DeltaConfigKey::AutoOptimizeOptimizeWrite
+        | DeltaConfigKey::CheckpointWriteStatsAsJson
+        | DeltaConfigKey::CheckpointWriteStatsAsStruct
+        | DeltaConfigKey::EnableChangeDataFeed
+        | DeltaConfigKey::EnableDeletionVectors
+        | DeltaConfigKey::EnableExpiredLogCleanup
+        | DeltaConfigKey::RandomizeFilePrefixes
+        | DeltaConfigKey::TuneFileSizesForRewrites => parses::<bool>(value, "expected a boolean"),
+        DeltaConfigKey::CheckpointInterval => match value.parse::<i32>() {
+            Ok(interval) if interval > 0 => Ok(()),
+            _ => Err("expected a positive integer".to_string()),
+        },
+        DeltaConfigKey::DataSkippingNumIndexedCols => match value.parse::<i32>() {
+            Ok(cols) if cols >= -1 => Ok(()),
+            _ => Err("expected -1 or a non-negative integer".to_string()),
+        },
+        DeltaConfigKey::TargetFileSize => match value.parse::<i64>() {
+            Ok(size) if size > 0 => Ok(()),
+            _ => Err("expected a positive number of bytes".to_string()),
+        },
+        DeltaConfigKey::RandomPrefixLength => parses::<u32>(value, "expected a length"),
+        DeltaConfigKey::DataSkippingStatsColumns => {
+            if value.split(',').any(|column| column.trim().is_empty()) {
+                Err("expected a comma separated list of column names".to_string())
+            } else {
+                Ok(())
+            }
+        }
+        DeltaConfigKey::DeletedFileRetentionDuration
+        | DeltaConfigKey::LogRetentionDuration
+        | DeltaConfigKey::SetTransactionRetentionDuration => parse_interval(value)
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+        DeltaConfigKey::IsolationLevel => parses::<IsolationLevel>(
+            value,
+            "expected one of Serializable, WriteSerializable or SnapshotIsolation",
+        ),
+        DeltaConfigKey::CheckpointPolicy => {
+            parses::<CheckpointPolicy>(value, "expected classic or v2")
+        }
+        DeltaConfigKey::ColumnMappingMode => {
+            parses::<ColumnMappingMode>(value, "expected none, id or name")
+        }
+        DeltaConfigKey::MinReaderVersion | DeltaConfigKey::MinWriterVersion => {
+            parses::<i32>(value, "expected a protocol version")
+        }
+    }
+}
+
 const SECONDS_PER_MINUTE: u64 = 60;
 const SECONDS_PER_HOUR: u64 = 60 * SECONDS_PER_MINUTE;
 const SECONDS_PER_DAY: u64 = 24 * SECONDS_PER_HOUR;
 const SECONDS_PER_WEEK: u64 = 7 * SECONDS_PER_DAY;
 
-fn parse_interval(value: &str) -> Result<Duration, DeltaConfigError> {
+pub(crate) fn parse_interval(value: &str) -> Result<Duration, DeltaConfigError> {
     let not_an_interval = || DeltaConfigError::Validation(format!("'{value}' is not an interval"));
 
     if !value.starts_with("interval ") {
@@ -594,6 +694,40 @@ mod tests {
         assert_eq!(config.checkpoint_interval(), 10,)
     }
 
+    #[test]
+    fn validate_config_test() {
+        let mut md = dummy_metadata();
+        for (key, value) in [
+            (DeltaConfigKey::AppendOnly, "true"),
+            (DeltaConfigKey::CheckpointInterval, "100"),
+            (DeltaConfigKey::DataSkippingNumIndexedCols, "-1"),
+            (DeltaConfigKey::DataSkippingStatsColumns, "id,value"),
+            (DeltaConfigKey::LogRetentionDuration, "interval 30 days"),
+            (DeltaConfigKey::ColumnMappingMode, "name"),
+        ] {
+            md.configuration
+                .insert(key.as_ref().to_string(), Some(value.to_string()));
+        }
+        md.configuration
+            .insert("custom.property".to_string(), Some("anything".to_string()));
+        assert!(TableConfig(&md.configuration).validate().is_ok());
+
+        for (key, value) in [
+            (DeltaConfigKey::AppendOnly, "yes"),
+            (DeltaConfigKey::CheckpointInterval, "0"),
+            (DeltaConfigKey::DataSkippingNumIndexedCols, "-2"),
+            (DeltaConfigKey::DataSkippingStatsColumns, "id,,value"),
+            (DeltaConfigKey::LogRetentionDuration, "30 days"),
+            (DeltaConfigKey::ColumnMappingMode, "position"),
+        ] {
+            let mut md = dummy_metadata();
+            md.configuration
+                .insert(key.as_ref().to_string(), Some(value.to_string()));
+            let err = TableConfig(&md.configuration).validate().unwrap_err();
+            assert!(err.to_string().contains(key.as_ref()), "{err}");
+        }
+    }
+
     #[test]
     fn get_boolean_from_metadata_test() {
         let md = dummy_metadata();
@@ -0,0 +1,363 @@
+//! Table property conventions for parquet modular encryption
+//!
+//! Encrypted tables declare the keys protecting their data files in the table configuration,
+//! identifying keys by id so the key material itself never enters the log:
+//!
+//! - `encryption.footer.keyId`: key encrypting the parquet footers, required for encrypted tables
+//! - `encryption.column.<column>.keyId`: key encrypting the column `<column>`, where nested
+//!   columns are referenced by their dot separated path
+//! - `encryption.algorithm`: either `AES_GCM_V1` (the default) or `AES_GCM_CTR_V1`
+//!
+//! Key ids are resolved into key material by a [`KeyResolver`], typically backed by a KMS.
+//!
+//! The parquet version used by this crate does not implement modular encryption yet. Writers
+//! therefore refuse to write to tables declaring encryption keys rather than writing their data
+//! in plain text, see [`check_can_write`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+
+use futures::future::try_join_all;
+
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::kernel::Metadata;
+
+/// Prefix of all encryption table properties
+pub const ENCRYPTION_PROPERTY_PREFIX: &str = "encryption.";
+/// Table property holding the id of the footer key
+pub const FOOTER_KEY_ID_PROPERTY: &str = "encryption.footer.keyId";
+/// Table property holding the encryption algorithm
+pub const ALGORITHM_PROPERTY: &str = "encryption.algorithm";
+
+const COLUMN_KEY_PREFIX: &str = "encryption.column.";
+const KEY_ID_SUFFIX: &str = ".keyId";
+
+/// Errors related to table encryption
+#[derive(thiserror::Error, Debug)]
+enum EncryptionError {
+    #[error("Invalid encryption property {key}: {message}")]
+    InvalidProperty { key: String, message: String },
+
+    #[error("Table declares encryption properties but no footer key in {FOOTER_KEY_ID_PROPERTY}")]
+    MissingFooterKey,
+
+    #[error("Encryption key {key_id} has {length} bytes, expected 16, 24 or 32")]
+    InvalidKeyLength { key_id: String, length: usize },
+
+    #[error("Writing encrypted tables requires parquet modular encryption, which is not supported by this version of delta-rs")]
+    Unsupported,
+}
+
+impl From<EncryptionError> for DeltaTableError {
+    fn from(err: EncryptionError) -> Self {
+        DeltaTableError::GenericError {
+            source: Box::new(err),
+        }
+    }
+}
+
+/// Parquet modular encryption algorithms
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EncryptionAlgorithm {
+    /// AES GCM for all modules
+    #[default]
+    AesGcmV1,
+    /// AES GCM for metadata and AES CTR for data pages
+    AesGcmCtrV1,
+}
+
+impl AsRef<str> for EncryptionAlgorithm {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::AesGcmV1 => "AES_GCM_V1",
+            Self::AesGcmCtrV1 => "AES_GCM_CTR_V1",
+        }
+    }
+}
+
+impl std::str::FromStr for EncryptionAlgorithm {
+    type Err = DeltaTableError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "AES_GCM_V1" => Ok(Self::AesGcmV1),
+            "AES_GCM_CTR_V1" => Ok(Self::AesGcmCtrV1),
+            _ => Err(EncryptionError::InvalidProperty {
+                key: ALGORITHM_PROPERTY.to_string(),
+                message: format!("unknown algorithm {s}"),
+            }
+            .into()),
+        }
+    }
+}
+
+/// Resolves key ids declared in the table properties into key material
+#[async_trait::async_trait]
+pub trait KeyResolver: Debug + Send + Sync {
+    /// The key identified by `key_id`
+    async fn resolve_key(&self, key_id: &str) -> DeltaResult<Vec<u8>>;
+}
+
+/// Encryption settings of a table, as declared in its properties
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TableEncryption {
+    /// Id of the key encrypting the parquet footers
+    pub footer_key_id: String,
+    /// Ids of the keys encrypting individual columns, keyed by column path
+    pub column_key_ids: BTreeMap<String, String>,
+    /// The encryption algorithm
+    pub algorithm: EncryptionAlgorithm,
+}
+
+/// Key material resolved for a [`TableEncryption`]
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionKeys {
+    /// Key encrypting the parquet footers
+    pub footer_key: Vec<u8>,
+    /// Keys encrypting individual columns, keyed by column path
+    pub column_keys: BTreeMap<String, Vec<u8>>,
+}
+
+impl Debug for EncryptionKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // never print key material
+        f.debug_struct("EncryptionKeys")
+            .field("column_keys", &self.column_keys.keys())
+            .finish_non_exhaustive()
+    }
+}
+
+impl TableEncryption {
+    /// Create encryption settings with the given footer key
+    pub fn new(footer_key_id: impl Into<String>) -> Self {
+        Self {
+            footer_key_id: footer_key_id.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Encrypt `column` with the key `key_id`
+    pub fn with_column_key(mut self, column: impl Into<String>, key_id: impl Into<String>) -> Self {
+        self.column_key_ids.insert(column.into(), key_id.into());
+        self
+    }
+
+    /// Use the encryption algorithm `algorithm`
+    pub fn with_algorithm(mut self, algorithm: EncryptionAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Parse the encryption settings from a table configuration.
+    ///
+    /// Returns `None` if the table does not declare any encryption properties.
+    pub fn try_from_configuration(
+        configuration: &HashMap<String, Option<String>>,
+    ) -> DeltaResult<Option<Self>> {
+        let mut footer_key_id = None;
+        let mut column_key_ids = BTreeMap::new();
+        let mut algorithm = EncryptionAlgorithm::default();
+        let mut declared = false;
+        for (key, value) in configuration {
+            if !key.starts_with(ENCRYPTION_PROPERTY_PREFIX) {
+                continue;
+            }
+            declared = true;
+            let value = value
+                .as_deref()
+                .filter(|value| !value.is_empty())
+                .ok_or_else(|| EncryptionError::InvalidProperty {
+                    key: key.clone(),
+                    message: "value must not be empty".to_string(),
+                })?;
+            if key == FOOTER_KEY_ID_PROPERTY {
+                footer_key_id = Some(value.to_string());
+            } else if key == ALGORITHM_PROPERTY {
+                algorithm = value.parse()?;
+            } else if let Some(column) = key
+                .strip_prefix(COLUMN_KEY_PREFIX)
+                .and_then(|key| key.strip_suffix(KEY_ID_SUFFIX))
+                .filter(|column| !column.is_empty())
+            {
+                column_key_ids.insert(column.to_string(), value.to_string());
+            } else {
+                return Err(EncryptionError::InvalidProperty {
+                    key: key.clone(),
+                    message: "unknown encryption property".to_string(),
+                }
+                .into());
+            }
+        }
+        if !declared {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            footer_key_id: footer_key_id.ok_or(EncryptionError::MissingFooterKey)?,
+            column_key_ids,
+            algorithm,
+        }))
+    }
+
+    /// Parse the encryption settings from the table metadata
+    pub fn try_from_metadata(metadata: &Metadata) -> DeltaResult<Option<Self>> {
+        Self::try_from_configuration(&metadata.configuration)
+    }
+
+    /// The table properties declaring these settings
+    pub fn to_configuration(&self) -> HashMap<String, Option<String>> {
+        let mut configuration = HashMap::from([
+            (
+                FOOTER_KEY_ID_PROPERTY.to_string(),
+                Some(self.footer_key_id.clone()),
+            ),
+            (
+                ALGORITHM_PROPERTY.to_string(),
+                Some(self.algorithm.as_ref().to_string()),
+            ),
+        ]);
+        configuration.extend(self.column_key_ids.iter().map(|(column, key_id)| {
+            (
+                format!("{COLUMN_KEY_PREFIX}{column}{KEY_ID_SUFFIX}"),
+                Some(key_id.clone()),
+            )
+        }));
+        configuration
+    }
+
+    /// Id of the key encrypting `column`, or `None` if the column is stored in plain text
+    pub fn column_key_id(&self, column: &str) -> Option<&str> {
+        self.column_key_ids.get(column).map(String::as_str)
+    }
+
+    /// Resolve all keys of the table using `resolver`
+    pub async fn resolve_keys(&self, resolver: &dyn KeyResolver) -> DeltaResult<EncryptionKeys> {
+        let resolve = |key_id: &str| {
+            let key_id = key_id.to_string();
+            async move {
+                let key = resolver.resolve_key(&key_id).await?;
+                if ![16, 24, 32].contains(&key.len()) {
+                    return Err(EncryptionError::InvalidKeyLength {
+                        key_id,
+                        length: key.len(),
+                    }
+                    .into());
+                }
+                Ok::<_, DeltaTableError>(key)
+            }
+        };
+        let footer_key = resolve(&self.footer_key_id).await?;
+        let column_keys = try_join_all(self.column_key_ids.iter().map(|(column, key_id)| {
+            let resolved = resolve(key_id);
+            async move { Ok::<_, DeltaTableError>((column.clone(), resolved.await?)) }
+        }))
+        .await?
+        .into_iter()
+        .collect();
+        Ok(EncryptionKeys {
+            footer_key,
+            column_keys,
+        })
+    }
+}
+
+/// Check that data files can be written for a table with `configuration`.
+///
+/// Fails for tables declaring encryption keys, so their data is never written in plain text.
+pub fn check_can_write(configuration: &HashMap<String, Option<String>>) -> DeltaResult<()> {
+    match TableEncryption::try_from_configuration(configuration)? {
+        Some(_) => Err(EncryptionError::Unsupported.into()),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct StaticResolver(HashMap<String, Vec<u8>>);
+
+    #[async_trait::async_trait]
+    impl KeyResolver for StaticResolver {
+        async fn resolve_key(&self, key_id: &str) -> DeltaResult<Vec<u8>> {
+            self.0
+                .get(key_id)
+                .cloned()
+                .ok_or_else(|| DeltaTableError::Generic(format!("unknown key {key_id}")))
+        }
+    }
+
+    #[test]
+    fn test_configuration_roundtrip() {
+        let encryption = TableEncryption::new("footer")
+            .with_column_key("ssn", "pii")
+            .with_column_key("address.street", "pii")
+            .with_algorithm(EncryptionAlgorithm::AesGcmCtrV1);
+        let mut configuration = encryption.to_configuration();
+        assert_eq!(
+            configuration.get("encryption.column.address.street.keyId"),
+            Some(&Some("pii".to_string()))
+        );
+        configuration.insert("delta.appendOnly".to_string(), Some("true".to_string()));
+
+        let parsed = TableEncryption::try_from_configuration(&configuration)
+            .unwrap()
+            .unwrap();
+        assert_eq!(parsed, encryption);
+        assert_eq!(parsed.column_key_id("address.street"), Some("pii"));
+        assert_eq!(parsed.column_key_id("name"), None);
+        assert!(check_can_write(&configuration).is_err());
+
+        assert!(TableEncryption::try_from_configuration(&HashMap::new())
+            .unwrap()
+            .is_none());
+        assert!(check_can_write(&HashMap::new()).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_configuration() {
+        let invalid = [
+            vec![("encryption.column.ssn.keyId", "pii")],
+            vec![("encryption.footer.keyId", "")],
+            vec![
+                ("encryption.footer.keyId", "footer"),
+                ("encryption.other", "x"),
+            ],
+            vec![
+                ("encryption.footer.keyId", "footer"),
+                ("encryption.algorithm", "ROT13"),
+            ],
+        ];
+        for properties in invalid {
+            let configuration = properties
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), Some(value.to_string())))
+                .collect();
+            assert!(TableEncryption::try_from_configuration(&configuration).is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_keys() {
+        let resolver = StaticResolver(HashMap::from([
+            ("footer".to_string(), vec![1; 16]),
+            ("pii".to_string(), vec![2; 32]),
+            ("short".to_string(), vec![3; 8]),
+        ]));
+        let keys = TableEncryption::new("footer")
+            .with_column_key("ssn", "pii")
+            .resolve_keys(&resolver)
+            .await
+            .unwrap();
+        assert_eq!(keys.footer_key, vec![1; 16]);
+        assert_eq!(keys.column_keys.get("ssn"), Some(&vec![2; 32]));
+        assert!(!format!("{keys:?}").contains("[1, 1"));
+
+        for encryption in [
+            TableEncryption::new("footer").with_column_key("ssn", "missing"),
+            TableEncryption::new("short"),
+        ] {
+            assert!(encryption.resolve_keys(&resolver).await.is_err());
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Tags of data files.
+//!
+//! Add actions carry a free-form `tags` map, which writers use to record the provenance of a
+//! file inside the log, e.g. the ingestion source or the target size of an optimize run. Tags
+//! are written with [`WriteBuilder::with_file_tags`](crate::operations::write::WriteBuilder::with_file_tags)
+//! and files are selected by their tags with [`FileTagFilter`]s, both when listing files with
+//! [`DeltaTable::get_files_by_tags`](crate::DeltaTable::get_files_by_tags) and when scanning
+//! the table through DataFusion.
+//!
+//! # Example
+//! ```rust ignore
+//! let (table, _) = DeltaOps(table)
+//!     .write(batches)
+//!     .with_file_tags([("ingestion.source", "kafka")])
+//!     .await?;
+//! let files = table.get_files_by_tags(&[FileTagFilter::equals("ingestion.source", "kafka")])?;
+//! ````
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Time the file was inserted, in microseconds since the epoch
+pub const INSERTION_TIME: &str = "INSERTION_TIME";
+/// Target size of the optimize run which wrote the file, in bytes
+pub const OPTIMIZE_TARGET_SIZE: &str = "OPTIMIZE_TARGET_SIZE";
+
+/// Condition on the tags of a data file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FileTagFilter {
+    /// The file has the tag, with any value
+    Exists(String),
+    /// The file does not have the tag
+    Missing(String),
+    /// The file has the tag with the value
+    Equals(String, String),
+    /// The file does not have the tag with the value, files without the tag match
+    NotEquals(String, String),
+}
+
+impl FileTagFilter {
+    /// The file has the tag `key`
+    pub fn exists(key: impl Into<String>) -> Self {
+        Self::Exists(key.into())
+    }
+
+    /// The file does not have the tag `key`
+    pub fn missing(key: impl Into<String>) -> Self {
+        Self::Missing(key.into())
+    }
+
+    /// The file has the tag `key` with `value`
+    pub fn equals(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::Equals(key.into(), value.into())
+    }
+
+    /// The file does not have the tag `key` with `value`
+    pub fn not_equals(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::NotEquals(key.into(), value.into())
+    }
+
+    /// Whether a file with `tags` matches the filter
+    pub fn matches(&self, tags: Option<&HashMap<String, Option<String>>>) -> bool {
+        let tag = |key: &str| tags.and_then(|tags| tags.get(key));
+        match self {
+            Self::Exists(key) => tag(key).is_some(),
+            Self::Missing(key) => tag(key).is_none(),
+            Self::Equals(key, value) => {
+                tag(key).is_some_and(|tag| tag.as_deref() == Some(value.as_str()))
+            }
+            Self::NotEquals(key, value) => {
+                tag(key).map_or(true, |tag| tag.as_deref() != Some(value.as_str()))
+            }
+        }
+    }
+}
+
+/// Whether a file with `tags` matches all `filters`
+pub fn matches_all(
+    filters: &[FileTagFilter],
+    tags: Option<&HashMap<String, Option<String>>>,
+) -> bool {
+    filters.iter().all(|filter| filter.matches(tags))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_tag_filter() {
+        let tags = HashMap::from([
+            ("source".to_string(), Some("kafka".to_string())),
+            ("empty".to_string(), None),
+        ]);
+        let tags = Some(&tags);
+
+        assert!(FileTagFilter::exists("source").matches(tags));
+        assert!(FileTagFilter::exists("empty").matches(tags));
+        assert!(!FileTagFilter::exists("other").matches(tags));
+        assert!(FileTagFilter::missing("other").matches(tags));
+        assert!(FileTagFilter::equals("source", "kafka").matches(tags));
+        assert!(!FileTagFilter::equals("source", "s3").matches(tags));
+        assert!(!FileTagFilter::equals("empty", "").matches(tags));
+        assert!(FileTagFilter::not_equals("source", "s3").matches(tags));
+        assert!(FileTagFilter::not_equals("other", "s3").matches(tags));
+
+        assert!(!FileTagFilter::exists("source").matches(None));
+        assert!(FileTagFilter::not_equals("source", "kafka").matches(None));
+        assert!(matches_all(&[], None));
+        assert!(!matches_all(
+            &[
+                FileTagFilter::exists("source"),
+                FileTagFilter::equals("source", "s3")
+            ],
+            tags
+        ));
+    }
+}
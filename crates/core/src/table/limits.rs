@@ -0,0 +1,181 @@
+//! Concurrency limits for scans and commits on a table.
+//!
+//! Embedded multi-tenant services share a process between many consumers of the same tables.
+//! A [`TableLimiter`] bounds the number of concurrent scans and commit attempts on a table, so
+//! that a single heavy consumer cannot starve the others. Permits are handed out in the order
+//! they were requested. Limiters are cheap to clone and clones share their permits.
+//!
+//! [`DeltaTablePool`](super::pool::DeltaTablePool) keeps one limiter per table, configured with
+//! [`DeltaTablePoolConfig::limits`](super::pool::DeltaTablePoolConfig::limits).
+//!
+//! # Example
+//! ```rust ignore
+//! let limiter = TableLimiter::new(ConcurrencyLimits {
+//!     max_concurrent_scans: Some(4),
+//!     max_concurrent_commits: Some(1),
+//! });
+//! let _permit = limiter.acquire_scan().await?;
+//! let batches = DeltaOps(table).load().await?;
+//! ````
+
+use std::sync::Arc;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::errors::{DeltaResult, DeltaTableError};
+
+/// Maximum number of concurrent operations on a table, unlimited if `None`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConcurrencyLimits {
+    /// Maximum number of concurrent scans
+    pub max_concurrent_scans: Option<usize>,
+    /// Maximum number of concurrent commit attempts
+    pub max_concurrent_commits: Option<usize>,
+}
+
+/// Permission to run a scan or commit, released when dropped
+#[derive(Debug)]
+pub struct TablePermit {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+/// Enforces [`ConcurrencyLimits`] on a table.
+/// See this module's documentation for more information
+#[derive(Debug, Clone)]
+pub struct TableLimiter {
+    limits: ConcurrencyLimits,
+    scans: Option<Arc<Semaphore>>,
+    commits: Option<Arc<Semaphore>>,
+}
+
+impl Default for TableLimiter {
+    fn default() -> Self {
+        Self::new(ConcurrencyLimits::default())
+    }
+}
+
+fn semaphore(limit: Option<usize>) -> Option<Arc<Semaphore>> {
+    limit.map(|limit| Arc::new(Semaphore::new(limit.max(1))))
+}
+
+async fn acquire(semaphore: &Option<Arc<Semaphore>>) -> DeltaResult<TablePermit> {
+    let permit = match semaphore {
+        Some(semaphore) => Some(semaphore.clone().acquire_owned().await.map_err(|err| {
+            DeltaTableError::GenericError {
+                source: Box::new(err),
+            }
+        })?),
+        None => None,
+    };
+    Ok(TablePermit { _permit: permit })
+}
+
+fn try_acquire(semaphore: &Option<Arc<Semaphore>>) -> Option<TablePermit> {
+    match semaphore {
+        Some(semaphore) => semaphore
+            .clone()
+            .try_acquire_owned()
+            .ok()
+            .map(|permit| TablePermit {
+                _permit: Some(permit),
+            }),
+        None => Some(TablePermit { _permit: None }),
+    }
+}
+
+fn in_use(semaphore: &Option<Arc<Semaphore>>, limit: Option<usize>) -> usize {
+    match (semaphore, limit) {
+        (Some(semaphore), Some(limit)) => limit.max(1) - semaphore.available_permits(),
+        _ => 0,
+    }
+}
+
+impl TableLimiter {
+    /// Create a limiter enforcing `limits`
+    pub fn new(limits: ConcurrencyLimits) -> Self {
+        Self {
+            scans: semaphore(limits.max_concurrent_scans),
+            commits: semaphore(limits.max_concurrent_commits),
+            limits,
+        }
+    }
+
+    /// The enforced limits
+    pub fn limits(&self) -> &ConcurrencyLimits {
+        &self.limits
+    }
+
+    /// Wait for permission to scan the table
+    pub async fn acquire_scan(&self) -> DeltaResult<TablePermit> {
+        acquire(&self.scans).await
+    }
+
+    /// Wait for permission to attempt a commit
+    pub async fn acquire_commit(&self) -> DeltaResult<TablePermit> {
+        acquire(&self.commits).await
+    }
+
+    /// Permission to scan the table, if available without waiting
+    pub fn try_acquire_scan(&self) -> Option<TablePermit> {
+        try_acquire(&self.scans)
+    }
+
+    /// Permission to attempt a commit, if available without waiting
+    pub fn try_acquire_commit(&self) -> Option<TablePermit> {
+        try_acquire(&self.commits)
+    }
+
+    /// Number of scans currently holding a permit
+    pub fn active_scans(&self) -> usize {
+        in_use(&self.scans, self.limits.max_concurrent_scans)
+    }
+
+    /// Number of commit attempts currently holding a permit
+    pub fn active_commits(&self) -> usize {
+        in_use(&self.commits, self.limits.max_concurrent_commits)
+    }
+
+    /// Whether no permits are held
+    pub fn is_idle(&self) -> bool {
+        self.active_scans() == 0 && self.active_commits() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_table_limiter() {
+        let limiter = TableLimiter::new(ConcurrencyLimits {
+            max_concurrent_scans: Some(2),
+            max_concurrent_commits: Some(1),
+        });
+        assert!(limiter.is_idle());
+
+        let first = limiter.acquire_scan().await.unwrap();
+        let _second = limiter.clone().acquire_scan().await.unwrap();
+        assert_eq!(limiter.active_scans(), 2);
+        assert!(limiter.try_acquire_scan().is_none());
+
+        let commit = limiter.acquire_commit().await.unwrap();
+        assert!(limiter.try_acquire_commit().is_none());
+        drop(commit);
+        assert!(limiter.try_acquire_commit().is_some());
+
+        drop(first);
+        assert!(limiter.try_acquire_scan().is_some());
+        assert_eq!(limiter.active_scans(), 1);
+        assert!(!limiter.is_idle());
+    }
+
+    #[test]
+    fn test_unlimited() {
+        let limiter = TableLimiter::default();
+        let permits = (0..100)
+            .map(|_| limiter.try_acquire_scan().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(permits.len(), 100);
+        assert!(limiter.is_idle());
+    }
+}
@@ -0,0 +1,707 @@
+//! Validate the actions of the delta log against the JSON schema of the Delta protocol.
+//!
+//! Loading a table is lenient: fields this crate does not know are ignored, and an action of
+//! the wrong shape fails the whole load with the first parse error. Tables written by third
+//! party engines are better ingested with strict validation, which checks every action of the
+//! replayed commits and collects all violations into a [`LogValidationReport`].
+//!
+//! Tables may also use protocol extensions newer than this crate. Replay ignores actions and
+//! fields it does not know, and checkpoints written from the replayed state cannot carry them,
+//! so checkpointing such a table silently drops them. The [`UnknownActionPolicy`] of a table,
+//! see [`DeltaTableBuilder::with_unknown_action_policy`](super::builder::DeltaTableBuilder::with_unknown_action_policy),
+//! decides whether they are ignored, reported or rejected.
+//!
+//! # Example
+//! ```rust ignore
+//! let table = DeltaTableBuilder::from_uri("s3://bucket/table")
+//!     .with_strict_validation()
+//!     .load()
+//!     .await?;
+//!
+//! let report = table.validate_log().await?;
+//! for violation in report.violations {
+//!     println!("{violation}");
+//! }
+//! ````
+
+use futures::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::warn;
+
+use crate::logstore::LogStore;
+use crate::{DeltaResult, DeltaTableError};
+
+const READ_CONCURRENCY: usize = 16;
+
+/// JSON type of a field of an action
+#[derive(Debug, Clone, Copy)]
+enum FieldType {
+    String,
+    Integer,
+    Long,
+    Boolean,
+    /// An object with string values, which may be null
+    StringMap,
+    StringArray,
+    Struct(&'static [Field]),
+}
+
+impl FieldType {
+    fn name(&self) -> &'static str {
+        match self {
+            FieldType::String => "string",
+            FieldType::Integer => "integer",
+            FieldType::Long => "long",
+            FieldType::Boolean => "boolean",
+            FieldType::StringMap => "map of strings",
+            FieldType::StringArray => "array of strings",
+            FieldType::Struct(_) => "object",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Field {
+    name: &'static str,
+    field_type: FieldType,
+    required: bool,
+}
+
+const fn required(name: &'static str, field_type: FieldType) -> Field {
+    Field {
+        name,
+        field_type,
+        required: true,
+    }
+}
+
+const fn optional(name: &'static str, field_type: FieldType) -> Field {
+    Field {
+        name,
+        field_type,
+        required: false,
+    }
+}
+
+const DELETION_VECTOR: &[Field] = &[
+    required("storageType", FieldType::String),
+    required("pathOrInlineDv", FieldType::String),
+    optional("offset", FieldType::Integer),
+    required("sizeInBytes", FieldType::Integer),
+    required("cardinality", FieldType::Long),
+    optional("maxRowIndex", FieldType::Long),
+];
+
+const FORMAT: &[Field] = &[
+    required("provider", FieldType::String),
+    optional("options", FieldType::StringMap),
+];
+
+const ADD: &[Field] = &[
+    required("path", FieldType::String),
+    required("partitionValues", FieldType::StringMap),
+    required("size", FieldType::Long),
+    required("modificationTime", FieldType::Long),
+    required("dataChange", FieldType::Boolean),
+    optional("stats", FieldType::String),
+    optional("tags", FieldType::StringMap),
+    optional("deletionVector", FieldType::Struct(DELETION_VECTOR)),
+    optional("baseRowId", FieldType::Long),
+    optional("defaultRowCommitVersion", FieldType::Long),
+    optional("clusteringProvider", FieldType::String),
+];
+
+const REMOVE: &[Field] = &[
+    required("path", FieldType::String),
+    required("dataChange", FieldType::Boolean),
+    optional("deletionTimestamp", FieldType::Long),
+    optional("extendedFileMetadata", FieldType::Boolean),
+    optional("partitionValues", FieldType::StringMap),
+    optional("size", FieldType::Long),
+    optional("stats", FieldType::String),
+    optional("tags", FieldType::StringMap),
+    optional("deletionVector", FieldType::Struct(DELETION_VECTOR)),
+    optional("baseRowId", FieldType::Long),
+    optional("defaultRowCommitVersion", FieldType::Long),
+];
+
+const CDC: &[Field] = &[
+    required("path", FieldType::String),
+    required("partitionValues", FieldType::StringMap),
+    required("size", FieldType::Long),
+    required("dataChange", FieldType::Boolean),
+    optional("tags", FieldType::StringMap),
+];
+
+const METADATA: &[Field] = &[
+    required("id", FieldType::String),
+    optional("name", FieldType::String),
+    optional("description", FieldType::String),
+    required("format", FieldType::Struct(FORMAT)),
+    required("schemaString", FieldType::String),
+    required("partitionColumns", FieldType::StringArray),
+    optional("createdTime", FieldType::Long),
+    required("configuration", FieldType::StringMap),
+];
+
+const PROTOCOL: &[Field] = &[
+    required("minReaderVersion", FieldType::Integer),
+    required("minWriterVersion", FieldType::Integer),
+    optional("readerFeatures", FieldType::StringArray),
+    optional("writerFeatures", FieldType::StringArray),
+];
+
+const TXN: &[Field] = &[
+    required("appId", FieldType::String),
+    required("version", FieldType::Long),
+    optional("lastUpdated", FieldType::Long),
+];
+
+const DOMAIN_METADATA: &[Field] = &[
+    required("domain", FieldType::String),
+    required("configuration", FieldType::String),
+    required("removed", FieldType::Boolean),
+];
+
+/// Commit info is free form, engines add any provenance information they like
+const COMMIT_INFO: &str = "commitInfo";
+
+/// Fields of the other actions allowed in commit files
+fn action_fields(action: &str) -> Option<&'static [Field]> {
+    match action {
+        "add" => Some(ADD),
+        "remove" => Some(REMOVE),
+        "cdc" => Some(CDC),
+        "metaData" => Some(METADATA),
+        "protocol" => Some(PROTOCOL),
+        "txn" => Some(TXN),
+        "domainMetadata" => Some(DOMAIN_METADATA),
+        _ => None,
+    }
+}
+
+/// Kind of a [`LogSchemaViolation`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum LogViolationKind {
+    /// The line is not a JSON object, or is not valid JSON at all
+    #[serde(rename_all = "camelCase")]
+    InvalidJson {
+        /// Error raised while parsing
+        message: String,
+    },
+    /// The line does not contain exactly one action
+    #[serde(rename_all = "camelCase")]
+    ActionCount {
+        /// Number of actions on the line
+        count: usize,
+    },
+    /// The action is not defined by the protocol
+    #[serde(rename_all = "camelCase")]
+    UnknownAction {
+        /// Name of the action
+        action: String,
+    },
+    /// The action contains a field which is not defined by the protocol
+    #[serde(rename_all = "camelCase")]
+    UnknownField {
+        /// Name of the action
+        action: String,
+        /// Path of the field, nested fields are separated by dots
+        field: String,
+    },
+    /// The action lacks a required field
+    #[serde(rename_all = "camelCase")]
+    MissingField {
+        /// Name of the action
+        action: String,
+        /// Path of the field, nested fields are separated by dots
+        field: String,
+    },
+    /// A field of the action has the wrong type
+    #[serde(rename_all = "camelCase")]
+    WrongType {
+        /// Name of the action
+        action: String,
+        /// Path of the field, nested fields are separated by dots
+        field: String,
+        /// Type required by the protocol
+        expected: String,
+        /// The offending value
+        found: String,
+    },
+}
+
+/// A single action violating the protocol's JSON schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogSchemaViolation {
+    /// Version of the commit containing the action
+    pub version: i64,
+    /// Line of the action in the commit file, starting at 1
+    pub line: usize,
+    /// What is wrong with the action
+    #[serde(flatten)]
+    pub kind: LogViolationKind,
+}
+
+impl std::fmt::Display for LogSchemaViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "version {} line {}: ", self.version, self.line)?;
+        match &self.kind {
+            LogViolationKind::InvalidJson { message } => write!(f, "invalid JSON: {message}"),
+            LogViolationKind::ActionCount { count } => {
+                write!(f, "expected exactly one action, found {count}")
+            }
+            LogViolationKind::UnknownAction { action } => write!(f, "unknown action '{action}'"),
+            LogViolationKind::UnknownField { action, field } => {
+                write!(f, "unknown field '{field}' in {action}")
+            }
+            LogViolationKind::MissingField { action, field } => {
+                write!(f, "missing field '{field}' in {action}")
+            }
+            LogViolationKind::WrongType {
+                action,
+                field,
+                expected,
+                found,
+            } => write!(
+                f,
+                "field '{field}' in {action} should be {expected}, found {found}"
+            ),
+        }
+    }
+}
+
+/// Result of validating the commits of a table
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogValidationReport {
+    /// Number of commits that were checked
+    pub commits_checked: usize,
+    /// Number of actions that were checked
+    pub actions_checked: usize,
+    /// All violations found
+    pub violations: Vec<LogSchemaViolation>,
+}
+
+impl LogValidationReport {
+    /// Whether no violations were found
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Keep only the actions and fields unknown to this crate, dropping all other violations
+    pub(crate) fn into_unknown(mut self) -> Self {
+        self.violations
+            .retain(|violation| violation.kind.is_unknown());
+        self
+    }
+}
+
+impl LogViolationKind {
+    /// Whether the violation is an action or field not defined by the protocol
+    fn is_unknown(&self) -> bool {
+        matches!(
+            self,
+            LogViolationKind::UnknownAction { .. } | LogViolationKind::UnknownField { .. }
+        )
+    }
+}
+
+/// Handling of actions and fields of the log which are not defined by the protocol
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UnknownActionPolicy {
+    /// Skip them during replay, and drop them from checkpoints
+    #[default]
+    Ignore,
+    /// Log a warning during replay, and refuse to write checkpoints which would drop them
+    Warn,
+    /// Fail loading the table
+    Error,
+}
+
+impl UnknownActionPolicy {
+    /// Apply the policy to the replayed commit `version`
+    pub(crate) fn check_commit(&self, version: i64, data: &[u8]) -> DeltaResult<()> {
+        if *self == UnknownActionPolicy::Ignore {
+            return Ok(());
+        }
+        let mut violations = Vec::new();
+        let actions_checked = validate_commit(version, data, &mut violations);
+        let report = LogValidationReport {
+            commits_checked: 1,
+            actions_checked,
+            violations,
+        }
+        .into_unknown();
+        if report.is_valid() {
+            return Ok(());
+        }
+        match self {
+            UnknownActionPolicy::Error => Err(DeltaTableError::InvalidLogSchema {
+                report: Box::new(report),
+            }),
+            _ => {
+                for violation in &report.violations {
+                    warn!("Replaying unsupported log content, {violation}");
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn type_matches(field_type: FieldType, value: &Value) -> bool {
+    match field_type {
+        FieldType::String => value.is_string(),
+        FieldType::Integer => value
+            .as_i64()
+            .is_some_and(|value| i32::try_from(value).is_ok()),
+        FieldType::Long => value.is_i64(),
+        FieldType::Boolean => value.is_boolean(),
+        FieldType::StringMap => value.as_object().is_some_and(|map| {
+            map.values()
+                .all(|value| value.is_string() || value.is_null())
+        }),
+        FieldType::StringArray => value
+            .as_array()
+            .is_some_and(|values| values.iter().all(Value::is_string)),
+        FieldType::Struct(_) => value.is_object(),
+    }
+}
+
+/// Abbreviated rendering of an offending value
+fn describe(value: &Value) -> String {
+    const MAX_LEN: usize = 64;
+    let mut rendered = value.to_string();
+    if rendered.len() > MAX_LEN {
+        let end = (0..=MAX_LEN)
+            .rev()
+            .find(|end| rendered.is_char_boundary(*end))
+            .unwrap_or_default();
+        rendered.truncate(end);
+        rendered.push_str("...");
+    }
+    rendered
+}
+
+fn validate_fields(
+    action: &str,
+    prefix: &str,
+    fields: &[Field],
+    object: &Map<String, Value>,
+    violations: &mut Vec<LogViolationKind>,
+) {
+    let path = |name: &str| format!("{prefix}{name}");
+    for name in object.keys() {
+        if !fields.iter().any(|field| field.name == name) {
+            violations.push(LogViolationKind::UnknownField {
+                action: action.to_string(),
+                field: path(name),
+            });
+        }
+    }
+    for field in fields {
+        match object.get(field.name) {
+            None => {
+                if field.required {
+                    violations.push(LogViolationKind::MissingField {
+                        action: action.to_string(),
+                        field: path(field.name),
+                    });
+                }
+            }
+            // writers may serialize absent optional fields as null
+            Some(Value::Null) if !field.required => {}
+            Some(value) if !type_matches(field.field_type, value) => {
+                violations.push(LogViolationKind::WrongType {
+                    action: action.to_string(),
+                    field: path(field.name),
+                    expected: field.field_type.name().to_string(),
+                    found: describe(value),
+                });
+            }
+            Some(Value::Object(nested)) => {
+                if let FieldType::Struct(nested_fields) = field.field_type {
+                    let prefix = format!("{}.", path(field.name));
+                    validate_fields(action, &prefix, nested_fields, nested, violations);
+                }
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+/// Validate a single line of a commit file
+fn validate_line(line: &[u8]) -> Vec<LogViolationKind> {
+    let value = match serde_json::from_slice::<Value>(line) {
+        Ok(value) => value,
+        Err(err) => {
+            return vec![LogViolationKind::InvalidJson {
+                message: err.to_string(),
+            }]
+        }
+    };
+    let Value::Object(object) = value else {
+        return vec![LogViolationKind::InvalidJson {
+            message: format!("expected an object, found {}", describe(&value)),
+        }];
+    };
+    if object.len() != 1 {
+        return vec![LogViolationKind::ActionCount {
+            count: object.len(),
+        }];
+    }
+
+    let mut violations = Vec::new();
+    for (action, body) in &object {
+        let fields = action_fields(action);
+        if fields.is_none() && action != COMMIT_INFO {
+            violations.push(LogViolationKind::UnknownAction {
+                action: action.clone(),
+            });
+            continue;
+        }
+        let Value::Object(body) = body else {
+            violations.push(LogViolationKind::WrongType {
+                action: action.clone(),
+                field: String::new(),
+                expected: "object".to_string(),
+                found: describe(body),
+            });
+            continue;
+        };
+        if let Some(fields) = fields {
+            validate_fields(action, "", fields, body, &mut violations);
+        }
+    }
+    violations
+}
+
+/// Validate all actions of a commit file, returning the number of actions checked
+pub(crate) fn validate_commit(
+    version: i64,
+    data: &[u8],
+    violations: &mut Vec<LogSchemaViolation>,
+) -> usize {
+    let mut actions = 0;
+    for (index, line) in data.split(|b| *b == b'\n').enumerate() {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+        actions += 1;
+        violations.extend(
+            validate_line(line)
+                .into_iter()
+                .map(|kind| LogSchemaViolation {
+                    version,
+                    line: index + 1,
+                    kind,
+                }),
+        );
+    }
+    actions
+}
+
+/// Validate the commits with the given versions
+pub(crate) async fn validate_log(
+    log_store: &dyn LogStore,
+    versions: impl IntoIterator<Item = i64>,
+) -> DeltaResult<LogValidationReport> {
+    let mut commits = futures::stream::iter(versions)
+        .map(|version| async move {
+            let bytes = log_store.read_commit_entry(version).await?;
+            DeltaResult::Ok(bytes.map(|bytes| (version, bytes)))
+        })
+        .buffer_unordered(READ_CONCURRENCY)
+        .try_filter_map(|commit| futures::future::ready(Ok(commit)))
+        .try_collect::<Vec<_>>()
+        .await?;
+    commits.sort_by_key(|(version, _)| *version);
+
+    let mut report = LogValidationReport::default();
+    for (version, bytes) in commits {
+        report.commits_checked += 1;
+        report.actions_checked += validate_commit(version, &bytes, &mut report.violations);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(data: &str) -> Vec<LogViolationKind> {
+        let mut violations = Vec::new();
+        validate_commit(0, data.as_bytes(), &mut violations);
+        violations.into_iter().map(|v| v.kind).collect()
+    }
+
+    #[test]
+    fn test_validate_valid_actions() {
+        let data = [
+            r#"{"commitInfo":{"timestamp":1,"operation":"WRITE","engineInfo":"other-engine"}}"#,
+            r#"{"protocol":{"minReaderVersion":1,"minWriterVersion":2}}"#,
+            r#"{"add":{"path":"a.parquet","partitionValues":{"p":null},"size":1,"modificationTime":1,"dataChange":true,"stats":null}}"#,
+            r#"{"remove":{"path":"b.parquet","dataChange":true,"deletionTimestamp":1}}"#,
+            r#"{"txn":{"appId":"app","version":3}}"#,
+        ]
+        .join("\n");
+        assert_eq!(kinds(&data), vec![]);
+    }
+
+    #[test]
+    fn test_validate_violations() {
+        let data = [
+            r#"{"add":{"path":"a.parquet","partitionValues":{},"size":"1","modificationTime":1,"dataChange":true,"extra":1}}"#,
+            r#"{"remove":{"path":"b.parquet","deletionVector":{"storageType":"u","pathOrInlineDv":"x","sizeInBytes":1}}}"#,
+            r#"{"fancyAction":{}}"#,
+            r#"{"txn":{"appId":"app","version":3},"protocol":{}}"#,
+            r#"not json"#,
+        ]
+        .join("\n");
+        assert_eq!(
+            kinds(&data),
+            vec![
+                LogViolationKind::UnknownField {
+                    action: "add".to_string(),
+                    field: "extra".to_string(),
+                },
+                LogViolationKind::WrongType {
+                    action: "add".to_string(),
+                    field: "size".to_string(),
+                    expected: "long".to_string(),
+                    found: "\"1\"".to_string(),
+                },
+                LogViolationKind::MissingField {
+                    action: "remove".to_string(),
+                    field: "dataChange".to_string(),
+                },
+                LogViolationKind::MissingField {
+                    action: "remove".to_string(),
+                    field: "deletionVector.cardinality".to_string(),
+                },
+                LogViolationKind::UnknownAction {
+                    action: "fancyAction".to_string(),
+                },
+                LogViolationKind::ActionCount { count: 2 },
+                LogViolationKind::InvalidJson {
+                    message: "expected ident at line 1 column 2".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_table_log() {
+        let table = crate::open_table("../test/tests/data/simple_table")
+            .await
+            .unwrap();
+        let report = table.validate_log().await.unwrap();
+        assert!(report.is_valid(), "{:?}", report.violations);
+        assert_eq!(report.commits_checked, 5);
+    }
+
+    /// A table whose metadata action has a field unknown to the protocol
+    fn table_with_vendor_field(extra_actions: &[&str]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let log_dir = dir.path().join("_delta_log");
+        std::fs::create_dir(&log_dir).unwrap();
+        let mut commit = vec![
+            r#"{"protocol":{"minReaderVersion":1,"minWriterVersion":2}}"#,
+            r#"{"metaData":{"id":"1","format":{"provider":"parquet","options":{}},"schemaString":"{\"type\":\"struct\",\"fields\":[]}","partitionColumns":[],"configuration":{},"vendorField":true}}"#,
+        ];
+        commit.extend(extra_actions);
+        std::fs::write(log_dir.join("00000000000000000000.json"), commit.join("\n")).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_strict_load() {
+        let dir = table_with_vendor_field(&[]);
+        let uri = dir.path().to_str().unwrap();
+
+        // lenient loading ignores the unknown field
+        crate::DeltaTableBuilder::from_uri(uri)
+            .load()
+            .await
+            .unwrap();
+
+        let err = crate::DeltaTableBuilder::from_uri(uri)
+            .with_strict_validation()
+            .load()
+            .await
+            .unwrap_err();
+        let crate::DeltaTableError::InvalidLogSchema { report } = err else {
+            panic!("expected a schema violation, got {err}");
+        };
+        assert_eq!(report.commits_checked, 1);
+        assert_eq!(report.actions_checked, 2);
+        assert_eq!(
+            report.violations[0].kind,
+            LogViolationKind::UnknownField {
+                action: "metaData".to_string(),
+                field: "vendorField".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_check_commit() {
+        let data = [
+            r#"{"txn":{"appId":"app","version":3,"newField":1}}"#,
+            r#"{"add":{"path":"a.parquet"}}"#,
+        ]
+        .join("\n");
+        assert!(UnknownActionPolicy::Ignore
+            .check_commit(2, data.as_bytes())
+            .is_ok());
+        assert!(UnknownActionPolicy::Warn
+            .check_commit(2, data.as_bytes())
+            .is_ok());
+
+        let err = UnknownActionPolicy::Error
+            .check_commit(2, data.as_bytes())
+            .unwrap_err();
+        let DeltaTableError::InvalidLogSchema { report } = err else {
+            panic!("expected a schema violation, got {err}");
+        };
+        // only unknown content is subject to the policy, not the incomplete add
+        assert_eq!(
+            report.violations,
+            vec![LogSchemaViolation {
+                version: 2,
+                line: 1,
+                kind: LogViolationKind::UnknownField {
+                    action: "txn".to_string(),
+                    field: "newField".to_string(),
+                },
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_action_policy() {
+        let dir = table_with_vendor_field(&[r#"{"futureAction":{"id":1}}"#]);
+        let uri = dir.path().to_str().unwrap();
+        let load = |policy| {
+            crate::DeltaTableBuilder::from_uri(uri)
+                .with_unknown_action_policy(policy)
+                .load()
+        };
+
+        assert!(load(UnknownActionPolicy::Error).await.is_err());
+
+        // the unknown content is replayed, but not dropped by a checkpoint
+        let table = load(UnknownActionPolicy::Warn).await.unwrap();
+        assert_eq!(table.version(), 0);
+        assert!(crate::checkpoints::create_checkpoint(&table).await.is_err());
+
+        let table = load(UnknownActionPolicy::Ignore).await.unwrap();
+        crate::checkpoints::create_checkpoint(&table).await.unwrap();
+    }
+}
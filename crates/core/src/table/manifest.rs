@@ -0,0 +1,203 @@
+//! Static JSON manifests of table snapshots.
+//!
+//! A [`TableManifest`] describes a version of a table, its schema, partition columns,
+//! configuration and data files with their statistics, as a single JSON document. Tooling
+//! which can't read the Delta log, e.g. scripts in other languages, can consume the manifest
+//! instead, and manifests attached to bug reports make a snapshot easy to inspect. The output
+//! is stable: files are sorted by path and maps by key, so manifests of the same snapshot are
+//! identical.
+//!
+//! A manifest can also be used to open the table it was created from, at the described
+//! version, see [`TableManifest::open`].
+//!
+//! # Example
+//! ```rust ignore
+//! let json = table.to_manifest_json()?;
+//! // ...
+//! let table = TableManifest::from_json(&json)?.open(HashMap::new()).await?;
+//! ````
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::kernel::StructType;
+use crate::table::builder::DeltaTableBuilder;
+use crate::DeltaTable;
+
+/// Version of the manifest format written by this crate
+pub const MANIFEST_FORMAT_VERSION: u32 = 1;
+
+/// A data file of a [`TableManifest`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestFile {
+    /// Path of the file relative to the table root
+    pub path: String,
+    /// Size of the file in bytes
+    pub size: i64,
+    /// Values of the partition columns of the file
+    pub partition_values: BTreeMap<String, Option<String>>,
+    /// Time the file was created, in milliseconds since the epoch
+    pub modification_time: i64,
+    /// Statistics of the file, if collected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<Value>,
+}
+
+/// A description of a version of a table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableManifest {
+    /// Version of the manifest format, see [`MANIFEST_FORMAT_VERSION`]
+    pub format_version: u32,
+    /// Uri of the table root
+    pub table_uri: String,
+    /// The described version of the table
+    pub version: i64,
+    /// Minimum reader version required by the table
+    pub min_reader_version: i32,
+    /// Minimum writer version required by the table
+    pub min_writer_version: i32,
+    /// Schema of the table
+    pub schema: StructType,
+    /// Columns the table is partitioned by
+    pub partition_columns: Vec<String>,
+    /// Configuration of the table
+    pub configuration: BTreeMap<String, Option<String>>,
+    /// Data files of the table, sorted by path
+    pub files: Vec<ManifestFile>,
+}
+
+impl TableManifest {
+    /// Describe the loaded version of `table`
+    pub fn try_from_table(table: &DeltaTable) -> DeltaResult<Self> {
+        let snapshot = table.snapshot()?;
+        let metadata = snapshot.metadata();
+        let mut files = snapshot
+            .file_actions()?
+            .into_iter()
+            .map(|add| {
+                let stats = add
+                    .stats
+                    .as_deref()
+                    .map(serde_json::from_str::<Value>)
+                    .transpose()?;
+                Ok(ManifestFile {
+                    path: add.path,
+                    size: add.size,
+                    partition_values: add.partition_values.into_iter().collect(),
+                    modification_time: add.modification_time,
+                    stats,
+                })
+            })
+            .collect::<DeltaResult<Vec<_>>>()?;
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(Self {
+            format_version: MANIFEST_FORMAT_VERSION,
+            table_uri: table.table_uri(),
+            version: snapshot.version(),
+            min_reader_version: snapshot.protocol().min_reader_version,
+            min_writer_version: snapshot.protocol().min_writer_version,
+            schema: snapshot.schema().clone(),
+            partition_columns: metadata.partition_columns.clone(),
+            configuration: metadata.configuration.clone().into_iter().collect(),
+            files,
+        })
+    }
+
+    /// Serialize the manifest as pretty printed JSON
+    pub fn to_json(&self) -> DeltaResult<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parse a manifest, failing for manifests written in a newer format
+    pub fn from_json(json: &str) -> DeltaResult<Self> {
+        let manifest: Self = serde_json::from_str(json)?;
+        if manifest.format_version > MANIFEST_FORMAT_VERSION {
+            return Err(DeltaTableError::Generic(format!(
+                "Manifest format version {} is newer than the supported version {MANIFEST_FORMAT_VERSION}",
+                manifest.format_version
+            )));
+        }
+        Ok(manifest)
+    }
+
+    /// Open the table at the described version, failing if its files differ from the manifest
+    pub async fn open(&self, storage_options: HashMap<String, String>) -> DeltaResult<DeltaTable> {
+        let table = DeltaTableBuilder::from_uri(&self.table_uri)
+            .with_storage_options(storage_options)
+            .with_version(self.version)
+            .load()
+            .await?;
+        let paths = table
+            .snapshot()?
+            .file_actions()?
+            .into_iter()
+            .map(|add| add.path)
+            .collect::<HashSet<_>>();
+        if paths.len() != self.files.len()
+            || !self.files.iter().all(|file| paths.contains(&file.path))
+        {
+            return Err(DeltaTableError::Generic(format!(
+                "Files of version {} of the table don't match the manifest",
+                self.version
+            )));
+        }
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::DeltaOps;
+    use crate::writer::test_utils::{get_delta_schema, get_record_batch};
+
+    #[tokio::test]
+    async fn test_manifest_roundtrip() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let table = DeltaOps::try_from_uri(tmp_dir.path().to_str().unwrap())
+            .await
+            .unwrap()
+            .create()
+            .with_columns(get_delta_schema().fields().clone())
+            .with_partition_columns(["modified"])
+            .await
+            .unwrap();
+        let table = DeltaOps(table)
+            .write(vec![get_record_batch(None, false)])
+            .await
+            .unwrap();
+
+        let json = table.to_manifest_json().unwrap();
+        assert_eq!(json, table.to_manifest_json().unwrap());
+        let manifest = TableManifest::from_json(&json).unwrap();
+        assert_eq!(manifest.format_version, MANIFEST_FORMAT_VERSION);
+        assert_eq!(manifest.version, 1);
+        assert_eq!(manifest.partition_columns, vec!["modified"]);
+        assert_eq!(manifest.files.len(), 2);
+        assert!(manifest.files[0].path < manifest.files[1].path);
+        assert_eq!(manifest.files[0].stats.as_ref().unwrap()["numRecords"], 8);
+
+        let opened = manifest.open(HashMap::new()).await.unwrap();
+        assert_eq!(opened.version(), 1);
+
+        let table = DeltaOps(table)
+            .write(vec![get_record_batch(None, false)])
+            .with_save_mode(crate::protocol::SaveMode::Overwrite)
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 2);
+        let mut stale = manifest.clone();
+        stale.version = 2;
+        assert!(stale.open(HashMap::new()).await.is_err());
+
+        let mut newer = serde_json::from_str::<Value>(&json).unwrap();
+        newer["formatVersion"] = Value::from(MANIFEST_FORMAT_VERSION + 1);
+        assert!(TableManifest::from_json(&newer.to_string()).is_err());
+    }
+}
@@ -6,27 +6,48 @@ use std::fmt;
 use std::fmt::Formatter;
 
 use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
 use futures::TryStreamExt;
 use object_store::{path::Path, ObjectStore};
 use serde::de::{Error, SeqAccess, Visitor};
 use serde::ser::SerializeSeq;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use tracing::debug;
+use tracing::{debug, warn};
 
 use self::builder::DeltaTableConfig;
+use self::config::TableConfig;
+use self::file_tags::FileTagFilter;
+use self::log_validation::LogValidationReport;
+use self::snapshot_cache::SnapshotCache;
 use self::state::DeltaTableState;
+use self::verify::{VerificationLevel, VerificationReport};
+use crate::data_catalog::DataCatalog;
 use crate::kernel::{
     Action, CommitInfo, DataCheck, DataType, LogicalFile, Metadata, Protocol, StructType,
 };
 use crate::logstore::{self, LogStoreConfig, LogStoreRef};
 use crate::partitions::PartitionFilter;
+use crate::schema::evolution::{self, SchemaEvolution};
 use crate::storage::{commit_uri_from_version, ObjectStoreRef};
 use crate::{DeltaResult, DeltaTableError};
 
 pub mod builder;
 pub mod config;
+pub mod encryption;
+pub mod file_tags;
+pub mod limits;
+pub mod log_validation;
+pub mod manifest;
+pub mod pins;
+#[cfg(feature = "polars")]
+pub mod polars;
+pub mod pool;
+mod snapshot_cache;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_index;
 pub mod state;
 pub mod state_arrow;
+pub mod verify;
 
 /// Metadata for a checkpoint file
 #[derive(Serialize, Deserialize, Debug, Default, Clone, Copy)]
@@ -319,6 +340,36 @@ impl DeltaTable {
         self.update_incremental(None).await
     }
 
+    /// Check the loaded snapshot for violations of the protocol invariants, e.g. duplicate
+    /// files, unparsable statistics or, depending on `level`, data files missing from storage.
+    pub async fn verify(&self, level: VerificationLevel) -> DeltaResult<VerificationReport> {
+        verify::verify_table(&self.log_store, self.snapshot()?, level).await
+    }
+
+    /// Validate every action of the commits replayed for the loaded snapshot against the JSON
+    /// schema of the protocol, collecting unknown fields and wrong types into a report.
+    pub async fn validate_log(&self) -> DeltaResult<LogValidationReport> {
+        let versions = self.snapshot()?.snapshot().snapshot().commit_versions();
+        log_validation::validate_log(self.log_store.as_ref(), versions).await
+    }
+
+    /// Push the current location, schema, partition columns and properties of the table to its
+    /// entry `database_name.table_name` in `catalog`, e.g. after the schema evolved.
+    pub async fn sync_to_catalog(
+        &self,
+        catalog: &dyn DataCatalog,
+        catalog_id: Option<String>,
+        database_name: &str,
+        table_name: &str,
+    ) -> DeltaResult<()> {
+        catalog
+            .sync_table_metadata(catalog_id, database_name, table_name, self)
+            .await
+            .map_err(|err| DeltaTableError::GenericError {
+                source: Box::new(err),
+            })
+    }
+
     /// Get the list of actions for the next commit
     pub async fn peek_next_commit(
         &self,
@@ -348,13 +399,43 @@ impl DeltaTable {
         match self.state.as_mut() {
             Some(state) => state.update(self.log_store.clone(), max_version).await,
             _ => {
-                let state = DeltaTableState::try_new(
-                    &Path::default(),
-                    self.log_store.object_store(),
-                    self.config.clone(),
-                    max_version,
-                )
-                .await?;
+                let cache = self
+                    .config
+                    .snapshot_cache_dir
+                    .as_ref()
+                    .map(|dir| SnapshotCache::new(dir, &self.log_store.root_uri()));
+                let cached = match &cache {
+                    Some(cache) => {
+                        cache
+                            .load(max_version, &self.config, self.log_store.as_ref())
+                            .await
+                    }
+                    None => None,
+                };
+                let cached_version = cached.as_ref().map(|state| state.version());
+                let state = match cached {
+                    Some(mut state) => {
+                        debug!("loaded cached snapshot for version {}", state.version());
+                        state.update(self.log_store.clone(), max_version).await?;
+                        state
+                    }
+                    None => {
+                        DeltaTableState::try_new(
+                            &Path::default(),
+                            self.log_store.log_object_store(),
+                            self.config.clone(),
+                            max_version,
+                        )
+                        .await?
+                    }
+                };
+                if let Some(cache) = cache {
+                    if cached_version != Some(state.version()) {
+                        if let Err(err) = cache.store(&state, self.log_store.as_ref()).await {
+                            warn!("failed to cache snapshot: {err}");
+                        }
+                    }
+                }
                 self.state = Some(state);
                 Ok(())
             }
@@ -380,7 +461,8 @@ impl DeltaTable {
             Some(ts) => Ok(ts),
             None => {
                 let meta = self
-                    .object_store()
+                    .log_store
+                    .log_object_store()
                     .head(&commit_uri_from_version(version))
                     .await?;
                 let ts = meta.last_modified.timestamp_millis();
@@ -398,7 +480,7 @@ impl DeltaTable {
             .snapshot()?
             .snapshot
             .snapshot()
-            .commit_infos(self.object_store(), limit)
+            .commit_infos(self.log_store.log_object_store(), limit)
             .await?
             .try_collect::<Vec<_>>()
             .await?;
@@ -430,6 +512,15 @@ impl DeltaTable {
             .collect())
     }
 
+    /// Returns the file list tracked in current table state filtered by the tags of the files
+    pub fn get_files_by_tags(&self, filters: &[FileTagFilter]) -> DeltaResult<Vec<Path>> {
+        Ok(self
+            .snapshot()?
+            .get_active_add_actions_by_tags(filters)?
+            .map(|add| add.object_store_path())
+            .collect())
+    }
+
     /// Return the file uris as strings for the partition(s)
     pub fn get_file_uris_by_partitions(
         &self,
@@ -445,11 +536,10 @@ impl DeltaTable {
     /// Returns an iterator of file names present in the loaded state
     #[inline]
     pub fn get_files_iter(&self) -> DeltaResult<impl Iterator<Item = Path> + '_> {
-        Ok(self
-            .state
+        self.state
             .as_ref()
             .ok_or(DeltaTableError::NoMetadata)?
-            .file_paths_iter())
+            .file_paths_iter()
     }
 
     /// Returns a URIs for all active files present in the current table version.
@@ -458,13 +548,13 @@ impl DeltaTable {
             .state
             .as_ref()
             .ok_or(DeltaTableError::NoMetadata)?
-            .file_paths_iter()
+            .file_paths_iter()?
             .map(|path| self.log_store.to_uri(&path)))
     }
 
-    /// Get the number of files in the table - retrn 0 if no metadata is loaded
-    pub fn get_files_count(&self) -> usize {
-        self.state.as_ref().map(|s| s.files_count()).unwrap_or(0)
+    /// Get the number of files in the table - return 0 if no metadata is loaded
+    pub fn get_files_count(&self) -> DeltaResult<usize> {
+        self.state.as_ref().map_or(Ok(0), |s| s.files_count())
     }
 
     /// Returns the currently loaded state snapshot.
@@ -472,6 +562,25 @@ impl DeltaTable {
         self.state.as_ref().ok_or(DeltaTableError::NotInitialized)
     }
 
+    /// Describe the loaded version of the table as a stable JSON document, see [`manifest`]
+    pub fn to_manifest_json(&self) -> DeltaResult<String> {
+        manifest::TableManifest::try_from_table(self)?.to_json()
+    }
+
+    /// Stable fingerprint of the schema of the loaded version, see [`evolution::fingerprint`]
+    pub fn schema_fingerprint(&self) -> DeltaResult<String> {
+        evolution::fingerprint(self.get_schema()?)
+    }
+
+    /// Stream the schema changes committed after `since_version` up to the loaded version,
+    /// see [`evolution::schema_changes`]
+    pub fn schema_changes(
+        &self,
+        since_version: i64,
+    ) -> BoxStream<'static, DeltaResult<SchemaEvolution>> {
+        evolution::schema_changes(self.log_store.clone(), since_version, self.version())
+    }
+
     /// Returns current table protocol
     pub fn protocol(&self) -> DeltaResult<&Protocol> {
         Ok(self
@@ -486,6 +595,14 @@ impl DeltaTable {
         Ok(self.snapshot()?.metadata())
     }
 
+    /// Returns the typed configuration of the loaded state, after validating all well known
+    /// properties set on the table.
+    pub fn config(&self) -> DeltaResult<TableConfig<'_>> {
+        let config = self.snapshot()?.table_config();
+        config.validate()?;
+        Ok(config)
+    }
+
     /// Returns the current version of the DeltaTable based on the loaded metadata.
     pub fn get_app_transaction_version(&self) -> HashMap<String, i64> {
         self.state
@@ -509,11 +626,14 @@ impl DeltaTable {
     /// Time travel Delta table to the latest version that's created at or before provided
     /// `datetime` argument.
     ///
+    /// The `datetime` can be given either as [`chrono::DateTime`] or [`std::time::SystemTime`].
+    ///
     /// Internally, this methods performs a binary search on all Delta transaction logs.
     pub async fn load_with_datetime(
         &mut self,
-        datetime: DateTime<Utc>,
+        datetime: impl Into<DateTime<Utc>>,
     ) -> Result<(), DeltaTableError> {
+        let datetime = datetime.into();
         let mut min_version = 0;
         let mut max_version = self.get_latest_version().await?;
         let mut version = min_version;
@@ -0,0 +1,249 @@
+//! Registry of table versions pinned by long-running readers.
+//!
+//! Vacuum deletes files that were removed from the table, and log cleanup deletes old commits,
+//! without knowing which versions readers are still scanning. Long-running readers register the
+//! version they scan as a [`SnapshotPin`] in a [`PinRegistry`], and
+//! [`VacuumBuilder::with_pin_registry`](crate::operations::vacuum::VacuumBuilder::with_pin_registry)
+//! and [`cleanup_metadata_with_pins`](crate::protocol::checkpoints::cleanup_metadata_with_pins)
+//! keep everything needed to read the oldest pinned version.
+//!
+//! Pins are leases which expire, so that crashed readers do not block cleanup forever. Readers
+//! scanning for longer than the lease renew their pin by registering it again.
+//!
+//! [`SnapshotPinRegistry`] keeps pins in memory, which covers readers and cleanup running in the
+//! same process. Pins can additionally be shared with other processes through a shared
+//! registry, e.g. one backed by DynamoDB.
+//!
+//! # Example
+//! ```rust ignore
+//! let registry = Arc::new(SnapshotPinRegistry::default());
+//! let pin = registry.pin(&table, Duration::from_secs(3600)).await?;
+//! // ... scan the table
+//! registry.release(&pin).await?;
+//! ````
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use object_store::path::Path;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::DeltaResult;
+use crate::kernel::Action;
+use crate::logstore::{get_actions, LogStoreRef};
+use crate::DeltaTable;
+
+/// A table version pinned by a reader
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotPin {
+    /// Unique id of the pin
+    pub id: String,
+    /// Root uri of the table
+    pub table_uri: String,
+    /// The pinned version
+    pub version: i64,
+    /// Time in milliseconds since the Unix epoch after which the pin is ignored
+    pub expires_at: i64,
+}
+
+impl SnapshotPin {
+    /// Create a pin of `version` of the table at `table_uri`, expiring after `lease`
+    pub fn new(table_uri: impl Into<String>, version: i64, lease: Duration) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            table_uri: table_uri.into(),
+            version,
+            expires_at: expiry(lease),
+        }
+    }
+
+    /// Extend the lease of the pin to `lease` from now. The pin must be registered again for
+    /// the renewal to take effect
+    pub fn renew(&mut self, lease: Duration) {
+        self.expires_at = expiry(lease);
+    }
+
+    /// Whether the lease of the pin has expired
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now().timestamp_millis()
+    }
+}
+
+fn expiry(lease: Duration) -> i64 {
+    Utc::now()
+        .timestamp_millis()
+        .saturating_add(lease.as_millis().try_into().unwrap_or(i64::MAX))
+}
+
+/// Storage of [`SnapshotPin`]s
+#[async_trait]
+pub trait PinRegistry: Debug + Send + Sync {
+    /// Add `pin` to the registry, or replace a pin with the same id to renew it
+    async fn register(&self, pin: &SnapshotPin) -> DeltaResult<()>;
+
+    /// Remove `pin` from the registry
+    async fn release(&self, pin: &SnapshotPin) -> DeltaResult<()>;
+
+    /// Pins of the table at `table_uri`, possibly including expired ones
+    async fn pins(&self, table_uri: &str) -> DeltaResult<Vec<SnapshotPin>>;
+
+    /// The oldest version of the table at `table_uri` pinned by a pin that did not expire
+    async fn min_pinned_version(&self, table_uri: &str) -> DeltaResult<Option<i64>> {
+        Ok(self
+            .pins(table_uri)
+            .await?
+            .iter()
+            .filter(|pin| !pin.is_expired())
+            .map(|pin| pin.version)
+            .min())
+    }
+}
+
+/// Reference to a [`PinRegistry`]
+pub type PinRegistryRef = Arc<dyn PinRegistry>;
+
+/// In-memory [`PinRegistry`], optionally mirroring pins into a shared registry.
+/// See this module's documentation for more information
+#[derive(Debug, Default)]
+pub struct SnapshotPinRegistry {
+    pins: Mutex<HashMap<String, HashMap<String, SnapshotPin>>>,
+    shared: Option<PinRegistryRef>,
+}
+
+impl SnapshotPinRegistry {
+    /// Create a registry which also registers pins in, and consults pins of, `shared`
+    pub fn with_shared(shared: PinRegistryRef) -> Self {
+        Self {
+            pins: Mutex::new(HashMap::new()),
+            shared: Some(shared),
+        }
+    }
+
+    /// Pin the loaded version of `table` for `lease`
+    pub async fn pin(&self, table: &DeltaTable, lease: Duration) -> DeltaResult<SnapshotPin> {
+        let pin = SnapshotPin::new(table.table_uri(), table.version(), lease);
+        self.register(&pin).await?;
+        Ok(pin)
+    }
+}
+
+#[async_trait]
+impl PinRegistry for SnapshotPinRegistry {
+    async fn register(&self, pin: &SnapshotPin) -> DeltaResult<()> {
+        if let Some(shared) = &self.shared {
+            shared.register(pin).await?;
+        }
+        let mut pins = self.pins.lock();
+        let table_pins = pins.entry(pin.table_uri.clone()).or_default();
+        // drop expired pins of the table while we are at it
+        table_pins.retain(|_, pin| !pin.is_expired());
+        table_pins.insert(pin.id.clone(), pin.clone());
+        Ok(())
+    }
+
+    async fn release(&self, pin: &SnapshotPin) -> DeltaResult<()> {
+        {
+            let mut pins = self.pins.lock();
+            if let Some(table_pins) = pins.get_mut(&pin.table_uri) {
+                table_pins.remove(&pin.id);
+                if table_pins.is_empty() {
+                    pins.remove(&pin.table_uri);
+                }
+            }
+        }
+        if let Some(shared) = &self.shared {
+            shared.release(pin).await?;
+        }
+        Ok(())
+    }
+
+    async fn pins(&self, table_uri: &str) -> DeltaResult<Vec<SnapshotPin>> {
+        let mut pins = self
+            .pins
+            .lock()
+            .get(table_uri)
+            .map(|pins| pins.values().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        if let Some(shared) = &self.shared {
+            for pin in shared.pins(table_uri).await? {
+                if !pins.iter().any(|local| local.id == pin.id) {
+                    pins.push(pin);
+                }
+            }
+        }
+        Ok(pins)
+    }
+}
+
+/// Paths of all data files that are part of any version of the table from `version` up to
+/// `until_version`
+pub(crate) async fn files_referenced_since(
+    log_store: &LogStoreRef,
+    version: i64,
+    until_version: i64,
+) -> DeltaResult<HashSet<Path>> {
+    let mut table = DeltaTable::new(log_store.clone(), Default::default());
+    table.load_version(version).await?;
+    let mut files = table.get_files_iter()?.collect::<HashSet<_>>();
+
+    for version in version + 1..=until_version {
+        let Some(commit) = log_store.read_commit_entry(version).await? else {
+            continue;
+        };
+        for action in get_actions(version, commit).await? {
+            if let Action::Add(add) = action {
+                files.insert(match Path::parse(&add.path) {
+                    Ok(path) => path,
+                    Err(_) => Path::from(add.path.as_str()),
+                });
+            }
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_snapshot_pin_registry() {
+        let shared = Arc::new(SnapshotPinRegistry::default());
+        let registry = SnapshotPinRegistry::with_shared(shared.clone());
+        assert_eq!(
+            registry.min_pinned_version("memory:///").await.unwrap(),
+            None
+        );
+
+        let first = SnapshotPin::new("memory:///", 3, Duration::from_secs(60));
+        let second = SnapshotPin::new("memory:///", 5, Duration::from_secs(60));
+        registry.register(&first).await.unwrap();
+        shared.register(&second).await.unwrap();
+        assert_eq!(
+            registry.min_pinned_version("memory:///").await.unwrap(),
+            Some(3)
+        );
+        assert_eq!(shared.pins("memory:///").await.unwrap().len(), 2);
+
+        registry.release(&first).await.unwrap();
+        assert_eq!(
+            registry.min_pinned_version("memory:///").await.unwrap(),
+            Some(5)
+        );
+        assert_eq!(registry.min_pinned_version("other").await.unwrap(), None);
+
+        let expired = SnapshotPin::new("memory:///", 1, Duration::ZERO);
+        assert!(expired.is_expired());
+        registry.register(&expired).await.unwrap();
+        assert_eq!(
+            registry.min_pinned_version("memory:///").await.unwrap(),
+            Some(5)
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! Polars DataFrame interop
+//!
+//! Converts between arrow record batches and Polars data frames through the Arrow C data
+//! interface, without copying column buffers.
+//!
+//! # Example
+//! ```rust ignore
+//! let table = open_table("../path/to/table").await?;
+//! let df = table.to_polars().await?;
+//! let (table, _) = DeltaOps(table)
+//!     .write_polars(&df)?
+//!     .with_save_mode(SaveMode::Overwrite)
+//!     .await?;
+//! ````
+
+use std::sync::Arc;
+
+use ::polars::export::arrow::array::Array as PolarsArray;
+use ::polars::export::arrow::datatypes::Field as PolarsField;
+use ::polars::export::arrow::ffi as polars_ffi;
+use ::polars::prelude::{DataFrame, PolarsError, Series};
+use arrow::array::{make_array, ArrayRef};
+use arrow::ffi::{from_ffi, to_ffi, FFI_ArrowArray, FFI_ArrowSchema};
+use arrow::record_batch::RecordBatch;
+use arrow_schema::{Field, Schema as ArrowSchema};
+use futures::TryStreamExt;
+
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::operations::write::WriteBuilder;
+use crate::operations::DeltaOps;
+use crate::DeltaTable;
+
+impl From<PolarsError> for DeltaTableError {
+    fn from(err: PolarsError) -> Self {
+        DeltaTableError::GenericError {
+            source: Box::new(err),
+        }
+    }
+}
+
+fn array_to_polars(array: &ArrayRef) -> DeltaResult<Box<dyn PolarsArray>> {
+    let (ffi_array, ffi_schema) = to_ffi(&array.to_data())?;
+    // SAFETY: arrow and polars both implement the Arrow C data interface, so their FFI structs
+    // share the same layout. Polars takes ownership of the array and copies the schema.
+    let field = unsafe {
+        polars_ffi::import_field_from_c(
+            &*(&ffi_schema as *const FFI_ArrowSchema as *const polars_ffi::ArrowSchema),
+        )
+    }?;
+    let array = unsafe {
+        polars_ffi::import_array_from_c(
+            std::mem::transmute::<FFI_ArrowArray, polars_ffi::ArrowArray>(ffi_array),
+            field.data_type().clone(),
+        )
+    }?;
+    Ok(array)
+}
+
+fn array_from_polars(array: Box<dyn PolarsArray>) -> DeltaResult<ArrayRef> {
+    let field = PolarsField::new("", array.data_type().clone(), true);
+    let ffi_schema = polars_ffi::export_field_to_c(&field);
+    let ffi_array = polars_ffi::export_array_to_c(array);
+    // SAFETY: see `array_to_polars`, arrow takes ownership of the exported array and schema
+    let data = unsafe {
+        let ffi_schema =
+            std::mem::transmute::<polars_ffi::ArrowSchema, FFI_ArrowSchema>(ffi_schema);
+        let ffi_array = std::mem::transmute::<polars_ffi::ArrowArray, FFI_ArrowArray>(ffi_array);
+        from_ffi(ffi_array, &ffi_schema)?
+    };
+    Ok(make_array(data))
+}
+
+/// Convert a record batch to a Polars data frame
+pub fn record_batch_to_polars(batch: &RecordBatch) -> DeltaResult<DataFrame> {
+    let columns = batch
+        .schema()
+        .fields()
+        .iter()
+        .zip(batch.columns())
+        .map(|(field, array)| {
+            let array = array_to_polars(array)?;
+            Ok(Series::try_from((field.name().as_str(), array))?)
+        })
+        .collect::<DeltaResult<Vec<_>>>()?;
+    Ok(DataFrame::new(columns)?)
+}
+
+/// Convert a Polars data frame to record batches, one per chunk of the data frame
+pub fn polars_to_record_batches(df: &DataFrame) -> DeltaResult<Vec<RecordBatch>> {
+    let mut df = df.clone();
+    df.align_chunks();
+    let names = df.get_column_names();
+    df.iter_chunks()
+        .map(|chunk| {
+            let columns = chunk
+                .into_arrays()
+                .into_iter()
+                .map(array_from_polars)
+                .collect::<DeltaResult<Vec<_>>>()?;
+            let schema = ArrowSchema::new(
+                names
+                    .iter()
+                    .zip(&columns)
+                    .map(|(name, array)| Field::new(*name, array.data_type().clone(), true))
+                    .collect::<Vec<_>>(),
+            );
+            Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+        })
+        .collect()
+}
+
+impl DeltaTable {
+    /// Read the current version of the table into a Polars data frame
+    pub async fn to_polars(&self) -> DeltaResult<DataFrame> {
+        let (table, stream) = DeltaOps(self.clone()).load().await?;
+        let batches = stream.try_collect::<Vec<_>>().await?;
+        let mut df =
+            record_batch_to_polars(&RecordBatch::new_empty(table.snapshot()?.arrow_schema()?))?;
+        for batch in &batches {
+            df.vstack_mut(&record_batch_to_polars(batch)?)?;
+        }
+        Ok(df)
+    }
+}
+
+impl DeltaOps {
+    /// Write a Polars data frame to the table
+    pub fn write_polars(self, df: &DataFrame) -> DeltaResult<WriteBuilder> {
+        Ok(self.write(polars_to_record_batches(df)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::polars::prelude::*;
+
+    use super::*;
+    use crate::protocol::SaveMode;
+    use crate::writer::test_utils::{get_delta_schema, get_record_batch};
+
+    #[test]
+    fn test_round_trip() {
+        let batch = get_record_batch(None, false);
+        let df = record_batch_to_polars(&batch).unwrap();
+        assert_eq!(df.shape(), (batch.num_rows(), batch.num_columns()));
+        assert_eq!(df.get_column_names(), vec!["id", "value", "modified"]);
+
+        let batches = polars_to_record_batches(&df).unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].num_rows(), batch.num_rows());
+        assert_eq!(batches[0].column(1), batch.column(1));
+    }
+
+    #[tokio::test]
+    async fn test_write_and_read_polars() {
+        let table = DeltaOps::new_in_memory()
+            .create()
+            .with_columns(get_delta_schema().fields().clone())
+            .await
+            .unwrap();
+        let empty = table.to_polars().await.unwrap();
+        assert_eq!(empty.shape(), (0, 3));
+
+        let df = df!(
+            "id" => ["A", "B", "C"],
+            "value" => [1i32, 2, 3],
+            "modified" => ["2021-02-01", "2021-02-01", "2021-02-02"],
+        )
+        .unwrap();
+        let table = DeltaOps(table)
+            .write_polars(&df)
+            .unwrap()
+            .with_save_mode(SaveMode::Append)
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 1);
+
+        let actual = table
+            .to_polars()
+            .await
+            .unwrap()
+            .sort(["id"], false, false)
+            .unwrap();
+        assert_eq!(actual.shape(), (3, 3));
+        assert!(actual
+            .column("value")
+            .unwrap()
+            .equals(&Series::new("value", [1i32, 2, 3])));
+    }
+}
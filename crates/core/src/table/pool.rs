@@ -0,0 +1,282 @@
+//! Pool of open table handles shared across requests.
+//!
+//! Servers answering queries for many tables cannot afford to load a table from scratch for
+//! every request. A [`DeltaTablePool`] keeps loaded tables keyed by uri and hands out clones of
+//! them. A table is refreshed incrementally when its snapshot is older than the configured time
+//! to live, and the least recently used table is evicted once the pool is full.
+//!
+//! Concurrent requests for a table which is not loaded yet, or which needs a refresh, wait for a
+//! single load instead of all loading it.
+//!
+//! Each pooled table has a [`TableLimiter`] enforcing [`DeltaTablePoolConfig::limits`], see
+//! [`DeltaTablePool::scan_permit`] and [`DeltaTablePool::commit_permit`]. Tables with
+//! outstanding permits are only evicted when no idle table is left to evict.
+//!
+//! # Example
+//! ```rust ignore
+//! let pool = DeltaTablePool::new(DeltaTablePoolConfig::default());
+//! let table = pool.get("s3://bucket/tables/events").await?;
+//! ````
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use tracing::debug;
+
+use super::builder::DeltaTableBuilder;
+use super::limits::{ConcurrencyLimits, TableLimiter, TablePermit};
+use crate::errors::DeltaResult;
+use crate::DeltaTable;
+
+/// Configuration of a [`DeltaTablePool`]
+#[derive(Debug, Clone)]
+pub struct DeltaTablePoolConfig {
+    /// Maximum number of tables kept open
+    pub max_size: usize,
+    /// Age of a snapshot after which the table is refreshed before it is handed out
+    pub ttl: Duration,
+    /// Storage options used to open the tables
+    pub storage_options: HashMap<String, String>,
+    /// Concurrency limits applied to each table
+    pub limits: ConcurrencyLimits,
+}
+
+impl Default for DeltaTablePoolConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 1000,
+            ttl: Duration::from_secs(30),
+            storage_options: HashMap::new(),
+            limits: ConcurrencyLimits::default(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Slot {
+    /// The table and the time its snapshot was last loaded or refreshed
+    table: Option<(DeltaTable, Instant)>,
+}
+
+struct PoolEntry {
+    slot: Arc<tokio::sync::Mutex<Slot>>,
+    limiter: TableLimiter,
+    last_used: Instant,
+}
+
+/// Caches open table handles keyed by uri.
+/// See this module's documentation for more information
+pub struct DeltaTablePool {
+    config: DeltaTablePoolConfig,
+    entries: Mutex<HashMap<String, PoolEntry>>,
+}
+
+impl std::fmt::Debug for DeltaTablePool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeltaTablePool")
+            .field("config", &self.config)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl DeltaTablePool {
+    /// Create an empty pool
+    pub fn new(config: DeltaTablePoolConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Number of tables in the pool
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    /// Whether the pool holds no tables
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().is_empty()
+    }
+
+    /// Whether the table at `uri` is in the pool
+    pub fn contains(&self, uri: &str) -> bool {
+        self.entries.lock().contains_key(uri)
+    }
+
+    /// Remove the table at `uri` from the pool, so that it is loaded again on the next request
+    pub fn invalidate(&self, uri: &str) {
+        self.entries.lock().remove(uri);
+    }
+
+    /// Remove all tables from the pool
+    pub fn clear(&self) {
+        self.entries.lock().clear();
+    }
+
+    /// The limiter of the table at `uri`
+    pub fn limiter(&self, uri: &str) -> TableLimiter {
+        self.entry(uri).1
+    }
+
+    /// Wait for permission to scan the table at `uri`
+    pub async fn scan_permit(&self, uri: &str) -> DeltaResult<TablePermit> {
+        self.limiter(uri).acquire_scan().await
+    }
+
+    /// Wait for permission to attempt a commit to the table at `uri`
+    pub async fn commit_permit(&self, uri: &str) -> DeltaResult<TablePermit> {
+        self.limiter(uri).acquire_commit().await
+    }
+
+    /// Get the table at `uri`, loading it or refreshing its snapshot as needed
+    pub async fn get(&self, uri: &str) -> DeltaResult<DeltaTable> {
+        let (slot, limiter) = self.entry(uri);
+        let mut slot = slot.lock().await;
+
+        let result = match slot.table.as_mut() {
+            Some((table, refreshed_at)) if refreshed_at.elapsed() < self.config.ttl => {
+                Ok(table.clone())
+            }
+            Some((table, refreshed_at)) => {
+                debug!("refreshing pooled table {uri}");
+                table.update().await.map(|_| {
+                    *refreshed_at = Instant::now();
+                    table.clone()
+                })
+            }
+            None => {
+                debug!("loading pooled table {uri}");
+                let loaded = DeltaTableBuilder::from_uri(uri)
+                    .with_storage_options(self.config.storage_options.clone())
+                    .load()
+                    .await;
+                if let Ok(table) = &loaded {
+                    slot.table = Some((table.clone(), Instant::now()));
+                }
+                loaded
+            }
+        };
+        if result.is_err() && slot.table.is_none() && limiter.is_idle() {
+            self.invalidate(uri);
+        }
+        result
+    }
+
+    /// The slot and limiter of `uri`, inserting an empty slot and evicting the least recently
+    /// used table if the pool is full
+    fn entry(&self, uri: &str) -> (Arc<tokio::sync::Mutex<Slot>>, TableLimiter) {
+        let mut entries = self.entries.lock();
+        if let Some(entry) = entries.get_mut(uri) {
+            entry.last_used = Instant::now();
+            return (entry.slot.clone(), entry.limiter.clone());
+        }
+
+        while !entries.is_empty() && entries.len() >= self.config.max_size {
+            // prefer idle tables, so that the limits of busy tables stay in place
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| (!entry.limiter.is_idle(), entry.last_used))
+                .map(|(uri, _)| uri.clone());
+            if let Some(oldest) = oldest {
+                debug!("evicting pooled table {oldest}");
+                entries.remove(&oldest);
+            }
+        }
+        let slot = Arc::new(tokio::sync::Mutex::new(Slot::default()));
+        let limiter = TableLimiter::new(self.config.limits.clone());
+        entries.insert(
+            uri.to_string(),
+            PoolEntry {
+                slot: slot.clone(),
+                limiter: limiter.clone(),
+                last_used: Instant::now(),
+            },
+        );
+        (slot, limiter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::writer::test_utils::{create_initialized_table, get_record_batch};
+    use crate::writer::{DeltaWriter, RecordBatchWriter};
+
+    const SIMPLE_TABLE: &str = "../test/tests/data/simple_table";
+    const DELTA_0_8_0: &str = "../test/tests/data/delta-0.8.0";
+
+    #[tokio::test]
+    async fn test_pool_caches_and_evicts() {
+        let pool = DeltaTablePool::new(DeltaTablePoolConfig {
+            max_size: 1,
+            ..Default::default()
+        });
+        assert!(pool.is_empty());
+
+        let table = pool.get(SIMPLE_TABLE).await.unwrap();
+        assert_eq!(table.version(), 4);
+        assert!(pool.contains(SIMPLE_TABLE));
+        assert_eq!(pool.get(SIMPLE_TABLE).await.unwrap().version(), 4);
+
+        pool.get(DELTA_0_8_0).await.unwrap();
+        assert_eq!(pool.len(), 1);
+        assert!(!pool.contains(SIMPLE_TABLE));
+
+        pool.invalidate(DELTA_0_8_0);
+        assert!(pool.is_empty());
+        assert!(pool.get("../test/tests/data/does_not_exist").await.is_err());
+        assert!(pool.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pool_limits() {
+        let pool = DeltaTablePool::new(DeltaTablePoolConfig {
+            max_size: 2,
+            limits: ConcurrencyLimits {
+                max_concurrent_scans: Some(1),
+                max_concurrent_commits: None,
+            },
+            ..Default::default()
+        });
+
+        let permit = pool.scan_permit(SIMPLE_TABLE).await.unwrap();
+        assert!(pool.limiter(SIMPLE_TABLE).try_acquire_scan().is_none());
+        assert!(pool.limiter(SIMPLE_TABLE).try_acquire_commit().is_some());
+
+        // idle tables are evicted before busy ones, even if they were used more recently
+        pool.get(DELTA_0_8_0).await.unwrap();
+        pool.get("../test/tests/data/delta-0.2.0").await.unwrap();
+        assert!(pool.contains(SIMPLE_TABLE));
+        assert!(!pool.contains(DELTA_0_8_0));
+
+        drop(permit);
+        assert!(pool.limiter(SIMPLE_TABLE).try_acquire_scan().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_pool_refreshes_after_ttl() {
+        let mut table = create_initialized_table(&[]).await;
+        let uri = table.table_uri();
+
+        let cached = DeltaTablePool::new(DeltaTablePoolConfig {
+            ttl: Duration::from_secs(3600),
+            ..Default::default()
+        });
+        let refreshed = DeltaTablePool::new(DeltaTablePoolConfig {
+            ttl: Duration::ZERO,
+            ..Default::default()
+        });
+        assert_eq!(cached.get(&uri).await.unwrap().version(), 0);
+        assert_eq!(refreshed.get(&uri).await.unwrap().version(), 0);
+
+        let mut writer = RecordBatchWriter::for_table(&table).unwrap();
+        writer.write(get_record_batch(None, false)).await.unwrap();
+        writer.flush_and_commit(&mut table).await.unwrap();
+
+        assert_eq!(cached.get(&uri).await.unwrap().version(), 0);
+        assert_eq!(refreshed.get(&uri).await.unwrap().version(), 1);
+    }
+}
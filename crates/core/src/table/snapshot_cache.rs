@@ -0,0 +1,295 @@
+//! Local cache of resolved table snapshots.
+//!
+//! Snapshots are serialized, compressed with zstd and stored in a local directory keyed by
+//! table uri and version. When a table is loaded, the most recent cached snapshot is used as
+//! starting point and only the commits written after it are replayed, which makes restarts of
+//! long-running readers on tables with large logs considerably faster.
+//!
+//! Each entry records the size, e-tag and modification time of the commit file of its version.
+//! Entries are only reused while that commit file is unchanged, so tables which were recreated
+//! at the same location or whose log was cleaned up are loaded from the log again.
+
+use std::path::{Path, PathBuf};
+
+use object_store::ObjectMeta;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::builder::DeltaTableConfig;
+use super::state::DeltaTableState;
+use crate::logstore::LogStore;
+use crate::storage::commit_uri_from_version;
+use crate::DeltaResult;
+
+const CACHE_FILE_SUFFIX: &str = ".snapshot.zst";
+const ZSTD_LEVEL: i32 = 3;
+
+/// Identifies the commit file a cached snapshot was resolved from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CommitFingerprint {
+    size: usize,
+    e_tag: Option<String>,
+    last_modified: i64,
+}
+
+impl From<ObjectMeta> for CommitFingerprint {
+    fn from(meta: ObjectMeta) -> Self {
+        Self {
+            size: meta.size,
+            e_tag: meta.e_tag,
+            last_modified: meta.last_modified.timestamp_millis(),
+        }
+    }
+}
+
+/// Fingerprint of the commit file of `version` in the log
+async fn commit_fingerprint(
+    log_store: &dyn LogStore,
+    version: i64,
+) -> DeltaResult<CommitFingerprint> {
+    let meta = log_store
+        .log_object_store()
+        .head(&commit_uri_from_version(version))
+        .await?;
+    Ok(meta.into())
+}
+
+/// Serialized cache entry, with a borrowed state when writing
+#[derive(Serialize, Deserialize)]
+struct CacheEntry<S> {
+    commit: CommitFingerprint,
+    state: S,
+}
+
+/// Cached snapshots of a single table
+pub(crate) struct SnapshotCache {
+    table_dir: PathBuf,
+}
+
+impl SnapshotCache {
+    /// Create a cache for the table at `table_uri`, rooted at `cache_dir`
+    pub(crate) fn new(cache_dir: &Path, table_uri: &str) -> Self {
+        let key = utf8_percent_encode(table_uri, NON_ALPHANUMERIC).to_string();
+        Self {
+            table_dir: cache_dir.join(key),
+        }
+    }
+
+    fn path_for(&self, version: i64) -> PathBuf {
+        self.table_dir
+            .join(format!("{version:020}{CACHE_FILE_SUFFIX}"))
+    }
+
+    /// The most recent cached version that is not newer than `max_version`
+    async fn latest_version(&self, max_version: Option<i64>) -> Option<i64> {
+        let mut entries = tokio::fs::read_dir(&self.table_dir).await.ok()?;
+        let mut latest = None;
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let version = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_suffix(CACHE_FILE_SUFFIX))
+                .and_then(|version| version.parse::<i64>().ok());
+            if let Some(version) = version {
+                if max_version.map_or(true, |max| version <= max) {
+                    latest = latest.max(Some(version));
+                }
+            }
+        }
+        latest
+    }
+
+    /// Load the most recent cached snapshot not newer than `max_version`.
+    ///
+    /// Unreadable cache entries, entries that were created with a different table configuration
+    /// and entries whose commit file changed in the log are ignored.
+    pub(crate) async fn load(
+        &self,
+        max_version: Option<i64>,
+        config: &DeltaTableConfig,
+        log_store: &dyn LogStore,
+    ) -> Option<DeltaTableState> {
+        let version = self.latest_version(max_version).await?;
+        let entry = match self.read(version).await {
+            Ok(entry) => entry,
+            Err(err) => {
+                debug!("failed to read cached snapshot for version {version}: {err}");
+                return None;
+            }
+        };
+        if entry.state.snapshot().load_config() != config {
+            debug!("ignoring cached snapshot for version {version} with different config");
+            return None;
+        }
+        match commit_fingerprint(log_store, version).await {
+            Ok(commit) if commit == entry.commit => Some(entry.state),
+            Ok(_) => {
+                debug!("ignoring cached snapshot for version {version} with a changed commit");
+                None
+            }
+            Err(err) => {
+                debug!("ignoring cached snapshot for version {version} not in the log: {err}");
+                None
+            }
+        }
+    }
+
+    async fn read(&self, version: i64) -> DeltaResult<CacheEntry<DeltaTableState>> {
+        let compressed = tokio::fs::read(self.path_for(version)).await?;
+        let data = zstd::decode_all(compressed.as_slice())?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    /// Store `state` in the cache, replacing the older cached versions of the table.
+    ///
+    /// States older than the most recent cached version, like those of time travel queries, are
+    /// not stored so that they don't evict the snapshot used when loading the latest version.
+    pub(crate) async fn store(
+        &self,
+        state: &DeltaTableState,
+        log_store: &dyn LogStore,
+    ) -> DeltaResult<()> {
+        if self.latest_version(None).await > Some(state.version()) {
+            return Ok(());
+        }
+        let entry = CacheEntry {
+            commit: commit_fingerprint(log_store, state.version()).await?,
+            state,
+        };
+        let data = serde_json::to_vec(&entry)?;
+        let compressed = zstd::encode_all(data.as_slice(), ZSTD_LEVEL)?;
+
+        tokio::fs::create_dir_all(&self.table_dir).await?;
+        // write to a temporary file first, so concurrent readers never see partial entries
+        let path = self.path_for(state.version());
+        let tmp_path = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+        tokio::fs::write(&tmp_path, compressed).await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+
+        let mut entries = tokio::fs::read_dir(&self.table_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let older = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_suffix(CACHE_FILE_SUFFIX))
+                .and_then(|version| version.parse::<i64>().ok())
+                .is_some_and(|version| version < state.version());
+            if older {
+                // the entry may already have been removed by another process
+                let _ = tokio::fs::remove_file(entry.path()).await;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::table::builder::DeltaTableBuilder;
+    use crate::DeltaTable;
+
+    #[tokio::test]
+    async fn test_snapshot_cache_roundtrip() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let table = DeltaTableBuilder::from_uri("../test/tests/data/simple_table")
+            .with_version(2)
+            .with_snapshot_cache_dir(cache_dir.path())
+            .load()
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 2);
+
+        let cache = SnapshotCache::new(cache_dir.path(), &table.log_store().root_uri());
+        assert_eq!(cache.latest_version(None).await, Some(2));
+        assert_eq!(cache.latest_version(Some(1)).await, None);
+
+        let cached = cache
+            .load(None, &table.config, table.log_store().as_ref())
+            .await
+            .unwrap();
+        assert_eq!(cached.version(), 2);
+        assert_eq!(
+            cached.file_actions().unwrap().len(),
+            table.snapshot().unwrap().file_actions().unwrap().len()
+        );
+
+        // loading a newer version starts from the cached snapshot and replaces it
+        let table = DeltaTableBuilder::from_uri("../test/tests/data/simple_table")
+            .with_snapshot_cache_dir(cache_dir.path())
+            .load()
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 4);
+        assert_eq!(cache.latest_version(None).await, Some(4));
+        assert_eq!(
+            table.get_files_iter().unwrap().count(),
+            DeltaTableBuilder::from_uri("../test/tests/data/simple_table")
+                .load()
+                .await
+                .unwrap()
+                .get_files_iter()
+                .unwrap()
+                .count()
+        );
+
+        // time travel does not evict the most recent snapshot
+        let table = DeltaTableBuilder::from_uri("../test/tests/data/simple_table")
+            .with_version(3)
+            .with_snapshot_cache_dir(cache_dir.path())
+            .load()
+            .await
+            .unwrap();
+        assert_eq!(table.version(), 3);
+        assert_eq!(cache.latest_version(None).await, Some(4));
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_cache_recreated_table() {
+        use crate::operations::DeltaOps;
+        use crate::writer::test_utils::{get_delta_schema, get_record_batch};
+
+        async fn create_table(uri: &str) -> DeltaTable {
+            let table = DeltaOps::try_from_uri(uri)
+                .await
+                .unwrap()
+                .create()
+                .with_columns(get_delta_schema().fields().clone())
+                .await
+                .unwrap();
+            DeltaOps(table)
+                .write(vec![get_record_batch(None, false)])
+                .await
+                .unwrap()
+        }
+
+        let cache_dir = tempfile::tempdir().unwrap();
+        let table_dir = tempfile::tempdir().unwrap();
+        let uri = table_dir.path().to_str().unwrap();
+        create_table(uri).await;
+        let load = || {
+            DeltaTableBuilder::from_uri(uri)
+                .with_snapshot_cache_dir(cache_dir.path())
+                .load()
+        };
+        let table = load().await.unwrap();
+        let cache = SnapshotCache::new(cache_dir.path(), &table.log_store().root_uri());
+        assert_eq!(cache.latest_version(None).await, Some(1));
+
+        // a table recreated at the same location has the same versions, but other commits
+        std::fs::remove_dir_all(uri).unwrap();
+        let recreated = create_table(uri).await;
+        assert!(cache
+            .load(None, &table.config, table.log_store().as_ref())
+            .await
+            .is_none());
+        let table = load().await.unwrap();
+        assert_eq!(table.version(), 1);
+        assert_eq!(
+            table.get_files_iter().unwrap().collect::<Vec<_>>(),
+            recreated.get_files_iter().unwrap().collect::<Vec<_>>()
+        );
+    }
+}
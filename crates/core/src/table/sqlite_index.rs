@@ -0,0 +1,457 @@
+//! Local sqlite index of the files of a table
+//!
+//! Tables with millions of files are expensive to hold in memory just to answer which files
+//! belong to a partition or may contain a value. A [`SqliteFileIndex`] keeps the `add` actions
+//! of the table (path, partition values and statistics) in a local sqlite database, so that such
+//! questions are answered with indexed queries instead.
+//!
+//! The index records the table version it reflects and is updated incrementally by replaying
+//! the commits added since. Only the initial build loads the table state, from the latest
+//! checkpoint, into memory.
+//!
+//! # Example
+//! ```rust ignore
+//! let index = SqliteFileIndex::open("/var/cache/delta/events.sqlite")?;
+//! index.update(&table.log_store()).await?;
+//! let files = index.files(&[PartitionFilter::try_from(("date", "=", "2024-01-01"))?])?;
+//! ````
+
+use std::collections::HashMap;
+
+use parking_lot::Mutex;
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row};
+use serde_json::Value;
+use tracing::debug;
+
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::kernel::{Action, Add, DataType, Metadata, Scalar};
+use crate::logstore::{get_actions, LogStoreRef};
+use crate::table::state::DeltaTableState;
+use crate::{DeltaTable, DeltaTablePartition, PartitionFilter, PartitionValue};
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS files (
+    path TEXT PRIMARY KEY,
+    size INTEGER NOT NULL,
+    modification_time INTEGER NOT NULL,
+    partition_values TEXT NOT NULL,
+    stats TEXT
+);
+CREATE TABLE IF NOT EXISTS partition_values (
+    path TEXT NOT NULL,
+    name TEXT NOT NULL,
+    value TEXT,
+    PRIMARY KEY (path, name)
+);
+CREATE INDEX IF NOT EXISTS partition_values_by_value ON partition_values (name, value);
+"#;
+
+const SELECT_FILES: &str = "SELECT path, size, modification_time, partition_values, stats, \
+     json_extract(stats, '$.numRecords') FROM files";
+
+const VERSION_KEY: &str = "version";
+const PARTITION_TYPES_KEY: &str = "partitionTypes";
+
+impl From<rusqlite::Error> for DeltaTableError {
+    fn from(err: rusqlite::Error) -> Self {
+        DeltaTableError::GenericError {
+            source: Box::new(err),
+        }
+    }
+}
+
+/// A file of the table as recorded in a [`SqliteFileIndex`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedFile {
+    /// Path of the file relative to the table root
+    pub path: String,
+    /// Size of the file in bytes
+    pub size: i64,
+    /// Time the file was created, in milliseconds since the Unix epoch
+    pub modification_time: i64,
+    /// Partition values of the file
+    pub partition_values: HashMap<String, Option<String>>,
+    /// Number of records in the file, if statistics were collected
+    pub num_records: Option<i64>,
+    /// Statistics of the file as JSON string
+    pub stats: Option<String>,
+}
+
+impl IndexedFile {
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<(Self, String)> {
+        Ok((
+            Self {
+                path: row.get(0)?,
+                size: row.get(1)?,
+                modification_time: row.get(2)?,
+                partition_values: HashMap::new(),
+                num_records: row.get(5)?,
+                stats: row.get(4)?,
+            },
+            row.get(3)?,
+        ))
+    }
+}
+
+/// Sqlite database indexing the files of a table.
+/// See this module's documentation for more information
+#[derive(Debug)]
+pub struct SqliteFileIndex {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteFileIndex {
+    /// Open or create the index stored at `path`
+    pub fn open(path: impl AsRef<std::path::Path>) -> DeltaResult<Self> {
+        Self::try_new(Connection::open(path)?)
+    }
+
+    /// Create an index held in memory
+    pub fn open_in_memory() -> DeltaResult<Self> {
+        Self::try_new(Connection::open_in_memory()?)
+    }
+
+    fn try_new(conn: Connection) -> DeltaResult<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// The table version reflected by the index, `None` if it was never built
+    pub fn version(&self) -> DeltaResult<Option<i64>> {
+        let conn = self.conn.lock();
+        get_meta(&conn, VERSION_KEY)?
+            .map(|version| {
+                version
+                    .parse::<i64>()
+                    .map_err(|err| DeltaTableError::Generic(err.to_string()))
+            })
+            .transpose()
+    }
+
+    /// Bring the index up to date with the latest version of the table, returning that version.
+    ///
+    /// An index which was never built is built from the table state, afterwards only the new
+    /// commits are read.
+    pub async fn update(&self, log_store: &LogStoreRef) -> DeltaResult<i64> {
+        let mut version = match self.version()? {
+            Some(version) => version,
+            None => {
+                let mut table = DeltaTable::new(log_store.clone(), Default::default());
+                table.load().await?;
+                self.rebuild(table.snapshot()?)?;
+                table.version()
+            }
+        };
+        while let Some(commit) = log_store.read_commit_entry(version + 1).await? {
+            let actions = get_actions(version + 1, commit).await?;
+            self.apply(version + 1, &actions)?;
+            version += 1;
+        }
+        Ok(version)
+    }
+
+    /// Replace the content of the index with the files of `snapshot`
+    pub fn rebuild(&self, snapshot: &DeltaTableState) -> DeltaResult<()> {
+        debug!("rebuilding file index at version {}", snapshot.version());
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM files", [])?;
+        tx.execute("DELETE FROM partition_values", [])?;
+        set_partition_types(&tx, snapshot.metadata())?;
+        for add in snapshot.file_actions()? {
+            insert_file(&tx, &add)?;
+        }
+        set_meta(&tx, VERSION_KEY, &snapshot.version().to_string())?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Apply the actions of the commit with `version`, which must directly follow the version
+    /// of the index
+    pub fn apply(&self, version: i64, actions: &[Action]) -> DeltaResult<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let current = get_meta(&tx, VERSION_KEY)?;
+        if current.as_deref() != Some((version - 1).to_string().as_str()) {
+            return Err(DeltaTableError::Generic(format!(
+                "cannot apply version {version} to file index at version {current:?}"
+            )));
+        }
+        for action in actions {
+            match action {
+                Action::Add(add) => insert_file(&tx, add)?,
+                Action::Remove(remove) => delete_file(&tx, &remove.path)?,
+                Action::Metadata(metadata) => set_partition_types(&tx, metadata)?,
+                _ => {}
+            }
+        }
+        set_meta(&tx, VERSION_KEY, &version.to_string())?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Number of files in the table
+    pub fn num_files(&self) -> DeltaResult<u64> {
+        let conn = self.conn.lock();
+        let count: i64 = conn.query_row("SELECT count(*) FROM files", [], |row| row.get(0))?;
+        Ok(count as u64)
+    }
+
+    /// Files matching all partition `filters`
+    pub fn files(&self, filters: &[PartitionFilter]) -> DeltaResult<Vec<IndexedFile>> {
+        let conn = self.conn.lock();
+        let partition_types = get_partition_types(&conn)?;
+
+        // equality filters are pushed into the query, all filters are checked on the results
+        let mut sql = format!("{SELECT_FILES} WHERE 1 = 1");
+        let mut params = Vec::new();
+        for filter in filters {
+            let values = match &filter.value {
+                PartitionValue::Equal(value) if !value.is_empty() => vec![value.clone()],
+                PartitionValue::In(values) => values.clone(),
+                _ => continue,
+            };
+            if matches!(
+                partition_types.get(&filter.key),
+                Some(DataType::Primitive(crate::kernel::PrimitiveType::Timestamp))
+            ) {
+                continue;
+            }
+            sql.push_str(&format!(
+                " AND path IN (SELECT path FROM partition_values WHERE name = ? AND value IN ({}))",
+                vec!["?"; values.len()].join(", ")
+            ));
+            params.push(filter.key.clone());
+            params.extend(values);
+        }
+
+        let files = query_files(&conn, &sql, params_from_iter(params))?;
+        files
+            .into_iter()
+            .filter_map(
+                |file| match matches_filters(&file, filters, &partition_types) {
+                    Ok(true) => Some(Ok(file)),
+                    Ok(false) => None,
+                    Err(err) => Some(Err(err)),
+                },
+            )
+            .collect()
+    }
+
+    /// Files which may contain values of `column` between `min` and `max`, according to their
+    /// statistics. Files without statistics for the column are always included
+    pub fn files_in_range(
+        &self,
+        column: &str,
+        min: &Value,
+        max: &Value,
+    ) -> DeltaResult<Vec<IndexedFile>> {
+        let conn = self.conn.lock();
+        let sql = format!(
+            "{SELECT_FILES} WHERE json_extract(stats, ?1) IS NULL \
+             OR json_extract(stats, ?2) IS NULL \
+             OR (json_extract(stats, ?1) <= ?4 AND json_extract(stats, ?2) >= ?3)"
+        );
+        query_files(
+            &conn,
+            &sql,
+            params![
+                stats_path("minValues", column),
+                stats_path("maxValues", column),
+                sql_value(min)?,
+                sql_value(max)?,
+            ],
+        )
+    }
+}
+
+fn get_meta(conn: &Connection, key: &str) -> rusqlite::Result<Option<String>> {
+    conn.query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+        row.get(0)
+    })
+    .optional()
+}
+
+fn set_meta(conn: &Connection, key: &str, value: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+        [key, value],
+    )?;
+    Ok(())
+}
+
+fn set_partition_types(conn: &Connection, metadata: &Metadata) -> DeltaResult<()> {
+    let schema = metadata.schema()?;
+    let types = metadata
+        .partition_columns
+        .iter()
+        .map(|column| {
+            Ok((
+                column.clone(),
+                schema.field_with_name(column)?.data_type().clone(),
+            ))
+        })
+        .collect::<DeltaResult<HashMap<_, _>>>()?;
+    set_meta(conn, PARTITION_TYPES_KEY, &serde_json::to_string(&types)?)?;
+    Ok(())
+}
+
+fn get_partition_types(conn: &Connection) -> DeltaResult<HashMap<String, DataType>> {
+    match get_meta(conn, PARTITION_TYPES_KEY)? {
+        Some(types) => Ok(serde_json::from_str(&types)?),
+        None => Ok(HashMap::new()),
+    }
+}
+
+fn insert_file(conn: &Connection, add: &Add) -> DeltaResult<()> {
+    delete_file(conn, &add.path)?;
+    conn.execute(
+        "INSERT INTO files (path, size, modification_time, partition_values, stats) \
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            add.path,
+            add.size,
+            add.modification_time,
+            serde_json::to_string(&add.partition_values)?,
+            add.stats,
+        ],
+    )?;
+    for (name, value) in &add.partition_values {
+        conn.execute(
+            "INSERT INTO partition_values (path, name, value) VALUES (?1, ?2, ?3)",
+            params![add.path, name, value],
+        )?;
+    }
+    Ok(())
+}
+
+fn delete_file(conn: &Connection, path: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM files WHERE path = ?1", [path])?;
+    conn.execute("DELETE FROM partition_values WHERE path = ?1", [path])?;
+    Ok(())
+}
+
+fn query_files(
+    conn: &Connection,
+    sql: &str,
+    params: impl rusqlite::Params,
+) -> DeltaResult<Vec<IndexedFile>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map(params, IndexedFile::from_row)?;
+    rows.map(|row| {
+        let (mut file, partition_values) = row?;
+        file.partition_values = serde_json::from_str(&partition_values)?;
+        Ok(file)
+    })
+    .collect()
+}
+
+fn matches_filters(
+    file: &IndexedFile,
+    filters: &[PartitionFilter],
+    partition_types: &HashMap<String, DataType>,
+) -> DeltaResult<bool> {
+    for filter in filters {
+        let data_type = partition_types.get(&filter.key).ok_or_else(|| {
+            DeltaTableError::InvalidPartitionFilter {
+                partition_filter: format!("{filter:?}"),
+            }
+        })?;
+        let DataType::Primitive(primitive) = data_type else {
+            return Err(DeltaTableError::Generic(
+                "nested partitioning values are not supported".to_string(),
+            ));
+        };
+        let value = match file.partition_values.get(&filter.key).cloned().flatten() {
+            Some(value) => primitive.parse_scalar(&value)?,
+            None => Scalar::Null(data_type.clone()),
+        };
+        let partition = DeltaTablePartition::from_partition_value((&filter.key, &value));
+        if !filter.match_partition(&partition, data_type) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn stats_path(stat: &str, column: &str) -> String {
+    let column = column
+        .split('.')
+        .map(|part| format!("\"{part}\""))
+        .collect::<Vec<_>>()
+        .join(".");
+    format!("$.{stat}.{column}")
+}
+
+fn sql_value(value: &Value) -> DeltaResult<SqlValue> {
+    match value {
+        Value::Number(number) => Ok(match number.as_i64() {
+            Some(value) => SqlValue::Integer(value),
+            None => SqlValue::Real(number.as_f64().unwrap_or(f64::NAN)),
+        }),
+        Value::String(value) => Ok(SqlValue::Text(value.clone())),
+        Value::Bool(value) => Ok(SqlValue::Integer(*value as i64)),
+        _ => Err(DeltaTableError::Generic(format!(
+            "unsupported statistics value {value}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::writer::test_utils::{create_initialized_table, get_record_batch};
+    use crate::writer::{DeltaWriter, RecordBatchWriter};
+
+    #[tokio::test]
+    async fn test_sqlite_file_index() {
+        let mut table = create_initialized_table(&["modified".to_string()]).await;
+        let index = SqliteFileIndex::open_in_memory().unwrap();
+        assert_eq!(index.version().unwrap(), None);
+        assert_eq!(index.update(&table.log_store()).await.unwrap(), 0);
+        assert_eq!(index.num_files().unwrap(), 0);
+
+        let mut writer = RecordBatchWriter::for_table(&table).unwrap();
+        writer.write(get_record_batch(None, false)).await.unwrap();
+        writer.flush_and_commit(&mut table).await.unwrap();
+
+        assert_eq!(index.update(&table.log_store()).await.unwrap(), 1);
+        assert_eq!(
+            index.num_files().unwrap() as usize,
+            table.get_files_count().unwrap()
+        );
+
+        let filter = PartitionFilter::try_from(("modified", "=", "2021-02-02")).unwrap();
+        let files = index.files(&[filter]).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(
+            files[0].partition_values.get("modified"),
+            Some(&Some("2021-02-02".to_string()))
+        );
+        assert!(files[0].num_records.is_some());
+
+        let filter = PartitionFilter::try_from(("modified", ">", "2021-02-01")).unwrap();
+        assert_eq!(index.files(&[filter]).unwrap().len(), 1);
+
+        let files = index
+            .files_in_range("value", &Value::from(100), &Value::from(200))
+            .unwrap();
+        assert!(files.is_empty());
+        let files = index
+            .files_in_range("value", &Value::from(1), &Value::from(2))
+            .unwrap();
+        assert!(!files.is_empty());
+
+        // a fresh index built from the table state matches the incrementally updated one
+        let rebuilt = SqliteFileIndex::open_in_memory().unwrap();
+        assert_eq!(rebuilt.update(&table.log_store()).await.unwrap(), 1);
+        assert_eq!(rebuilt.num_files().unwrap(), index.num_files().unwrap());
+    }
+}
@@ -2,17 +2,20 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::SystemTime;
 
-use chrono::Utc;
-use futures::TryStreamExt;
+use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
 use object_store::{path::Path, ObjectStore};
 use serde::{Deserialize, Serialize};
 
 use super::config::TableConfig;
+use super::file_tags::{self, FileTagFilter};
 use super::{get_partition_col_data_types, DeltaTableConfig};
 use crate::kernel::{
-    Action, Add, DataType, EagerSnapshot, LogDataHandler, LogicalFile, Metadata, Protocol, Remove,
-    StructType,
+    Action, Add, DataType, DomainMetadata, EagerSnapshot, LogDataHandler, LogicalFile, Metadata,
+    Protocol, Remove, Scalar, StructType,
 };
 use crate::logstore::LogStore;
 use crate::operations::transaction::CommitData;
@@ -55,6 +58,18 @@ impl DeltaTableState {
         self.snapshot.version_timestamp(version)
     }
 
+    /// Get the timestamp when a version commit was created, as [`chrono::DateTime`].
+    pub fn version_datetime(&self, version: i64) -> Option<DateTime<Utc>> {
+        self.version_timestamp(version)
+            .and_then(DateTime::<Utc>::from_timestamp_millis)
+    }
+
+    /// Get the timestamp when a version commit was created, as [`std::time::SystemTime`].
+    pub fn version_system_time(&self, version: i64) -> Option<SystemTime> {
+        self.version_timestamp(version)
+            .map(crate::protocol::time_utils::system_time_from_millis)
+    }
+
     /// Construct a delta table state object from a list of actions
     #[cfg(test)]
     pub fn from_actions(actions: Vec<Action>) -> DeltaResult<Self> {
@@ -94,7 +109,7 @@ impl DeltaTableState {
     }
 
     /// Returns a semantic accessor to the currently loaded log data.
-    pub fn log_data(&self) -> LogDataHandler<'_> {
+    pub fn log_data(&self) -> DeltaResult<LogDataHandler<'_>> {
         self.snapshot.log_data()
     }
 
@@ -136,17 +151,65 @@ impl DeltaTableState {
         Ok(self.snapshot.file_actions()?.collect())
     }
 
+    /// Stream the add actions of the files of the table, optionally only of the partitions
+    /// matching all `filters`.
+    ///
+    /// Unlike [`Self::file_actions`] the files are read from the log, one record batch at a
+    /// time, instead of the loaded state. This bounds the memory used for tables with millions
+    /// of files, and works for tables loaded without files, see
+    /// [`DeltaTableBuilder::without_files`](crate::DeltaTableBuilder::without_files).
+    pub fn files_iter<'a>(
+        &'a self,
+        store: Arc<dyn ObjectStore>,
+        filters: &'a [PartitionFilter],
+    ) -> DeltaResult<BoxStream<'a, DeltaResult<Add>>> {
+        let metadata = self.metadata();
+        let nonpartitioned_columns: Vec<String> = filters
+            .iter()
+            .filter(|f| !metadata.partition_columns.contains(&f.key))
+            .map(|f| f.key.to_string())
+            .collect();
+        if !nonpartitioned_columns.is_empty() {
+            return Err(DeltaTableError::ColumnsNotPartitioned {
+                nonpartitioned_columns,
+            });
+        }
+
+        let partition_col_data_types: HashMap<&String, &DataType> =
+            get_partition_col_data_types(self.schema(), metadata)
+                .into_iter()
+                .collect();
+        let stream = self.snapshot.snapshot().file_actions_stream(store)?;
+        if filters.is_empty() {
+            return Ok(stream);
+        }
+        Ok(stream
+            .try_filter_map(move |add| {
+                let partitions = add_partitions(&add, &partition_col_data_types);
+                futures::future::ready(partitions.map(|partitions| {
+                    filters
+                        .iter()
+                        .all(|filter| {
+                            filter.match_partitions(&partitions, &partition_col_data_types)
+                        })
+                        .then_some(add)
+                }))
+            })
+            .boxed())
+    }
+
     /// Get the number of files in the current table state
-    pub fn files_count(&self) -> usize {
+    pub fn files_count(&self) -> DeltaResult<usize> {
         self.snapshot.files_count()
     }
 
     /// Returns an iterator of file names present in the loaded state
     #[inline]
-    pub fn file_paths_iter(&self) -> impl Iterator<Item = Path> + '_ {
-        self.log_data()
+    pub fn file_paths_iter(&self) -> DeltaResult<impl Iterator<Item = Path> + '_> {
+        Ok(self
+            .log_data()?
             .into_iter()
-            .map(|add| add.object_store_path())
+            .map(|add| add.object_store_path()))
     }
 
     /// HashMap containing the last txn version stored for every app id writing txn
@@ -155,6 +218,14 @@ impl DeltaTableState {
         &self.app_transaction_version
     }
 
+    /// The latest configuration of every metadata domain of the table, keyed by domain.
+    pub async fn domain_metadata(
+        &self,
+        store: Arc<dyn ObjectStore>,
+    ) -> DeltaResult<HashMap<String, DomainMetadata>> {
+        self.snapshot.snapshot().domain_metadata(store).await
+    }
+
     /// The most recent protocol of the table.
     pub fn protocol(&self) -> &Protocol {
         self.snapshot.protocol()
@@ -241,7 +312,7 @@ impl DeltaTableState {
                 .into_iter()
                 .collect();
 
-        Ok(self.log_data().into_iter().filter_map(move |add| {
+        Ok(self.log_data()?.into_iter().filter_map(move |add| {
             let partitions = add.partition_values();
             if partitions.is_err() {
                 return Some(Err(DeltaTableError::Generic(
@@ -264,4 +335,57 @@ impl DeltaTableState {
             }
         }))
     }
+    /// Get the files of the table whose tags match all `filters`
+    pub fn get_active_add_actions_by_tags<'a>(
+        &'a self,
+        filters: &'a [FileTagFilter],
+    ) -> DeltaResult<impl Iterator<Item = LogicalFile<'a>> + 'a> {
+        Ok(self
+            .log_data()?
+            .into_iter()
+            .filter(move |file| file_tags::matches_all(filters, file.tags().as_ref())))
+    }
+
+    /// Get the files of the table whose deletion vectors mark at least `threshold`, a fraction
+    /// between 0 and 1, of their records as deleted. These files are the candidates to rewrite
+    /// for bounding the read amplification of deletion vectors
+    pub fn files_by_deletion_vector_coverage(
+        &self,
+        threshold: f64,
+    ) -> DeltaResult<impl Iterator<Item = LogicalFile<'_>> + '_> {
+        Ok(self.log_data()?.into_iter().filter(move |file| {
+            file.deletion_vector().is_some()
+                && file
+                    .deletion_vector_coverage()
+                    .is_some_and(|coverage| coverage >= threshold)
+        }))
+    }
+}
+
+/// The typed partition values of `add`
+fn add_partitions(
+    add: &Add,
+    partition_col_data_types: &HashMap<&String, &DataType>,
+) -> DeltaResult<Vec<DeltaTablePartition>> {
+    add.partition_values
+        .iter()
+        .map(|(key, value)| {
+            let data_type = partition_col_data_types.get(key).ok_or_else(|| {
+                DeltaTableError::Generic(format!("Unknown partition column '{key}'"))
+            })?;
+            let value = match (data_type, value) {
+                (DataType::Primitive(primitive), Some(value)) => primitive.parse_scalar(value)?,
+                (_, None) => Scalar::Null((*data_type).clone()),
+                _ => {
+                    return Err(DeltaTableError::Generic(
+                        "nested partitioning values are not supported".to_string(),
+                    ))
+                }
+            };
+            Ok(DeltaTablePartition::from_partition_value((
+                key.as_str(),
+                &value,
+            )))
+        })
+        .collect()
 }
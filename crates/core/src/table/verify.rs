@@ -0,0 +1,374 @@
+//! Verify the integrity of a loaded table snapshot.
+//!
+//! Verification never modifies the table, it only collects violations of the invariants
+//! defined by the Delta protocol into a [`VerificationReport`], which can be used for
+//! operational audits of tables written by different engines.
+
+use std::collections::HashSet;
+
+use futures::{StreamExt, TryStreamExt};
+use object_store::ObjectStore;
+use serde::Serialize;
+
+use super::state::DeltaTableState;
+use crate::kernel::{Action, DeletionVectorDescriptor, Protocol};
+use crate::logstore::{get_actions, LogStoreRef};
+use crate::operations::transaction::PROTOCOL;
+use crate::{DeltaResult, ObjectStoreError};
+
+const HEAD_CONCURRENCY: usize = 16;
+
+/// Thoroughness of [`DeltaTable::verify`](super::DeltaTable::verify)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum VerificationLevel {
+    /// Only check the loaded snapshot: active files, statistics and protocol
+    Snapshot,
+    /// Additionally check every commit still present in the log for conflicting file actions
+    Log,
+    /// Additionally issue a HEAD request for every active file to ensure it exists
+    Full,
+}
+
+/// A single violated invariant
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum VerificationIssue {
+    /// The same file is added more than once, either in the snapshot or in a single commit
+    #[serde(rename_all = "camelCase")]
+    DuplicateAdd {
+        /// Path of the file
+        path: String,
+        /// The commit containing the duplicates, `None` for the loaded snapshot
+        version: Option<i64>,
+    },
+    /// A single commit both adds and removes the same file
+    #[serde(rename_all = "camelCase")]
+    AddAndRemove {
+        /// Path of the file
+        path: String,
+        /// The commit containing both actions
+        version: i64,
+    },
+    /// An active file does not exist in the object store
+    #[serde(rename_all = "camelCase")]
+    MissingFile {
+        /// Path of the file
+        path: String,
+    },
+    /// The statistics of an active file cannot be parsed
+    #[serde(rename_all = "camelCase")]
+    InvalidStats {
+        /// Path of the file
+        path: String,
+        /// Error raised while parsing
+        message: String,
+    },
+    /// The protocol action is inconsistent or not supported by this crate
+    #[serde(rename_all = "camelCase")]
+    InvalidProtocol {
+        /// Description of the problem
+        message: String,
+    },
+    /// A partition column is missing from the table schema
+    #[serde(rename_all = "camelCase")]
+    UnknownPartitionColumn {
+        /// Name of the partition column
+        column: String,
+    },
+}
+
+/// Result of verifying a table snapshot
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationReport {
+    /// Verified version of the table
+    pub version: i64,
+    /// Level the verification was run with
+    pub level: VerificationLevel,
+    /// Number of active files that were checked
+    pub files_checked: usize,
+    /// Number of commits that were checked
+    pub commits_checked: usize,
+    /// All violations found
+    pub issues: Vec<VerificationIssue>,
+}
+
+impl VerificationReport {
+    /// Whether no issues were found
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Files are identified by their path and deletion vector, see the protocol on reconciliation
+fn file_key(path: &str, dv: Option<&DeletionVectorDescriptor>) -> (String, Option<String>) {
+    let dv = dv.map(|dv| {
+        format!(
+            "{}{}@{:?}",
+            dv.storage_type.as_ref(),
+            dv.path_or_inline_dv,
+            dv.offset
+        )
+    });
+    (path.to_string(), dv)
+}
+
+fn verify_protocol(protocol: &Protocol, issues: &mut Vec<VerificationIssue>) {
+    let mut invalid = |message: String| issues.push(VerificationIssue::InvalidProtocol { message });
+
+    match (protocol.min_reader_version, &protocol.reader_features) {
+        (3.., None) => invalid("reader version 3 requires reader features".to_string()),
+        // the log replay reads absent feature lists as empty sets
+        (..=2, Some(features)) if !features.is_empty() => invalid(format!(
+            "reader features require reader version 3, found {}",
+            protocol.min_reader_version
+        )),
+        _ => {}
+    }
+    match (protocol.min_writer_version, &protocol.writer_features) {
+        (7.., None) => invalid("writer version 7 requires writer features".to_string()),
+        (..=6, Some(features)) if !features.is_empty() => invalid(format!(
+            "writer features require writer version 7, found {}",
+            protocol.min_writer_version
+        )),
+        _ => {}
+    }
+    if protocol.min_reader_version >= 3 && protocol.min_writer_version < 7 {
+        invalid("reader version 3 requires writer version 7".to_string());
+    }
+    if let (Some(reader), Some(writer)) = (&protocol.reader_features, &protocol.writer_features) {
+        let writer = writer.iter().map(|f| f.as_ref()).collect::<HashSet<_>>();
+        for feature in reader {
+            if !writer.contains(feature.as_ref()) {
+                invalid(format!(
+                    "reader feature '{feature}' is missing from writer features"
+                ));
+            }
+        }
+    }
+}
+
+fn verify_snapshot(
+    snapshot: &DeltaTableState,
+    issues: &mut Vec<VerificationIssue>,
+) -> DeltaResult<usize> {
+    verify_protocol(snapshot.protocol(), issues);
+    if let Err(err) = PROTOCOL.can_read_from(snapshot) {
+        issues.push(VerificationIssue::InvalidProtocol {
+            message: err.to_string(),
+        });
+    }
+
+    let schema = snapshot.schema();
+    for column in &snapshot.metadata().partition_columns {
+        if schema.field_with_name(column).is_err() {
+            issues.push(VerificationIssue::UnknownPartitionColumn {
+                column: column.clone(),
+            });
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut files_checked = 0;
+    for add in snapshot.file_actions()? {
+        files_checked += 1;
+        if !seen.insert(add.path.clone()) {
+            issues.push(VerificationIssue::DuplicateAdd {
+                path: add.path.clone(),
+                version: None,
+            });
+        }
+        if let Err(err) = add.get_stats() {
+            issues.push(VerificationIssue::InvalidStats {
+                path: add.path.clone(),
+                message: err.to_string(),
+            });
+        }
+    }
+    Ok(files_checked)
+}
+
+fn verify_commit(version: i64, actions: &[Action], issues: &mut Vec<VerificationIssue>) {
+    let mut added = HashSet::new();
+    let mut removed = HashSet::new();
+    for action in actions {
+        match action {
+            Action::Add(add) => {
+                let key = file_key(&add.path, add.deletion_vector.as_ref());
+                if !added.insert(key) {
+                    issues.push(VerificationIssue::DuplicateAdd {
+                        path: add.path.clone(),
+                        version: Some(version),
+                    });
+                }
+            }
+            Action::Remove(remove) => {
+                removed.insert(file_key(&remove.path, remove.deletion_vector.as_ref()));
+            }
+            _ => {}
+        }
+    }
+    let mut conflicts = added
+        .into_iter()
+        .filter(|key| removed.contains(key))
+        .map(|(path, _)| path)
+        .collect::<Vec<_>>();
+    conflicts.sort();
+    issues.extend(
+        conflicts
+            .into_iter()
+            .map(|path| VerificationIssue::AddAndRemove { path, version }),
+    );
+}
+
+/// Check all commits up to the snapshot version that have not been cleaned up yet
+async fn verify_log(
+    log_store: &LogStoreRef,
+    snapshot: &DeltaTableState,
+    issues: &mut Vec<VerificationIssue>,
+) -> DeltaResult<usize> {
+    let mut commits_checked = 0;
+    for version in (0..=snapshot.version()).rev() {
+        let Some(bytes) = log_store.read_commit_entry(version).await? else {
+            break;
+        };
+        let actions = get_actions(version, bytes).await?;
+        verify_commit(version, &actions, issues);
+        commits_checked += 1;
+    }
+    Ok(commits_checked)
+}
+
+async fn verify_files_exist(
+    log_store: &LogStoreRef,
+    snapshot: &DeltaTableState,
+    issues: &mut Vec<VerificationIssue>,
+) -> DeltaResult<()> {
+    let store = log_store.object_store();
+    let mut missing = futures::stream::iter(
+        snapshot
+            .log_data()?
+            .into_iter()
+            .map(|file| (file.path().to_string(), file.object_store_path())),
+    )
+    // files referenced by absolute uri may live in a different store
+    .filter(|(path, _)| futures::future::ready(!path.contains("://")))
+    .map(|(path, location)| {
+        let store = store.clone();
+        async move {
+            match store.head(&location).await {
+                Ok(_) => Ok(None),
+                Err(ObjectStoreError::NotFound { .. }) => Ok(Some(path)),
+                Err(err) => Err(err),
+            }
+        }
+    })
+    .buffer_unordered(HEAD_CONCURRENCY)
+    .try_filter_map(|path| futures::future::ready(Ok(path)))
+    .try_collect::<Vec<_>>()
+    .await?;
+    missing.sort();
+    issues.extend(
+        missing
+            .into_iter()
+            .map(|path| VerificationIssue::MissingFile { path }),
+    );
+    Ok(())
+}
+
+pub(crate) async fn verify_table(
+    log_store: &LogStoreRef,
+    snapshot: &DeltaTableState,
+    level: VerificationLevel,
+) -> DeltaResult<VerificationReport> {
+    let mut issues = Vec::new();
+    let files_checked = verify_snapshot(snapshot, &mut issues)?;
+    let commits_checked = if level >= VerificationLevel::Log {
+        verify_log(log_store, snapshot, &mut issues).await?
+    } else {
+        0
+    };
+    if level >= VerificationLevel::Full {
+        verify_files_exist(log_store, snapshot, &mut issues).await?;
+    }
+    Ok(VerificationReport {
+        version: snapshot.version(),
+        level,
+        files_checked,
+        commits_checked,
+        issues,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::{Add, Remove};
+
+    fn add(path: &str) -> Action {
+        Action::Add(Add {
+            path: path.to_string(),
+            data_change: true,
+            ..Default::default()
+        })
+    }
+
+    fn remove(path: &str) -> Action {
+        Action::Remove(Remove {
+            path: path.to_string(),
+            data_change: true,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_verify_commit() {
+        let mut issues = Vec::new();
+        verify_commit(
+            3,
+            &[
+                add("a.parquet"),
+                add("a.parquet"),
+                add("b.parquet"),
+                remove("b.parquet"),
+            ],
+            &mut issues,
+        );
+        assert_eq!(
+            issues,
+            vec![
+                VerificationIssue::DuplicateAdd {
+                    path: "a.parquet".to_string(),
+                    version: Some(3)
+                },
+                VerificationIssue::AddAndRemove {
+                    path: "b.parquet".to_string(),
+                    version: 3
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_verify_protocol() {
+        let mut issues = Vec::new();
+        verify_protocol(&Protocol::new(1, 2), &mut issues);
+        assert!(issues.is_empty());
+
+        verify_protocol(&Protocol::new(3, 2), &mut issues);
+        assert_eq!(issues.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_verify_table() {
+        let table = crate::open_table("../test/tests/data/simple_table")
+            .await
+            .unwrap();
+        let report = table.verify(VerificationLevel::Full).await.unwrap();
+        assert!(report.is_valid(), "{:?}", report.issues);
+        assert_eq!(report.version, 4);
+        assert_eq!(report.commits_checked, 5);
+        assert_eq!(report.files_checked, table.get_files_count().unwrap());
+    }
+}
@@ -0,0 +1,230 @@
+//! Naming schemes of written data files
+//!
+//! Writers name data files `part-<counter>-<writer id>-c000<compression>.parquet` by default,
+//! following the convention of Spark. Downstream tooling which lists files in lexicographic
+//! order may require a different scheme, which is provided by a [`FileNameStrategy`] set on the
+//! [`WriterConfig`](crate::operations::writer::WriterConfig) or with
+//! [`WriteBuilder::with_file_name_strategy`](crate::operations::write::WriteBuilder::with_file_name_strategy).
+//! [`CustomFileNames`] covers the common variations: a fixed prefix, a custom extension,
+//! zero-padded counters and time-sortable UUID v7 ids.
+//!
+//! # Example
+//! ```rust ignore
+//! let names = CustomFileNames::default()
+//!     .with_prefix("ingest-")
+//!     .with_counter_width(0)
+//!     .with_time_sortable_ids(true);
+//! let (table, _) = DeltaOps(table)
+//!     .write(batches)
+//!     .with_file_name_strategy(Arc::new(names))
+//!     .await?;
+//! ````
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use parquet::schema::types::ColumnPath;
+use uuid::{NoContext, Timestamp, Uuid};
+
+use crate::operations::vacuum::Clock;
+
+/// Information about the file being named
+#[derive(Debug, Clone, Copy)]
+pub struct FileNameContext<'a> {
+    /// Id of the writer, shared by all files of a writer
+    pub writer_id: &'a Uuid,
+    /// Number of the file among the files the writer wrote to the partition
+    pub part_count: usize,
+    /// Properties of the parquet writer the file is written with
+    pub writer_properties: &'a WriterProperties,
+}
+
+impl FileNameContext<'_> {
+    /// Extension of the compression codec of the file, e.g. `.snappy`, empty when uncompressed
+    pub fn compression_extension(&self) -> &'static str {
+        // We can not access the default column properties but the current implementation will return
+        // the default compression when the column is not found
+        let column_path = ColumnPath::new(Vec::new());
+        match self.writer_properties.compression(&column_path) {
+            // This is to match HADOOP's convention
+            // https://github.com/apache/parquet-mr/blob/c4977579ab3b149ea045a177b039f055b5408e8f/parquet-common/src/main/java/org/apache/parquet/hadoop/metadata/CompressionCodecName.java#L27-L34
+            Compression::UNCOMPRESSED => "",
+            Compression::SNAPPY => ".snappy",
+            Compression::GZIP(_) => ".gz",
+            Compression::LZO => ".lzo",
+            Compression::BROTLI(_) => ".br",
+            Compression::LZ4 => ".lz4",
+            Compression::ZSTD(_) => ".zstd",
+            Compression::LZ4_RAW => ".lz4raw",
+        }
+    }
+}
+
+/// Scheme to name the data files written to a table
+pub trait FileNameStrategy: Debug + Send + Sync {
+    /// Name of the next data file, without the directory it is written to
+    fn file_name(&self, context: &FileNameContext<'_>) -> String;
+}
+
+/// The default naming scheme, `part-00001-<writer id>-c000.snappy.parquet`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeltaFileNames;
+
+impl FileNameStrategy for DeltaFileNames {
+    fn file_name(&self, context: &FileNameContext<'_>) -> String {
+        // TODO: what does c000 mean?
+        format!(
+            "part-{:0>5}-{}-c000{}.parquet",
+            context.part_count,
+            context.writer_id,
+            context.compression_extension()
+        )
+    }
+}
+
+/// A configurable naming scheme, `<prefix><counter>-<id><extension>`
+///
+/// Names sort by write time when the counter is disabled and ids are time-sortable
+#[derive(Debug, Clone)]
+pub struct CustomFileNames {
+    prefix: String,
+    extension: Option<String>,
+    counter_width: usize,
+    time_sortable_ids: bool,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl Default for CustomFileNames {
+    fn default() -> Self {
+        Self {
+            prefix: "part-".to_string(),
+            extension: None,
+            counter_width: 5,
+            time_sortable_ids: false,
+            clock: None,
+        }
+    }
+}
+
+impl CustomFileNames {
+    /// Set the prefix of every file name, `part-` by default
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Set the extension of every file name, including the leading dot. By default the
+    /// extension is the compression codec followed by `.parquet`, e.g. `.snappy.parquet`
+    pub fn with_extension(mut self, extension: impl Into<String>) -> Self {
+        self.extension = Some(extension.into());
+        self
+    }
+
+    /// Set the number of digits the counter is zero-padded to, `0` leaves the counter out
+    pub fn with_counter_width(mut self, counter_width: usize) -> Self {
+        self.counter_width = counter_width;
+        self
+    }
+
+    /// Use a time-sortable UUID v7 generated for every file as id, rather than the id of the writer
+    pub fn with_time_sortable_ids(mut self, time_sortable_ids: bool) -> Self {
+        self.time_sortable_ids = time_sortable_ids;
+        self
+    }
+
+    /// add a time source for the time-sortable ids, for testing
+    #[doc(hidden)]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+}
+
+impl FileNameStrategy for CustomFileNames {
+    fn file_name(&self, context: &FileNameContext<'_>) -> String {
+        let id = match (self.time_sortable_ids, &self.clock) {
+            (false, _) => *context.writer_id,
+            (true, None) => Uuid::now_v7(),
+            (true, Some(clock)) => {
+                let millis = clock.current_timestamp_millis().max(0) as u64;
+                let nanos = (millis % 1000) as u32 * 1_000_000;
+                Uuid::new_v7(Timestamp::from_unix(NoContext, millis / 1000, nanos))
+            }
+        };
+        let counter = match self.counter_width {
+            0 => String::new(),
+            width => format!("{:0>width$}-", context.part_count),
+        };
+        let extension = match &self.extension {
+            Some(extension) => extension.clone(),
+            None => format!("{}.parquet", context.compression_extension()),
+        };
+        format!("{}{counter}{id}{extension}", self.prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct FixedClock(i64);
+
+    impl Clock for FixedClock {
+        fn current_timestamp_millis(&self) -> i64 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_custom_file_names() {
+        let writer_id = Uuid::parse_str("02f09a3f-1624-3b1d-8409-44eff7708208").unwrap();
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let context = FileNameContext {
+            writer_id: &writer_id,
+            part_count: 7,
+            writer_properties: &props,
+        };
+
+        assert_eq!(
+            DeltaFileNames.file_name(&context),
+            "part-00007-02f09a3f-1624-3b1d-8409-44eff7708208-c000.snappy.parquet"
+        );
+        assert_eq!(
+            CustomFileNames::default().file_name(&context),
+            "part-00007-02f09a3f-1624-3b1d-8409-44eff7708208.snappy.parquet"
+        );
+        assert_eq!(
+            CustomFileNames::default()
+                .with_prefix("ingest-")
+                .with_extension(".parq")
+                .with_counter_width(8)
+                .file_name(&context),
+            "ingest-00000007-02f09a3f-1624-3b1d-8409-44eff7708208.parq"
+        );
+
+        let names = CustomFileNames::default()
+            .with_prefix("")
+            .with_counter_width(0)
+            .with_time_sortable_ids(true);
+        let first = names
+            .clone()
+            .with_clock(Arc::new(FixedClock(1_700_000_000_999)))
+            .file_name(&context);
+        let second = names
+            .with_clock(Arc::new(FixedClock(1_700_000_001_000)))
+            .file_name(&context);
+        assert!(first < second);
+        assert!(first.ends_with(".snappy.parquet"));
+        let id = Uuid::parse_str(first.trim_end_matches(".snappy.parquet")).unwrap();
+        assert_eq!(id.get_version_num(), 7);
+        assert_eq!(
+            id.get_timestamp().unwrap().to_unix(),
+            (1_700_000_000, 999_000_000)
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! Write record batches to a delta table from a background task
+//!
+//! A [`DeltaWriterHandle`] decouples producers, e.g. request handlers of an async service, from
+//! file and log IO. Batches sent through the handle are queued in a bounded channel, so
+//! [`DeltaWriterHandle::send`] waits when the background task falls behind. The task buffers
+//! batches with a [`RecordBatchWriter`] and commits them whenever the buffer exceeds the
+//! configured size, the commit interval elapses or a flush is requested.
+//!
+//! If writing or committing fails the task stops: pending and further sends fail and the error
+//! is returned by [`DeltaWriterHandle::close`].
+//!
+//! # Example
+//! ```rust ignore
+//! let handle = DeltaWriterHandle::spawn(table, WriterHandleConfig::default())?;
+//! handle.send(batch).await?;
+//! let version = handle.flush().await?;
+//! let table = handle.close().await?;
+//! ````
+
+use std::time::Duration;
+
+use arrow::record_batch::RecordBatch;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+use super::{DeltaWriter, RecordBatchWriter};
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::DeltaTable;
+
+/// Configuration of a [`DeltaWriterHandle`]
+#[derive(Debug, Clone)]
+pub struct WriterHandleConfig {
+    /// Number of batches queued before senders wait for the background task
+    pub channel_capacity: usize,
+    /// Buffered size in bytes after which batches are committed
+    pub max_buffer_size: usize,
+    /// Interval after which buffered batches are committed
+    pub commit_interval: Duration,
+}
+
+impl Default for WriterHandleConfig {
+    fn default() -> Self {
+        Self {
+            channel_capacity: 64,
+            max_buffer_size: 128 * 1024 * 1024,
+            commit_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+enum Message {
+    Write(RecordBatch),
+    Flush(oneshot::Sender<DeltaResult<Option<i64>>>),
+}
+
+fn stopped() -> DeltaTableError {
+    DeltaTableError::Generic("The background writer task has stopped".to_string())
+}
+
+/// Sends record batches to a background task writing them to a delta table.
+/// See this module's documentation for more information
+#[derive(Debug)]
+pub struct DeltaWriterHandle {
+    sender: mpsc::Sender<Message>,
+    task: JoinHandle<DeltaResult<DeltaTable>>,
+}
+
+impl std::fmt::Debug for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Write(batch) => write!(f, "Write({} rows)", batch.num_rows()),
+            Self::Flush(_) => write!(f, "Flush"),
+        }
+    }
+}
+
+impl DeltaWriterHandle {
+    /// Spawn a background task writing to `table` on the current tokio runtime
+    pub fn spawn(table: DeltaTable, config: WriterHandleConfig) -> DeltaResult<Self> {
+        let writer = RecordBatchWriter::for_table(&table)?;
+        let (sender, receiver) = mpsc::channel(config.channel_capacity.max(1));
+        let task = tokio::spawn(run(table, writer, receiver, config));
+        Ok(Self { sender, task })
+    }
+
+    /// Queue a batch for writing, waiting while the queue is full
+    pub async fn send(&self, batch: RecordBatch) -> DeltaResult<()> {
+        self.sender
+            .send(Message::Write(batch))
+            .await
+            .map_err(|_| stopped())
+    }
+
+    /// Queue a batch for writing, failing if the queue is full
+    pub fn try_send(&self, batch: RecordBatch) -> DeltaResult<()> {
+        self.sender
+            .try_send(Message::Write(batch))
+            .map_err(|err| match err {
+                mpsc::error::TrySendError::Full(_) => {
+                    DeltaTableError::Generic("The writer queue is full".to_string())
+                }
+                mpsc::error::TrySendError::Closed(_) => stopped(),
+            })
+    }
+
+    /// Commit all batches sent so far, returning the committed version if any were buffered
+    pub async fn flush(&self) -> DeltaResult<Option<i64>> {
+        let (sender, receiver) = oneshot::channel();
+        self.sender
+            .send(Message::Flush(sender))
+            .await
+            .map_err(|_| stopped())?;
+        receiver.await.map_err(|_| stopped())?
+    }
+
+    /// Commit all batches sent so far and stop the background task, returning the table
+    pub async fn close(self) -> DeltaResult<DeltaTable> {
+        drop(self.sender);
+        self.task
+            .await
+            .map_err(|err| DeltaTableError::GenericError {
+                source: Box::new(err),
+            })?
+    }
+}
+
+async fn commit(
+    writer: &mut RecordBatchWriter,
+    table: &mut DeltaTable,
+) -> DeltaResult<Option<i64>> {
+    if writer.buffered_record_batch_count() == 0 {
+        return Ok(None);
+    }
+    let version = writer.flush_and_commit(table).await?;
+    debug!("background writer committed version {version}");
+    Ok(Some(version))
+}
+
+async fn run(
+    mut table: DeltaTable,
+    mut writer: RecordBatchWriter,
+    mut receiver: mpsc::Receiver<Message>,
+    config: WriterHandleConfig,
+) -> DeltaResult<DeltaTable> {
+    let mut interval = tokio::time::interval(config.commit_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // the first tick completes immediately
+    interval.tick().await;
+
+    loop {
+        tokio::select! {
+            message = receiver.recv() => match message {
+                Some(Message::Write(batch)) => {
+                    writer.write(batch).await?;
+                    if writer.buffer_len() >= config.max_buffer_size {
+                        commit(&mut writer, &mut table).await?;
+                        interval.reset();
+                    }
+                }
+                Some(Message::Flush(reply)) => {
+                    let result = commit(&mut writer, &mut table).await;
+                    interval.reset();
+                    match result {
+                        Ok(version) => {
+                            let _ = reply.send(Ok(version));
+                        }
+                        Err(err) => {
+                            let _ = reply.send(Err(DeltaTableError::Generic(err.to_string())));
+                            return Err(err);
+                        }
+                    }
+                }
+                None => {
+                    commit(&mut writer, &mut table).await?;
+                    return Ok(table);
+                }
+            },
+            _ = interval.tick() => {
+                commit(&mut writer, &mut table).await?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::writer::test_utils::{create_initialized_table, get_record_batch};
+
+    #[tokio::test]
+    async fn test_writer_handle() {
+        let table = create_initialized_table(&[]).await;
+        let handle = DeltaWriterHandle::spawn(table, WriterHandleConfig::default()).unwrap();
+
+        handle.send(get_record_batch(None, false)).await.unwrap();
+        handle.try_send(get_record_batch(None, false)).unwrap();
+        assert_eq!(handle.flush().await.unwrap(), Some(1));
+        assert_eq!(handle.flush().await.unwrap(), None);
+
+        handle.send(get_record_batch(None, false)).await.unwrap();
+        let table = handle.close().await.unwrap();
+        assert_eq!(table.version(), 2);
+        assert_eq!(table.get_files_count().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_writer_handle_commits_on_size() {
+        let table = create_initialized_table(&[]).await;
+        let config = WriterHandleConfig {
+            max_buffer_size: 1,
+            ..Default::default()
+        };
+        let handle = DeltaWriterHandle::spawn(table, config).unwrap();
+
+        for _ in 0..3 {
+            handle.send(get_record_batch(None, false)).await.unwrap();
+        }
+        let table = handle.close().await.unwrap();
+        assert_eq!(table.version(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_writer_handle_commits_on_interval() {
+        let table = create_initialized_table(&[]).await;
+        let config = WriterHandleConfig {
+            commit_interval: Duration::from_millis(50),
+            ..Default::default()
+        };
+        let handle = DeltaWriterHandle::spawn(table, config).unwrap();
+
+        handle.send(get_record_batch(None, false)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(handle.flush().await.unwrap(), None);
+        assert_eq!(handle.close().await.unwrap().version(), 1);
+    }
+}
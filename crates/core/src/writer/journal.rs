@@ -0,0 +1,346 @@
+//! Local journal of in-progress writes used to recover after a crash
+//!
+//! A [`RecordBatchWriter`](super::RecordBatchWriter) configured with a [`WriteJournal`] records
+//! every data file before and after uploading it, and seals the file set right before committing
+//! it. The commit carries an application transaction with the journal's app id, so a restarted
+//! writer can tell whether a sealed file set made it into the log.
+//!
+//! On restart [`RecordBatchWriter::recover`](super::RecordBatchWriter::recover) inspects the
+//! journal: a sealed file set which was not committed is committed, while files of an unsealed
+//! set are deleted from the table's storage unless they are part of the table. The journal is
+//! cleared afterwards.
+//!
+//! # Example
+//! ```rust ignore
+//! let journal = WriteJournal::open("/var/lib/ingest/journal", "ingest-pod-0")?;
+//! let mut writer = RecordBatchWriter::for_table(&table)?.with_journal(journal);
+//! writer.recover(&mut table).await?;
+//! writer.write(batch).await?;
+//! writer.flush_and_commit(&mut table).await?;
+//! ````
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::path::{Path as LocalPath, PathBuf};
+
+use object_store::path::Path;
+use object_store::{Error as ObjectStoreError, ObjectStore};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::kernel::{Action, Add, Txn};
+use crate::logstore::latest_transaction_version;
+use crate::DeltaTable;
+
+/// An entry of the journal, serialized as a single json line
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum JournalEntry {
+    /// A data file is about to be uploaded
+    Pending {
+        /// Path of the file relative to the table root
+        path: String,
+    },
+    /// A data file was uploaded
+    Staged {
+        /// The add action of the file
+        add: Box<Add>,
+    },
+    /// All staged files are about to be committed with the given transaction version
+    Sealed {
+        /// Version of the application transaction committed along with the files
+        txn_version: i64,
+    },
+}
+
+/// Outcome of [`RecordBatchWriter::recover`](super::RecordBatchWriter::recover)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecoveryOutcome {
+    /// The journal did not record any in-progress write
+    Clean,
+    /// A sealed file set was committed, creating the given table version
+    Committed(i64),
+    /// A sealed file set had already been committed before the crash
+    AlreadyCommitted,
+    /// Files of an unsealed file set were deleted, paths are relative to the table root
+    Removed(Vec<String>),
+}
+
+/// A journal of in-progress writes kept on the local file system.
+/// See this module's documentation for more information
+#[derive(Debug, Clone)]
+pub struct WriteJournal {
+    path: PathBuf,
+    app_id: String,
+}
+
+impl WriteJournal {
+    /// Open the journal at `path`, creating parent directories as needed.
+    ///
+    /// `app_id` identifies the writer in the application transactions of its commits and must
+    /// be stable across restarts and unique among the writers of a table.
+    pub fn open(path: impl AsRef<LocalPath>, app_id: impl Into<String>) -> DeltaResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(Self {
+            path,
+            app_id: app_id.into(),
+        })
+    }
+
+    /// The application id used for the transactions of journaled commits
+    pub fn app_id(&self) -> &str {
+        &self.app_id
+    }
+
+    /// Whether the journal records no in-progress write
+    pub fn is_empty(&self) -> DeltaResult<bool> {
+        Ok(self.read()?.is_empty())
+    }
+
+    /// Forget all recorded writes
+    pub fn clear(&self) -> DeltaResult<()> {
+        match std::fs::remove_file(&self.path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn append(&self, entry: &JournalEntry) -> DeltaResult<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(&line)?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    pub(crate) fn read(&self) -> DeltaResult<Vec<JournalEntry>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        let lines = BufReader::new(file)
+            .lines()
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut entries = Vec::with_capacity(lines.len());
+        for (idx, line) in lines.iter().enumerate() {
+            match serde_json::from_str(line) {
+                Ok(entry) => entries.push(entry),
+                // the last line may be torn if the process crashed while appending it
+                Err(_) if idx + 1 == lines.len() => {
+                    debug!("ignoring incomplete last line of write journal")
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(entries)
+    }
+
+    /// The version of the last application transaction of this journal in the table's log
+    pub(crate) async fn committed_txn_version(
+        &self,
+        table: &DeltaTable,
+    ) -> DeltaResult<Option<i64>> {
+        latest_transaction_version(table.log_store().as_ref(), table.version(), &self.app_id).await
+    }
+
+    /// The application transaction committed along with a sealed file set
+    pub(crate) async fn next_txn(&self, table: &DeltaTable) -> DeltaResult<Txn> {
+        let version = self.committed_txn_version(table).await?.unwrap_or(0);
+        Ok(Txn {
+            app_id: self.app_id.clone(),
+            version: version + 1,
+            last_updated: Some(chrono::Utc::now().timestamp_millis()),
+        })
+    }
+
+    pub(crate) async fn recover(
+        &self,
+        storage: &dyn ObjectStore,
+        table: &mut DeltaTable,
+    ) -> DeltaResult<RecoveryOutcome> {
+        let entries = self.read()?;
+        if entries.is_empty() {
+            return Ok(RecoveryOutcome::Clean);
+        }
+        table.update().await?;
+
+        let sealed = entries.iter().find_map(|entry| match entry {
+            JournalEntry::Sealed { txn_version } => Some(*txn_version),
+            _ => None,
+        });
+        let outcome = match sealed {
+            Some(txn_version) => {
+                let committed = self
+                    .committed_txn_version(table)
+                    .await?
+                    .is_some_and(|version| version >= txn_version);
+                if committed {
+                    RecoveryOutcome::AlreadyCommitted
+                } else {
+                    let mut actions = entries
+                        .into_iter()
+                        .filter_map(|entry| match entry {
+                            JournalEntry::Staged { add } => Some(Action::Add(*add)),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>();
+                    actions.push(Action::Txn(Txn {
+                        app_id: self.app_id.clone(),
+                        version: txn_version,
+                        last_updated: Some(chrono::Utc::now().timestamp_millis()),
+                    }));
+                    RecoveryOutcome::Committed(
+                        super::flush_and_commit(actions, HashMap::new(), table).await?,
+                    )
+                }
+            }
+            None => {
+                let live = table.get_files_iter()?.collect::<HashSet<_>>();
+                let mut removed = Vec::new();
+                for entry in entries {
+                    let JournalEntry::Pending { path } = entry else {
+                        continue;
+                    };
+                    let location = Path::parse(&path)?;
+                    if live.contains(&location) {
+                        continue;
+                    }
+                    match storage.delete(&location).await {
+                        Ok(()) | Err(ObjectStoreError::NotFound { .. }) => removed.push(path),
+                        Err(err) => return Err(DeltaTableError::from(err)),
+                    }
+                }
+                RecoveryOutcome::Removed(removed)
+            }
+        };
+        debug!("recovered write journal: {outcome:?}");
+        self.clear()?;
+        Ok(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::writer::test_utils::{create_initialized_table, get_record_batch};
+    use crate::writer::{DeltaWriter, RecordBatchWriter};
+
+    fn journal(dir: &tempfile::TempDir) -> WriteJournal {
+        WriteJournal::open(dir.path().join("journal").join("writer.log"), "test-writer").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_journal_cleared_after_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut table = create_initialized_table(&[]).await;
+        let mut writer = RecordBatchWriter::for_table(&table)
+            .unwrap()
+            .with_journal(journal(&dir));
+
+        writer.write(get_record_batch(None, false)).await.unwrap();
+        assert_eq!(writer.flush_and_commit(&mut table).await.unwrap(), 1);
+        assert!(journal(&dir).is_empty().unwrap());
+        assert_eq!(
+            journal(&dir).committed_txn_version(&table).await.unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            writer.recover(&mut table).await.unwrap(),
+            RecoveryOutcome::Clean
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recover_removes_unsealed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut table = create_initialized_table(&[]).await;
+        let mut writer = RecordBatchWriter::for_table(&table)
+            .unwrap()
+            .with_journal(journal(&dir));
+
+        // crash after uploading the files but before committing them
+        writer.write(get_record_batch(None, false)).await.unwrap();
+        let adds = writer.flush().await.unwrap();
+        let path = Path::parse(&adds[0].path).unwrap();
+        assert!(table.object_store().head(&path).await.is_ok());
+
+        let mut writer = RecordBatchWriter::for_table(&table)
+            .unwrap()
+            .with_journal(journal(&dir));
+        let outcome = writer.recover(&mut table).await.unwrap();
+        assert_eq!(
+            outcome,
+            RecoveryOutcome::Removed(vec![adds[0].path.clone()])
+        );
+        assert!(table.object_store().head(&path).await.is_err());
+        assert_eq!(table.version(), 0);
+        assert!(journal(&dir).is_empty().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_recover_commits_sealed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut table = create_initialized_table(&[]).await;
+        let mut writer = RecordBatchWriter::for_table(&table)
+            .unwrap()
+            .with_journal(journal(&dir));
+
+        // crash after sealing the file set but before committing it
+        writer.write(get_record_batch(None, false)).await.unwrap();
+        writer.flush().await.unwrap();
+        let journal = journal(&dir);
+        journal
+            .append(&JournalEntry::Sealed {
+                txn_version: journal.next_txn(&table).await.unwrap().version,
+            })
+            .unwrap();
+
+        let outcome = writer.recover(&mut table).await.unwrap();
+        assert_eq!(outcome, RecoveryOutcome::Committed(1));
+        assert_eq!(table.get_files_count().unwrap(), 1);
+
+        // a sealed file set is not committed twice
+        let txn_version = journal.next_txn(&table).await.unwrap().version - 1;
+        journal
+            .append(&JournalEntry::Sealed { txn_version })
+            .unwrap();
+        let outcome = writer.recover(&mut table).await.unwrap();
+        assert_eq!(outcome, RecoveryOutcome::AlreadyCommitted);
+        assert_eq!(table.version(), 1);
+        assert_eq!(
+            journal.committed_txn_version(&table).await.unwrap(),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_torn_last_line_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = journal(&dir);
+        journal
+            .append(&JournalEntry::Pending {
+                path: "part-00000.parquet".to_string(),
+            })
+            .unwrap();
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(dir.path().join("journal").join("writer.log"))
+            .unwrap();
+        file.write_all(b"{\"staged\":{\"add\"").unwrap();
+
+        assert_eq!(journal.read().unwrap().len(), 1);
+        journal.clear().unwrap();
+        assert!(journal.is_empty().unwrap());
+    }
+}
@@ -1,5 +1,7 @@
 //! Abstractions and implementations for writing data to delta tables
 
+use std::collections::HashMap;
+
 use arrow::{datatypes::SchemaRef, error::ArrowError};
 use async_trait::async_trait;
 use object_store::Error as ObjectStoreError;
@@ -12,14 +14,24 @@ use crate::operations::transaction::CommitBuilder;
 use crate::protocol::{ColumnCountStat, DeltaOperation, SaveMode};
 use crate::DeltaTable;
 
+pub use handle::{DeltaWriterHandle, WriterHandleConfig};
+pub use journal::{RecoveryOutcome, WriteJournal};
 pub use json::JsonWriter;
 pub use record_batch::RecordBatchWriter;
 pub use stats::create_add;
+pub use typed::TypedWriter;
+pub use watermark::{LateDataPolicy, Watermark};
 
+pub mod file_name;
+pub mod handle;
+pub mod journal;
 pub mod json;
 pub mod record_batch;
+pub mod rows;
 pub(crate) mod stats;
+pub mod typed;
 pub mod utils;
+pub mod watermark;
 
 #[cfg(test)]
 pub mod test_utils;
@@ -57,6 +69,15 @@ pub(crate) enum DeltaWriterError {
         sample_error: ParquetError,
     },
 
+    /// A value of a binary column was not a valid base64 encoded string.
+    #[error("Failed to decode base64 value for binary column {column}: {source}")]
+    InvalidBinaryValue {
+        /// The name of the binary column
+        column: String,
+        /// The wrapped [`base64::DecodeError`]
+        source: base64::DecodeError,
+    },
+
     /// Serialization of delta log statistics failed.
     #[error("Failed to write statistics value {debug_value} with logical type {logical_type:?}")]
     StatsParsingFailed {
@@ -152,13 +173,14 @@ pub trait DeltaWriter<T> {
     /// and commit the changes to the Delta log, creating a new table version.
     async fn flush_and_commit(&mut self, table: &mut DeltaTable) -> Result<i64, DeltaTableError> {
         let adds: Vec<_> = self.flush().await?.drain(..).map(Action::Add).collect();
-        flush_and_commit(adds, table).await
+        flush_and_commit(adds, HashMap::new(), table).await
     }
 }
 
 /// Method for flushing to be used by writers
 pub(crate) async fn flush_and_commit(
     adds: Vec<Action>,
+    app_metadata: HashMap<String, Value>,
     table: &mut DeltaTable,
 ) -> Result<i64, DeltaTableError> {
     let snapshot = table.snapshot()?;
@@ -176,6 +198,7 @@ pub(crate) async fn flush_and_commit(
 
     let version = CommitBuilder::default()
         .with_actions(adds)
+        .with_app_metadata(app_metadata)
         .build(Some(snapshot), table.log_store.clone(), operation)?
         .await?
         .version();
@@ -18,20 +18,25 @@ use indexmap::IndexMap;
 use object_store::{path::Path, ObjectStore};
 use parquet::{arrow::ArrowWriter, errors::ParquetError};
 use parquet::{basic::Compression, file::properties::WriterProperties};
+use serde::Serialize;
 use tracing::log::*;
 use uuid::Uuid;
 
+use super::journal::{JournalEntry, RecoveryOutcome, WriteJournal};
+use super::rows::{RowEncoder, SerdeRowEncoder};
 use super::stats::create_add;
 use super::utils::{
     arrow_schema_without_partitions, next_data_path, record_batch_without_partitions,
     ShareableBuffer,
 };
+use super::watermark::{Watermark, WatermarkTracker};
 use super::{DeltaWriter, DeltaWriterError, WriteMode};
 use crate::errors::DeltaTableError;
 use crate::kernel::{Action, Add, PartitionsExt, Scalar, StructType};
 use crate::operations::cast::merge_schema;
 use crate::storage::ObjectStoreRetryExt;
 use crate::table::builder::DeltaTableBuilder;
+use crate::table::encryption::check_can_write;
 use crate::DeltaTable;
 
 /// Writes messages to a delta lake table.
@@ -43,6 +48,8 @@ pub struct RecordBatchWriter {
     should_evolve: bool,
     partition_columns: Vec<String>,
     arrow_writers: HashMap<String, PartitionWriter>,
+    journal: Option<WriteJournal>,
+    watermark: Option<WatermarkTracker>,
 }
 
 impl std::fmt::Debug for RecordBatchWriter {
@@ -78,6 +85,8 @@ impl RecordBatchWriter {
             partition_columns: partition_columns.unwrap_or_default(),
             should_evolve: false,
             arrow_writers: HashMap::new(),
+            journal: None,
+            watermark: None,
         })
     }
 
@@ -85,6 +94,7 @@ impl RecordBatchWriter {
     pub fn for_table(table: &DeltaTable) -> Result<Self, DeltaTableError> {
         // Initialize an arrow schema ref from the delta table schema
         let metadata = table.metadata()?;
+        check_can_write(&metadata.configuration)?;
         let arrow_schema =
             <ArrowSchema as TryFrom<&StructType>>::try_from(&metadata.schema()?.clone())?;
         let arrow_schema_ref = Arc::new(arrow_schema);
@@ -104,6 +114,8 @@ impl RecordBatchWriter {
             partition_columns,
             should_evolve: false,
             arrow_writers: HashMap::new(),
+            journal: None,
+            watermark: None,
         })
     }
 
@@ -124,6 +136,9 @@ impl RecordBatchWriter {
     /// Resets internal state.
     pub fn reset(&mut self) {
         self.arrow_writers.clear();
+        if let Some(watermark) = &mut self.watermark {
+            watermark.reset_late_rows();
+        }
     }
 
     /// Returns the arrow schema representation of the delta table schema defined for the wrapped
@@ -167,6 +182,75 @@ impl RecordBatchWriter {
         self
     }
 
+    /// Record in-progress writes in the given journal, see [`super::journal`]
+    pub fn with_journal(mut self, journal: WriteJournal) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Track the watermark of the event time column of the written data, see [`super::watermark`]
+    pub fn with_watermark(mut self, watermark: Watermark) -> Self {
+        self.watermark = Some(WatermarkTracker::new(watermark));
+        self
+    }
+
+    /// The current watermark in microseconds since the epoch, if a watermark is tracked and
+    /// records with an event time were written
+    pub fn watermark(&self) -> Option<i64> {
+        self.watermark
+            .as_ref()
+            .and_then(|watermark| watermark.current())
+    }
+
+    /// Take the late records routed to the side output of the watermark
+    pub fn take_late_rows(&mut self) -> Vec<RecordBatch> {
+        self.watermark
+            .as_mut()
+            .map(|watermark| watermark.take_late_batches())
+            .unwrap_or_default()
+    }
+
+    /// Commit or clean up the writes recorded in the journal by a previous writer that did not
+    /// shut down cleanly. Should be called before writing any data.
+    pub async fn recover(
+        &mut self,
+        table: &mut DeltaTable,
+    ) -> Result<RecoveryOutcome, DeltaTableError> {
+        let journal = self.journal.as_ref().ok_or_else(|| {
+            DeltaTableError::Generic("The writer has no journal to recover from".to_string())
+        })?;
+        journal.recover(self.storage.as_ref(), table).await
+    }
+
+    /// Write rows implementing [`Serialize`] into the internal write buffers.
+    ///
+    /// Rows are converted to record batches in chunks with a [`SerdeRowEncoder`], which is
+    /// convenient for low volume writes where building record batches manually is overkill.
+    pub async fn write_serde_rows<T: Serialize>(
+        &mut self,
+        rows: impl IntoIterator<Item = T>,
+    ) -> Result<(), DeltaTableError> {
+        self.write_rows_with_encoder(rows, &mut SerdeRowEncoder::default())
+            .await
+    }
+
+    /// Write rows into the internal write buffers, converting chunks of rows to record batches
+    /// with `encoder`.
+    pub async fn write_rows_with_encoder<T, E: RowEncoder<T>>(
+        &mut self,
+        rows: impl IntoIterator<Item = T>,
+        encoder: &mut E,
+    ) -> Result<(), DeltaTableError> {
+        let chunk_size = encoder.chunk_size().max(1);
+        let mut rows = rows.into_iter().peekable();
+        while rows.peek().is_some() {
+            let chunk = rows.by_ref().take(chunk_size).collect::<Vec<_>>();
+            let batch = encoder.encode(self.arrow_schema(), &chunk)?;
+            self.write(batch).await?;
+        }
+        Ok(())
+    }
+
     fn divide_by_partition_values(
         &mut self,
         values: &RecordBatch,
@@ -196,11 +280,25 @@ impl DeltaWriter<RecordBatch> for RecordBatchWriter {
         // on its flush_and_commit
         self.should_evolve = mode == WriteMode::MergeSchema;
 
-        for result in self.divide_by_partition_values(&values)? {
+        let values = match &mut self.watermark {
+            Some(watermark) => watermark.observe(values)?,
+            None => values,
+        };
+        let partitions = match mode {
+            // merged batches keep their own columns, the partition writers merge the schemas
+            WriteMode::MergeSchema => divide_by_partition_values(
+                arrow_schema_without_partitions(&values.schema(), &self.partition_columns),
+                self.partition_columns.clone(),
+                &values,
+            )?,
+            WriteMode::Default => self.divide_by_partition_values(&values)?,
+        };
+        for result in partitions {
             let schema = self
                 .write_partition(result.record_batch, &result.partition_values, mode.clone())
                 .await?;
-            self.arrow_schema_ref = schema;
+            self.arrow_schema_ref =
+                schema_with_partitions(&schema, &self.arrow_schema_ref, &self.partition_columns);
         }
         Ok(())
     }
@@ -217,14 +315,25 @@ impl DeltaWriter<RecordBatch> for RecordBatchWriter {
             let path = next_data_path(&prefix, 0, &uuid, &writer.writer_properties);
             let obj_bytes = Bytes::from(writer.buffer.to_vec());
             let file_size = obj_bytes.len() as i64;
+            if let Some(journal) = &self.journal {
+                journal.append(&JournalEntry::Pending {
+                    path: path.to_string(),
+                })?;
+            }
             self.storage.put_with_retries(&path, obj_bytes, 15).await?;
 
-            actions.push(create_add(
+            let add = create_add(
                 &writer.partition_values,
                 path.to_string(),
                 file_size,
                 &metadata,
-            )?);
+            )?;
+            if let Some(journal) = &self.journal {
+                journal.append(&JournalEntry::Staged {
+                    add: Box::new(add.clone()),
+                })?;
+            }
+            actions.push(add);
         }
         Ok(actions)
     }
@@ -232,18 +341,39 @@ impl DeltaWriter<RecordBatch> for RecordBatchWriter {
     /// Flush the internal write buffers to files in the delta table folder structure.
     /// and commit the changes to the Delta log, creating a new table version.
     async fn flush_and_commit(&mut self, table: &mut DeltaTable) -> Result<i64, DeltaTableError> {
-        use crate::kernel::{Metadata, StructType};
         let mut adds: Vec<Action> = self.flush().await?.drain(..).map(Action::Add).collect();
 
         if self.arrow_schema_ref != self.original_schema_ref && self.should_evolve {
+            // keep the id, partition columns and configuration of the table, only the schema
+            // evolves
             let schema: StructType = self.arrow_schema_ref.clone().try_into()?;
-            // TODO: Handle partition columns somehow? Can we even evolve partition columns? Maybe
-            // this should just propagate the existing columns in the new action
-            let part_cols: Vec<String> = vec![];
-            let metadata = Metadata::try_new(schema, part_cols, HashMap::new())?;
+            let mut metadata = table.metadata()?.clone();
+            metadata.schema_string = serde_json::to_string(&schema)?;
             adds.push(Action::Metadata(metadata));
         }
-        super::flush_and_commit(adds, table).await
+        let app_metadata = self
+            .watermark
+            .as_ref()
+            .map(|watermark| watermark.commit_info())
+            .unwrap_or_default();
+        let version = match &self.journal {
+            Some(journal) => {
+                let txn = journal.next_txn(table).await?;
+                journal.append(&JournalEntry::Sealed {
+                    txn_version: txn.version,
+                })?;
+                adds.push(Action::Txn(txn));
+                let version = super::flush_and_commit(adds, app_metadata, table).await?;
+                journal.clear()?;
+                version
+            }
+            None => super::flush_and_commit(adds, app_metadata, table).await?,
+        };
+        if let Some(watermark) = &mut self.watermark {
+            watermark.reset_late_rows();
+        }
+        self.original_schema_ref = self.arrow_schema_ref.clone();
+        Ok(version)
     }
 }
 
@@ -367,6 +497,38 @@ impl PartitionWriter {
     }
 }
 
+/// The schema of the table after `written`, the schema of a partition's data files, was
+/// written. Partition columns keep their position in the `current` table schema, columns added
+/// by schema evolution are appended.
+fn schema_with_partitions(
+    written: &ArrowSchemaRef,
+    current: &ArrowSchemaRef,
+    partition_columns: &[String],
+) -> ArrowSchemaRef {
+    if partition_columns.is_empty() {
+        return written.clone();
+    }
+    let mut fields = current
+        .fields()
+        .iter()
+        .map(|field| match written.fields().find(field.name()) {
+            Some((_, written_field)) if !partition_columns.contains(field.name()) => {
+                written_field.clone()
+            }
+            _ => field.clone(),
+        })
+        .collect::<Vec<_>>();
+    for field in written.fields() {
+        if current.fields().find(field.name()).is_none() {
+            fields.push(field.clone());
+        }
+    }
+    Arc::new(ArrowSchema::new_with_metadata(
+        fields,
+        written.metadata().clone(),
+    ))
+}
+
 /// Partition a RecordBatch along partition columns
 pub(crate) fn divide_by_partition_values(
     arrow_schema: ArrowSchemaRef,
@@ -451,6 +613,7 @@ fn lexsort_to_indices(arrays: &[ArrayRef]) -> UInt32Array {
 mod tests {
     use super::*;
     use crate::operations::create::CreateBuilder;
+    use crate::table::config::DeltaConfigKey;
     use crate::writer::test_utils::*;
     use arrow::json::ReaderBuilder;
     use arrow_array::{Int32Array, RecordBatch, StringArray};
@@ -469,6 +632,102 @@ mod tests {
         assert!(writer.buffer_len() > 0);
     }
 
+    #[tokio::test]
+    async fn test_write_serde_rows() {
+        #[derive(serde::Serialize)]
+        struct Row {
+            id: String,
+            value: i32,
+            modified: String,
+        }
+
+        let partition_cols = vec!["modified".to_string()];
+        let mut table = create_initialized_table(&partition_cols).await;
+        let mut writer = RecordBatchWriter::for_table(&table).unwrap();
+
+        let rows = (0..5).map(|i| Row {
+            id: format!("{i}"),
+            value: i,
+            modified: format!("2021-02-0{}", i % 2 + 1),
+        });
+        let mut encoder = SerdeRowEncoder::default().with_chunk_size(2);
+        writer
+            .write_rows_with_encoder(rows, &mut encoder)
+            .await
+            .unwrap();
+        assert_eq!(writer.buffered_record_batch_count(), 5);
+
+        writer.flush_and_commit(&mut table).await.unwrap();
+        assert_eq!(table.get_files_count().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_write_with_watermark() {
+        use crate::kernel::{DataType as DeltaDataType, PrimitiveType, StructField};
+        use crate::operations::DeltaOps;
+        use crate::writer::{LateDataPolicy, Watermark};
+        use arrow_array::TimestampMicrosecondArray;
+        use std::time::Duration;
+
+        let mut table = DeltaOps::new_in_memory()
+            .create()
+            .with_columns(vec![
+                StructField::new(
+                    "value",
+                    DeltaDataType::Primitive(PrimitiveType::Integer),
+                    true,
+                ),
+                StructField::new(
+                    "event_time",
+                    DeltaDataType::Primitive(PrimitiveType::Timestamp),
+                    true,
+                ),
+            ])
+            .await
+            .unwrap();
+        let mut writer = RecordBatchWriter::for_table(&table)
+            .unwrap()
+            .with_watermark(
+                Watermark::new("event_time", Duration::from_secs(1))
+                    .with_late_data_policy(LateDataPolicy::Flag),
+            );
+        let schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("value", DataType::Int32, true),
+            Field::new(
+                "event_time",
+                DataType::Timestamp(arrow_schema::TimeUnit::Microsecond, Some("UTC".into())),
+                true,
+            ),
+        ]));
+        let batch = |event_times: Vec<i64>| {
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int32Array::from(vec![1; event_times.len()])),
+                    Arc::new(TimestampMicrosecondArray::from(event_times).with_timezone("UTC")),
+                ],
+            )
+            .unwrap()
+        };
+
+        writer
+            .write(batch(vec![5_000_000, 3_000_000]))
+            .await
+            .unwrap();
+        writer
+            .write(batch(vec![1_000_000, 4_500_000]))
+            .await
+            .unwrap();
+        assert_eq!(writer.watermark(), Some(4_000_000));
+        writer.flush_and_commit(&mut table).await.unwrap();
+
+        let history = table.history(Some(1)).await.unwrap();
+        assert_eq!(history[0].info["watermark"], serde_json::json!(4_000_000));
+        assert_eq!(history[0].info["lateRows"], serde_json::json!(1));
+        assert!(writer.take_late_rows().is_empty());
+        assert_eq!(table.get_files_count().unwrap(), 1);
+    }
+
     #[tokio::test]
     async fn test_divide_record_batch_no_partition() {
         let batch = get_record_batch(None, false);
@@ -774,6 +1033,69 @@ mod tests {
             );
         }
 
+        #[tokio::test]
+        async fn test_write_schema_evolution_partitioned() {
+            let table_schema = get_delta_schema();
+            let table_dir = tempfile::tempdir().unwrap();
+            let table_path = table_dir.path();
+
+            let mut table = CreateBuilder::new()
+                .with_location(table_path.to_str().unwrap())
+                .with_table_name("test-table")
+                .with_columns(table_schema.fields().clone())
+                .with_partition_columns(vec!["modified"])
+                .with_configuration_property(DeltaConfigKey::AppendOnly, Some("true"))
+                .await
+                .unwrap();
+            let table_id = table.metadata().unwrap().id.clone();
+
+            let mut writer = RecordBatchWriter::for_table(&table).unwrap();
+            writer.write(get_record_batch(None, false)).await.unwrap();
+            let version = writer.flush_and_commit(&mut table).await.unwrap();
+            assert_eq!(version, 1);
+
+            let second_schema = Arc::new(ArrowSchema::new(vec![
+                Field::new("id", DataType::Utf8, true),
+                Field::new("modified", DataType::Utf8, true),
+                Field::new("name", DataType::Utf8, true),
+            ]));
+            let second_batch = RecordBatch::try_new(
+                second_schema,
+                vec![
+                    Arc::new(StringArray::from(vec![Some("A"), Some("B")])), // id
+                    Arc::new(StringArray::from(vec![
+                        Some("2021-02-03"),
+                        Some("2021-02-03"),
+                    ])), // modified
+                    Arc::new(StringArray::from(vec![Some("will"), Some("robert")])), // name
+                ],
+            )
+            .unwrap();
+            writer
+                .write_with_mode(second_batch, WriteMode::MergeSchema)
+                .await
+                .unwrap();
+            let version = writer.flush_and_commit(&mut table).await.unwrap();
+            assert_eq!(version, 2);
+            table.load().await.expect("Failed to load table");
+
+            let metadata = table.metadata().unwrap();
+            assert_eq!(metadata.id, table_id);
+            assert_eq!(metadata.partition_columns, vec!["modified".to_string()]);
+            assert_eq!(
+                metadata.configuration.get("delta.appendOnly"),
+                Some(&Some("true".to_string()))
+            );
+            let found_columns = metadata
+                .schema()
+                .unwrap()
+                .fields()
+                .iter()
+                .map(|f| f.name().clone())
+                .collect::<Vec<_>>();
+            assert_eq!(found_columns, vec!["id", "value", "modified", "name"]);
+        }
+
         #[tokio::test]
         async fn test_schema_evolution_column_type_mismatch() {
             let batch = get_record_batch(None, false);
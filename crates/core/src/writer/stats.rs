@@ -4,8 +4,11 @@ use std::{collections::HashMap, ops::AddAssign};
 
 use indexmap::IndexMap;
 use parquet::format::FileMetaData;
-use parquet::schema::types::{ColumnDescriptor, SchemaDescriptor};
-use parquet::{basic::LogicalType, errors::ParquetError};
+use parquet::schema::types::{ColumnDescriptor, SchemaDescriptor, Type};
+use parquet::{
+    basic::{ConvertedType, LogicalType, Repetition},
+    errors::ParquetError,
+};
 use parquet::{
     file::{metadata::RowGroupMetaData, statistics::Statistics},
     format::TimeUnit,
@@ -104,6 +107,14 @@ fn stats_from_file_metadata(
             .flatten();
 
         if let Some(stats) = maybe_stats {
+            // Special handling for columns nested in lists or maps
+            if column_descr.max_rep_level() > 0 {
+                let path =
+                    get_logical_path(schema_descriptor.get_column_root(i), column_path_parts);
+                apply_null_count_for_path(&path, stats.null_count as i64, &mut null_count);
+                continue;
+            }
+
             apply_min_max_for_column(
                 stats,
                 column_descr.clone(),
@@ -123,6 +134,11 @@ fn stats_from_file_metadata(
     })
 }
 
+/// Maximum number of characters kept in min/max statistics of string columns.
+///
+/// This matches the default prefix length used by Spark when collecting statistics.
+pub const STATS_STRING_PREFIX_LENGTH: usize = 32;
+
 /// Logical scalars extracted from statistics. These are used to aggregate
 /// minimums and maximums. We can't use the physical scalars because they
 /// are not ordered correctly for some types. For example, decimals are stored
@@ -284,6 +300,55 @@ impl StatsScalar {
     }
 }
 
+impl StatsScalar {
+    /// Truncate a string minimum to the first [`STATS_STRING_PREFIX_LENGTH`] characters.
+    ///
+    /// Any prefix of a string sorts before the string itself, so the truncated
+    /// value is still a valid lower bound.
+    fn truncate_min(self) -> Self {
+        match self {
+            Self::String(v) => match v.char_indices().nth(STATS_STRING_PREFIX_LENGTH) {
+                Some((idx, _)) => Self::String(v[..idx].to_string()),
+                None => Self::String(v),
+            },
+            other => other,
+        }
+    }
+
+    /// Truncate a string maximum to the first [`STATS_STRING_PREFIX_LENGTH`] characters,
+    /// incrementing the last character so the result is still a valid upper bound.
+    ///
+    /// Returns `None` if no upper bound can be derived from the prefix, i.e. when
+    /// every character of the prefix is already the maximum code point.
+    fn truncate_max(self) -> Option<Self> {
+        match self {
+            Self::String(v) => match v.char_indices().nth(STATS_STRING_PREFIX_LENGTH) {
+                Some((idx, _)) => {
+                    let mut prefix: Vec<char> = v[..idx].chars().collect();
+                    while let Some(last) = prefix.pop() {
+                        if let Some(next) = next_char(last) {
+                            prefix.push(next);
+                            return Some(Self::String(prefix.into_iter().collect()));
+                        }
+                    }
+                    None
+                }
+                None => Some(Self::String(v)),
+            },
+            other => Some(other),
+        }
+    }
+}
+
+/// The next unicode scalar value after `c`, skipping the surrogate range.
+fn next_char(c: char) -> Option<char> {
+    match c {
+        '\u{D7FF}' => Some('\u{E000}'),
+        char::MAX => None,
+        c => char::from_u32(c as u32 + 1),
+    }
+}
+
 impl From<StatsScalar> for serde_json::Value {
     fn from(scalar: StatsScalar) -> Self {
         match scalar {
@@ -323,8 +388,15 @@ impl From<(&Statistics, &Option<LogicalType>)> for AggregatedStats {
         let (stats, logical_type) = value;
         let null_count = stats.null_count();
         if stats.has_min_max_set() {
-            let min = StatsScalar::try_from_stats(stats, logical_type, true).ok();
-            let max = StatsScalar::try_from_stats(stats, logical_type, false).ok();
+            // Like Spark, we don't collect min/max for binary columns, since they are
+            // neither part of the stats schema nor meaningful for data skipping.
+            let is_binary = |v: &StatsScalar| matches!(v, StatsScalar::Bytes(_));
+            let min = StatsScalar::try_from_stats(stats, logical_type, true)
+                .ok()
+                .filter(|v| !is_binary(v));
+            let max = StatsScalar::try_from_stats(stats, logical_type, false)
+                .ok()
+                .filter(|v| !is_binary(v));
             Self {
                 min,
                 max,
@@ -367,38 +439,79 @@ impl AddAssign for AggregatedStats {
     }
 }
 
-/// For a list field, we don't want the inner field names. We need to chuck out
-/// the list and items fields from the path, but also need to handle the
-/// peculiar case where the user named the list field "list" or "item".
+/// For a column nested in a list or map, we don't want the inner field names. Parquet
+/// represents these with intermediate repeated groups, e.g. a list `some_list` is stored
+/// as `some_list.list.item`, so the path is cut off at the first field annotated as a list
+/// or map, or at the first repeated field for legacy layouts. Using the schema rather than
+/// the field names also handles the peculiar case where the user named a field "list" or "item".
 ///
 /// For example:
 ///
-/// * ["some_nested_list", "list", "item", "list", "item"] -> "some_nested_list"
-/// * ["some_list", "list", "item"] -> "some_list"
-/// * ["list", "list", "item"] -> "list"
-/// * ["item", "list", "item"] -> "item"
-fn get_list_field_name(column_descr: &Arc<ColumnDescriptor>) -> Option<String> {
-    let max_rep_levels = column_descr.max_rep_level();
-    let column_path_parts = column_descr.path().parts();
-
-    // If there are more nested names, we can't handle them yet.
-    if column_path_parts.len() > (2 * max_rep_levels + 1) as usize {
-        return None;
+/// * ["some_nested_list", "list", "item", "list", "item"] -> ["some_nested_list"]
+/// * ["some_list", "list", "item"] -> ["some_list"]
+/// * ["meta", "tags", "list", "item"] -> ["meta", "tags"]
+/// * ["some_map", "key_value", "key"] -> ["some_map"]
+fn get_logical_path(column_root: &Type, column_path_parts: &[String]) -> Vec<String> {
+    let mut path = Vec::new();
+    let mut current = column_root;
+    let mut remaining = column_path_parts.iter().skip(1);
+    loop {
+        path.push(current.name().to_string());
+        if !current.is_group() || is_repeated_or_collection(current) {
+            return path;
+        }
+        match remaining
+            .next()
+            .and_then(|name| current.get_fields().iter().find(|f| f.name() == name))
+        {
+            Some(child) => current = child,
+            None => return path,
+        }
     }
+}
 
-    let mut column_path_parts = column_path_parts.to_vec();
-    let mut items_seen = 0;
-    let mut lists_seen = 0;
-    while let Some(part) = column_path_parts.pop() {
-        match (part.as_str(), lists_seen, items_seen) {
-            ("list", seen, _) if seen == max_rep_levels => return Some("list".to_string()),
-            ("item", _, seen) if seen == max_rep_levels => return Some("item".to_string()),
-            ("list", _, _) => lists_seen += 1,
-            ("item", _, _) => items_seen += 1,
-            (other, _, _) => return Some(other.to_string()),
+fn is_repeated_or_collection(field: &Type) -> bool {
+    let info = field.get_basic_info();
+    (info.has_repetition() && info.repetition() == Repetition::REPEATED)
+        || matches!(
+            info.logical_type(),
+            Some(LogicalType::List) | Some(LogicalType::Map)
+        )
+        || matches!(
+            info.converted_type(),
+            ConvertedType::LIST | ConvertedType::MAP | ConvertedType::MAP_KEY_VALUE
+        )
+}
+
+/// Lists and maps only carry a null count, which is stored at the logical path of the
+/// collection within the (possibly nested) null count map.
+///
+/// Collections with several leaf columns, such as the keys and values of a map, share their
+/// logical path. Every leaf counts the rows where the collection itself is null, so the smallest
+/// count is kept, i.e. the count of the map keys, which cannot be null themselves.
+fn apply_null_count_for_path(
+    path: &[String],
+    null_count: i64,
+    null_counts: &mut HashMap<String, ColumnCountStat>,
+) {
+    match path {
+        [key] => {
+            let null_count = match null_counts.get(key) {
+                Some(ColumnCountStat::Value(existing)) => null_count.min(*existing),
+                _ => null_count,
+            };
+            null_counts.insert(key.clone(), ColumnCountStat::Value(null_count));
+        }
+        [key, remaining @ ..] => {
+            if let ColumnCountStat::Column(children) = null_counts
+                .entry(key.clone())
+                .or_insert_with(|| ColumnCountStat::Column(HashMap::new()))
+            {
+                apply_null_count_for_path(remaining, null_count, children);
+            }
         }
+        [] => {}
     }
-    None
 }
 
 fn apply_min_max_for_column(
@@ -409,28 +522,17 @@ fn apply_min_max_for_column(
     max_values: &mut HashMap<String, ColumnValueStat>,
     null_counts: &mut HashMap<String, ColumnCountStat>,
 ) -> Result<(), DeltaWriterError> {
-    // Special handling for list column
-    if column_descr.max_rep_level() > 0 {
-        let key = get_list_field_name(&column_descr);
-
-        if let Some(key) = key {
-            null_counts.insert(key, ColumnCountStat::Value(statistics.null_count as i64));
-        }
-
-        return Ok(());
-    }
-
     match (column_path_parts.len(), column_path_parts.first()) {
         // Base case - we are at the leaf struct level in the path
         (1, _) => {
             let key = column_descr.name().to_string();
 
-            if let Some(min) = statistics.min {
+            if let Some(min) = statistics.min.map(StatsScalar::truncate_min) {
                 let min = ColumnValueStat::Value(min.into());
                 min_values.insert(key.clone(), min);
             }
 
-            if let Some(max) = statistics.max {
+            if let Some(max) = statistics.max.and_then(StatsScalar::truncate_max) {
                 let max = ColumnValueStat::Value(max.into());
                 max_values.insert(key.clone(), max);
             }
@@ -645,6 +747,180 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_string_stats_truncation() {
+        let short = "GET".to_string();
+        assert_eq!(
+            StatsScalar::String(short.clone()).truncate_min(),
+            StatsScalar::String(short.clone())
+        );
+        assert_eq!(
+            StatsScalar::String(short.clone()).truncate_max(),
+            Some(StatsScalar::String(short))
+        );
+
+        let long = format!("{}abc", "a".repeat(STATS_STRING_PREFIX_LENGTH - 1));
+        let min = StatsScalar::String(long.clone()).truncate_min();
+        let max = StatsScalar::String(long.clone()).truncate_max().unwrap();
+        assert_eq!(
+            min,
+            StatsScalar::String("a".repeat(STATS_STRING_PREFIX_LENGTH))
+        );
+        assert_eq!(
+            max,
+            StatsScalar::String(format!("{}b", "a".repeat(STATS_STRING_PREFIX_LENGTH - 1)))
+        );
+        assert!(min <= StatsScalar::String(long.clone()));
+        assert!(max > StatsScalar::String(long));
+
+        // trailing max code points are dropped before incrementing
+        let long = format!(
+            "{}{}{}",
+            "a".repeat(STATS_STRING_PREFIX_LENGTH - 2),
+            '\u{D7FF}',
+            char::MAX.to_string().repeat(3)
+        );
+        assert_eq!(
+            StatsScalar::String(long).truncate_max(),
+            Some(StatsScalar::String(format!(
+                "{}{}",
+                "a".repeat(STATS_STRING_PREFIX_LENGTH - 2),
+                '\u{E000}'
+            )))
+        );
+
+        let unbounded = char::MAX.to_string().repeat(STATS_STRING_PREFIX_LENGTH + 1);
+        assert_eq!(StatsScalar::String(unbounded).truncate_max(), None);
+
+        // multi-byte characters are truncated on character boundaries
+        let long = "é".repeat(STATS_STRING_PREFIX_LENGTH + 1);
+        assert_eq!(
+            StatsScalar::String(long).truncate_min(),
+            StatsScalar::String("é".repeat(STATS_STRING_PREFIX_LENGTH))
+        );
+    }
+
+    #[test]
+    fn test_logical_path_of_nested_collections() {
+        let message = "
+            message schema {
+                OPTIONAL group some_list (LIST) {
+                    REPEATED group list {
+                        OPTIONAL BYTE_ARRAY item (UTF8);
+                    }
+                }
+                OPTIONAL group meta {
+                    OPTIONAL group tags (LIST) {
+                        REPEATED group list {
+                            OPTIONAL group item {
+                                OPTIONAL INT32 list;
+                            }
+                        }
+                    }
+                    OPTIONAL INT32 value;
+                }
+                OPTIONAL group some_map (MAP) {
+                    REPEATED group key_value {
+                        REQUIRED BYTE_ARRAY key (UTF8);
+                        OPTIONAL INT64 value;
+                    }
+                }
+                REPEATED INT32 legacy;
+            }
+        ";
+        let schema = Arc::new(SchemaDescriptor::new(Arc::new(
+            parquet::schema::parser::parse_message_type(message).unwrap(),
+        )));
+
+        let paths = (0..schema.num_columns())
+            .map(|i| {
+                let column = schema.column(i);
+                get_logical_path(schema.get_column_root(i), column.path().parts()).join(".")
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                "some_list",
+                "meta.tags",
+                "meta.value",
+                "some_map",
+                "some_map",
+                "legacy"
+            ]
+        );
+
+        let mut null_counts = HashMap::new();
+        apply_null_count_for_path(
+            &["meta".to_string(), "tags".to_string()],
+            3,
+            &mut null_counts,
+        );
+        let meta = null_counts.get("meta").unwrap().as_column().unwrap();
+        assert_eq!(meta.get("tags").unwrap().as_value(), Some(3));
+    }
+
+    #[test]
+    fn test_stats_of_structs_with_collections() {
+        use arrow::array::{
+            ArrayRef, Int32Array, Int64Builder, ListBuilder, MapBuilder, StringBuilder, StructArray,
+        };
+        use arrow::datatypes::{DataType, Field};
+        use arrow::record_batch::RecordBatch;
+        use parquet::arrow::ArrowWriter;
+
+        let mut tags = ListBuilder::new(StringBuilder::new());
+        tags.append_value([Some("a")]);
+        tags.append_null();
+        tags.append_value([Some("b")]);
+        let tags = Arc::new(tags.finish()) as ArrayRef;
+        let value = Arc::new(Int32Array::from(vec![Some(1), Some(5), None])) as ArrayRef;
+        let meta = StructArray::from(vec![
+            (
+                Arc::new(Field::new("tags", tags.data_type().clone(), true)),
+                tags,
+            ),
+            (Arc::new(Field::new("value", DataType::Int32, true)), value),
+        ]);
+
+        let mut some_map = MapBuilder::new(None, StringBuilder::new(), Int64Builder::new());
+        some_map.keys().append_value("k");
+        some_map.values().append_value(1);
+        some_map.append(true).unwrap();
+        some_map.append(false).unwrap();
+        some_map.keys().append_value("k");
+        some_map.values().append_null();
+        some_map.append(true).unwrap();
+
+        let batch = RecordBatch::try_from_iter(vec![
+            ("meta", Arc::new(meta) as ArrayRef),
+            ("some_map", Arc::new(some_map.finish()) as ArrayRef),
+        ])
+        .unwrap();
+        let mut writer = ArrowWriter::try_new(Vec::new(), batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        let file_metadata = writer.close().unwrap();
+
+        let stats = stats_from_file_metadata(&IndexMap::new(), &file_metadata).unwrap();
+
+        let min = stats.min_values.get("meta").unwrap().as_column().unwrap();
+        assert_eq!(min.len(), 1);
+        assert_eq!(min.get("value").unwrap().as_value(), Some(&json!(1)));
+        let max = stats.max_values.get("meta").unwrap().as_column().unwrap();
+        assert_eq!(max.len(), 1);
+        assert_eq!(max.get("value").unwrap().as_value(), Some(&json!(5)));
+        assert!(!stats.min_values.contains_key("some_map"));
+
+        let null_count = stats.null_count.get("meta").unwrap().as_column().unwrap();
+        assert_eq!(null_count.get("value").unwrap().as_value(), Some(1));
+        assert_eq!(null_count.get("tags").unwrap().as_value(), Some(1));
+        // the null value of the map doesn't count as a null map
+        assert_eq!(
+            stats.null_count.get("some_map").unwrap().as_value(),
+            Some(1)
+        );
+    }
+
     #[tokio::test]
     async fn test_delta_stats() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -703,8 +979,9 @@ mod tests {
                 ("date", ColumnValueStat::Value(v)) => {
                     assert_eq!("2021-06-22", v.as_str().unwrap())
                 }
+                // string statistics are truncated to their prefix
                 ("uuid", ColumnValueStat::Value(v)) => {
-                    assert_eq!("176c770d-92af-4a21-bf76-5d8c5261d659", v.as_str().unwrap())
+                    assert_eq!("176c770d-92af-4a21-bf76-5d8c5261", v.as_str().unwrap())
                 }
                 _ => panic!("Key should not be present"),
             }
@@ -735,7 +1012,7 @@ mod tests {
                     assert_eq!("2021-06-22", v.as_str().unwrap())
                 }
                 ("uuid", ColumnValueStat::Value(v)) => {
-                    assert_eq!("a98bea04-d119-4f21-8edc-eb218b5849af", v.as_str().unwrap())
+                    assert_eq!("a98bea04-d119-4f21-8edc-eb218b59", v.as_str().unwrap())
                 }
                 _ => panic!("Key should not be present"),
             }
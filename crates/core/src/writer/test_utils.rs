@@ -292,8 +292,8 @@ pub fn create_bare_table() -> DeltaTable {
 
 pub async fn create_initialized_table(partition_cols: &[String]) -> DeltaTable {
     let table_schema = get_delta_schema();
-    let table_dir = tempfile::tempdir().unwrap();
-    let table_path = table_dir.path();
+    // keep the directory, tests read the log of the table after it was created
+    let table_path = tempfile::tempdir().unwrap().keep();
 
     CreateBuilder::new()
         .with_location(table_path.to_str().unwrap())
@@ -0,0 +1,362 @@
+//! Write Rust structs to delta tables
+//!
+//! Types implementing [`DeltaSchema`] describe their Delta schema and can be converted to arrow
+//! [`RecordBatch`]es, so they can be written with a [`TypedWriter`]. With the `derive` feature
+//! enabled, [`DeltaSchema`] can be derived for structs whose fields implement
+//! [`DeltaFieldType`]. `Option` fields are nullable, all other fields are required. Fields can
+//! be renamed with `#[delta(rename = "...")]`. When using the derive through a crate re-exporting
+//! `deltalake_core`, pass its name with `#[delta(crate = "deltalake")]`.
+//!
+//! # Examples
+//! ```rust ignore
+//! #[derive(DeltaSchema)]
+//! struct Event {
+//!     id: i64,
+//!     #[delta(rename = "eventName")]
+//!     name: String,
+//!     timestamp: DateTime<Utc>,
+//!     tag: Option<String>,
+//! }
+//!
+//! let table = DeltaOps::try_from_uri("../path/to/empty/dir")
+//!     .await?
+//!     .create()
+//!     .with_columns(Event::schema().fields().clone())
+//!     .await?;
+//! let mut writer = TypedWriter::<Event>::for_table(&table)?;
+//! writer.write(events).await?;
+//! writer.flush_and_commit(&mut table).await?;
+//! ````
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use arrow::array::{
+    Array, ArrayRef, BinaryArray, BooleanArray, Date32Array, Float32Array, Float64Array,
+    Int16Array, Int32Array, Int64Array, Int8Array, StringArray, StructArray,
+    TimestampMicrosecondArray,
+};
+use arrow::buffer::NullBuffer;
+use arrow::datatypes::{Field as ArrowField, Fields};
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+use super::{DeltaWriter, RecordBatchWriter, WriteMode};
+use crate::errors::{DeltaResult, DeltaTableError};
+use crate::kernel::{Add, DataType, PrimitiveType, StructType};
+use crate::operations::cast::cast_record_batch;
+use crate::DeltaTable;
+
+#[cfg(feature = "derive")]
+pub use deltalake_derive::DeltaSchema;
+
+/// A Rust type stored in a column of a delta table
+pub trait DeltaFieldType: Sized {
+    /// Delta type of the column
+    fn data_type() -> DataType;
+
+    /// Whether the column is nullable
+    fn nullable() -> bool {
+        false
+    }
+
+    /// Convert column values to an arrow array, with `None` for null values
+    fn to_array(values: &[Option<&Self>]) -> DeltaResult<ArrayRef>;
+}
+
+/// A Rust type stored as a row of a delta table
+pub trait DeltaSchema: DeltaFieldType {
+    /// Delta schema of the table
+    fn schema() -> StructType;
+
+    /// Convert rows to a record batch with the table schema
+    fn to_record_batch(rows: &[Self]) -> DeltaResult<RecordBatch> {
+        let values = rows.iter().map(Some).collect::<Vec<_>>();
+        let array = Self::to_array(&values)?;
+        let array = array
+            .as_any()
+            .downcast_ref::<StructArray>()
+            .ok_or_else(|| DeltaTableError::Generic("Row type is not a struct".to_string()))?;
+        Ok(RecordBatch::from(array))
+    }
+}
+
+/// Combine the arrays of the fields of `schema` into a struct array, with null rows where
+/// `valid` is false
+pub fn to_struct_array(
+    schema: &StructType,
+    columns: Vec<ArrayRef>,
+    valid: Vec<bool>,
+) -> DeltaResult<ArrayRef> {
+    let fields = schema
+        .fields()
+        .iter()
+        .map(ArrowField::try_from)
+        .collect::<Result<Fields, _>>()?;
+    Ok(Arc::new(StructArray::try_new(
+        fields,
+        columns,
+        Some(NullBuffer::from(valid)),
+    )?))
+}
+
+macro_rules! primitive_field_type {
+    ($rust:ty, $primitive:ident, $array:ty, |$value:ident| $convert:expr) => {
+        impl DeltaFieldType for $rust {
+            fn data_type() -> DataType {
+                DataType::Primitive(PrimitiveType::$primitive)
+            }
+
+            fn to_array(values: &[Option<&Self>]) -> DeltaResult<ArrayRef> {
+                Ok(Arc::new(
+                    values
+                        .iter()
+                        .map(|value| value.map(|$value| $convert))
+                        .collect::<$array>(),
+                ))
+            }
+        }
+    };
+}
+
+primitive_field_type!(bool, Boolean, BooleanArray, |value| *value);
+primitive_field_type!(i8, Byte, Int8Array, |value| *value);
+primitive_field_type!(i16, Short, Int16Array, |value| *value);
+primitive_field_type!(i32, Integer, Int32Array, |value| *value);
+primitive_field_type!(i64, Long, Int64Array, |value| *value);
+primitive_field_type!(f32, Float, Float32Array, |value| *value);
+primitive_field_type!(f64, Double, Float64Array, |value| *value);
+primitive_field_type!(String, String, StringArray, |value| value.as_str());
+primitive_field_type!(Vec<u8>, Binary, BinaryArray, |value| value.as_slice());
+primitive_field_type!(NaiveDate, Date, Date32Array, |value| {
+    (*value - NaiveDate::default()).num_days() as i32
+});
+primitive_field_type!(
+    NaiveDateTime,
+    TimestampNtz,
+    TimestampMicrosecondArray,
+    |value| { value.timestamp_micros() }
+);
+
+impl DeltaFieldType for DateTime<Utc> {
+    fn data_type() -> DataType {
+        DataType::Primitive(PrimitiveType::Timestamp)
+    }
+
+    fn to_array(values: &[Option<&Self>]) -> DeltaResult<ArrayRef> {
+        Ok(Arc::new(
+            values
+                .iter()
+                .map(|value| value.map(|value| value.timestamp_micros()))
+                .collect::<TimestampMicrosecondArray>()
+                .with_timezone("UTC"),
+        ))
+    }
+}
+
+impl<T: DeltaFieldType> DeltaFieldType for Option<T> {
+    fn data_type() -> DataType {
+        T::data_type()
+    }
+
+    fn nullable() -> bool {
+        true
+    }
+
+    fn to_array(values: &[Option<&Self>]) -> DeltaResult<ArrayRef> {
+        let values = values
+            .iter()
+            .map(|value| value.and_then(Option::as_ref))
+            .collect::<Vec<_>>();
+        T::to_array(&values)
+    }
+}
+
+/// Writes rows of type `T` to a delta table
+pub struct TypedWriter<T> {
+    writer: RecordBatchWriter,
+    row_type: PhantomData<fn(T)>,
+}
+
+impl<T: DeltaSchema> TypedWriter<T> {
+    /// Creates a [`TypedWriter`] to write rows to the provided table
+    ///
+    /// Rows are cast to the table schema, so the table may contain additional nullable
+    /// columns and may declare columns nullable that are required in `T`.
+    pub fn for_table(table: &DeltaTable) -> DeltaResult<Self> {
+        Ok(Self {
+            writer: RecordBatchWriter::for_table(table)?,
+            row_type: PhantomData,
+        })
+    }
+
+    /// The underlying record batch writer
+    pub fn inner(&self) -> &RecordBatchWriter {
+        &self.writer
+    }
+}
+
+#[async_trait]
+impl<T: DeltaSchema + Send + Sync> DeltaWriter<Vec<T>> for TypedWriter<T> {
+    /// Write rows into the internal write buffers with the default write mode
+    async fn write(&mut self, values: Vec<T>) -> Result<(), DeltaTableError> {
+        self.write_with_mode(values, WriteMode::Default).await
+    }
+
+    /// Write rows into the internal write buffers with the specified [`WriteMode`]
+    async fn write_with_mode(
+        &mut self,
+        values: Vec<T>,
+        mode: WriteMode,
+    ) -> Result<(), DeltaTableError> {
+        let batch = T::to_record_batch(&values)?;
+        let batch = match mode {
+            WriteMode::Default => {
+                cast_record_batch(&batch, self.writer.arrow_schema(), false, true)?
+            }
+            WriteMode::MergeSchema => batch,
+        };
+        self.writer.write_with_mode(batch, mode).await
+    }
+
+    /// Flush the internal write buffers to files in the delta table folder structure.
+    async fn flush(&mut self) -> Result<Vec<Add>, DeltaTableError> {
+        self.writer.flush().await
+    }
+
+    /// Flush the internal write buffers to files in the delta table folder structure.
+    /// and commit the changes to the Delta log, creating a new table version.
+    async fn flush_and_commit(&mut self, table: &mut DeltaTable) -> Result<i64, DeltaTableError> {
+        self.writer.flush_and_commit(table).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::AsArray;
+    use arrow::datatypes::Int64Type;
+
+    use super::*;
+    use crate::kernel::StructField;
+    use crate::operations::DeltaOps;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Event {
+        id: i64,
+        name: Option<String>,
+    }
+
+    // what `#[derive(DeltaSchema)]` generates for `Event`
+    impl DeltaFieldType for Event {
+        fn data_type() -> DataType {
+            DataType::Struct(Box::new(Self::schema()))
+        }
+
+        fn to_array(values: &[Option<&Self>]) -> DeltaResult<ArrayRef> {
+            let columns = vec![
+                i64::to_array(
+                    &values
+                        .iter()
+                        .map(|value| value.map(|value| &value.id))
+                        .collect::<Vec<_>>(),
+                )?,
+                <Option<String>>::to_array(
+                    &values
+                        .iter()
+                        .map(|value| value.map(|value| &value.name))
+                        .collect::<Vec<_>>(),
+                )?,
+            ];
+            let valid = values.iter().map(Option::is_some).collect();
+            to_struct_array(&Self::schema(), columns, valid)
+        }
+    }
+
+    impl DeltaSchema for Event {
+        fn schema() -> StructType {
+            StructType::new(vec![
+                StructField::new("id", i64::data_type(), i64::nullable()),
+                StructField::new(
+                    "name",
+                    <Option<String>>::data_type(),
+                    <Option<String>>::nullable(),
+                ),
+            ])
+        }
+    }
+
+    fn events() -> Vec<Event> {
+        vec![
+            Event {
+                id: 1,
+                name: Some("a".to_string()),
+            },
+            Event { id: 2, name: None },
+        ]
+    }
+
+    #[test]
+    fn test_to_record_batch() {
+        let batch = Event::to_record_batch(&events()).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert!(!batch.schema().field(0).is_nullable());
+        assert!(batch.schema().field(1).is_nullable());
+        assert_eq!(
+            batch.column(0).as_primitive::<Int64Type>().values(),
+            &[1, 2]
+        );
+        assert_eq!(batch.column(1).null_count(), 1);
+    }
+
+    #[test]
+    fn test_primitive_conversions() {
+        let date = NaiveDate::from_ymd_opt(1970, 1, 11).unwrap();
+        let array = NaiveDate::to_array(&[Some(&date), None]).unwrap();
+        assert_eq!(
+            array
+                .as_primitive::<arrow::datatypes::Date32Type>()
+                .value(0),
+            10
+        );
+        assert!(array.is_null(1));
+
+        let timestamp = DateTime::<Utc>::from_timestamp(1, 0).unwrap();
+        let array = <DateTime<Utc>>::to_array(&[Some(&timestamp)]).unwrap();
+        assert_eq!(
+            array.data_type(),
+            &arrow::datatypes::DataType::Timestamp(
+                arrow::datatypes::TimeUnit::Microsecond,
+                Some("UTC".into())
+            )
+        );
+    }
+
+    #[cfg(feature = "datafusion")]
+    #[tokio::test]
+    async fn test_typed_writer() {
+        use crate::writer::test_utils::datafusion::get_data_sorted;
+
+        let mut table = DeltaOps::new_in_memory()
+            .create()
+            .with_columns(Event::schema().fields().clone())
+            .await
+            .unwrap();
+
+        let mut writer = TypedWriter::<Event>::for_table(&table).unwrap();
+        writer.write(events()).await.unwrap();
+        let version = writer.flush_and_commit(&mut table).await.unwrap();
+        assert_eq!(version, 1);
+
+        let expected = vec![
+            "+----+------+",
+            "| id | name |",
+            "+----+------+",
+            "| 1  | a    |",
+            "| 2  |      |",
+            "+----+------+",
+        ];
+        let actual = get_data_sorted(&table, "id, name").await;
+        datafusion::assert_batches_sorted_eq!(&expected, &actual);
+    }
+}
@@ -4,18 +4,24 @@
 use std::io::Write;
 use std::sync::Arc;
 
-use arrow::datatypes::{Schema as ArrowSchema, SchemaRef as ArrowSchemaRef};
+use arrow::array::{
+    Array, ArrayRef, AsArray, BinaryArray, FixedSizeBinaryArray, LargeBinaryArray, StructArray,
+};
+use arrow::datatypes::{
+    DataType, Field, FieldRef, Schema as ArrowSchema, SchemaRef as ArrowSchemaRef,
+};
 use arrow::json::ReaderBuilder;
 use arrow::record_batch::*;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 use object_store::path::Path;
 use parking_lot::RwLock;
-use parquet::basic::Compression;
 use parquet::file::properties::WriterProperties;
-use parquet::schema::types::ColumnPath;
 use serde_json::Value;
 use uuid::Uuid;
 
 use crate::errors::DeltaResult;
+use crate::writer::file_name::{DeltaFileNames, FileNameContext, FileNameStrategy};
 use crate::writer::DeltaWriterError;
 
 /// Generate the name of the file to be written
@@ -28,48 +34,124 @@ pub(crate) fn next_data_path(
     writer_id: &Uuid,
     writer_properties: &WriterProperties,
 ) -> Path {
-    fn compression_to_str(compression: &Compression) -> &str {
-        match compression {
-            // This is to match HADOOP's convention
-            // https://github.com/apache/parquet-mr/blob/c4977579ab3b149ea045a177b039f055b5408e8f/parquet-common/src/main/java/org/apache/parquet/hadoop/metadata/CompressionCodecName.java#L27-L34
-            Compression::UNCOMPRESSED => "",
-            Compression::SNAPPY => ".snappy",
-            Compression::GZIP(_) => ".gz",
-            Compression::LZO => ".lzo",
-            Compression::BROTLI(_) => ".br",
-            Compression::LZ4 => ".lz4",
-            Compression::ZSTD(_) => ".zstd",
-            Compression::LZ4_RAW => ".lz4raw",
-        }
-    }
-
-    // We can not access the default column properties but the current implementation will return
-    // the default compression when the column is not found
-    let column_path = ColumnPath::new(Vec::new());
-    let compression = writer_properties.compression(&column_path);
-
-    let part = format!("{:0>5}", part_count);
-
-    // TODO: what does c000 mean?
-    let file_name = format!(
-        "part-{}-{}-c000{}.parquet",
-        part,
+    let context = FileNameContext {
         writer_id,
-        compression_to_str(&compression)
-    );
-    prefix.child(file_name)
+        part_count,
+        writer_properties,
+    };
+    prefix.child(DeltaFileNames.file_name(&context))
 }
 
 /// Convert a vector of json values to a RecordBatch
+///
+/// Values for binary columns are expected to be base64 encoded strings, since JSON
+/// has no native representation for arbitrary bytes.
 pub fn record_batch_from_message(
     arrow_schema: Arc<ArrowSchema>,
     json: &[Value],
 ) -> DeltaResult<RecordBatch> {
-    let mut decoder = ReaderBuilder::new(arrow_schema).build_decoder().unwrap();
+    if !arrow_schema
+        .fields()
+        .iter()
+        .any(|f| contains_binary(f.data_type()))
+    {
+        let mut decoder = ReaderBuilder::new(arrow_schema).build_decoder().unwrap();
+        decoder.serialize(json)?;
+        return decoder
+            .flush()?
+            .ok_or_else(|| DeltaWriterError::EmptyRecordBatch.into());
+    }
+
+    // read binary columns as strings first, and decode them afterwards
+    let json_schema = Arc::new(ArrowSchema::new_with_metadata(
+        arrow_schema
+            .fields()
+            .iter()
+            .map(binary_as_string)
+            .collect::<Vec<_>>(),
+        arrow_schema.metadata().clone(),
+    ));
+    let mut decoder = ReaderBuilder::new(json_schema).build_decoder().unwrap();
     decoder.serialize(json)?;
-    decoder
-        .flush()?
-        .ok_or_else(|| DeltaWriterError::EmptyRecordBatch.into())
+    let batch = decoder.flush()?.ok_or(DeltaWriterError::EmptyRecordBatch)?;
+
+    let columns = batch
+        .columns()
+        .iter()
+        .zip(arrow_schema.fields().iter())
+        .map(|(column, field)| decode_binary(column, field))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(RecordBatch::try_new(arrow_schema, columns)?)
+}
+
+fn contains_binary(data_type: &DataType) -> bool {
+    match data_type {
+        DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_) => true,
+        DataType::Struct(fields) => fields.iter().any(|f| contains_binary(f.data_type())),
+        _ => false,
+    }
+}
+
+fn binary_as_string(field: &FieldRef) -> FieldRef {
+    let data_type = match field.data_type() {
+        DataType::Binary | DataType::FixedSizeBinary(_) => DataType::Utf8,
+        DataType::LargeBinary => DataType::LargeUtf8,
+        DataType::Struct(fields) => DataType::Struct(fields.iter().map(binary_as_string).collect()),
+        _ => return field.clone(),
+    };
+    Arc::new(Field::clone(field).with_data_type(data_type))
+}
+
+fn decode_base64<'a>(
+    field: &Field,
+    values: impl Iterator<Item = Option<&'a str>>,
+) -> Result<Vec<Option<Vec<u8>>>, DeltaWriterError> {
+    values
+        .map(|value| {
+            value
+                .map(|v| BASE64.decode(v))
+                .transpose()
+                .map_err(|source| DeltaWriterError::InvalidBinaryValue {
+                    column: field.name().clone(),
+                    source,
+                })
+        })
+        .collect()
+}
+
+fn decode_binary(column: &ArrayRef, field: &Field) -> Result<ArrayRef, DeltaWriterError> {
+    Ok(match field.data_type() {
+        DataType::Binary => {
+            let values = decode_base64(field, column.as_string::<i32>().iter())?;
+            Arc::new(BinaryArray::from_iter(values))
+        }
+        DataType::LargeBinary => {
+            let values = decode_base64(field, column.as_string::<i64>().iter())?;
+            Arc::new(LargeBinaryArray::from_iter(values))
+        }
+        DataType::FixedSizeBinary(size) => {
+            let values = decode_base64(field, column.as_string::<i32>().iter())?;
+            Arc::new(FixedSizeBinaryArray::try_from_sparse_iter_with_size(
+                values.into_iter(),
+                *size,
+            )?)
+        }
+        DataType::Struct(fields) if contains_binary(field.data_type()) => {
+            let array = column.as_struct();
+            let children = array
+                .columns()
+                .iter()
+                .zip(fields.iter())
+                .map(|(child, child_field)| decode_binary(child, child_field))
+                .collect::<Result<Vec<_>, _>>()?;
+            Arc::new(StructArray::try_new(
+                fields.clone(),
+                children,
+                array.nulls().cloned(),
+            )?)
+        }
+        _ => column.clone(),
+    })
 }
 
 /// Remove any partition related columns from the record batch
@@ -160,7 +242,34 @@ impl Write for ShareableBuffer {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use parquet::basic::{BrotliLevel, GzipLevel, ZstdLevel};
+    use parquet::basic::{BrotliLevel, Compression, GzipLevel, ZstdLevel};
+
+    #[test]
+    fn test_record_batch_from_message_with_binary() {
+        let schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("id", DataType::Utf8, true),
+            Field::new("payload", DataType::Binary, true),
+            Field::new("digest", DataType::FixedSizeBinary(2), true),
+        ]));
+        let json = vec![
+            serde_json::json!({"id": "a", "payload": "aGVsbG8=", "digest": "AAE="}),
+            serde_json::json!({"id": "b", "payload": null, "digest": null}),
+        ];
+
+        let batch = record_batch_from_message(schema.clone(), &json).unwrap();
+        assert_eq!(batch.schema(), schema);
+
+        let payload = batch.column(1).as_binary::<i32>();
+        assert_eq!(payload.value(0), b"hello");
+        assert!(payload.is_null(1));
+
+        let digest = batch.column(2).as_fixed_size_binary();
+        assert_eq!(digest.value(0), &[0u8, 1u8]);
+        assert!(digest.is_null(1));
+
+        let invalid = vec![serde_json::json!({"id": "c", "payload": "not base64!"})];
+        assert!(record_batch_from_message(schema, &invalid).is_err());
+    }
 
     #[test]
     fn test_data_path() {
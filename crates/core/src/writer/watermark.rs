@@ -0,0 +1,246 @@
+//! Event time watermarks for streaming writes
+//!
+//! Streaming sinks receive records out of order, and downstream incremental aggregations need to
+//! know up to which event time the table is complete. A [`Watermark`] declares the event time
+//! column of the written data and how late records may arrive: the watermark trails the largest
+//! event time seen by the writer by the configured delay. Records with an event time before the
+//! watermark are late and, depending on the [`LateDataPolicy`], are either routed to a side
+//! output or written and counted in the `commitInfo` of the next commit. Every commit carries
+//! the current watermark as `watermark`, in microseconds since the epoch.
+//!
+//! The watermark only advances between batches, so the records of a batch are compared to the
+//! watermark as of the previous batch. Records without an event time are never late.
+//!
+//! # Example
+//! ```rust ignore
+//! let mut writer = RecordBatchWriter::for_table(&table)?.with_watermark(
+//!     Watermark::new("event_time", Duration::from_secs(600))
+//!         .with_late_data_policy(LateDataPolicy::SideOutput),
+//! );
+//! writer.write(batch).await?;
+//! writer.flush_and_commit(&mut table).await?;
+//! let late = writer.take_late_rows();
+//! ````
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use arrow::array::{Array, ArrayRef, BooleanArray};
+use arrow::compute::filter_record_batch;
+use arrow::compute::kernels::boolean::not;
+use arrow::record_batch::RecordBatch;
+use arrow_array::cast::AsArray;
+use arrow_array::types::TimestampMicrosecondType;
+use arrow_schema::{DataType, TimeUnit};
+use serde_json::Value;
+
+use crate::errors::{DeltaResult, DeltaTableError};
+
+/// What to do with records arriving after the watermark
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LateDataPolicy {
+    /// Don't write late records, but keep them to be taken from the writer
+    #[default]
+    SideOutput,
+    /// Write late records and count them as `lateRows` in the `commitInfo`
+    Flag,
+}
+
+/// Declaration of the event time column of streaming writes and the delay of its watermark
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Watermark {
+    column: String,
+    delay: Duration,
+    policy: LateDataPolicy,
+}
+
+impl Watermark {
+    /// Track the watermark of the timestamp or date `column`, trailing the largest event time
+    /// by `delay`
+    pub fn new(column: impl Into<String>, delay: Duration) -> Self {
+        Self {
+            column: column.into(),
+            delay,
+            policy: LateDataPolicy::default(),
+        }
+    }
+
+    /// Set what to do with late records, routed to the side output by default
+    pub fn with_late_data_policy(mut self, policy: LateDataPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+}
+
+/// State of the watermark of a writer
+#[derive(Debug)]
+pub(crate) struct WatermarkTracker {
+    watermark: Watermark,
+    max_event_time: Option<i64>,
+    late_rows: usize,
+    late_batches: Vec<RecordBatch>,
+}
+
+impl WatermarkTracker {
+    pub(crate) fn new(watermark: Watermark) -> Self {
+        Self {
+            watermark,
+            max_event_time: None,
+            late_rows: 0,
+            late_batches: Vec::new(),
+        }
+    }
+
+    /// The current watermark in microseconds since the epoch
+    pub(crate) fn current(&self) -> Option<i64> {
+        let delay = i64::try_from(self.watermark.delay.as_micros()).unwrap_or(i64::MAX);
+        self.max_event_time
+            .map(|max_event_time| max_event_time.saturating_sub(delay))
+    }
+
+    /// Separate the late records of `batch` and advance the watermark, returning the records
+    /// to write
+    pub(crate) fn observe(&mut self, batch: RecordBatch) -> DeltaResult<RecordBatch> {
+        let column = &self.watermark.column;
+        let array = batch.column_by_name(column).ok_or_else(|| {
+            DeltaTableError::Generic(format!("Event time column '{column}' not found in data"))
+        })?;
+        let event_times = event_time_micros(column, array)?;
+        let event_times = event_times.as_primitive::<TimestampMicrosecondType>();
+
+        let watermark = self.current();
+        let late = event_times
+            .iter()
+            .map(|event_time| match (event_time, watermark) {
+                (Some(event_time), Some(watermark)) => Some(event_time < watermark),
+                _ => Some(false),
+            })
+            .collect::<BooleanArray>();
+        if let Some(max) = arrow::compute::max(event_times) {
+            let current = self.max_event_time.unwrap_or(max);
+            self.max_event_time = Some(current.max(max));
+        }
+
+        let late_rows = late.true_count();
+        if late_rows == 0 {
+            return Ok(batch);
+        }
+        self.late_rows += late_rows;
+        match self.watermark.policy {
+            LateDataPolicy::Flag => Ok(batch),
+            LateDataPolicy::SideOutput => {
+                self.late_batches.push(filter_record_batch(&batch, &late)?);
+                Ok(filter_record_batch(&batch, &not(&late)?)?)
+            }
+        }
+    }
+
+    /// Entries of the `commitInfo` describing the watermark and the late records written since
+    /// the last commit
+    pub(crate) fn commit_info(&self) -> HashMap<String, Value> {
+        let mut info = HashMap::new();
+        if let Some(watermark) = self.current() {
+            info.insert("watermark".to_string(), Value::from(watermark));
+        }
+        if self.watermark.policy == LateDataPolicy::Flag {
+            info.insert("lateRows".to_string(), Value::from(self.late_rows));
+        }
+        info
+    }
+
+    /// Reset the count of late records after committing or discarding the buffered records
+    pub(crate) fn reset_late_rows(&mut self) {
+        self.late_rows = 0;
+    }
+
+    /// Take the late records routed to the side output
+    pub(crate) fn take_late_batches(&mut self) -> Vec<RecordBatch> {
+        std::mem::take(&mut self.late_batches)
+    }
+}
+
+/// Cast a timestamp or date column to microseconds since the epoch in UTC
+fn event_time_micros(column: &str, array: &ArrayRef) -> DeltaResult<ArrayRef> {
+    match array.data_type() {
+        DataType::Timestamp(_, _) | DataType::Date32 | DataType::Date64 => Ok(arrow_cast::cast(
+            array,
+            &DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+        )?),
+        data_type => Err(DeltaTableError::Generic(format!(
+            "Event time column '{column}' must be a timestamp or date, found {data_type}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use arrow_array::{Int32Array, TimestampMicrosecondArray};
+    use arrow_schema::{Field, Schema};
+
+    fn batch(event_times: Vec<Option<i64>>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("value", DataType::Int32, true),
+            Field::new(
+                "event_time",
+                DataType::Timestamp(TimeUnit::Microsecond, None),
+                true,
+            ),
+        ]));
+        let values = Int32Array::from_iter_values(0..event_times.len() as i32);
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(values),
+                Arc::new(TimestampMicrosecondArray::from(event_times)),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_watermark_side_output() {
+        let mut tracker =
+            WatermarkTracker::new(Watermark::new("event_time", Duration::from_micros(10)));
+        assert_eq!(tracker.current(), None);
+
+        let written = tracker.observe(batch(vec![Some(100), Some(50)])).unwrap();
+        assert_eq!(written.num_rows(), 2);
+        assert_eq!(tracker.current(), Some(90));
+
+        let written = tracker
+            .observe(batch(vec![Some(89), Some(90), None, Some(120)]))
+            .unwrap();
+        assert_eq!(written.num_rows(), 3);
+        assert_eq!(tracker.current(), Some(110));
+        let late = tracker.take_late_batches();
+        assert_eq!(late.len(), 1);
+        assert_eq!(late[0].num_rows(), 1);
+        assert!(tracker.take_late_batches().is_empty());
+        assert_eq!(
+            tracker.commit_info(),
+            HashMap::from([("watermark".to_string(), Value::from(110))])
+        );
+    }
+
+    #[test]
+    fn test_watermark_flag() {
+        let mut tracker = WatermarkTracker::new(
+            Watermark::new("event_time", Duration::from_micros(10))
+                .with_late_data_policy(LateDataPolicy::Flag),
+        );
+        tracker.observe(batch(vec![Some(100)])).unwrap();
+        let written = tracker.observe(batch(vec![Some(10), Some(20)])).unwrap();
+        assert_eq!(written.num_rows(), 2);
+        assert!(tracker.take_late_batches().is_empty());
+        assert_eq!(tracker.commit_info()["lateRows"], Value::from(2));
+        tracker.reset_late_rows();
+        assert_eq!(tracker.commit_info()["lateRows"], Value::from(0));
+
+        assert!(tracker.observe(batch(vec![])).is_ok());
+        let mut tracker = WatermarkTracker::new(Watermark::new("value", Duration::ZERO));
+        assert!(tracker.observe(batch(vec![Some(1)])).is_err());
+    }
+}
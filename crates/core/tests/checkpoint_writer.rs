@@ -314,17 +314,15 @@ mod checkpoints_with_tombstones {
         let version = fs_common::commit_removes(&mut table, vec![&r1, &r2]).await;
         let (schema, actions) = create_checkpoint_and_parse(&table, path, version).await;
 
-        // r2 has extended_file_metadata=false, then every tombstone should be so, even r1
-        assert_ne!(actions, vec![r1.clone(), r2.clone()]);
-        assert!(!schema.contains("size"));
-        assert!(!schema.contains("partitionValues"));
-        assert!(!schema.contains("tags"));
-        let r1_updated = Remove {
-            extended_file_metadata: Some(false),
-            size: None,
+        // the extended file metadata of r1 is kept even though r2 has none
+        assert!(schema.contains("size"));
+        assert!(schema.contains("partitionValues"));
+        assert!(schema.contains("tags"));
+        // absent partition values are read as empty maps
+        assert!(actions.contains(&Remove {
+            partition_values: Some(HashMap::new()),
             ..r1
-        };
-        assert!(actions.contains(&r1_updated));
+        }));
         assert!(actions.contains(&r2));
     }
 
@@ -339,9 +337,7 @@ mod checkpoints_with_tombstones {
 
         // r1 extended_file_metadata=true, but the size is null.
         // We should fix this by setting extended_file_metadata=false
-        assert!(!schema.contains("size"));
-        assert!(!schema.contains("partitionValues"));
-        assert!(!schema.contains("tags"));
+        assert!(schema.contains("size"));
         assert!(actions.contains(&Remove {
             extended_file_metadata: Some(false),
             size: None,
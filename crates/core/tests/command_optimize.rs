@@ -1,3 +1,4 @@
+use std::sync::Mutex;
 use std::time::Duration;
 use std::{error::Error, sync::Arc};
 
@@ -6,10 +7,12 @@ use arrow_schema::{DataType as ArrowDataType, Field, Schema as ArrowSchema};
 use arrow_select::concat::concat_batches;
 use deltalake_core::errors::DeltaTableError;
 use deltalake_core::kernel::{Action, DataType, PrimitiveType, StructField};
+use deltalake_core::operations::cancellation::CancellationToken;
 use deltalake_core::operations::optimize::{
     create_merge_plan, MetricDetails, Metrics, OptimizeType,
 };
-use deltalake_core::operations::transaction::{CommitBuilder, CommitProperties};
+use deltalake_core::operations::progress::{Progress, ProgressListener};
+use deltalake_core::operations::transaction::{CommitBuilder, CommitProperties, TransactionError};
 use deltalake_core::operations::DeltaOps;
 use deltalake_core::protocol::DeltaOperation;
 use deltalake_core::storage::ObjectStoreRef;
@@ -168,7 +171,13 @@ async fn test_optimize_non_partitioned_table() -> Result<(), Box<dyn Error>> {
     .await?;
 
     let version = dt.version();
-    assert_eq!(dt.get_files_count(), 5);
+    assert_eq!(dt.get_files_count().unwrap(), 5);
+    let sizes = dt
+        .snapshot()?
+        .file_actions()?
+        .into_iter()
+        .map(|add| (add.path, add.size))
+        .collect::<Vec<_>>();
 
     let optimize = DeltaOps(dt).optimize().with_target_size(2_000_000);
     let (dt, metrics) = optimize.await?;
@@ -178,7 +187,20 @@ async fn test_optimize_non_partitioned_table() -> Result<(), Box<dyn Error>> {
     assert_eq!(metrics.num_files_removed, 4);
     assert_eq!(metrics.total_considered_files, 5);
     assert_eq!(metrics.partitions_optimized, 1);
-    assert_eq!(dt.get_files_count(), 2);
+    assert_eq!(dt.get_files_count().unwrap(), 2);
+    // the large file is kept, the four small ones are rewritten
+    let kept = dt
+        .get_files_iter()?
+        .map(|path| path.to_string())
+        .collect::<Vec<_>>();
+    let rewritten = sizes
+        .iter()
+        .filter(|(path, _)| !kept.contains(path))
+        .collect::<Vec<_>>();
+    assert_eq!(rewritten.len(), 4);
+    let bytes_rewritten = rewritten.iter().map(|(_, size)| size).sum::<i64>();
+    assert!(bytes_rewritten > 0);
+    assert_eq!(metrics.bytes_rewritten, bytes_rewritten);
 
     let commit_info = dt.history(None).await?;
     let last_commit = &commit_info[0];
@@ -189,6 +211,209 @@ async fn test_optimize_non_partitioned_table() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_optimize_dry_run() -> Result<(), Box<dyn Error>> {
+    let context = setup_test(false).await?;
+    let mut dt = context.table;
+    let mut writer = RecordBatchWriter::for_table(&dt)?;
+
+    write(
+        &mut writer,
+        &mut dt,
+        tuples_to_batch(vec![(1, 2), (1, 3), (1, 4)], "2022-05-22")?,
+    )
+    .await?;
+    write(
+        &mut writer,
+        &mut dt,
+        tuples_to_batch(vec![(2, 1), (2, 3), (2, 3)], "2022-05-23")?,
+    )
+    .await?;
+
+    let version = dt.version();
+    let mut files = dt
+        .get_files_iter()?
+        .map(|f| f.to_string())
+        .collect::<Vec<_>>();
+
+    let (dt, metrics) = DeltaOps(dt).optimize().with_dry_run(true).await?;
+
+    assert_eq!(version, dt.version());
+    assert_eq!(dt.get_files_count().unwrap(), 2);
+    assert!(metrics.dry_run);
+    assert_eq!(metrics.num_files_added, 1);
+    assert_eq!(metrics.num_files_removed, 2);
+    assert_eq!(metrics.files_removed.total_files, 2);
+    assert_eq!(metrics.bytes_rewritten, 0);
+    let mut files_to_remove = metrics.files_to_remove.clone();
+    files_to_remove.sort();
+    files.sort();
+    assert_eq!(files_to_remove, files);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_optimize_cancelled() -> Result<(), Box<dyn Error>> {
+    let context = setup_test(false).await?;
+    let mut dt = context.table;
+    let mut writer = RecordBatchWriter::for_table(&dt)?;
+
+    write(
+        &mut writer,
+        &mut dt,
+        tuples_to_batch(vec![(1, 2), (1, 3), (1, 4)], "2022-05-22")?,
+    )
+    .await?;
+    write(
+        &mut writer,
+        &mut dt,
+        tuples_to_batch(vec![(2, 1), (2, 3), (2, 3)], "2022-05-23")?,
+    )
+    .await?;
+    let version = dt.version();
+
+    let token = CancellationToken::new();
+    token.cancel();
+    let result = DeltaOps(dt.clone())
+        .optimize()
+        .with_cancellation_token(token.clone())
+        .await;
+    assert!(matches!(result, Err(DeltaTableError::Cancelled)));
+
+    let plan = create_merge_plan(
+        OptimizeType::Compact,
+        dt.snapshot()?,
+        &[],
+        None,
+        WriterProperties::builder().build(),
+    )?
+    .with_cancellation_token(token);
+    let result = plan
+        .execute(
+            dt.log_store(),
+            dt.snapshot()?,
+            1,
+            20,
+            None,
+            CommitProperties::default(),
+        )
+        .await;
+    assert!(matches!(result, Err(DeltaTableError::Cancelled)));
+
+    dt.update().await?;
+    assert_eq!(version, dt.version());
+    assert_eq!(dt.get_files_count().unwrap(), 2);
+    let data_files = dt
+        .object_store()
+        .list(None)
+        .try_filter(|meta| futures::future::ready(meta.location.as_ref().ends_with(".parquet")))
+        .try_collect::<Vec<_>>()
+        .await?;
+    assert_eq!(data_files.len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+/// An optimize which runs out of time commits nothing and reports its progress
+async fn test_optimize_timeout() -> Result<(), Box<dyn Error>> {
+    let context = setup_test(false).await?;
+    let mut dt = context.table;
+    let mut writer = RecordBatchWriter::for_table(&dt)?;
+
+    write(
+        &mut writer,
+        &mut dt,
+        tuples_to_batch(vec![(1, 2), (1, 3), (1, 4)], "2022-05-22")?,
+    )
+    .await?;
+    write(
+        &mut writer,
+        &mut dt,
+        tuples_to_batch(vec![(2, 1), (2, 3), (2, 3)], "2022-05-22")?,
+    )
+    .await?;
+    let version = dt.version();
+
+    let result = DeltaOps(dt.clone())
+        .optimize()
+        .with_timeout(Duration::ZERO)
+        .await;
+    match result {
+        Err(DeltaTableError::Timeout { timeout, progress }) => {
+            assert_eq!(timeout, Duration::ZERO);
+            assert_eq!(progress.operation, "OPTIMIZE");
+            assert_eq!(progress.files_total, Some(2));
+            assert!(!progress.finished);
+        }
+        other => panic!("expected a timeout, got {other:?}"),
+    }
+
+    dt.update().await?;
+    assert_eq!(version, dt.version());
+    assert_eq!(dt.get_files_count().unwrap(), 2);
+
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+struct RecordingListener {
+    updates: Mutex<Vec<Progress>>,
+}
+
+impl ProgressListener for RecordingListener {
+    fn on_progress(&self, progress: &Progress) {
+        self.updates.lock().unwrap().push(progress.clone());
+    }
+}
+
+#[tokio::test]
+async fn test_optimize_progress() -> Result<(), Box<dyn Error>> {
+    let context = setup_test(true).await?;
+    let mut dt = context.table;
+    let mut writer = RecordBatchWriter::for_table(&dt)?;
+
+    for (x, date) in [
+        (1, "2022-05-22"),
+        (2, "2022-05-22"),
+        (3, "2022-05-23"),
+        (4, "2022-05-23"),
+    ] {
+        write(&mut writer, &mut dt, tuples_to_batch(vec![(x, 1)], date)?).await?;
+    }
+    let bytes = dt
+        .snapshot()?
+        .file_actions()?
+        .iter()
+        .map(|add| add.size as u64)
+        .sum::<u64>();
+
+    let listener = Arc::new(RecordingListener::default());
+    let (_, metrics) = DeltaOps(dt)
+        .optimize()
+        .with_progress_listener(listener.clone())
+        .await?;
+    assert_eq!(metrics.num_files_removed, 4);
+
+    let updates = listener.updates.lock().unwrap();
+    // one update per rewritten partition, and one when finished
+    assert_eq!(updates.len(), 3);
+    assert_eq!(updates[0].files_total, Some(4));
+    assert_eq!(updates[0].bytes_total, Some(bytes));
+    assert_eq!(updates[0].files_processed, 2);
+    assert_eq!(
+        updates[0].fraction().map(|f| f > 0.0 && f < 1.0),
+        Some(true)
+    );
+    assert!(updates[2].finished);
+    assert_eq!(updates[2].operation, "OPTIMIZE");
+    assert_eq!(updates[2].files_processed, 4);
+    assert_eq!(updates[2].bytes_processed, bytes);
+
+    Ok(())
+}
+
 async fn write(
     writer: &mut RecordBatchWriter,
     table: &mut DeltaTable,
@@ -240,7 +465,7 @@ async fn test_optimize_with_partitions() -> Result<(), Box<dyn Error>> {
     assert_eq!(version + 1, dt.version());
     assert_eq!(metrics.num_files_added, 1);
     assert_eq!(metrics.num_files_removed, 2);
-    assert_eq!(dt.get_files_count(), 3);
+    assert_eq!(dt.get_files_count().unwrap(), 3);
 
     let partition_adds = dt
         .get_active_add_actions_by_partitions(&filter)?
@@ -293,7 +518,7 @@ async fn test_conflict_for_remove_actions() -> Result<(), Box<dyn Error>> {
 
     let uri = context.tmp_dir.path().to_str().to_owned().unwrap();
     let other_dt = deltalake_core::open_table(uri).await?;
-    let add = &other_dt.snapshot()?.log_data().into_iter().next().unwrap();
+    let add = &other_dt.snapshot()?.log_data()?.into_iter().next().unwrap();
     let remove = add.remove_action(true);
 
     let operation = DeltaOperation::Delete { predicate: None };
@@ -425,6 +650,67 @@ async fn test_commit_interval() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+async fn setup_max_commit_size_test() -> Result<Context, Box<dyn Error>> {
+    let mut context = setup_test(true).await?;
+    let mut writer = RecordBatchWriter::for_table(&context.table)?;
+    for partition in ["2022-05-22", "2022-05-23", "2022-05-24"] {
+        for _i in 0..2 {
+            write(
+                &mut writer,
+                &mut context.table,
+                tuples_to_batch(vec![(1, 2), (1, 3), (1, 4)], partition)?,
+            )
+            .await?;
+        }
+    }
+    Ok(context)
+}
+
+#[tokio::test]
+/// Validate that bins exceeding the maximum commit size together are committed separately
+async fn test_max_commit_size() -> Result<(), Box<dyn Error>> {
+    // the size of committing all bins at once
+    let context = setup_max_commit_size_test().await?;
+    let mut dt = context.table;
+    let version = dt.version();
+    DeltaOps(dt.clone()).optimize().await?;
+    let commit = Path::from(format!("_delta_log/{:020}.json", version + 1));
+    let size = dt.object_store().head(&commit).await?.size;
+    dt.update().await?;
+    assert_eq!(dt.version(), version + 1);
+
+    let context = setup_max_commit_size_test().await?;
+    let mut dt = context.table;
+    let version = dt.version();
+    let (_, metrics) = DeltaOps(dt.clone())
+        .optimize()
+        .with_commit_properties(CommitProperties::default().with_max_commit_size(size - 1))
+        .await?;
+    assert_eq!(metrics.num_files_added, 3);
+    assert_eq!(metrics.num_files_removed, 6);
+    dt.update().await?;
+    assert!(dt.version() > version + 1);
+    assert_eq!(dt.get_files_count()?, 3);
+
+    // the actions of a single bin are never split
+    let context = setup_max_commit_size_test().await?;
+    let mut dt = context.table;
+    let version = dt.version();
+    let result = DeltaOps(dt.clone())
+        .optimize()
+        .with_commit_properties(CommitProperties::default().with_max_commit_size(100))
+        .await;
+    assert!(matches!(
+        result,
+        Err(DeltaTableError::Transaction {
+            source: TransactionError::CommitTooLarge { .. }
+        })
+    ));
+    dt.update().await?;
+    assert_eq!(dt.version(), version);
+    Ok(())
+}
+
 #[tokio::test]
 /// Validate that bin packing is idempotent.
 async fn test_idempotent() -> Result<(), Box<dyn Error>> {
@@ -515,6 +801,9 @@ async fn test_idempotent_metrics() -> Result<(), Box<dyn Error>> {
         preserve_insertion_order: true,
         files_added: expected_metric_details.clone(),
         files_removed: expected_metric_details,
+        bytes_rewritten: 0,
+        dry_run: false,
+        files_to_remove: vec![],
     };
 
     assert_eq!(expected, metrics);
@@ -753,6 +1042,53 @@ async fn test_zorder_unpartitioned() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_with_z_order_matches_zorder_type() -> Result<(), Box<dyn Error>> {
+    let context = setup_test(false).await?;
+    let mut dt = context.table;
+    let mut writer = RecordBatchWriter::for_table(&dt)?;
+
+    write(
+        &mut writer,
+        &mut dt,
+        tuples_to_batch(vec![(1, 1), (1, 2), (1, 2)], "1970-01-01")?,
+    )
+    .await?;
+
+    write(
+        &mut writer,
+        &mut dt,
+        tuples_to_batch(vec![(2, 1), (2, 2), (1, 2)], "1970-01-04")?,
+    )
+    .await?;
+
+    let optimize = DeltaOps(dt).optimize().with_z_order(vec!["date", "x", "y"]);
+    let (dt, metrics) = optimize.await?;
+
+    assert_eq!(metrics.num_files_added, 1);
+    assert_eq!(metrics.num_files_removed, 2);
+
+    let files = dt.get_files_iter()?.collect::<Vec<_>>();
+    assert_eq!(files.len(), 1);
+
+    // Same layout as `OptimizeType::ZOrder` over the same columns
+    let actual = read_parquet_file(&files[0], dt.object_store()).await?;
+    let x = actual
+        .column(0)
+        .as_any()
+        .downcast_ref::<Int32Array>()
+        .unwrap();
+    let y = actual
+        .column(1)
+        .as_any()
+        .downcast_ref::<Int32Array>()
+        .unwrap();
+    assert_eq!(x.values().to_vec(), vec![1, 2, 1, 1, 1, 2]);
+    assert_eq!(y.values().to_vec(), vec![1, 1, 2, 2, 2, 2]);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_zorder_partitioned() -> Result<(), Box<dyn Error>> {
     let context = setup_test(true).await?;
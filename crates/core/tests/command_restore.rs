@@ -112,7 +112,29 @@ async fn test_restore_by_version() -> Result<(), Box<dyn Error>> {
         .restore()
         .with_version_to_restore(0)
         .await?;
-    assert_eq!(result.0.get_files_count(), 0);
+    assert_eq!(result.0.get_files_count().unwrap(), 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_restore_dry_run() -> Result<(), Box<dyn Error>> {
+    let context = setup_test().await?;
+    let table = context.table;
+    let files = table.snapshot()?.file_actions()?;
+
+    let (table, metrics) = DeltaOps(table)
+        .restore()
+        .with_version_to_restore(1)
+        .with_dry_run(true)
+        .await?;
+    assert!(metrics.dry_run);
+    assert_eq!(metrics.num_restored_file, 1);
+    assert_eq!(metrics.num_removed_file, 2);
+    assert_eq!(metrics.files_to_restore.len(), 1);
+    assert_eq!(metrics.files_to_remove.len(), 2);
+    assert_eq!(table.version(), 3);
+    assert_eq!(table.snapshot()?.file_actions()?, files);
+    assert_eq!(table.get_latest_version().await?, 3);
     Ok(())
 }
 
@@ -170,7 +192,7 @@ async fn test_restore_with_error_params() -> Result<(), Box<dyn Error>> {
 async fn test_restore_file_missing() -> Result<(), Box<dyn Error>> {
     let context = setup_test().await?;
 
-    for file in context.table.snapshot()?.log_data() {
+    for file in context.table.snapshot()?.log_data()? {
         let p = context.tmp_dir.path().join(file.path().as_ref());
         fs::remove_file(p).unwrap();
     }
@@ -189,7 +211,9 @@ async fn test_restore_file_missing() -> Result<(), Box<dyn Error>> {
         .restore()
         .with_version_to_restore(1)
         .await;
-    assert!(result.is_err());
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("missing files when restoring"));
+    assert!(err.contains(".parquet"));
     Ok(())
 }
 
@@ -197,7 +221,7 @@ async fn test_restore_file_missing() -> Result<(), Box<dyn Error>> {
 async fn test_restore_allow_file_missing() -> Result<(), Box<dyn Error>> {
     let context = setup_test().await?;
 
-    for file in context.table.snapshot()?.log_data() {
+    for file in context.table.snapshot()?.log_data()? {
         let p = context.tmp_dir.path().join(file.path().as_ref());
         fs::remove_file(p).unwrap();
     }
@@ -1,7 +1,9 @@
 use chrono::Duration;
 use deltalake_core::kernel::StructType;
+use deltalake_core::operations::cancellation::CancellationToken;
 use deltalake_core::operations::vacuum::Clock;
 use deltalake_core::operations::DeltaOps;
+use deltalake_core::DeltaTableError;
 use deltalake_test::clock::TestClock;
 use deltalake_test::*;
 use object_store::{path::Path, Error as ObjectStoreError, ObjectStore};
@@ -85,6 +87,49 @@ async fn test_non_partitioned_table() {
     assert!(!is_deleted(&mut context, &Path::from("dont_delete_me.parquet")).await);
 }
 
+#[tokio::test]
+// Validate vacuum deletes every file when deletes run concurrently
+async fn test_concurrent_deletes() {
+    let mut context = TestContext::from_env().await;
+    let mut table = context
+        .create_table_from_schema(get_xy_date_schema(), &[])
+        .await;
+    let clock = TestClock::from_systemtime();
+
+    let paths = (0..5)
+        .map(|i| format!("delete_me_{i}.parquet"))
+        .collect::<Vec<_>>();
+    for path in &paths {
+        add_file(
+            &mut table,
+            &Path::from(path.as_str()),
+            "random junk".as_bytes().into(),
+            &[],
+            clock.current_timestamp_millis(),
+            true,
+        )
+        .await;
+    }
+
+    clock.tick(Duration::seconds(10));
+    for path in &paths {
+        remove_file(&mut table, path, &[], clock.current_timestamp_millis()).await;
+    }
+
+    clock.tick(Duration::days(8));
+    let (_, metrics) = DeltaOps(table)
+        .vacuum()
+        .with_clock(Arc::new(clock.clone()))
+        .with_max_concurrent_deletes(2)
+        .await
+        .unwrap();
+
+    assert_eq!(metrics.files_deleted.len(), paths.len());
+    for path in &paths {
+        assert!(is_deleted(&mut context, &Path::from(path.as_str())).await);
+    }
+}
+
 #[tokio::test]
 // Validate vacuum works on a table with multiple partitions
 async fn test_partitioned_table() {
@@ -293,6 +338,91 @@ async fn test_non_managed_files() {
     }
 }
 
+#[tokio::test]
+// Validate a cancelled vacuum neither deletes files nor commits
+async fn test_cancelled() {
+    let mut context = TestContext::from_env().await;
+    let mut table = context
+        .create_table_from_schema(get_xy_date_schema(), &[])
+        .await;
+    let clock = TestClock::from_systemtime();
+    let path = Path::from("delete_me.parquet");
+
+    add_file(
+        &mut table,
+        &path,
+        "random junk".as_bytes().into(),
+        &[],
+        clock.current_timestamp_millis(),
+        true,
+    )
+    .await;
+    clock.tick(Duration::seconds(10));
+    remove_file(
+        &mut table,
+        "delete_me.parquet",
+        &[],
+        clock.current_timestamp_millis(),
+    )
+    .await;
+    let version = table.version();
+
+    clock.tick(Duration::days(8));
+    let token = CancellationToken::new();
+    token.cancel();
+    let result = DeltaOps(table.clone())
+        .vacuum()
+        .with_clock(Arc::new(clock.clone()))
+        .with_cancellation_token(token)
+        .await;
+
+    assert!(matches!(result, Err(DeltaTableError::Cancelled)));
+    assert!(!is_deleted(&mut context, &path).await);
+    table.update().await.unwrap();
+    assert_eq!(table.version(), version);
+}
+
+#[tokio::test]
+async fn test_timeout() {
+    let mut context = TestContext::from_env().await;
+    let mut table = context
+        .create_table_from_schema(get_xy_date_schema(), &[])
+        .await;
+    let clock = TestClock::from_systemtime();
+    let path = Path::from("delete_me.parquet");
+
+    add_file(
+        &mut table,
+        &path,
+        "random junk".as_bytes().into(),
+        &[],
+        clock.current_timestamp_millis(),
+        true,
+    )
+    .await;
+    clock.tick(Duration::seconds(10));
+    remove_file(
+        &mut table,
+        "delete_me.parquet",
+        &[],
+        clock.current_timestamp_millis(),
+    )
+    .await;
+    let version = table.version();
+
+    clock.tick(Duration::days(8));
+    let result = DeltaOps(table.clone())
+        .vacuum()
+        .with_clock(Arc::new(clock.clone()))
+        .with_timeout(std::time::Duration::ZERO)
+        .await;
+
+    assert!(matches!(result, Err(DeltaTableError::Timeout { .. })));
+    assert!(!is_deleted(&mut context, &path).await);
+    table.update().await.unwrap();
+    assert_eq!(table.version(), version);
+}
+
 async fn is_deleted(context: &mut TestContext, path: &Path) -> bool {
     let backend = context.get_storage();
     let res = backend.object_store().head(path).await;
@@ -19,7 +19,7 @@ use datafusion::datasource::physical_plan::ParquetExec;
 use datafusion::datasource::TableProvider;
 use datafusion::execution::context::{SessionContext, SessionState, TaskContext};
 use datafusion::physical_plan::coalesce_partitions::CoalescePartitionsExec;
-use datafusion::physical_plan::{common::collect, metrics::Label};
+use datafusion::physical_plan::{common::collect, displayable, metrics::Label};
 use datafusion::physical_plan::{visit_execution_plan, ExecutionPlan, ExecutionPlanVisitor};
 use datafusion_common::scalar::ScalarValue;
 use datafusion_common::ScalarValue::*;
@@ -177,6 +177,45 @@ mod local {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_datafusion_partition_filters_exact() -> Result<()> {
+        let ctx = SessionContext::new();
+        let table = open_table("../test/tests/data/delta-0.8.0-partitioned")
+            .await
+            .unwrap();
+        ctx.register_table("demo", Arc::new(table))?;
+
+        // filters on partition columns are applied by the scan
+        let df = ctx
+            .sql("SELECT CAST( day as int ) as my_day FROM demo WHERE year = '2021' AND CAST( month as int ) = 12")
+            .await?;
+        let plan = df.clone().create_physical_plan().await?;
+        let plan = displayable(plan.as_ref()).indent(true).to_string();
+        assert!(!plan.contains("FilterExec"), "{plan}");
+        let batches = df.collect().await?;
+        let expected = vec![
+            "+--------+",
+            "| my_day |",
+            "+--------+",
+            "| 20     |",
+            "| 20     |",
+            "| 4      |",
+            "+--------+",
+        ];
+        assert_batches_sorted_eq!(&expected, &batches);
+
+        // other filters are still applied after the scan
+        let plan = ctx
+            .sql("SELECT value FROM demo WHERE year = '2021' AND value = '6'")
+            .await?
+            .create_physical_plan()
+            .await?;
+        let plan = displayable(plan.as_ref()).indent(true).to_string();
+        assert!(plan.contains("FilterExec"), "{plan}");
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_datafusion_write_from_serialized_delta_scan() -> Result<()> {
         // Build an execution plan for scanning a DeltaTable and serialize it to bytes.
@@ -433,6 +472,69 @@ mod local {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_datafusion_partition_stats() -> Result<()> {
+        // column statistics are ordered like the scan schema, with partition columns last
+        let table = open_table("../test/tests/data/delta-0.8.0-numeric-partition")
+            .await
+            .unwrap();
+        let statistics = table.snapshot()?.datafusion_table_statistics().unwrap();
+        assert_eq!(statistics.column_statistics.len(), 3);
+
+        // `z` has no statistics in the log
+        let stats = &statistics.column_statistics[0];
+        assert_eq!(stats.min_value, Precision::Absent);
+        assert_eq!(stats.max_value, Precision::Absent);
+
+        // `x` and `y` statistics are derived from the partition values
+        let stats = &statistics.column_statistics[1];
+        assert_eq!(stats.null_count, Precision::Exact(0));
+        assert_eq!(stats.min_value, Precision::Exact(ScalarValue::from(9_i64)));
+        assert_eq!(stats.max_value, Precision::Exact(ScalarValue::from(10_i64)));
+        let stats = &statistics.column_statistics[2];
+        assert_eq!(
+            stats.min_value,
+            Precision::Exact(ScalarValue::from(9.9_f64))
+        );
+        assert_eq!(
+            stats.max_value,
+            Precision::Exact(ScalarValue::from(10.0_f64))
+        );
+
+        let ctx = SessionContext::new();
+        ctx.register_table("test_table", Arc::new(table))?;
+        let actual = ctx
+            .sql("SELECT x, y FROM test_table WHERE x > 9")
+            .await?
+            .collect()
+            .await?;
+        let expected = vec![
+            "+----+------+",
+            "| x  | y    |",
+            "+----+------+",
+            "| 10 | 10.0 |",
+            "+----+------+",
+        ];
+        assert_batches_sorted_eq!(&expected, &actual);
+
+        // null partitions are skipped for the bounds, string partitions are dictionary encoded
+        let table = open_table("../test/tests/data/delta-0.8.0-null-partition")
+            .await
+            .unwrap();
+        let statistics = table.snapshot()?.datafusion_table_statistics().unwrap();
+        let stats = statistics.column_statistics.last().unwrap();
+        let a = Precision::Exact(ScalarValue::Dictionary(
+            Box::new(ArrowDataType::UInt16),
+            Box::new(ScalarValue::from("A")),
+        ));
+        // the files have no record counts, so the number of nulls is unknown
+        assert_eq!(stats.null_count, Precision::Absent);
+        assert_eq!(stats.min_value, a);
+        assert_eq!(stats.max_value, a);
+
+        Ok(())
+    }
+
     async fn get_scan_metrics(
         table: &DeltaTable,
         state: &SessionState,
@@ -0,0 +1,302 @@
+//! Property based tests round-tripping schemas and actions through JSON commits, the in-memory
+//! table state and parquet checkpoints.
+
+use std::collections::HashMap;
+
+use arrow_schema::Schema as ArrowSchema;
+use deltalake_core::checkpoints::create_checkpoint;
+use deltalake_core::kernel::{
+    Action, Add, ArrayType, DataType, MapType, Metadata, PrimitiveType, Protocol, Remove,
+    StructField, StructType, Txn,
+};
+use deltalake_core::operations::transaction::{CommitBuilder, TableReference};
+use deltalake_core::protocol::{DeltaOperation, SaveMode};
+use deltalake_core::{DeltaOps, DeltaTable};
+use proptest::prelude::*;
+use serde_json::json;
+
+/// Printable unicode strings, including characters which need escaping in JSON and paths
+fn unicode_string() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "\\PC{1,16}",
+        "[a-z0-9_ %#{}\\[\\]\"\\\\.-]{1,16}",
+        Just("ünïcødé ✓ 😀".to_string()),
+    ]
+}
+
+fn primitive_type() -> impl Strategy<Value = PrimitiveType> {
+    prop_oneof![
+        Just(PrimitiveType::String),
+        Just(PrimitiveType::Long),
+        Just(PrimitiveType::Integer),
+        Just(PrimitiveType::Short),
+        Just(PrimitiveType::Byte),
+        Just(PrimitiveType::Float),
+        Just(PrimitiveType::Double),
+        Just(PrimitiveType::Boolean),
+        Just(PrimitiveType::Binary),
+        Just(PrimitiveType::Date),
+        Just(PrimitiveType::Timestamp),
+        Just(PrimitiveType::TimestampNtz),
+        (1u8..=38)
+            .prop_flat_map(|precision| (Just(precision), 0..=precision as i8))
+            .prop_map(|(precision, scale)| PrimitiveType::Decimal(precision, scale)),
+    ]
+}
+
+fn struct_type(field: impl Strategy<Value = DataType>) -> impl Strategy<Value = StructType> {
+    prop::collection::hash_map(unicode_string(), (field, any::<bool>()), 1..6).prop_map(|fields| {
+        StructType::new(
+            fields
+                .into_iter()
+                .map(|(name, (data_type, nullable))| StructField::new(name, data_type, nullable))
+                .collect(),
+        )
+    })
+}
+
+fn data_type() -> impl Strategy<Value = DataType> {
+    primitive_type()
+        .prop_map(DataType::Primitive)
+        .prop_recursive(3, 24, 4, |inner| {
+            prop_oneof![
+                (inner.clone(), any::<bool>()).prop_map(|(element, contains_null)| {
+                    DataType::Array(Box::new(ArrayType::new(element, contains_null)))
+                }),
+                (primitive_type(), inner.clone(), any::<bool>()).prop_map(
+                    |(key, value, value_contains_null)| {
+                        DataType::Map(Box::new(MapType::new(
+                            DataType::Primitive(key),
+                            value,
+                            value_contains_null,
+                        )))
+                    }
+                ),
+                struct_type(inner).prop_map(|schema| DataType::Struct(Box::new(schema))),
+            ]
+        })
+}
+
+fn schema() -> impl Strategy<Value = StructType> {
+    struct_type(data_type())
+}
+
+fn string_map() -> impl Strategy<Value = HashMap<String, Option<String>>> {
+    prop::collection::hash_map(unicode_string(), prop::option::of(unicode_string()), 0..4)
+}
+
+fn stats() -> impl Strategy<Value = String> {
+    (any::<i64>(), any::<i64>(), any::<i64>(), 0..=i64::MAX).prop_map(
+        |(min, max, null_count, num_records)| {
+            json!({
+                "numRecords": num_records,
+                "minValues": {"id": min},
+                "maxValues": {"id": max},
+                "nullCount": {"id": null_count},
+            })
+            .to_string()
+        },
+    )
+}
+
+fn add() -> impl Strategy<Value = Add> {
+    (
+        unicode_string(),
+        string_map(),
+        any::<i64>(),
+        any::<i64>(),
+        any::<bool>(),
+        prop::option::of(stats()),
+        prop::option::of(string_map()),
+    )
+        .prop_map(
+            |(path, partition_values, size, modification_time, data_change, stats, tags)| Add {
+                path,
+                partition_values,
+                size,
+                modification_time,
+                data_change,
+                stats,
+                tags,
+                ..Default::default()
+            },
+        )
+}
+
+fn remove() -> impl Strategy<Value = Remove> {
+    (
+        unicode_string(),
+        any::<bool>(),
+        prop::option::of(any::<i64>()),
+        prop::option::of(string_map()),
+        prop::option::of(any::<i64>()),
+    )
+        .prop_map(
+            |(path, data_change, deletion_timestamp, partition_values, size)| Remove {
+                path,
+                data_change,
+                deletion_timestamp,
+                extended_file_metadata: Some(partition_values.is_some()),
+                partition_values,
+                size,
+                ..Default::default()
+            },
+        )
+}
+
+fn action() -> impl Strategy<Value = Action> {
+    prop_oneof![
+        add().prop_map(Action::Add),
+        remove().prop_map(Action::Remove),
+        (
+            unicode_string(),
+            any::<i64>(),
+            prop::option::of(any::<i64>())
+        )
+            .prop_map(|(app_id, version, last_updated)| Action::Txn(Txn {
+                app_id,
+                version,
+                last_updated,
+            })),
+        (1..=3i32, 1..=7i32).prop_map(|(min_reader_version, min_writer_version)| {
+            Action::Protocol(Protocol {
+                min_reader_version,
+                min_writer_version,
+                reader_features: None,
+                writer_features: None,
+            })
+        }),
+        (schema(), string_map()).prop_map(|(schema, configuration)| Action::Metadata(
+            Metadata::try_new(schema, Vec::<String>::new(), configuration).unwrap()
+        )),
+    ]
+}
+
+/// Adds of a table partitioned by a string column `part`, with statistics for a `long` column
+/// `id`
+fn partitioned_adds() -> impl Strategy<Value = Vec<Add>> {
+    prop::collection::vec(
+        (
+            unicode_string(),
+            prop::option::of("\\PC{1,16}"),
+            0..=i64::MAX,
+            any::<i64>(),
+            prop::option::of(stats()),
+        ),
+        1..20,
+    )
+    .prop_map(|files| {
+        files
+            .into_iter()
+            .enumerate()
+            .map(
+                |(index, (name, part, size, modification_time, stats))| Add {
+                    // prefixed by the index to keep paths unique
+                    path: format!("{index}-{}", name.replace('/', "_")),
+                    partition_values: HashMap::from([("part".to_string(), part)]),
+                    size,
+                    modification_time,
+                    data_change: true,
+                    stats,
+                    ..Default::default()
+                },
+            )
+            .collect()
+    })
+}
+
+fn sorted_files(table: &DeltaTable) -> Vec<Add> {
+    let mut files = table.snapshot().unwrap().file_actions().unwrap();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    files
+}
+
+fn assert_same_files(actual: &[Add], expected: &[Add]) {
+    assert_eq!(actual.len(), expected.len());
+    for (actual, expected) in actual.iter().zip(expected) {
+        assert_eq!(actual.path, expected.path);
+        assert_eq!(actual.partition_values, expected.partition_values);
+        assert_eq!(actual.size, expected.size);
+        assert_eq!(actual.modification_time, expected.modification_time);
+        assert_eq!(actual.stats, expected.stats);
+    }
+}
+
+async fn checkpoint_roundtrip(mut adds: Vec<Add>) {
+    let mut table = DeltaOps::new_in_memory()
+        .create()
+        .with_column("id", DataType::Primitive(PrimitiveType::Long), true, None)
+        .with_column(
+            "part",
+            DataType::Primitive(PrimitiveType::String),
+            true,
+            None,
+        )
+        .with_partition_columns(["part"])
+        .await
+        .unwrap();
+    CommitBuilder::default()
+        .with_actions(adds.iter().cloned().map(Action::Add).collect())
+        .build(
+            Some(table.snapshot().unwrap() as &dyn TableReference),
+            table.log_store(),
+            DeltaOperation::Write {
+                mode: SaveMode::Append,
+                partition_by: Some(vec!["part".to_string()]),
+                predicate: None,
+            },
+        )
+        .unwrap()
+        .await
+        .unwrap();
+    table.update().await.unwrap();
+    adds.sort_by(|a, b| a.path.cmp(&b.path));
+
+    // JSON commit to in-memory state
+    assert_same_files(&sorted_files(&table), &adds);
+
+    // in-memory state to parquet checkpoint and back
+    create_checkpoint(&table).await.unwrap();
+    let mut loaded = DeltaTable::new(table.log_store(), Default::default());
+    loaded.load().await.unwrap();
+    assert_eq!(loaded.version(), 1);
+    assert_same_files(&sorted_files(&loaded), &adds);
+}
+
+proptest! {
+    #[test]
+    fn action_json_roundtrip(action in action()) {
+        let json = serde_json::to_string(&action).unwrap();
+        let parsed: Action = serde_json::from_str(&json).unwrap();
+        prop_assert_eq!(parsed, action);
+    }
+
+    #[test]
+    fn schema_json_roundtrip(schema in schema()) {
+        let json = serde_json::to_string(&schema).unwrap();
+        let parsed: StructType = serde_json::from_str(&json).unwrap();
+        prop_assert_eq!(&parsed, &schema);
+
+        let metadata =
+            Metadata::try_new(schema.clone(), Vec::<String>::new(), HashMap::new()).unwrap();
+        prop_assert_eq!(metadata.schema().unwrap(), schema);
+    }
+
+    #[test]
+    fn schema_arrow_roundtrip(schema in schema()) {
+        let arrow_schema = ArrowSchema::try_from(&schema).unwrap();
+        let converted = StructType::try_from(&arrow_schema).unwrap();
+        prop_assert_eq!(converted, schema);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn checkpoint_roundtrip_preserves_files(adds in partitioned_adds()) {
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(checkpoint_roundtrip(adds));
+    }
+}
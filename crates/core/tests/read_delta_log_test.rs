@@ -172,3 +172,42 @@ async fn read_delta_table_from_dlt() {
     assert_eq!(table.version(), 1);
     assert!(table.get_schema().is_ok());
 }
+
+#[tokio::test]
+async fn test_stream_files_without_loading_files() -> DeltaResult<()> {
+    use deltalake_core::{DeltaTableError, PartitionFilter};
+    use futures::TryStreamExt;
+
+    let table = DeltaTableBuilder::from_uri("../test/tests/data/delta-0.8.0-partitioned")
+        .without_files()
+        .load()
+        .await?;
+    let snapshot = table.snapshot()?;
+    assert!(matches!(
+        snapshot.file_actions(),
+        Err(DeltaTableError::NotInitializedWithFiles(_))
+    ));
+
+    let store = table.log_store().log_object_store();
+    let files = snapshot
+        .files_iter(store.clone(), &[])?
+        .try_collect::<Vec<_>>()
+        .await?;
+    assert_eq!(files.len(), 6);
+
+    let filters = [
+        PartitionFilter::try_from(("year", "=", "2021"))?,
+        PartitionFilter::try_from(("month", "=", "12"))?,
+    ];
+    let mut paths = snapshot
+        .files_iter(store.clone(), &filters)?
+        .map_ok(|add| add.partition_values["day"].clone().unwrap())
+        .try_collect::<Vec<_>>()
+        .await?;
+    paths.sort();
+    assert_eq!(paths, vec!["20".to_string(), "4".to_string()]);
+
+    let filters = [PartitionFilter::try_from(("value", "=", "1"))?];
+    assert!(snapshot.files_iter(store, &filters).is_err());
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use chrono::{DateTime, FixedOffset, Utc};
 use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
 
 #[tokio::test]
 async fn time_travel_by_ds() {
@@ -81,6 +82,19 @@ async fn time_travel_by_ds() {
     .await
     .unwrap();
     assert_eq!(table.version(), 4);
+
+    // time travel also accepts std timestamps
+    let timestamp = UNIX_EPOCH + Duration::from_secs(ds_to_ts("2020-05-03T22:47:31-07:00") as u64);
+    let table = deltalake_core::DeltaTableBuilder::from_uri("../test/tests/data/simple_table")
+        .with_timestamp(timestamp)
+        .load()
+        .await
+        .unwrap();
+    assert_eq!(table.version(), 2);
+    assert_eq!(
+        table.snapshot().unwrap().version_system_time(2),
+        Some(timestamp)
+    );
 }
 
 fn ds_to_ts(ds: &str) -> i64 {
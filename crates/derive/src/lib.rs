@@ -0,0 +1,137 @@
+//! Derive macros for deltalake
+//!
+//! `#[derive(DeltaSchema)]` implements `DeltaSchema` and `DeltaFieldType` from
+//! `deltalake_core::writer::typed` for structs with named fields, so they can be written to
+//! delta tables with a `TypedWriter` or nested in other derived structs.
+//!
+//! Supported attributes:
+//! - `#[delta(crate = "deltalake")]` on the struct: path of the crate re-exporting
+//!   `deltalake_core`, defaults to `deltalake_core`
+//! - `#[delta(rename = "name")]` on a field: name of the column, defaults to the field name
+
+#![deny(missing_docs)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr, Path};
+
+/// Derive `DeltaSchema` and `DeltaFieldType` for a struct with named fields
+#[proc_macro_derive(DeltaSchema, attributes(delta))]
+pub fn derive_delta_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let mut krate: Path = syn::parse_quote!(::deltalake_core);
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("delta"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                krate = meta.value()?.parse::<LitStr>()?.parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported delta attribute"))
+            }
+        })?;
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "DeltaSchema can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "DeltaSchema can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut idents = Vec::with_capacity(fields.len());
+    let mut types = Vec::with_capacity(fields.len());
+    let mut names = Vec::with_capacity(fields.len());
+    for field in fields {
+        let ident = field.ident.clone().expect("named fields have an ident");
+        let mut name = ident.to_string();
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("delta"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported delta field attribute"))
+                }
+            })?;
+        }
+        idents.push(ident);
+        types.push(field.ty.clone());
+        names.push(name);
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let typed = quote!(#krate::writer::typed);
+    let kernel = quote!(#krate::kernel);
+
+    Ok(quote! {
+        impl #impl_generics #typed::DeltaFieldType for #ident #ty_generics #where_clause {
+            fn data_type() -> #kernel::DataType {
+                #kernel::DataType::Struct(::std::boxed::Box::new(
+                    <Self as #typed::DeltaSchema>::schema(),
+                ))
+            }
+
+            fn to_array(
+                values: &[::std::option::Option<&Self>],
+            ) -> #krate::DeltaResult<#krate::arrow::array::ArrayRef> {
+                let columns = ::std::vec![
+                    #(
+                        <#types as #typed::DeltaFieldType>::to_array(
+                            &values
+                                .iter()
+                                .map(|value| value.map(|value| &value.#idents))
+                                .collect::<::std::vec::Vec<_>>(),
+                        )?
+                    ),*
+                ];
+                let valid = values.iter().map(::std::option::Option::is_some).collect();
+                #typed::to_struct_array(
+                    &<Self as #typed::DeltaSchema>::schema(),
+                    columns,
+                    valid,
+                )
+            }
+        }
+
+        impl #impl_generics #typed::DeltaSchema for #ident #ty_generics #where_clause {
+            fn schema() -> #kernel::StructType {
+                #kernel::StructType::new(::std::vec![
+                    #(
+                        #kernel::StructField::new(
+                            #names,
+                            <#types as #typed::DeltaFieldType>::data_type(),
+                            <#types as #typed::DeltaFieldType>::nullable(),
+                        )
+                    ),*
+                ])
+            }
+        }
+    })
+}
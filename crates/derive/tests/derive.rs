@@ -0,0 +1,85 @@
+use chrono::{DateTime, TimeZone, Utc};
+use deltalake_core::kernel::{DataType, StructField, StructType};
+use deltalake_core::writer::typed::{DeltaFieldType, DeltaSchema, TypedWriter};
+use deltalake_core::writer::DeltaWriter;
+use deltalake_core::DeltaOps;
+
+#[derive(Debug, Clone, DeltaSchema)]
+struct Location {
+    city: String,
+    zip: Option<i32>,
+}
+
+#[derive(Debug, Clone, DeltaSchema)]
+struct Event {
+    id: i64,
+    #[delta(rename = "eventName")]
+    name: String,
+    timestamp: DateTime<Utc>,
+    location: Option<Location>,
+}
+
+fn events() -> Vec<Event> {
+    vec![
+        Event {
+            id: 1,
+            name: "created".to_string(),
+            timestamp: Utc.timestamp_opt(1_700_000_000, 0).unwrap(),
+            location: Some(Location {
+                city: "Berlin".to_string(),
+                zip: None,
+            }),
+        },
+        Event {
+            id: 2,
+            name: "deleted".to_string(),
+            timestamp: Utc.timestamp_opt(1_700_000_100, 0).unwrap(),
+            location: None,
+        },
+    ]
+}
+
+#[test]
+fn test_derived_schema() {
+    let location = StructType::new(vec![
+        StructField::new("city", DataType::STRING, false),
+        StructField::new("zip", DataType::INTEGER, true),
+    ]);
+    let expected = StructType::new(vec![
+        StructField::new("id", DataType::LONG, false),
+        StructField::new("eventName", DataType::STRING, false),
+        StructField::new("timestamp", DataType::TIMESTAMP, false),
+        StructField::new(
+            "location",
+            DataType::struct_type(location.fields().clone()),
+            true,
+        ),
+    ]);
+    assert_eq!(Event::schema(), expected);
+    assert!(<Option<Location>>::nullable());
+}
+
+#[test]
+fn test_derived_record_batch() {
+    let batch = Event::to_record_batch(&events()).unwrap();
+    assert_eq!(batch.num_rows(), 2);
+    assert_eq!(batch.num_columns(), 4);
+    assert_eq!(batch.schema().field(1).name(), "eventName");
+    assert_eq!(batch.column(3).null_count(), 1);
+}
+
+#[tokio::test]
+async fn test_typed_writer() {
+    let mut table = DeltaOps::new_in_memory()
+        .create()
+        .with_columns(Event::schema().fields().clone())
+        .await
+        .unwrap();
+
+    let mut writer = TypedWriter::<Event>::for_table(&table).unwrap();
+    writer.write(events()).await.unwrap();
+    writer.write(events()).await.unwrap();
+    let version = writer.flush_and_commit(&mut table).await.unwrap();
+    assert_eq!(version, 1);
+    assert_eq!(table.get_files_count().unwrap(), 1);
+}
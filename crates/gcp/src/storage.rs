@@ -0,0 +1,157 @@
+//! Google Cloud Storage backend.
+//!
+//! Commits are created by copying a temporary commit file to `_delta_log/<version>.json` with
+//! `ifGenerationMatch=0`, which GCS only accepts if the destination doesn't exist, so concurrent
+//! writers can't overwrite each other's commits.
+
+use std::ops::Range;
+
+use bytes::Bytes;
+use deltalake_core::storage::ObjectStoreRef;
+use deltalake_core::Path;
+use futures::stream::BoxStream;
+use object_store::{
+    GetOptions, GetResult, ListResult, MultipartId, ObjectMeta, ObjectStore, PutOptions, PutResult,
+    Result as ObjectStoreResult,
+};
+use tokio::io::AsyncWrite;
+use tracing::warn;
+
+/// [`ObjectStore`] for Google Cloud Storage, see the [module documentation](self)
+pub struct GcsStorageBackend {
+    inner: ObjectStoreRef,
+}
+
+impl GcsStorageBackend {
+    /// Wrap a store created for Google Cloud Storage
+    pub fn new(inner: ObjectStoreRef) -> Self {
+        Self { inner }
+    }
+}
+
+impl std::fmt::Display for GcsStorageBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GcsStorageBackend({})", self.inner)
+    }
+}
+
+impl std::fmt::Debug for GcsStorageBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GcsStorageBackend({})", self.inner)
+    }
+}
+
+#[async_trait::async_trait]
+impl ObjectStore for GcsStorageBackend {
+    async fn put(&self, location: &Path, bytes: Bytes) -> ObjectStoreResult<PutResult> {
+        self.inner.put(location, bytes).await
+    }
+
+    async fn put_opts(
+        &self,
+        location: &Path,
+        bytes: Bytes,
+        options: PutOptions,
+    ) -> ObjectStoreResult<PutResult> {
+        self.inner.put_opts(location, bytes, options).await
+    }
+
+    async fn get(&self, location: &Path) -> ObjectStoreResult<GetResult> {
+        self.inner.get(location).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> ObjectStoreResult<GetResult> {
+        self.inner.get_opts(location, options).await
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> ObjectStoreResult<Bytes> {
+        self.inner.get_range(location, range).await
+    }
+
+    async fn head(&self, location: &Path) -> ObjectStoreResult<ObjectMeta> {
+        self.inner.head(location).await
+    }
+
+    async fn delete(&self, location: &Path) -> ObjectStoreResult<()> {
+        self.inner.delete(location).await
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, ObjectStoreResult<ObjectMeta>> {
+        self.inner.list(prefix)
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'_, ObjectStoreResult<ObjectMeta>> {
+        self.inner.list_with_offset(prefix, offset)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> ObjectStoreResult<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.inner.copy_if_not_exists(from, to).await
+    }
+
+    async fn rename_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.inner.copy_if_not_exists(from, to).await?;
+        // the commit exists at this point, failing to clean up only leaves a temporary file
+        // behind and must not fail the commit
+        if let Err(err) = self.inner.delete(from).await {
+            warn!("failed to delete {from} after renaming it to {to}: {err}");
+        }
+        Ok(())
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> ObjectStoreResult<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        self.inner.put_multipart(location).await
+    }
+
+    async fn abort_multipart(
+        &self,
+        location: &Path,
+        multipart_id: &MultipartId,
+    ) -> ObjectStoreResult<()> {
+        self.inner.abort_multipart(location, multipart_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rename_if_not_exists() {
+        let store = GcsStorageBackend::new(Arc::new(InMemory::new()));
+        let commit = Path::from("_delta_log/00000000000000000000.json");
+        let first = Path::from("_delta_log/_commit_a.json.tmp");
+        let second = Path::from("_delta_log/_commit_b.json.tmp");
+        store.put(&first, Bytes::from("a")).await.unwrap();
+        store.put(&second, Bytes::from("b")).await.unwrap();
+
+        store.rename_if_not_exists(&first, &commit).await.unwrap();
+        assert!(store.head(&first).await.is_err());
+        let err = store
+            .rename_if_not_exists(&second, &commit)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, object_store::Error::AlreadyExists { .. }));
+        assert!(store.head(&second).await.is_ok());
+        let data = store.get(&commit).await.unwrap().bytes().await.unwrap();
+        assert_eq!(data, Bytes::from("a"));
+    }
+}
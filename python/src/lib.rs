@@ -798,6 +798,7 @@ impl RawDeltaTable {
             .snapshot()
             .map_err(PythonError::from)?
             .log_data()
+            .map_err(PythonError::from)?
             .into_iter()
             .filter_map(|f| {
                 let path = f.path().to_string();